@@ -0,0 +1,147 @@
+//! Errors of the `encode` and `decode` subcommands.
+//!
+//! By default an error is written in place of the result, e.g. `Invalid key: ...`, as it always
+//! was. With `--error-format json` it is written to stderr as one JSON object and the command
+//! exits with status 1, so editor plugins and web wrappers can tell errors from results and
+//! point at the bad part of the input:
+//!
+//! ```text
+//! {"error":{"code":"unknown-token","message":"Unknown token \"4\" at position 6","position":6}}
+//! ```
+//!
+//! `position` is the character offset into the message, from 0, or `null` when the error is
+//! not about one place in the message.
+
+use cipha::ciphers::UnknownToken;
+use serde_json::{json, Value};
+
+use crate::i18n::{tr, tr_format};
+
+/// The `--error-format` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(tr_format!(
+                "Unknown error format {} (expected text or json)",
+                format!("{:?}", name)
+            )),
+        }
+    }
+}
+
+/// What went wrong, given as the `code` of a JSON error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The key options do not make a valid key for the cipher.
+    InvalidKey,
+    /// The `--table` file is missing or invalid.
+    InvalidTable,
+    /// The message cannot be deciphered, e.g. a wrong group count.
+    InvalidInput,
+    /// A token of the message stands for no character.
+    UnknownToken,
+    /// The `--authenticate` check value does not match.
+    AuthenticationFailed,
+    /// The `--cipher` is not one the CLI knows.
+    UnsupportedCipher,
+}
+
+impl ErrorCode {
+    /// Returns the code as written in JSON errors, e.g. `invalid-key`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidKey => "invalid-key",
+            ErrorCode::InvalidTable => "invalid-table",
+            ErrorCode::InvalidInput => "invalid-input",
+            ErrorCode::UnknownToken => "unknown-token",
+            ErrorCode::AuthenticationFailed => "authentication-failed",
+            ErrorCode::UnsupportedCipher => "unsupported-cipher",
+        }
+    }
+}
+
+/// An error of enciphering or deciphering a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CipherError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub position: Option<usize>,
+}
+
+impl CipherError {
+    /// Creates an error that is not about one place in the message.
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CipherError {
+            code,
+            message: message.into(),
+            position: None,
+        }
+    }
+
+    /// Creates an invalid key error.
+    pub fn invalid_key(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidKey, message)
+    }
+
+    /// Returns the error as the JSON object written by `--error-format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "error": {
+                "code": self.code.as_str(),
+                "message": self.message,
+                "position": self.position,
+            }
+        })
+    }
+
+    /// Reports the error in the given format, returning the text to use in place of the result
+    /// and the status to exit with: the error itself and 0, or, after writing the JSON to
+    /// stderr, no text and 1.
+    pub fn report(self, format: ErrorFormat) -> (String, i32) {
+        match format {
+            ErrorFormat::Text => (self.to_string(), 0),
+            ErrorFormat::Json => {
+                eprintln!("{}", self.to_json());
+                (String::new(), 1)
+            }
+        }
+    }
+}
+
+impl From<UnknownToken> for CipherError {
+    fn from(unknown: UnknownToken) -> Self {
+        CipherError {
+            code: ErrorCode::UnknownToken,
+            message: unknown.to_string(),
+            position: Some(unknown.position),
+        }
+    }
+}
+
+impl std::fmt::Display for CipherError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            ErrorCode::InvalidKey => write!(f, "{}", tr_format!("Invalid key: {}", self.message)),
+            ErrorCode::InvalidTable => {
+                write!(f, "{}", tr_format!("Invalid table: {}", self.message))
+            }
+            ErrorCode::AuthenticationFailed => write!(
+                f,
+                "{}",
+                tr_format!("Authentication failed: {}", self.message)
+            ),
+            ErrorCode::UnsupportedCipher => write!(f, "{}", tr("Unsupported cipher")),
+            ErrorCode::InvalidInput | ErrorCode::UnknownToken => write!(f, "{}", self.message),
+        }
+    }
+}
@@ -0,0 +1,509 @@
+//! Translations of the CLI's help and messages.
+//!
+//! Messages are looked up by their English text, gettext style, so a message without a
+//! translation is shown in English. The language comes from `--lang`, or else from the
+//! `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables, and is English when none of them
+//! names a supported language.
+
+use std::fmt::Display;
+use std::sync::RwLock;
+
+/// A language the CLI is translated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Es,
+    Fr,
+    De,
+}
+
+impl Lang {
+    /// Reads the language of a locale name such as `es`, `fr_FR.UTF-8` or `de-AT`.
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Lang::En),
+            "es" => Some(Lang::Es),
+            "fr" => Some(Lang::Fr),
+            "de" => Some(Lang::De),
+            _ => None,
+        }
+    }
+
+    /// Chooses the language from a `--lang` argument, falling back to the locale environment
+    /// variables and then to English.
+    pub fn detect(args: &[String]) -> Lang {
+        let flag = args
+            .iter()
+            .enumerate()
+            .find_map(|(i, arg)| match arg.as_str() {
+                "--lang" => args.get(i + 1).cloned(),
+                _ => arg.strip_prefix("--lang=").map(str::to_string),
+            });
+        flag.into_iter()
+            .chain(
+                ["LC_ALL", "LC_MESSAGES", "LANG"]
+                    .iter()
+                    .filter_map(|name| std::env::var(name).ok())
+                    .filter(|value| !value.is_empty())
+                    .take(1),
+            )
+            .find_map(|locale| Lang::from_locale(&locale))
+            .unwrap_or(Lang::En)
+    }
+}
+
+impl std::str::FromStr for Lang {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        Lang::from_locale(name).ok_or_else(|| {
+            fill(
+                tr("Unsupported language {} (expected en, es, fr or de)"),
+                &[&format!("{:?}", name)],
+            )
+        })
+    }
+}
+
+static LANG: RwLock<Lang> = RwLock::new(Lang::En);
+
+/// Sets the language of the messages.
+pub fn set_lang(lang: Lang) {
+    *LANG.write().unwrap_or_else(|e| e.into_inner()) = lang;
+}
+
+// Translations into Spanish, French and German, keyed by the English text.
+const CATALOG: &[(&str, [&str; 3])] = &[
+    (
+        "A simple CLI for ciphers and crypto",
+        [
+            "Una CLI sencilla para cifrados y criptografía",
+            "Une CLI simple pour les chiffres et la cryptographie",
+            "Eine einfache CLI für Chiffren und Kryptografie",
+        ],
+    ),
+    (
+        "Encode a message using a cipher",
+        [
+            "Cifra un mensaje con un cifrado",
+            "Chiffre un message avec un chiffre",
+            "Verschlüsselt eine Nachricht mit einer Chiffre",
+        ],
+    ),
+    (
+        "Decode a message using a cipher",
+        [
+            "Descifra un mensaje con un cifrado",
+            "Déchiffre un message avec un chiffre",
+            "Entschlüsselt eine Nachricht mit einer Chiffre",
+        ],
+    ),
+    (
+        "Run a message through a chain of ciphers",
+        [
+            "Pasa un mensaje por una cadena de cifrados",
+            "Fait passer un message par une chaîne de chiffres",
+            "Schickt eine Nachricht durch eine Kette von Chiffren",
+        ],
+    ),
+    (
+        "Analyze a message or keystream",
+        [
+            "Analiza un mensaje o un flujo de clave",
+            "Analyse un message ou un flux de clé",
+            "Analysiert eine Nachricht oder einen Schlüsselstrom",
+        ],
+    ),
+    (
+        "Hash a message or check candidates against a digest",
+        [
+            "Calcula el hash de un mensaje o comprueba candidatos contra un resumen",
+            "Calcule le hachage d'un message ou teste des candidats contre une empreinte",
+            "Berechnet den Hash einer Nachricht oder prüft Kandidaten gegen einen Hashwert",
+        ],
+    ),
+    (
+        "Crack a ciphertext by trying every key",
+        [
+            "Rompe un texto cifrado probando todas las claves",
+            "Casse un texte chiffré en essayant toutes les clés",
+            "Bricht einen Geheimtext, indem jeder Schlüssel probiert wird",
+        ],
+    ),
+    (
+        "Transmit a message as Morse signal timings",
+        [
+            "Transmite un mensaje como tiempos de señal Morse",
+            "Transmet un message sous forme de signaux Morse",
+            "Sendet eine Nachricht als Morse-Signalzeiten",
+        ],
+    ),
+    (
+        "Run as a long-lived JSON-RPC or HTTP server",
+        [
+            "Se ejecuta como servidor JSON-RPC o HTTP",
+            "S'exécute comme serveur JSON-RPC ou HTTP",
+            "Läuft als dauerhafter JSON-RPC- oder HTTP-Server",
+        ],
+    ),
+    (
+        "Language of help and messages: en, es, fr or de (default: from LANG)",
+        [
+            "Idioma de la ayuda y los mensajes: en, es, fr o de (por defecto: según LANG)",
+            "Langue de l'aide et des messages : en, es, fr ou de (par défaut : selon LANG)",
+            "Sprache der Hilfe und Meldungen: en, es, fr oder de (Standard: aus LANG)",
+        ],
+    ),
+    (
+        "Output to a file instead of stdout",
+        [
+            "Escribe la salida en un archivo en lugar de stdout",
+            "Écrit la sortie dans un fichier au lieu de stdout",
+            "Schreibt die Ausgabe in eine Datei statt nach stdout",
+        ],
+    ),
+    (
+        "The cipher to use (e.g., rot13, caesar)",
+        [
+            "El cifrado que se usa (p. ej., rot13, caesar)",
+            "Le chiffre à utiliser (p. ex. rot13, caesar)",
+            "Die zu verwendende Chiffre (z. B. rot13, caesar)",
+        ],
+    ),
+    (
+        "The message to encode",
+        [
+            "El mensaje que se cifra",
+            "Le message à chiffrer",
+            "Die zu verschlüsselnde Nachricht",
+        ],
+    ),
+    (
+        "The message to decode",
+        [
+            "El mensaje que se descifra",
+            "Le message à déchiffrer",
+            "Die zu entschlüsselnde Nachricht",
+        ],
+    ),
+    (
+        "Read the message from a file",
+        [
+            "Lee el mensaje de un archivo",
+            "Lit le message dans un fichier",
+            "Liest die Nachricht aus einer Datei",
+        ],
+    ),
+    (
+        "Shift value for Caesar cipher (default: 3)",
+        [
+            "Desplazamiento del cifrado César (por defecto: 3)",
+            "Décalage du chiffre de César (par défaut : 3)",
+            "Verschiebung der Caesar-Chiffre (Standard: 3)",
+        ],
+    ),
+    (
+        "Key to encrypt or decrypt by",
+        [
+            "Clave con la que se cifra o descifra",
+            "Clé de chiffrement ou de déchiffrement",
+            "Schlüssel zum Ver- oder Entschlüsseln",
+        ],
+    ),
+    (
+        "Invalid key: {}",
+        [
+            "Clave no válida: {}",
+            "Clé invalide : {}",
+            "Ungültiger Schlüssel: {}",
+        ],
+    ),
+    (
+        "Invalid table: {}",
+        [
+            "Tabla no válida: {}",
+            "Table invalide : {}",
+            "Ungültige Tabelle: {}",
+        ],
+    ),
+    (
+        "Invalid stage: {}",
+        [
+            "Etapa no válida: {}",
+            "Étape invalide : {}",
+            "Ungültige Stufe: {}",
+        ],
+    ),
+    (
+        "Authentication failed: {}",
+        [
+            "Falló la autenticación: {}",
+            "Échec de l'authentification : {}",
+            "Authentifizierung fehlgeschlagen: {}",
+        ],
+    ),
+    (
+        "Unsupported cipher",
+        [
+            "Cifrado no admitido",
+            "Chiffre non pris en charge",
+            "Nicht unterstützte Chiffre",
+        ],
+    ),
+    (
+        "Either --message or --file must be provided",
+        [
+            "Hay que indicar --message o --file",
+            "Il faut indiquer --message ou --file",
+            "--message oder --file muss angegeben werden",
+        ],
+    ),
+    (
+        "Could not read file: {}",
+        [
+            "No se pudo leer el archivo: {}",
+            "Impossible de lire le fichier : {}",
+            "Datei konnte nicht gelesen werden: {}",
+        ],
+    ),
+    (
+        "Could not decode file: {}",
+        [
+            "No se pudo decodificar el archivo: {}",
+            "Impossible de décoder le fichier : {}",
+            "Datei konnte nicht dekodiert werden: {}",
+        ],
+    ),
+    (
+        "Could not read key file: {}",
+        [
+            "No se pudo leer el archivo de clave: {}",
+            "Impossible de lire le fichier de clé : {}",
+            "Schlüsseldatei konnte nicht gelesen werden: {}",
+        ],
+    ),
+    (
+        "The {} cipher has no key square",
+        [
+            "El cifrado {} no tiene cuadrado de clave",
+            "Le chiffre {} n'a pas de carré de clé",
+            "Die Chiffre {} hat kein Schlüsselquadrat",
+        ],
+    ),
+    (
+        "The {} cipher has no fixed permutation",
+        [
+            "El cifrado {} no tiene una permutación fija",
+            "Le chiffre {} n'a pas de permutation fixe",
+            "Die Chiffre {} hat keine feste Permutation",
+        ],
+    ),
+    (
+        "The {} cipher has no key stream to align",
+        [
+            "El cifrado {} no tiene flujo de clave que alinear",
+            "Le chiffre {} n'a pas de flux de clé à aligner",
+            "Die Chiffre {} hat keinen Schlüsselstrom zum Ausrichten",
+        ],
+    ),
+    ("Plaintext", ["Texto claro", "Texte clair", "Klartext"]),
+    ("Key", ["Clave", "Clé", "Schlüssel"]),
+    (
+        "Ciphertext",
+        ["Texto cifrado", "Texte chiffré", "Geheimtext"],
+    ),
+    (
+        "Warning: {}",
+        ["Aviso: {}", "Avertissement : {}", "Warnung: {}"],
+    ),
+    ("Unknown error format {} (expected text or json)", ["Formato de error desconocido {} (se esperaba text o json)", "Format d'erreur inconnu {} (text ou json attendu)", "Unbekanntes Fehlerformat {} (erwartet: text oder json)"]),
+    ("Unsupported language {} (expected en, es, fr or de)", ["Idioma no admitido {} (se esperaba en, es, fr o de)", "Langue non prise en charge {} (en, es, fr ou de attendu)", "Nicht unterstützte Sprache {} (erwartet: en, es, fr oder de)"]),
+    ("Append the command (without keys), an input hash and the output to this Markdown file, or JSON Lines if it ends in .json or .jsonl", ["Añade el comando (sin claves), un hash de la entrada y la salida a este archivo Markdown, o JSON Lines si termina en .json o .jsonl", "Ajoute la commande (sans les clés), une empreinte de l'entrée et la sortie à ce fichier Markdown, ou en JSON Lines s'il se termine par .json ou .jsonl", "Hängt den Befehl (ohne Schlüssel), einen Hash der Eingabe und die Ausgabe an diese Markdown-Datei an, oder als JSON Lines, wenn sie auf .json oder .jsonl endet"]),
+    ("Encoding of input files: utf-8, utf-16le, utf-16be, latin-1 or windows-1252 (default: detected)", ["Codificación de los archivos de entrada: utf-8, utf-16le, utf-16be, latin-1 o windows-1252 (por defecto: detectada)", "Encodage des fichiers d'entrée : utf-8, utf-16le, utf-16be, latin-1 ou windows-1252 (par défaut : détecté)", "Kodierung der Eingabedateien: utf-8, utf-16le, utf-16be, latin-1 oder windows-1252 (Standard: erkannt)"]),
+    ("Line endings of the output: preserve (those of the input file), lf or crlf", ["Finales de línea de la salida: preserve (los del archivo de entrada), lf o crlf", "Fins de ligne de la sortie : preserve (celles du fichier d'entrée), lf ou crlf", "Zeilenenden der Ausgabe: preserve (die der Eingabedatei), lf oder crlf"]),
+    ("Keep the byte order mark (and UTF-16 encoding) of the input file in the output", ["Conserva la marca de orden de bytes (y la codificación UTF-16) del archivo de entrada en la salida", "Conserve l'indicateur d'ordre des octets (et l'encodage UTF-16) du fichier d'entrée dans la sortie", "Behält die Bytereihenfolge-Markierung (und die UTF-16-Kodierung) der Eingabedatei in der Ausgabe bei"]),
+    ("Render the output: bigtext draws it in large ASCII-art letters", ["Representa la salida: bigtext la dibuja en grandes letras de arte ASCII", "Rendu de la sortie : bigtext la dessine en grandes lettres d'art ASCII", "Darstellung der Ausgabe: bigtext zeichnet sie in großen ASCII-Art-Buchstaben"]),
+    ("Write the result exactly: no trailing newline and no colors, even on a terminal", ["Escribe el resultado tal cual: sin salto de línea final ni colores, incluso en un terminal", "Écrit le résultat tel quel : sans saut de ligne final ni couleurs, même dans un terminal", "Schreibt das Ergebnis unverändert: ohne abschließenden Zeilenumbruch und ohne Farben, auch im Terminal"]),
+    ("Do not end the output with a newline (the default when stdout is a pipe)", ["No termina la salida con un salto de línea (por defecto cuando stdout es una tubería)", "Ne termine pas la sortie par un saut de ligne (par défaut quand stdout est un tube)", "Beendet die Ausgabe nicht mit einem Zeilenumbruch (Standard, wenn stdout eine Pipe ist)"]),
+    ("Report encode and decode errors as text in place of the result, or as a JSON object with code, message and position on stderr, exiting with status 1", ["Informa de los errores de cifrado y descifrado como texto en lugar del resultado, o como un objeto JSON con code, message y position en stderr, saliendo con estado 1", "Signale les erreurs de chiffrement et de déchiffrement en texte à la place du résultat, ou en objet JSON avec code, message et position sur stderr, avec le code de sortie 1", "Meldet Fehler beim Ver- und Entschlüsseln als Text anstelle des Ergebnisses oder als JSON-Objekt mit code, message und position auf stderr, mit Exit-Status 1"]),
+    ("Append a check value keyed by --key so decoding can detect wrong keys", ["Añade un valor de comprobación con la clave --key para que el descifrado detecte claves erróneas", "Ajoute une valeur de contrôle dépendant de --key pour que le déchiffrement détecte les mauvaises clés", "Hängt einen mit --key verschlüsselten Prüfwert an, damit das Entschlüsseln falsche Schlüssel erkennt"]),
+    ("Telegram-style counts: summary reports letters and groups after the message, header puts a GR check line before it", ["Recuentos al estilo telegráfico: summary indica letras y grupos tras el mensaje, header pone una línea de control GR delante", "Comptes à la manière des télégrammes : summary indique les lettres et les groupes après le message, header met une ligne de contrôle GR avant lui", "Zählungen im Telegrammstil: summary nennt Buchstaben und Gruppen nach der Nachricht, header setzt eine GR-Prüfzeile davor"]),
+    ("Print the key square of the polybius, playfair, adfgx, adfgvx or bifid cipher above the result", ["Muestra el cuadrado de clave del cifrado polybius, playfair, adfgx, adfgvx o bifid encima del resultado", "Affiche le carré de clé du chiffre polybius, playfair, adfgx, adfgvx ou bifid au-dessus du résultat", "Zeigt das Schlüsselquadrat der Chiffre polybius, playfair, adfgx, adfgvx oder bifid über dem Ergebnis"]),
+    ("Print the ciphertext position, from 0, of every plaintext character above the result (transposition ciphers)", ["Muestra la posición en el texto cifrado, desde 0, de cada carácter del texto claro encima del resultado (cifrados de transposición)", "Affiche la position dans le texte chiffré, à partir de 0, de chaque caractère du texte clair au-dessus du résultat (chiffres de transposition)", "Zeigt die Position im Geheimtext, ab 0, jedes Klartextzeichens über dem Ergebnis (Transpositionschiffren)"]),
+    ("Show the message above the result, highlighting the characters the cipher changed", ["Muestra el mensaje encima del resultado, resaltando los caracteres que el cifrado cambió", "Affiche le message au-dessus du résultat, en surlignant les caractères que le chiffre a changés", "Zeigt die Nachricht über dem Ergebnis und hebt die von der Chiffre geänderten Zeichen hervor"]),
+    ("Print the message, the key letter used for each of its letters and the result as aligned rows", ["Muestra el mensaje, la letra de clave usada para cada una de sus letras y el resultado como filas alineadas", "Affiche le message, la lettre de clé utilisée pour chacune de ses lettres et le résultat en lignes alignées", "Zeigt die Nachricht, den für jeden ihrer Buchstaben verwendeten Schlüsselbuchstaben und das Ergebnis als ausgerichtete Zeilen"]),
+    ("Characters per block of --layout aligned", ["Caracteres por bloque de --layout aligned", "Caractères par bloc de --layout aligned", "Zeichen pro Block von --layout aligned"]),
+    ("Write --layout aligned as LaTeX tables", ["Escribe --layout aligned como tablas LaTeX", "Écrit --layout aligned sous forme de tableaux LaTeX", "Schreibt --layout aligned als LaTeX-Tabellen"]),
+    ("The cipher to use (e.g., rot13, caesar, reverse, gematria)", ["El cifrado que se usará (p. ej., rot13, caesar, reverse, gematria)", "Le chiffre à utiliser (p. ex. rot13, caesar, reverse, gematria)", "Die zu verwendende Chiffre (z. B. rot13, caesar, reverse, gematria)"]),
+    ("Verify and remove the check value appended by encode --authenticate", ["Verifica y elimina el valor de comprobación añadido por encode --authenticate", "Vérifie et retire la valeur de contrôle ajoutée par encode --authenticate", "Prüft und entfernt den von encode --authenticate angehängten Prüfwert"]),
+    ("Warn if the decoded message does not look like language (wrong key or cipher?)", ["Avisa si el mensaje descifrado no parece lenguaje (¿clave o cifrado erróneos?)", "Avertit si le message déchiffré ne ressemble pas à une langue (mauvaise clé ou mauvais chiffre ?)", "Warnt, wenn die entschlüsselte Nachricht nicht nach Sprache aussieht (falscher Schlüssel oder falsche Chiffre?)"]),
+    ("A cipher and its options, e.g. \"caesar --shift 3\"; repeat for every stage", ["Un cifrado y sus opciones, p. ej. \"caesar --shift 3\"; repítalo para cada etapa", "Un chiffre et ses options, p. ex. \"caesar --shift 3\" ; à répéter pour chaque étape", "Eine Chiffre und ihre Optionen, z. B. \"caesar --shift 3\"; für jede Stufe wiederholen"]),
+    ("Decode by running the inverse of the chain", ["Descifra ejecutando la inversa de la cadena", "Déchiffre en exécutant l'inverse de la chaîne", "Entschlüsselt, indem die Umkehrung der Kette ausgeführt wird"]),
+    ("Merge Caesar shifts and cancel stages that undo each other, printing the effective chain to stderr", ["Fusiona los desplazamientos César y anula las etapas que se deshacen entre sí, mostrando la cadena efectiva en stderr", "Fusionne les décalages de César et annule les étapes qui se défont mutuellement, en affichant la chaîne effective sur stderr", "Fasst Caesar-Verschiebungen zusammen und streicht Stufen, die einander aufheben, und gibt die wirksame Kette auf stderr aus"]),
+    ("The message to run through the chain", ["El mensaje que pasará por la cadena", "Le message à faire passer par la chaîne", "Die Nachricht, die durch die Kette geschickt wird"]),
+    ("Run frequency, runs and poker tests over a keystream (bits, digits, letters or binary file)", ["Ejecuta las pruebas de frecuencia, rachas y póquer sobre un flujo de clave (bits, dígitos, letras o archivo binario)", "Exécute les tests de fréquence, de séquences et du poker sur un flux de clé (bits, chiffres, lettres ou fichier binaire)", "Führt Häufigkeits-, Run- und Pokertests über einen Schlüsselstrom aus (Bits, Ziffern, Buchstaben oder Binärdatei)"]),
+    ("Analyze every file of a directory together and print a JSON report", ["Analiza juntos todos los archivos de un directorio y muestra un informe JSON", "Analyse ensemble tous les fichiers d'un répertoire et affiche un rapport JSON", "Analysiert alle Dateien eines Verzeichnisses gemeinsam und gibt einen JSON-Bericht aus"]),
+    ("The message to analyze", ["El mensaje que se analizará", "Le message à analyser", "Die zu analysierende Nachricht"]),
+    ("The hash algorithm: md5, sha1 or sha256", ["El algoritmo de hash: md5, sha1 o sha256", "L'algorithme de hachage : md5, sha1 ou sha256", "Der Hash-Algorithmus: md5, sha1 oder sha256"]),
+    ("Check every line of the message against this hex digest", ["Comprueba cada línea del mensaje contra este resumen hexadecimal", "Teste chaque ligne du message contre cette empreinte hexadécimale", "Prüft jede Zeile der Nachricht gegen diesen hexadezimalen Hashwert"]),
+    ("The message to hash", ["El mensaje del que se calculará el hash", "Le message à hacher", "Die Nachricht, deren Hash berechnet wird"]),
+    ("The cipher to crack: caesar or decimation, or with --assume-prefix also vigenere, enigma, enigma-plugboard, columnar, rail-fence or scytale", ["El cifrado que se romperá: caesar o decimation, o con --assume-prefix también vigenere, enigma, enigma-plugboard, columnar, rail-fence o scytale", "Le chiffre à casser : caesar ou decimation, ou avec --assume-prefix aussi vigenere, enigma, enigma-plugboard, columnar, rail-fence ou scytale", "Die zu brechende Chiffre: caesar oder decimation, oder mit --assume-prefix auch vigenere, enigma, enigma-plugboard, columnar, rail-fence oder scytale"]),
+    ("Stop at the first key that produces this plaintext", ["Se detiene en la primera clave que produce este texto claro", "S'arrête à la première clé qui produit ce texte clair", "Hält beim ersten Schlüssel an, der diesen Klartext ergibt"]),
+    ("Stop at the first key whose plaintext contains this word", ["Se detiene en la primera clave cuyo texto claro contiene esta palabra", "S'arrête à la première clé dont le texte clair contient ce mot", "Hält beim ersten Schlüssel an, dessen Klartext dieses Wort enthält"]),
+    ("Only keep keys whose plaintext opens with this text, e.g. WETTERBERICHT, DEAR or \"ATTACK AT\"; can be given several times. For enigma the plugboard is taken as empty and the middle ring as A, so its double step may come at the wrong letter; enigma-plugboard searches the plugboard like the Bombe, but only from an opening that closes three loops and without the middle rotor stepping within it", ["Conserva solo las claves cuyo texto claro empieza por este texto, p. ej. WETTERBERICHT, DEAR o \"ATTACK AT\"; puede darse varias veces. Para enigma el tablero de conexiones se toma vacío y el anillo central como A, así que su doble paso puede caer en la letra equivocada; enigma-plugboard busca el tablero de conexiones como la Bombe, pero solo a partir de una apertura que cierre tres bucles y sin que el rotor central avance dentro de ella", "Ne garde que les clés dont le texte clair commence par ce texte, p. ex. WETTERBERICHT, DEAR ou \"ATTACK AT\" ; peut être donné plusieurs fois. Pour enigma le tableau de connexions est supposé vide et l'anneau du milieu à A, si bien que son double pas peut tomber sur la mauvaise lettre ; enigma-plugboard cherche le tableau de connexions comme la Bombe, mais seulement à partir d'un début qui ferme trois boucles et sans que le rotor du milieu avance pendant celui-ci", "Behält nur Schlüssel, deren Klartext mit diesem Text beginnt, z. B. WETTERBERICHT, DEAR oder \"ATTACK AT\"; kann mehrmals angegeben werden. Bei enigma wird das Steckerbrett als leer und der mittlere Ring als A angenommen, sodass sein Doppelschritt beim falschen Buchstaben kommen kann; enigma-plugboard sucht das Steckerbrett wie die Bombe, aber nur ab einem Anfang, der drei Schleifen schließt, und ohne dass die mittlere Walze darin weiterschaltet"]),
+    ("How many of the best candidates to print without a crib", ["Cuántos de los mejores candidatos mostrar sin un texto conocido", "Combien des meilleurs candidats afficher sans crib", "Wie viele der besten Kandidaten ohne Crib ausgegeben werden"]),
+    ("Clean up the plaintexts with a beam search over a letter n-gram model keeping this many readings", ["Limpia los textos claros con una búsqueda en haz sobre un modelo de n-gramas de letras que conserva este número de lecturas", "Nettoie les textes clairs par une recherche en faisceau sur un modèle de n-grammes de lettres qui garde ce nombre de lectures", "Bereinigt die Klartexte mit einer Strahlsuche über ein Buchstaben-N-Gramm-Modell, die so viele Lesarten behält"]),
+    ("Text file to count the trigram model of the beam search cleanup from (default: built-in English)", ["Archivo de texto del que contar el modelo de trigramas de la limpieza por búsqueda en haz (por defecto: inglés integrado)", "Fichier texte d'où compter le modèle de trigrammes du nettoyage par recherche en faisceau (par défaut : anglais intégré)", "Textdatei, aus der das Trigramm-Modell der Strahlsuche-Bereinigung gezählt wird (Standard: eingebautes Englisch)"]),
+    ("Stop searching after this many milliseconds and print the best keys found so far", ["Deja de buscar tras este número de milisegundos y muestra las mejores claves encontradas hasta entonces", "Arrête la recherche après ce nombre de millisecondes et affiche les meilleures clés trouvées jusque-là", "Bricht die Suche nach so vielen Millisekunden ab und gibt die bis dahin besten Schlüssel aus"]),
+    ("The ciphertext to crack", ["El texto cifrado que se romperá", "Le texte chiffré à casser", "Der zu brechende Geheimtext"]),
+    ("Read the ciphertext from a file", ["Lee el texto cifrado de un archivo", "Lit le texte chiffré depuis un fichier", "Liest den Geheimtext aus einer Datei"]),
+    ("The message to transmit", ["El mensaje que se transmitirá", "Le message à transmettre", "Die zu übertragende Nachricht"]),
+    ("Speed in words per minute", ["Velocidad en palabras por minuto", "Vitesse en mots par minute", "Geschwindigkeit in Wörtern pro Minute"]),
+    ("Stream the timings to a serial port, e.g. /dev/ttyUSB0 (requires the signal feature)", ["Envía los tiempos a un puerto serie, p. ej. /dev/ttyUSB0 (requiere la característica signal)", "Envoie les durées sur un port série, p. ex. /dev/ttyUSB0 (nécessite la fonctionnalité signal)", "Sendet die Zeiten an eine serielle Schnittstelle, z. B. /dev/ttyUSB0 (erfordert das Feature signal)"]),
+    ("Baud rate of the serial port", ["Velocidad en baudios del puerto serie", "Débit en bauds du port série", "Baudrate der seriellen Schnittstelle"]),
+    ("Stream the timings to an MQTT broker, e.g. 127.0.0.1:1883 (requires the signal feature)", ["Envía los tiempos a un broker MQTT, p. ej. 127.0.0.1:1883 (requiere la característica signal)", "Envoie les durées à un broker MQTT, p. ex. 127.0.0.1:1883 (nécessite la fonctionnalité signal)", "Sendet die Zeiten an einen MQTT-Broker, z. B. 127.0.0.1:1883 (erfordert das Feature signal)"]),
+    ("MQTT topic to publish to", ["Tema MQTT en el que publicar", "Sujet MQTT sur lequel publier", "MQTT-Topic, an das veröffentlicht wird"]),
+    ("Serve JSON-RPC requests, one per line, on stdin/stdout", ["Atiende peticiones JSON-RPC, una por línea, en stdin/stdout", "Sert des requêtes JSON-RPC, une par ligne, sur stdin/stdout", "Beantwortet JSON-RPC-Anfragen, eine pro Zeile, auf stdin/stdout"]),
+    ("Serve a REST API on the given address, e.g. 127.0.0.1:8080 (requires the server feature)", ["Sirve una API REST en la dirección dada, p. ej. 127.0.0.1:8080 (requiere la característica server)", "Sert une API REST à l'adresse donnée, p. ex. 127.0.0.1:8080 (nécessite la fonctionnalité server)", "Stellt eine REST-API unter der angegebenen Adresse bereit, z. B. 127.0.0.1:8080 (erfordert das Feature server)"]),
+    ("Shift lowercase letters by this instead of --shift (caesar)", ["Desplaza las letras minúsculas en esta cantidad en lugar de --shift (caesar)", "Décale les lettres minuscules de cette valeur au lieu de --shift (caesar)", "Verschiebt Kleinbuchstaben um diesen Wert statt um --shift (caesar)"]),
+    ("Shift digits by this around 0-9, which are otherwise unchanged (caesar)", ["Desplaza los dígitos en esta cantidad dentro de 0-9, que de otro modo no cambian (caesar)", "Décale les chiffres de cette valeur dans 0-9, qui sinon restent inchangés (caesar)", "Verschiebt Ziffern um diesen Wert innerhalb von 0-9, die sonst unverändert bleiben (caesar)"]),
+    ("Growth of the shift per letter (trithemius) or per key period (progressive) (default: 1)", ["Aumento del desplazamiento por letra (trithemius) o por periodo de clave (progressive) (por defecto: 1)", "Augmentation du décalage par lettre (trithemius) ou par période de clé (progressive) (par défaut : 1)", "Zunahme der Verschiebung pro Buchstabe (trithemius) oder pro Schlüsselperiode (progressive) (Standard: 1)"]),
+    ("Characters of the zig-zag to skip before the message (rail-fence) (default: 0), rows below the message to read (jefferson) (default: 1), or characters in the first cell, 1 or 2 (amsco) (default: 2)", ["Caracteres del zigzag que se saltan antes del mensaje (rail-fence) (por defecto: 0), filas bajo el mensaje que se leen (jefferson) (por defecto: 1), o caracteres de la primera celda, 1 o 2 (amsco) (por defecto: 2)", "Caractères du zigzag à sauter avant le message (rail-fence) (par défaut : 0), lignes sous le message à lire (jefferson) (par défaut : 1), ou caractères de la première case, 1 ou 2 (amsco) (par défaut : 2)", "Zeichen des Zickzacks, die vor der Nachricht übersprungen werden (rail-fence) (Standard: 0), zu lesende Zeilen unter der Nachricht (jefferson) (Standard: 1), oder Zeichen in der ersten Zelle, 1 oder 2 (amsco) (Standard: 2)"]),
+    ("File of key text for the running key and book ciphers, used instead of --key", ["Archivo con el texto de clave de los cifrados de clave continua y de libro, usado en lugar de --key", "Fichier du texte de clé des chiffres à clé courante et à livre, utilisé à la place de --key", "Datei mit dem Schlüsseltext der Laufschlüssel- und Buchchiffren, anstelle von --key verwendet"]),
+    ("Multiplier for the Affine cipher, coprime with 26 (default: 1)", ["Multiplicador del cifrado afín, coprimo con 26 (por defecto: 1)", "Multiplicateur du chiffre affine, premier avec 26 (par défaut : 1)", "Multiplikator der affinen Chiffre, teilerfremd zu 26 (Standard: 1)"]),
+    ("Shift for the Affine cipher (default: 0)", ["Desplazamiento del cifrado afín (por defecto: 0)", "Décalage du chiffre affine (par défaut : 0)", "Verschiebung der affinen Chiffre (Standard: 0)"]),
+    ("CSV or TSV file of source,code token pairs for the table cipher", ["Archivo CSV o TSV de pares de símbolos origen,código para el cifrado de tabla", "Fichier CSV ou TSV de paires de symboles source,code pour le chiffre à table", "CSV- oder TSV-Datei mit Tokenpaaren Quelle,Code für die Tabellenchiffre"]),
+    ("Fill the last row with this character (columnar transposition, scytale, grille) or split pairs with it (playfair)", ["Rellena la última fila con este carácter (transposición columnar, escítala, rejilla) o separa los pares con él (playfair)", "Remplit la dernière ligne avec ce caractère (transposition par colonnes, scytale, grille) ou sépare les paires avec lui (playfair)", "Füllt die letzte Zeile mit diesem Zeichen auf (Spaltentransposition, Skytale, Schablone) oder trennt damit Paare (playfair)"]),
+    ("Row and column labels for the Polybius square, e.g. ADFGX or 12345/ABCDE", ["Etiquetas de filas y columnas del cuadrado de Polibio, p. ej. ADFGX o 12345/ABCDE", "Étiquettes des lignes et des colonnes du carré de Polybe, p. ex. ADFGX ou 12345/ABCDE", "Zeilen- und Spaltenbeschriftungen des Polybios-Quadrats, z. B. ADFGX oder 12345/ABCDE"]),
+    ("Character of a tap for the tap code (default: .)", ["Carácter de un golpe del código de golpes (por defecto: .)", "Caractère d'un coup du code à coups (par défaut : .)", "Zeichen eines Klopfzeichens im Klopfcode (Standard: .)"]),
+    ("Separators between the two groups of taps of a letter and between letters for the tap code, e.g. \",/ \" (default: \" /  \")", ["Separadores entre los dos grupos de golpes de una letra y entre letras del código de golpes, p. ej. \",/ \" (por defecto: \" /  \")", "Séparateurs entre les deux groupes de coups d'une lettre et entre les lettres du code à coups, p. ex. \",/ \" (par défaut : \" /  \")", "Trennzeichen zwischen den zwei Klopfgruppen eines Buchstabens und zwischen Buchstaben im Klopfcode, z. B. \",/ \" (Standard: \" /  \")"]),
+    ("Enigma rotors from left to right, e.g. \"I II III\" (default: I II III), or jefferson wheel numbers in order, e.g. \"3 1 2\"", ["Rotores de Enigma de izquierda a derecha, p. ej. \"I II III\" (por defecto: I II III), o números de las ruedas jefferson en orden, p. ej. \"3 1 2\"", "Rotors d'Enigma de gauche à droite, p. ex. \"I II III\" (par défaut : I II III), ou numéros des roues jefferson dans l'ordre, p. ex. \"3 1 2\"", "Enigma-Walzen von links nach rechts, z. B. \"I II III\" (Standard: I II III), oder die Nummern der jefferson-Scheiben in Reihenfolge, z. B. \"3 1 2\""]),
+    ("Enigma reflector, B or C (default: B)", ["Reflector de Enigma, B o C (por defecto: B)", "Réflecteur d'Enigma, B ou C (par défaut : B)", "Enigma-Umkehrwalze, B oder C (Standard: B)"]),
+    ("Enigma rotor start positions from left to right, e.g. BLA (default: AAA), or the 12 Lorenz wheel positions from 1 (default: all 1)", ["Posiciones iniciales de los rotores de Enigma de izquierda a derecha, p. ej. BLA (por defecto: AAA), o las 12 posiciones de las ruedas de Lorenz desde 1 (por defecto: todas 1)", "Positions de départ des rotors d'Enigma de gauche à droite, p. ex. BLA (par défaut : AAA), ou les 12 positions des roues de Lorenz à partir de 1 (par défaut : toutes à 1)", "Startstellungen der Enigma-Walzen von links nach rechts, z. B. BLA (Standard: AAA), oder die 12 Radstellungen der Lorenz ab 1 (Standard: alle 1)"]),
+    ("Enigma ring settings as letters or numbers, e.g. BUL or \"02 21 12\" (default: AAA)", ["Posiciones de los anillos de Enigma como letras o números, p. ej. BUL o \"02 21 12\" (por defecto: AAA)", "Réglages des anneaux d'Enigma en lettres ou en nombres, p. ex. BUL ou \"02 21 12\" (par défaut : AAA)", "Ringstellungen der Enigma als Buchstaben oder Zahlen, z. B. BUL oder \"02 21 12\" (Standard: AAA)"]),
+    ("Enigma plugboard pairs, e.g. \"AV BS CG\"", ["Pares del tablero de conexiones de Enigma, p. ej. \"AV BS CG\"", "Paires du tableau de connexions d'Enigma, p. ex. \"AV BS CG\"", "Steckerpaare der Enigma, z. B. \"AV BS CG\""]),
+    ("Case of the output letters of letter ciphers: preserve, upper, lower, or aca for capital ciphertext and lowercase plaintext (default: preserve)", ["Caja de las letras de salida de los cifrados de letras: preserve, upper, lower, o aca para texto cifrado en mayúsculas y texto claro en minúsculas (por defecto: preserve)", "Casse des lettres de sortie des chiffres de lettres : preserve, upper, lower, ou aca pour un texte chiffré en majuscules et un texte clair en minuscules (par défaut : preserve)", "Groß- und Kleinschreibung der ausgegebenen Buchstaben von Buchstabenchiffren: preserve, upper, lower oder aca für Geheimtext in Großbuchstaben und Klartext in Kleinbuchstaben (Standard: preserve)"]),
+    ("Alphabet of the caesar, vigenere, atbash, affine and substitution ciphers: latin, greek, russian, turkish or its letters (default: latin)", ["Alfabeto de los cifrados caesar, vigenere, atbash, affine y substitution: latin, greek, russian, turkish o sus letras (por defecto: latin)", "Alphabet des chiffres caesar, vigenere, atbash, affine et substitution : latin, greek, russian, turkish ou ses lettres (par défaut : latin)", "Alphabet der Chiffren caesar, vigenere, atbash, affine und substitution: latin, greek, russian, turkish oder seine Buchstaben (Standard: latin)"]),
+    ("What to do with spaces, digits and punctuation before encoding: preserve, strip, error, or a single character to replace them with (default: preserve)", ["Qué hacer con espacios, dígitos y puntuación antes de cifrar: preserve, strip, error, o un único carácter por el que sustituirlos (por defecto: preserve)", "Que faire des espaces, des chiffres et de la ponctuation avant le chiffrement : preserve, strip, error, ou un caractère unique par lequel les remplacer (par défaut : preserve)", "Was vor dem Verschlüsseln mit Leerzeichen, Ziffern und Satzzeichen geschieht: preserve, strip, error oder ein einzelnes Zeichen, das sie ersetzt (Standard: preserve)"]),
+    ("Write the ciphertext in groups of this many characters separated by spaces, and remove all whitespace before decoding (not for token encodings such as morse)", ["Escribe el texto cifrado en grupos de este número de caracteres separados por espacios, y elimina todo espacio en blanco antes de descifrar (no para codificaciones por símbolos como morse)", "Écrit le texte chiffré par groupes de ce nombre de caractères séparés par des espaces, et retire tous les blancs avant le déchiffrement (pas pour les codages par symboles comme morse)", "Schreibt den Geheimtext in Gruppen aus so vielen Zeichen, durch Leerzeichen getrennt, und entfernt vor dem Entschlüsseln alle Leerräume (nicht für Token-Kodierungen wie morse)"]),
+    ("Break the lines of the ciphertext at this many characters, between groups with --group-size, and join them before decoding", ["Corta las líneas del texto cifrado en este número de caracteres, entre grupos con --group-size, y las une antes de descifrar", "Coupe les lignes du texte chiffré à ce nombre de caractères, entre les groupes avec --group-size, et les rejoint avant le déchiffrement", "Bricht die Zeilen des Geheimtexts nach so vielen Zeichen um, zwischen Gruppen bei --group-size, und fügt sie vor dem Entschlüsseln zusammen"]),
+    ("Could not get message", ["No se pudo obtener el mensaje", "Impossible d'obtenir le message", "Nachricht konnte nicht gelesen werden"]),
+    ("Warning: {} ({})", ["Aviso: {} ({})", "Avertissement : {} ({})", "Warnung: {} ({})"]),
+    ("{}\nLetters: {}\nGroups: {}\n{}", ["{}\nLetras: {}\nGrupos: {}\n{}", "{}\nLettres : {}\nGroupes : {}\n{}", "{}\nBuchstaben: {}\nGruppen: {}\n{}"]),
+    ("Warning: output does not look like language — wrong key or cipher? (score {}, expected at least {})", ["Aviso: la salida no parece lenguaje — ¿clave o cifrado erróneos? (puntuación {}, se esperaba al menos {})", "Avertissement : la sortie ne ressemble pas à une langue — mauvaise clé ou mauvais chiffre ? (score {}, au moins {} attendu)", "Warnung: Die Ausgabe sieht nicht nach Sprache aus — falscher Schlüssel oder falsche Chiffre? (Wert {}, erwartet mindestens {})"]),
+    ("Effective chain: {}", ["Cadena efectiva: {}", "Chaîne effective : {}", "Wirksame Kette: {}"]),
+    ("Could not serve requests", ["No se pudieron atender las peticiones", "Impossible de servir les requêtes", "Anfragen konnten nicht beantwortet werden"]),
+    ("No transport selected (e.g. --stdio or --http)", ["No se ha seleccionado ningún transporte (p. ej. --stdio o --http)", "Aucun transport choisi (p. ex. --stdio ou --http)", "Kein Transport ausgewählt (z. B. --stdio oder --http)"]),
+    ("Could not create file", ["No se pudo crear el archivo", "Impossible de créer le fichier", "Datei konnte nicht erstellt werden"]),
+    ("Could not write to output", ["No se pudo escribir la salida", "Impossible d'écrire la sortie", "Ausgabe konnte nicht geschrieben werden"]),
+    ("The key row is only shown for the alphabet A to Z", ["La fila de clave solo se muestra para el alfabeto de la A a la Z", "La ligne de clé n'est affichée que pour l'alphabet de A à Z", "Die Schlüsselzeile wird nur für das Alphabet A bis Z angezeigt"]),
+    ("Stage must name a cipher", ["La etapa debe nombrar un cifrado", "L'étape doit nommer un chiffre", "Die Stufe muss eine Chiffre nennen"]),
+    ("none, the stages cancel out", ["ninguna, las etapas se anulan entre sí", "aucune, les étapes s'annulent", "keine, die Stufen heben sich auf"]),
+    ("Unsupported cipher {}", ["Cifrado no admitido {}", "Chiffre non pris en charge {}", "Nicht unterstützte Chiffre {}"]),
+    ("--key must be provided", ["Debe indicarse --key", "--key doit être fourni", "--key muss angegeben werden"]),
+    ("Offset must be an integer, found {}", ["El desplazamiento debe ser un entero, se encontró {}", "Le décalage doit être un entier, trouvé {}", "Der Versatz muss eine ganze Zahl sein, gefunden: {}"]),
+    ("Separators must be the group and letter separators separated by '/', found {}", ["Los separadores deben ser los separadores de grupo y de letra separados por '/', se encontró {}", "Les séparateurs doivent être ceux des groupes et des lettres séparés par '/', trouvé {}", "Die Trennzeichen müssen die Gruppen- und Buchstabentrenner sein, getrennt durch '/', gefunden: {}"]),
+    ("Grille: {}", ["Rejilla: {}", "Grille : {}", "Schablone: {}"]),
+    ("Period must be a positive integer, found {}", ["El periodo debe ser un entero positivo, se encontró {}", "La période doit être un entier positif, trouvé {}", "Die Periode muss eine positive ganze Zahl sein, gefunden: {}"]),
+    ("Expected two keywords separated by a comma, found {}", ["Se esperaban dos palabras clave separadas por una coma, se encontró {}", "Deux mots-clés séparés par une virgule attendus, trouvé {}", "Zwei durch ein Komma getrennte Schlüsselwörter erwartet, gefunden: {}"]),
+    ("--rotors must name three rotors, e.g. \"I II III\"", ["--rotors debe nombrar tres rotores, p. ej. \"I II III\"", "--rotors doit nommer trois rotors, p. ex. \"I II III\"", "--rotors muss drei Walzen nennen, z. B. \"I II III\""]),
+    ("Wheel numbers must be positive integers, found {}", ["Los números de rueda deben ser enteros positivos, se encontró {}", "Les numéros de roue doivent être des entiers positifs, trouvé {}", "Die Scheibennummern müssen positive ganze Zahlen sein, gefunden: {}"]),
+    ("Wheel positions must be positive integers, found {}", ["Las posiciones de las ruedas deben ser enteros positivos, se encontró {}", "Les positions des roues doivent être des entiers positifs, trouvé {}", "Die Radstellungen müssen positive ganze Zahlen sein, gefunden: {}"]),
+    ("{} must be a positive integer, found {}", ["{} debe ser un entero positivo, se encontró {}", "{} doit être un entier positif, trouvé {}", "{} muss eine positive ganze Zahl sein, gefunden: {}"]),
+    ("--table must be provided", ["Debe indicarse --table", "--table doit être fourni", "--table muss angegeben werden"]),
+    ("Could not read table: {}", ["No se pudo leer la tabla: {}", "Impossible de lire la table : {}", "Tabelle konnte nicht gelesen werden: {}"]),
+    ("Could not parse table: {}", ["No se pudo analizar la tabla: {}", "Impossible d'analyser la table : {}", "Tabelle konnte nicht geparst werden: {}"]),
+    ("--group-size cannot be used with {}, whose tokens are separated by spaces", ["--group-size no puede usarse con {}, cuyos símbolos se separan con espacios", "--group-size ne peut pas être utilisé avec {}, dont les symboles sont séparés par des espaces", "--group-size kann nicht mit {} verwendet werden, dessen Tokens durch Leerzeichen getrennt sind"]),
+    ("--wrap cannot be used with {}, which is drawn on several lines", ["--wrap no puede usarse con {}, que se dibuja en varias líneas", "--wrap ne peut pas être utilisé avec {}, qui est dessiné sur plusieurs lignes", "--wrap kann nicht mit {} verwendet werden, das über mehrere Zeilen gezeichnet wird"]),
+    ("No analysis selected (e.g. --randomness)", ["No se ha seleccionado ningún análisis (p. ej. --randomness)", "Aucune analyse choisie (p. ex. --randomness)", "Keine Analyse ausgewählt (z. B. --randomness)"]),
+    ("Cracking {} needs an assumed opening (--assume-prefix)", ["Romper {} requiere una apertura supuesta (--assume-prefix)", "Casser {} nécessite un début supposé (--assume-prefix)", "Das Brechen von {} erfordert einen angenommenen Anfang (--assume-prefix)"]),
+    ("Unsupported cipher (expected caesar, decimation, vigenere, enigma, enigma-plugboard, columnar, rail-fence or scytale)", ["Cifrado no admitido (se esperaba caesar, decimation, vigenere, enigma, enigma-plugboard, columnar, rail-fence o scytale)", "Chiffre non pris en charge (caesar, decimation, vigenere, enigma, enigma-plugboard, columnar, rail-fence ou scytale attendu)", "Nicht unterstützte Chiffre (erwartet: caesar, decimation, vigenere, enigma, enigma-plugboard, columnar, rail-fence oder scytale)"]),
+    ("No key fits the known plaintext", ["Ninguna clave encaja con el texto claro conocido", "Aucune clé ne correspond au texte clair connu", "Kein Schlüssel passt zum bekannten Klartext"]),
+    ("No key fits the assumed opening", ["Ninguna clave encaja con la apertura supuesta", "Aucune clé ne correspond au début supposé", "Kein Schlüssel passt zum angenommenen Anfang"]),
+    ("Could not read model: {}", ["No se pudo leer el modelo: {}", "Impossible de lire le modèle : {}", "Modell konnte nicht gelesen werden: {}"]),
+    ("Could not read directory: {}", ["No se pudo leer el directorio: {}", "Impossible de lire le répertoire : {}", "Verzeichnis konnte nicht gelesen werden: {}"]),
+    ("Could not read {}: {}", ["No se pudo leer {}: {}", "Impossible de lire {} : {}", "{} konnte nicht gelesen werden: {}"]),
+    ("The {} stage requires building cipha-cli with the `{}` feature", ["La etapa {} requiere compilar cipha-cli con la característica `{}`", "L'étape {} nécessite de compiler cipha-cli avec la fonctionnalité `{}`", "Die Stufe {} erfordert, cipha-cli mit dem Feature `{}` zu bauen"]),
+    ("No candidate matches the digest", ["Ningún candidato coincide con el resumen", "Aucun candidat ne correspond à l'empreinte", "Kein Kandidat passt zum Hashwert"]),
+    ("Hashing requires building cipha-cli with the `hash` feature", ["El hash requiere compilar cipha-cli con la característica `hash`", "Le hachage nécessite de compiler cipha-cli avec la fonctionnalité `hash`", "Hashing erfordert, cipha-cli mit dem Feature `hash` zu bauen"]),
+    ("Server stopped", ["Servidor detenido", "Serveur arrêté", "Server beendet"]),
+    ("HTTP server support requires building cipha-cli with the `server` feature", ["El servidor HTTP requiere compilar cipha-cli con la característica `server`", "Le serveur HTTP nécessite de compiler cipha-cli avec la fonctionnalité `server`", "Der HTTP-Server erfordert, cipha-cli mit dem Feature `server` zu bauen"]),
+    ("Either --serial or --mqtt must be provided", ["Debe indicarse --serial o --mqtt", "--serial ou --mqtt doit être fourni", "--serial oder --mqtt muss angegeben werden"]),
+    ("Could not transmit: {}", ["No se pudo transmitir: {}", "Impossible de transmettre : {}", "Übertragung fehlgeschlagen: {}"]),
+    ("Transmitted {} signal periods", ["Transmitidos {} periodos de señal", "{} périodes de signal transmises", "{} Signalperioden übertragen"]),
+    ("Serial and MQTT output require building cipha-cli with the `signal` feature", ["La salida serie y MQTT requiere compilar cipha-cli con la característica `signal`", "La sortie série et MQTT nécessite de compiler cipha-cli avec la fonctionnalité `signal`", "Serielle und MQTT-Ausgabe erfordern, cipha-cli mit dem Feature `signal` zu bauen"]),
+    ("Parse error: {}", ["Error de análisis: {}", "Erreur d'analyse : {}", "Parse-Fehler: {}"]),
+    ("Missing method", ["Falta el método", "Méthode manquante", "Methode fehlt"]),
+    ("Method not found: {}", ["Método no encontrado: {}", "Méthode introuvable : {}", "Methode nicht gefunden: {}"]),
+    ("Could not listen on {}: {}", ["No se pudo escuchar en {}: {}", "Impossible d'écouter sur {} : {}", "Konnte nicht auf {} lauschen: {}"]),
+    ("Request body must be UTF-8", ["El cuerpo de la petición debe ser UTF-8", "Le corps de la requête doit être en UTF-8", "Der Anfragetext muss UTF-8 sein"]),
+    ("Only POST is supported", ["Solo se admite POST", "Seul POST est pris en charge", "Nur POST wird unterstützt"]),
+    ("Invalid JSON body: {}", ["Cuerpo JSON no válido: {}", "Corps JSON invalide : {}", "Ungültiger JSON-Text: {}"]),
+    ("Unknown endpoint: {}", ["Punto de acceso desconocido: {}", "Point d'accès inconnu : {}", "Unbekannter Endpunkt: {}"]),
+    ("Params known_plaintext and known_contains cannot be combined", ["Los parámetros known_plaintext y known_contains no pueden combinarse", "Les paramètres known_plaintext et known_contains ne peuvent pas être combinés", "Die Parameter known_plaintext und known_contains können nicht kombiniert werden"]),
+    ("Param assume_prefix must be a string or an array of strings", ["El parámetro assume_prefix debe ser una cadena o un array de cadenas", "Le paramètre assume_prefix doit être une chaîne ou un tableau de chaînes", "Der Parameter assume_prefix muss ein String oder ein Array von Strings sein"]),
+    ("Param {} must be an integer between 0 and 255", ["El parámetro {} debe ser un entero entre 0 y 255", "Le paramètre {} doit être un entier entre 0 et 255", "Der Parameter {} muss eine ganze Zahl zwischen 0 und 255 sein"]),
+    ("Param {} must be a non-negative integer", ["El parámetro {} debe ser un entero no negativo", "Le paramètre {} doit être un entier positif ou nul", "Der Parameter {} muss eine nicht negative ganze Zahl sein"]),
+    ("Missing string param: {}", ["Falta el parámetro de cadena: {}", "Paramètre chaîne manquant : {}", "Fehlender String-Parameter: {}"]),
+    ("Could not open serial port {}: {}", ["No se pudo abrir el puerto serie {}: {}", "Impossible d'ouvrir le port série {} : {}", "Serielle Schnittstelle {} konnte nicht geöffnet werden: {}"]),
+    ("Could not connect to {}: {}", ["No se pudo conectar a {}: {}", "Impossible de se connecter à {} : {}", "Verbindung zu {} fehlgeschlagen: {}"]),
+    ("Could not send MQTT CONNECT: {}", ["No se pudo enviar MQTT CONNECT: {}", "Impossible d'envoyer MQTT CONNECT : {}", "MQTT CONNECT konnte nicht gesendet werden: {}"]),
+    ("Could not read MQTT CONNACK: {}", ["No se pudo leer MQTT CONNACK: {}", "Impossible de lire MQTT CONNACK : {}", "MQTT CONNACK konnte nicht gelesen werden: {}"]),
+    ("MQTT topic is {} bytes long but at most 65535 are allowed", ["El tema MQTT tiene {} bytes, pero se permiten como máximo 65535", "Le sujet MQTT fait {} octets, mais 65535 au plus sont permis", "Das MQTT-Topic ist {} Bytes lang, erlaubt sind höchstens 65535"]),
+    ("MQTT broker refused the connection (code {})", ["El broker MQTT rechazó la conexión (código {})", "Le broker MQTT a refusé la connexion (code {})", "Der MQTT-Broker hat die Verbindung abgelehnt (Code {})"]),
+    ("Could not write transcript: {}", ["No se pudo escribir la transcripción: {}", "Impossible d'écrire la transcription : {}", "Transkript konnte nicht geschrieben werden: {}"]),
+    ("Width", ["Anchura", "Largeur", "Breite"]),
+    ("Rails", ["Raíles", "Rails", "Schienen"]),
+    ("Number of streams", ["Número de flujos", "Nombre de flux", "Anzahl der Ströme"]),
+    ("Skip", ["Salto", "Pas", "Sprung"]),
+    ("Diameter", ["Diámetro", "Diamètre", "Durchmesser"]),
+    ("Prints help information", ["Muestra la información de ayuda", "Affiche l'aide", "Zeigt die Hilfe an"]),
+    ("Prints version information", ["Muestra la información de versión", "Affiche la version", "Zeigt die Versionsinformationen an"]),
+];
+
+/// Translates an English message into the language set with [`set_lang`], falling back to the
+/// English text if there is no translation.
+pub fn tr(english: &'static str) -> &'static str {
+    tr_into(*LANG.read().unwrap_or_else(|e| e.into_inner()), english)
+}
+
+// Translates an English message into the language.
+fn tr_into(lang: Lang, english: &'static str) -> &'static str {
+    let column = match lang {
+        Lang::En => return english,
+        Lang::Es => 0,
+        Lang::Fr => 1,
+        Lang::De => 2,
+    };
+    CATALOG
+        .iter()
+        .find(|(source, _)| *source == english)
+        .map_or(english, |(_, translations)| translations[column])
+}
+
+/// Fills the `{}` placeholders of a translated message with the arguments, in order.
+pub fn fill(message: &str, args: &[&dyn Display]) -> String {
+    let mut parts = message.split("{}");
+    let mut filled = parts.next().unwrap_or("").to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(part);
+    }
+    filled
+}
+
+/// Formats a message like `format!`, translating the English format string first.
+macro_rules! tr_format {
+    ($message:literal $(, $arg:expr)* $(,)?) => {
+        $crate::i18n::fill($crate::i18n::tr($message), &[$(&$arg),*])
+    };
+}
+
+pub(crate) use tr_format;
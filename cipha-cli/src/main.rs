@@ -9,11 +9,61 @@
 //! ```
 //!
 
-use structopt::StructOpt;
+use serde_json::{json, Value};
 use std::fs::File;
-use std::io::{Write, Read, stdout};
-extern crate cipha; 
-use cipha::utils::{alpha2num, atbash_cipher, atbash_decipher, caesar_cipher, morse_code_cipher, morse_code_decipher, num2alpha, reverse_cipher, rot13, vigenere_cipher, vigenere_decipher};
+use std::io::{stdin, stdout, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use structopt::StructOpt;
+extern crate cipha;
+use cipha::alphabet::Alphabet;
+use cipha::analysis::beam::{BeamDecoder, NgramModel};
+#[cfg(feature = "hash")]
+use cipha::analysis::digest::{hex_digest, verify_digest, HashAlgorithm};
+use cipha::analysis::prefix::{self, PrefixAttack, PrefixSolution};
+use cipha::analysis::{
+    analyze_corpus, caesar_candidates_with_tracker, decimation_candidates_with_tracker,
+    english_score, estimate_strength, looks_like_english, randomness_tests, Candidate, CipherClass,
+    CipherConfig, Crib, ENGLISH_SCORE_THRESHOLD,
+};
+use cipha::authentication::Authenticated;
+use cipha::budget::{Budget, BudgetTracker};
+use cipha::ciphers::{
+    AdfgvxCipher, AffineCipher, AmscoCipher, AtbashCipher, BaudotCode, BaudotFormat, BifidCipher,
+    BookCipher, BookScheme, BoustrophedonCipher, CaesarBoxCipher, CaesarCipher, Cipher,
+    ColumnarTranspositionCipher, DecimationCipher, DoubleTranspositionCipher, FallibleCipher,
+    Grille, GrilleCipher, InterleaveCipher, KeySchedule, KeyedCaesarCipher, MorseCode, MorseTiming,
+    NomenclatorCipher, NumericSubstitution, PigpenCipher, PlayfairCipher, PolyalphabeticCipher,
+    PolybiusGrid, PolybiusSquare, PortaCipher, PortaTable, RailFenceCipher, ReverseLettersCipher,
+    ReverseWordsCipher, RotN, RunningKeyCipher, ScytaleCipher, SpiralCipher, SubstitutionCipher,
+    TableCipher, TapCode, TokenCode, Transposition, TrithemiusCipher, UnknownToken, VigenereCipher,
+    WordTranspositionCipher, UNKNOWN,
+};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use cipha::compression::{Codec, Compress};
+use cipha::grid::SpiralDirection;
+use cipha::machines::{EnigmaMachine, JeffersonWheel, LorenzMachine, Rotor};
+use cipha::nulls::{NullPadding, NullPlacement};
+use cipha::pipeline::{simplify_stages, Elementary, Pipeline, Simplified};
+use cipha::render::{aligned_rows, big_text, polybius_grid, Markup};
+use cipha::text::{
+    add_group_count, convert_line_endings, decode_text, detect_encoding, encode_text,
+    parse_roman_numerals, romanize_numbers, strip_group_count, Formatter, GroupCount, LetterCase,
+    LineEnding, NonAlphaPolicy, RomanParsing, TextEncoding,
+};
+mod error;
+mod i18n;
+mod serve;
+mod signal;
+mod transcript;
+
+use error::{CipherError, ErrorCode, ErrorFormat};
+use i18n::{tr, tr_format, Lang};
+
+use cipha::utils::{
+    alpha2num, morse_code_cipher, morse_code_decipher, num2alpha, reverse_cipher, rot13, rot18,
+    rot47, rot5,
+};
 
 /// A simple CLI for ciphers and cryptography.
 ///
@@ -24,20 +74,175 @@ use cipha::utils::{alpha2num, atbash_cipher, atbash_decipher, caesar_cipher, mor
 /// cipha-cli encode --cipher rot13 --message "Hello, World!"
 /// cipha-cli encode --cipher caesar --message "Hello, World!" --shift 3
 /// ```
+// `long_about` repeats the translated `about`, which the doc comments would replace in `--help`.
 #[derive(Debug, StructOpt)]
-#[structopt(name = "cipha-cli", about = "A simple CLI for ciphers and crypto")]
+#[structopt(
+    name = "cipha-cli",
+    about = tr("A simple CLI for ciphers and crypto"),
+    long_about = tr("A simple CLI for ciphers and crypto"),
+    help_message = tr("Prints help information"),
+    version_message = tr("Prints version information")
+)]
 struct Cli {
     /// The subcommand to execute.
     #[structopt(subcommand)]
     cmd: Command,
+    /// Language of help and messages.
+    #[structopt(
+        long,
+        global = true,
+        help = tr("Language of help and messages: en, es, fr or de (default: from LANG)")
+    )]
+    lang: Option<Lang>,
+    /// Append a record of the invocation to a transcript file.
+    #[structopt(
+        long,
+        help = tr("Append the command (without keys), an input hash and the output to this Markdown file, or JSON Lines if it ends in .json or .jsonl")
+    )]
+    transcript: Option<String>,
     /// Output to a file instead of stdout.
-    #[structopt(short, long, help = "Output to a file instead of stdout")]
+    #[structopt(short, long, help = tr("Output to a file instead of stdout"))]
     output_file: Option<String>,
+    /// The encoding of input files.
+    #[structopt(
+        long,
+        help = tr("Encoding of input files: utf-8, utf-16le, utf-16be, latin-1 or windows-1252 (default: detected)")
+    )]
+    encoding: Option<TextEncoding>,
+    /// The line endings of the output.
+    #[structopt(
+        long,
+        help = tr("Line endings of the output: preserve (those of the input file), lf or crlf")
+    )]
+    line_endings: Option<LineEndings>,
+    /// Whether the byte order mark of an input file is kept in the output.
+    #[structopt(
+        long,
+        default_value = "strip",
+        possible_values = &["preserve", "strip"],
+        help = tr("Keep the byte order mark (and UTF-16 encoding) of the input file in the output")
+    )]
+    bom: String,
+    /// Render the output, e.g. as large ASCII-art letters.
+    #[structopt(
+        long,
+        possible_values = &["bigtext"],
+        help = tr("Render the output: bigtext draws it in large ASCII-art letters")
+    )]
+    render: Option<String>,
+    /// Write the result exactly as the cipher produced it.
+    #[structopt(
+        long,
+        help = tr("Write the result exactly: no trailing newline and no colors, even on a terminal")
+    )]
+    raw: bool,
+    /// Do not end the output with a newline.
+    #[structopt(
+        long,
+        help = tr("Do not end the output with a newline (the default when stdout is a pipe)")
+    )]
+    no_newline: bool,
+    /// How encode and decode report errors.
+    #[structopt(
+        long,
+        default_value = "text",
+        possible_values = &["text", "json"],
+        help = tr("Report encode and decode errors as text in place of the result, or as a JSON object with code, message and position on stderr, exiting with status 1")
+    )]
+    error_format: ErrorFormat,
 }
 
-/// Subcommands for the CLI.
-///
-/// These subcommands allow you to either encode or decode a message using different ciphers.
+/// The `--line-endings` option: keep the input file's layout or convert to one convention.
+#[derive(Debug, Clone, Copy)]
+enum LineEndings {
+    Preserve,
+    Convert(LineEnding),
+}
+
+impl std::str::FromStr for LineEndings {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "preserve" => Ok(LineEndings::Preserve),
+            _ => name.parse().map(LineEndings::Convert),
+        }
+    }
+}
+
+/// The layout of an input file that decoding drops: its byte order mark and line endings.
+struct InputLayout {
+    encoding: TextEncoding,
+    bom: bool,
+    line_ending: Option<LineEnding>,
+    final_newline: bool,
+}
+
+impl InputLayout {
+    /// Reads the layout of the file, or returns `None` if it cannot be read or decoded.
+    fn read(path: &str, encoding: Option<TextEncoding>) -> Option<Self> {
+        let bytes = std::fs::read(path).ok()?;
+        let text = decode_text(&bytes, encoding).ok()?;
+        let encoding = encoding.unwrap_or_else(|| detect_encoding(&bytes));
+        let bom = match encoding {
+            TextEncoding::Utf8 => bytes.starts_with(b"\xEF\xBB\xBF"),
+            TextEncoding::Utf16Le => bytes.starts_with(b"\xFF\xFE"),
+            TextEncoding::Utf16Be => bytes.starts_with(b"\xFE\xFF"),
+            TextEncoding::Latin1 | TextEncoding::Windows1252 => false,
+        };
+        Some(InputLayout {
+            encoding,
+            bom,
+            line_ending: LineEnding::detect(&text),
+            final_newline: text.ends_with('\n'),
+        })
+    }
+}
+
+impl Command {
+    /// Reads the message or file a command works on, for the transcript.
+    fn input(&self) -> Option<Vec<u8>> {
+        match self {
+            Command::Encode { message, file, .. }
+            | Command::Decode { message, file, .. }
+            | Command::Chain { message, file, .. }
+            | Command::Analyze { message, file, .. }
+            | Command::Hash { message, file, .. }
+            | Command::Crack { message, file, .. }
+            | Command::Transmit { message, file, .. } => {
+                get_bytes(message.clone(), file.clone()).ok()
+            }
+            Command::Serve { .. } => None,
+        }
+    }
+
+    /// Returns the file the message of `encode`, `decode` or `chain` is read from, if any.
+    fn input_file(&self) -> Option<&str> {
+        match self {
+            Command::Encode {
+                message: None,
+                file,
+                ..
+            }
+            | Command::Decode {
+                message: None,
+                file,
+                ..
+            }
+            | Command::Chain {
+                message: None,
+                file,
+                ..
+            } => file.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+// Subcommands for the CLI. These are plain comments rather than doc comments, which structopt
+// would show in place of the `about` of `Cli`.
+//
+// These subcommands allow you to either encode or decode a message using different ciphers.
 #[derive(Debug, StructOpt)]
 enum Command {
     /// Encode a message using a cipher.
@@ -48,24 +253,98 @@ enum Command {
     /// - `--message`: The message to encode.
     /// - `--file`: Read the message from a file.
     /// - `--shift`: Shift value for Caesar cipher (default: 3).
+    /// - `--shift-lower`, `--shift-digits`: Separate shifts of lowercase letters and of digits for the Caesar cipher.
+    /// - `--step`: Growth of the shift for the Trithemius and progressive ciphers (default: 1).
+    /// - `--offset`: Start of the zig-zag for the rail fence cipher (default: 0), the row of the Jefferson wheel cipher (default: 1), or the size of the first AMSCO cell (default: 2).
     /// - `--key`: Key to encrypt by.
-    #[structopt(name = "encode", about = "Encode a message using a cipher")]
+    /// - `--key-file`: Read the key text of the running key and book ciphers from a file.
+    /// - `--key-a`, `--key-b`: Multiplier and shift for the Affine cipher.
+    /// - `--table`: CSV or TSV translation table for the table cipher, code table for the nomenclator, or tokens of the token code.
+    /// - `--padding`: Character to fill the last row of a columnar transposition or scytale with, or to split Playfair pairs with.
+    /// - `--labels`: Row and column labels for the Polybius square.
+    /// - `--dot`: Character of a tap for the tap code.
+    /// - `--separators`: Separators of the two groups of a letter and of letters for the tap code.
+    /// - `--case`: Case of the output letters: `preserve`, `upper`, `lower` or `aca`.
+    /// - `--alphabet`: Alphabet of the caesar, vigenere, atbash, affine and substitution ciphers: `latin`, `greek`, `russian`, `turkish` or its letters.
+    /// - `--non-alpha`: Spaces, digits and punctuation before encoding: `preserve`, `strip`, `error` or a character to replace them with.
+    /// - `--group-size`, `--wrap`: Groups and line width of the ciphertext, undone before decoding.
+    /// - `--authenticate`: Append a check value keyed by `--key` so decoding can detect wrong keys.
+    /// - `--diff`: Show the message above the result with the changed characters highlighted.
+    /// - `--show-key`: Print the key square of a Polybius-based cipher above the result.
+    /// - `--show-permutation`: Print the ciphertext position of every character of the message
+    ///   above the result, for transposition ciphers.
+    /// - `--group-count`: Report the letter and group counts (`summary`) or put a `GR` check
+    ///   line before the message (`header`).
+    /// - `--layout aligned`: Print the message, its key stream and the result as aligned rows,
+    ///   wrapped at `--width` characters, as LaTeX with `--latex`.
+    #[structopt(
+        name = "encode",
+        about = tr("Encode a message using a cipher"),
+        long_about = tr("Encode a message using a cipher"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
     Encode {
         /// The cipher to use.
-        #[structopt(short, long, help = "The cipher to use (e.g., rot13, caesar)")]
+        #[structopt(short, long, help = tr("The cipher to use (e.g., rot13, caesar)"))]
         cipher: String,
         /// The message to encode.
-        #[structopt(short = "m", long = "message", help = "The message to encode")]
+        #[structopt(short = "m", long = "message", help = tr("The message to encode"))]
         message: Option<String>,
         /// Read the message from a file.
-        #[structopt(short = "f", long = "file", help = "Read the message from a file")]
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
         file: Option<String>,
-        /// Shift value for Caesar cipher.
-        #[structopt(short, long, help = "Shift value for Caesar cipher (default: 3)")]
-        shift: Option<u8>,
-        /// Key to encrypt by.
-        #[structopt(short = "k", long = "key", help = "Key to encrypt by")]
-        key: Option<String>,
+        /// Append a keyed check value to the message.
+        #[structopt(
+            long,
+            help = tr("Append a check value keyed by --key so decoding can detect wrong keys")
+        )]
+        authenticate: bool,
+        /// Report the letter and group counts of the result.
+        #[structopt(
+            long,
+            possible_values = &["summary", "header"],
+            help = tr("Telegram-style counts: summary reports letters and groups after the message, header puts a GR check line before it")
+        )]
+        group_count: Option<String>,
+        /// Print the key square above the result.
+        #[structopt(
+            long,
+            help = tr("Print the key square of the polybius, playfair, adfgx, adfgvx or bifid cipher above the result")
+        )]
+        show_key: bool,
+        /// Print the permutation of a transposition above the result.
+        #[structopt(
+            long,
+            help = tr("Print the ciphertext position, from 0, of every plaintext character above the result (transposition ciphers)")
+        )]
+        show_permutation: bool,
+        /// Show the message above the result with the changes highlighted.
+        #[structopt(
+            long,
+            help = tr("Show the message above the result, highlighting the characters the cipher changed")
+        )]
+        diff: bool,
+        /// Lay out the message, key stream and result as aligned rows.
+        #[structopt(
+            long,
+            possible_values = &["aligned"],
+            help = tr("Print the message, the key letter used for each of its letters and the result as aligned rows")
+        )]
+        layout: Option<String>,
+        /// Width at which the aligned rows wrap.
+        #[structopt(
+            long,
+            default_value = "60",
+            help = tr("Characters per block of --layout aligned")
+        )]
+        width: usize,
+        /// Write the aligned rows as a LaTeX table.
+        #[structopt(long, help = tr("Write --layout aligned as LaTeX tables"))]
+        latex: bool,
+        /// The cipher keys.
+        #[structopt(flatten)]
+        keys: CipherArgs,
     },
     /// Decode a message using a cipher.
     ///
@@ -75,121 +354,2757 @@ enum Command {
     /// - `--message`: The message to decode.
     /// - `--file`: Read the message from a file.
     /// - `--shift`: Shift value for Caesar cipher (default: 3).
+    /// - `--shift-lower`, `--shift-digits`: Separate shifts of lowercase letters and of digits for the Caesar cipher.
+    /// - `--step`: Growth of the shift for the Trithemius and progressive ciphers (default: 1).
+    /// - `--offset`: Start of the zig-zag for the rail fence cipher (default: 0), the row of the Jefferson wheel cipher (default: 1), or the size of the first AMSCO cell (default: 2).
     /// - `--key`: Key to decrypt by.
-    #[structopt(name = "decode", about = "Decode a message using a cipher")]
+    /// - `--key-file`: Read the key text of the running key and book ciphers from a file.
+    /// - `--key-a`, `--key-b`: Multiplier and shift for the Affine cipher.
+    /// - `--table`: CSV or TSV translation table for the table cipher, code table for the nomenclator, or tokens of the token code.
+    /// - `--padding`: Character to fill the last row of a columnar transposition or scytale with, or to split Playfair pairs with.
+    /// - `--labels`: Row and column labels for the Polybius square.
+    /// - `--dot`: Character of a tap for the tap code.
+    /// - `--separators`: Separators of the two groups of a letter and of letters for the tap code.
+    /// - `--case`: Case of the output letters: `preserve`, `upper`, `lower` or `aca`.
+    /// - `--alphabet`: Alphabet of the caesar, vigenere, atbash, affine and substitution ciphers: `latin`, `greek`, `russian`, `turkish` or its letters.
+    /// - `--non-alpha`: Spaces, digits and punctuation before encoding: `preserve`, `strip`, `error` or a character to replace them with.
+    /// - `--group-size`, `--wrap`: Groups and line width of the ciphertext, undone before decoding.
+    /// - `--authenticate`: Verify and remove the check value appended by `encode --authenticate`.
+    /// - `--check`: Warn if the decoded message does not look like language.
+    /// - `--diff`: Show the message above the result with the changed characters highlighted.
+    /// - `--show-key`: Print the key square of a Polybius-based cipher above the result.
+    /// - `--show-permutation`: Print the ciphertext position of every character of the plaintext
+    ///   above the result, for transposition ciphers.
+    ///
+    /// A `GR` group count header line, as written by `encode --group-count header`, is removed
+    /// and checked against the message before decoding.
+    #[structopt(
+        name = "decode",
+        about = tr("Decode a message using a cipher"),
+        long_about = tr("Decode a message using a cipher"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
     Decode {
         /// The cipher to use.
-        #[structopt(short, long, help = "The cipher to use (e.g., rot13, caesar, reverse, gematria)")]
+        #[structopt(
+            short,
+            long,
+            help = tr("The cipher to use (e.g., rot13, caesar, reverse, gematria)")
+        )]
         cipher: String,
         /// The message to decode.
-        #[structopt(short = "m", long = "message", help = "The message to decode")]
+        #[structopt(short = "m", long = "message", help = tr("The message to decode"))]
+        message: Option<String>,
+        /// Read the message from a file.
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
+        file: Option<String>,
+        /// Verify the keyed check value of the message.
+        #[structopt(
+            long,
+            help = tr("Verify and remove the check value appended by encode --authenticate")
+        )]
+        authenticate: bool,
+        /// Warn if the output does not look like language.
+        #[structopt(
+            long,
+            help = tr("Warn if the decoded message does not look like language (wrong key or cipher?)")
+        )]
+        check: bool,
+        /// Print the key square above the result.
+        #[structopt(
+            long,
+            help = tr("Print the key square of the polybius, playfair, adfgx, adfgvx or bifid cipher above the result")
+        )]
+        show_key: bool,
+        /// Print the permutation of a transposition above the result.
+        #[structopt(
+            long,
+            help = tr("Print the ciphertext position, from 0, of every plaintext character above the result (transposition ciphers)")
+        )]
+        show_permutation: bool,
+        /// Show the message above the result with the changes highlighted.
+        #[structopt(
+            long,
+            help = tr("Show the message above the result, highlighting the characters the cipher changed")
+        )]
+        diff: bool,
+        /// The cipher keys.
+        #[structopt(flatten)]
+        keys: CipherArgs,
+    },
+    /// Run a message through a chain of ciphers.
+    ///
+    /// # Options
+    ///
+    /// - `--stage`: A cipher and its options, e.g. `"vigenere --key LEMON"`. Repeat for every stage.
+    /// - `--decode`: Undo the chain: the stages run in reverse order, each decoding.
+    /// - `--simplify`: Merge Caesar shifts and cancel stages that undo each other before running,
+    ///   printing the effective chain to stderr.
+    /// - `--message`: The message to run through the chain.
+    /// - `--file`: Read the message from a file.
+    #[structopt(
+        name = "chain",
+        about = tr("Run a message through a chain of ciphers"),
+        long_about = tr("Run a message through a chain of ciphers"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Chain {
+        /// The stages of the chain, in encoding order.
+        #[structopt(
+            long = "stage",
+            required = true,
+            help = tr("A cipher and its options, e.g. \"caesar --shift 3\"; repeat for every stage")
+        )]
+        stages: Vec<String>,
+        /// Undo the chain instead of running it.
+        #[structopt(long, help = tr("Decode by running the inverse of the chain"))]
+        decode: bool,
+        /// Simplify the chain before running it.
+        #[structopt(
+            long,
+            help = tr("Merge Caesar shifts and cancel stages that undo each other, printing the effective chain to stderr")
+        )]
+        simplify: bool,
+        /// The message to run through the chain.
+        #[structopt(
+            short = "m",
+            long = "message",
+            help = tr("The message to run through the chain")
+        )]
+        message: Option<String>,
+        /// Read the message from a file.
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
+        file: Option<String>,
+    },
+    /// Analyze a message or keystream.
+    ///
+    /// # Options
+    ///
+    /// - `--randomness`: Run the frequency, runs and poker tests over a keystream.
+    /// - `--corpus`: Analyze every file of a directory together and print a JSON report.
+    /// - `--message`: The message to analyze.
+    /// - `--file`: Read the message from a file.
+    #[structopt(
+        name = "analyze",
+        about = tr("Analyze a message or keystream"),
+        long_about = tr("Analyze a message or keystream"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Analyze {
+        /// Run statistical randomness tests.
+        #[structopt(
+            long,
+            help = tr("Run frequency, runs and poker tests over a keystream (bits, digits, letters or binary file)")
+        )]
+        randomness: bool,
+        /// Analyze a directory of ciphertext files together.
+        #[structopt(
+            long,
+            help = tr("Analyze every file of a directory together and print a JSON report")
+        )]
+        corpus: Option<String>,
+        /// The message to analyze.
+        #[structopt(short = "m", long = "message", help = tr("The message to analyze"))]
         message: Option<String>,
         /// Read the message from a file.
-        #[structopt(short = "f", long = "file", help = "Read the message from a file")]
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
         file: Option<String>,
-        /// Shift value for Caesar cipher.
-        #[structopt(short, long, help = "Shift value for Caesar cipher (default: 3)")]
-        shift: Option<u8>,
-        /// Key to decrypt by.
-        #[structopt(short = "k", long = "key", help = "Key to decrypt by")]
-        key: Option<String>,
+    },
+    /// Hash a message or check candidate plaintexts against a published digest.
+    ///
+    /// # Options
+    ///
+    /// - `--algorithm`: md5, sha1 or sha256 (default: all three, or the one matching `--verify`).
+    /// - `--verify`: A hex digest to check every line of the message against.
+    /// - `--message`: The message to hash.
+    /// - `--file`: Read the message from a file.
+    ///
+    /// Requires the `hash` feature.
+    #[structopt(
+        name = "hash",
+        about = tr("Hash a message or check candidates against a digest"),
+        long_about = tr("Hash a message or check candidates against a digest"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Hash {
+        /// The hash algorithm.
+        #[structopt(long, help = tr("The hash algorithm: md5, sha1 or sha256"))]
+        algorithm: Option<String>,
+        /// The digest to check the candidates against.
+        #[structopt(long, help = tr("Check every line of the message against this hex digest"))]
+        verify: Option<String>,
+        /// The message to hash.
+        #[structopt(short = "m", long = "message", help = tr("The message to hash"))]
+        message: Option<String>,
+        /// Read the message from a file.
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
+        file: Option<String>,
+    },
+    /// Crack a ciphertext by trying every key.
+    ///
+    /// # Options
+    ///
+    /// - `--cipher`: The cipher to crack (caesar, decimation, or with `--assume-prefix` also vigenere, enigma, enigma-plugboard, columnar, rail-fence or scytale).
+    /// - `--known-plaintext`: The whole answer; stop at the first key that produces it.
+    /// - `--known-contains`: A word of the answer; stop at the first key whose plaintext has it.
+    /// - `--assume-prefix`: A stereotyped opening of the answer, such as WETTERBERICHT; can be given several times. `enigma` assumes an empty plugboard and the middle ring at A; `enigma-plugboard` searches the plugboard but needs an opening that closes three loops.
+    /// - `--top`: How many of the best candidates to print without a crib (default: 5).
+    /// - `--beam-width`: Clean up the plaintexts with a beam search keeping this many readings.
+    /// - `--model`: Text file to count the beam search's trigram model from, instead of the built-in English.
+    /// - `--max-time`: Stop searching after this many milliseconds and print the best keys found so far.
+    /// - `--message`: The ciphertext to crack.
+    /// - `--file`: Read the ciphertext from a file.
+    #[structopt(
+        name = "crack",
+        about = tr("Crack a ciphertext by trying every key"),
+        long_about = tr("Crack a ciphertext by trying every key"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Crack {
+        /// The cipher to crack.
+        #[structopt(
+            short = "c",
+            long = "cipher",
+            help = tr("The cipher to crack: caesar or decimation, or with --assume-prefix also vigenere, enigma, enigma-plugboard, columnar, rail-fence or scytale")
+        )]
+        cipher: String,
+        /// The whole known answer.
+        #[structopt(
+            long,
+            conflicts_with = "known-contains",
+            help = tr("Stop at the first key that produces this plaintext")
+        )]
+        known_plaintext: Option<String>,
+        /// A word known to be in the answer.
+        #[structopt(
+            long,
+            help = tr("Stop at the first key whose plaintext contains this word")
+        )]
+        known_contains: Option<String>,
+        /// Stereotyped openings assumed for the answer.
+        #[structopt(
+            long,
+            number_of_values = 1,
+            help = tr("Only keep keys whose plaintext opens with this text, e.g. WETTERBERICHT, DEAR or \"ATTACK AT\"; can be given several times. For enigma the plugboard is taken as empty and the middle ring as A, so its double step may come at the wrong letter; enigma-plugboard searches the plugboard like the Bombe, but only from an opening that closes three loops and without the middle rotor stepping within it")
+        )]
+        assume_prefix: Vec<String>,
+        /// How many candidates to print.
+        #[structopt(
+            long,
+            default_value = "5",
+            help = tr("How many of the best candidates to print without a crib")
+        )]
+        top: usize,
+        /// Width of the beam search cleanup.
+        #[structopt(
+            long,
+            help = tr("Clean up the plaintexts with a beam search over a letter n-gram model keeping this many readings")
+        )]
+        beam_width: Option<usize>,
+        /// Corpus of the beam search cleanup's model.
+        #[structopt(
+            long,
+            help = tr("Text file to count the trigram model of the beam search cleanup from (default: built-in English)")
+        )]
+        model: Option<String>,
+        /// Time limit of the search in milliseconds.
+        #[structopt(
+            long,
+            help = tr("Stop searching after this many milliseconds and print the best keys found so far")
+        )]
+        max_time: Option<u64>,
+        /// The ciphertext to crack.
+        #[structopt(short = "m", long = "message", help = tr("The ciphertext to crack"))]
+        message: Option<String>,
+        /// Read the ciphertext from a file.
+        #[structopt(short = "f", long = "file", help = tr("Read the ciphertext from a file"))]
+        file: Option<String>,
+    },
+    /// Transmit a message as Morse signal timings.
+    ///
+    /// # Options
+    ///
+    /// - `--message`: The message to transmit.
+    /// - `--file`: Read the message from a file.
+    /// - `--wpm`: Speed in words per minute (default: 20).
+    /// - `--serial`: Stream the timings to a serial port (requires the `signal` feature).
+    /// - `--baud`: Baud rate of the serial port (default: 9600).
+    /// - `--mqtt`: Stream the timings to an MQTT broker (requires the `signal` feature).
+    /// - `--topic`: MQTT topic to publish to (default: cipha/morse).
+    #[structopt(
+        name = "transmit",
+        about = tr("Transmit a message as Morse signal timings"),
+        long_about = tr("Transmit a message as Morse signal timings"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Transmit {
+        /// The message to transmit.
+        #[structopt(short = "m", long = "message", help = tr("The message to transmit"))]
+        message: Option<String>,
+        /// Read the message from a file.
+        #[structopt(short = "f", long = "file", help = tr("Read the message from a file"))]
+        file: Option<String>,
+        /// Speed in words per minute.
+        #[structopt(long, default_value = "20", help = tr("Speed in words per minute"))]
+        wpm: u32,
+        /// Serial port to stream the timings to.
+        #[structopt(
+            long,
+            help = tr("Stream the timings to a serial port, e.g. /dev/ttyUSB0 (requires the signal feature)")
+        )]
+        serial: Option<String>,
+        /// Baud rate of the serial port.
+        #[structopt(long, default_value = "9600", help = tr("Baud rate of the serial port"))]
+        baud: u32,
+        /// MQTT broker to stream the timings to.
+        #[structopt(
+            long,
+            help = tr("Stream the timings to an MQTT broker, e.g. 127.0.0.1:1883 (requires the signal feature)")
+        )]
+        mqtt: Option<String>,
+        /// MQTT topic to publish to.
+        #[structopt(long, default_value = "cipha/morse", help = tr("MQTT topic to publish to"))]
+        topic: String,
+    },
+    /// Run as a long-lived server.
+    ///
+    /// # Options
+    ///
+    /// - `--stdio`: Serve JSON-RPC 2.0 requests, one per line, on stdin/stdout.
+    /// - `--http`: Serve a REST API on the given address (requires the `server` feature).
+    #[structopt(
+        name = "serve",
+        about = tr("Run as a long-lived JSON-RPC or HTTP server"),
+        long_about = tr("Run as a long-lived JSON-RPC or HTTP server"),
+        help_message = tr("Prints help information"),
+        version_message = tr("Prints version information")
+    )]
+    Serve {
+        /// Serve requests over stdin/stdout.
+        #[structopt(long, help = tr("Serve JSON-RPC requests, one per line, on stdin/stdout"))]
+        stdio: bool,
+        /// Serve a REST API on the given address.
+        #[structopt(
+            long,
+            help = tr("Serve a REST API on the given address, e.g. 127.0.0.1:8080 (requires the server feature)")
+        )]
+        http: Option<String>,
     },
 }
 
+// Key options shared by the encode and decode subcommands. Not a doc comment, which structopt
+// would show in place of the `about` of those subcommands.
+#[derive(Debug, Default, StructOpt)]
+struct CipherArgs {
+    /// Shift value for Caesar cipher.
+    #[structopt(short, long, help = tr("Shift value for Caesar cipher (default: 3)"))]
+    shift: Option<u8>,
+    /// Shift of lowercase letters for the Caesar cipher.
+    #[structopt(
+        long = "shift-lower",
+        help = tr("Shift lowercase letters by this instead of --shift (caesar)")
+    )]
+    shift_lower: Option<u8>,
+    /// Shift of digits for the Caesar cipher.
+    #[structopt(
+        long = "shift-digits",
+        help = tr("Shift digits by this around 0-9, which are otherwise unchanged (caesar)")
+    )]
+    shift_digits: Option<u8>,
+    /// Growth of the shift for the Trithemius and progressive ciphers.
+    #[structopt(
+        long,
+        help = tr("Growth of the shift per letter (trithemius) or per key period (progressive) (default: 1)")
+    )]
+    step: Option<u8>,
+    /// Start of the zig-zag for the rail fence cipher, row of the Jefferson wheel cipher, or
+    /// size of the first AMSCO cell.
+    #[structopt(
+        long,
+        help = tr("Characters of the zig-zag to skip before the message (rail-fence) (default: 0), rows below the message to read (jefferson) (default: 1), or characters in the first cell, 1 or 2 (amsco) (default: 2)")
+    )]
+    offset: Option<u8>,
+    /// Key to encrypt or decrypt by.
+    #[structopt(short = "k", long = "key", help = tr("Key to encrypt or decrypt by"))]
+    key: Option<String>,
+    /// Key text for the running key and book ciphers.
+    #[structopt(
+        long = "key-file",
+        help = tr("File of key text for the running key and book ciphers, used instead of --key")
+    )]
+    key_file: Option<String>,
+    /// Multiplier for the Affine cipher.
+    #[structopt(
+        long = "key-a",
+        help = tr("Multiplier for the Affine cipher, coprime with 26 (default: 1)")
+    )]
+    key_a: Option<u8>,
+    /// Shift for the Affine cipher.
+    #[structopt(long = "key-b", help = tr("Shift for the Affine cipher (default: 0)"))]
+    key_b: Option<u8>,
+    /// Translation table for the table cipher.
+    #[structopt(
+        long,
+        help = tr("CSV or TSV file of source,code token pairs for the table cipher")
+    )]
+    table: Option<String>,
+    /// Padding character for complete transpositions.
+    #[structopt(
+        long,
+        help = tr("Fill the last row with this character (columnar transposition, scytale, grille) or split pairs with it (playfair)")
+    )]
+    padding: Option<char>,
+    /// Row and column labels for the Polybius square.
+    #[structopt(
+        long,
+        help = tr("Row and column labels for the Polybius square, e.g. ADFGX or 12345/ABCDE")
+    )]
+    labels: Option<String>,
+    /// Character of a tap for the tap code.
+    #[structopt(long, help = tr("Character of a tap for the tap code (default: .)"))]
+    dot: Option<char>,
+    /// Separators for the tap code.
+    #[structopt(
+        long,
+        help = tr("Separators between the two groups of taps of a letter and between letters for the tap code, e.g. \",/ \" (default: \" /  \")")
+    )]
+    separators: Option<String>,
+    /// Rotor order for the Enigma machine, or wheel order for the Jefferson wheel cipher.
+    #[structopt(
+        long,
+        help = tr("Enigma rotors from left to right, e.g. \"I II III\" (default: I II III), or jefferson wheel numbers in order, e.g. \"3 1 2\"")
+    )]
+    rotors: Option<String>,
+    /// Reflector for the Enigma machine.
+    #[structopt(long, help = tr("Enigma reflector, B or C (default: B)"))]
+    reflector: Option<String>,
+    /// Starting rotor positions for the Enigma machine, or wheel positions for the Lorenz machine.
+    #[structopt(
+        long,
+        help = tr("Enigma rotor start positions from left to right, e.g. BLA (default: AAA), or the 12 Lorenz wheel positions from 1 (default: all 1)")
+    )]
+    positions: Option<String>,
+    /// Ring settings for the Enigma machine.
+    #[structopt(
+        long,
+        help = tr("Enigma ring settings as letters or numbers, e.g. BUL or \"02 21 12\" (default: AAA)")
+    )]
+    rings: Option<String>,
+    /// Plugboard pairs for the Enigma machine.
+    #[structopt(long, help = tr("Enigma plugboard pairs, e.g. \"AV BS CG\""))]
+    plugboard: Option<String>,
+    /// Case of the output letters.
+    #[structopt(
+        long,
+        possible_values = &["preserve", "upper", "lower", "aca"],
+        help = tr("Case of the output letters of letter ciphers: preserve, upper, lower, or aca for capital ciphertext and lowercase plaintext (default: preserve)")
+    )]
+    case: Option<String>,
+    /// Alphabet of the letter ciphers.
+    #[structopt(
+        long,
+        help = tr("Alphabet of the caesar, vigenere, atbash, affine and substitution ciphers: latin, greek, russian, turkish or its letters (default: latin)")
+    )]
+    alphabet: Option<String>,
+    /// What to do with characters that are not letters before encoding.
+    #[structopt(
+        long,
+        help = tr("What to do with spaces, digits and punctuation before encoding: preserve, strip, error, or a single character to replace them with (default: preserve)")
+    )]
+    non_alpha: Option<String>,
+    /// Size of the groups the ciphertext is written in.
+    #[structopt(
+        long,
+        help = tr("Write the ciphertext in groups of this many characters separated by spaces, and remove all whitespace before decoding (not for token encodings such as morse)")
+    )]
+    group_size: Option<usize>,
+    /// Width at which the lines of the ciphertext wrap.
+    #[structopt(
+        long,
+        help = tr("Break the lines of the ciphertext at this many characters, between groups with --group-size, and join them before decoding")
+    )]
+    wrap: Option<usize>,
+}
 
 fn main() {
+    // The help is translated while the arguments are parsed, so find the language first
+    let args: Vec<String> = std::env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    i18n::set_lang(Lang::detect(&args));
     let opt = Cli::from_args();
-    // add cmd_type to avoid trying to borrow opt.cmd after its fields have been moved out,
-    let (cipher, message, shift, key, cmd_type) = match opt.cmd {
-        Command::Encode { cipher, message, file, shift, key } => {
-            let msg = get_message(message, file).expect("Could not get message");
-            (cipher, msg, shift, key, "encode")
+    if let Some(lang) = opt.lang {
+        i18n::set_lang(lang);
+    }
+    let encoding = opt.encoding;
+    let error_format = opt.error_format;
+    let layout = opt
+        .cmd
+        .input_file()
+        .and_then(|path| InputLayout::read(path, encoding));
+    let input = opt.transcript.as_ref().and_then(|_| opt.cmd.input());
+    // The status to exit with once the transcript is written, 1 after an error reported as JSON
+    let mut exit_code = 0;
+    let mut report = |error: CipherError| {
+        let (text, code) = error.report(error_format);
+        exit_code = exit_code.max(code);
+        text
+    };
+    let result = match opt.cmd {
+        Command::Encode {
+            cipher,
+            message,
+            file,
+            authenticate,
+            group_count,
+            show_key,
+            show_permutation,
+            diff,
+            layout,
+            width,
+            latex,
+            keys,
+        } => {
+            let msg = get_message(message, file, encoding)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            let square = show_key.then(|| key_square(&cipher, &keys));
+            let permutation =
+                show_permutation.then(|| permutation_row(&cipher, &keys, msg.chars().count()));
+            let key_row = layout.is_some().then(|| key_row(&cipher, &keys, &msg));
+            if let Some(config) = cipher_config(&cipher, &keys) {
+                let estimate = estimate_strength(&config, msg.chars().count());
+                if let Some(warning) = estimate.warning {
+                    eprintln!(
+                        "{}",
+                        tr_format!("Warning: {} ({})", warning, estimate.summary)
+                    );
+                }
+            }
+            let encoded = if authenticate {
+                let key = keys.key.clone().unwrap_or_default();
+                Authenticated::new(ChainStage { cipher, keys }, &key).encipher(&msg)
+            } else {
+                try_encode_message(cipher, msg.clone(), &keys).unwrap_or_else(&mut report)
+            };
+            let encoded = match group_count.as_deref() {
+                Some("header") => add_group_count(&encoded),
+                Some(_) => {
+                    let count = GroupCount::of(&encoded);
+                    tr_format!(
+                        "{}\nLetters: {}\nGroups: {}\n{}",
+                        encoded,
+                        count.letters,
+                        count.groups,
+                        count
+                    )
+                }
+                None => encoded,
+            };
+            let encoded = match key_row {
+                Some(Ok(key_row)) => {
+                    let markup = if latex { Markup::Latex } else { Markup::Text };
+                    let rows = [
+                        (tr("Plaintext"), msg.as_str()),
+                        (tr("Key"), key_row.as_str()),
+                        (tr("Ciphertext"), encoded.as_str()),
+                    ];
+                    aligned_rows(&rows, width, markup)
+                }
+                Some(Err(e)) => {
+                    eprintln!("{}", tr_format!("Warning: {}", e));
+                    encoded
+                }
+                None => encoded,
+            };
+            let encoded = if diff {
+                diff_display(&msg, &encoded, use_color(&opt.output_file, opt.raw))
+            } else {
+                encoded
+            };
+            with_header(square, with_header(permutation, encoded))
+        }
+        Command::Decode {
+            cipher,
+            message,
+            file,
+            authenticate,
+            check,
+            show_key,
+            show_permutation,
+            diff,
+            keys,
+        } => {
+            let msg = get_message(message, file, encoding)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            let square = show_key.then(|| key_square(&cipher, &keys));
+            let permutation = show_permutation.then(|| {
+                let len = strip_group_count(&msg).unwrap_or(&msg).chars().count();
+                permutation_row(&cipher, &keys, len)
+            });
+            let decoded = match strip_group_count(&msg) {
+                Err(e) => report(CipherError::new(ErrorCode::InvalidInput, e)),
+                Ok(body) if authenticate => {
+                    let key = keys.key.clone().unwrap_or_default();
+                    match Authenticated::new(ChainStage { cipher, keys }, &key).decipher(body) {
+                        Ok(text) => text,
+                        Err(e) => report(CipherError::new(ErrorCode::AuthenticationFailed, e)),
+                    }
+                }
+                Ok(body) => {
+                    // Look past the --group-size and --wrap layout, whose errors are reported
+                    // by the decoding; positions then count in the joined ciphertext
+                    let joined = ciphertext_formatter(&cipher, &keys)
+                        .map(|formatter| formatter.unformat(body))
+                        .ok();
+                    let unknown = joined
+                        .as_deref()
+                        .and_then(|joined| unknown_token(&cipher, joined, &keys));
+                    let unknown = unknown.map(|mut unknown| {
+                        // Count from the start of the message, before any group count header
+                        unknown.position += msg.chars().count() - body.chars().count();
+                        CipherError::from(unknown)
+                    });
+                    match (unknown, error_format) {
+                        // An unknown token is an error in JSON, and a warning in text
+                        (Some(error), ErrorFormat::Json) => report(error),
+                        (unknown, _) => {
+                            if let Some(error) = unknown {
+                                eprintln!("{}", tr_format!("Warning: {}", error));
+                            }
+                            try_decode_message(cipher, body.to_string(), &keys)
+                                .unwrap_or_else(&mut report)
+                        }
+                    }
+                }
+            };
+            if check && !looks_like_english(&decoded) {
+                eprintln!(
+                    "{}",
+                    tr_format!(
+                        "Warning: output does not look like language — wrong key or cipher? (score {}, expected at least {})",
+                        format!("{:.2}", english_score(&decoded)),
+                        format!("{:.2}", ENGLISH_SCORE_THRESHOLD)
+                    )
+                );
+            }
+            let decoded = if diff {
+                diff_display(&msg, &decoded, use_color(&opt.output_file, opt.raw))
+            } else {
+                decoded
+            };
+            with_header(square, with_header(permutation, decoded))
+        }
+        Command::Chain {
+            stages,
+            decode,
+            simplify,
+            message,
+            file,
+        } => {
+            let msg = get_message(message, file, encoding)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            let pipeline = chain_pipeline(&stages, simplify).and_then(|pipeline| {
+                if simplify {
+                    eprintln!(
+                        "{}",
+                        tr_format!("Effective chain: {}", effective_chain(&stages)?)
+                    );
+                }
+                Ok(pipeline)
+            });
+            let output = match pipeline {
+                Ok(pipeline) if decode => pipeline.decipher(&msg),
+                Ok(pipeline) => pipeline.encipher(&msg),
+                Err(e) => Err(tr_format!("Invalid stage: {}", e)),
+            };
+            output.unwrap_or_else(|e| report(CipherError::new(ErrorCode::InvalidInput, e)))
+        }
+        Command::Analyze {
+            corpus: Some(directory),
+            ..
+        } => analyze_corpus_directory(&directory, encoding).unwrap_or_else(|e| e),
+        Command::Analyze {
+            randomness,
+            message,
+            file,
+            ..
+        } => {
+            let data = get_bytes(message, file)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            analyze_message(&data, randomness)
+        }
+        Command::Hash {
+            algorithm,
+            verify,
+            message,
+            file,
+        } => {
+            let data = get_bytes(message, file)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            hash_message(&data, algorithm.as_deref(), verify.as_deref()).unwrap_or_else(|e| e)
+        }
+        Command::Crack {
+            cipher,
+            known_plaintext,
+            known_contains,
+            assume_prefix,
+            top,
+            beam_width,
+            model,
+            max_time,
+            message,
+            file,
+        } => {
+            let msg = get_message(message, file, encoding)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            let crib = known_plaintext
+                .map(Crib::Plaintext)
+                .or(known_contains.map(Crib::Contains));
+            match beam_decoder(beam_width, model.as_deref()) {
+                Ok(beam) => crack_message(
+                    &cipher,
+                    &msg,
+                    crib.as_ref(),
+                    &assume_prefix,
+                    top,
+                    &crack_budget(max_time),
+                    beam.as_ref(),
+                ),
+                Err(e) => e,
+            }
+        }
+        Command::Transmit {
+            message,
+            file,
+            wpm,
+            serial,
+            baud,
+            mqtt,
+            topic,
+        } => {
+            let msg = get_message(message, file, encoding)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not get message"), e));
+            let timings = MorseCode::new().timings(&msg, wpm);
+            match (serial, mqtt) {
+                (None, None) => timings
+                    .iter()
+                    .map(signal::timing_event)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                (serial, mqtt) => transmit_timings(&timings, serial, baud, mqtt, &topic),
+            }
         }
-        Command::Decode { cipher, message, file, shift, key } => {
-            let msg = get_message(message, file).expect("Could not get message");
-            (cipher, msg, shift, key, "decode")
+        Command::Serve { stdio, http } => {
+            if stdio {
+                serve::serve_stdio(stdin().lock(), stdout())
+                    .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not serve requests"), e));
+                return;
+            }
+            match http {
+                Some(address) => serve_http(&address),
+                None => tr("No transport selected (e.g. --stdio or --http)").to_string(),
+            }
         }
     };
-    
-    let result = match cmd_type {
-        "encode" => encode_message(cipher, message, shift, key),
-        "decode" => decode_message(cipher, message, shift, key),
-        _ => "Unsupported command".to_string(),
+
+    let result = match opt.render.as_deref() {
+        Some("bigtext") => big_text(&result),
+        _ => result,
     };
+    if let Some(path) = &opt.transcript {
+        let entry = transcript::Entry {
+            time: SystemTime::now(),
+            args: &args,
+            input: input.as_deref(),
+            output: &result,
+        };
+        if let Err(e) = transcript::append(path, &entry) {
+            eprintln!("{}", tr_format!("Warning: {}", e));
+        }
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
 
     let mut output: Box<dyn Write> = if let Some(file_path) = &opt.output_file {
-        Box::new(File::create(file_path).expect("Could not create file"))
+        Box::new(
+            File::create(file_path)
+                .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not create file"), e)),
+        )
     } else {
         Box::new(stdout())
     };
+    let newline = !(opt.raw || opt.no_newline || opt.output_file.is_none() && stdout_is_pipe());
+    let bytes = render_output(
+        result,
+        layout.as_ref(),
+        opt.line_endings,
+        opt.bom == "preserve",
+        newline,
+    );
+    output
+        .write_all(&bytes)
+        .unwrap_or_else(|e| panic!("{}: {:?}", tr("Could not write to output"), e));
+}
+
+/// Lays out the result for writing, ending it with a newline if `newline` is set.
+///
+/// With `--line-endings preserve` the result takes the line endings of the input file and ends
+/// with a newline only if the input did, so an unchanged layout round-trips exactly; without an
+/// input file a newline is appended as usual. `lf` and `crlf` convert every line ending. With
+/// `--bom preserve` the byte order mark of the input file, and with it a UTF-16 encoding, is
+/// written back.
+fn render_output(
+    result: String,
+    layout: Option<&InputLayout>,
+    line_endings: Option<LineEndings>,
+    preserve_bom: bool,
+    newline: bool,
+) -> Vec<u8> {
+    let text = match (line_endings, layout) {
+        (Some(LineEndings::Preserve), Some(layout)) => {
+            let ending = layout.line_ending.unwrap_or(LineEnding::Lf);
+            let mut text = convert_line_endings(&result, ending);
+            if let Some(body) = text.strip_suffix(ending.as_str()) {
+                text.truncate(body.len());
+            }
+            if layout.final_newline {
+                text.push_str(ending.as_str());
+            }
+            text
+        }
+        (Some(LineEndings::Convert(ending)), _) => {
+            let mut text = result;
+            if newline && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            convert_line_endings(&text, ending)
+        }
+        _ if newline => result + "\n",
+        _ => result,
+    };
+    match layout {
+        Some(layout) if preserve_bom && layout.bom => {
+            encode_text(&text, layout.encoding, true).expect("UTF-8 and UTF-16 encode any text")
+        }
+        _ => text.into_bytes(),
+    }
+}
+
+/// Lays out the key square of the cipher for `--show-key`.
+///
+/// # Errors
+///
+/// Returns an error if the cipher has no key square or its key is invalid.
+fn key_square(cipher: &str, keys: &CipherArgs) -> Result<String, String> {
+    let square = match cipher {
+        "polybius" | "polybius6" => polybius_square(cipher, keys)?,
+        "adfgx" | "adfgvx" => adfgvx_cipher(cipher, keys)?.square().clone(),
+        "bifid" => bifid_cipher(keys)?.square().clone(),
+        "playfair" | "playfair6" | "playfair7" => playfair_cipher(cipher, keys)?.square().clone(),
+        _ => return Err(tr_format!("The {} cipher has no key square", cipher)),
+    };
+    Ok(polybius_grid(&square))
+}
+
+/// Lists the ciphertext position of every character of a plaintext of `len` characters for
+/// `--show-permutation`.
+///
+/// # Errors
+///
+/// Returns an error if the cipher is not a transposition or its key is invalid.
+fn permutation_row(cipher: &str, keys: &CipherArgs, len: usize) -> Result<String, String> {
+    let permutation = match cipher {
+        "reverse" => (0..len).rev().collect(),
+        "columnar" => columnar_cipher(keys)?.permutation(len),
+        "amsco" => amsco_cipher(keys)?.permutation(len),
+        "double-columnar" => double_transposition_cipher(keys)?.permutation(len),
+        "interleave" => interleave_cipher(keys)?.permutation(len),
+        "decimation" => decimation_cipher(keys)?.permutation(len)?,
+        "boustrophedon" => BoustrophedonCipher::new(numeric_key(keys, "Width")?)?.permutation(len),
+        "scytale" => scytale_cipher(keys)?.permutation(len),
+        "rail-fence" => rail_fence_cipher(keys)?.permutation(len),
+        "caesar-box" => caesar_box_cipher(keys)?.permutation(len),
+        "grille" if keys.key.is_some() => grille_cipher(keys)?.permutation(len),
+        "spiral" | "spiral-out" => spiral_cipher(cipher, keys)?.permutation(len),
+        _ => return Err(tr_format!("The {} cipher has no fixed permutation", cipher)),
+    };
+    Ok(permutation
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// Puts the key square or permutation, if one was asked for, above the result, warning on
+/// stderr if there is none to show.
+fn with_header(header: Option<Result<String, String>>, result: String) -> String {
+    match header {
+        Some(Ok(header)) => format!("{}\n\n{}", header, result),
+        Some(Err(e)) => {
+            eprintln!("{}", tr_format!("Warning: {}", e));
+            result
+        }
+        None => result,
+    }
+}
+
+/// Writes the key letter used for each letter of the message under it for `--layout aligned`,
+/// leaving spaces under the characters the cipher passes through.
+///
+/// # Errors
+///
+/// Returns an error if the cipher has no key stream or its key is invalid.
+fn key_row(cipher: &str, keys: &CipherArgs, message: &str) -> Result<String, String> {
+    if keys.alphabet.is_some() {
+        return Err(tr("The key row is only shown for the alphabet A to Z").to_string());
+    }
+    let letter = |shift: u8| (b'A' + shift % 26) as char;
+    let (stream, lower, digits): (Vec<Option<u8>>, Option<u8>, Option<u8>) = match cipher {
+        "rot13" => (vec![Some(13)], None, None),
+        "caesar" => (
+            vec![Some(keys.shift.unwrap_or(3))],
+            keys.shift_lower,
+            keys.shift_digits,
+        ),
+        "vigenere" => {
+            let key = keys.key.as_deref().unwrap_or("");
+            let stream = PolyalphabeticCipher::vigenere(key).key_stream_for(message);
+            (stream, None, None)
+        }
+        "progressive" => (progressive_cipher(keys).key_stream_for(message), None, None),
+        "trithemius" => {
+            let letters = message.chars().filter(char::is_ascii_alphabetic).count();
+            let stream = trithemius_cipher(keys).key_stream(letters);
+            (stream.into_iter().map(Some).collect(), None, None)
+        }
+        "porta" | "porta-historical" => {
+            let key = keys.key.as_deref().unwrap_or("");
+            let stream = PolyalphabeticCipher::porta(key).key_stream_for(message);
+            (stream, None, None)
+        }
+        "running-key" => (
+            running_key_cipher(keys)?.key_stream_for(message)?,
+            None,
+            None,
+        ),
+        _ => {
+            return Err(tr_format!(
+                "The {} cipher has no key stream to align",
+                cipher
+            ))
+        }
+    };
+    // The Caesar and ROT13 shifts repeat over every letter
+    let repeat = matches!(cipher, "caesar" | "rot13");
+    let mut letters = 0;
+    Ok(message
+        .chars()
+        .map(|c| {
+            let key = if c.is_ascii_digit() {
+                digits.map(|shift| (b'0' + shift % 10) as char)
+            } else if c.is_ascii_lowercase() && lower.is_some() {
+                lower.map(letter)
+            } else if c.is_ascii_alphabetic() {
+                let index = if repeat { 0 } else { letters };
+                letters += 1;
+                stream.get(index).map(|k| k.map_or(UNKNOWN, letter))
+            } else {
+                None
+            };
+            key.unwrap_or(' ')
+        })
+        .collect())
+}
+
+/// Returns whether `--diff` may color its output: only onto a terminal, and not with `--raw` or
+/// when `NO_COLOR` is set.
+fn use_color(output_file: &Option<String>, raw: bool) -> bool {
+    use std::io::IsTerminal;
+    output_file.is_none()
+        && !raw
+        && stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Returns whether stdout is a pipe or socket, which gets the result without a trailing newline,
+/// as `printf` would write it, so that tools such as `base64` and `curl` see only the result.
+/// Where pipes cannot be told from files, anything but a terminal counts as a pipe.
+fn stdout_is_pipe() -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd;
+        use std::os::unix::fs::FileTypeExt;
+        stdout()
+            .as_fd()
+            .try_clone_to_owned()
+            .map(File::from)
+            .and_then(|file| file.metadata())
+            .is_ok_and(|metadata| {
+                let file_type = metadata.file_type();
+                file_type.is_fifo() || file_type.is_socket()
+            })
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::IsTerminal;
+        !stdout().is_terminal()
+    }
+}
+
+/// Lays out the input of a cipher above its output for `--diff`, line by line and character by
+/// character.
+///
+/// Characters the cipher left in place are dimmed and changed ones highlighted. Without color a
+/// row of carets under each pair of lines marks the changed columns instead.
+fn diff_display(input: &str, output: &str, color: bool) -> String {
+    const DIM: &str = "\x1b[0;2m";
+    const HIGHLIGHT: &str = "\x1b[0;1;31m";
+    const RESET: &str = "\x1b[0m";
+    let input_lines: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+    let output_lines: Vec<Vec<char>> = output.lines().map(|l| l.chars().collect()).collect();
+    let empty = Vec::new();
+    let mut rows = Vec::new();
+    for i in 0..input_lines.len().max(output_lines.len()) {
+        let before = input_lines.get(i).unwrap_or(&empty);
+        let after = output_lines.get(i).unwrap_or(&empty);
+        let changed = |column: usize| before.get(column) != after.get(column);
+        if color {
+            for line in [before, after] {
+                let mut row = String::new();
+                let mut highlighted = None;
+                for (column, &c) in line.iter().enumerate() {
+                    if highlighted != Some(changed(column)) {
+                        highlighted = Some(changed(column));
+                        row.push_str(if changed(column) { HIGHLIGHT } else { DIM });
+                    }
+                    row.push(c);
+                }
+                if !line.is_empty() {
+                    row.push_str(RESET);
+                }
+                rows.push(row);
+            }
+        } else {
+            rows.push(before.iter().collect());
+            rows.push(after.iter().collect());
+            let width = before.len().max(after.len());
+            let marks: String = (0..width)
+                .map(|column| if changed(column) { '^' } else { ' ' })
+                .collect();
+            rows.push(marks.trim_end().to_string());
+        }
+    }
+    rows.join("\n")
+}
+
+/// One of the named ciphers of `encode` and `decode` with its options, used as a chain stage
+/// or wrapped for authentication.
+struct ChainStage {
+    cipher: String,
+    keys: CipherArgs,
+}
+
+impl Cipher for ChainStage {
+    fn encipher(&self, plaintext: &str) -> String {
+        encode_message(self.cipher.clone(), plaintext.to_string(), &self.keys)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        decode_message(self.cipher.clone(), ciphertext.to_string(), &self.keys)
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        // A case, non-alphabetic or layout policy is a step of its own, which the simplifier
+        // cannot merge
+        if self.keys.case.is_some()
+            || self.keys.non_alpha.is_some()
+            || self.keys.group_size.is_some()
+            || self.keys.wrap.is_some()
+        {
+            return None;
+        }
+        match self.cipher.as_str() {
+            "rot13" => Some(Elementary::Shift(13)),
+            "caesar" => caesar_cipher(&self.keys).ok()?.elementary(),
+            "atbash" => atbash(&self.keys).ok()?.elementary(),
+            "reverse" => Some(Elementary::Reverse),
+            "reverse-words" => Some(Elementary::ReverseWords),
+            "reverse-letters" => Some(Elementary::ReverseLetters),
+            _ => None,
+        }
+    }
+}
+
+/// A chain stage run through the fallible encoding and decoding, so that a stage that fails
+/// stops the chain with its error.
+struct FallibleStage(ChainStage);
+
+impl FallibleCipher for FallibleStage {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        let ChainStage { cipher, keys } = &self.0;
+        try_encode_message(cipher.clone(), plaintext.to_string(), keys).map_err(|e| e.to_string())
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let ChainStage { cipher, keys } = &self.0;
+        try_decode_message(cipher.clone(), ciphertext.to_string(), keys).map_err(|e| e.to_string())
+    }
+}
+
+/// Builds the pipeline for the `chain` subcommand from its `--stage` specifications, with
+/// adjacent elementary stages combined if `simplify` is set.
+///
+/// Each specification is a cipher name followed by the same key options as `encode`, separated
+/// by whitespace, e.g. `"adfgx --key KEYWORD,CARGO"`.
+fn chain_pipeline(stages: &[String], simplify: bool) -> Result<Pipeline, String> {
+    let stages = stages
+        .iter()
+        .map(|stage| chain_stage(stage))
+        .collect::<Result<Vec<_>, String>>()?;
+    let stages = if simplify {
+        simplify_stages(stages, |stage| stage.elementary())
+    } else {
+        stages.into_iter().map(Simplified::Stage).collect()
+    };
+    let mut pipeline = Pipeline::new();
+    for stage in stages {
+        match stage {
+            Simplified::Stage(stage) => pipeline.push_fallible(FallibleStage(stage)),
+            Simplified::Elementary(elementary) => pipeline.push(elementary),
+        }
+    }
+    Ok(pipeline)
+}
 
-    writeln!(output, "{}", result).expect("Could not write to output");
+/// Parses one `--stage` specification.
+fn chain_stage(stage: &str) -> Result<ChainStage, String> {
+    let mut words = stage.split_whitespace();
+    let cipher = words
+        .next()
+        .ok_or(tr("Stage must name a cipher"))?
+        .to_string();
+    let keys = CipherArgs::from_iter_safe(std::iter::once("stage").chain(words))
+        .map_err(|e| format!("{}: {}", stage, e.message.lines().next().unwrap_or("")))?;
+    Ok(ChainStage { cipher, keys })
+}
+
+/// Describes what the `chain` stages simplify to, as `--stage` options.
+fn effective_chain(stages: &[String]) -> Result<String, String> {
+    let parsed = stages
+        .iter()
+        .map(|stage| Ok((stage, chain_stage(stage)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let simplified = simplify_stages(parsed, |(_, stage)| stage.elementary());
+    if simplified.is_empty() {
+        return Ok(tr("none, the stages cancel out").to_string());
+    }
+    let specs: Vec<String> = simplified
+        .into_iter()
+        .map(|stage| match stage {
+            Simplified::Stage((spec, _)) => spec.clone(),
+            Simplified::Elementary(Elementary::Shift(13)) => "rot13".to_string(),
+            Simplified::Elementary(Elementary::Shift(shift)) => format!("caesar --shift {}", shift),
+            Simplified::Elementary(Elementary::Atbash) => "atbash".to_string(),
+            Simplified::Elementary(Elementary::Reverse) => "reverse".to_string(),
+            Simplified::Elementary(Elementary::ReverseWords) => "reverse-words".to_string(),
+            Simplified::Elementary(Elementary::ReverseLetters) => "reverse-letters".to_string(),
+        })
+        .map(|spec| format!("--stage {:?}", spec))
+        .collect();
+    Ok(specs.join(" "))
 }
 
 /// Retrieves the message from either the command-line argument or a file.
 ///
+/// Files are decoded with the given encoding, or the detected one if there is none.
+///
 /// # Errors
 ///
-/// Returns an error if neither `--message` nor `--file` is provided.
-fn get_message(message: Option<String>, file: Option<String>) -> Result<String, String> {
+/// Returns an error if neither `--message` nor `--file` is provided, or the file cannot be read
+/// or decoded.
+fn get_message(
+    message: Option<String>,
+    file: Option<String>,
+    encoding: Option<TextEncoding>,
+) -> Result<String, String> {
     match (message, file) {
         (Some(msg), _) => Ok(msg),
         (_, Some(file_path)) => {
-            let mut file = File::open(file_path).map_err(|e| format!("Could not open file: {}", e))?;
-            let mut contents = String::new();
-            file.read_to_string(&mut contents).map_err(|e| format!("Could not read file: {}", e))?;
-            Ok(contents)
+            let bytes =
+                std::fs::read(file_path).map_err(|e| tr_format!("Could not read file: {}", e))?;
+            decode_text(&bytes, encoding).map_err(|e| tr_format!("Could not decode file: {}", e))
+        }
+        (None, None) => Err(tr("Either --message or --file must be provided").to_string()),
+    }
+}
+
+/// Retrieves the raw bytes of the message from either the command-line argument or a file.
+///
+/// Unlike [`get_message`] the file does not need to be valid UTF-8, so binary keystreams can be analyzed.
+///
+/// # Errors
+///
+/// Returns an error if neither `--message` nor `--file` is provided.
+fn get_bytes(message: Option<String>, file: Option<String>) -> Result<Vec<u8>, String> {
+    match (message, file) {
+        (Some(msg), _) => Ok(msg.into_bytes()),
+        (_, Some(file_path)) => {
+            std::fs::read(file_path).map_err(|e| tr_format!("Could not read file: {}", e))
         }
-        (None, None) => Err("Either --message or --file must be provided".to_string()),
+        (None, None) => Err(tr("Either --message or --file must be provided").to_string()),
     }
 }
 
+/// Encodes a message like [`try_encode_message`], with an error in place of the result.
+fn encode_message(cipher: String, message: String, keys: &CipherArgs) -> String {
+    try_encode_message(cipher, message, keys).unwrap_or_else(|e| e.to_string())
+}
 
 /// Encodes a message using the specified cipher.
 ///
 /// # Supported Ciphers
 ///
 /// - `rot13`: ROT13 cipher.
+/// - `rot5`, `rot18`, `rot47`: ROT5 on the digits, ROT13 and ROT5 together, or ROT47 on all printable ASCII.
+/// - `rot-n`: Rotation by `--shift` (default: half the alphabet) around the `--key` alphabet, e.g. Cyrillic or Greek.
 /// - `caesar`: Caesar cipher with the given shift.
 /// - `reverse`: Reverses the message.
 /// - `gematria`: Converts alphabetic characters to their corresponding numerical values.
 /// - `vigenere`: Vigenere cipher with the given key.
-/// - `morse`: Encodes the message into Morse code.
+/// - `trithemius`: Shift growing by `--step` per letter, starting at `--shift` (default: 0).
+/// - `progressive`: Vigenere cipher whose `--key` shifts on by `--step` after every period.
+/// - `running-key`: Vigenere cipher keyed by the text of `--key-file` (or `--key`), at least as long as the message.
+/// - `book`, `book-letters`: Page.line.word or line.word.letter coordinates into the text of `--key-file` (or `--key`).
+/// - `porta`, `porta-historical`: Porta cipher with the `--key`, using the modern or della Porta's original table.
+/// - `morse`: Encodes the message into Morse code, with the letters' codes permuted by the `--key` keyword if given.
 /// - `atbash`:  Atbash cipher the message.
-fn encode_message(cipher: String, message: String, shift: Option<u8>, key: Option<String>) -> String {
-    match cipher.as_str() {
+/// - `affine`: Affine cipher with the given `--key-a` and `--key-b`.
+/// - `table`: Codebook cipher with the translation table read from `--table`.
+/// - `nomenclator`: Numeric codes for the words, syllables and letters of the `--table` (CSV, or JSON if it ends in `.json`).
+/// - `substitution`: Substitution cipher with the `--key` alphabet or keyword.
+/// - `keyed-caesar`: Alphabet mixed with the `--key` keyword and rotated by `--shift` (default: 0).
+/// - `interleave`: Splits the message into `--key` streams of every Nth character.
+/// - `decimation`: Reads every `--key`th character cyclically.
+/// - `primes`, `squares`, `ascii`: Numeric substitution with primes, squares or ASCII codes offset by `--key`.
+/// - `baudot`, `baudot-hex`, `baudot-tape`: ITA2 teleprinter code written as 5-bit binary groups, hex values or punched tape.
+/// - `tap`: Tap code, with taps written as `--dot` and groups and letters separated by `--separators`.
+/// - `cards`, `dominoes`: Letters and digits written as playing cards or double-nine domino tiles.
+/// - `resistor`: Digits written as the color bands of resistors.
+/// - `flags`, `flag-emoji`: Letters and digits written as maritime signal flags, by name or as Unicode regional indicators and keycaps.
+/// - `tokens`: Letters and digits written as the tokens of the `--table`, one character per line.
+/// - `pigpen`: Pigpen cipher written with Unicode look-alike glyphs, or the 26 glyphs in `--key`.
+/// - `pigpen-ascii`: Pigpen cipher drawn as three lines of ASCII art per line of text.
+/// - `polybius`, `polybius6`: 5x5 or 6x6 Polybius square, mixed with the `--key` keyword and labelled by `--labels`.
+/// - `playfair`, `playfair6`, `playfair7`: Playfair cipher over a 5x5, 6x6 or 7x7 square mixed with the `--key` keyword, padded with `--padding` (default: X).
+/// - `adfgx`, `adfgvx`: ADFGX or ADFGVX cipher keyed by the square and transposition keywords in `--key`.
+/// - `bifid`: Bifid cipher keyed by the square keyword and the period in `--key`.
+/// - `enigma`: Enigma machine set up by `--rotors`, `--reflector`, `--positions`, `--rings` and `--plugboard`.
+/// - `jefferson`: Jefferson wheel cipher with the wheel alphabets in `--key`, taken in the `--rotors` order and read `--offset` rows down.
+/// - `lorenz`: Lorenz SZ40 machine with the twelve pin patterns in `--key` and the wheel positions in `--positions`, written in Bletchley teleprinter letters.
+/// - `columnar`: Columnar transposition keyed by `--key`, padded with `--padding` if given.
+/// - `amsco`: Columnar transposition of alternating pairs and single letters keyed by `--key`, the first cell `--offset` characters long.
+/// - `double-columnar`: Double columnar transposition keyed by the two keywords in `--key`.
+/// - `boustrophedon`: Writes rows `--key` wide and reverses every second row.
+/// - `scytale`: Scytale with `--key` letters around the rod, padded with `--padding` if given.
+/// - `rail-fence`: Rail fence with `--key` rails, the zig-zag starting `--offset` places in.
+/// - `caesar-box`: Written in rows of the smallest square, or `--key` characters wide, and read by columns.
+/// - `grille`: Turning grille with the holes marked `X` in `--key`, e.g. `X.../.X.X/..../.X..`.
+/// - `spiral`, `spiral-out`: Writes rows `--key` wide and reads them along an inward or outward spiral.
+/// - `reverse-words`: Reverses the order of the words.
+/// - `roman`: Replaces numbers with Roman numerals.
+/// - `reverse-letters`: Reverses the letters within each word.
+/// - `word-transposition`: Columnar transposition of whole words keyed by `--key`.
+/// - `nulls`: Pads the message with nulls that flatten its letter frequencies, after it or one
+///   after every `--step` letters.
+/// - `gzip`, `zstd`: Compresses the message into base64 (requires the matching feature).
+/// - `gunzip`, `unzstd`: Decompresses a base64 message (requires the matching feature).
+fn try_encode_message(
+    cipher: String,
+    message: String,
+    keys: &CipherArgs,
+) -> Result<String, CipherError> {
+    let policy = non_alpha_policy(keys).map_err(CipherError::invalid_key)?;
+    let message = policy
+        .apply(&message)
+        .map_err(|e| CipherError::new(ErrorCode::InvalidInput, e))?;
+    let output = match cipher.as_str() {
         "rot13" => rot13(message),
-        "caesar" => caesar_cipher(message, shift.unwrap_or(3)),
+        "rot5" => rot5(message),
+        "rot18" => rot18(message),
+        "rot47" => rot47(message),
+        "rot-n" => match rot_n(keys) {
+            Ok(rot_n) => rot_n.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "caesar" => match caesar_cipher(keys) {
+            Ok(caesar) => caesar.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
         "reverse" => reverse_cipher(&message),
         "gematria" => alpha2num(&message),
-        "vigenere" => vigenere_cipher(&message, &key.unwrap_or("".to_string())),
-        "morse" => morse_code_cipher(&message),
-        "atbash" => atbash_cipher(&message),
-        _ => "Unsupported cipher".to_string(),
+        "vigenere" => match vigenere(keys) {
+            Ok(vigenere) => vigenere.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "trithemius" => trithemius_cipher(keys).encipher(&message),
+        "progressive" => progressive_cipher(keys).encipher(&message),
+        "running-key" => {
+            match running_key_cipher(keys).and_then(|running_key| running_key.encipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "book" | "book-letters" => {
+            match book_cipher(&cipher, keys).and_then(|book| book.encipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "porta" | "porta-historical" => porta_cipher(&cipher, keys).encipher(&message),
+        "morse" => match keys.key.as_deref() {
+            None => morse_code_cipher(&message),
+            Some(keyword) => match MorseCode::new().with_keyword(keyword) {
+                Ok(morse) => morse.encode(&message),
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            },
+        },
+        "atbash" => match atbash(keys) {
+            Ok(atbash) => atbash.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "affine" => match affine_cipher(keys) {
+            Ok(affine) => affine.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "table" => match table_cipher(keys) {
+            Ok(table) => table.encipher(&message),
+            Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+        },
+        "nomenclator" => match nomenclator_cipher(keys) {
+            Ok(nomenclator) => nomenclator.encipher(&message),
+            Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+        },
+        "substitution" => match substitution_cipher(keys) {
+            Ok(substitution) => substitution.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "keyed-caesar" => match keyed_caesar_cipher(keys) {
+            Ok(keyed_caesar) => keyed_caesar.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "interleave" => match interleave_cipher(keys) {
+            Ok(interleave) => interleave.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "decimation" => {
+            match decimation_cipher(keys).and_then(|decimation| decimation.encipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "primes" | "squares" | "ascii" => match numeric_substitution(&cipher, keys) {
+            Ok(numeric) => numeric.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "cards" | "dominoes" | "resistor" | "flags" | "flag-emoji" | "tokens" => {
+            match token_code(&cipher, keys) {
+                Ok(code) => code.encipher(&message),
+                Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+            }
+        }
+        "pigpen" => match pigpen_cipher(keys) {
+            Ok(pigpen) => pigpen.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "pigpen-ascii" => PigpenCipher::art(&message),
+        "baudot" | "baudot-hex" | "baudot-tape" => baudot_code(&cipher).encode(&message),
+        "tap" => match tap_code(keys) {
+            Ok(tap) => tap.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "polybius" | "polybius6" => match polybius_square(&cipher, keys) {
+            Ok(square) => square.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "playfair" | "playfair6" | "playfair7" => match playfair_cipher(&cipher, keys) {
+            Ok(playfair) => playfair.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "adfgx" | "adfgvx" => match adfgvx_cipher(&cipher, keys) {
+            Ok(adfgvx) => adfgvx.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "bifid" => match bifid_cipher(keys) {
+            Ok(bifid) => bifid.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "enigma" => match enigma_machine(keys) {
+            Ok(enigma) => enigma.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "jefferson" => match jefferson_wheel(keys) {
+            Ok(wheels) => wheels.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "lorenz" => match lorenz_machine(keys) {
+            Ok(lorenz) => lorenz.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "columnar" => match columnar_cipher(keys) {
+            Ok(columnar) => columnar.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "amsco" => match amsco_cipher(keys) {
+            Ok(amsco) => amsco.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "double-columnar" => match double_transposition_cipher(keys) {
+            Ok(double) => double.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "boustrophedon" => match numeric_key(keys, "Width").and_then(BoustrophedonCipher::new) {
+            Ok(boustrophedon) => boustrophedon.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "scytale" => match scytale_cipher(keys) {
+            Ok(scytale) => scytale.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "rail-fence" => match rail_fence_cipher(keys) {
+            Ok(rail_fence) => rail_fence.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "caesar-box" => match caesar_box_cipher(keys) {
+            Ok(caesar_box) => caesar_box.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "grille" => {
+            let grille = match keys.key {
+                Some(_) => grille_cipher(keys),
+                None => random_grille_cipher(keys, message.chars().count()),
+            };
+            match grille {
+                Ok(grille) => grille.encipher(&message),
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "spiral" | "spiral-out" => match spiral_cipher(&cipher, keys) {
+            Ok(spiral) => spiral.encipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "roman" => romanize_numbers(&message),
+        "reverse-words" => ReverseWordsCipher::new().encipher(&message),
+        "reverse-letters" => ReverseLettersCipher::new().encipher(&message),
+        "word-transposition" => {
+            match WordTranspositionCipher::new(keys.key.as_deref().unwrap_or("")) {
+                Ok(transposition) => transposition.encipher(&message),
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "nulls" => match null_padding(keys) {
+            Ok(padding) => padding.pad(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "gzip" | "gunzip" | "zstd" | "unzstd" => compression_stage(&cipher, &message, false)?,
+        _ => {
+            return Err(CipherError::new(
+                ErrorCode::UnsupportedCipher,
+                tr_format!("Unsupported cipher {}", format!("{:?}", cipher)),
+            ))
+        }
+    };
+    let case = output_case(&cipher, keys, true).map_err(CipherError::invalid_key)?;
+    let formatter = ciphertext_formatter(&cipher, keys).map_err(CipherError::invalid_key)?;
+    Ok(formatter.with_case(case).format(&output))
+}
+
+/// Describes the cipher and key selected on the command line, if the cipher is known.
+fn cipher_config(cipher: &str, keys: &CipherArgs) -> Option<CipherConfig> {
+    match cipher {
+        "rot13" => Some(CipherConfig::Rot13),
+        // The warnings about the shift do not hold when lowercase letters or digits shift apart,
+        // nor the key space for another alphabet than A to Z
+        "caesar"
+            if keys.shift_lower.is_none()
+                && keys.shift_digits.is_none()
+                && keys.alphabet.is_none() =>
+        {
+            Some(CipherConfig::Caesar {
+                shift: keys.shift.unwrap_or(3),
+            })
+        }
+        "reverse" => Some(CipherConfig::Reverse),
+        "gematria" => Some(CipherConfig::Gematria),
+        "vigenere" if keys.alphabet.is_none() => Some(CipherConfig::Vigenere {
+            key: keys.key.clone().unwrap_or_default(),
+        }),
+        // Keyed Morse code is a substitution, not the keyless encoding
+        "morse" if keys.key.is_none() => Some(CipherConfig::Morse),
+        "atbash" => Some(CipherConfig::Atbash),
+        "rail-fence" => Some(CipherConfig::RailFence {
+            rails: numeric_key(keys, "Rails").ok()?,
+        }),
+        _ => None,
+    }
+}
+
+/// Reads the `--alphabet` of the letter ciphers, if one is given.
+fn cipher_alphabet(keys: &CipherArgs) -> Result<Option<Alphabet>, String> {
+    keys.alphabet.as_deref().map(str::parse).transpose()
+}
+
+/// Builds the Caesar cipher from `--shift`, `--shift-lower`, `--shift-digits` and `--alphabet`.
+fn caesar_cipher(keys: &CipherArgs) -> Result<CaesarCipher, String> {
+    let mut caesar = CaesarCipher::new(keys.shift.unwrap_or(3));
+    if let Some(shift) = keys.shift_lower {
+        caesar = caesar.with_lower_shift(shift);
+    }
+    if let Some(shift) = keys.shift_digits {
+        caesar = caesar.with_digit_shift(shift);
+    }
+    if let Some(alphabet) = cipher_alphabet(keys)? {
+        caesar = caesar.with_alphabet(alphabet);
+    }
+    Ok(caesar)
+}
+
+/// Builds the Vigenere cipher from `--key` and `--alphabet`.
+fn vigenere(keys: &CipherArgs) -> Result<VigenereCipher, String> {
+    let vigenere = VigenereCipher::new(keys.key.as_deref().unwrap_or(""));
+    Ok(match cipher_alphabet(keys)? {
+        Some(alphabet) => vigenere.with_alphabet(alphabet)?,
+        None => vigenere,
+    })
+}
+
+/// Builds the Atbash cipher of the `--alphabet`.
+fn atbash(keys: &CipherArgs) -> Result<AtbashCipher, String> {
+    Ok(match cipher_alphabet(keys)? {
+        Some(alphabet) => AtbashCipher::new().with_alphabet(alphabet),
+        None => AtbashCipher::new(),
+    })
+}
+
+/// Builds the rotation around the `--key` alphabet by `--shift`, or by half the alphabet.
+fn rot_n(keys: &CipherArgs) -> Result<RotN, String> {
+    let alphabet = keys.key.as_deref().ok_or(tr("--key must be provided"))?;
+    match keys.shift {
+        Some(shift) => RotN::new(shift.into(), alphabet),
+        None => RotN::reciprocal(alphabet),
+    }
+}
+
+/// Builds the Affine cipher from `--key-a`, `--key-b` and `--alphabet`.
+fn affine_cipher(keys: &CipherArgs) -> Result<AffineCipher, String> {
+    let affine = AffineCipher::new(
+        keys.key_a.unwrap_or(1).into(),
+        keys.key_b.unwrap_or(0).into(),
+    )?;
+    match cipher_alphabet(keys)? {
+        Some(alphabet) => affine.with_alphabet(alphabet),
+        None => Ok(affine),
+    }
+}
+
+/// Builds the Trithemius cipher from `--shift` and `--step`.
+fn trithemius_cipher(keys: &CipherArgs) -> TrithemiusCipher {
+    TrithemiusCipher::new()
+        .with_offset(keys.shift.unwrap_or(0))
+        .with_step(keys.step.unwrap_or(1))
+}
+
+/// Builds the progressive-key Vigenere cipher from `--key` and `--step`.
+fn progressive_cipher(keys: &CipherArgs) -> PolyalphabeticCipher {
+    PolyalphabeticCipher::vigenere(keys.key.as_deref().unwrap_or(""))
+        .with_schedule(KeySchedule::Progressive(keys.step.unwrap_or(1) % 26))
+}
+
+/// Reads the key text of the running key and book ciphers from `--key-file`, or takes it from
+/// `--key` without one.
+fn key_text(keys: &CipherArgs) -> Result<String, String> {
+    match &keys.key_file {
+        Some(path) => {
+            std::fs::read_to_string(path).map_err(|e| tr_format!("Could not read key file: {}", e))
+        }
+        None => Ok(keys.key.clone().unwrap_or_default()),
     }
 }
 
+/// Builds the running key cipher from the key text.
+fn running_key_cipher(keys: &CipherArgs) -> Result<RunningKeyCipher, String> {
+    Ok(RunningKeyCipher::new(&key_text(keys)?))
+}
+
+/// Builds the book cipher from the key text, locating letters rather than words for
+/// `book-letters`.
+fn book_cipher(cipher: &str, keys: &CipherArgs) -> Result<BookCipher, String> {
+    let scheme = if cipher == "book-letters" {
+        BookScheme::LineWordLetter
+    } else {
+        BookScheme::PageLineWord
+    };
+    Ok(BookCipher::new(&key_text(keys)?).with_scheme(scheme))
+}
+
+/// Builds the Porta cipher from `--key`, with della Porta's table for `porta-historical`.
+fn porta_cipher(cipher: &str, keys: &CipherArgs) -> PortaCipher {
+    let table = if cipher == "porta-historical" {
+        PortaTable::Historical
+    } else {
+        PortaTable::Modern
+    };
+    PortaCipher::new(keys.key.as_deref().unwrap_or("")).with_table(table)
+}
+
+/// Builds the substitution cipher from `--key`, read as a full alphabet if it has 26 letters
+/// and as a keyword otherwise, or as many letters as the `--alphabet` has.
+fn substitution_cipher(keys: &CipherArgs) -> Result<SubstitutionCipher, String> {
+    let key = keys.key.as_deref().unwrap_or("");
+    match cipher_alphabet(keys)? {
+        Some(alphabet) if key.chars().count() == alphabet.len() => {
+            SubstitutionCipher::from_alphabet_in(alphabet, key)
+        }
+        Some(alphabet) => SubstitutionCipher::from_keyword_in(alphabet, key),
+        None if key.len() == 26 => SubstitutionCipher::from_alphabet(key),
+        None => SubstitutionCipher::from_keyword(key),
+    }
+}
+
+/// Builds the keyed Caesar cipher from the `--key` keyword and `--shift`.
+fn keyed_caesar_cipher(keys: &CipherArgs) -> Result<KeyedCaesarCipher, String> {
+    KeyedCaesarCipher::new(keys.key.as_deref().unwrap_or(""), keys.shift.unwrap_or(0))
+}
+
+/// Builds the interleave transform with the number of streams given by `--key`.
+fn interleave_cipher(keys: &CipherArgs) -> Result<InterleaveCipher, String> {
+    InterleaveCipher::new(numeric_key(keys, "Number of streams")?)
+}
+
+/// Builds the decimation cipher with the skip given by `--key`.
+fn decimation_cipher(keys: &CipherArgs) -> Result<DecimationCipher, String> {
+    DecimationCipher::new(numeric_key(keys, "Skip")?)
+}
+
+/// Finds the first token of the message that a token-based cipher has no character for, which
+/// deciphering writes as `?` or drops.
+fn unknown_token(cipher: &str, message: &str, keys: &CipherArgs) -> Option<UnknownToken> {
+    match cipher {
+        "primes" | "squares" | "ascii" => numeric_substitution(cipher, keys)
+            .ok()?
+            .unknown_token(message),
+        "cards" | "dominoes" | "resistor" | "flags" | "flag-emoji" | "tokens" => {
+            token_code(cipher, keys).ok()?.unknown_token(message)
+        }
+        "morse" => match keys.key.as_deref() {
+            None => MorseCode::new(),
+            Some(keyword) => MorseCode::new().with_keyword(keyword).ok()?,
+        }
+        .unknown_token(message),
+        _ => None,
+    }
+}
+
+/// Builds the numeric substitution named by the cipher, with the ASCII offset given by `--key`.
+fn numeric_substitution(cipher: &str, keys: &CipherArgs) -> Result<NumericSubstitution, String> {
+    match cipher {
+        "primes" => Ok(NumericSubstitution::primes()),
+        "squares" => Ok(NumericSubstitution::squares()),
+        _ => {
+            let key = keys.key.as_deref().unwrap_or("0");
+            let offset = key.trim().parse().map_err(|_| {
+                tr_format!("Offset must be an integer, found {}", format!("{:?}", key))
+            })?;
+            Ok(NumericSubstitution::ascii(offset))
+        }
+    }
+}
+
+/// Builds the pigpen cipher, with the glyphs of `A` to `Z` in `--key` separated by spaces or
+/// commas if given.
+fn pigpen_cipher(keys: &CipherArgs) -> Result<PigpenCipher, String> {
+    match keys.key.as_deref() {
+        None => Ok(PigpenCipher::new()),
+        Some(key) => PigpenCipher::with_glyphs(
+            &key.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|glyph| !glyph.is_empty())
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+
+/// Builds the ITA2 converter writing codes in binary, in hex for `baudot-hex` or as punched tape
+/// for `baudot-tape`.
+fn baudot_code(cipher: &str) -> BaudotCode {
+    BaudotCode::new().with_format(match cipher {
+        "baudot-hex" => BaudotFormat::Hex,
+        "baudot-tape" => BaudotFormat::Tape,
+        _ => BaudotFormat::Binary,
+    })
+}
+
+/// Builds the tap code, with taps written as `--dot` if given.
+///
+/// `--separators` holds the separator of the two groups of taps of a letter and the separator
+/// of letters, separated by a slash.
+fn tap_code(keys: &CipherArgs) -> Result<TapCode, String> {
+    let mut tap = TapCode::new();
+    if let Some(dot) = keys.dot {
+        tap = tap.with_dot(dot)?;
+    }
+    if let Some(separators) = keys.separators.as_deref() {
+        let (group, letter) = separators.split_once('/').ok_or_else(|| {
+            tr_format!(
+                "Separators must be the group and letter separators separated by '/', found {}",
+                format!("{:?}", separators)
+            )
+        })?;
+        tap = tap.with_separators(group, letter)?;
+    }
+    Ok(tap)
+}
+
+/// Builds the Polybius square, 6x6 for `polybius6`, mixed with the `--key` keyword if given.
+///
+/// `--labels` holds the labels shared by rows and columns, or row and column labels separated
+/// by a slash.
+fn polybius_square(cipher: &str, keys: &CipherArgs) -> Result<PolybiusSquare, String> {
+    let grid = if cipher == "polybius6" {
+        PolybiusGrid::SixBySix
+    } else {
+        PolybiusGrid::FiveByFive
+    };
+    let square = PolybiusSquare::new(grid).with_keyword(keys.key.as_deref().unwrap_or(""));
+    match keys.labels.as_deref() {
+        Some(labels) => {
+            let (rows, columns) = labels.split_once('/').unwrap_or((labels, labels));
+            square.with_labels(rows, columns)
+        }
+        None => Ok(square),
+    }
+}
+
+/// Builds the Playfair cipher, 6x6 for `playfair6` and 7x7 for `playfair7`, from the `--key`
+/// keyword and `--padding`.
+fn playfair_cipher(cipher: &str, keys: &CipherArgs) -> Result<PlayfairCipher, String> {
+    let grid = match cipher {
+        "playfair6" => PolybiusGrid::SixBySix,
+        "playfair7" => PolybiusGrid::SevenBySeven,
+        _ => PolybiusGrid::FiveByFive,
+    };
+    let playfair = PlayfairCipher::new(grid, keys.key.as_deref().unwrap_or(""));
+    match keys.padding {
+        Some(padding) => playfair.with_padding(padding),
+        None => Ok(playfair),
+    }
+}
+
+/// Builds the columnar transposition from the `--key` keyword, complete if `--padding` is given.
+fn columnar_cipher(keys: &CipherArgs) -> Result<ColumnarTranspositionCipher, String> {
+    let columnar = ColumnarTranspositionCipher::new(keys.key.as_deref().unwrap_or(""))?;
+    Ok(match keys.padding {
+        Some(padding) => columnar.with_padding(padding),
+        None => columnar,
+    })
+}
+
+/// Builds the scytale from the diameter in `--key`, complete if `--padding` is given.
+fn scytale_cipher(keys: &CipherArgs) -> Result<ScytaleCipher, String> {
+    let scytale = ScytaleCipher::new(numeric_key(keys, "Diameter")?)?;
+    Ok(match keys.padding {
+        Some(padding) => scytale.with_padding(padding),
+        None => scytale,
+    })
+}
+
+/// Builds the AMSCO cipher from the keyword in `--key` and the first cell size in `--offset`.
+fn amsco_cipher(keys: &CipherArgs) -> Result<AmscoCipher, String> {
+    AmscoCipher::new(keys.key.as_deref().unwrap_or(""))?
+        .with_first_cell(keys.offset.unwrap_or(2).into())
+}
+
+/// Builds the rail fence cipher from the number of rails in `--key` and `--offset`.
+fn rail_fence_cipher(keys: &CipherArgs) -> Result<RailFenceCipher, String> {
+    let rail_fence = RailFenceCipher::new(numeric_key(keys, "Rails")?)?;
+    Ok(rail_fence.with_offset(keys.offset.unwrap_or(0).into()))
+}
+
+/// Builds the Caesar box with rows as wide as `--key`, or square without a key.
+fn caesar_box_cipher(keys: &CipherArgs) -> Result<CaesarBoxCipher, String> {
+    match keys.key {
+        Some(_) => CaesarBoxCipher::new().with_width(numeric_key(keys, "Width")?),
+        None => Ok(CaesarBoxCipher::new()),
+    }
+}
+
+/// Builds the turning grille from the pattern in `--key`, padded with `--padding` if given.
+fn grille_cipher(keys: &CipherArgs) -> Result<GrilleCipher, String> {
+    let grille: Grille = keys.key.as_deref().unwrap_or("").parse()?;
+    Ok(with_grille_padding(GrilleCipher::new(grille), keys))
+}
+
+/// Builds a turning grille with a random pattern large enough for the message, printing the
+/// pattern to stderr so the message can be deciphered.
+fn random_grille_cipher(keys: &CipherArgs, len: usize) -> Result<GrilleCipher, String> {
+    let size = (2..)
+        .step_by(2)
+        .find(|side| side * side >= len)
+        .unwrap_or(2);
+    let grille = Grille::random(size)?;
+    eprintln!("{}", tr_format!("Grille: {}", grille.rows().join("/")));
+    Ok(with_grille_padding(GrilleCipher::new(grille), keys))
+}
+
+/// Fills the last square of a turning grille with `--padding` if given.
+fn with_grille_padding(grille: GrilleCipher, keys: &CipherArgs) -> GrilleCipher {
+    match keys.padding {
+        Some(padding) => grille.with_padding(padding),
+        None => grille,
+    }
+}
+
+/// Builds the double transposition from the two keywords in `--key`.
+fn double_transposition_cipher(keys: &CipherArgs) -> Result<DoubleTranspositionCipher, String> {
+    let (first, second) = keyword_pair(keys)?;
+    DoubleTranspositionCipher::new(first, second)
+}
+
+/// Builds the ADFGVX cipher (ADFGX for `adfgx`) from the square and transposition keywords in `--key`.
+fn adfgvx_cipher(cipher: &str, keys: &CipherArgs) -> Result<AdfgvxCipher, String> {
+    let grid = if cipher == "adfgx" {
+        PolybiusGrid::FiveByFive
+    } else {
+        PolybiusGrid::SixBySix
+    };
+    let (square, transposition) = keyword_pair(keys)?;
+    AdfgvxCipher::new(grid, square, transposition)
+}
+
+/// Builds the bifid cipher from the square keyword and the period in `--key`.
+fn bifid_cipher(keys: &CipherArgs) -> Result<BifidCipher, String> {
+    let (keyword, period) = keyword_pair(keys)?;
+    let period = period.parse().map_err(|_| {
+        tr_format!(
+            "Period must be a positive integer, found {}",
+            format!("{:?}", period)
+        )
+    })?;
+    BifidCipher::new(keyword, period)
+}
+
+/// Splits `--key` into two keywords separated by a comma or space.
+fn keyword_pair(keys: &CipherArgs) -> Result<(&str, &str), String> {
+    let key = keys.key.as_deref().unwrap_or("");
+    match key
+        .split([',', ' '])
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [first, second] => Ok((first, second)),
+        _ => Err(tr_format!(
+            "Expected two keywords separated by a comma, found {}",
+            format!("{:?}", key)
+        )),
+    }
+}
+
+/// Builds the spiral transposition, tracing outwards for `spiral-out`, with the width given by `--key`.
+fn spiral_cipher(cipher: &str, keys: &CipherArgs) -> Result<SpiralCipher, String> {
+    let direction = if cipher == "spiral-out" {
+        SpiralDirection::Outward
+    } else {
+        SpiralDirection::Inward
+    };
+    SpiralCipher::new(numeric_key(keys, "Width")?, direction)
+}
+
+/// Sets up the Enigma machine from the `--rotors`, `--reflector`, `--positions`, `--rings`
+/// and `--plugboard` options.
+fn enigma_machine(keys: &CipherArgs) -> Result<EnigmaMachine, String> {
+    let rotors: Vec<Rotor> = keys
+        .rotors
+        .as_deref()
+        .unwrap_or("I II III")
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|name| !name.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    let rotors: [Rotor; 3] = rotors
+        .try_into()
+        .map_err(|_| tr("--rotors must name three rotors, e.g. \"I II III\"").to_string())?;
+    let reflector = keys.reflector.as_deref().unwrap_or("B").parse()?;
+    EnigmaMachine::new(rotors, reflector)?
+        .with_positions(keys.positions.as_deref().unwrap_or("AAA"))?
+        .with_rings(keys.rings.as_deref().unwrap_or("AAA"))?
+        .with_plugboard(keys.plugboard.as_deref().unwrap_or(""))
+}
+
+/// Sets up the Jefferson wheel cipher from the wheel alphabets in `--key`, the wheel order in
+/// `--rotors` and the row in `--offset`.
+fn jefferson_wheel(keys: &CipherArgs) -> Result<JeffersonWheel, String> {
+    let alphabets: Vec<&str> = keys
+        .key
+        .as_deref()
+        .unwrap_or("")
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|alphabet| !alphabet.is_empty())
+        .collect();
+    let mut wheels = JeffersonWheel::new(&alphabets)?.with_offset(keys.offset.unwrap_or(1))?;
+    if let Some(order) = keys.rotors.as_deref() {
+        let order: Vec<usize> = order
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|number| !number.is_empty())
+            .map(|number| {
+                number.parse().map_err(|_| {
+                    tr_format!(
+                        "Wheel numbers must be positive integers, found {}",
+                        format!("{:?}", number)
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        wheels = wheels.with_order(&order)?;
+    }
+    Ok(wheels)
+}
+
+/// Sets up the Lorenz machine from the twelve pin patterns in `--key` and the wheel positions
+/// in `--positions`.
+fn lorenz_machine(keys: &CipherArgs) -> Result<LorenzMachine, String> {
+    let patterns: Vec<&str> = keys
+        .key
+        .as_deref()
+        .unwrap_or("")
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|pattern| !pattern.is_empty())
+        .collect();
+    let lorenz = LorenzMachine::new(&patterns)?;
+    match keys.positions.as_deref() {
+        Some(positions) => {
+            let positions: Vec<usize> = positions
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|number| !number.is_empty())
+                .map(|number| {
+                    number.parse().map_err(|_| {
+                        tr_format!(
+                            "Wheel positions must be positive integers, found {}",
+                            format!("{:?}", number)
+                        )
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            lorenz.with_positions(&positions)
+        }
+        None => Ok(lorenz),
+    }
+}
+
+/// Parses `--key` as a positive integer, naming it `what` in the error.
+fn numeric_key(keys: &CipherArgs, what: &'static str) -> Result<usize, String> {
+    let key = keys.key.as_deref().unwrap_or("");
+    key.trim().parse().map_err(|_| {
+        tr_format!(
+            "{} must be a positive integer, found {}",
+            tr(what),
+            format!("{:?}", key)
+        )
+    })
+}
+
+/// Loads the table cipher from the `--table` file.
+fn table_cipher(keys: &CipherArgs) -> Result<TableCipher, String> {
+    let path = keys
+        .table
+        .as_deref()
+        .ok_or(tr("--table must be provided"))?;
+    let csv =
+        std::fs::read_to_string(path).map_err(|e| tr_format!("Could not read table: {}", e))?;
+    TableCipher::from_csv(&csv)
+}
+
+/// Builds the token code named by the cipher, or loads it from the `--table` file.
+fn token_code(cipher: &str, keys: &CipherArgs) -> Result<TokenCode, String> {
+    match cipher {
+        "cards" => Ok(TokenCode::playing_cards()),
+        "dominoes" => Ok(TokenCode::dominoes()),
+        "resistor" => Ok(TokenCode::resistor_colors()),
+        "flags" => Ok(TokenCode::signal_flags()),
+        "flag-emoji" => Ok(TokenCode::flag_emoji()),
+        _ => {
+            let path = keys
+                .table
+                .as_deref()
+                .ok_or(tr("--table must be provided"))?;
+            let csv = std::fs::read_to_string(path)
+                .map_err(|e| tr_format!("Could not read table: {}", e))?;
+            TokenCode::from_csv(&csv)
+        }
+    }
+}
+
+/// Loads the nomenclator from the `--table` file: CSV or TSV, or a JSON object of tokens and
+/// their numbers if the file name ends in `.json`.
+fn nomenclator_cipher(keys: &CipherArgs) -> Result<NomenclatorCipher, String> {
+    let path = keys
+        .table
+        .as_deref()
+        .ok_or(tr("--table must be provided"))?;
+    let text =
+        std::fs::read_to_string(path).map_err(|e| tr_format!("Could not read table: {}", e))?;
+    if path.to_ascii_lowercase().ends_with(".json") {
+        serde_json::from_str(&text).map_err(|e| tr_format!("Could not parse table: {}", e))
+    } else {
+        NomenclatorCipher::from_csv(&text)
+    }
+}
+
+/// Decodes a message like [`try_decode_message`], with an error in place of the result.
+fn decode_message(cipher: String, message: String, keys: &CipherArgs) -> String {
+    try_decode_message(cipher, message, keys).unwrap_or_else(|e| e.to_string())
+}
 
 /// Decodes a message using the specified cipher.
 ///
 /// # Supported Ciphers
 ///
 /// - `rot13`: ROT13 cipher.
+/// - `rot5`, `rot18`, `rot47`: ROT5 on the digits, ROT13 and ROT5 together, or ROT47 on all printable ASCII.
+/// - `rot-n`: Rotation by `--shift` (default: half the alphabet) around the `--key` alphabet, e.g. Cyrillic or Greek.
 /// - `caesar`: Caesar cipher with the given shift.
 /// - `reverse`: Reverses the message.
 /// - `gematria`: Converts numerical values back to alphabetic characters.
 /// - `vigenere`: Vigenere cipher with the given key.
-/// - `morse`: Decodes Morse code back to the original message.
+/// - `trithemius`: Shift growing by `--step` per letter, starting at `--shift` (default: 0).
+/// - `progressive`: Vigenere cipher whose `--key` shifts on by `--step` after every period.
+/// - `running-key`: Vigenere cipher keyed by the text of `--key-file` (or `--key`), at least as long as the message.
+/// - `book`, `book-letters`: Page.line.word or line.word.letter coordinates into the text of `--key-file` (or `--key`).
+/// - `porta`, `porta-historical`: Porta cipher with the `--key`, using the modern or della Porta's original table.
+/// - `morse`: Decodes Morse code back to the original message, with the letters' codes permuted by the `--key` keyword if given.
 /// - `atbash`: Decodes Atbash cipher back to the original message.
-fn decode_message(cipher: String, message: String, shift: Option<u8>, key: Option<String>) -> String {
-    match cipher.as_str() {
+/// - `affine`: Affine cipher with the given `--key-a` and `--key-b`.
+/// - `table`: Codebook cipher with the translation table read from `--table`.
+/// - `nomenclator`: Numeric codes for the words, syllables and letters of the `--table` (CSV, or JSON if it ends in `.json`).
+/// - `substitution`: Substitution cipher with the `--key` alphabet or keyword.
+/// - `keyed-caesar`: Alphabet mixed with the `--key` keyword and rotated by `--shift` (default: 0).
+/// - `interleave`: Splits the message into `--key` streams of every Nth character.
+/// - `decimation`: Reads every `--key`th character cyclically.
+/// - `primes`, `squares`, `ascii`: Numeric substitution with primes, squares or ASCII codes offset by `--key`.
+/// - `baudot`, `baudot-hex`, `baudot-tape`: ITA2 teleprinter code written as 5-bit binary groups, hex values or punched tape.
+/// - `tap`: Tap code, with taps written as `--dot` and groups and letters separated by `--separators`.
+/// - `cards`, `dominoes`: Letters and digits written as playing cards or double-nine domino tiles.
+/// - `resistor`: Digits written as the color bands of resistors.
+/// - `flags`, `flag-emoji`: Letters and digits written as maritime signal flags, by name or as Unicode regional indicators and keycaps.
+/// - `tokens`: Letters and digits written as the tokens of the `--table`, one character per line.
+/// - `pigpen`: Pigpen cipher written with Unicode look-alike glyphs, or the 26 glyphs in `--key`.
+/// - `pigpen-ascii`: Pigpen cipher drawn as three lines of ASCII art per line of text.
+/// - `polybius`, `polybius6`: 5x5 or 6x6 Polybius square, mixed with the `--key` keyword and labelled by `--labels`.
+/// - `playfair`, `playfair6`, `playfair7`: Playfair cipher over a 5x5, 6x6 or 7x7 square mixed with the `--key` keyword, padded with `--padding` (default: X).
+/// - `adfgx`, `adfgvx`: ADFGX or ADFGVX cipher keyed by the square and transposition keywords in `--key`.
+/// - `bifid`: Bifid cipher keyed by the square keyword and the period in `--key`.
+/// - `enigma`: Enigma machine set up by `--rotors`, `--reflector`, `--positions`, `--rings` and `--plugboard`.
+/// - `jefferson`: Jefferson wheel cipher with the wheel alphabets in `--key` in the `--rotors` order, reading the row `--offset` rows up, or the most English-like row without `--offset`.
+/// - `lorenz`: Lorenz SZ40 machine with the twelve pin patterns in `--key` and the wheel positions in `--positions`, reading Bletchley teleprinter letters.
+/// - `columnar`: Columnar transposition keyed by `--key`, padded with `--padding` if given.
+/// - `amsco`: Columnar transposition of alternating pairs and single letters keyed by `--key`, the first cell `--offset` characters long.
+/// - `double-columnar`: Double columnar transposition keyed by the two keywords in `--key`.
+/// - `boustrophedon`: Writes rows `--key` wide and reverses every second row.
+/// - `scytale`: Scytale with `--key` letters around the rod, padded with `--padding` if given.
+/// - `rail-fence`: Rail fence with `--key` rails, the zig-zag starting `--offset` places in.
+/// - `caesar-box`: Written in rows of the smallest square, or `--key` characters wide, and read by columns.
+/// - `grille`: Turning grille with the holes marked `X` in `--key`, e.g. `X.../.X.X/..../.X..`.
+/// - `spiral`, `spiral-out`: Writes rows `--key` wide and reads them along an inward or outward spiral.
+/// - `reverse-words`: Reverses the order of the words.
+/// - `roman`: Replaces canonical Roman numerals with numbers.
+/// - `reverse-letters`: Reverses the letters within each word.
+/// - `word-transposition`: Columnar transposition of whole words keyed by `--key`.
+/// - `nulls`: Pads the message with nulls that flatten its letter frequencies, after it or one
+///   after every `--step` letters.
+/// - `gzip`, `zstd`: Compresses the message into base64 (requires the matching feature).
+/// - `gunzip`, `unzstd`: Decompresses a base64 message (requires the matching feature).
+fn try_decode_message(
+    cipher: String,
+    message: String,
+    keys: &CipherArgs,
+) -> Result<String, CipherError> {
+    let message = ciphertext_formatter(&cipher, keys)
+        .map_err(CipherError::invalid_key)?
+        .unformat(&message);
+    let output = match cipher.as_str() {
         "rot13" => rot13(message),
-        "caesar" => caesar_cipher(message, shift.unwrap_or(3) * 25), // Reverse shift for decryption
+        "rot5" => rot5(message),
+        "rot18" => rot18(message),
+        "rot47" => rot47(message),
+        "rot-n" => match rot_n(keys) {
+            Ok(rot_n) => rot_n.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "caesar" => match caesar_cipher(keys) {
+            Ok(caesar) => caesar.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
         "reverse" => reverse_cipher(&message),
         "gematria" => num2alpha(&message),
-        "vigenere" => vigenere_decipher(&message, &key.unwrap_or("".to_string())),
-        "morse" => morse_code_decipher(&message),
-        "atbash" => atbash_decipher(&message),
-        _ => "Unsupported cipher".to_string(),
+        "vigenere" => match vigenere(keys) {
+            Ok(vigenere) => vigenere.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "trithemius" => trithemius_cipher(keys).decipher(&message),
+        "progressive" => progressive_cipher(keys).decipher(&message),
+        "running-key" => {
+            match running_key_cipher(keys).and_then(|running_key| running_key.decipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "book" | "book-letters" => {
+            match book_cipher(&cipher, keys).and_then(|book| book.decipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "porta" | "porta-historical" => porta_cipher(&cipher, keys).decipher(&message),
+        "morse" => match keys.key.as_deref() {
+            None => morse_code_decipher(&message),
+            Some(keyword) => match MorseCode::new().with_keyword(keyword) {
+                Ok(morse) => morse.decode(&message),
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            },
+        },
+        "atbash" => match atbash(keys) {
+            Ok(atbash) => atbash.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "affine" => match affine_cipher(keys) {
+            Ok(affine) => affine.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "table" => match table_cipher(keys) {
+            Ok(table) => table.decipher(&message),
+            Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+        },
+        "nomenclator" => match nomenclator_cipher(keys) {
+            Ok(nomenclator) => nomenclator.decipher(&message),
+            Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+        },
+        "substitution" => match substitution_cipher(keys) {
+            Ok(substitution) => substitution.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "keyed-caesar" => match keyed_caesar_cipher(keys) {
+            Ok(keyed_caesar) => keyed_caesar.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "interleave" => match interleave_cipher(keys) {
+            Ok(interleave) => interleave.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "decimation" => {
+            match decimation_cipher(keys).and_then(|decimation| decimation.decipher(&message)) {
+                Ok(text) => text,
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "primes" | "squares" | "ascii" => match numeric_substitution(&cipher, keys) {
+            Ok(numeric) => numeric.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "cards" | "dominoes" | "resistor" | "flags" | "flag-emoji" | "tokens" => {
+            match token_code(&cipher, keys) {
+                Ok(code) => code.decipher(&message),
+                Err(e) => return Err(CipherError::new(ErrorCode::InvalidTable, e)),
+            }
+        }
+        "pigpen" => match pigpen_cipher(keys) {
+            Ok(pigpen) => pigpen.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "pigpen-ascii" => PigpenCipher::from_art(&message),
+        "baudot" | "baudot-hex" | "baudot-tape" => baudot_code(&cipher).decode(&message),
+        "tap" => match tap_code(keys) {
+            Ok(tap) => tap.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "polybius" | "polybius6" => match polybius_square(&cipher, keys) {
+            Ok(square) => square.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "playfair" | "playfair6" | "playfair7" => match playfair_cipher(&cipher, keys) {
+            Ok(playfair) => playfair.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "adfgx" | "adfgvx" => match adfgvx_cipher(&cipher, keys) {
+            Ok(adfgvx) => adfgvx.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "bifid" => match bifid_cipher(keys) {
+            Ok(bifid) => bifid.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "enigma" => match enigma_machine(keys) {
+            Ok(enigma) => enigma.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "jefferson" => match jefferson_wheel(keys) {
+            Ok(wheels) if keys.offset.is_some() => wheels.decipher(&message),
+            Ok(wheels) => wheels
+                .rows(&message)
+                .into_iter()
+                .next()
+                .map(|row| row.plaintext)
+                .unwrap_or_default(),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "lorenz" => match lorenz_machine(keys) {
+            Ok(lorenz) => lorenz.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "columnar" => match columnar_cipher(keys) {
+            Ok(columnar) => columnar.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "amsco" => match amsco_cipher(keys) {
+            Ok(amsco) => amsco.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "double-columnar" => match double_transposition_cipher(keys) {
+            Ok(double) => double.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "boustrophedon" => match numeric_key(keys, "Width").and_then(BoustrophedonCipher::new) {
+            Ok(boustrophedon) => boustrophedon.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "scytale" => match scytale_cipher(keys) {
+            Ok(scytale) => scytale.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "rail-fence" => match rail_fence_cipher(keys) {
+            Ok(rail_fence) => rail_fence.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "caesar-box" => match caesar_box_cipher(keys) {
+            Ok(caesar_box) => caesar_box.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "grille" => match grille_cipher(keys) {
+            Ok(grille) => grille.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "spiral" | "spiral-out" => match spiral_cipher(&cipher, keys) {
+            Ok(spiral) => spiral.decipher(&message),
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "roman" => parse_roman_numerals(&message, RomanParsing::Strict),
+        "reverse-words" => ReverseWordsCipher::new().decipher(&message),
+        "reverse-letters" => ReverseLettersCipher::new().decipher(&message),
+        "word-transposition" => {
+            match WordTranspositionCipher::new(keys.key.as_deref().unwrap_or("")) {
+                Ok(transposition) => transposition.decipher(&message),
+                Err(e) => return Err(CipherError::invalid_key(e)),
+            }
+        }
+        "nulls" => match null_padding(keys) {
+            Ok(padding) => padding
+                .strip(&message)
+                .map_err(|e| CipherError::new(ErrorCode::InvalidInput, e))?,
+            Err(e) => return Err(CipherError::invalid_key(e)),
+        },
+        "gzip" | "gunzip" | "zstd" | "unzstd" => compression_stage(&cipher, &message, true)?,
+        _ => {
+            return Err(CipherError::new(
+                ErrorCode::UnsupportedCipher,
+                tr_format!("Unsupported cipher {}", format!("{:?}", cipher)),
+            ))
+        }
+    };
+    let case = output_case(&cipher, keys, false).map_err(CipherError::invalid_key)?;
+    Ok(case.apply(&output))
+}
+
+/// Returns the `--case` policy for the result of encoding (`ciphertext`) or decoding.
+///
+/// `aca` writes capital ciphertext and lowercase plaintext, as the American Cryptogram
+/// Association does. The policy is meant for letter ciphers, as with the opt-in
+/// [`Cased`](cipha::ciphers::Cased) wrapper of the library, so the outputs whose case carries
+/// information keep it: the base64 of the compression stages, and the ciphertext of `rot47`,
+/// `rot-n` (whose alphabet may hold both cases), `table` and `nomenclator`.
+fn output_case(cipher: &str, keys: &CipherArgs, ciphertext: bool) -> Result<LetterCase, String> {
+    match keys.case.as_deref() {
+        _ if matches!(cipher, "gzip" | "gunzip" | "zstd" | "unzstd") => Ok(LetterCase::Preserve),
+        _ if ciphertext && matches!(cipher, "rot47" | "rot-n" | "table" | "nomenclator") => {
+            Ok(LetterCase::Preserve)
+        }
+        None => Ok(LetterCase::Preserve),
+        Some(case) if case.eq_ignore_ascii_case("aca") => Ok(if ciphertext {
+            LetterCase::Upper
+        } else {
+            LetterCase::Lower
+        }),
+        Some(case) => case.parse(),
+    }
+}
+
+/// Returns the `--group-size` and `--wrap` layout of the ciphertext.
+///
+/// Grouping drops the spaces between the tokens of the token encodings and the words of the
+/// word transpositions, and wrapping adds line breaks to the drawings of the ciphers written on
+/// several lines, so neither could be undone for them.
+fn ciphertext_formatter(cipher: &str, keys: &CipherArgs) -> Result<Formatter, String> {
+    let tokens = matches!(
+        cipher,
+        "gematria"
+            | "morse"
+            | "primes"
+            | "squares"
+            | "ascii"
+            | "cards"
+            | "dominoes"
+            | "resistor"
+            | "flags"
+            | "flag-emoji"
+            | "tokens"
+            | "table"
+            | "nomenclator"
+            | "book"
+            | "tap"
+            | "reverse-words"
+            | "reverse-letters"
+            | "word-transposition"
+    );
+    let drawn = matches!(cipher, "pigpen-ascii" | "baudot-tape");
+    if keys.group_size.is_some_and(|size| size > 0) && (tokens || drawn) {
+        return Err(tr_format!(
+            "--group-size cannot be used with {}, whose tokens are separated by spaces",
+            cipher
+        ));
+    }
+    if keys.wrap.is_some_and(|width| width > 0) && drawn {
+        return Err(tr_format!(
+            "--wrap cannot be used with {}, which is drawn on several lines",
+            cipher
+        ));
+    }
+    Ok(Formatter::new()
+        .with_groups(keys.group_size.unwrap_or(0))
+        .with_width(keys.wrap.unwrap_or(0)))
+}
+
+/// Returns the `--non-alpha` policy for the message to encode.
+fn non_alpha_policy(keys: &CipherArgs) -> Result<NonAlphaPolicy, String> {
+    keys.non_alpha
+        .as_deref()
+        .map_or(Ok(NonAlphaPolicy::Preserve), str::parse)
+}
+
+/// Analyzes a message with the selected analyses.
+///
+/// # Supported Analyses
+///
+/// - `randomness`: Frequency, runs and poker tests over a keystream.
+fn analyze_message(data: &[u8], randomness: bool) -> String {
+    if randomness {
+        randomness_tests(data).to_string()
+    } else {
+        tr("No analysis selected (e.g. --randomness)").to_string()
+    }
+}
+
+/// Tries every key of the cipher on the ciphertext.
+///
+/// With a crib the search stops at the first candidate that fits it; without one the `top` best
+/// scoring candidates are listed. Assumed openings keep only the candidates that start with one
+/// of them, and make the ciphers whose keyspace is too big to try in full crackable. A beam
+/// decoder cleans up the plaintexts found as a last step. The search and the cleanup spend one
+/// budget, and what was found when it runs out is printed.
+fn crack_message(
+    cipher: &str,
+    ciphertext: &str,
+    crib: Option<&Crib>,
+    prefixes: &[String],
+    top: usize,
+    budget: &Budget,
+    beam: Option<&BeamDecoder>,
+) -> String {
+    let mut tracker = budget.start();
+    let attack = match cipher {
+        "vigenere" => Some(PrefixAttack::Vigenere),
+        "enigma" => Some(PrefixAttack::Enigma),
+        "enigma-plugboard" => Some(PrefixAttack::EnigmaPlugboard),
+        "columnar" => Some(PrefixAttack::Columnar),
+        "rail-fence" => Some(PrefixAttack::RailFence),
+        "scytale" => Some(PrefixAttack::Scytale),
+        _ => None,
+    };
+    if let Some(attack) = attack {
+        if prefixes.is_empty() {
+            return tr_format!(
+                "Cracking {} needs an assumed opening (--assume-prefix)",
+                cipher
+            );
+        }
+        return crack_with_prefixes(ciphertext, attack, prefixes, crib, top, &mut tracker, beam)
+            .unwrap_or_else(|e| e);
+    }
+    let prefixes: Vec<Crib> = prefixes.iter().cloned().map(Crib::Prefix).collect();
+    let candidates: Box<dyn Iterator<Item = Candidate>> = match cipher {
+        "caesar" => Box::new(caesar_candidates_with_tracker(ciphertext, &mut tracker)),
+        "decimation" => Box::new(decimation_candidates_with_tracker(ciphertext, &mut tracker)),
+        _ => {
+            return tr("Unsupported cipher (expected caesar, decimation, vigenere, enigma, enigma-plugboard, columnar, rail-fence or scytale)")
+                .to_string()
+        }
+    };
+    let candidates = candidates.filter(|candidate| {
+        prefixes.is_empty()
+            || prefixes
+                .iter()
+                .any(|prefix| prefix.matches(&candidate.plaintext))
+    });
+    let candidates: Vec<Candidate> = match crib {
+        Some(crib) => match crib.find(candidates) {
+            Some(candidate) => vec![candidate],
+            None => return tr("No key fits the known plaintext").to_string(),
+        },
+        None => {
+            let mut candidates: Vec<Candidate> = candidates.collect();
+            candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+            candidates.truncate(top);
+            candidates
+        }
+    };
+    // The search is over, so the cleanup spends what it left of the budget
+    let mut candidates: Vec<Candidate> = candidates
+        .into_iter()
+        .map(|candidate| match beam {
+            Some(beam) => beam.clean_with_tracker(candidate, &mut tracker),
+            None => candidate,
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+        .iter()
+        .map(|candidate| format!("Key {}: {}", candidate.key, candidate.plaintext))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cracks a cipher whose keys can only be searched under an assumed opening of the message.
+///
+/// The keys that fit any of the openings (and the crib, if given) are listed, `top` best first.
+/// The openings and the cleanup of the plaintexts spend the same budget.
+///
+/// # Errors
+///
+/// Returns an error if no key fits.
+fn crack_with_prefixes(
+    ciphertext: &str,
+    attack: PrefixAttack,
+    prefixes: &[String],
+    crib: Option<&Crib>,
+    top: usize,
+    tracker: &mut BudgetTracker,
+    beam: Option<&BeamDecoder>,
+) -> Result<String, String> {
+    let mut solutions: Vec<PrefixSolution> = prefixes
+        .iter()
+        .flat_map(|opening| prefix::solve_with_tracker(ciphertext, opening, attack, tracker))
+        .filter(|solution| crib.is_none_or(|crib| crib.matches(&solution.plaintext)))
+        .collect();
+    if solutions.is_empty() {
+        return Err(tr("No key fits the assumed opening").to_string());
+    }
+    solutions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut solutions: Vec<PrefixSolution> = solutions
+        .into_iter()
+        .take(top)
+        .map(|solution| match beam {
+            Some(beam) => {
+                let plaintext = beam.decode_with_tracker(&solution.plaintext, tracker);
+                PrefixSolution {
+                    score: english_score(&plaintext),
+                    plaintext,
+                    ..solution
+                }
+            }
+            None => solution,
+        })
+        .collect();
+    solutions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(solutions
+        .iter()
+        .map(|solution| format!("Key {}: {}", solution.key, solution.plaintext))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Returns the budget of `crack`, limited to `max_time` milliseconds if given.
+fn crack_budget(max_time: Option<u64>) -> Budget {
+    match max_time {
+        Some(millis) => Budget::unlimited().with_max_time(Duration::from_millis(millis)),
+        None => Budget::unlimited(),
+    }
+}
+
+/// Builds the beam search cleanup of `crack` if `--beam-width` or `--model` is given.
+///
+/// # Errors
+///
+/// Returns an error if the model file cannot be read or the width is 0.
+fn beam_decoder(width: Option<usize>, model: Option<&str>) -> Result<Option<BeamDecoder>, String> {
+    if width.is_none() && model.is_none() {
+        return Ok(None);
     }
-}
\ No newline at end of file
+    let model = match model {
+        Some(path) => {
+            let corpus = std::fs::read_to_string(path)
+                .map_err(|e| tr_format!("Could not read model: {}", e))?;
+            NgramModel::train(&corpus, 3)?
+        }
+        None => NgramModel::english(),
+    };
+    let beam = BeamDecoder::new(model);
+    match width {
+        Some(width) => beam.with_width(width).map(Some),
+        None => Ok(Some(beam)),
+    }
+}
+
+/// Analyzes every file of a directory as one corpus and reports the results as JSON.
+///
+/// Files are read in name order and decoded like `--file`; subdirectories are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the directory or one of its files cannot be read.
+fn analyze_corpus_directory(
+    directory: &str,
+    encoding: Option<TextEncoding>,
+) -> Result<String, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(directory)
+        .map_err(|e| tr_format!("Could not read directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect();
+    paths.sort();
+    let texts = paths
+        .iter()
+        .map(|path| {
+            std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| decode_text(&bytes, encoding))
+                .map_err(|e| tr_format!("Could not read {}: {}", path.display(), e))
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+    let names: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    let report = analyze_corpus(&texts.iter().map(String::as_str).collect::<Vec<_>>());
+
+    let files: Vec<Value> = names
+        .iter()
+        .zip(&report.statistics)
+        .map(|(name, statistics)| {
+            let (class, period) = match statistics.class {
+                CipherClass::Transposition => ("transposition", None),
+                CipherClass::Monoalphabetic => ("monoalphabetic", None),
+                CipherClass::Polyalphabetic { period } => ("polyalphabetic", period),
+                CipherClass::Undetermined => ("undetermined", None),
+            };
+            json!({
+                "file": name,
+                "letters": statistics.letters,
+                "index_of_coincidence": statistics.index_of_coincidence,
+                "english_coincidence": statistics.english_coincidence,
+                "likely_cipher": class,
+                "period": period,
+            })
+        })
+        .collect();
+    let comparisons: Vec<Value> = report
+        .comparisons
+        .iter()
+        .map(|comparison| {
+            json!({
+                "files": [names[comparison.first], names[comparison.second]],
+                "ic_difference": comparison.ic_difference,
+                "cross_coincidence": comparison.cross_coincidence,
+                "depth_coincidence": comparison.depth_coincidence,
+                "same_ic": comparison.same_ic,
+                "likely_same_key": comparison.likely_same_key,
+                "likely_same_cipher": comparison.likely_same_cipher,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "files": files, "comparisons": comparisons }))
+        .map_err(|e| e.to_string())
+}
+
+/// Builds the null padding stage, interleaving a null after every `--step` letters if given and
+/// appending the nulls otherwise.
+fn null_padding(keys: &CipherArgs) -> Result<NullPadding, String> {
+    match keys.step {
+        Some(step) => NullPadding::interleaved(step.into()),
+        None => Ok(NullPadding::new(NullPlacement::Append)),
+    }
+}
+
+/// Runs a compression stage: `gzip` and `zstd` compress into base64, `gunzip` and `unzstd`
+/// decompress base64, and decoding does the opposite.
+///
+/// # Errors
+///
+/// Returns an error if decompressing fails or the codec was not compiled in.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+fn compression_stage(cipher: &str, message: &str, decode: bool) -> Result<String, CipherError> {
+    let (codec, compressing) = match cipher {
+        #[cfg(feature = "gzip")]
+        "gzip" => (Codec::Gzip, true),
+        #[cfg(feature = "gzip")]
+        "gunzip" => (Codec::Gzip, false),
+        #[cfg(feature = "zstd")]
+        "zstd" => (Codec::Zstd, true),
+        #[cfg(feature = "zstd")]
+        "unzstd" => (Codec::Zstd, false),
+        _ => return Err(missing_compression(cipher)),
+    };
+    let stage = Compress::new(codec);
+    if compressing != decode {
+        Ok(stage.encipher(message))
+    } else {
+        stage
+            .decipher(message)
+            .map_err(|e| CipherError::new(ErrorCode::InvalidInput, e))
+    }
+}
+
+/// Reports that compression was not compiled in.
+#[cfg(not(any(feature = "gzip", feature = "zstd")))]
+fn compression_stage(cipher: &str, _message: &str, _decode: bool) -> Result<String, CipherError> {
+    Err(missing_compression(cipher))
+}
+
+/// Reports the feature a compression stage requires.
+fn missing_compression(cipher: &str) -> CipherError {
+    let feature = if cipher.ends_with("zstd") {
+        "zstd"
+    } else {
+        "gzip"
+    };
+    CipherError::new(
+        ErrorCode::InvalidInput,
+        tr_format!(
+            "The {} stage requires building cipha-cli with the `{}` feature",
+            cipher,
+            feature
+        ),
+    )
+}
+
+/// Hashes the message, or checks every line of it against a hex digest and reports the matches.
+///
+/// # Errors
+///
+/// Returns an error if the algorithm or the digest is invalid.
+#[cfg(feature = "hash")]
+fn hash_message(
+    data: &[u8],
+    algorithm: Option<&str>,
+    verify: Option<&str>,
+) -> Result<String, String> {
+    let algorithm = algorithm.map(str::parse::<HashAlgorithm>).transpose()?;
+    let Some(expected) = verify else {
+        let algorithms = algorithm.map_or(HashAlgorithm::ALL.to_vec(), |a| vec![a]);
+        return Ok(algorithms
+            .into_iter()
+            .map(|a| format!("{}: {}", a, hex_digest(a, data)))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    };
+    let mut matches = Vec::new();
+    for candidate in data.split(|&b| b == b'\n') {
+        let candidate = candidate.strip_suffix(b"\r").unwrap_or(candidate);
+        if verify_digest(candidate, expected, algorithm)? {
+            matches.push(format!("Match: {}", String::from_utf8_lossy(candidate)));
+        }
+    }
+    if matches.is_empty() {
+        Ok(tr("No candidate matches the digest").to_string())
+    } else {
+        Ok(matches.join("\n"))
+    }
+}
+
+/// Reports that hashing was not compiled in.
+#[cfg(not(feature = "hash"))]
+fn hash_message(
+    _data: &[u8],
+    _algorithm: Option<&str>,
+    _verify: Option<&str>,
+) -> Result<String, String> {
+    Err(tr("Hashing requires building cipha-cli with the `hash` feature").to_string())
+}
+
+/// Serves the REST API on the given address.
+#[cfg(feature = "server")]
+fn serve_http(address: &str) -> String {
+    match serve::serve_http(address) {
+        Ok(()) => tr("Server stopped").to_string(),
+        Err(e) => e,
+    }
+}
+
+/// Reports that the HTTP server was not compiled in.
+#[cfg(not(feature = "server"))]
+fn serve_http(_address: &str) -> String {
+    tr("HTTP server support requires building cipha-cli with the `server` feature").to_string()
+}
+
+/// Streams the timings in real time to the serial port or MQTT broker.
+#[cfg(feature = "signal")]
+fn transmit_timings(
+    timings: &[MorseTiming],
+    serial: Option<String>,
+    baud: u32,
+    mqtt: Option<String>,
+    topic: &str,
+) -> String {
+    let sink: Result<Box<dyn signal::SignalSink>, String> = match (serial, mqtt) {
+        (Some(path), _) => signal::SerialSink::open(&path, baud)
+            .map(|s| Box::new(s) as Box<dyn signal::SignalSink>),
+        (_, Some(address)) => signal::MqttSink::connect(&address, topic)
+            .map(|s| Box::new(s) as Box<dyn signal::SignalSink>),
+        (None, None) => Err(tr("Either --serial or --mqtt must be provided").to_string()),
+    };
+    match sink.and_then(|mut sink| {
+        signal::transmit(timings, sink.as_mut())
+            .map_err(|e| tr_format!("Could not transmit: {}", e))
+    }) {
+        Ok(()) => tr_format!("Transmitted {} signal periods", timings.len()),
+        Err(e) => e,
+    }
+}
+
+/// Reports that signal output was not compiled in.
+#[cfg(not(feature = "signal"))]
+fn transmit_timings(
+    _timings: &[MorseTiming],
+    _serial: Option<String>,
+    _baud: u32,
+    _mqtt: Option<String>,
+    _topic: &str,
+) -> String {
+    tr("Serial and MQTT output require building cipha-cli with the `signal` feature").to_string()
+}
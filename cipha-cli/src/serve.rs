@@ -0,0 +1,312 @@
+//! Long-lived server modes that drive the CLI operations.
+//!
+//! With `--stdio` each line read from stdin is one JSON-RPC 2.0 request and each response is
+//! written as one line to stdout. With the `server` feature `--http` exposes the same operations
+//! as REST endpoints.
+//! ```bash
+//! echo '{"jsonrpc":"2.0","id":1,"method":"encode","params":{"cipher":"rot13","message":"Hello"}}' | cipha-cli serve --stdio
+//! ```
+
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+use cipha::analysis::Crib;
+
+use crate::i18n::{tr, tr_format};
+use crate::{
+    analyze_message, beam_decoder, crack_budget, crack_message, try_decode_message,
+    try_encode_message, CipherArgs,
+};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// How long `crack` may search, in milliseconds, when a request gives no `max_time`, so that one
+/// request cannot hold the server forever.
+const DEFAULT_CRACK_MILLIS: u64 = 10_000;
+
+/// Serves JSON-RPC requests read line by line from `input`, writing the responses to `output`.
+///
+/// Blank lines are ignored and the server stops at the end of the input.
+pub fn serve_stdio(input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", handle_request(&line))?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Handles a single JSON-RPC request and returns the response object.
+///
+/// # Supported Methods
+///
+/// - `encode`: params `cipher`, `message` and optionally `shift`, `shift_lower`, `shift_digits`, `key`, `step`, `offset`, `key_a`, `key_b`, `padding`, `labels`, `dot`, `separators`, `case`, `alphabet`, `non_alpha`, `group_size`, `wrap` and the Enigma settings `rotors`, `reflector`, `positions`, `rings` and `plugboard`.
+/// - `decode`: params `cipher`, `message` and optionally `shift`, `shift_lower`, `shift_digits`, `key`, `step`, `offset`, `key_a`, `key_b`, `padding`, `labels`, `dot`, `separators`, `case`, `alphabet`, `non_alpha`, `group_size`, `wrap` and the Enigma settings `rotors`, `reflector`, `positions`, `rings` and `plugboard`.
+/// - `crack`: params `cipher`, `message` and optionally `known_plaintext` or `known_contains`, `assume_prefix` (a string or an array of them), `top` (default: 5), `beam_width` and `max_time` (milliseconds, default: 10000).
+/// - `analyze`: params `message` and optionally `randomness`.
+pub fn handle_request(line: &str) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return error_response(Value::Null, PARSE_ERROR, &tr_format!("Parse error: {}", e))
+        }
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = match request.get("method").and_then(Value::as_str) {
+        Some(method) => method,
+        None => return error_response(id, INVALID_REQUEST, tr("Missing method")),
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    match dispatch(method, &params) {
+        Some(Ok(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Some(Err(message)) => error_response(id, INVALID_PARAMS, &message),
+        None => error_response(
+            id,
+            METHOD_NOT_FOUND,
+            &tr_format!("Method not found: {}", method),
+        ),
+    }
+}
+
+// Runs an operation by name, returning `None` for unknown operations.
+fn dispatch(method: &str, params: &Value) -> Option<Result<String, String>> {
+    let result = match method {
+        "encode" | "decode" => cipher_params(params).and_then(|(cipher, message, keys)| {
+            let result = if method == "encode" {
+                try_encode_message(cipher, message, &keys)
+            } else {
+                try_decode_message(cipher, message, &keys)
+            };
+            result.map_err(|e| e.to_string())
+        }),
+        "crack" => crack_params(params).and_then(|(cipher, message, crib, prefixes, top)| {
+            let beam = beam_decoder(usize_param(params, "beam_width")?, None)?;
+            let max_time = usize_param(params, "max_time")?
+                .map_or(DEFAULT_CRACK_MILLIS, |millis| millis as u64);
+            Ok(crack_message(
+                &cipher,
+                &message,
+                crib.as_ref(),
+                &prefixes,
+                top,
+                &crack_budget(Some(max_time)),
+                beam.as_ref(),
+            ))
+        }),
+        "analyze" => string_param(params, "message").map(|message| {
+            let randomness = params
+                .get("randomness")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            analyze_message(message.as_bytes(), randomness)
+        }),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Serves the REST API on `address` until the process is stopped.
+///
+/// `POST /encode`, `POST /decode`, `POST /crack` and `POST /analyze` take the same JSON params as
+/// the JSON-RPC methods and answer with `{"result": ...}` or `{"error": ...}`.
+#[cfg(feature = "server")]
+pub fn serve_http(address: &str) -> Result<(), String> {
+    let server = tiny_http::Server::http(address)
+        .map_err(|e| tr_format!("Could not listen on {}: {}", address, e))?;
+    let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, response) = if request.as_reader().read_to_string(&mut body).is_err() {
+            (400, json!({ "error": tr("Request body must be UTF-8") }))
+        } else {
+            handle_http(request.method().as_str(), request.url(), &body)
+        };
+        let response = tiny_http::Response::from_string(response.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        // A client hanging up must not stop the server
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+// Handles a single REST call and returns the status code and JSON body.
+#[cfg(feature = "server")]
+fn handle_http(method: &str, url: &str, body: &str) -> (u16, Value) {
+    let operation = url.trim_start_matches('/');
+    if method != "POST" {
+        return (405, json!({ "error": tr("Only POST is supported") }));
+    }
+    let params: Value = match serde_json::from_str(body) {
+        Ok(params) => params,
+        Err(e) => {
+            return (
+                400,
+                json!({ "error": tr_format!("Invalid JSON body: {}", e) }),
+            )
+        }
+    };
+    match dispatch(operation, &params) {
+        Some(Ok(result)) => (200, json!({ "result": result })),
+        Some(Err(message)) => (400, json!({ "error": message })),
+        None => (
+            404,
+            json!({ "error": tr_format!("Unknown endpoint: {}", url) }),
+        ),
+    }
+}
+
+// Extracts the cipher, message and key params shared by encode and decode.
+fn cipher_params(params: &Value) -> Result<(String, String, CipherArgs), String> {
+    let cipher = string_param(params, "cipher")?;
+    let message = string_param(params, "message")?;
+    let keys = CipherArgs {
+        shift: u8_param(params, "shift")?,
+        shift_lower: u8_param(params, "shift_lower")?,
+        shift_digits: u8_param(params, "shift_digits")?,
+        step: u8_param(params, "step")?,
+        offset: u8_param(params, "offset")?,
+        key: optional_string_param(params, "key"),
+        // Key files are on disk too, so clients pass running key and book texts in `key`
+        key_file: None,
+        key_a: u8_param(params, "key_a")?,
+        key_b: u8_param(params, "key_b")?,
+        // Tables are files on disk, which the server does not expose to clients
+        table: None,
+        padding: params
+            .get("padding")
+            .and_then(Value::as_str)
+            .and_then(|p| p.chars().next()),
+        labels: optional_string_param(params, "labels"),
+        dot: params
+            .get("dot")
+            .and_then(Value::as_str)
+            .and_then(|d| d.chars().next()),
+        separators: optional_string_param(params, "separators"),
+        rotors: optional_string_param(params, "rotors"),
+        reflector: optional_string_param(params, "reflector"),
+        positions: optional_string_param(params, "positions"),
+        rings: optional_string_param(params, "rings"),
+        plugboard: optional_string_param(params, "plugboard"),
+        case: optional_string_param(params, "case"),
+        alphabet: optional_string_param(params, "alphabet"),
+        non_alpha: optional_string_param(params, "non_alpha"),
+        group_size: usize_param(params, "group_size")?,
+        wrap: usize_param(params, "wrap")?,
+    };
+    Ok((cipher, message, keys))
+}
+
+// The cipher, ciphertext, crib, assumed openings and number of candidates of a crack.
+type CrackParams = (String, String, Option<Crib>, Vec<String>, usize);
+
+// Extracts the params of crack, as the options of the crack subcommand.
+fn crack_params(params: &Value) -> Result<CrackParams, String> {
+    let cipher = string_param(params, "cipher")?;
+    let message = string_param(params, "message")?;
+    let crib = match (
+        optional_string_param(params, "known_plaintext"),
+        optional_string_param(params, "known_contains"),
+    ) {
+        (Some(_), Some(_)) => {
+            return Err(
+                tr("Params known_plaintext and known_contains cannot be combined").to_string(),
+            )
+        }
+        (Some(plaintext), None) => Some(Crib::Plaintext(plaintext)),
+        (None, contains) => contains.map(Crib::Contains),
+    };
+    let prefixes = match params.get("assume_prefix") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::String(prefix)) => vec![prefix.clone()],
+        Some(Value::Array(prefixes)) => prefixes
+            .iter()
+            .map(|prefix| prefix.as_str().map(str::to_string))
+            .collect::<Option<_>>()
+            .ok_or(tr(
+                "Param assume_prefix must be a string or an array of strings",
+            ))?,
+        Some(_) => {
+            return Err(tr("Param assume_prefix must be a string or an array of strings").into())
+        }
+    };
+    let top = usize_param(params, "top")?.unwrap_or(5);
+    Ok((cipher, message, crib, prefixes, top))
+}
+
+// Extracts an optional string param.
+fn optional_string_param(params: &Value, name: &str) -> Option<String> {
+    params.get(name).and_then(Value::as_str).map(str::to_string)
+}
+
+// Extracts an optional integer param between 0 and 255.
+fn u8_param(params: &Value, name: &str) -> Result<Option<u8>, String> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u8::try_from(v).ok())
+            .map(Some)
+            .ok_or_else(|| tr_format!("Param {} must be an integer between 0 and 255", name)),
+    }
+}
+
+// Extracts an optional non-negative integer param.
+fn usize_param(params: &Value, name: &str) -> Result<Option<usize>, String> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| usize::try_from(v).ok())
+            .map(Some)
+            .ok_or_else(|| tr_format!("Param {} must be a non-negative integer", name)),
+    }
+}
+
+// Extracts a required string param.
+fn string_param(params: &Value, name: &str) -> Result<String, String> {
+    params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| tr_format!("Missing string param: {}", name))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_cipher_error_is_a_bad_request() {
+        let (status, response) = handle_http(
+            "POST",
+            "/decode",
+            r#"{"cipher":"running-key","key":"AB","message":"Hello"}"#,
+        );
+        assert_eq!(status, 400);
+        assert_eq!(
+            response,
+            json!({ "error": "Invalid key: Key text has 2 letters but the message has 5" })
+        );
+
+        let (status, response) = handle_http(
+            "POST",
+            "/encode",
+            r#"{"cipher":"caesar","shift":3,"message":"Hello"}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(response, json!({ "result": "Khoor" }));
+    }
+}
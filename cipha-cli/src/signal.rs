@@ -0,0 +1,220 @@
+//! Streams Morse on/off timings to physical signalling hardware.
+//!
+//! Every period of the signal is sent as a line such as `ON 60` or `OFF 180` (duration in
+//! milliseconds) at the moment it starts, so a lamp or buzzer can simply follow the events.
+//! ```bash
+//! cipha-cli transmit --message "SOS" --wpm 15 --serial /dev/ttyUSB0
+//! cipha-cli transmit --message "SOS" --mqtt 127.0.0.1:1883 --topic cipha/morse
+//! ```
+
+#[cfg(feature = "signal")]
+use crate::i18n::tr_format;
+use cipha::ciphers::MorseTiming;
+#[cfg(feature = "signal")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "signal")]
+use std::net::TcpStream;
+#[cfg(feature = "signal")]
+use std::thread::sleep;
+
+/// A destination for Morse signal events.
+#[cfg(feature = "signal")]
+pub trait SignalSink {
+    /// Sends a single event line, e.g. `ON 60`.
+    fn send(&mut self, event: &str) -> io::Result<()>;
+}
+
+/// Formats a timing as an event line.
+pub fn timing_event(timing: &MorseTiming) -> String {
+    let state = if timing.on { "ON" } else { "OFF" };
+    format!("{} {}", state, timing.duration.as_millis())
+}
+
+/// Sends the timings to the sink in real time, switching the signal off at the end.
+#[cfg(feature = "signal")]
+pub fn transmit(timings: &[MorseTiming], sink: &mut dyn SignalSink) -> io::Result<()> {
+    for timing in timings {
+        sink.send(&timing_event(timing))?;
+        sleep(timing.duration);
+    }
+    sink.send("OFF 0")
+}
+
+/// Writes events as lines to a serial port.
+#[cfg(feature = "signal")]
+pub struct SerialSink {
+    port: Box<dyn serialport::SerialPort>,
+}
+
+#[cfg(feature = "signal")]
+impl SerialSink {
+    /// Opens the serial port at `path` with the given baud rate.
+    pub fn open(path: &str, baud_rate: u32) -> Result<Self, String> {
+        let port = serialport::new(path, baud_rate)
+            .open()
+            .map_err(|e| tr_format!("Could not open serial port {}: {}", path, e))?;
+        Ok(SerialSink { port })
+    }
+}
+
+#[cfg(feature = "signal")]
+impl SignalSink for SerialSink {
+    fn send(&mut self, event: &str) -> io::Result<()> {
+        writeln!(self.port, "{}", event)?;
+        self.port.flush()
+    }
+}
+
+/// Publishes events to an MQTT topic (MQTT 3.1.1, QoS 0).
+#[cfg(feature = "signal")]
+pub struct MqttSink {
+    stream: TcpStream,
+    topic: String,
+}
+
+#[cfg(feature = "signal")]
+impl MqttSink {
+    /// Connects to the broker at `address` (`host:port`) and prepares to publish to `topic`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the topic is longer than the 65535 bytes MQTT allows, or if the broker
+    /// cannot be reached or refuses the connection.
+    pub fn connect(address: &str, topic: &str) -> Result<Self, String> {
+        if topic.len() > u16::MAX as usize {
+            return Err(tr_format!(
+                "MQTT topic is {} bytes long but at most 65535 are allowed",
+                topic.len()
+            ));
+        }
+        let mut stream = TcpStream::connect(address)
+            .map_err(|e| tr_format!("Could not connect to {}: {}", address, e))?;
+
+        stream
+            .write_all(&connect_packet(&format!("cipha-{}", std::process::id())))
+            .map_err(|e| tr_format!("Could not send MQTT CONNECT: {}", e))?;
+
+        let mut connack = [0u8; 4];
+        stream
+            .read_exact(&mut connack)
+            .map_err(|e| tr_format!("Could not read MQTT CONNACK: {}", e))?;
+        if connack[0] != 0x20 || connack[3] != 0 {
+            return Err(tr_format!(
+                "MQTT broker refused the connection (code {})",
+                connack[3]
+            ));
+        }
+
+        Ok(MqttSink {
+            stream,
+            topic: topic.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "signal")]
+impl SignalSink for MqttSink {
+    fn send(&mut self, event: &str) -> io::Result<()> {
+        self.stream.write_all(&publish_packet(&self.topic, event))
+    }
+}
+
+#[cfg(feature = "signal")]
+impl Drop for MqttSink {
+    fn drop(&mut self) {
+        // DISCONNECT, ignoring errors since the broker may already be gone
+        let _ = self.stream.write_all(&[0xE0, 0x00]);
+    }
+}
+
+// Builds a CONNECT packet with a clean session and a keep alive of 60 seconds.
+#[cfg(feature = "signal")]
+fn connect_packet(client_id: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_string(&mut body, "MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(0x02); // clean session
+    body.extend_from_slice(&60u16.to_be_bytes()); // keep alive in seconds
+    push_string(&mut body, client_id);
+    packet(0x10, &body)
+}
+
+// Builds a QoS 0 PUBLISH packet of the event to the topic.
+#[cfg(feature = "signal")]
+fn publish_packet(topic: &str, event: &str) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_string(&mut body, topic);
+    body.extend_from_slice(event.as_bytes());
+    packet(0x30, &body)
+}
+
+// Appends a length-prefixed UTF-8 string as used throughout MQTT. Callers keep it under 64 KiB.
+#[cfg(feature = "signal")]
+fn push_string(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+// Builds an MQTT packet from its header byte and body, encoding the remaining length.
+#[cfg(feature = "signal")]
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+#[cfg(all(test, feature = "signal"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_packet() {
+        let mut expected = vec![0x10, 17, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 60, 0, 5];
+        expected.extend_from_slice(b"cipha");
+        assert_eq!(connect_packet("cipha"), expected);
+    }
+
+    #[test]
+    fn test_publish_packet() {
+        let mut expected = vec![0x30, 16, 0, 9];
+        expected.extend_from_slice(b"cipha/sosON 60");
+        assert_eq!(publish_packet("cipha/sos", "ON 60"), expected);
+    }
+
+    #[test]
+    fn test_remaining_length() {
+        for (length, encoded) in [
+            (0, vec![0x00]),
+            (127, vec![0x7F]),
+            (128, vec![0x80, 0x01]),
+            (16383, vec![0xFF, 0x7F]),
+            (16384, vec![0x80, 0x80, 0x01]),
+        ] {
+            let packet = packet(0x30, &vec![0; length]);
+            assert_eq!(packet[1..1 + encoded.len()], encoded[..]);
+            assert_eq!(packet.len(), 1 + encoded.len() + length);
+        }
+    }
+
+    #[test]
+    fn test_connect_rejects_long_topic() {
+        // Fails before connecting, so no broker is needed
+        let topic = "a".repeat(65536);
+        assert!(MqttSink::connect("127.0.0.1:1", &topic)
+            .err()
+            .unwrap()
+            .contains("65536 bytes"));
+    }
+}
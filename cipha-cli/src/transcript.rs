@@ -0,0 +1,215 @@
+//! The `--transcript` record of a session.
+//!
+//! Every invocation with `--transcript` appends one entry to the file: the time, the command
+//! line with its keys and message left out, a hash of the input and the output. The file is
+//! Markdown, or JSON Lines (one object per line) if its name ends in `.json` or `.jsonl`, so a
+//! session can be replayed, shared or checked later.
+//!
+//! The hash identifies the input without recording it, so two entries can be checked to have
+//! worked on the same message. It is not a secret: a short message can be guessed from it.
+
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n::tr_format;
+
+// Options whose values are key material and are left out of the transcript.
+const SECRET_OPTIONS: &[&str] = &[
+    "-k",
+    "--key",
+    "--key-a",
+    "--key-b",
+    "-s",
+    "--shift",
+    "--shift-lower",
+    "--shift-digits",
+    "--step",
+    "--offset",
+    "--rotors",
+    "--reflector",
+    "--positions",
+    "--rings",
+    "--plugboard",
+];
+
+// Options whose values are the input, which is recorded by its hash instead.
+const MESSAGE_OPTIONS: &[&str] = &["-m", "--message"];
+
+/// One invocation of the CLI.
+pub struct Entry<'a> {
+    /// When the command ran.
+    pub time: SystemTime,
+    /// The command line, starting with the program name.
+    pub args: &'a [String],
+    /// The message or file contents the command read, if any.
+    pub input: Option<&'a [u8]>,
+    /// The output of the command.
+    pub output: &'a str,
+}
+
+/// Appends the entry to the transcript file, creating it if needed.
+pub fn append(path: &str, entry: &Entry) -> Result<(), String> {
+    let record = if path.ends_with(".json") || path.ends_with(".jsonl") {
+        json_record(entry)
+    } else {
+        markdown_record(entry)
+    };
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(record.as_bytes()))
+        .map_err(|e| tr_format!("Could not write transcript: {}", e))
+}
+
+/// Replaces the values of key options with `***` and the message with `<message>`, including
+/// inside `--stage` specifications.
+pub fn redact(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(replacement) = replacement(arg) {
+            redacted.push(arg.clone());
+            if args.next().is_some() {
+                redacted.push(replacement.to_string());
+            }
+        } else if arg == "--stage" {
+            redacted.push(arg.clone());
+            if let Some(stage) = args.next() {
+                let words: Vec<String> = stage.split_whitespace().map(str::to_string).collect();
+                redacted.push(redact(&words).join(" "));
+            }
+        } else if let Some((option, _value)) = arg.split_once('=') {
+            match replacement(option) {
+                Some(replacement) => redacted.push(format!("{}={}", option, replacement)),
+                None => redacted.push(arg.clone()),
+            }
+        } else {
+            // A short option with its value attached, such as -kLEMON
+            let attached = arg
+                .get(..2)
+                .filter(|_| !arg.starts_with("--") && arg.len() > 2);
+            match attached.and_then(|option| Some((option, replacement(option)?))) {
+                Some((option, replacement)) => redacted.push(format!("{}{}", option, replacement)),
+                None => redacted.push(arg.clone()),
+            }
+        }
+    }
+    redacted
+}
+
+// Returns what the value of the option is replaced by, if it is a key or the message.
+fn replacement(option: &str) -> Option<&'static str> {
+    if SECRET_OPTIONS.contains(&option) {
+        Some("***")
+    } else if MESSAGE_OPTIONS.contains(&option) {
+        Some("<message>")
+    } else {
+        None
+    }
+}
+
+// Quotes an argument for a shell if it is empty or has spaces or quotes.
+fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Hashes bytes with 64-bit FNV-1a.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// Formats a time as an ISO 8601 UTC timestamp to the second.
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+    // Converts days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// Writes the program name without its directory, followed by the redacted arguments.
+fn command_line(args: &[String]) -> Vec<String> {
+    let program = args
+        .first()
+        .and_then(|path| std::path::Path::new(path).file_stem())
+        .map_or("cipha-cli".to_string(), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+    std::iter::once(program)
+        .chain(redact(args.get(1..).unwrap_or_default()))
+        .collect()
+}
+
+// Formats the entry as a Markdown section.
+fn markdown_record(entry: &Entry) -> String {
+    let command: Vec<String> = command_line(entry.args).iter().map(|a| quote(a)).collect();
+    let input = match entry.input {
+        Some(input) => format!("{} bytes, FNV-1a `{:016x}`", input.len(), fnv1a(input)),
+        None => "none".to_string(),
+    };
+    // A fence longer than any run of backticks in the output
+    let longest_run = entry
+        .output
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!(
+        "## {}\n\n`{}`\n\n- Input: {}\n- Output:\n\n{}text\n{}\n{}\n\n",
+        timestamp(entry.time),
+        command.join(" "),
+        input,
+        fence,
+        entry.output,
+        fence
+    )
+}
+
+// Formats the entry as one line of JSON.
+fn json_record(entry: &Entry) -> String {
+    let input = entry.input.map(|input| {
+        json!({
+            "bytes": input.len(),
+            "fnv1a": format!("{:016x}", fnv1a(input)),
+        })
+    });
+    let record = json!({
+        "time": timestamp(entry.time),
+        "command": command_line(entry.args),
+        "input": input,
+        "output": entry.output,
+    });
+    format!("{}\n", record)
+}
@@ -1,14 +1,11 @@
 use assert_cmd::Command;
 
-
 #[test]
 fn test_cli_version_args() {
     let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
-    cmd.arg("-V")
-    .assert().success().stdout("cipha-cli 0.1.0\n");
+    cmd.arg("-V").assert().success().stdout("cipha-cli 0.1.0\n");
 }
 
-
 #[test]
 fn test_cli_encode_rot13() {
     let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
@@ -17,7 +14,9 @@ fn test_cli_encode_rot13() {
         .arg("rot13")
         .arg("--message")
         .arg("Hello, World!")
-        .assert().success().stdout("Uryyb, Jbeyq!\n");
+        .assert()
+        .success()
+        .stdout("Uryyb, Jbeyq!");
 }
 
 #[test]
@@ -30,7 +29,9 @@ fn test_cli_encode_caesar() {
         .arg("Hello, World!")
         .arg("--shift")
         .arg("3")
-        .assert().success().stdout("Khoor, Zruog!\n");
+        .assert()
+        .success()
+        .stdout("Khoor, Zruog!");
 }
 
 #[test]
@@ -41,7 +42,9 @@ fn test_cli_decode_rot13() {
         .arg("rot13")
         .arg("--message")
         .arg("Uryyb, Jbeyq!")
-        .assert().success().stdout("Hello, World!\n");
+        .assert()
+        .success()
+        .stdout("Hello, World!");
 }
 
 #[test]
@@ -54,5 +57,2669 @@ fn test_cli_decode_caesar() {
         .arg("Khoor, Zruog!")
         .arg("--shift")
         .arg("3")
-        .assert().success().stdout("Hello, World!\n");
-}
\ No newline at end of file
+        .assert()
+        .success()
+        .stdout("Hello, World!");
+}
+#[test]
+fn test_cli_analyze_randomness() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("analyze")
+        .arg("--randomness")
+        .arg("--message")
+        .arg("01".repeat(500));
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("runs: statistic="));
+    assert!(stdout.contains("Overall: FAIL"));
+}
+
+#[test]
+fn test_cli_serve_stdio() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("serve")
+        .arg("--stdio")
+        .write_stdin(concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"encode","params":{"cipher":"caesar","message":"Hello","shift":3}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"crack","params":{"cipher":"caesar","message":"Phhw ph dw qrrq","top":1}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":3,"method":"crack","params":{"cipher":"vigenere","message":"Oimf Xlvx, hup wtwcxizh ypehsf zr Ycaoek","assume_prefix":["ATTACK AT","DEAR KARL"],"top":1}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":4,"method":"crack","params":{"cipher":"caesar","message":"Phhw","assume_prefix":3}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":5,"method":"unknown","params":{}}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":6,"method":"decode","params":{"cipher":"running-key","key":"AB","message":"Hello"}}"#, "\n",
+            "not json\n",
+        ))
+        .assert().success().stdout(concat!(
+            r#"{"id":1,"jsonrpc":"2.0","result":"Khoor"}"#, "\n",
+            r#"{"id":2,"jsonrpc":"2.0","result":"Key 3: Meet me at noon"}"#, "\n",
+            r#"{"id":3,"jsonrpc":"2.0","result":"Key LEMON: Dear Karl, the shipment leaves on Monday"}"#, "\n",
+            r#"{"error":{"code":-32602,"message":"Param assume_prefix must be a string or an array of strings"},"id":4,"jsonrpc":"2.0"}"#, "\n",
+            r#"{"error":{"code":-32601,"message":"Method not found: unknown"},"id":5,"jsonrpc":"2.0"}"#, "\n",
+            r#"{"error":{"code":-32602,"message":"Invalid key: Key text has 2 letters but the message has 5"},"id":6,"jsonrpc":"2.0"}"#, "\n",
+            r#"{"error":{"code":-32700,"message":"Parse error: expected ident at line 1 column 2"},"id":null,"jsonrpc":"2.0"}"#, "\n",
+        ));
+}
+
+#[cfg(not(feature = "server"))]
+#[test]
+fn test_cli_serve_http_requires_feature() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("serve")
+        .arg("--http")
+        .arg("127.0.0.1:0")
+        .assert()
+        .success()
+        .stdout("HTTP server support requires building cipha-cli with the `server` feature");
+}
+
+#[test]
+fn test_cli_transmit_timings() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("transmit")
+        .arg("--message")
+        .arg("ET")
+        .arg("--wpm")
+        .arg("20")
+        .assert()
+        .success()
+        .stdout("ON 60\nOFF 180\nON 180");
+}
+
+#[test]
+fn test_cli_encode_warns_on_weak_parameters() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--message")
+        .arg("Hello")
+        .arg("--shift")
+        .arg("26")
+        .assert().success()
+        .stdout("Hello")
+        .stderr("Warning: a Caesar shift of 26 leaves the message unchanged (breakable by brute force over 25 shifts with ~5 chars)\n");
+}
+
+#[test]
+fn test_cli_affine_round_trip() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("affine")
+        .arg("--message")
+        .arg("Affine Cipher")
+        .arg("--key-a")
+        .arg("5")
+        .arg("--key-b")
+        .arg("8")
+        .assert()
+        .success()
+        .stdout("Ihhwvc Swfrcp");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("affine")
+        .arg("--message")
+        .arg("Ihhwvc Swfrcp")
+        .arg("--key-a")
+        .arg("5")
+        .arg("--key-b")
+        .arg("8")
+        .assert()
+        .success()
+        .stdout("Affine Cipher");
+}
+
+#[test]
+fn test_cli_trithemius_and_progressive() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("trithemius")
+        .arg("--message")
+        .arg("Hello, World!")
+        .assert()
+        .success()
+        .stdout("Hfnos, Buytm!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("trithemius")
+        .arg("--shift")
+        .arg("3")
+        .arg("--step")
+        .arg("2")
+        .arg("--message")
+        .arg("DFHJ")
+        .assert()
+        .success()
+        .stdout("AAAA");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("progressive")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--message")
+        .arg("ATTACKATDAWN")
+        .assert()
+        .success()
+        .stdout("LXFOPWFGSOJT");
+}
+
+#[test]
+fn test_cli_porta() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("porta")
+        .arg("--key")
+        .arg("FORTIFICATION")
+        .arg("--message")
+        .arg("SYNNJSCVRNRLAHUTU")
+        .assert()
+        .success()
+        .stdout("DEFENDTHEEASTWALL");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("porta-historical")
+        .arg("--key")
+        .arg("C")
+        .arg("--message")
+        .arg("Attack")
+        .assert()
+        .success()
+        .stdout("Zhhzow");
+}
+
+#[test]
+fn test_cli_running_key() {
+    let key_file = std::env::temp_dir().join("cipha_cli_test_running_key.txt");
+    std::fs::write(
+        &key_file,
+        "It was the best of times,\nit was the worst of times.\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("running-key")
+        .arg("--key-file")
+        .arg(&key_file)
+        .arg("--message")
+        .arg("Attack at dawn!")
+        .assert()
+        .success()
+        .stdout("Impaud hx eeog!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("running-key")
+        .arg("--key")
+        .arg("Call me Ishmael")
+        .arg("--message")
+        .arg("Attack at dawn and at dusk")
+        .assert()
+        .success()
+        .stdout("Invalid key: Key text has 13 letters but the message has 21");
+}
+
+#[test]
+fn test_cli_book() {
+    let key_file = std::env::temp_dir().join("cipha_cli_test_book.txt");
+    std::fs::write(
+        &key_file,
+        "It was the best of times,\nit was the worst of times.\n\x0cIt was the age of wisdom.\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("book")
+        .arg("--key-file")
+        .arg(&key_file)
+        .arg("--message")
+        .arg("It was the age")
+        .assert()
+        .success()
+        .stdout("1.1.1 1.1.2 1.1.3 2.1.4");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("book-letters")
+        .arg("--key-file")
+        .arg(&key_file)
+        .arg("--message")
+        .arg("[1,1,2] [1,3,3] [1,2,2]")
+        .assert()
+        .success()
+        .stdout("tea");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("book")
+        .arg("--key-file")
+        .arg(&key_file)
+        .arg("--message")
+        .arg("It was the worst of times")
+        .assert()
+        .success()
+        .stdout("1.1.1 1.1.2 1.1.3 1.2.4 1.1.5 1.1.6");
+}
+
+#[test]
+fn test_cli_lang() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env("LC_ALL", "C")
+        .arg("--lang")
+        .arg("es")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("affine")
+        .arg("--key-a")
+        .arg("2")
+        .arg("--message")
+        .arg("Hola")
+        .assert()
+        .success()
+        .stdout("Clave no válida: Key a = 2 must be coprime with 26");
+
+    // The locale is used without --lang, and --lang wins over it
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    let output = cmd
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env("LANG", "fr_FR.UTF-8")
+        .arg("-h")
+        .output()
+        .unwrap();
+    let help = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        help.contains("Chiffre un message avec un chiffre"),
+        "{}",
+        help
+    );
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env("LC_ALL", "de_DE.UTF-8")
+        .arg("--lang=en")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("nonsense")
+        .arg("--message")
+        .arg("Hallo")
+        .assert()
+        .success()
+        .stdout("Unsupported cipher");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env("LC_ALL", "de_DE.UTF-8")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("nonsense")
+        .arg("--message")
+        .arg("Hallo")
+        .assert()
+        .success()
+        .stdout("Nicht unterstützte Chiffre");
+
+    // The help of the subcommands is translated too, in place of their doc comments
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    let output = cmd
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env("LANG", "de_DE.UTF-8")
+        .arg("encode")
+        .arg("--help")
+        .output()
+        .unwrap();
+    let help = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        help.contains("Verschlüsselt eine Nachricht mit einer Chiffre")
+            && help.contains("Die zu verwendende Chiffre (z. B. rot13, caesar)")
+            && help.contains("Zeigt die Hilfe an")
+            && !help.contains("Encode a message"),
+        "{}",
+        help
+    );
+
+    // --lang is accepted after the subcommand, and the errors are translated
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env("LC_ALL", "C")
+        .arg("crack")
+        .arg("--lang")
+        .arg("es")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--message")
+        .arg("LXFOPV")
+        .assert()
+        .success()
+        .stdout("Romper vigenere requiere una apertura supuesta (--assume-prefix)");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--lang")
+        .arg("xx")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_keyed_caesar() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("keyed-caesar")
+        .arg("--key")
+        .arg("KRYPTOS")
+        .arg("--shift")
+        .arg("3")
+        .arg("--message")
+        .arg("Hello, World!")
+        .assert()
+        .success()
+        .stdout("Dahhl, Zlqhs!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("keyed-caesar")
+        .arg("--key")
+        .arg("KRYPTOS")
+        .arg("--message")
+        .arg("Ateeh, Vhlep!")
+        .assert()
+        .success()
+        .stdout("Hello, World!");
+}
+
+#[test]
+fn test_cli_transcript() {
+    let markdown = std::env::temp_dir().join("cipha_cli_test_transcript.md");
+    let json = std::env::temp_dir().join("cipha_cli_test_transcript.jsonl");
+    let _ = std::fs::remove_file(&markdown);
+    let _ = std::fs::remove_file(&json);
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--transcript")
+        .arg(&markdown)
+        .arg("encode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("Lxfopv ef rnhr");
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--transcript")
+        .arg(&markdown)
+        .arg("chain")
+        .arg("--stage")
+        .arg("caesar -s 3")
+        .arg("-m")
+        .arg("hello")
+        .assert()
+        .success();
+
+    let transcript = std::fs::read_to_string(&markdown).unwrap();
+    assert_eq!(transcript.matches("\n## ").count() + 1, 2, "{}", transcript);
+    assert!(transcript.contains("encode --cipher vigenere --key *** --message <message>`"));
+    assert!(transcript.contains("- Input: 14 bytes, FNV-1a `"));
+    assert!(transcript.contains("```text\nLxfopv ef rnhr\n```\n"));
+    assert!(transcript.contains("chain --stage \"caesar -s ***\" -m <message>`"));
+    assert!(transcript.contains("FNV-1a `a430d84680aabd0b`"));
+    assert!(!transcript.contains("LEMON") && !transcript.contains("Attack"));
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--transcript")
+        .arg(&json)
+        .arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift=3")
+        .arg("--message")
+        .arg("Khoor")
+        .assert()
+        .success()
+        .stdout("Hello");
+    let record: serde_json::Value =
+        serde_json::from_str(std::fs::read_to_string(&json).unwrap().trim()).unwrap();
+    assert_eq!(record["command"][6], "--shift=***");
+    assert_eq!(record["input"]["bytes"], 5);
+    assert_eq!(record["output"], "Hello");
+}
+
+#[test]
+fn test_cli_keyed_morse() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("morse")
+        .arg("--key")
+        .arg("ZEBRAS")
+        .arg("--message")
+        .arg("Flee at once")
+        .assert()
+        .success()
+        .stdout(".. --- -... -... / . ..- / .-. --.- --. -...");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("morse")
+        .arg("--key")
+        .arg("ZEBRAS")
+        .arg("--message")
+        .arg(".. --- -... -... / . ..- / .-. --.- --. -...")
+        .assert()
+        .success()
+        .stdout("FLEE AT ONCE");
+}
+
+#[test]
+fn test_cli_caesar_case_shifts() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift")
+        .arg("3")
+        .arg("--shift-lower")
+        .arg("7")
+        .arg("--shift-digits")
+        .arg("5")
+        .arg("--message")
+        .arg("Agent 007")
+        .assert()
+        .success()
+        .stdout("Dnlua 552");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift")
+        .arg("3")
+        .arg("--shift-lower")
+        .arg("7")
+        .arg("--message")
+        .arg("Klssv, Zvysk!")
+        .assert()
+        .success()
+        .stdout("Hello, World!");
+}
+
+#[test]
+fn test_cli_caesar_box() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar-box")
+        .arg("--message")
+        .arg("HELLOWORLD")
+        .assert()
+        .success()
+        .stdout("HOLEWDLOLR");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar-box")
+        .arg("--key")
+        .arg("5")
+        .arg("--message")
+        .arg("HWEOLRLLOD")
+        .assert()
+        .success()
+        .stdout("HELLOWORLD");
+}
+
+#[test]
+fn test_cli_playfair() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("playfair")
+        .arg("--key")
+        .arg("PLAYFAIR EXAMPLE")
+        .arg("--message")
+        .arg("Hide the gold in the tree stump")
+        .assert()
+        .success()
+        .stdout("BMODZBXDNABEKUDMUIXMMOUVIF");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    let output = cmd
+        .arg("encode")
+        .arg("--cipher")
+        .arg("playfair6")
+        .arg("--key")
+        .arg("PLAYFAIR EXAMPLE")
+        .arg("--padding")
+        .arg("Q")
+        .arg("--message")
+        .arg("Room 101")
+        .output()
+        .unwrap();
+    let ciphertext = String::from_utf8(output.stdout).unwrap();
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("playfair6")
+        .arg("--key")
+        .arg("PLAYFAIR EXAMPLE")
+        .arg("--padding")
+        .arg("Q")
+        .arg("--message")
+        .arg(ciphertext.trim())
+        .assert()
+        .success()
+        .stdout("ROOM101Q");
+}
+
+#[test]
+fn test_cli_amsco() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("amsco")
+        .arg("--key")
+        .arg("4132")
+        .arg("--message")
+        .arg("INCOMPLETECOLUMNAR")
+        .assert()
+        .success()
+        .stdout("CETMPCOROMENAINLLU");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("amsco")
+        .arg("--key")
+        .arg("4132")
+        .arg("--offset")
+        .arg("1")
+        .arg("--message")
+        .arg("NCTUMMPOAROECNILEL")
+        .assert()
+        .success()
+        .stdout("INCOMPLETECOLUMNAR");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("amsco")
+        .arg("--key")
+        .arg("4132")
+        .arg("--offset")
+        .arg("3")
+        .arg("--message")
+        .arg("INCOMPLETECOLUMNAR")
+        .assert()
+        .success()
+        .stdout("Invalid key: The first cell holds 1 or 2 characters, not 3");
+}
+
+#[test]
+fn test_cli_aligned_layout() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--message")
+        .arg("ATTACK AT DAWN")
+        .arg("--layout")
+        .arg("aligned")
+        .arg("--width")
+        .arg("9");
+    cmd.assert().success().stdout(
+        "Plaintext:  ATTACK AT\nKey:        LEMONL EM\nCiphertext: LXFOPV EF\n\n\
+         Plaintext:   DAWN\nKey:         ONLE\nCiphertext:  RNHR",
+    );
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift")
+        .arg("1")
+        .arg("--message")
+        .arg("A b")
+        .arg("--layout")
+        .arg("aligned")
+        .arg("--latex");
+    cmd.assert().success().stdout(
+        "\\begin{tabular}{l*{3}{c}}\nPlaintext & A &  & b \\\\\nKey & B &  & B \\\\\n\
+         Ciphertext & B &  & c \\\\\n\\end{tabular}",
+    );
+
+    // Ciphers without a key stream print their usual output
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("reverse")
+        .arg("--message")
+        .arg("abc")
+        .arg("--layout")
+        .arg("aligned");
+    cmd.assert().success().stdout("cba");
+}
+
+#[test]
+fn test_cli_grille() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("grille")
+        .arg("--key")
+        .arg("X.../.X.X/..../.X..")
+        .arg("--padding")
+        .arg("X")
+        .arg("--message")
+        .arg("HELLO");
+    cmd.assert().success().stdout("HXXOXEXLXXXXXLXX");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("grille")
+        .arg("--key")
+        .arg("X.../.X.X/..../.X..")
+        .arg("--message")
+        .arg("JDKTTIAMSAAWNACT");
+    cmd.assert().success().stdout("JIMATTACKSATDAWN");
+
+    // Without a key, a random grille is made and printed to stderr
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("grille")
+        .arg("--message")
+        .arg("ATTACK");
+    let output = cmd.assert().success().get_output().clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let pattern = stderr.strip_prefix("Grille: ").unwrap().trim_end();
+    assert_eq!(pattern.len(), 19);
+    assert_eq!(pattern.matches('X').count(), 4);
+    assert_eq!(String::from_utf8(output.stdout).unwrap().len(), 6);
+}
+
+#[test]
+fn test_cli_rail_fence() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rail-fence")
+        .arg("--key")
+        .arg("3")
+        .arg("--message")
+        .arg("WEAREDISCOVERED");
+    cmd.assert().success().stdout("WECRERDSOEEAIVD");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("rail-fence")
+        .arg("--key")
+        .arg("3")
+        .arg("--offset")
+        .arg("2")
+        .arg("--message")
+        .arg("AIVDERDSOEEWECR");
+    cmd.assert().success().stdout("WEAREDISCOVERED");
+}
+
+#[test]
+fn test_cli_trailing_newline() {
+    // A pipe gets the result alone, a file gets it as a line
+    let output = std::env::temp_dir().join("cipha_cli_test_newline.txt");
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--output-file")
+        .arg(&output)
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "Uv\n");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--no-newline")
+        .arg("--output-file")
+        .arg(&output)
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .success();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "Uv");
+    std::fs::remove_file(&output).unwrap();
+
+    // --raw drops the colors of --diff too
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env_remove("NO_COLOR")
+        .arg("--raw")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--diff")
+        .arg("--message")
+        .arg("Hi!")
+        .assert()
+        .success()
+        .stdout("Hi!\nUv!\n^^");
+}
+
+#[test]
+fn test_cli_error_format_json() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--error-format")
+        .arg("json")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("affine")
+        .arg("--key-a")
+        .arg("2")
+        .arg("--message")
+        .arg("Hola")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("{\"error\":{\"code\":\"invalid-key\",\"message\":\"Key a = 2 must be coprime with 26\",\"position\":null}}\n");
+
+    // Positions count from the start of the message, including a group count header
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--error-format")
+        .arg("json")
+        .arg("decode")
+        .arg("--cipher")
+        .arg("primes")
+        .arg("--message")
+        .arg("GR 4\n2 3 / 4 5")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("{\"error\":{\"code\":\"unknown-token\",\"message\":\"Unknown token \\\"4\\\" at position 11\",\"position\":11}}\n");
+
+    // As text, the unknown token is a warning and the error replaces the result
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("morse")
+        .arg("--message")
+        .arg("... --.-.- ...")
+        .assert()
+        .success()
+        .stdout("SS")
+        .stderr("Warning: Unknown token \"--.-.-\" at position 4\n");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--error-format")
+        .arg("text")
+        .arg("decode")
+        .arg("--cipher")
+        .arg("nope")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .success()
+        .stdout("Unsupported cipher");
+
+    // The transcript still records a run that fails with a JSON error
+    let transcript = std::env::temp_dir().join("cipha_cli_test_error_transcript.jsonl");
+    let _ = std::fs::remove_file(&transcript);
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--error-format")
+        .arg("json")
+        .arg("--transcript")
+        .arg(&transcript)
+        .arg("encode")
+        .arg("--cipher")
+        .arg("nope")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .code(1)
+        .stdout("");
+    let entries = std::fs::read_to_string(&transcript).unwrap();
+    assert_eq!(entries.lines().count(), 1, "{}", entries);
+    std::fs::remove_file(&transcript).unwrap();
+}
+
+#[test]
+fn test_cli_show_permutation() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rail-fence")
+        .arg("--key")
+        .arg("2")
+        .arg("--show-permutation")
+        .arg("--message")
+        .arg("HELLO")
+        .assert()
+        .success()
+        .stdout("0 3 1 4 2\n\nHLOEL");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("reverse")
+        .arg("--show-permutation")
+        .arg("--message")
+        .arg("cba")
+        .assert()
+        .success()
+        .stdout("2 1 0\n\nabc");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--show-permutation")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .success()
+        .stdout("Kl")
+        .stderr("Warning: The caesar cipher has no fixed permutation\n");
+}
+
+#[test]
+fn test_cli_pigpen() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("pigpen")
+        .arg("--message")
+        .arg("Meet at the well")
+        .assert()
+        .success()
+        .stdout("⊐\u{307}□□› ⌟› ›⊓□ ∨\u{307}□⌞\u{307}⌞\u{307}");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("pigpen")
+        .arg("--key")
+        .arg("a,b,c,d,e,f,g,h,i,j,k,l,m,n,o,p,q,r,s,t,u,v,w,x,y,z")
+        .arg("--message")
+        .arg("dig here")
+        .assert()
+        .success()
+        .stdout("DIG HERE");
+
+    let art = "--+ +-- --+\n  | |     |\n--+ |     |";
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("pigpen-ascii")
+        .arg("--message")
+        .arg("Dig")
+        .assert()
+        .success()
+        .stdout(art);
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("pigpen-ascii")
+        .arg(format!("--message={}", art))
+        .assert()
+        .success()
+        .stdout("DIG");
+}
+
+#[test]
+fn test_cli_tap_code() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("tap")
+        .arg("--message")
+        .arg("Water")
+        .assert()
+        .success()
+        .stdout("..... ..  . .  .... ....  . .....  .... ..");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("tap")
+        .arg("--dot")
+        .arg("x")
+        .arg("--separators")
+        .arg(",/ ")
+        .arg("--message")
+        .arg("Water")
+        .assert()
+        .success()
+        .stdout("xxxxx,xx x,x xxxx,xxxx x,xxxxx xxxx,xx");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("tap")
+        .arg("--message")
+        .arg("52 11 44 15 42 / ** *")
+        .assert()
+        .success()
+        .stdout("WATER F");
+}
+
+#[test]
+fn test_cli_baudot() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("baudot")
+        .arg("--message")
+        .arg("Ryry 73")
+        .assert()
+        .success()
+        .stdout("01010 10101 01010 10101 00100 11011 11100 10000");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("baudot-hex")
+        .arg("--message")
+        .arg("0a 15 0a 15 04 1b 07 01")
+        .assert()
+        .success()
+        .stdout("RYRY 73");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("baudot-tape")
+        .arg("--message")
+        .arg("CQ")
+        .assert()
+        .success()
+        .stdout("| o.oo |\n|oo.o o|");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("baudot-tape")
+        .arg("--message")
+        .arg("| o.oo |\n|oo.o o|")
+        .assert()
+        .success()
+        .stdout("CQ");
+}
+
+#[test]
+fn test_cli_crack_beam_cleanup() {
+    // The last letter of the ciphertext was garbled
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--top")
+        .arg("1")
+        .arg("--message")
+        .arg("Wkh wrwdo lv iliwahq")
+        .assert()
+        .success()
+        .stdout("Key 3: The total is fiftxen");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--top")
+        .arg("1")
+        .arg("--beam-width")
+        .arg("16")
+        .arg("--message")
+        .arg("Wkh wrwdo lv iliwahq")
+        .assert()
+        .success()
+        .stdout("Key 3: The total is fifteen");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--beam-width")
+        .arg("0")
+        .arg("--message")
+        .arg("Wkh wrwdo")
+        .assert()
+        .success()
+        .stdout("Beam width must be at least 1");
+}
+
+#[test]
+fn test_cli_crack_assume_prefix() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--assume-prefix")
+        .arg("ATTACK AT")
+        .arg("--assume-prefix")
+        .arg("DEAR KARL")
+        .arg("--top")
+        .arg("1")
+        .arg("--message")
+        .arg("Oimf Xlvx, hup wtwcxizh ypehsf zr Ycaoek")
+        .assert()
+        .success()
+        .stdout("Key LEMON: Dear Karl, the shipment leaves on Monday");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("enigma")
+        .arg("--assume-prefix")
+        .arg("WETTERBERICHT")
+        .arg("--top")
+        .arg("2")
+        .arg("--message")
+        .arg("IPGHDPINFHYLX ZGTF RXBKV")
+        .assert()
+        .success()
+        .stdout(
+            "Key II IV V B BLA: WETTERBERICHT FUER HEUTE\n\
+             Key II IV V B BLE rings AAE: WETTERBERICHT FUER HEUTT",
+        );
+
+    // Too short an opening to close three loops for the Bombe
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("enigma-plugboard")
+        .arg("--assume-prefix")
+        .arg("WETTERBERICHT")
+        .arg("--message")
+        .arg("IPGHDPINFHYLX ZGTF RXBKV")
+        .assert()
+        .success()
+        .stdout("No key fits the assumed opening");
+
+    // Out of time before any key is tried
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("enigma")
+        .arg("--assume-prefix")
+        .arg("WETTERBERICHT")
+        .arg("--max-time")
+        .arg("0")
+        .arg("--message")
+        .arg("IPGHDPINFHYLX ZGTF RXBKV")
+        .assert()
+        .success()
+        .stdout("No key fits the assumed opening");
+
+    // The opening also narrows down the keys of a brute-force crack
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--assume-prefix")
+        .arg("meet")
+        .arg("--message")
+        .arg("Phhw ph dw qrrq")
+        .assert()
+        .success()
+        .stdout("Key 3: Meet me at noon");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("columnar")
+        .arg("--message")
+        .arg("C EITTHTB WTTR")
+        .assert()
+        .success()
+        .stdout("Cracking columnar needs an assumed opening (--assume-prefix)");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("scytale")
+        .arg("--assume-prefix")
+        .arg("DEAR")
+        .arg("--message")
+        .arg("C EITTHTB WTTR")
+        .assert()
+        .success()
+        .stdout("No key fits the assumed opening");
+}
+
+#[test]
+fn test_cli_output_case() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--case")
+        .arg("aca")
+        .arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("LXFOPV EF RNHR");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--case")
+        .arg("aca")
+        .arg("--message")
+        .arg("LXFOPV EF RNHR")
+        .assert()
+        .success()
+        .stdout("attack at dawn");
+
+    // Ciphers that write capitals follow the policy too
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("playfair")
+        .arg("--key")
+        .arg("PLAYFAIR EXAMPLE")
+        .arg("--case")
+        .arg("lower")
+        .arg("--message")
+        .arg("Hide the gold")
+        .assert()
+        .success()
+        .stdout("bmodzbxdnage");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--case")
+        .arg("title")
+        .arg("--message")
+        .arg("Hello")
+        .assert()
+        .failure();
+
+    // The case of rot47 ciphertext is part of the message, so it is kept for the round trip
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot47")
+        .arg("--case")
+        .arg("upper")
+        .arg("--message")
+        .arg("ABC xyz")
+        .assert()
+        .success()
+        .stdout("pqr IJK");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("rot47")
+        .arg("--message")
+        .arg("pqr IJK")
+        .assert()
+        .success()
+        .stdout("ABC xyz");
+}
+
+#[test]
+fn test_cli_rot_variants() {
+    for (cipher, expected) in [
+        ("rot5", "Room 656, Café!"),
+        ("rot18", "Ebbz 656, Pnsé!"),
+        ("rot47", "#@@> `_`[ r27éP"),
+    ] {
+        let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+        cmd.arg("encode")
+            .arg("--cipher")
+            .arg(cipher)
+            .arg("--message")
+            .arg("Room 101, Café!")
+            .assert()
+            .success()
+            .stdout(expected);
+
+        let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+        cmd.arg("decode")
+            .arg("--cipher")
+            .arg(cipher)
+            .arg("--message")
+            .arg(expected)
+            .assert()
+            .success()
+            .stdout("Room 101, Café!");
+    }
+}
+
+#[test]
+fn test_cli_rot_n() {
+    let cyrillic = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя";
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot-n")
+        .arg("--key")
+        .arg(cyrillic)
+        .arg("--shift")
+        .arg("3")
+        .arg("--message")
+        .arg("привет, мир!")
+        .assert()
+        .success()
+        .stdout("тулезх, плу!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("rot-n")
+        .arg("--key")
+        .arg(cyrillic)
+        .arg("--shift")
+        .arg("3")
+        .arg("--message")
+        .arg("тулезх, плу!")
+        .assert()
+        .success()
+        .stdout("привет, мир!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot-n")
+        .arg("--message")
+        .arg("Hello")
+        .assert()
+        .success()
+        .stdout("Invalid key: --key must be provided");
+
+    // An alphabet with both cases rotates them as letters of their own
+    let base62 = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    for (command, message, expected) in [
+        ("encode", "Hello 42", "m9GGJ ZX"),
+        ("decode", "m9GGJ ZX", "Hello 42"),
+    ] {
+        let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+        cmd.arg(command)
+            .arg("--cipher")
+            .arg("rot-n")
+            .arg("--key")
+            .arg(base62)
+            .arg("--message")
+            .arg(message)
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+}
+
+#[test]
+fn test_cli_alphabet() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift")
+        .arg("3")
+        .arg("--alphabet")
+        .arg("russian")
+        .arg("--message")
+        .arg("Съешь")
+        .assert()
+        .success()
+        .stdout("Фэзыя");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("ΚΛΕΙΔΙ")
+        .arg("--alphabet")
+        .arg("greek")
+        .arg("--message")
+        .arg("Τλοοοέακ")
+        .assert()
+        .success()
+        .stdout("Καλημέρα");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("KEY")
+        .arg("--alphabet")
+        .arg("greek")
+        .arg("--message")
+        .arg("Γεια σου")
+        .assert()
+        .success()
+        .stdout("Invalid key: Key character 'K' is not in the alphabet");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--alphabet")
+        .arg("klingon")
+        .arg("--message")
+        .arg("Hello")
+        .assert()
+        .success()
+        .stdout("Invalid key: Alphabet has 'n' twice");
+}
+
+#[test]
+fn test_cli_cards_and_dominoes() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("cards")
+        .arg("--message")
+        .arg("Room 7")
+        .assert()
+        .success()
+        .stdout("5♦ 2♦ 2♦ K♣ / 7♥");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("dominoes")
+        .arg("--message")
+        .arg("1|1 0|4 2|7")
+        .assert()
+        .success()
+        .stdout("KEY");
+
+    let table = std::env::temp_dir().join("cipha_cli_test_tokens.csv");
+    std::fs::write(&table, "S,sun\nM,moon\n").unwrap();
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("tokens")
+        .arg("--table")
+        .arg(&table)
+        .arg("--message")
+        .arg("Sms")
+        .assert()
+        .success()
+        .stdout("sun moon sun");
+}
+
+#[test]
+fn test_cli_resistor_and_flags() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("resistor")
+        .arg("--message")
+        .arg("470")
+        .assert()
+        .success()
+        .stdout("yellow violet black");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("flags")
+        .arg("--message")
+        .arg("mike alfa yankee delta alfa yankee")
+        .assert()
+        .success()
+        .stdout("MAYDAY");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("flag-emoji")
+        .arg("--message")
+        .arg("SOS")
+        .assert()
+        .success()
+        .stdout("🇸 🇴 🇸");
+}
+
+#[test]
+fn test_cli_non_alpha() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--non-alpha")
+        .arg("strip")
+        .arg("--message")
+        .arg("Meet me at 10!")
+        .assert()
+        .success()
+        .stdout("Phhwphdw");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--non-alpha")
+        .arg("X")
+        .arg("--message")
+        .arg("Meet me")
+        .assert()
+        .success()
+        .stdout("PhhwAph");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--non-alpha")
+        .arg("error")
+        .arg("--message")
+        .arg("Meet me")
+        .assert()
+        .success()
+        .stdout("Non-alphabetic character ' ' at position 4");
+}
+
+#[test]
+fn test_cli_group_size_and_wrap() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--group-size")
+        .arg("5")
+        .arg("--wrap")
+        .arg("24")
+        .arg("--case")
+        .arg("upper")
+        .arg("--message")
+        .arg("Attack at dawn, then hold the bridge")
+        .assert()
+        .success()
+        .stdout("LXFOP VEFRN HR,FV RYLAZ\nQELQP ETHSS");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("vigenere")
+        .arg("--key")
+        .arg("LEMON")
+        .arg("--group-size")
+        .arg("5")
+        .arg("--message")
+        .arg("LXFOP VEFRN HR,FV RYLAZ\nQELQP ETHSS")
+        .assert()
+        .success()
+        .stdout("ATTACKATDAWN,THENHOLDTHEBRIDGE");
+}
+
+#[test]
+fn test_cli_group_size_and_wrap_token_encodings() {
+    // Grouping would run the morse letters together, so it is refused
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("morse")
+        .arg("--group-size")
+        .arg("5")
+        .arg("--message")
+        .arg("SOS HELP")
+        .assert()
+        .success()
+        .stdout("Invalid key: --group-size cannot be used with morse, whose tokens are separated by spaces");
+
+    // Wrapping alone breaks the lines anywhere and joins them back
+    for (cipher, message, wrapped, decoded) in [
+        (
+            "morse",
+            "SOS HELP",
+            "... --- \n... / ..\n.. . .-.\n. .--.",
+            "SOS HELP",
+        ),
+        ("cards", "Go 4", "7♣ 2♦ / \n4♥", "GO 4"),
+    ] {
+        let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+        cmd.arg("encode")
+            .arg("--cipher")
+            .arg(cipher)
+            .arg("--wrap")
+            .arg("8")
+            .arg("--message")
+            .arg(message)
+            .assert()
+            .success()
+            .stdout(wrapped);
+
+        let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+        cmd.arg("decode")
+            .arg("--cipher")
+            .arg(cipher)
+            .arg("--wrap")
+            .arg("8")
+            .arg("--message")
+            .arg(wrapped)
+            .assert()
+            .success()
+            .stderr("")
+            .stdout(decoded);
+    }
+}
+
+#[test]
+fn test_cli_affine_rejects_non_coprime_key() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    let output = cmd
+        .arg("encode")
+        .arg("--cipher")
+        .arg("affine")
+        .arg("--message")
+        .arg("Hello")
+        .arg("--key-a")
+        .arg("13")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Invalid key:"));
+}
+
+#[test]
+fn test_cli_table_cipher() {
+    let table = std::env::temp_dir().join("cipha_cli_test_codebook.csv");
+    std::fs::write(&table, "attack,0425\nat,17\ndawn,1172\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("table")
+        .arg("--table")
+        .arg(&table)
+        .arg("--message")
+        .arg("attack at dawn")
+        .assert()
+        .success()
+        .stdout("0425 17 1172");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("table")
+        .arg("--table")
+        .arg(&table)
+        .arg("--message")
+        .arg("0425 17 1172")
+        .assert()
+        .success()
+        .stdout("attack at dawn");
+}
+
+#[test]
+fn test_cli_nomenclator() {
+    let csv = std::env::temp_dir().join("cipha_cli_test_nomenclator.csv");
+    std::fs::write(&csv, "le roi,125\nfr,22\nan,41,78\nce,303\na,7\n,99\n").unwrap();
+    let json = std::env::temp_dir().join("cipha_cli_test_nomenclator.json");
+    std::fs::write(&json, r#"{"le roi": 125, "an": [41, "078"], "": [99]}"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("nomenclator")
+        .arg("--table")
+        .arg(&csv)
+        .arg("--message")
+        .arg("Le roi a France")
+        .assert()
+        .success()
+        .stdout("125 7 22 41 303");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("nomenclator")
+        .arg("--table")
+        .arg(&json)
+        .arg("--message")
+        .arg("125 99 41 078 5")
+        .assert()
+        .success()
+        .stdout("le roianan?");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("nomenclator")
+        .arg("--table")
+        .arg(&json)
+        .arg("--message")
+        .arg("Le roi an an")
+        .assert()
+        .success()
+        .stdout("125 41 078");
+}
+
+#[test]
+fn test_cli_word_transforms() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("reverse-words")
+        .arg("--message")
+        .arg("attack at dawn")
+        .assert()
+        .success()
+        .stdout("dawn at attack");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("word-transposition")
+        .arg("--key")
+        .arg("CAB")
+        .arg("--message")
+        .arg("are at discovered once we flee")
+        .assert()
+        .success()
+        .stdout("we are discovered flee at once");
+}
+
+#[test]
+fn test_cli_substitution_keyword_and_alphabet() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("substitution")
+        .arg("--key")
+        .arg("ZEBRAS")
+        .arg("--message")
+        .arg("Flee at once!")
+        .assert()
+        .success()
+        .stdout("Siaa zq lkba!");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("substitution")
+        .arg("--key")
+        .arg("QWERTYUIOPASDFGHJKLZXCVBNM")
+        .arg("--message")
+        .arg("Itssg, Vgksr!")
+        .assert()
+        .success()
+        .stdout("Hello, World!");
+}
+
+#[test]
+fn test_cli_interleave() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("interleave")
+        .arg("--key")
+        .arg("3")
+        .arg("--message")
+        .arg("HLWLEOODL R")
+        .assert()
+        .success()
+        .stdout("HELLO WORLD");
+}
+
+#[test]
+fn test_cli_decimation() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("decimation")
+        .arg("--key")
+        .arg("3")
+        .arg("--message")
+        .arg("GEOCACHE")
+        .assert()
+        .success()
+        .stdout("GCHEAEOC");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("decimation")
+        .arg("--key")
+        .arg("3")
+        .arg("--message")
+        .arg("GEOCACHES")
+        .assert()
+        .success()
+        .stdout("Invalid key: Skip 3 must be coprime with the message length 9");
+}
+
+#[test]
+fn test_cli_route_transforms() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("boustrophedon")
+        .arg("--key")
+        .arg("5")
+        .arg("--message")
+        .arg("WE ARE DISCOVERED")
+        .assert()
+        .success()
+        .stdout("WE ARSID ECOVERDE");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("spiral")
+        .arg("--key")
+        .arg("4")
+        .arg("--message")
+        .arg("ABCDHLKJIEFG")
+        .assert()
+        .success()
+        .stdout("ABCDEFGHIJKL");
+}
+
+#[test]
+fn test_cli_columnar_transposition() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("columnar")
+        .arg("--key")
+        .arg("ZEBRAS")
+        .arg("--padding")
+        .arg("Q")
+        .arg("--message")
+        .arg("WEAREDISCOVEREDFLEEATONCE")
+        .assert()
+        .success()
+        .stdout("EVLNQACDTQESEAQROFOQDEECQWIREE");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("columnar")
+        .arg("--key")
+        .arg("ZEBRAS")
+        .arg("--message")
+        .arg("EVLNACDTESEAROFODEECWIREE")
+        .assert()
+        .success()
+        .stdout("WEAREDISCOVEREDFLEEATONCE");
+}
+
+#[test]
+fn test_cli_double_columnar_transposition() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("double-columnar")
+        .arg("--key")
+        .arg("ZEBRAS,STRIPE")
+        .arg("--message")
+        .arg("CAEENSOIAEDRLEFWEDREEVTOC")
+        .assert()
+        .success()
+        .stdout("WEAREDISCOVEREDFLEEATONCE");
+}
+
+#[test]
+fn test_cli_roman_numerals() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("roman")
+        .arg("--message")
+        .arg("25/12/1915")
+        .assert()
+        .success()
+        .stdout("XXV/XII/MCMXV");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("roman")
+        .arg("--message")
+        .arg("XXV/XII/MCMXV")
+        .assert()
+        .success()
+        .stdout("25/12/1915");
+}
+
+#[test]
+fn test_cli_polybius_square() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("polybius")
+        .arg("--message")
+        .arg("Hello")
+        .assert()
+        .success()
+        .stdout("23 15 31 31 34");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("polybius6")
+        .arg("--labels")
+        .arg("ADFGVX")
+        .arg("--message")
+        .arg("AA DA AV FD GD")
+        .assert()
+        .success()
+        .stdout("AGENT");
+}
+
+#[test]
+fn test_cli_numeric_substitution() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("primes")
+        .arg("--message")
+        .arg("Hi there")
+        .assert()
+        .success()
+        .stdout("19 23 / 71 19 11 61 11");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("ascii")
+        .arg("--key=-10")
+        .arg("--message")
+        .arg("57 55 57 62 59")
+        .assert()
+        .success()
+        .stdout("CACHE");
+}
+
+#[test]
+fn test_cli_adfgvx() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("adfgvx")
+        .arg("--key")
+        .arg("NA1C3H8TB2OME5WRPD4F6G7I9J0KLQSUVXYZ,PRIVACY")
+        .arg("--message")
+        .arg("DGDDDAGDDGAFADDFDADVDVFAADVX")
+        .assert()
+        .success()
+        .stdout("ATTACKAT1200AM");
+}
+
+#[test]
+fn test_cli_bifid() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("bifid")
+        .arg("--key")
+        .arg("PHQGMEAYLNOFDXKRCVSZWBUTI,5")
+        .arg("--message")
+        .arg("defend the east wall of the castle")
+        .assert()
+        .success()
+        .stdout("FFYHMKHYCPLIASHADTRLHCCHLBLR");
+}
+
+#[test]
+fn test_cli_chain_simplify() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain")
+        .arg("--simplify")
+        .arg("--stage")
+        .arg("caesar --shift 3")
+        .arg("--stage")
+        .arg("rot13")
+        .arg("--stage")
+        .arg("vigenere --key LEMON")
+        .arg("--stage")
+        .arg("atbash")
+        .arg("--stage")
+        .arg("atbash")
+        .arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("Bnvefl uv hdxh")
+        .stderr(
+            "Effective chain: --stage \"caesar --shift 16\" --stage \"vigenere --key LEMON\"\n",
+        );
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain")
+        .arg("--simplify")
+        .arg("--decode")
+        .arg("--stage")
+        .arg("reverse")
+        .arg("--stage")
+        .arg("reverse")
+        .arg("--message")
+        .arg("same")
+        .assert()
+        .success()
+        .stdout("same")
+        .stderr("Effective chain: none, the stages cancel out\n");
+}
+
+#[test]
+fn test_cli_chain_decode_uses_inverse() {
+    let stages = ["caesar --shift 3", "vigenere --key LEMON", "reverse-words"];
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain");
+    for stage in stages {
+        cmd.arg("--stage").arg(stage);
+    }
+    cmd.arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("uqku hi Oairsy");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain").arg("--decode");
+    for stage in stages {
+        cmd.arg("--stage").arg(stage);
+    }
+    cmd.arg("--message")
+        .arg("uqku hi Oairsy")
+        .assert()
+        .success()
+        .stdout("Attack at dawn");
+}
+
+#[test]
+fn test_cli_chain_reports_failing_stage() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain")
+        .arg("--stage")
+        .arg("caesar --shift 3")
+        .arg("--stage")
+        .arg("running-key --key AB")
+        .arg("--message")
+        .arg("Hello")
+        .assert()
+        .success()
+        .stdout("Invalid key: Key text has 2 letters but the message has 5");
+}
+
+#[test]
+fn test_cli_chain_nulls() {
+    let stages = ["vigenere --key LEMON", "nulls --step 3"];
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain");
+    for stage in stages {
+        cmd.arg("--stage").arg(stage);
+    }
+    cmd.arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("Lxfaopvb ef rcnhrd");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain").arg("--decode");
+    for stage in stages {
+        cmd.arg("--stage").arg(stage);
+    }
+    cmd.arg("--message")
+        .arg("Lxfaopvb ef rcnhrd")
+        .assert()
+        .success()
+        .stdout("Attack at dawn");
+
+    // Appended nulls end with their count in base 26
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("nulls")
+        .arg("--message")
+        .arg("ABBACDEFGHIJKLMNOPQRSTUVWXYZCDEFGHIJKLMNOPQRSTUVWXYZABW")
+        .assert()
+        .success()
+        .stdout("ABBA");
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_cli_chain_gunzip() {
+    // printf uryyb | gzip -n | base64
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain")
+        .arg("--stage")
+        .arg("gunzip")
+        .arg("--stage")
+        .arg("rot13")
+        .arg("--message")
+        .arg("H4sIAAAAAAAAAystqqxMAgChYUcwBQAAAA==")
+        .assert()
+        .success()
+        .stdout("hello");
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn test_cli_gunzip_invalid_input_is_an_error() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--error-format")
+        .arg("json")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("gunzip")
+        .arg("--message")
+        .arg("!!")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("{\"error\":{\"code\":\"invalid-input\",\"message\":\"Invalid base64 character '!'\",\"position\":null}}\n");
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn test_cli_chain_gunzip_requires_feature() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("chain")
+        .arg("--stage")
+        .arg("gunzip")
+        .arg("--message")
+        .arg("H4sIAAAAAAAAAystqqxMAgChYUcwBQAAAA==")
+        .assert()
+        .success()
+        .stdout("The gunzip stage requires building cipha-cli with the `gzip` feature");
+}
+
+#[cfg(feature = "hash")]
+#[test]
+fn test_cli_hash_verify() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("hash")
+        .arg("--algorithm")
+        .arg("md5")
+        .arg("--message")
+        .arg("hello")
+        .assert()
+        .success()
+        .stdout("MD5: 5d41402abc4b2a76b9719d911017c592");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("hash")
+        .arg("--verify")
+        .arg("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        .arg("--message")
+        .arg("hullo\nhello\nhallo")
+        .assert()
+        .success()
+        .stdout("Match: hello");
+}
+
+#[cfg(not(feature = "hash"))]
+#[test]
+fn test_cli_hash_requires_feature() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("hash")
+        .arg("--message")
+        .arg("hello")
+        .assert()
+        .success()
+        .stdout("Hashing requires building cipha-cli with the `hash` feature");
+}
+
+#[test]
+fn test_cli_crack() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--top")
+        .arg("1")
+        .arg("--message")
+        .arg("Tlla tl ha aol vsk vhr ayll")
+        .assert()
+        .success()
+        .stdout("Key 7: Meet me at the old oak tree");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--known-contains")
+        .arg("oak tree")
+        .arg("--message")
+        .arg("Tlla tl ha aol vsk vhr ayll")
+        .assert()
+        .success()
+        .stdout("Key 7: Meet me at the old oak tree");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("crack")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--known-plaintext")
+        .arg("meet me at noon")
+        .arg("--message")
+        .arg("Tlla tl ha aol vsk vhr ayll")
+        .assert()
+        .success()
+        .stdout("No key fits the known plaintext");
+}
+
+#[test]
+fn test_cli_file_encodings() {
+    let path = std::env::temp_dir().join("cipha_cli_test_windows_1252.txt");
+    std::fs::write(&path, b"\x93Caf\xe9\x94").unwrap();
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("\u{201c}Pns\u{e9}\u{201d}");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--encoding")
+        .arg("latin-1")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("\u{93}Pns\u{e9}\u{94}");
+
+    std::fs::write(&path, b"\xff\xfeH\0i\0").unwrap();
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("Uv");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_cli_diff() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--diff")
+        .arg("--message")
+        .arg("Hi, 2 you")
+        .assert()
+        .success()
+        .stdout("Hi, 2 you\nKl, 2 brx\n^^    ^^^");
+
+    // Output that isn't a terminal gets the carets even without NO_COLOR
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.env_remove("NO_COLOR")
+        .arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--diff")
+        .arg("--message")
+        .arg("Kl!")
+        .assert()
+        .success()
+        .stdout("Kl!\nHi!\n^^");
+}
+
+#[test]
+fn test_cli_group_count() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--group-count")
+        .arg("summary")
+        .arg("--message")
+        .arg("HELLO THERE")
+        .assert()
+        .success()
+        .stdout("KHOOR WKHUH\nLetters: 10\nGroups: 2\nGR 2");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--group-count")
+        .arg("header")
+        .arg("--message")
+        .arg("HELLO THERE")
+        .assert()
+        .success()
+        .stdout("GR 2\nKHOOR WKHUH");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--message")
+        .arg("GR 2\nKHOOR WKHUH")
+        .assert()
+        .success()
+        .stdout("HELLO THERE");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--message")
+        .arg("GR 3\nKHOOR WKHUH")
+        .assert()
+        .success()
+        .stdout("Group count mismatch: the header says GR 3 but the message has 2 groups");
+}
+
+#[test]
+fn test_cli_show_key() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("polybius")
+        .arg("--key")
+        .arg("ZEBRA")
+        .arg("--show-key")
+        .arg("--message")
+        .arg("11 12")
+        .assert()
+        .success()
+        .stdout(
+            "  1 2 3 4 5\n1 Z E B R A\n2 C D F G H\n3 I K L M N\n4 O P Q S T\n5 U V W X Y\n\nZE",
+        );
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--show-key")
+        .arg("--message")
+        .arg("hi")
+        .assert()
+        .success()
+        .stdout("kl")
+        .stderr("Warning: The caesar cipher has no key square\n");
+}
+
+#[test]
+fn test_cli_render_bigtext() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--render")
+        .arg("bigtext")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--message")
+        .arg("Uv")
+        .assert()
+        .success()
+        .stdout("#   # ###\n#   #  #\n#####  #\n#   #  #\n#   # ###");
+}
+
+#[test]
+fn test_cli_line_endings_and_bom() {
+    let input = std::env::temp_dir().join("cipha_cli_test_crlf_input.txt");
+    let output = std::env::temp_dir().join("cipha_cli_test_crlf_output.txt");
+    std::fs::write(&input, b"\xEF\xBB\xBFHello\r\nWorld\r\n").unwrap();
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout("Uryyb\r\nJbeyq\r\n");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--line-endings")
+        .arg("preserve")
+        .arg("--bom")
+        .arg("preserve")
+        .arg("--output-file")
+        .arg(&output)
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&input)
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read(&output).unwrap(),
+        b"\xEF\xBB\xBFUryyb\r\nJbeyq\r\n"
+    );
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("--line-endings")
+        .arg("lf")
+        .arg("encode")
+        .arg("--cipher")
+        .arg("rot13")
+        .arg("--file")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout("Uryyb\nJbeyq\n");
+    std::fs::remove_file(&input).unwrap();
+    std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn test_cli_analyze_corpus() {
+    let corpus = std::env::temp_dir().join("cipha_cli_test_corpus");
+    std::fs::create_dir_all(&corpus).unwrap();
+    std::fs::write(
+        corpus.join("a.txt"),
+        "Xp kcg hhf pfdx ct xtyfd, wh aoe fwp kzcdx ch gwyid, wh aoe fwp oyf cr kwgrqy",
+    )
+    .unwrap();
+    std::fs::write(corpus.join("b.txt"), "short").unwrap();
+    let output = Command::cargo_bin("cipha-cli")
+        .unwrap()
+        .arg("analyze")
+        .arg("--corpus")
+        .arg(&corpus)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["files"][0]["file"], "a.txt");
+    assert_eq!(report["files"][1]["likely_cipher"], "undetermined");
+    assert_eq!(report["comparisons"][0]["files"][1], "b.txt");
+    assert_eq!(report["comparisons"][0]["likely_same_cipher"], false);
+}
+
+#[test]
+fn test_cli_enigma() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("enigma")
+        .arg("--message")
+        .arg("AAAAA")
+        .assert()
+        .success()
+        .stdout("BDZGO");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("enigma")
+        .arg("--rotors")
+        .arg("II IV V")
+        .arg("--rings")
+        .arg("02 21 12")
+        .arg("--positions")
+        .arg("BLA")
+        .arg("--plugboard")
+        .arg("AV BS CG DL FU HZ IN KM OW RX")
+        .arg("--message")
+        .arg("EDPUD NRGYS ZRCXN")
+        .assert()
+        .success()
+        .stdout("AUFKL XABTE ILUNG");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("enigma")
+        .arg("--rotors")
+        .arg("I I III")
+        .arg("--message")
+        .arg("AAAAA")
+        .assert()
+        .success()
+        .stdout("Invalid key: Each rotor can only be used once");
+}
+
+#[test]
+fn test_cli_jefferson() {
+    let wheels = "ABCDEFGHIJKLMNOPQRSTUVWXYZ ZYXWVUTSRQPONMLKJIHGFEDCBA QWERTYUIOPASDFGHJKLZXCVBNM";
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("jefferson")
+        .arg("--key")
+        .arg(wheels)
+        .arg("--rotors")
+        .arg("3 1 2")
+        .arg("--offset")
+        .arg("7")
+        .arg("--message")
+        .arg("Retreat at once to the river")
+        .assert()
+        .success()
+        .stdout("Almalts hm huvp ah sox apopy");
+
+    // Without --offset the most English-like row is found
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("jefferson")
+        .arg("--key")
+        .arg(wheels)
+        .arg("--rotors")
+        .arg("3 1 2")
+        .arg("--message")
+        .arg("Almalts hm huvp ah sox apopy")
+        .assert()
+        .success()
+        .stdout("Retreat at once to the river");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("jefferson")
+        .arg("--key")
+        .arg(wheels)
+        .arg("--rotors")
+        .arg("1 4")
+        .arg("--message")
+        .arg("Hi")
+        .assert()
+        .success()
+        .stdout("Invalid key: Wheel numbers must be from 1 to 3, found 4");
+}
+
+#[test]
+fn test_cli_lorenz() {
+    let pins: Vec<String> = [41, 31, 29, 26, 23, 43, 47, 51, 53, 59, 61, 37]
+        .iter()
+        .enumerate()
+        .map(|(wheel, &size)| {
+            (0..size)
+                .map(|i| if (i * 7 + wheel) % 3 == 0 { 'x' } else { '.' })
+                .collect()
+        })
+        .collect();
+    let pins = pins.join(" ");
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("lorenz")
+        .arg("--key")
+        .arg(&pins)
+        .arg("--positions")
+        .arg("1 5 9 2 14 30 3 7 11 40 20 8")
+        .arg("--message")
+        .arg("Attack at dawn")
+        .assert()
+        .success()
+        .stdout("G/BUBI/HBXDRUJ");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("lorenz")
+        .arg("--key")
+        .arg(&pins)
+        .arg("--positions")
+        .arg("1 5 9 2 14 30 3 7 11 40 20 8")
+        .arg("--message")
+        .arg("G/BUBI/HBXDRUJ")
+        .assert()
+        .success()
+        .stdout("ATTACK AT DAWN");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("lorenz")
+        .arg("--key")
+        .arg(&pins)
+        .arg("--positions")
+        .arg("1 2 3")
+        .arg("--message")
+        .arg("Attack")
+        .assert()
+        .success()
+        .stdout("Invalid key: The Lorenz machine needs 12 wheel positions, found 3");
+}
+#[test]
+fn test_cli_authenticate() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.args([
+        "encode",
+        "--cipher",
+        "vigenere",
+        "--key",
+        "LEMON",
+        "--authenticate",
+    ])
+    .arg("--message")
+    .arg("Attack at dawn")
+    .assert()
+    .success()
+    .stdout("Lxfopv ef rnhr WSRE");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.args([
+        "decode",
+        "--cipher",
+        "vigenere",
+        "--key",
+        "LEMON",
+        "--authenticate",
+    ])
+    .arg("--message")
+    .arg("Lxfopv ef rnhr WSRE")
+    .assert()
+    .success()
+    .stdout("Attack at dawn");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.args([
+        "decode",
+        "--cipher",
+        "vigenere",
+        "--key",
+        "LEMNO",
+        "--authenticate",
+    ])
+    .arg("--message")
+    .arg("Lxfopv ef rnhr WSRE")
+    .assert()
+    .success()
+    .stdout(
+        "Authentication failed: Check value mismatch: wrong key, wrong cipher or damaged message",
+    );
+}
+
+#[test]
+fn test_cli_decode_check() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--shift")
+        .arg("5")
+        .arg("--check")
+        .arg("--message")
+        .arg("Wkh vhfuhw phhwlqj lv dw qrrq")
+        .assert()
+        .success()
+        .stdout("Rfc qcapcr kccrgle gq yr lmml")
+        .stderr("Warning: output does not look like language — wrong key or cipher? (score -3.62, expected at least -3.30)\n");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("caesar")
+        .arg("--check")
+        .arg("--message")
+        .arg("Wkh vhfuhw phhwlqj lv dw qrrq")
+        .assert()
+        .success()
+        .stdout("The secret meeting is at noon")
+        .stderr("");
+}
+
+#[test]
+fn test_cli_scytale() {
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("encode")
+        .arg("--cipher")
+        .arg("scytale")
+        .arg("--key")
+        .arg("4")
+        .arg("--message")
+        .arg("IAMHURTVERYBADLYHELP")
+        .assert()
+        .success()
+        .stdout("IRYYATBHMVAEHEDLURLP");
+
+    let mut cmd = Command::cargo_bin("cipha-cli").unwrap();
+    cmd.arg("decode")
+        .arg("--cipher")
+        .arg("scytale")
+        .arg("--key")
+        .arg("3")
+        .arg("--padding")
+        .arg("X")
+        .arg("--message")
+        .arg("AEIBFJCGXDHX")
+        .assert()
+        .success()
+        .stdout("ABCDEFGHIJXX");
+}
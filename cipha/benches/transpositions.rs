@@ -0,0 +1,65 @@
+//! Times the transposition ciphers on texts of one to eight megabytes.
+//!
+//! Run with `cargo bench -p cipha`. Every cipher should take about twice as long for twice the
+//! text, which the last column shows as the time per megabyte staying level.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use cipha::ciphers::{
+    AmscoCipher, Cipher, ColumnarTranspositionCipher, DoubleTranspositionCipher, RailFenceCipher,
+    ScytaleCipher,
+};
+
+const MEGABYTE: usize = 1 << 20;
+
+fn main() {
+    let ciphers: Vec<(&str, Box<dyn Cipher>)> = vec![
+        (
+            "rail fence",
+            Box::new(RailFenceCipher::new(7).unwrap().with_offset(3)),
+        ),
+        (
+            "columnar",
+            Box::new(ColumnarTranspositionCipher::new("ZEBRAS").unwrap()),
+        ),
+        (
+            "double columnar",
+            Box::new(DoubleTranspositionCipher::new("ZEBRAS", "STRIPE").unwrap()),
+        ),
+        ("amsco", Box::new(AmscoCipher::new("31452").unwrap())),
+        ("scytale", Box::new(ScytaleCipher::new(11).unwrap())),
+    ];
+    let sentence = "WE ARE DISCOVERED, FLEE AT ONCE. ";
+    println!(
+        "{:<16} {:>6} {:>12} {:>12} {:>12}",
+        "cipher", "MB", "encipher", "decipher", "per MB"
+    );
+    for (name, cipher) in &ciphers {
+        for megabytes in [1, 2, 4, 8] {
+            let text: String = sentence
+                .chars()
+                .cycle()
+                .take(megabytes * MEGABYTE)
+                .collect();
+            let (ciphertext, encipher) = time(|| cipher.encipher(&text));
+            let (plaintext, decipher) = time(|| cipher.decipher(&ciphertext));
+            assert_eq!(plaintext, text, "{} does not round-trip", name);
+            println!(
+                "{:<16} {:>6} {:>12.2?} {:>12.2?} {:>12.2?}",
+                name,
+                megabytes,
+                encipher,
+                decipher,
+                decipher / megabytes as u32
+            );
+        }
+    }
+}
+
+// Runs `f` and returns its result with how long it took.
+fn time(f: impl FnOnce() -> String) -> (String, Duration) {
+    let start = Instant::now();
+    let result = black_box(f());
+    (result, start.elapsed())
+}
@@ -0,0 +1,265 @@
+//! Alphabets other than A to Z.
+//!
+//! The classical ciphers were used with whatever alphabet the language had: Greek, Cyrillic,
+//! Turkish with its dotted and dotless I. An [`Alphabet`] is the ordered set of letters a
+//! cipher counts with, and the Caesar, Vigenère, Atbash, Affine and substitution ciphers take
+//! one in place of A to Z.
+//!
+//! ```rust
+//! use cipha::alphabet::Alphabet;
+//! use cipha::ciphers::CaesarCipher;
+//!
+//! let caesar = CaesarCipher::new(3).with_alphabet(Alphabet::russian());
+//! assert_eq!(caesar.encipher("Съешь же ещё"), "Фэзыя йз зьи");
+//! assert_eq!(caesar.decipher("Фэзыя йз зьи"), "Съешь же ещё");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// An ordered set of letters with their positions, from 0.
+///
+/// Every letter may have a form in the other case, which takes the same position and keeps its
+/// case through a cipher: [`Alphabet::new`] finds it with the Unicode case mappings and
+/// [`Alphabet::with_cases`] takes it as given, for languages like Turkish where those mappings
+/// are wrong. Characters without a case, such as digits or symbols, are letters too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: Vec<char>,
+    // The letters in the other case, or the letters themselves if they have none
+    others: Vec<char>,
+    // The position of every form of a letter, and whether it is the other case
+    index: HashMap<char, (usize, bool)>,
+}
+
+impl Alphabet {
+    /// Creates an alphabet of the given letters, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no letters or a letter is given twice, in either case.
+    pub fn new(letters: &str) -> Result<Self, String> {
+        let letters: Vec<char> = letters.chars().collect();
+        let others = letters
+            .iter()
+            .map(|&c| {
+                let other = if c.is_uppercase() {
+                    single_char(c.to_lowercase())
+                } else {
+                    single_char(c.to_uppercase())
+                };
+                other.unwrap_or(c)
+            })
+            .collect();
+        Alphabet::from_cases(letters, others)
+    }
+
+    /// Creates an alphabet of the given characters, in order, each standing only for itself:
+    /// the other case of a letter is not part of the alphabet unless it is given too, as its
+    /// own letter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no characters or a character is given twice.
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// let base62 = Alphabet::exact("0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz")
+    ///     .unwrap();
+    /// assert_eq!((base62.index_of('A'), base62.index_of('a')), (Some(10), Some(36)));
+    /// assert!(Alphabet::new("Aa").is_err());
+    /// ```
+    pub fn exact(letters: &str) -> Result<Self, String> {
+        let letters: Vec<char> = letters.chars().collect();
+        Alphabet::from_cases(letters.clone(), letters)
+    }
+
+    /// Creates an alphabet from its letters in one case and the same letters in the other.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the two have different lengths, there are no letters or a letter is
+    /// given twice.
+    pub fn with_cases(letters: &str, others: &str) -> Result<Self, String> {
+        let letters: Vec<char> = letters.chars().collect();
+        let others: Vec<char> = others.chars().collect();
+        if letters.len() != others.len() {
+            return Err("Both cases of the alphabet must have the same letters".to_string());
+        }
+        Alphabet::from_cases(letters, others)
+    }
+
+    fn from_cases(letters: Vec<char>, others: Vec<char>) -> Result<Self, String> {
+        if letters.is_empty() {
+            return Err("Alphabet must not be empty".to_string());
+        }
+        let mut index = HashMap::new();
+        for (i, (&letter, &other)) in letters.iter().zip(&others).enumerate() {
+            if index.insert(letter, (i, false)).is_some() {
+                return Err(format!("Alphabet has {:?} twice", letter));
+            }
+            if other != letter && index.insert(other, (i, true)).is_some() {
+                return Err(format!("Alphabet has {:?} twice", other));
+            }
+        }
+        Ok(Alphabet {
+            letters,
+            others,
+            index,
+        })
+    }
+
+    /// The 26 letters `A` to `Z`.
+    pub fn latin() -> Self {
+        Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ").expect("valid alphabet")
+    }
+
+    /// The 24 letters of the Greek alphabet, `Α` to `Ω`. The final sigma `ς` is a form of `Σ`
+    /// only as far as the case mappings make it one: it is enciphered as lowercase `σ` would be.
+    pub fn greek() -> Self {
+        let mut alphabet = Alphabet::new("ΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩ").expect("valid alphabet");
+        alphabet.index.insert('ς', (17, true));
+        alphabet
+    }
+
+    /// The 33 letters of the Russian alphabet, `А` to `Я` with `Ё` after `Е`.
+    pub fn russian() -> Self {
+        Alphabet::new("АБВГДЕЁЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯ").expect("valid alphabet")
+    }
+
+    /// The 29 letters of the Turkish alphabet, with dotless `I ı` and dotted `İ i` as separate
+    /// letters.
+    pub fn turkish() -> Self {
+        Alphabet::with_cases(
+            "ABCÇDEFGĞHIİJKLMNOÖPRSŞTUÜVYZ",
+            "abcçdefgğhıijklmnoöprsştuüvyz",
+        )
+        .expect("valid alphabet")
+    }
+
+    /// Returns the number of letters.
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    /// Returns `false`, since an alphabet has at least one letter.
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    /// Returns the letters in order, in the case they were given.
+    pub fn letters(&self) -> String {
+        self.letters.iter().collect()
+    }
+
+    /// Returns `true` if this is [`Alphabet::latin`], whichever case it was given in.
+    pub fn is_latin(&self) -> bool {
+        self.len() == 26 && ('A'..='Z').all(|c| self.index_of(c) == Some((c as u8 - b'A') as usize))
+    }
+
+    /// Returns the position of a letter in either case, or `None` if it is not in the alphabet.
+    pub fn index_of(&self, c: char) -> Option<usize> {
+        self.index.get(&c).map(|&(i, _)| i)
+    }
+
+    /// Returns the letter at a position, counting round from the end to the start, in the case
+    /// the letters were given.
+    pub fn letter(&self, index: usize) -> char {
+        self.letters[index % self.len()]
+    }
+
+    /// Replaces a letter by the one at the position `f` gives for its own, counting round, in
+    /// the same case. Characters outside the alphabet are returned unchanged.
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// let greek = Alphabet::greek();
+    /// assert_eq!(greek.replace('ω', |i| i + 1), 'α');
+    /// assert_eq!(greek.replace('!', |i| i + 1), '!');
+    /// ```
+    pub fn replace(&self, c: char, f: impl FnOnce(usize) -> usize) -> char {
+        match self.index.get(&c) {
+            Some(&(i, other)) => {
+                let j = f(i) % self.len();
+                if other {
+                    self.others[j]
+                } else {
+                    self.letters[j]
+                }
+            }
+            None => c,
+        }
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet::latin()
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.letters())
+    }
+}
+
+impl FromStr for Alphabet {
+    type Err = String;
+
+    /// Reads `latin`, `greek`, `russian` or `turkish`, in any case, or else the letters of the
+    /// alphabet.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_ascii_lowercase().as_str() {
+            "latin" => Ok(Alphabet::latin()),
+            "greek" => Ok(Alphabet::greek()),
+            "russian" | "cyrillic" => Ok(Alphabet::russian()),
+            "turkish" => Ok(Alphabet::turkish()),
+            _ => Alphabet::new(s),
+        }
+    }
+}
+
+// Returns the only character of a case conversion, or `None` if it gave several.
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alphabet_cases() {
+        let greek = Alphabet::greek();
+        assert_eq!(greek.len(), 24);
+        assert_eq!(greek.index_of('Σ'), Some(17));
+        assert_eq!(greek.index_of('σ'), Some(17));
+        assert_eq!(greek.replace('ς', |i| i + 1), 'τ');
+        assert_eq!(greek.letter(24), 'Α');
+
+        let turkish = Alphabet::turkish();
+        assert_eq!(turkish.replace('ı', |i| i + 1), 'i');
+        assert_eq!(turkish.replace('I', |i| i + 1), 'İ');
+        assert_eq!(turkish.replace('x', |i| i + 1), 'x');
+
+        let symbols = Alphabet::new("♠♥♦♣").unwrap();
+        assert_eq!(symbols.replace('♣', |i| i + 1), '♠');
+        assert!(Alphabet::latin().is_latin());
+        assert!(Alphabet::new("abcdefghijklmnopqrstuvwxyz")
+            .unwrap()
+            .is_latin());
+        assert!(!symbols.is_latin());
+    }
+
+    #[test]
+    fn test_alphabet_rejects_repeats() {
+        assert!(Alphabet::new("").is_err());
+        assert!(Alphabet::new("abcA").is_err());
+        assert!(Alphabet::with_cases("AB", "a").is_err());
+        assert_eq!("Greek".parse(), Ok(Alphabet::greek()));
+        assert_eq!("xyz".parse::<Alphabet>().unwrap().letters(), "xyz");
+    }
+}
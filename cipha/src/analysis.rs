@@ -0,0 +1,2112 @@
+//! Cryptanalysis helpers for classical ciphers.
+//!
+//! These functions do not encrypt or decrypt on their own, they help recover
+//! keys or plaintext from ciphertexts produced by the ciphers in this crate.
+
+use crate::analysis::beam::BeamDecoder;
+use crate::budget::{Budget, BudgetTracker};
+use crate::ciphers::{
+    AffineCipher, AtbashCipher, CaesarCipher, Cipher, DecimationCipher, SubstitutionCipher,
+    VigenereCipher, UNKNOWN,
+};
+use crate::math::{gcd, modulo};
+
+pub mod beam;
+pub mod prefix;
+pub mod stream;
+
+#[cfg(feature = "hash")]
+pub mod digest;
+
+/// Relative frequencies (in percent) of the letters `A`..=`Z` in English text.
+pub const ENGLISH_FREQUENCIES: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Relative frequencies (in percent) of the letters `A`..=`Z` in German text, with umlauts and
+/// `ß` left out.
+pub const GERMAN_FREQUENCIES: [f64; 26] = [
+    6.516, 1.886, 2.732, 5.076, 16.396, 1.656, 3.009, 4.577, 6.550, 0.268, 1.417, 3.437, 2.534,
+    9.776, 2.594, 0.670, 0.018, 7.003, 7.270, 6.154, 4.166, 0.846, 1.921, 0.034, 0.039, 1.134,
+];
+
+/// Relative frequencies (in percent) of the letters `A`..=`Z` in French text, with accented
+/// letters left out.
+pub const FRENCH_FREQUENCIES: [f64; 26] = [
+    7.636, 0.901, 3.260, 3.669, 14.715, 1.066, 0.866, 0.737, 7.529, 0.613, 0.074, 5.456, 2.968,
+    7.095, 5.796, 2.521, 1.362, 6.693, 7.948, 7.244, 6.311, 1.838, 0.049, 0.427, 0.128, 0.326,
+];
+
+/// Relative frequencies (in percent) of the letters `A`..=`Z` in Spanish text, with accented
+/// letters and `Ñ` left out.
+pub const SPANISH_FREQUENCIES: [f64; 26] = [
+    11.525, 2.215, 4.019, 5.010, 12.181, 0.692, 1.768, 0.703, 6.247, 0.493, 0.011, 4.967, 3.157,
+    6.712, 8.683, 2.510, 0.877, 6.871, 7.977, 4.632, 2.927, 1.138, 0.017, 0.215, 1.008, 0.467,
+];
+
+/// A language with a table of letter frequencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    /// Returns the relative frequencies (in percent) of the letters `A`..=`Z` in the language.
+    pub fn frequencies(self) -> &'static [f64; 26] {
+        match self {
+            Language::English => &ENGLISH_FREQUENCIES,
+            Language::German => &GERMAN_FREQUENCIES,
+            Language::French => &FRENCH_FREQUENCIES,
+            Language::Spanish => &SPANISH_FREQUENCIES,
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = String;
+
+    /// Reads a language from its English name or its two-letter code, such as `german` or `de`.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "en" | "english" => Ok(Language::English),
+            "de" | "german" => Ok(Language::German),
+            "fr" | "french" => Ok(Language::French),
+            "es" | "spanish" => Ok(Language::Spanish),
+            _ => Err(format!(
+                "Unknown language {:?} (expected en, de, fr or es)",
+                name
+            )),
+        }
+    }
+}
+
+// The most common English bigrams with their frequencies in percent.
+const COMMON_BIGRAMS: &[(&str, f64)] = &[
+    ("TH", 3.56),
+    ("HE", 3.07),
+    ("IN", 2.43),
+    ("ER", 2.05),
+    ("AN", 1.99),
+    ("RE", 1.85),
+    ("ON", 1.76),
+    ("AT", 1.49),
+    ("EN", 1.45),
+    ("ND", 1.35),
+    ("TI", 1.34),
+    ("ES", 1.34),
+    ("OR", 1.28),
+    ("TE", 1.20),
+    ("OF", 1.17),
+    ("ED", 1.17),
+    ("IS", 1.13),
+    ("IT", 1.12),
+    ("AL", 1.09),
+    ("AR", 1.07),
+    ("ST", 1.05),
+    ("TO", 1.04),
+    ("NT", 1.04),
+    ("NG", 0.95),
+    ("SE", 0.93),
+    ("HA", 0.93),
+    ("AS", 0.87),
+    ("OU", 0.87),
+    ("IO", 0.83),
+    ("LE", 0.83),
+    ("VE", 0.83),
+    ("CO", 0.79),
+    ("ME", 0.79),
+    ("DE", 0.76),
+    ("HI", 0.76),
+    ("RI", 0.73),
+    ("RO", 0.73),
+    ("IC", 0.70),
+    ("NE", 0.69),
+    ("EA", 0.69),
+    ("RA", 0.69),
+    ("CE", 0.65),
+    ("LI", 0.62),
+    ("CH", 0.60),
+    ("LL", 0.58),
+    ("BE", 0.58),
+    ("MA", 0.57),
+    ("SI", 0.55),
+    ("OM", 0.55),
+    ("UR", 0.54),
+];
+
+// Score given to bigrams involving characters that are not letters.
+const NON_ALPHA_SCORE: f64 = -3.0;
+
+/// Scores how likely the letter `a` is to be followed by `b` in English text.
+///
+/// The score is a base-10 log probability, so higher (closer to zero) is better.
+/// Common bigrams use their measured frequency, every other pair of letters is
+/// estimated from single letter frequencies with a penalty. A pair with an
+/// [unknown](crate::ciphers::UNKNOWN) letter scores what its possible letters score on
+/// average, weighted by their English frequency, so solvers neither favour nor avoid gaps.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::bigram_score;
+/// assert!(bigram_score('T', 'H') > bigram_score('Q', 'Z'));
+/// ```
+pub fn bigram_score(a: char, b: char) -> f64 {
+    let a = a.to_ascii_uppercase();
+    let b = b.to_ascii_uppercase();
+    let is_letter = |c: char| c.is_ascii_uppercase() || c == UNKNOWN;
+    if !is_letter(a) || !is_letter(b) {
+        return NON_ALPHA_SCORE;
+    }
+    if a == UNKNOWN || b == UNKNOWN {
+        return unknown_bigram_score(a, b);
+    }
+
+    for (bigram, frequency) in COMMON_BIGRAMS {
+        let mut chars = bigram.chars();
+        if chars.next() == Some(a) && chars.next() == Some(b) {
+            return (frequency / 100.0).log10();
+        }
+    }
+
+    let pa = ENGLISH_FREQUENCIES[(a as u8 - b'A') as usize] / 100.0;
+    let pb = ENGLISH_FREQUENCIES[(b as u8 - b'A') as usize] / 100.0;
+    (pa * pb).log10() - 0.5
+}
+
+/// The result of a multiple anagramming attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnagramSolution {
+    /// The order in which the ciphertext columns must be read to rebuild the plaintext rows.
+    pub column_order: Vec<usize>,
+    /// The recovered plaintexts, in the same order as the ciphertexts.
+    pub plaintexts: Vec<String>,
+    /// The total bigram score of the solution.
+    pub score: f64,
+    /// Whether the search finished, `false` if the budget ran out and this is the best partial result.
+    pub complete: bool,
+}
+
+// Above this many columns an exhaustive search is too slow and a greedy search is used.
+const EXHAUSTIVE_COLUMN_LIMIT: usize = 8;
+
+/// Recovers the column order of several columnar transposition ciphertexts by multiple anagramming.
+///
+/// All ciphertexts must have the same length and must have been enciphered with the same key,
+/// using a complete columnar transposition with `columns` columns (the ciphertext is the
+/// plaintext columns read off one after another). The columns of every message are placed
+/// side by side and ordered so that the bigrams formed across all messages at once read
+/// like English, which is far more reliable than anagramming a single message.
+///
+/// # Errors
+///
+/// Returns an error if no ciphertexts are given, if their lengths differ, or if the length is
+/// not a multiple of `columns`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::multiple_anagram;
+/// let solution = multiple_anagram(&["EHTWADNNTRA", "EOTSTHEERHH"], 4);
+/// assert!(solution.is_err()); // 11 characters do not fill 4 columns
+///
+/// let solution = multiple_anagram(&["HTTAKAAAENECTTEDNW"], 3).unwrap();
+/// assert_eq!(solution.column_order.len(), 3);
+/// ```
+pub fn multiple_anagram(ciphertexts: &[&str], columns: usize) -> Result<AnagramSolution, String> {
+    multiple_anagram_with_budget(ciphertexts, columns, &Budget::unlimited())
+}
+
+/// Like [`multiple_anagram`], but stops searching when `budget` runs out.
+///
+/// The greedy starting order is always computed, so a result is returned even with an empty
+/// budget; `complete` is `false` when the search was cut short.
+pub fn multiple_anagram_with_budget(
+    ciphertexts: &[&str],
+    columns: usize,
+    budget: &Budget,
+) -> Result<AnagramSolution, String> {
+    multiple_anagram_with_tracker(ciphertexts, columns, &mut budget.start())
+}
+
+// Like `multiple_anagram_with_budget`, but spends a budget shared with a cleanup of the
+// plaintexts.
+fn multiple_anagram_with_tracker(
+    ciphertexts: &[&str],
+    columns: usize,
+    tracker: &mut BudgetTracker,
+) -> Result<AnagramSolution, String> {
+    if ciphertexts.is_empty() {
+        return Err("At least one ciphertext is required".to_string());
+    }
+    if columns < 2 {
+        return Err("At least two columns are required".to_string());
+    }
+
+    let messages: Vec<Vec<char>> = ciphertexts.iter().map(|c| c.chars().collect()).collect();
+    let length = messages[0].len();
+    if messages.iter().any(|m| m.len() != length) {
+        return Err("All ciphertexts must have the same length".to_string());
+    }
+    if length == 0 || !length.is_multiple_of(columns) {
+        return Err(format!(
+            "Ciphertext length {} is not a multiple of {} columns",
+            length, columns
+        ));
+    }
+    let rows = length / columns;
+
+    // scores[i][j] is how well column j follows column i across every row of every message
+    let mut scores = vec![vec![0.0; columns]; columns];
+    for (i, row) in scores.iter_mut().enumerate() {
+        for (j, score) in row.iter_mut().enumerate() {
+            if i == j {
+                continue;
+            }
+            for message in &messages {
+                for r in 0..rows {
+                    *score += bigram_score(message[i * rows + r], message[j * rows + r]);
+                }
+            }
+        }
+    }
+
+    let mut best = best_order_greedy(&scores, tracker);
+    if columns <= EXHAUSTIVE_COLUMN_LIMIT {
+        best_order_exhaustive(&scores, tracker, &mut best);
+    }
+    let (column_order, score) = best;
+
+    let plaintexts = messages
+        .iter()
+        .map(|message| {
+            let mut plaintext = String::with_capacity(length);
+            for r in 0..rows {
+                for &column in &column_order {
+                    plaintext.push(message[column * rows + r]);
+                }
+            }
+            plaintext
+        })
+        .collect();
+
+    Ok(AnagramSolution {
+        column_order,
+        plaintexts,
+        score,
+        complete: !tracker.exhausted(),
+    })
+}
+
+/// Like [`multiple_anagram_with_budget`], but cleans up the recovered plaintexts with a beam
+/// decoder as a last step. The search and the cleanup share the budget.
+///
+/// # Errors
+///
+/// Returns an error for the same inputs as [`multiple_anagram`].
+pub fn multiple_anagram_with_beam(
+    ciphertexts: &[&str],
+    columns: usize,
+    beam: &BeamDecoder,
+    budget: &Budget,
+) -> Result<AnagramSolution, String> {
+    let mut tracker = budget.start();
+    let solution = multiple_anagram_with_tracker(ciphertexts, columns, &mut tracker)?;
+    let plaintexts = solution
+        .plaintexts
+        .iter()
+        .map(|plaintext| beam.decode_with_tracker(plaintext, &mut tracker))
+        .collect();
+    Ok(AnagramSolution {
+        plaintexts,
+        ..solution
+    })
+}
+
+// Scores an order of columns by summing the scores of adjacent columns.
+fn order_score(scores: &[Vec<f64>], order: &[usize]) -> f64 {
+    order.windows(2).map(|pair| scores[pair[0]][pair[1]]).sum()
+}
+
+// Tries every permutation of the columns, improving on `best` until the budget runs out.
+fn best_order_exhaustive(
+    scores: &[Vec<f64>],
+    tracker: &mut BudgetTracker,
+    best: &mut (Vec<usize>, f64),
+) {
+    fn search(
+        scores: &[Vec<f64>],
+        order: &mut Vec<usize>,
+        used: &mut Vec<bool>,
+        current: f64,
+        best: &mut (Vec<usize>, f64),
+        tracker: &mut BudgetTracker,
+    ) {
+        if order.len() == scores.len() {
+            if current > best.1 {
+                *best = (order.clone(), current);
+            }
+            return;
+        }
+        for next in 0..scores.len() {
+            if used[next] {
+                continue;
+            }
+            if !tracker.tick() {
+                return;
+            }
+            let gain = order.last().map_or(0.0, |&last| scores[last][next]);
+            used[next] = true;
+            order.push(next);
+            search(scores, order, used, current + gain, best, tracker);
+            order.pop();
+            used[next] = false;
+        }
+    }
+
+    search(
+        scores,
+        &mut Vec::new(),
+        &mut vec![false; scores.len()],
+        0.0,
+        best,
+        tracker,
+    );
+}
+
+// Builds an order from every starting column by always appending the best following column.
+//
+// The first starting column is always tried so there is a result even with an empty budget.
+fn best_order_greedy(scores: &[Vec<f64>], tracker: &mut BudgetTracker) -> (Vec<usize>, f64) {
+    let mut best = (Vec::new(), f64::NEG_INFINITY);
+    for start in 0..scores.len() {
+        if start > 0 && !tracker.tick() {
+            break;
+        }
+        let mut order = vec![start];
+        let mut used = vec![false; scores.len()];
+        used[start] = true;
+        while order.len() < scores.len() {
+            let last = *order.last().unwrap();
+            let next = (0..scores.len())
+                .filter(|&c| !used[c])
+                .max_by(|&a, &b| scores[last][a].total_cmp(&scores[last][b]))
+                .unwrap();
+            used[next] = true;
+            order.push(next);
+        }
+        let score = order_score(scores, &order);
+        if score > best.1 {
+            best = (order, score);
+        }
+    }
+    best
+}
+
+// Returns the expected bigram score when one or both letters are unknown.
+fn unknown_bigram_score(a: char, b: char) -> f64 {
+    let candidates = |c: char| -> Vec<(char, f64)> {
+        if c == UNKNOWN {
+            ('A'..='Z').zip(ENGLISH_FREQUENCIES).collect()
+        } else {
+            vec![(c, 1.0)]
+        }
+    };
+    let (mut total, mut weight) = (0.0, 0.0);
+    for (x, wx) in candidates(a) {
+        for (y, wy) in candidates(b) {
+            total += wx * wy * bigram_score(x, y);
+            weight += wx * wy;
+        }
+    }
+    total / weight
+}
+
+/// A candidate decryption found by a brute-force attack.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// The key that produced the plaintext.
+    pub key: usize,
+    /// The decrypted text.
+    pub plaintext: String,
+    /// The [`english_score`] of the plaintext, higher is more English-like.
+    pub score: f64,
+}
+
+/// Scores how English-like a text is as the mean [`bigram_score`] of adjacent letters within words.
+///
+/// Pairs that straddle a word boundary are skipped, while runs of whitespace count as unlikely
+/// bigrams, so candidates that scramble the word structure score lower. Unknown letters count
+/// as letters with an average score. Texts without any scored pair score negative infinity.
+pub fn english_score(text: &str) -> f64 {
+    let is_letter = |c: char| c.is_ascii_alphabetic() || c == UNKNOWN;
+    let chars: Vec<char> = text.chars().collect();
+    let scores: Vec<f64> = chars
+        .windows(2)
+        .filter_map(|pair| match (pair[0], pair[1]) {
+            (a, b) if is_letter(a) && is_letter(b) => Some(bigram_score(a, b)),
+            (a, b) if a.is_whitespace() && b.is_whitespace() => Some(NON_ALPHA_SCORE),
+            _ => None,
+        })
+        .collect();
+    if scores.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+    scores.iter().sum::<f64>() / scores.len() as f64
+}
+
+/// The [`english_score`] below which a text does not look like English.
+///
+/// English sentences score around -2.5 and rarely below -3.2, while text deciphered with a
+/// wrong key or cipher usually scores -3.5 or lower.
+pub const ENGLISH_SCORE_THRESHOLD: f64 = -3.3;
+
+// Fewest letters a text needs before its English score is judged.
+const MIN_SCORED_LETTERS: usize = 10;
+
+/// Returns `true` unless the text scores below [`ENGLISH_SCORE_THRESHOLD`].
+///
+/// Texts with fewer than ten letters are too short to judge and always look like English.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::looks_like_english;
+/// assert!(looks_like_english("Meet me at the old bridge at noon"));
+/// assert!(!looks_like_english("Xpsg xp eg fkt cpf dcxdjt"));
+/// ```
+pub fn looks_like_english(text: &str) -> bool {
+    let letters = text
+        .chars()
+        .filter(|&c| c.is_ascii_alphabetic() || c == UNKNOWN)
+        .count();
+    letters < MIN_SCORED_LETTERS || english_score(text) >= ENGLISH_SCORE_THRESHOLD
+}
+
+/// Deciphers a decimation (skip) ciphertext with every valid skip, best candidates first.
+///
+/// Every skip from 1 up to the message length that is coprime with the length is tried, and the
+/// candidates are ranked by [`english_score`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::brute_force_decimation;
+/// let candidates = brute_force_decimation("T T  FEHTAIFTNEOLSIE");
+/// assert_eq!(candidates[0].key, 3);
+/// assert_eq!(candidates[0].plaintext, "THE TOTAL IS FIFTEEN");
+/// ```
+pub fn brute_force_decimation(ciphertext: &str) -> Vec<Candidate> {
+    ranked(decimation_candidates(ciphertext))
+}
+
+/// Like [`brute_force_decimation`], but only tries the skips the budget allows, one iteration
+/// each, and ranks the candidates found so far.
+pub fn brute_force_decimation_with_budget(ciphertext: &str, budget: &Budget) -> Vec<Candidate> {
+    ranked(decimation_candidates_with_budget(ciphertext, budget))
+}
+
+/// Like [`brute_force_decimation_with_budget`], but cleans up the plaintexts with a beam decoder
+/// as a last step, see [`brute_force_caesar_with_beam`].
+pub fn brute_force_decimation_with_beam(
+    ciphertext: &str,
+    beam: &BeamDecoder,
+    budget: &Budget,
+) -> Vec<Candidate> {
+    let mut tracker = budget.start();
+    let candidates = ranked(decimation_candidates_with_tracker(ciphertext, &mut tracker));
+    cleaned(candidates, beam, &mut tracker)
+}
+
+/// Deciphers a Caesar ciphertext with every shift from 0 to 25, best candidates first.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::brute_force_caesar;
+/// let candidates = brute_force_caesar("Wkh wrwdo lv iliwhhq");
+/// assert_eq!(candidates[0].key, 3);
+/// assert_eq!(candidates[0].plaintext, "The total is fifteen");
+/// ```
+pub fn brute_force_caesar(ciphertext: &str) -> Vec<Candidate> {
+    ranked(caesar_candidates(ciphertext))
+}
+
+/// Like [`brute_force_caesar`], but only tries the shifts the budget allows, one iteration
+/// each, and ranks the candidates found so far.
+pub fn brute_force_caesar_with_budget(ciphertext: &str, budget: &Budget) -> Vec<Candidate> {
+    ranked(caesar_candidates_with_budget(ciphertext, budget))
+}
+
+/// Like [`brute_force_caesar_with_budget`], but cleans up the plaintexts with a beam decoder as
+/// a last step.
+///
+/// The candidates are cleaned best first while the budget lasts, and keep the order of their
+/// scores before the cleanup, which makes any text look more like English.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::beam::{BeamDecoder, NgramModel};
+/// use cipha::analysis::brute_force_caesar_with_beam;
+/// use cipha::budget::Budget;
+///
+/// // One letter of the ciphertext was garbled
+/// let beam = BeamDecoder::new(NgramModel::english());
+/// let candidates = brute_force_caesar_with_beam("Wkh wrwdo lv iliwahq", &beam, &Budget::unlimited());
+/// assert_eq!(candidates[0].key, 3);
+/// assert_eq!(candidates[0].plaintext, "The total is fifteen");
+/// ```
+pub fn brute_force_caesar_with_beam(
+    ciphertext: &str,
+    beam: &BeamDecoder,
+    budget: &Budget,
+) -> Vec<Candidate> {
+    let mut tracker = budget.start();
+    let candidates = ranked(caesar_candidates_with_tracker(ciphertext, &mut tracker));
+    cleaned(candidates, beam, &mut tracker)
+}
+
+/// Lazily deciphers a decimation ciphertext with every valid skip, in order of the skip.
+///
+/// Unlike [`brute_force_decimation`] nothing is deciphered until it is asked for, so a search
+/// such as [`Crib::find`] can stop early.
+pub fn decimation_candidates(ciphertext: &str) -> impl Iterator<Item = Candidate> + '_ {
+    decimation_candidates_with_budget(ciphertext, &Budget::unlimited())
+}
+
+/// Like [`decimation_candidates`], but ends when the budget runs out. Every skip tried is one
+/// iteration, and the time counts from this call.
+pub fn decimation_candidates_with_budget<'a>(
+    ciphertext: &'a str,
+    budget: &Budget,
+) -> impl Iterator<Item = Candidate> + 'a {
+    let mut tracker = budget.start();
+    decimation_candidates_while(ciphertext, move || tracker.tick())
+}
+
+/// Like [`decimation_candidates_with_budget`], but spends a budget shared with other stages of a
+/// search.
+pub fn decimation_candidates_with_tracker<'a>(
+    ciphertext: &'a str,
+    tracker: &'a mut BudgetTracker,
+) -> impl Iterator<Item = Candidate> + 'a {
+    decimation_candidates_while(ciphertext, move || tracker.tick())
+}
+
+// Deciphers with every valid skip while `tick` allows it.
+fn decimation_candidates_while<'a>(
+    ciphertext: &'a str,
+    mut tick: impl FnMut() -> bool + 'a,
+) -> impl Iterator<Item = Candidate> + 'a {
+    let len = ciphertext.chars().count();
+    (1..len.max(2))
+        .take_while(move |_| tick())
+        .filter_map(move |skip| {
+            let plaintext = DecimationCipher::new(skip)
+                .ok()?
+                .decipher(ciphertext)
+                .ok()?;
+            Some(Candidate {
+                key: skip,
+                score: english_score(&plaintext),
+                plaintext,
+            })
+        })
+}
+
+/// Lazily deciphers a Caesar ciphertext with every shift from 0 to 25, in order of the shift.
+pub fn caesar_candidates(ciphertext: &str) -> impl Iterator<Item = Candidate> + '_ {
+    caesar_candidates_with_budget(ciphertext, &Budget::unlimited())
+}
+
+/// Like [`caesar_candidates`], but ends when the budget runs out. Every shift tried is one
+/// iteration, and the time counts from this call.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::caesar_candidates_with_budget;
+/// use cipha::budget::Budget;
+/// let budget = Budget::unlimited().with_max_iterations(4);
+/// assert_eq!(caesar_candidates_with_budget("Wkh wrwdo", &budget).count(), 4);
+/// ```
+pub fn caesar_candidates_with_budget<'a>(
+    ciphertext: &'a str,
+    budget: &Budget,
+) -> impl Iterator<Item = Candidate> + 'a {
+    let mut tracker = budget.start();
+    caesar_candidates_while(ciphertext, move || tracker.tick())
+}
+
+/// Like [`caesar_candidates_with_budget`], but spends a budget shared with other stages of a
+/// search.
+pub fn caesar_candidates_with_tracker<'a>(
+    ciphertext: &'a str,
+    tracker: &'a mut BudgetTracker,
+) -> impl Iterator<Item = Candidate> + 'a {
+    caesar_candidates_while(ciphertext, move || tracker.tick())
+}
+
+// Deciphers with every shift while `tick` allows it.
+fn caesar_candidates_while<'a>(
+    ciphertext: &'a str,
+    mut tick: impl FnMut() -> bool + 'a,
+) -> impl Iterator<Item = Candidate> + 'a {
+    (0..26u8).take_while(move |_| tick()).map(move |shift| {
+        let plaintext = CaesarCipher::new(shift).decipher(ciphertext);
+        Candidate {
+            key: shift as usize,
+            score: english_score(&plaintext),
+            plaintext,
+        }
+    })
+}
+
+// Cleans up the candidates with the beam decoder, in order, with what is left of the budget.
+fn cleaned(
+    candidates: Vec<Candidate>,
+    beam: &BeamDecoder,
+    tracker: &mut BudgetTracker,
+) -> Vec<Candidate> {
+    candidates
+        .into_iter()
+        .map(|candidate| beam.clean_with_tracker(candidate, tracker))
+        .collect()
+}
+
+// Collects the candidates, best first.
+fn ranked(candidates: impl Iterator<Item = Candidate>) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = candidates.collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+/// Part of the answer known in advance, used to stop a search at the right candidate.
+///
+/// Only letters are compared, ignoring case, so a crib still matches a candidate whose cipher
+/// dropped the spaces and punctuation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Crib {
+    /// The whole plaintext.
+    Plaintext(String),
+    /// A word or phrase somewhere in the plaintext.
+    Contains(String),
+    /// The opening of the plaintext, such as a stereotyped `WETTERBERICHT` or `DEAR`.
+    Prefix(String),
+}
+
+impl Crib {
+    /// Returns `true` if the candidate plaintext fits the crib.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::Crib;
+    /// assert!(Crib::Contains("at dawn".to_string()).matches("ATTACKATDAWN"));
+    /// assert!(Crib::Plaintext("Attack at dawn!".to_string()).matches("attack at dawn"));
+    /// assert!(!Crib::Plaintext("attack".to_string()).matches("attack at dawn"));
+    /// assert!(Crib::Prefix("Attack at".to_string()).matches("ATTACKATDAWN"));
+    /// ```
+    pub fn matches(&self, plaintext: &str) -> bool {
+        let letters = |text: &str| -> String {
+            text.chars()
+                .filter(char::is_ascii_alphabetic)
+                .map(|c| c.to_ascii_uppercase())
+                .collect()
+        };
+        match self {
+            Crib::Plaintext(crib) => letters(plaintext) == letters(crib),
+            Crib::Contains(crib) => letters(plaintext).contains(&letters(crib)),
+            Crib::Prefix(crib) => letters(plaintext).starts_with(&letters(crib)),
+        }
+    }
+
+    /// Returns the first candidate that fits the crib, without producing the ones after it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::{caesar_candidates, Crib};
+    /// let crib = Crib::Contains("dawn".to_string());
+    /// let found = crib.find(caesar_candidates("Dwwdfn dw gdzq")).unwrap();
+    /// assert_eq!(found.key, 3);
+    /// assert_eq!(found.plaintext, "Attack at dawn");
+    /// ```
+    pub fn find(&self, candidates: impl IntoIterator<Item = Candidate>) -> Option<Candidate> {
+        candidates
+            .into_iter()
+            .find(|candidate| self.matches(&candidate.plaintext))
+    }
+}
+
+/// A substitution hypothesis that matches ciphertext letters to English letters by frequency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrequencyFit {
+    /// The `(ciphertext, plaintext)` letter pairs, most frequent ciphertext letter first.
+    ///
+    /// Only letters that occur in the ciphertext are mapped, so the mapping may be partial.
+    pub mapping: Vec<(char, char)>,
+    /// The ciphertext decoded with the mapping, with case and non-letters preserved.
+    pub plaintext: String,
+}
+
+/// Matches the ciphertext letters to English letters purely by frequency rank.
+///
+/// The most frequent ciphertext letter becomes `E`, the next `T`, and so on down
+/// `ETAOINSHRDLCUMWFGYPBVKJXQZ`, with ties broken alphabetically. This is rarely the right
+/// answer on its own, but it is a quick starting hypothesis to refine by hand or with a
+/// hill-climber.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::frequency_fit;
+/// let fit = frequency_fit("XLI XIEQ");
+/// assert_eq!(fit.mapping[0], ('I', 'E'));
+/// assert_eq!(fit.plaintext, "TOE TEAI");
+/// ```
+pub fn frequency_fit(ciphertext: &str) -> FrequencyFit {
+    let mut english: Vec<usize> = (0..26).collect();
+    english.sort_by(|&a, &b| ENGLISH_FREQUENCIES[b].total_cmp(&ENGLISH_FREQUENCIES[a]));
+
+    let mut counts = [0usize; 26];
+    for c in ciphertext.chars().filter(char::is_ascii_alphabetic) {
+        counts[(c.to_ascii_uppercase() as u8 - b'A') as usize] += 1;
+    }
+    let mut cipher_letters: Vec<usize> = (0..26).filter(|&i| counts[i] > 0).collect();
+    // Stable sort, so ties stay in alphabetical order
+    cipher_letters.sort_by_key(|&i| std::cmp::Reverse(counts[i]));
+
+    let mut table = [None; 26];
+    let mapping: Vec<(char, char)> = cipher_letters
+        .iter()
+        .zip(&english)
+        .map(|(&c, &p)| {
+            table[c] = Some((b'A' + p as u8) as char);
+            ((b'A' + c as u8) as char, (b'A' + p as u8) as char)
+        })
+        .collect();
+    let plaintext = ciphertext
+        .chars()
+        .map(|c| {
+            match c
+                .is_ascii_alphabetic()
+                .then(|| table[(c.to_ascii_uppercase() as u8 - b'A') as usize])
+                .flatten()
+            {
+                Some(p) if c.is_ascii_lowercase() => p.to_ascii_lowercase(),
+                Some(p) => p,
+                None => c,
+            }
+        })
+        .collect();
+    FrequencyFit { mapping, plaintext }
+}
+
+/// The kind of symbols a keystream is made of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeystreamKind {
+    /// A stream of `0` and `1` characters, e.g. an LFSR output.
+    Bits,
+    /// A stream of decimal digits, e.g. a numeric one-time pad.
+    Digits,
+    /// A stream of letters, e.g. a Solitaire keystream or a letter one-time pad.
+    Letters,
+    /// Arbitrary binary data, tested bit by bit.
+    Bytes,
+}
+
+/// A keystream decoded into symbols ready for statistical testing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keystream {
+    /// How the keystream was interpreted.
+    pub kind: KeystreamKind,
+    /// The symbols, each in `0..alphabet_size`.
+    pub symbols: Vec<u8>,
+    /// The number of possible symbols.
+    pub alphabet_size: usize,
+}
+
+impl Keystream {
+    /// Interprets raw keystream data, ignoring whitespace.
+    ///
+    /// Text made only of `0`/`1` is read as bits, text made only of digits as decimal digits
+    /// and text made only of letters as letters `A`..=`Z`. Anything else is treated as binary
+    /// data and split into bits.
+    pub fn parse(data: &[u8]) -> Self {
+        let text: Vec<u8> = data
+            .iter()
+            .copied()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        if !text.is_empty() && text.iter().all(|b| *b == b'0' || *b == b'1') {
+            Keystream {
+                kind: KeystreamKind::Bits,
+                symbols: text.iter().map(|b| b - b'0').collect(),
+                alphabet_size: 2,
+            }
+        } else if !text.is_empty() && text.iter().all(|b| b.is_ascii_digit()) {
+            Keystream {
+                kind: KeystreamKind::Digits,
+                symbols: text.iter().map(|b| b - b'0').collect(),
+                alphabet_size: 10,
+            }
+        } else if !text.is_empty() && text.iter().all(|b| b.is_ascii_alphabetic()) {
+            Keystream {
+                kind: KeystreamKind::Letters,
+                symbols: text.iter().map(|b| b.to_ascii_uppercase() - b'A').collect(),
+                alphabet_size: 26,
+            }
+        } else {
+            Keystream {
+                kind: KeystreamKind::Bytes,
+                symbols: data
+                    .iter()
+                    .flat_map(|b| (0..8).rev().map(move |i| (b >> i) & 1))
+                    .collect(),
+                alphabet_size: 2,
+            }
+        }
+    }
+}
+
+/// The outcome of a single statistical test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessTest {
+    /// The name of the test.
+    pub name: &'static str,
+    /// The test statistic (a chi-squared value or a z-score depending on the test).
+    pub statistic: f64,
+    /// Whether the statistic lies inside the acceptance region, `None` if there was not enough data.
+    pub passed: Option<bool>,
+}
+
+/// The results of running the frequency, runs and poker tests over a keystream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessReport {
+    /// How the keystream was interpreted.
+    pub kind: KeystreamKind,
+    /// The number of symbols tested.
+    pub length: usize,
+    /// The individual test results.
+    pub tests: Vec<RandomnessTest>,
+}
+
+impl RandomnessReport {
+    /// Returns `true` if no test failed.
+    pub fn passed(&self) -> bool {
+        self.tests.iter().all(|t| t.passed != Some(false))
+    }
+}
+
+impl std::fmt::Display for RandomnessReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Keystream: {} symbols ({:?})", self.length, self.kind)?;
+        for test in &self.tests {
+            let verdict = match test.passed {
+                Some(true) => "PASS",
+                Some(false) => "FAIL",
+                None => "SKIPPED (not enough data)",
+            };
+            writeln!(
+                f,
+                "{}: statistic={:.3} {}",
+                test.name, test.statistic, verdict
+            )?;
+        }
+        write!(
+            f,
+            "Overall: {}",
+            if self.passed() { "PASS" } else { "FAIL" }
+        )
+    }
+}
+
+// Two sided z value for a 99.9% acceptance region.
+const Z_CRITICAL: f64 = 3.29;
+
+// Returns the lower and upper 99.9% bounds of a chi-squared distribution (Wilson-Hilferty).
+fn chi_squared_bounds(dof: f64) -> (f64, f64) {
+    let bound = |z: f64| {
+        let c = 2.0 / (9.0 * dof);
+        dof * (1.0 - c + z * c.sqrt()).powi(3)
+    };
+    (bound(-Z_CRITICAL).max(0.0), bound(Z_CRITICAL))
+}
+
+// Computes the chi-squared statistic of observed counts against a uniform distribution.
+fn chi_squared_uniform(counts: &[usize], total: usize) -> f64 {
+    let expected = total as f64 / counts.len() as f64;
+    counts
+        .iter()
+        .map(|&c| (c as f64 - expected).powi(2) / expected)
+        .sum()
+}
+
+/// Checks that every symbol occurs about equally often (chi-squared test).
+pub fn frequency_test(keystream: &Keystream) -> RandomnessTest {
+    let n = keystream.symbols.len();
+    let mut counts = vec![0; keystream.alphabet_size];
+    for &s in &keystream.symbols {
+        counts[s as usize] += 1;
+    }
+    let statistic = chi_squared_uniform(&counts, n);
+    let (low, high) = chi_squared_bounds((keystream.alphabet_size - 1) as f64);
+    RandomnessTest {
+        name: "frequency",
+        statistic,
+        passed: (n >= 5 * keystream.alphabet_size).then_some(statistic >= low && statistic <= high),
+    }
+}
+
+/// Checks that runs of repeated symbols are neither too frequent nor too rare (z-score).
+///
+/// For independent uniform symbols each neighbouring pair differs with probability
+/// `1 - 1/k`, so the number of runs has a known mean and variance.
+pub fn runs_test(keystream: &Keystream) -> RandomnessTest {
+    let n = keystream.symbols.len();
+    if n < 20 {
+        return RandomnessTest {
+            name: "runs",
+            statistic: 0.0,
+            passed: None,
+        };
+    }
+    let changes = keystream
+        .symbols
+        .windows(2)
+        .filter(|w| w[0] != w[1])
+        .count() as f64;
+    let p = 1.0 - 1.0 / keystream.alphabet_size as f64;
+    let mean = (n - 1) as f64 * p;
+    let variance = (n - 1) as f64 * p * (1.0 - p);
+    let statistic = (changes - mean) / variance.sqrt();
+    RandomnessTest {
+        name: "runs",
+        statistic,
+        passed: Some(statistic.abs() <= Z_CRITICAL),
+    }
+}
+
+/// Checks that every combination of consecutive symbols is equally likely (poker test).
+///
+/// The keystream is cut into non-overlapping hands of `m` symbols, with `m` as large as
+/// possible while every hand is still expected about five times (4 bits per hand for binary
+/// keystreams, as in FIPS 140-1).
+pub fn poker_test(keystream: &Keystream) -> RandomnessTest {
+    let n = keystream.symbols.len();
+    let k = keystream.alphabet_size;
+    let mut hand = 1;
+    while hand < 4 && k.pow(hand as u32 + 1) * 5 <= n / (hand + 1) {
+        hand += 1;
+    }
+    let categories = k.pow(hand as u32);
+    let hands = n / hand;
+    if hand < 2 || hands < 5 * categories {
+        return RandomnessTest {
+            name: "poker",
+            statistic: 0.0,
+            passed: None,
+        };
+    }
+
+    let mut counts = vec![0; categories];
+    for chunk in keystream.symbols.chunks_exact(hand) {
+        let index = chunk.iter().fold(0, |acc, &s| acc * k + s as usize);
+        counts[index] += 1;
+    }
+    let statistic = chi_squared_uniform(&counts, hands);
+    let (low, high) = chi_squared_bounds((categories - 1) as f64);
+    RandomnessTest {
+        name: "poker",
+        statistic,
+        passed: Some(statistic >= low && statistic <= high),
+    }
+}
+
+/// Runs the frequency, runs and poker tests over raw keystream data.
+///
+/// See [`Keystream::parse`] for how the data is interpreted.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::randomness_tests;
+/// let report = randomness_tests("01".repeat(500).as_bytes());
+/// assert!(!report.passed()); // far too regular
+/// ```
+pub fn randomness_tests(data: &[u8]) -> RandomnessReport {
+    let keystream = Keystream::parse(data);
+    RandomnessReport {
+        kind: keystream.kind,
+        length: keystream.symbols.len(),
+        tests: vec![
+            frequency_test(&keystream),
+            runs_test(&keystream),
+            poker_test(&keystream),
+        ],
+    }
+}
+
+/// A cipher together with its key material, as needed to reason about its strength.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CipherConfig {
+    /// ROT13, which has no key.
+    Rot13,
+    /// Caesar cipher with the given shift.
+    Caesar { shift: u8 },
+    /// Vigenère cipher with the given key.
+    Vigenere { key: String },
+    /// Atbash cipher, which has no key.
+    Atbash,
+    /// Rail fence cipher with the given number of rails.
+    RailFence { rails: usize },
+    /// Reversal of the message, which has no key.
+    Reverse,
+    /// Letter to number conversion, which has no key.
+    Gematria,
+    /// Morse code, which has no key.
+    Morse,
+}
+
+/// How much resistance a cipher offers to an attacker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strength {
+    /// The output is readable by anyone who recognizes the encoding.
+    None,
+    /// The key can be found by trying every possibility by hand.
+    Trivial,
+    /// The cipher falls to frequency analysis given enough text.
+    Weak,
+}
+
+/// A qualitative estimate of how resistant a configured cipher is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthEstimate {
+    /// The overall resistance.
+    pub strength: Strength,
+    /// The approximate number of keys, `1` for keyless encodings.
+    pub key_space: f64,
+    /// The approximate amount of ciphertext an attacker needs to break the cipher.
+    pub min_chars: usize,
+    /// A human readable description, e.g. "breakable by frequency analysis with ~200 chars".
+    pub summary: String,
+    /// Set when the chosen parameters are weak even for this cipher, e.g. a Caesar shift of 0.
+    pub warning: Option<String>,
+}
+
+/// Estimates how resistant a configured cipher is for a message of `message_len` characters.
+///
+/// Classical ciphers are never secure; the estimate describes which classical attack breaks the
+/// cipher and roughly how much text it needs, which is useful when teaching why.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::{estimate_strength, CipherConfig, Strength};
+/// let estimate = estimate_strength(&CipherConfig::Caesar { shift: 26 }, 100);
+/// assert_eq!(estimate.strength, Strength::None);
+/// assert!(estimate.warning.is_some());
+///
+/// let estimate = estimate_strength(&CipherConfig::Vigenere { key: "LEMON".to_string() }, 100);
+/// assert_eq!(estimate.strength, Strength::Weak);
+/// ```
+pub fn estimate_strength(config: &CipherConfig, message_len: usize) -> StrengthEstimate {
+    let keyless = |name: &str| StrengthEstimate {
+        strength: Strength::None,
+        key_space: 1.0,
+        min_chars: 1,
+        summary: format!("no key: {} is readable by anyone who recognizes it", name),
+        warning: None,
+    };
+
+    match config {
+        CipherConfig::Rot13 => keyless("ROT13"),
+        CipherConfig::Atbash => keyless("Atbash"),
+        CipherConfig::Reverse => keyless("reversed text"),
+        CipherConfig::Gematria => keyless("gematria"),
+        CipherConfig::Morse => keyless("Morse code"),
+        CipherConfig::Caesar { shift } => {
+            let warning = match shift % 26 {
+                0 => Some(format!(
+                    "a Caesar shift of {} leaves the message unchanged",
+                    shift
+                )),
+                13 => Some("a Caesar shift of 13 is ROT13, which everyone tries first".to_string()),
+                _ => None,
+            };
+            StrengthEstimate {
+                strength: if shift % 26 == 0 {
+                    Strength::None
+                } else {
+                    Strength::Trivial
+                },
+                key_space: 25.0,
+                min_chars: 5,
+                summary: "breakable by brute force over 25 shifts with ~5 chars".to_string(),
+                warning,
+            }
+        }
+        CipherConfig::Vigenere { key } => {
+            let key_len = key.chars().filter(|c| c.is_ascii_alphabetic()).count();
+            if key_len == 0 {
+                return StrengthEstimate {
+                    strength: Strength::None,
+                    key_space: 1.0,
+                    min_chars: 1,
+                    summary: "no key: the message is left unchanged".to_string(),
+                    warning: Some("an empty Vigenère key leaves the message unchanged".to_string()),
+                };
+            }
+            // Each key letter is a Caesar cipher that needs enough letters for frequency analysis
+            let min_chars = 40 * key_len;
+            let warning = if key_len == 1 {
+                Some("a one letter Vigenère key is just a Caesar cipher".to_string())
+            } else if message_len >= min_chars {
+                Some(format!(
+                    "the message is long enough ({} chars) to recover a {} letter key by frequency analysis",
+                    message_len, key_len
+                ))
+            } else {
+                None
+            };
+            StrengthEstimate {
+                strength: if key_len == 1 {
+                    Strength::Trivial
+                } else {
+                    Strength::Weak
+                },
+                key_space: 26f64.powi(key_len as i32),
+                min_chars,
+                summary: format!(
+                    "breakable by Kasiski examination and frequency analysis with ~{} chars",
+                    min_chars
+                ),
+                warning,
+            }
+        }
+        CipherConfig::RailFence { rails } => {
+            let warning = if *rails < 2 || *rails >= message_len {
+                Some(format!(
+                    "{} rails leave a {} char message unchanged",
+                    rails, message_len
+                ))
+            } else {
+                None
+            };
+            StrengthEstimate {
+                strength: if warning.is_some() {
+                    Strength::None
+                } else {
+                    Strength::Trivial
+                },
+                key_space: message_len.saturating_sub(2).max(1) as f64,
+                min_chars: 10,
+                summary: "breakable by trying every number of rails with ~10 chars".to_string(),
+                warning,
+            }
+        }
+    }
+}
+
+/// The units of a text whose letters form an acrostic or telestich.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextUnit {
+    /// Every non-blank line.
+    Lines,
+    /// Every whitespace-separated word.
+    Words,
+}
+
+// Splits the text into the units, skipping those without letters.
+fn text_units(text: &str, unit: TextUnit) -> impl Iterator<Item = &str> {
+    let units: Box<dyn Iterator<Item = &str>> = match unit {
+        TextUnit::Lines => Box::new(text.lines()),
+        TextUnit::Words => Box::new(text.split_whitespace()),
+    };
+    units.filter(|unit| unit.chars().any(char::is_alphabetic))
+}
+
+/// Extracts the acrostic of a text: the first letter of every line or word.
+///
+/// Leading punctuation and digits are skipped, so `"(Every)"` contributes `E`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::{acrostic, TextUnit};
+/// let poem = "Hidden in plain sight\nEvery line begins\nLike a whisper\nLost in the margin\nOnly the first letters speak";
+/// assert_eq!(acrostic(poem, TextUnit::Lines), "HELLO");
+/// ```
+pub fn acrostic(text: &str, unit: TextUnit) -> String {
+    text_units(text, unit)
+        .filter_map(|unit| unit.chars().find(|c| c.is_alphabetic()))
+        .collect()
+}
+
+/// Extracts the telestich of a text: the last letter of every line or word.
+///
+/// Trailing punctuation and digits are skipped, so `"end."` contributes `d`.
+pub fn telestich(text: &str, unit: TextUnit) -> String {
+    text_units(text, unit)
+        .filter_map(|unit| unit.chars().rev().find(|c| c.is_alphabetic()))
+        .collect()
+}
+
+// Returns the Roman numeral value of a letter, reading U as V, J as I and W as VV.
+fn numeral_value(c: char) -> u32 {
+    match c.to_ascii_uppercase() {
+        'I' | 'J' => 1,
+        'V' | 'U' => 5,
+        'X' | 'W' => 10,
+        'L' => 50,
+        'C' => 100,
+        'D' => 500,
+        'M' => 1000,
+        _ => 0,
+    }
+}
+
+/// Sums the Roman numeral letters of a chronogram.
+///
+/// Every numeral letter counts at its value regardless of position (`IV` adds up to 6, not 4),
+/// with `U` read as `V`, `J` as `I` and `W` as `VV`. If `marked_only` is set only uppercase
+/// letters count, as in chronograms that capitalise the numerals; otherwise case is ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::chronogram;
+/// // A chronogram for 1632, the year Gustavus Adolphus fell at Lützen
+/// assert_eq!(chronogram("ChrIstVs DVX ergo trIVMphVs", true), 1632);
+/// ```
+pub fn chronogram(text: &str, marked_only: bool) -> u32 {
+    text.chars()
+        .filter(|c| !marked_only || c.is_ascii_uppercase())
+        .map(numeral_value)
+        .sum()
+}
+
+/// The ciphers whose keys [`recover_key`] can recover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KnownPlaintextCipher {
+    /// Caesar cipher.
+    Caesar,
+    /// Affine cipher.
+    Affine,
+    /// Vigenère cipher.
+    Vigenere,
+    /// Atbash cipher, which has no key to recover.
+    Atbash,
+    /// Monoalphabetic substitution cipher.
+    Substitution,
+}
+
+/// A key recovered by [`recover_key`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveredKey {
+    /// The Caesar shift.
+    Caesar { shift: u8 },
+    /// The Affine multiplier and shift.
+    Affine { a: i64, b: i64 },
+    /// The shortest repeating Vigenère key that explains the texts.
+    Vigenere { key: String },
+    /// The texts are consistent with Atbash.
+    Atbash,
+    /// The plaintext to ciphertext letters seen in the texts, in alphabetical order. Letters
+    /// that do not occur in the plaintext are missing.
+    Substitution { mapping: Vec<(char, char)> },
+}
+
+impl RecoveredKey {
+    /// Returns the cipher with the recovered key, ready to decipher the rest of the corpus.
+    ///
+    /// A substitution key only yields a cipher once all 26 letters are known.
+    pub fn cipher(&self) -> Option<Box<dyn Cipher>> {
+        Some(match self {
+            RecoveredKey::Caesar { shift } => Box::new(CaesarCipher::new(*shift)),
+            RecoveredKey::Affine { a, b } => Box::new(AffineCipher::new(*a, *b).ok()?),
+            RecoveredKey::Vigenere { key } => Box::new(VigenereCipher::new(key)),
+            RecoveredKey::Atbash => Box::new(AtbashCipher::new()),
+            RecoveredKey::Substitution { mapping } => {
+                if mapping.len() != 26 {
+                    return None;
+                }
+                let alphabet: String = mapping.iter().map(|&(_, c)| c).collect();
+                Box::new(SubstitutionCipher::from_alphabet(&alphabet).ok()?)
+            }
+        })
+    }
+}
+
+/// Recovers the key of a cipher from a plaintext and its ciphertext.
+///
+/// Only the letters of the two texts are compared, case-insensitively, so they must contain the
+/// same number of letters.
+///
+/// # Errors
+///
+/// Returns an error if the texts have no letters or different numbers of letters, if no key of
+/// the cipher turns the plaintext into the ciphertext, or if the texts are too short to tell the
+/// possible Affine keys apart.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::{recover_key, KnownPlaintextCipher, RecoveredKey};
+/// let key = recover_key("ATTACK AT DAWN", "LXFOPV EF RNHR", KnownPlaintextCipher::Vigenere);
+/// assert_eq!(key, Ok(RecoveredKey::Vigenere { key: "LEMON".to_string() }));
+///
+/// let cipher = key.unwrap().cipher().unwrap();
+/// assert_eq!(cipher.decipher("ZVNWG"), "ORBIT");
+/// ```
+pub fn recover_key(
+    plaintext: &str,
+    ciphertext: &str,
+    cipher: KnownPlaintextCipher,
+) -> Result<RecoveredKey, String> {
+    let letters = |text: &str| -> Vec<i64> {
+        text.chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .map(|c| (c.to_ascii_uppercase() as u8 - b'A') as i64)
+            .collect()
+    };
+    let (plain, cipher_letters) = (letters(plaintext), letters(ciphertext));
+    if plain.is_empty() || plain.len() != cipher_letters.len() {
+        return Err(format!(
+            "Texts must have the same number of letters, found {} and {}",
+            plain.len(),
+            cipher_letters.len()
+        ));
+    }
+    let pairs: Vec<(i64, i64)> = plain.into_iter().zip(cipher_letters).collect();
+    let letter = |n: i64| (b'A' + n as u8) as char;
+    let inconsistent = || {
+        Err(format!(
+            "No {:?} key turns the plaintext into the ciphertext",
+            cipher
+        ))
+    };
+
+    match cipher {
+        KnownPlaintextCipher::Caesar => {
+            let shift = modulo(pairs[0].1 - pairs[0].0, 26);
+            if pairs.iter().all(|&(p, c)| modulo(c - p, 26) == shift) {
+                Ok(RecoveredKey::Caesar { shift: shift as u8 })
+            } else {
+                inconsistent()
+            }
+        }
+        KnownPlaintextCipher::Affine => {
+            let keys: Vec<(i64, i64)> = (1..26)
+                .filter(|&a| gcd(a, 26) == 1)
+                .flat_map(|a| (0..26).map(move |b| (a, b)))
+                .filter(|&(a, b)| pairs.iter().all(|&(p, c)| modulo(a * p + b, 26) == c))
+                .collect();
+            match keys.as_slice() {
+                [] => inconsistent(),
+                [(a, b)] => Ok(RecoveredKey::Affine { a: *a, b: *b }),
+                _ => Err(format!(
+                    "Texts are too short to tell {} possible keys apart",
+                    keys.len()
+                )),
+            }
+        }
+        KnownPlaintextCipher::Vigenere => {
+            let shifts: Vec<i64> = pairs.iter().map(|&(p, c)| modulo(c - p, 26)).collect();
+            let period = (1..=shifts.len())
+                .find(|&n| (n..shifts.len()).all(|i| shifts[i] == shifts[i - n]))
+                .unwrap_or(shifts.len());
+            Ok(RecoveredKey::Vigenere {
+                key: shifts[..period].iter().map(|&s| letter(s)).collect(),
+            })
+        }
+        KnownPlaintextCipher::Atbash => {
+            if pairs.iter().all(|&(p, c)| c == 25 - p) {
+                Ok(RecoveredKey::Atbash)
+            } else {
+                inconsistent()
+            }
+        }
+        KnownPlaintextCipher::Substitution => {
+            let mut mapping: Vec<(char, char)> = Vec::new();
+            for (p, c) in pairs.into_iter().map(|(p, c)| (letter(p), letter(c))) {
+                match mapping.iter().find(|&&(mp, mc)| mp == p || mc == c) {
+                    Some(&(mp, mc)) if (mp, mc) == (p, c) => {}
+                    Some(&(mp, mc)) => {
+                        return Err(format!(
+                            "{} and {} cannot both map to {} and {}",
+                            mp, p, mc, c
+                        ))
+                    }
+                    None => mapping.push((p, c)),
+                }
+            }
+            mapping.sort();
+            Ok(RecoveredKey::Substitution { mapping })
+        }
+    }
+}
+
+// Index of coincidence above which letters are taken to follow a natural language
+// distribution: English scores about 0.066, uniformly random letters about 0.038.
+const LANGUAGE_COINCIDENCE: f64 = 0.055;
+
+// Fewest letters the coincidence statistics are trusted on.
+const MIN_STATISTIC_LETTERS: usize = 20;
+
+// Largest period tried when estimating the period of a polyalphabetic cipher.
+const MAX_PERIOD: usize = 20;
+
+// Returns the letters of the text as numbers 0..26, with `None` for unknown letters.
+fn letter_stream(text: &str) -> Vec<Option<usize>> {
+    text.chars()
+        .filter(|&c| c.is_ascii_alphabetic() || c == UNKNOWN)
+        .map(|c| {
+            c.is_ascii_alphabetic()
+                .then(|| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+        })
+        .collect()
+}
+
+// Counts every letter of the stream, skipping unknown letters.
+fn letter_counts<'a>(letters: impl IntoIterator<Item = &'a Option<usize>>) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for letter in letters.into_iter().flatten() {
+        counts[*letter] += 1;
+    }
+    counts
+}
+
+// Returns the probability that two letters drawn without replacement are the same.
+fn coincidence(counts: &[usize; 26]) -> f64 {
+    let n: usize = counts.iter().sum();
+    if n < 2 {
+        return 0.0;
+    }
+    let pairs: usize = counts.iter().map(|c| c * c.saturating_sub(1)).sum();
+    pairs as f64 / (n * (n - 1)) as f64
+}
+
+// Returns the probability that letters drawn from the two counts are the same.
+fn cross_coincidence(a: &[usize; 26], b: &[usize; 26]) -> f64 {
+    let (na, nb): (usize, usize) = (a.iter().sum(), b.iter().sum());
+    if na == 0 || nb == 0 {
+        return 0.0;
+    }
+    let pairs: usize = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    pairs as f64 / (na * nb) as f64
+}
+
+/// Computes the index of coincidence of the letters of a text.
+///
+/// This is the probability that two letters picked at random from the text are the same:
+/// about 0.066 for English and for any transposition or monoalphabetic substitution of it,
+/// and down to about 0.038 for polyalphabetic ciphers with long keys. Returns `0.0` for texts
+/// with fewer than two letters.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::index_of_coincidence;
+/// assert_eq!(index_of_coincidence("AABB"), 1.0 / 3.0);
+/// ```
+pub fn index_of_coincidence(text: &str) -> f64 {
+    coincidence(&letter_counts(&letter_stream(text)))
+}
+
+/// Estimates the period of a polyalphabetic cipher.
+///
+/// Returns the smallest period up to `max_period` for which the letters taken every period
+/// positions apart have the index of coincidence of natural language, or `None` if there is
+/// no such period.
+pub fn estimate_period(text: &str, max_period: usize) -> Option<usize> {
+    let letters = letter_stream(text);
+    period_of(letters.len(), max_period, |period, column| {
+        letter_counts(letters.iter().skip(column).step_by(period))
+    })
+}
+
+// Finds the smallest period whose columns, counted by `columns(period, column)` over a stream
+// of `len` letters, have the index of coincidence of natural language.
+fn period_of(
+    len: usize,
+    max_period: usize,
+    columns: impl Fn(usize, usize) -> [usize; 26],
+) -> Option<usize> {
+    (1..=max_period)
+        .take_while(|period| len / period >= MIN_STATISTIC_LETTERS / 4)
+        .find(|&period| {
+            let total: f64 = (0..period)
+                .map(|column| coincidence(&columns(period, column)))
+                .sum();
+            total / period as f64 >= LANGUAGE_COINCIDENCE
+        })
+}
+
+/// The kind of cipher a ciphertext most likely comes from, judged by its letter statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CipherClass {
+    /// The letters are English letters in another order.
+    Transposition,
+    /// The letters have a natural language distribution, but not the English one.
+    Monoalphabetic,
+    /// The letters are flattened, with the period if one could be estimated.
+    Polyalphabetic { period: Option<usize> },
+    /// The text is too short to tell.
+    Undetermined,
+}
+
+/// Letter statistics of a single ciphertext.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStatistics {
+    /// The number of letters, including unknown ones.
+    pub letters: usize,
+    /// The index of coincidence of the letters.
+    pub index_of_coincidence: f64,
+    /// The probability that a letter of the text matches a letter of English text, about
+    /// 0.066 when the text is made of English letters.
+    pub english_coincidence: f64,
+    /// The most likely kind of cipher.
+    pub class: CipherClass,
+}
+
+/// Computes the letter statistics of a ciphertext and guesses the kind of cipher behind it.
+pub fn text_statistics(text: &str) -> TextStatistics {
+    let letters = letter_stream(text);
+    statistics_of(letters.len(), &letter_counts(&letters), || {
+        estimate_period(text, MAX_PERIOD)
+    })
+}
+
+// Computes the statistics from the number of letters and the counts of the known ones,
+// estimating the period only when the letters look polyalphabetic.
+fn statistics_of(
+    letters: usize,
+    counts: &[usize; 26],
+    period: impl FnOnce() -> Option<usize>,
+) -> TextStatistics {
+    let index_of_coincidence = coincidence(counts);
+    let known: usize = counts.iter().sum();
+    let english_coincidence = if known == 0 {
+        0.0
+    } else {
+        counts
+            .iter()
+            .zip(ENGLISH_FREQUENCIES)
+            .map(|(&c, f)| c as f64 * f / 100.0)
+            .sum::<f64>()
+            / known as f64
+    };
+    let class = if known < MIN_STATISTIC_LETTERS {
+        CipherClass::Undetermined
+    } else if index_of_coincidence < LANGUAGE_COINCIDENCE {
+        CipherClass::Polyalphabetic { period: period() }
+    } else if english_coincidence >= LANGUAGE_COINCIDENCE {
+        CipherClass::Transposition
+    } else {
+        CipherClass::Monoalphabetic
+    };
+    TextStatistics {
+        letters,
+        index_of_coincidence,
+        english_coincidence,
+        class,
+    }
+}
+
+/// A comparison of two ciphertexts of a corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusComparison {
+    /// The index of the first ciphertext.
+    pub first: usize,
+    /// The index of the second ciphertext.
+    pub second: usize,
+    /// The absolute difference of the indices of coincidence.
+    pub ic_difference: f64,
+    /// The probability that a letter of one text matches a letter of the other, high when
+    /// both share a monoalphabetic key.
+    pub cross_coincidence: f64,
+    /// The rate of equal letters at the same positions, high when both texts were enciphered
+    /// with the same keystream ("in depth"). `None` if the texts overlap too little.
+    pub depth_coincidence: Option<f64>,
+    /// Whether the indices of coincidence are within 0.005 of each other.
+    pub same_ic: bool,
+    /// Whether the texts were likely enciphered with the same key.
+    pub likely_same_key: bool,
+    /// Whether the texts likely come from the same kind of cipher.
+    pub likely_same_cipher: bool,
+}
+
+/// The statistics of every ciphertext of a corpus and the comparisons between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusReport {
+    /// The statistics of each ciphertext, in the order given.
+    pub statistics: Vec<TextStatistics>,
+    /// The comparison of every pair of ciphertexts.
+    pub comparisons: Vec<CorpusComparison>,
+}
+
+/// Analyzes a corpus of ciphertexts together.
+///
+/// Besides the statistics of each text, every pair is compared: texts with the same
+/// monoalphabetic key share their letter distribution, and texts enciphered with the same
+/// polyalphabetic keystream agree on their letters at the same positions about as often as two
+/// English texts do. Keys of transpositions cannot be compared from letter statistics.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::analysis::{analyze_corpus, CipherClass};
+/// use cipha::ciphers::VigenereCipher;
+///
+/// let cipher = VigenereCipher::new("CRYPTO");
+/// let first = cipher.encipher("It was the best of times, it was the worst of times, it was the age of wisdom, it was the age of foolishness");
+/// let second = cipher.encipher("Call me Ishmael. Some years ago, never mind how long precisely, having little or no money in my purse");
+/// let report = analyze_corpus(&[&first, &second]);
+/// assert!(matches!(report.statistics[0].class, CipherClass::Polyalphabetic { .. }));
+/// assert!(report.comparisons[0].likely_same_key);
+/// ```
+pub fn analyze_corpus(texts: &[&str]) -> CorpusReport {
+    let streams: Vec<Vec<Option<usize>>> = texts.iter().map(|text| letter_stream(text)).collect();
+    let statistics: Vec<TextStatistics> = texts.iter().map(|text| text_statistics(text)).collect();
+    let mut comparisons = Vec::new();
+    for first in 0..texts.len() {
+        for second in first + 1..texts.len() {
+            let (a, b) = (&statistics[first], &statistics[second]);
+            let cross_coincidence = cross_coincidence(
+                &letter_counts(&streams[first]),
+                &letter_counts(&streams[second]),
+            );
+            let aligned: Vec<bool> = streams[first]
+                .iter()
+                .zip(&streams[second])
+                .filter_map(|pair| match pair {
+                    (Some(x), Some(y)) => Some(x == y),
+                    _ => None,
+                })
+                .collect();
+            let depth_coincidence = (aligned.len() >= MIN_STATISTIC_LETTERS).then(|| {
+                aligned.iter().filter(|&&equal| equal).count() as f64 / aligned.len() as f64
+            });
+            let likely_same_key = match (a.class, b.class) {
+                (CipherClass::Monoalphabetic, CipherClass::Monoalphabetic) => {
+                    cross_coincidence >= LANGUAGE_COINCIDENCE
+                }
+                (CipherClass::Polyalphabetic { .. }, CipherClass::Polyalphabetic { .. }) => {
+                    depth_coincidence.is_some_and(|d| d >= LANGUAGE_COINCIDENCE)
+                }
+                _ => false,
+            };
+            let likely_same_cipher = match (a.class, b.class) {
+                (
+                    CipherClass::Polyalphabetic { period: p },
+                    CipherClass::Polyalphabetic { period: q },
+                ) => p.is_none() || q.is_none() || p == q,
+                (CipherClass::Undetermined, _) | (_, CipherClass::Undetermined) => false,
+                (x, y) => x == y,
+            };
+            let ic_difference = (a.index_of_coincidence - b.index_of_coincidence).abs();
+            comparisons.push(CorpusComparison {
+                first,
+                second,
+                ic_difference,
+                cross_coincidence,
+                depth_coincidence,
+                same_ic: ic_difference <= 0.005,
+                likely_same_key,
+                likely_same_cipher,
+            });
+        }
+    }
+    CorpusReport {
+        statistics,
+        comparisons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphers::Rot13Cipher;
+
+    #[test]
+    fn test_language_frequencies() {
+        for language in ["en", "German", "FR", "spanish"] {
+            let frequencies = language.parse::<Language>().unwrap().frequencies();
+            let total: f64 = frequencies.iter().sum();
+            // Letters outside A-Z, such as umlauts and accented letters, make up the rest
+            assert!(
+                (90.0..100.1).contains(&total),
+                "{} sums to {}",
+                language,
+                total
+            );
+        }
+        assert_eq!("de".parse(), Ok(Language::German));
+        assert!("xx".parse::<Language>().is_err());
+    }
+
+    // Complete columnar transposition: read the plaintext columns in `order`.
+    fn transpose(plaintext: &str, order: &[usize]) -> String {
+        let chars: Vec<char> = plaintext.chars().collect();
+        let columns = order.len();
+        order
+            .iter()
+            .flat_map(|&c| chars.iter().skip(c).step_by(columns))
+            .collect()
+    }
+
+    #[test]
+    fn test_bigram_score() {
+        assert!(bigram_score('t', 'h') > bigram_score('x', 'q'));
+        assert_eq!(bigram_score('1', 'A'), NON_ALPHA_SCORE);
+    }
+
+    #[test]
+    fn test_multiple_anagram() {
+        let key = [2, 0, 4, 1, 3];
+        let plaintexts = [
+            "THEENEMYWILLATTACKTHENORTHERNBRIDGEATDAWN",
+            "SENDMORETROOPSTOTHEHILLBEFORETHEEVENINGXX",
+            "THEGENERALWANTSTHEREPORTONHISDESKBYNOONXX",
+        ];
+        // Trim every message to a common length that is a multiple of the key length.
+        let plaintexts: Vec<&str> = plaintexts.iter().map(|p| &p[..40]).collect();
+        let ciphertexts: Vec<String> = plaintexts.iter().map(|p| transpose(p, &key)).collect();
+        let refs: Vec<&str> = ciphertexts.iter().map(|c| c.as_str()).collect();
+
+        let solution = multiple_anagram(&refs, key.len()).unwrap();
+        assert_eq!(solution.plaintexts, plaintexts);
+    }
+
+    #[test]
+    fn test_multiple_anagram_with_budget() {
+        let ciphertext = transpose("WEAREDISCOVEREDFLEEATONCEXX", &[2, 0, 1]);
+        let budget = Budget::unlimited().with_max_iterations(0);
+        let solution = multiple_anagram_with_budget(&[&ciphertext], 3, &budget).unwrap();
+        assert!(!solution.complete);
+        assert_eq!(solution.column_order.len(), 3);
+
+        let solution = multiple_anagram(&[&ciphertext], 3).unwrap();
+        assert!(solution.complete);
+
+        // The cleanup leaves a good plaintext alone
+        let beam = BeamDecoder::new(beam::NgramModel::english());
+        let cleaned =
+            multiple_anagram_with_beam(&[&ciphertext], 3, &beam, &Budget::unlimited()).unwrap();
+        assert_eq!(cleaned, solution);
+    }
+
+    #[test]
+    fn test_brute_force_with_budget() {
+        let budget = Budget::unlimited().with_max_iterations(4);
+        let candidates = brute_force_caesar_with_budget("Wkh wrwdo lv iliwhhq", &budget);
+        assert_eq!(candidates.len(), 4);
+        assert_eq!(candidates[0].plaintext, "The total is fifteen");
+        let candidates = brute_force_decimation_with_budget("T T  FEHTAIFTNEOLSIE", &budget);
+        assert_eq!(candidates[0].plaintext, "THE TOTAL IS FIFTEEN");
+        assert!(candidates.iter().all(|candidate| candidate.key <= 4));
+
+        let budget = Budget::unlimited().with_max_time(std::time::Duration::ZERO);
+        assert!(brute_force_caesar_with_budget("Wkh", &budget).is_empty());
+
+        // The shifts use up the whole budget, so none is left for the cleanup
+        let beam = BeamDecoder::new(beam::NgramModel::english());
+        let budget = Budget::unlimited().with_max_iterations(26);
+        let candidates = brute_force_caesar_with_beam("Wkh wrwdo lv iliwahq", &beam, &budget);
+        assert_eq!(candidates[0].plaintext, "The total is fiftxen");
+        let budget = Budget::unlimited().with_max_iterations(26 + 17);
+        let candidates = brute_force_caesar_with_beam("Wkh wrwdo lv iliwahq", &beam, &budget);
+        assert_eq!(candidates[0].plaintext, "The total is fifteen");
+    }
+
+    #[test]
+    fn test_multiple_anagram_rejects_bad_input() {
+        assert!(multiple_anagram(&[], 3).is_err());
+        assert!(multiple_anagram(&["ABCDEF", "ABC"], 3).is_err());
+        assert!(multiple_anagram(&["ABCDEFG"], 3).is_err());
+    }
+
+    // A small linear congruential generator so the test does not need a random crate.
+    fn pseudo_random_bits(count: usize) -> String {
+        let mut state: u64 = 42;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                if state >> 63 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_brute_force_decimation() {
+        let plaintext = "MEET ME AT THE OLD OAK TREE AT NOON";
+        let ciphertext = DecimationCipher::new(17)
+            .unwrap()
+            .encipher(plaintext)
+            .unwrap();
+        let candidates = brute_force_decimation(&ciphertext);
+        // Only skips coprime with the 35 characters are tried
+        assert_eq!(candidates.len(), 24);
+        assert_eq!(candidates[0].key, 17);
+        assert_eq!(candidates[0].plaintext, plaintext);
+        assert!(english_score("THE") > english_score("QZX"));
+        assert!(english_score("THE END") > english_score("THE  END"));
+        assert_eq!(english_score("A"), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_crib_stops_search() {
+        let ciphertext = CaesarCipher::new(5).encipher("Meet me at the old oak tree");
+        assert_eq!(brute_force_caesar(&ciphertext)[0].key, 5);
+
+        let crib = Crib::Contains("OLD OAK".to_string());
+        let mut tried = 0;
+        let found = crib
+            .find(caesar_candidates(&ciphertext).inspect(|_| tried += 1))
+            .unwrap();
+        assert_eq!(found.key, 5);
+        assert_eq!(tried, 6);
+        assert!(Crib::Plaintext("meet me at the old oak tree".to_string())
+            .find(caesar_candidates(&ciphertext))
+            .is_some());
+        assert!(Crib::Contains("NOON".to_string())
+            .find(caesar_candidates(&ciphertext))
+            .is_none());
+
+        let ciphertext = DecimationCipher::new(7)
+            .unwrap()
+            .encipher("THE TOTAL IS FIFTEEN")
+            .unwrap();
+        let found = Crib::Contains("FIFTEEN".to_string())
+            .find(decimation_candidates(&ciphertext))
+            .unwrap();
+        assert_eq!(found.key, 7);
+    }
+
+    #[test]
+    fn test_acrostic_and_telestich() {
+        let text = "Silent night,\n\n  turns to day.\nOver the hill\nPast the sea";
+        assert_eq!(acrostic(text, TextUnit::Lines), "StOP");
+        assert_eq!(telestich(text, TextUnit::Lines), "tyla");
+        assert_eq!(acrostic("(Send) help now!", TextUnit::Words), "Shn");
+        assert_eq!(telestich("(Send) help now!", TextUnit::Words), "dpw");
+    }
+
+    #[test]
+    fn test_recover_key() {
+        use KnownPlaintextCipher::*;
+        assert_eq!(
+            recover_key("Hello", "Khoor", Caesar),
+            Ok(RecoveredKey::Caesar { shift: 3 })
+        );
+        assert!(recover_key("Hello", "Khoos", Caesar).is_err());
+        assert!(recover_key("Hello", "Kho", Caesar).is_err());
+
+        let ciphertext = AffineCipher::new(5, 8).unwrap().encipher("AFFINE CIPHER");
+        assert_eq!(
+            recover_key("AFFINE CIPHER", &ciphertext, Affine),
+            Ok(RecoveredKey::Affine { a: 5, b: 8 })
+        );
+        // A single pair fits many keys
+        assert!(recover_key("A", "I", Affine).is_err());
+
+        assert_eq!(
+            recover_key("wizard", "DRAZIW", Atbash),
+            Ok(RecoveredKey::Atbash)
+        );
+        // Texts shorter than the key give the key so far
+        assert_eq!(
+            recover_key("AAA", "LEM", Vigenere),
+            Ok(RecoveredKey::Vigenere {
+                key: "LEM".to_string()
+            })
+        );
+
+        let key = recover_key("BAD", "QZR", Substitution).unwrap();
+        assert_eq!(
+            key,
+            RecoveredKey::Substitution {
+                mapping: vec![('A', 'Z'), ('B', 'Q'), ('D', 'R')]
+            }
+        );
+        assert!(key.cipher().is_none());
+        assert!(recover_key("AB", "QQ", Substitution).is_err());
+        assert!(recover_key("AA", "QR", Substitution).is_err());
+    }
+
+    #[test]
+    fn test_chronogram() {
+        assert_eq!(chronogram("ChrIstVs DVX ergo trIVMphVs", true), 1632);
+        assert_eq!(chronogram("mix", false), 1011);
+        assert_eq!(chronogram("Jeu Wax", false), 1 + 5 + 10 + 10);
+    }
+
+    #[test]
+    fn test_unknown_letters_score_around() {
+        let unknown = bigram_score('T', '?');
+        assert!(unknown < bigram_score('T', 'H'));
+        assert!(unknown > bigram_score('T', 'Q'));
+        assert!(bigram_score('?', '?').is_finite());
+        assert_eq!(bigram_score('?', ' '), NON_ALPHA_SCORE);
+
+        // A damaged ciphertext still brute forces to the right key
+        let candidates = brute_force_decimation("T T  FEHTA?FTNEOLSIE");
+        assert_eq!(candidates[0].key, 3);
+        assert_eq!(candidates[0].plaintext, "THE TOTAL ?S FIFTEEN");
+        assert!(english_score("THE TO?AL") > english_score("TEH OTTLA"));
+    }
+
+    #[test]
+    fn test_frequency_fit() {
+        let plaintext =
+            "the quick brown fox jumps over the lazy dog and then the dog sleeps in the sun";
+        let fit = frequency_fit(&Rot13Cipher::new().encipher(plaintext));
+        // E falls into place, the rest is only a starting point
+        assert_eq!(fit.mapping[0], ('R', 'E'));
+        assert_eq!(fit.plaintext.len(), plaintext.len());
+        assert_eq!(fit.mapping.len(), 26);
+
+        let fit = frequency_fit("Aab, b!");
+        assert_eq!(fit.mapping, vec![('A', 'E'), ('B', 'T')]);
+        assert_eq!(fit.plaintext, "Eet, t!");
+    }
+
+    #[test]
+    fn test_keystream_parse() {
+        assert_eq!(Keystream::parse(b"0110 1").kind, KeystreamKind::Bits);
+        assert_eq!(Keystream::parse(b"31415").kind, KeystreamKind::Digits);
+        assert_eq!(
+            Keystream::parse(b"abcXYZ").symbols,
+            vec![0, 1, 2, 23, 24, 25]
+        );
+        assert_eq!(
+            Keystream::parse(&[0x80]).symbols,
+            vec![1, 0, 0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_randomness_tests() {
+        let report = randomness_tests(pseudo_random_bits(20000).as_bytes());
+        assert!(report.passed(), "{}", report);
+        assert!(report.tests.iter().all(|t| t.passed.is_some()));
+
+        let report = randomness_tests("0001".repeat(5000).as_bytes());
+        assert_eq!(report.tests[0].passed, Some(false));
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_estimate_strength() {
+        let estimate = estimate_strength(&CipherConfig::Rot13, 10);
+        assert_eq!(estimate.strength, Strength::None);
+        assert_eq!(estimate.warning, None);
+
+        let estimate = estimate_strength(&CipherConfig::Caesar { shift: 3 }, 10);
+        assert_eq!(estimate.strength, Strength::Trivial);
+        assert_eq!(estimate.warning, None);
+        assert!(estimate_strength(&CipherConfig::Caesar { shift: 13 }, 10)
+            .warning
+            .is_some());
+
+        let key = "LEMON".to_string();
+        assert_eq!(
+            estimate_strength(&CipherConfig::Vigenere { key: key.clone() }, 50).warning,
+            None
+        );
+        assert!(estimate_strength(&CipherConfig::Vigenere { key }, 500)
+            .warning
+            .is_some());
+        assert!(
+            estimate_strength(&CipherConfig::RailFence { rails: 30 }, 20)
+                .warning
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_looks_like_english() {
+        let plaintext = "It was the best of times, it was the worst of times";
+        assert!(looks_like_english(plaintext));
+        assert!(!looks_like_english(
+            &VigenereCipher::new("LEMON").encipher(plaintext)
+        ));
+        assert!(!looks_like_english(
+            &CaesarCipher::new(7).encipher(plaintext)
+        ));
+        // Too short to judge
+        assert!(looks_like_english("Xqzv"));
+        assert!(looks_like_english(""));
+    }
+
+    #[test]
+    fn test_analyze_corpus() {
+        let first = "It was the best of times, it was the worst of times, it was the age of \
+            wisdom, it was the age of foolishness, it was the epoch of belief, it was the epoch \
+            of incredulity, it was the season of Light, it was the season of Darkness";
+        let second = "Call me Ishmael. Some years ago, never mind how long precisely, having \
+            little or no money in my purse, and nothing particular to interest me on shore, I \
+            thought I would sail about a little and see the watery part of the world";
+        let vigenere = VigenereCipher::new("LEMON");
+        let substitution = SubstitutionCipher::from_keyword("ZEBRAS").unwrap();
+        let reversed: String = first.chars().rev().collect();
+        let first_vigenere = vigenere.encipher(first);
+        let second_vigenere = vigenere.encipher(second);
+        let first_substitution = substitution.encipher(first);
+        let second_substitution = substitution.encipher(second);
+        let report = analyze_corpus(&[
+            &first_vigenere,
+            &second_vigenere,
+            &first_substitution,
+            &second_substitution,
+            &reversed,
+            "TOO SHORT",
+        ]);
+
+        let classes: Vec<CipherClass> = report.statistics.iter().map(|s| s.class).collect();
+        assert_eq!(
+            classes,
+            vec![
+                CipherClass::Polyalphabetic { period: Some(5) },
+                CipherClass::Polyalphabetic { period: Some(5) },
+                CipherClass::Monoalphabetic,
+                CipherClass::Monoalphabetic,
+                CipherClass::Transposition,
+                CipherClass::Undetermined,
+            ]
+        );
+        assert_eq!(report.comparisons.len(), 15);
+        let pair = |first, second| {
+            report
+                .comparisons
+                .iter()
+                .find(|c| c.first == first && c.second == second)
+                .unwrap()
+        };
+        assert!(pair(0, 1).likely_same_key && pair(0, 1).likely_same_cipher);
+        assert!(pair(2, 3).likely_same_key && pair(2, 3).likely_same_cipher);
+        // The same letters substituted or transposed keep their index of coincidence
+        assert!(pair(2, 4).same_ic && !pair(2, 4).likely_same_cipher);
+        assert!(!pair(0, 2).same_ic);
+        assert!(!pair(4, 5).likely_same_cipher);
+        assert!(pair(4, 5).depth_coincidence.is_none());
+
+        let other_key = VigenereCipher::new("KEY").encipher(second);
+        let report = analyze_corpus(&[&first_vigenere, &other_key]);
+        assert!(!report.comparisons[0].likely_same_key);
+        assert_eq!(index_of_coincidence(""), 0.0);
+    }
+}
@@ -0,0 +1,461 @@
+//! Beam search reconstruction of plaintexts with a character n-gram model.
+//!
+//! A cracker ranks keys by how English-like their plaintext is, but the best key does not
+//! always give the whole answer: a letter garbled in transmission, a `?` for a symbol the
+//! decoder did not know or a letter a search got wrong is left behind in otherwise good text.
+//! [`BeamDecoder`] cleans such a plaintext up. It reads the text letter by letter and keeps the
+//! most likely readings so far, in which every letter is either kept or, at a cost, replaced by
+//! another, and unknown letters may be any letter for free.
+//!
+//! ```rust
+//! use cipha::analysis::beam::{BeamDecoder, NgramModel};
+//!
+//! let decoder = BeamDecoder::new(NgramModel::english());
+//! assert_eq!(decoder.decode("Meet me at the bridgq"), "Meet me at the bridge");
+//! assert_eq!(decoder.decode("th? ?ld bridge"), "the old bridge");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use super::{english_score, Candidate};
+use crate::budget::{Budget, BudgetTracker};
+use crate::ciphers::UNKNOWN;
+
+// Public domain English prose the built-in model is counted from.
+const ENGLISH_SAMPLE: &str = include_str!("english.txt");
+
+// Marks the start and end of a word in n-grams.
+const BOUNDARY: char = ' ';
+
+/// A character n-gram model of the letters of a language.
+///
+/// The model gives the base-10 log probability of a letter following the letters before it in
+/// a word, and of a word ending after its last letters. Words are runs of letters and anything
+/// else ends a word. The counts of the n-grams of each length are mixed with those of the
+/// shorter ones (Witten-Bell smoothing), so n-grams that were not seen are still possible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NgramModel {
+    order: usize,
+    // How often each n-gram was seen, up to the order
+    counts: HashMap<String, usize>,
+    // How often each context was followed by a letter or the end of a word, and by how many
+    // different ones
+    contexts: HashMap<String, (usize, usize)>,
+}
+
+impl NgramModel {
+    /// Returns a trigram model of English, counted from a few pages of public domain prose
+    /// that come with the crate.
+    pub fn english() -> Self {
+        Self::train(ENGLISH_SAMPLE, 3).expect("The English sample has letters")
+    }
+
+    /// Counts the n-grams of a corpus, up to the given order.
+    ///
+    /// Letters are counted regardless of case, and n-grams do not cross the end of a word.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is not from 1 to 5 or the corpus has no letters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::beam::NgramModel;
+    /// let model = NgramModel::train("the cat sat on the mat", 3).unwrap();
+    /// assert!(model.score(&['A'], 'T') > model.score(&['A'], 'H'));
+    /// ```
+    pub fn train(corpus: &str, order: usize) -> Result<Self, String> {
+        if !(1..=5).contains(&order) {
+            return Err(format!("Order must be from 1 to 5, found {}", order));
+        }
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for word in corpus.split(|c: char| !c.is_ascii_alphabetic()) {
+            if word.is_empty() {
+                continue;
+            }
+            let letters: Vec<char> = std::iter::once(BOUNDARY)
+                .chain(word.chars().map(|c| c.to_ascii_uppercase()))
+                .chain(std::iter::once(BOUNDARY))
+                .collect();
+            // Every letter and the end of the word, after the letters before them
+            for end in 2..=letters.len() {
+                for length in 1..=order.min(end) {
+                    let gram: String = letters[end - length..end].iter().collect();
+                    *counts.entry(gram).or_default() += 1;
+                }
+            }
+        }
+        if counts.is_empty() {
+            return Err("The corpus has no letters".to_string());
+        }
+        let mut contexts: HashMap<String, (usize, usize)> = HashMap::new();
+        for (gram, count) in &counts {
+            let context = contexts
+                .entry(gram[..gram.len() - 1].to_string())
+                .or_default();
+            context.0 += count;
+            context.1 += 1;
+        }
+        Ok(NgramModel {
+            order,
+            counts,
+            contexts,
+        })
+    }
+
+    /// Returns the length of the longest n-grams of the model.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Scores a letter following the given letters of its word, as a base-10 log probability.
+    ///
+    /// A space in the context stands for the start of the word and a space as the letter for
+    /// its end. Only the last `order - 1` characters of the context count.
+    pub fn score(&self, context: &[char], letter: char) -> f64 {
+        let longest = context.len().min(self.order - 1);
+        let context: String = context[context.len() - longest..]
+            .iter()
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let letter = letter.to_ascii_uppercase();
+        // The 26 letters and the end of a word are equally likely before any counts
+        let mut probability = 1.0 / 27.0;
+        for start in (0..=longest).rev() {
+            let context = &context[start..];
+            if let Some(&(total, types)) = self.contexts.get(context) {
+                let count = self
+                    .counts
+                    .get(&format!("{}{}", context, letter))
+                    .copied()
+                    .unwrap_or(0);
+                probability = (count as f64 + types as f64 * probability) / (total + types) as f64;
+            }
+        }
+        probability.log10()
+    }
+
+    /// Scores the letters of a text, as the sum of the base-10 log probabilities of its letters.
+    pub fn log_probability(&self, text: &str) -> f64 {
+        let mut context = vec![BOUNDARY];
+        let mut total = 0.0;
+        for c in text.chars().chain(std::iter::once(BOUNDARY)) {
+            if c.is_ascii_alphabetic() {
+                total += self.score(&context, c);
+                context.push(c);
+            } else if context.len() > 1 {
+                total += self.score(&context, BOUNDARY);
+                context = vec![BOUNDARY];
+            }
+        }
+        total
+    }
+}
+
+/// A beam search that rebuilds the most likely plaintext close to a given one.
+///
+/// Every letter of the text may be kept or replaced by any other letter, which costs
+/// [`change_cost`](Self::with_change_cost) (base-10 log probability) and so only happens when
+/// the [`NgramModel`] finds the text that much more likely. [`UNKNOWN`] letters are replaced
+/// for free. After every letter only the `width` best readings are kept. Case and everything
+/// but letters are kept as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeamDecoder {
+    model: NgramModel,
+    width: usize,
+    change_cost: f64,
+}
+
+// One reading of the text so far.
+struct Reading {
+    score: f64,
+    // The last letters, as many as the model looks back
+    context: Vec<char>,
+    // The reading's last letter in the arena of letters, if it has any
+    last: Option<usize>,
+}
+
+impl BeamDecoder {
+    /// Creates a beam decoder with the model, a width of 16 and a change cost of 4.
+    pub fn new(model: NgramModel) -> Self {
+        BeamDecoder {
+            model,
+            width: 16,
+            change_cost: 4.0,
+        }
+    }
+
+    /// Keeps the given number of readings after every letter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the width is 0.
+    pub fn with_width(mut self, width: usize) -> Result<Self, String> {
+        if width == 0 {
+            return Err("Beam width must be at least 1".to_string());
+        }
+        self.width = width;
+        Ok(self)
+    }
+
+    /// Sets the cost of replacing a letter, as a base-10 log probability: with a cost of 4 a
+    /// letter is only replaced where that makes the text ten thousand times more likely.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cost is negative or not finite.
+    pub fn with_change_cost(mut self, cost: f64) -> Result<Self, String> {
+        if !cost.is_finite() || cost < 0.0 {
+            return Err(format!(
+                "Change cost must be a finite number of 0 or more, found {}",
+                cost
+            ));
+        }
+        self.change_cost = cost;
+        Ok(self)
+    }
+
+    /// Returns the model of the decoder.
+    pub fn model(&self) -> &NgramModel {
+        &self.model
+    }
+
+    /// Returns the number of readings kept after every letter.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the most likely plaintext close to the given text.
+    pub fn decode(&self, text: &str) -> String {
+        self.decode_with_budget(text, &Budget::unlimited())
+    }
+
+    /// Like [`decode`](Self::decode), but when the budget runs out the best reading so far is
+    /// finished with the rest of the letters as they are. Every letter is one iteration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::beam::{BeamDecoder, NgramModel};
+    /// use cipha::budget::Budget;
+    ///
+    /// let decoder = BeamDecoder::new(NgramModel::english());
+    /// let budget = Budget::unlimited().with_max_iterations(3);
+    /// assert_eq!(decoder.decode_with_budget("th? ?ld", &budget), "the ?ld");
+    /// ```
+    pub fn decode_with_budget(&self, text: &str, budget: &Budget) -> String {
+        self.decode_with_tracker(text, &mut budget.start())
+    }
+
+    /// Like [`decode_with_budget`](Self::decode_with_budget), but spends a budget shared with
+    /// other stages of a search, so what they used is not available here and the other way round.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::beam::{BeamDecoder, NgramModel};
+    /// use cipha::budget::Budget;
+    ///
+    /// let decoder = BeamDecoder::new(NgramModel::english());
+    /// let mut tracker = Budget::unlimited().with_max_iterations(4).start();
+    /// assert_eq!(decoder.decode_with_tracker("th?", &mut tracker), "the");
+    /// // One letter of the budget is left, so only the first letter of the next text is decoded
+    /// assert_eq!(tracker.remaining().max_iterations, Some(1));
+    /// assert_eq!(decoder.decode_with_tracker("x ?ld", &mut tracker), "a ?ld");
+    /// ```
+    pub fn decode_with_tracker(&self, text: &str, tracker: &mut BudgetTracker) -> String {
+        let is_letter = |c: char| c.is_ascii_alphabetic() || c == UNKNOWN;
+        // Letters of every reading kept, each with the index of the letter before it
+        let mut arena: Vec<(Option<usize>, char)> = Vec::new();
+        let mut beam = vec![Reading {
+            score: 0.0,
+            context: vec![BOUNDARY],
+            last: None,
+        }];
+        let mut in_word = false;
+        for c in text.chars().chain(std::iter::once(BOUNDARY)) {
+            if !is_letter(c) {
+                if in_word {
+                    // The end of the word is scored, after which the context is forgotten, so
+                    // the best reading is the only one left
+                    for reading in &mut beam {
+                        reading.score += self.model.score(&reading.context, BOUNDARY);
+                    }
+                    beam.sort_by(|a, b| b.score.total_cmp(&a.score));
+                    beam.truncate(1);
+                    beam[0].context = vec![BOUNDARY];
+                    in_word = false;
+                }
+                continue;
+            }
+            in_word = true;
+            if !tracker.tick() {
+                // The readings are kept best first, so the best one takes the letter unchanged
+                beam.truncate(1);
+                arena.push((beam[0].last, c.to_ascii_uppercase()));
+                beam[0].last = Some(arena.len() - 1);
+                continue;
+            }
+            let mut expansions = Vec::new();
+            for (i, reading) in beam.iter().enumerate() {
+                for letter in 'A'..='Z' {
+                    let cost = if c == UNKNOWN || c.eq_ignore_ascii_case(&letter) {
+                        0.0
+                    } else {
+                        self.change_cost
+                    };
+                    let score = reading.score + self.model.score(&reading.context, letter) - cost;
+                    expansions.push((score, i, letter));
+                }
+            }
+            // Stable, so ties keep the order of the letters
+            expansions.sort_by(|a, b| b.0.total_cmp(&a.0));
+            let mut seen = HashSet::new();
+            let mut next = Vec::with_capacity(self.width);
+            for (score, i, letter) in expansions {
+                let mut context = beam[i].context.clone();
+                context.push(letter);
+                if context.len() >= self.model.order() {
+                    context.remove(0);
+                }
+                if !seen.insert(context.clone()) {
+                    // The same context with a better score is already kept
+                    continue;
+                }
+                arena.push((beam[i].last, letter));
+                next.push(Reading {
+                    score,
+                    context,
+                    last: Some(arena.len() - 1),
+                });
+                if next.len() == self.width {
+                    break;
+                }
+            }
+            beam = next;
+        }
+
+        let mut letters = Vec::new();
+        let mut last = beam[0].last;
+        while let Some(i) = last {
+            letters.push(arena[i].1);
+            last = arena[i].0;
+        }
+        let mut letters = letters.into_iter().rev();
+        // Unknown letters take the case of the letter before them, or of the first letter
+        let mut lowercase = text
+            .chars()
+            .find(char::is_ascii_alphabetic)
+            .is_some_and(|c| c.is_ascii_lowercase());
+        text.chars()
+            .map(|c| {
+                if !is_letter(c) {
+                    return c;
+                }
+                if c != UNKNOWN {
+                    lowercase = c.is_ascii_lowercase();
+                }
+                let letter = letters.next().unwrap_or(c);
+                if lowercase {
+                    letter.to_ascii_lowercase()
+                } else {
+                    letter
+                }
+            })
+            .collect()
+    }
+
+    /// Cleans up the plaintext of a cracker's candidate, scoring it again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::beam::{BeamDecoder, NgramModel};
+    /// use cipha::analysis::brute_force_caesar;
+    /// // One letter of the ciphertext was garbled
+    /// let best = brute_force_caesar("Wkh wrwdo lv iliwahq").remove(0);
+    /// assert_eq!(best.plaintext, "The total is fiftxen");
+    /// let cleaned = BeamDecoder::new(NgramModel::english()).clean(best);
+    /// assert_eq!(cleaned.plaintext, "The total is fifteen");
+    /// ```
+    pub fn clean(&self, candidate: Candidate) -> Candidate {
+        self.clean_with_budget(candidate, &Budget::unlimited())
+    }
+
+    /// Like [`clean`](Self::clean), decoding with [`decode_with_budget`](Self::decode_with_budget).
+    pub fn clean_with_budget(&self, candidate: Candidate, budget: &Budget) -> Candidate {
+        self.clean_with_tracker(candidate, &mut budget.start())
+    }
+
+    /// Like [`clean`](Self::clean), decoding with
+    /// [`decode_with_tracker`](Self::decode_with_tracker).
+    pub fn clean_with_tracker(
+        &self,
+        candidate: Candidate,
+        tracker: &mut BudgetTracker,
+    ) -> Candidate {
+        let plaintext = self.decode_with_tracker(&candidate.plaintext, tracker);
+        Candidate {
+            key: candidate.key,
+            score: english_score(&plaintext),
+            plaintext,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ngram_model() {
+        let english = NgramModel::english();
+        assert_eq!(english.order(), 3);
+        assert!(english.score(&['T'], 'H') > english.score(&['T'], 'Q'));
+        // Only the last two letters count in a trigram model
+        assert_eq!(
+            english.score(&['Q', 'T', 'H'], 'E'),
+            english.score(&['T', 'H'], 'E')
+        );
+        // Words are likelier to end after THE than after THQ
+        assert!(english.score(&['T', 'H', 'E'], ' ') > english.score(&['T', 'H', 'Q'], ' '));
+        assert!(english.log_probability("the end") > english.log_probability("qzx jvk"));
+
+        let model = NgramModel::train("Attack at dawn. Attack at dusk!", 3).unwrap();
+        assert!(model.score(&['T', 'T'], 'A') > model.score(&['T', 'T'], 'B'));
+        // Unseen n-grams are still possible
+        assert!(model.score(&['X', 'Y'], 'Z').is_finite());
+        assert!(NgramModel::train("1234", 3).is_err());
+        assert!(NgramModel::train("text", 6).is_err());
+    }
+
+    #[test]
+    fn test_beam_decoder() {
+        let decoder = BeamDecoder::new(NgramModel::english());
+        // Good text is left alone
+        let text = "Meet me at the old bridge at noon, and bring the map.";
+        assert_eq!(decoder.decode(text), text);
+        assert_eq!(decoder.decode("Th? ?ld bridge"), "The old bridge");
+        assert_eq!(decoder.decode(""), "");
+
+        let corpus = "we are discovered flee at once and meet at the river at dawn";
+        let model = NgramModel::train(corpus, 3).unwrap();
+        let decoder = BeamDecoder::new(model)
+            .with_width(4)
+            .unwrap()
+            .with_change_cost(1.0)
+            .unwrap();
+        assert_eq!(decoder.width(), 4);
+        assert_eq!(
+            decoder.decode("WE ARE DISCOVERED FLZE AT ONCX AND MQET AT THE RIVER"),
+            "WE ARE DISCOVERED FLEE AT ONCE AND MEET AT THE RIVER"
+        );
+        assert!(BeamDecoder::new(NgramModel::english())
+            .with_width(0)
+            .is_err());
+        assert!(BeamDecoder::new(NgramModel::english())
+            .with_change_cost(-1.0)
+            .is_err());
+    }
+}
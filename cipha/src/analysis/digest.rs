@@ -0,0 +1,174 @@
+//! Hash digests for checking candidate plaintexts.
+//!
+//! Puzzles often publish the hash of their answer so solvers can confirm it without it being
+//! given away. These helpers compute MD5, SHA-1 and SHA-256 digests and compare candidates
+//! against a published one. They need the `hash` feature.
+//!
+//! ```rust
+//! use cipha::analysis::digest::{hex_digest, verify_digest, HashAlgorithm};
+//!
+//! assert_eq!(
+//!     hex_digest(HashAlgorithm::Md5, b"hello"),
+//!     "5d41402abc4b2a76b9719d911017c592"
+//! );
+//! // The algorithm follows from the length of the digest
+//! assert!(verify_digest(b"hello", "5D41402ABC4B2A76B9719D911017C592", None).unwrap());
+//! assert!(!verify_digest(b"hullo", "5d41402abc4b2a76b9719d911017c592", None).unwrap());
+//! ```
+
+use std::fmt;
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::binary::{from_hex, to_hex};
+
+/// A hash algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// MD5, with 16 byte digests.
+    Md5,
+    /// SHA-1, with 20 byte digests.
+    Sha1,
+    /// SHA-256, with 32 byte digests.
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Every supported algorithm.
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Md5,
+        HashAlgorithm::Sha1,
+        HashAlgorithm::Sha256,
+    ];
+
+    /// Returns the length of the algorithm's digests in bytes.
+    pub fn digest_len(self) -> usize {
+        match self {
+            HashAlgorithm::Md5 => 16,
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Sha256 => 32,
+        }
+    }
+
+    /// Returns the algorithm whose digests are `len` bytes long, if any.
+    pub fn from_digest_len(len: usize) -> Option<Self> {
+        Self::ALL.into_iter().find(|alg| alg.digest_len() == len)
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+        })
+    }
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = String;
+
+    /// Parses an algorithm name in either case, with or without the dash, e.g. `"sha256"`.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            _ => Err(format!(
+                "Unknown hash algorithm {:?} (expected md5, sha1 or sha256)",
+                name
+            )),
+        }
+    }
+}
+
+/// Computes the digest of the data.
+pub fn digest(algorithm: HashAlgorithm, data: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::Md5 => Md5::digest(data).to_vec(),
+        HashAlgorithm::Sha1 => Sha1::digest(data).to_vec(),
+        HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+    }
+}
+
+/// Computes the digest of the data as lowercase hex.
+pub fn hex_digest(algorithm: HashAlgorithm, data: &[u8]) -> String {
+    to_hex(&digest(algorithm, data))
+}
+
+/// Checks the data against a hex digest.
+///
+/// Without an `algorithm` it is chosen by the length of the digest.
+///
+/// # Errors
+///
+/// Returns an error if the digest is not valid hex, or its length fits no algorithm or not the
+/// given one.
+pub fn verify_digest(
+    data: &[u8],
+    expected: &str,
+    algorithm: Option<HashAlgorithm>,
+) -> Result<bool, String> {
+    let expected = from_hex(expected)?;
+    let algorithm = match algorithm {
+        Some(algorithm) => algorithm,
+        None => HashAlgorithm::from_digest_len(expected.len()).ok_or_else(|| {
+            format!(
+                "A {} byte digest matches no supported algorithm",
+                expected.len()
+            )
+        })?,
+    };
+    if expected.len() != algorithm.digest_len() {
+        return Err(format!(
+            "{} digests are {} bytes, not {}",
+            algorithm,
+            algorithm.digest_len(),
+            expected.len()
+        ));
+    }
+    Ok(digest(algorithm, data) == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(
+            hex_digest(HashAlgorithm::Md5, b""),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            hex_digest(HashAlgorithm::Sha1, b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex_digest(HashAlgorithm::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        for algorithm in HashAlgorithm::ALL {
+            assert_eq!(digest(algorithm, b"abc").len(), algorithm.digest_len());
+            assert_eq!(algorithm.to_string().parse(), Ok(algorithm));
+        }
+        assert!("sha512".parse::<HashAlgorithm>().is_err());
+    }
+
+    #[test]
+    fn test_verify_digest() {
+        let sha1 = "a9993e364706816aba3e25717850c26c9cd0d89d";
+        assert_eq!(verify_digest(b"abc", sha1, None), Ok(true));
+        assert_eq!(
+            verify_digest(b"abc", sha1, Some(HashAlgorithm::Sha1)),
+            Ok(true)
+        );
+        assert_eq!(verify_digest(b"abd", sha1, None), Ok(false));
+        assert!(verify_digest(b"abc", sha1, Some(HashAlgorithm::Md5)).is_err());
+        assert!(verify_digest(b"abc", "abcd", None).is_err());
+        assert!(verify_digest(b"abc", "xyz", None).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+//! Attacks that assume a stereotyped opening of the message.
+//!
+//! Real traffic was full of set phrases: German weather reports opened with `WETTERBERICHT`,
+//! letters with `DEAR` and orders with `ATTACK AT`. Assuming such an opening pins down most of
+//! the key, so keyspaces far too big to try in full shrink to the few keys that fit it, which
+//! are then ranked by [`english_score`].
+//!
+//! ```rust
+//! use cipha::analysis::prefix::{solve, PrefixAttack};
+//! use cipha::ciphers::VigenereCipher;
+//!
+//! let ciphertext = VigenereCipher::new("LEMON").encipher("Dear Sir, the shipment leaves on Monday");
+//! let solutions = solve(&ciphertext, "Dear Sir", PrefixAttack::Vigenere);
+//! assert_eq!(solutions[0].key, "LEMON");
+//! assert_eq!(solutions[0].plaintext, "Dear Sir, the shipment leaves on Monday");
+//! ```
+
+use std::collections::HashSet;
+
+use super::beam::BeamDecoder;
+use super::{english_score, Crib};
+use crate::budget::{Budget, BudgetTracker};
+use crate::ciphers::{ColumnarTranspositionCipher, RailFenceCipher, ScytaleCipher, VigenereCipher};
+use crate::machines::{EnigmaMachine, Rotor};
+
+/// The ciphers [`solve`] can attack with an assumed opening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixAttack {
+    /// Vigenère cipher with a key no longer than the letters of the opening.
+    Vigenere,
+    /// Enigma I with three of the rotors I to V and an empty plugboard, see
+    /// [`EnigmaMachine::settings_for_prefix`].
+    Enigma,
+    /// Enigma I with three of the rotors I to V and plugged letters, found like on the Bombe
+    /// from an opening that closes at least three loops, see [`EnigmaMachine::bombe`].
+    EnigmaPlugboard,
+    /// Incomplete columnar transposition with up to eight columns.
+    Columnar,
+    /// Rail fence cipher with any number of rails and offset.
+    RailFence,
+    /// Scytale of any diameter.
+    Scytale,
+}
+
+/// A key under which the message opens with the assumed text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixSolution {
+    /// The key as the matching `encode` option takes it, e.g. `LEMON` for a Vigenère keyword or
+    /// `II IV V B BLA` for the rotors, reflector and start positions of an Enigma, followed by
+    /// its ring settings and plugboard pairs unless they are `AAA` and empty.
+    pub key: String,
+    /// The decrypted text.
+    pub plaintext: String,
+    /// The [`english_score`] of the plaintext, higher is more English-like.
+    pub score: f64,
+}
+
+// Widest columnar transposition tried, with its 8! = 40,320 column orders.
+const MAX_COLUMNS: usize = 8;
+
+// The rotors of the Enigma I, tried three at a time in every order.
+const ROTORS: [Rotor; 5] = [Rotor::I, Rotor::II, Rotor::III, Rotor::IV, Rotor::V];
+
+/// Finds the keys under which the ciphertext deciphers to a text starting with `prefix`, best
+/// first.
+///
+/// Only the letters of the opening are compared, ignoring case, as a [`Crib::Prefix`]. Keys
+/// that give the same plaintext are listed once, by the shortest of them. An opening without
+/// letters fits nothing.
+pub fn solve(ciphertext: &str, prefix: &str, attack: PrefixAttack) -> Vec<PrefixSolution> {
+    solve_with_budget(ciphertext, prefix, attack, &Budget::unlimited())
+}
+
+/// Like [`solve`], but stops when the budget runs out and ranks the keys found so far. Every
+/// key tried is one iteration, and an Enigma start position counts as a key.
+pub fn solve_with_budget(
+    ciphertext: &str,
+    prefix: &str,
+    attack: PrefixAttack,
+    budget: &Budget,
+) -> Vec<PrefixSolution> {
+    solve_with_tracker(ciphertext, prefix, attack, &mut budget.start())
+}
+
+/// Like [`solve_with_budget`], but spends a budget shared with other stages of a search, such as
+/// the other openings tried or a cleanup of the plaintexts.
+pub fn solve_with_tracker(
+    ciphertext: &str,
+    prefix: &str,
+    attack: PrefixAttack,
+    tracker: &mut BudgetTracker,
+) -> Vec<PrefixSolution> {
+    let crib = Crib::Prefix(prefix.to_string());
+    if !prefix.chars().any(|c| c.is_ascii_alphabetic()) {
+        return Vec::new();
+    }
+    let keyed = |key: String, plaintext: String| PrefixSolution {
+        key,
+        score: english_score(&plaintext),
+        plaintext,
+    };
+    let mut solutions: Vec<PrefixSolution> = match attack {
+        PrefixAttack::Vigenere => vigenere_keys(ciphertext, prefix)
+            .into_iter()
+            .take_while(|_| tracker.tick())
+            .map(|key| {
+                let plaintext = VigenereCipher::new(&key).decipher(ciphertext);
+                keyed(key, plaintext)
+            })
+            .collect(),
+        PrefixAttack::Enigma => {
+            EnigmaMachine::settings_for_prefix_with_tracker(ciphertext, prefix, &ROTORS, tracker)
+                .into_iter()
+                .map(|enigma| keyed(enigma_key(&enigma), enigma.decipher(ciphertext)))
+                .collect()
+        }
+        PrefixAttack::EnigmaPlugboard => {
+            EnigmaMachine::bombe_with_tracker(ciphertext, prefix, &ROTORS, tracker)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|enigma| keyed(enigma_key(&enigma), enigma.decipher(ciphertext)))
+                .collect()
+        }
+        PrefixAttack::Columnar => {
+            let len = ciphertext.chars().count();
+            let mut solutions = Vec::new();
+            'columns: for columns in 2..=MAX_COLUMNS.min(len) {
+                let mut order: Vec<u8> = (0..columns as u8).collect();
+                loop {
+                    if !tracker.tick() {
+                        break 'columns;
+                    }
+                    let keyword: String = order.iter().map(|&i| (b'A' + i) as char).collect();
+                    if let Ok(columnar) = ColumnarTranspositionCipher::new(&keyword) {
+                        let plaintext = columnar.decipher(ciphertext);
+                        if crib.matches(&plaintext) {
+                            solutions.push(keyed(keyword, plaintext));
+                        }
+                    }
+                    if !next_permutation(&mut order) {
+                        break;
+                    }
+                }
+            }
+            solutions
+        }
+        PrefixAttack::RailFence => {
+            let len = ciphertext.chars().count();
+            (2..len.max(2))
+                .flat_map(|rails| (0..2 * (rails - 1)).map(move |offset| (rails, offset)))
+                .take_while(|_| tracker.tick())
+                .filter_map(|(rails, offset)| {
+                    let plaintext = RailFenceCipher::new(rails)
+                        .ok()?
+                        .with_offset(offset)
+                        .decipher(ciphertext);
+                    let key = match offset {
+                        0 => rails.to_string(),
+                        _ => format!("{} offset {}", rails, offset),
+                    };
+                    crib.matches(&plaintext).then(|| keyed(key, plaintext))
+                })
+                .collect()
+        }
+        PrefixAttack::Scytale => {
+            let len = ciphertext.chars().count();
+            (2..len.max(2))
+                .take_while(|_| tracker.tick())
+                .filter_map(|diameter| {
+                    let plaintext = ScytaleCipher::new(diameter).ok()?.decipher(ciphertext);
+                    crib.matches(&plaintext)
+                        .then(|| keyed(diameter.to_string(), plaintext))
+                })
+                .collect()
+        }
+    };
+    solutions.sort_by_key(|solution| solution.key.len());
+    let mut seen = HashSet::new();
+    let mut solutions: Vec<PrefixSolution> = solutions
+        .into_iter()
+        .filter(|solution| crib.matches(&solution.plaintext))
+        .filter(|solution| seen.insert(solution.plaintext.clone()))
+        .collect();
+    solutions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    solutions
+}
+
+/// Like [`solve_with_budget`], but cleans up the plaintexts with a beam decoder as a last step.
+///
+/// The solutions are cleaned best first while the budget lasts, and keep the order of their
+/// scores before the cleanup.
+pub fn solve_with_beam(
+    ciphertext: &str,
+    prefix: &str,
+    attack: PrefixAttack,
+    beam: &BeamDecoder,
+    budget: &Budget,
+) -> Vec<PrefixSolution> {
+    let mut tracker = budget.start();
+    solve_with_tracker(ciphertext, prefix, attack, &mut tracker)
+        .into_iter()
+        .map(|solution| {
+            let plaintext = beam.decode_with_tracker(&solution.plaintext, &mut tracker);
+            PrefixSolution {
+                score: english_score(&plaintext),
+                plaintext,
+                ..solution
+            }
+        })
+        .collect()
+}
+
+// Returns the key of an Enigma: the rotors, reflector and start positions, then the ring settings
+// and plugboard pairs if there are any.
+fn enigma_key(enigma: &EnigmaMachine) -> String {
+    let [left, middle, right] = enigma.rotors();
+    let mut key = format!(
+        "{:?} {:?} {:?} {:?} {}",
+        left,
+        middle,
+        right,
+        enigma.reflector(),
+        enigma.positions()
+    );
+    if enigma.rings() != "AAA" {
+        key += &format!(" rings {}", enigma.rings());
+    }
+    if !enigma.plugboard().is_empty() {
+        key += &format!(" plugboard {}", enigma.plugboard());
+    }
+    key
+}
+
+// Returns the Vigenère keys, shortest first, that turn the opening into the start of the
+// ciphertext: every period up to the length of the opening whose shifts repeat.
+fn vigenere_keys(ciphertext: &str, prefix: &str) -> Vec<String> {
+    let letters = |text: &str| -> Vec<u8> {
+        text.bytes()
+            .filter(u8::is_ascii_alphabetic)
+            .map(|b| b.to_ascii_uppercase() - b'A')
+            .collect()
+    };
+    let shifts: Vec<u8> = letters(prefix)
+        .into_iter()
+        .zip(letters(ciphertext))
+        .map(|(p, c)| (c + 26 - p) % 26)
+        .collect();
+    (1..=shifts.len())
+        .filter(|&period| (period..shifts.len()).all(|i| shifts[i] == shifts[i - period]))
+        .map(|period| {
+            shifts[..period]
+                .iter()
+                .map(|&s| (b'A' + s) as char)
+                .collect()
+        })
+        .collect()
+}
+
+// Rearranges the slice into the next permutation in lexicographic order, returning `false`
+// after the last one.
+fn next_permutation(order: &mut [u8]) -> bool {
+    let Some(i) = order.windows(2).rposition(|pair| pair[0] < pair[1]) else {
+        return false;
+    };
+    let j = order.iter().rposition(|&x| x > order[i]).unwrap_or(i);
+    order.swap(i, j);
+    order[i + 1..].reverse();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machines::Reflector;
+
+    #[test]
+    fn test_vigenere_prefix() {
+        let ciphertext =
+            VigenereCipher::new("KEY").encipher("Attack at dawn, then hold the bridge");
+        let solutions = solve(&ciphertext, "ATTACK AT", PrefixAttack::Vigenere);
+        assert_eq!(solutions[0].key, "KEY");
+        assert_eq!(
+            solutions[0].plaintext,
+            "Attack at dawn, then hold the bridge"
+        );
+        // KEYKEY gives the same plaintext as KEY, and other periods do not repeat
+        let keys: Vec<&str> = solutions.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["KEY", "KEYKEYKE"]);
+        assert!(solve(&ciphertext, "DEAR", PrefixAttack::Vigenere)
+            .iter()
+            .all(|s| s.plaintext.starts_with("Dear")));
+        assert!(solve(&ciphertext, "...", PrefixAttack::Vigenere).is_empty());
+    }
+
+    #[test]
+    fn test_enigma_prefix() {
+        let enigma = EnigmaMachine::new([Rotor::IV, Rotor::II, Rotor::V], Reflector::B)
+            .unwrap()
+            .with_positions("RKQ")
+            .unwrap();
+        let ciphertext = enigma.encipher("WETTERBERICHT REGEN");
+        let solutions = solve(&ciphertext, "WETTERBERICHT", PrefixAttack::Enigma);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].key, "IV II V B RKQ");
+        assert_eq!(solutions[0].plaintext, "WETTERBERICHT REGEN");
+
+        let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B)
+            .unwrap()
+            .with_positions("DOW")
+            .unwrap()
+            .with_plugboard("AE RT")
+            .unwrap();
+        let message = "WETTERVORHERSAGE FUER DIE DEUTSCHE BUCHT";
+        let ciphertext = enigma.encipher(message);
+        assert!(solve(&ciphertext, "WETTERBERICHT", PrefixAttack::EnigmaPlugboard).is_empty());
+        // I II III with reflector B is the first rotor order tried
+        let budget = Budget::unlimited().with_max_iterations(26 * 26 * 26);
+        let solutions = solve_with_budget(
+            &ciphertext,
+            "WETTERVORHERSAGE FUER DIE",
+            PrefixAttack::EnigmaPlugboard,
+            &budget,
+        );
+        assert_eq!(solutions[0].key, "I II III B DOW plugboard AE RT");
+        assert_eq!(solutions[0].plaintext, message);
+    }
+
+    #[test]
+    fn test_transposition_prefix() {
+        let message = "ATTACK THE NORTHERN BRIDGE AT DAWN";
+        let columnar = ColumnarTranspositionCipher::new("ZEBRAS").unwrap();
+        let solutions = solve(
+            &columnar.encipher(message),
+            "ATTACK",
+            PrefixAttack::Columnar,
+        );
+        assert_eq!(solutions[0].key, "FCBDAE");
+        assert_eq!(solutions[0].plaintext, message);
+
+        let rail_fence = RailFenceCipher::new(4).unwrap().with_offset(3);
+        let solutions = solve(
+            &rail_fence.encipher(message),
+            "ATTACK",
+            PrefixAttack::RailFence,
+        );
+        assert_eq!(solutions[0].key, "4 offset 3");
+        assert_eq!(solutions[0].plaintext, message);
+
+        let scytale = ScytaleCipher::new(5).unwrap();
+        let solutions = solve(
+            &scytale.encipher(message),
+            "ATTACK THE",
+            PrefixAttack::Scytale,
+        );
+        assert_eq!(solutions[0].key, "5");
+        assert_eq!(solutions[0].plaintext, message);
+    }
+
+    #[test]
+    fn test_prefix_with_budget() {
+        let message = "ATTACK THE NORTHERN BRIDGE AT DAWN";
+        let ciphertext = ColumnarTranspositionCipher::new("ZEBRAS")
+            .unwrap()
+            .encipher(message);
+        // The six column orders come after every order of two to five columns
+        let budget = Budget::unlimited().with_max_iterations(2 + 6 + 24 + 120);
+        assert!(
+            solve_with_budget(&ciphertext, "ATTACK", PrefixAttack::Columnar, &budget).is_empty()
+        );
+        let budget = Budget::unlimited().with_max_iterations(2 + 6 + 24 + 120 + 720);
+        let solutions = solve_with_budget(&ciphertext, "ATTACK", PrefixAttack::Columnar, &budget);
+        assert_eq!(solutions[0].plaintext, message);
+        // A tracker shared by two openings leaves the second one what the first did not use
+        let mut tracker = budget.start();
+        let solutions =
+            solve_with_tracker(&ciphertext, "ATTACK", PrefixAttack::Columnar, &mut tracker);
+        assert_eq!(solutions[0].plaintext, message);
+        assert!(
+            solve_with_tracker(&ciphertext, "ATTACK", PrefixAttack::Columnar, &mut tracker)
+                .is_empty()
+        );
+
+        let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B)
+            .unwrap()
+            .with_positions("AAC")
+            .unwrap();
+        let ciphertext = enigma.encipher("WETTERBERICHT");
+        let budget = Budget::unlimited().with_max_iterations(3);
+        let solutions = solve_with_budget(&ciphertext, "WETTER", PrefixAttack::Enigma, &budget);
+        assert_eq!(solutions[0].key, "I II III B AAC");
+    }
+
+    #[test]
+    fn test_prefix_with_beam() {
+        use crate::analysis::beam::NgramModel;
+
+        // One letter of the ciphertext was garbled: SXR was received as SXQ
+        let ciphertext = "Xiqh zp ef hup sxq ocmpur lx zcby";
+        let solutions = solve(ciphertext, "Meet me", PrefixAttack::Vigenere);
+        assert_eq!(solutions[0].plaintext, "Meet me at the olc bridge at noon");
+        let beam = BeamDecoder::new(NgramModel::english());
+        let solutions = solve_with_beam(
+            ciphertext,
+            "Meet me",
+            PrefixAttack::Vigenere,
+            &beam,
+            &Budget::unlimited(),
+        );
+        assert_eq!(solutions[0].key, "LEMON");
+        assert_eq!(solutions[0].plaintext, "Meet me at the old bridge at noon");
+    }
+
+    #[test]
+    fn test_next_permutation() {
+        let mut order = [0, 1, 2];
+        let mut seen = vec![order];
+        while next_permutation(&mut order) {
+            seen.push(order);
+        }
+        assert_eq!(seen.len(), 6);
+        assert_eq!(seen[1], [0, 2, 1]);
+        assert_eq!(seen[5], [2, 1, 0]);
+    }
+}
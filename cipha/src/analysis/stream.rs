@@ -0,0 +1,237 @@
+//! Incremental analysis of text that arrives in chunks.
+//!
+//! A monitor of a long intercept or chat log cannot afford to scan everything again whenever a
+//! line comes in. [`AnalysisAccumulator`] keeps the counts behind the letter statistics and
+//! updates them with every chunk it is fed, so the frequencies, index of coincidence, n-grams
+//! and [`TextStatistics`] are always those of the whole text so far. A chunk may end anywhere,
+//! even in the middle of a word.
+//!
+//! ```rust
+//! use cipha::analysis::stream::AnalysisAccumulator;
+//! use cipha::analysis::text_statistics;
+//!
+//! let mut accumulator = AnalysisAccumulator::new();
+//! for chunk in ["Meet me at the br", "idge at noon", ", bring the papers"] {
+//!     accumulator.feed(chunk);
+//! }
+//! assert_eq!(accumulator.ngram_count("BRI"), 2);
+//! assert_eq!(
+//!     accumulator.statistics(),
+//!     text_statistics("Meet me at the bridge at noon, bring the papers")
+//! );
+//! ```
+
+use super::{coincidence, period_of, statistics_of, TextStatistics, MAX_PERIOD, UNKNOWN};
+
+/// Letter statistics of a text fed in chunks.
+///
+/// Letters are counted ignoring case, and the n-grams are counted on the letters alone, as a
+/// cryptanalyst writes out the ciphertext: spaces and punctuation are skipped and an
+/// [`UNKNOWN`] letter breaks the n-grams around it. Everything is updated in constant time per
+/// letter, whatever the length of the text so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisAccumulator {
+    letters: usize,
+    counts: [usize; 26],
+    bigrams: Vec<usize>,
+    trigrams: Vec<usize>,
+    // Letter counts of every column of every period up to MAX_PERIOD, period by period.
+    columns: Vec<[usize; 26]>,
+    // The last two letters fed, for the n-grams that span chunks.
+    last: [Option<usize>; 2],
+}
+
+impl Default for AnalysisAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisAccumulator {
+    /// Creates an accumulator that has seen no text.
+    pub fn new() -> Self {
+        AnalysisAccumulator {
+            letters: 0,
+            counts: [0; 26],
+            bigrams: vec![0; 26 * 26],
+            trigrams: vec![0; 26 * 26 * 26],
+            columns: vec![[0; 26]; MAX_PERIOD * (MAX_PERIOD + 1) / 2],
+            last: [None; 2],
+        }
+    }
+
+    /// Adds the next chunk of the text.
+    pub fn feed(&mut self, chunk: &str) {
+        for c in chunk.chars() {
+            let letter = match c {
+                UNKNOWN => None,
+                c if c.is_ascii_alphabetic() => {
+                    Some((c.to_ascii_uppercase() as u8 - b'A') as usize)
+                }
+                _ => continue,
+            };
+            if let Some(x) = letter {
+                self.counts[x] += 1;
+                for period in 1..=MAX_PERIOD {
+                    self.columns[column_index(period, self.letters % period)][x] += 1;
+                }
+                if let [_, Some(b)] = self.last {
+                    self.bigrams[b * 26 + x] += 1;
+                    if let [Some(a), _] = self.last {
+                        self.trigrams[(a * 26 + b) * 26 + x] += 1;
+                    }
+                }
+            }
+            self.letters += 1;
+            self.last = [self.last[1], letter];
+        }
+    }
+
+    /// Returns the number of letters fed, including unknown ones.
+    pub fn letters(&self) -> usize {
+        self.letters
+    }
+
+    /// Returns how often each letter `A` to `Z` occurred.
+    pub fn letter_counts(&self) -> [usize; 26] {
+        self.counts
+    }
+
+    /// Returns the relative frequencies of the letters in percent, like
+    /// [`ENGLISH_FREQUENCIES`](super::ENGLISH_FREQUENCIES), or all zeros before any letter.
+    pub fn frequencies(&self) -> [f64; 26] {
+        let known: usize = self.counts.iter().sum();
+        self.counts
+            .map(|count| count as f64 * 100.0 / known.max(1) as f64)
+    }
+
+    /// Returns the index of coincidence of the letters, see
+    /// [`index_of_coincidence`](super::index_of_coincidence).
+    pub fn index_of_coincidence(&self) -> f64 {
+        coincidence(&self.counts)
+    }
+
+    /// Returns how often an n-gram of one to three letters occurred, ignoring case, or 0 for
+    /// anything else.
+    pub fn ngram_count(&self, ngram: &str) -> usize {
+        let letters: Vec<usize> = ngram
+            .chars()
+            .map(|c| {
+                c.is_ascii_alphabetic()
+                    .then(|| (c.to_ascii_uppercase() as u8 - b'A') as usize)
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        match letters[..] {
+            [a] => self.counts[a],
+            [a, b] => self.bigrams[a * 26 + b],
+            [a, b, c] => self.trigrams[(a * 26 + b) * 26 + c],
+            _ => 0,
+        }
+    }
+
+    /// Returns the `count` most frequent n-grams of `n` letters (1 to 3) with how often they
+    /// occurred, most frequent first and ties in alphabetical order.
+    pub fn top_ngrams(&self, n: usize, count: usize) -> Vec<(String, usize)> {
+        let table: &[usize] = match n {
+            1 => &self.counts,
+            2 => &self.bigrams,
+            3 => &self.trigrams,
+            _ => return Vec::new(),
+        };
+        let mut top: Vec<(usize, usize)> = table
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, occurrences)| occurrences > 0)
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        top.into_iter()
+            .take(count)
+            .map(|(index, occurrences)| {
+                let ngram = (0..n)
+                    .rev()
+                    .map(|place| (b'A' + (index / 26usize.pow(place as u32) % 26) as u8) as char)
+                    .collect();
+                (ngram, occurrences)
+            })
+            .collect()
+    }
+
+    /// Estimates the period of a polyalphabetic cipher up to 20, as
+    /// [`estimate_period`](super::estimate_period) does on the whole text.
+    pub fn estimate_period(&self) -> Option<usize> {
+        period_of(self.letters, MAX_PERIOD, |period, column| {
+            self.columns[column_index(period, column)]
+        })
+    }
+
+    /// Returns the statistics of the text so far, the same as
+    /// [`text_statistics`](super::text_statistics) of the whole text.
+    pub fn statistics(&self) -> TextStatistics {
+        statistics_of(self.letters, &self.counts, || self.estimate_period())
+    }
+}
+
+// Returns where the counts of a column of a period are kept.
+fn column_index(period: usize, column: usize) -> usize {
+    period * (period - 1) / 2 + column
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{estimate_period, index_of_coincidence, text_statistics};
+    use crate::ciphers::VigenereCipher;
+
+    #[test]
+    fn test_accumulator_matches_whole_text() {
+        let text = VigenereCipher::new("CRYPTO").encipher(
+            "It was the best of times, it was the worst of times, it was the age of wisdom, \
+             it was the age of foolishness, it was the epoch of belief",
+        );
+        let mut accumulator = AnalysisAccumulator::new();
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in chars.chunks(7) {
+            accumulator.feed(&chunk.iter().collect::<String>());
+        }
+        assert_eq!(accumulator.statistics(), text_statistics(&text));
+        assert_eq!(
+            accumulator.estimate_period(),
+            estimate_period(&text, MAX_PERIOD)
+        );
+        assert_eq!(accumulator.estimate_period(), Some(6));
+        assert_eq!(
+            accumulator.index_of_coincidence(),
+            index_of_coincidence(&text)
+        );
+    }
+
+    #[test]
+    fn test_accumulator_ngrams() {
+        let mut accumulator = AnalysisAccumulator::default();
+        assert_eq!(accumulator.frequencies(), [0.0; 26]);
+        accumulator.feed("The th");
+        accumulator.feed("e? Th-e!");
+        assert_eq!(accumulator.letters(), 10);
+        assert_eq!(accumulator.ngram_count("t"), 3);
+        assert_eq!(accumulator.ngram_count("TH"), 3);
+        // The unknown letter breaks the n-grams around it, punctuation does not
+        assert_eq!(accumulator.ngram_count("THE"), 3);
+        assert_eq!(accumulator.ngram_count("ETH"), 1);
+        assert_eq!(accumulator.ngram_count("EE"), 0);
+        assert_eq!(accumulator.ngram_count("T?"), 0);
+        assert_eq!(accumulator.ngram_count("THEM"), 0);
+        assert_eq!(accumulator.frequencies()[4], 100.0 / 3.0);
+        assert_eq!(
+            accumulator.top_ngrams(2, 3),
+            [
+                ("HE".to_string(), 3),
+                ("TH".to_string(), 3),
+                ("ET".to_string(), 1)
+            ]
+        );
+        assert_eq!(accumulator.top_ngrams(3, 1), [("THE".to_string(), 3)]);
+        assert!(accumulator.top_ngrams(4, 1).is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+//! Keyed check values that detect wrong keys and damaged messages.
+//!
+//! Classical ciphers decipher anything with any key, so a wrong key only shows up as garbage
+//! that has to be read to be noticed. [`Authenticated`] appends a short check value to the
+//! plaintext before enciphering and verifies it after deciphering.
+
+use crate::ciphers::{Cipher, FallibleCipher};
+
+// The number of letters in a check value, giving a 1 in 25^4 (about 390 000) chance that a
+// wrong key goes unnoticed.
+const CHECK_LETTERS: usize = 4;
+
+// The letters of check values. J is left out so ciphers that merge I and J keep it intact.
+const CHECK_ALPHABET: &[u8; 25] = b"ABCDEFGHIKLMNOPQRSTUVWXYZ";
+
+// Computes the CRC-32 (IEEE) of the bytes, continuing from `crc`.
+fn crc32(crc: u32, bytes: impl IntoIterator<Item = u8>) -> u32 {
+    !bytes.into_iter().fold(!crc, |crc, byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 == 1 {
+                crc >> 1 ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// A cipher whose messages carry a keyed check value.
+///
+/// The check value is a CRC-32 over the authentication key and the letters of the plaintext,
+/// truncated to four letters. It is appended to the plaintext after a space and enciphered with
+/// it, so it is hidden in the ciphertext. Only letters are checked, with J counted as I, so
+/// ciphers may change case, spacing and punctuation and merge I and J as 5x5 squares do, but
+/// ciphers that pad the end of the message fail to verify.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::authentication::Authenticated;
+/// use cipha::ciphers::VigenereCipher;
+///
+/// let cipher = Authenticated::new(VigenereCipher::new("LEMON"), "LEMON");
+/// let ciphertext = cipher.encipher("Attack at dawn");
+/// assert_eq!(cipher.decipher(&ciphertext).unwrap(), "Attack at dawn");
+///
+/// let wrong_key = Authenticated::new(VigenereCipher::new("LEMNO"), "LEMON");
+/// assert!(wrong_key.decipher(&ciphertext).is_err());
+/// ```
+///
+/// A wrong key is an error, never a plaintext, so `Authenticated` is a [`FallibleCipher`]
+/// rather than a [`Cipher`].
+pub struct Authenticated<C> {
+    cipher: C,
+    key: String,
+}
+
+impl<C: Cipher> Authenticated<C> {
+    /// Wraps a cipher so its messages carry a check value keyed by `key`.
+    pub fn new(cipher: C, key: &str) -> Self {
+        Authenticated {
+            cipher,
+            key: key.to_string(),
+        }
+    }
+
+    /// Returns the check value of a plaintext: four uppercase letters other than J.
+    pub fn check_value(&self, plaintext: &str) -> String {
+        let letters = |text: &str| {
+            text.bytes()
+                .filter(u8::is_ascii_alphabetic)
+                .map(|b| match b.to_ascii_uppercase() {
+                    b'J' => b'I',
+                    b => b,
+                })
+                .collect::<Vec<u8>>()
+        };
+        // The separator keeps the key and the plaintext letters from running into each other
+        let crc = crc32(crc32(0, letters(&self.key)), std::iter::once(0));
+        let mut value = crc32(crc, letters(plaintext));
+        (0..CHECK_LETTERS)
+            .map(|_| {
+                let letter = CHECK_ALPHABET[(value % 25) as usize] as char;
+                value /= 25;
+                letter
+            })
+            .collect()
+    }
+
+    /// Appends the check value to the plaintext and enciphers both.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.cipher
+            .encipher(&format!("{} {}", plaintext, self.check_value(plaintext)))
+    }
+
+    /// Deciphers the ciphertext, verifies the check value and returns the plaintext without it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the check value is missing or does not match, which means a wrong
+    /// key, a wrong cipher or a damaged message.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let text = self.cipher.decipher(ciphertext);
+        let mut check_start = text.len();
+        let mut check = Vec::with_capacity(CHECK_LETTERS);
+        for (i, c) in text.char_indices().rev() {
+            if check.len() == CHECK_LETTERS {
+                break;
+            }
+            if c.is_ascii_alphabetic() {
+                check.push(c.to_ascii_uppercase());
+                check_start = i;
+            }
+        }
+        if check.len() < CHECK_LETTERS {
+            return Err("The message is too short to hold a check value".to_string());
+        }
+        let check: String = check.into_iter().rev().collect();
+        let plaintext = text[..check_start]
+            .strip_suffix(' ')
+            .unwrap_or(&text[..check_start]);
+        if check != self.check_value(plaintext) {
+            return Err(
+                "Check value mismatch: wrong key, wrong cipher or damaged message".to_string(),
+            );
+        }
+        Ok(plaintext.to_string())
+    }
+}
+
+impl<C: Cipher> FallibleCipher for Authenticated<C> {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        Ok(self.encipher(plaintext))
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphers::{BifidCipher, CaesarCipher, ReverseWordsCipher};
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(0, *b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(crc32(0, *b"1234"), *b"56789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_authenticated_round_trip() {
+        let caesar = Authenticated::new(CaesarCipher::new(3), "secret");
+        let ciphertext = caesar.encipher("Meet me at noon!");
+        assert_eq!(caesar.decipher(&ciphertext).unwrap(), "Meet me at noon!");
+        assert_eq!(
+            caesar.check_value("MEET me at noon"),
+            caesar.check_value("Meet me at noon!")
+        );
+        assert_ne!(
+            caesar.check_value("Meet me at noon"),
+            Authenticated::new(CaesarCipher::new(3), "other").check_value("Meet me at noon")
+        );
+
+        // Bifid drops spaces and punctuation, the letters still verify
+        let bifid = Authenticated::new(BifidCipher::new("KEYWORD", 5).unwrap(), "KEYWORD");
+        let ciphertext = bifid.encipher("DEFEND THE EAST WALL");
+        assert_eq!(bifid.decipher(&ciphertext).unwrap(), "DEFENDTHEEASTWALL");
+        let ciphertext = bifid.encipher("JUST JOKING");
+        assert_eq!(bifid.decipher(&ciphertext).unwrap(), "IUSTIOKING");
+
+        // Transpositions undo their own rearrangement before the check
+        let reverse = Authenticated::new(ReverseWordsCipher::new(), "key");
+        assert_eq!(
+            reverse.decipher(&reverse.encipher("one two")).unwrap(),
+            "one two"
+        );
+    }
+
+    #[test]
+    fn test_authenticated_detects_wrong_key() {
+        let ciphertext =
+            Authenticated::new(CaesarCipher::new(3), "secret").encipher("Meet me at noon");
+        for shift in [2, 4, 13] {
+            let wrong = Authenticated::new(CaesarCipher::new(shift), "secret");
+            assert!(wrong.decipher(&ciphertext).is_err());
+        }
+        let wrong = Authenticated::new(CaesarCipher::new(3), "guess");
+        assert!(wrong.decipher(&ciphertext).is_err());
+        let damaged = ciphertext.replacen('P', "Q", 1);
+        assert!(Authenticated::new(CaesarCipher::new(3), "secret")
+            .decipher(&damaged)
+            .is_err());
+        assert!(Authenticated::new(CaesarCipher::new(3), "secret")
+            .decipher("ABC")
+            .is_err());
+    }
+
+    #[test]
+    fn test_authenticated_wrong_key_is_an_error_through_dyn() {
+        let ciphertext =
+            Authenticated::new(CaesarCipher::new(3), "secret").encipher("Meet me at noon");
+        let inner: &dyn Cipher = &CaesarCipher::new(4);
+        let wrong = Authenticated::new(inner, "secret");
+        assert!(wrong.decipher(&ciphertext).is_err());
+
+        let stage: &dyn FallibleCipher = &Authenticated::new(CaesarCipher::new(4), "secret");
+        assert!(stage.try_decipher(&ciphertext).is_err());
+        let stage: &dyn FallibleCipher = &Authenticated::new(CaesarCipher::new(3), "secret");
+        assert_eq!(stage.try_decipher(&ciphertext).unwrap(), "Meet me at noon");
+
+        let pipeline =
+            Pipeline::new().then_fallible(Authenticated::new(CaesarCipher::new(4), "secret"));
+        assert!(pipeline.decipher(&ciphertext).is_err());
+    }
+}
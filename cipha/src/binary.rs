@@ -0,0 +1,233 @@
+//! Ciphers and encodings over raw bytes.
+//!
+//! The ciphers in [`ciphers`](crate::ciphers) work on letters; the ones here work on arbitrary
+//! data, which is usually shown as hex or base64, so encoders for both are included.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as lowercase hexadecimal.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::binary::to_hex;
+/// assert_eq!(to_hex(b"\x00\xffHi"), "00ff4869");
+/// ```
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decodes hexadecimal in either case, ignoring whitespace.
+///
+/// # Errors
+///
+/// Returns an error on a non-hex character or an odd number of digits.
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits = hex
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(16)
+                .map(|d| d as u8)
+                .ok_or_else(|| format!("Invalid hex digit '{}'", c))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    if digits.len() % 2 != 0 {
+        return Err("Hex must have an even number of digits".to_string());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+/// Encodes bytes as standard base64 with `=` padding.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::binary::to_base64;
+/// assert_eq!(to_base64(b"Man"), "TWFu");
+/// assert_eq!(to_base64(b"Ma"), "TWE=");
+/// ```
+pub fn to_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, &b)| bits | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes standard base64, ignoring whitespace. The `=` padding is optional.
+///
+/// # Errors
+///
+/// Returns an error on a character outside the base64 alphabet or a truncated last group.
+pub fn from_base64(base64: &str) -> Result<Vec<u8>, String> {
+    let sextets = base64
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let sextets = sextets
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| {
+            BASE64_ALPHABET
+                .iter()
+                .position(|&b| b as char == c)
+                .map(|p| p as u32)
+                .ok_or_else(|| format!("Invalid base64 character '{}'", c))
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    if sextets.len() % 4 == 1 {
+        return Err("Base64 ends with an incomplete group".to_string());
+    }
+    let mut decoded = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, &s)| bits | s << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            decoded.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(decoded)
+}
+
+/// A repeating-key XOR cipher over bytes.
+///
+/// Every byte of the data is XORed with the next byte of the key, starting over at the end of
+/// the key. XOR undoes itself, so enciphering and deciphering are the same operation.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::binary::XorCipher;
+/// let cipher = XorCipher::new(b"ICE").unwrap();
+/// let hex = cipher.encipher_hex(b"Burning 'em");
+/// assert_eq!(hex, "0b3637272a2b2e63622c2e");
+/// assert_eq!(cipher.decipher_hex(&hex).unwrap(), b"Burning 'em");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XorCipher {
+    key: Vec<u8>,
+}
+
+impl XorCipher {
+    /// Creates a new instance of the XOR cipher with the specified key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is empty.
+    pub fn new(key: &[u8]) -> Result<Self, String> {
+        if key.is_empty() {
+            return Err("Key must not be empty".to_string());
+        }
+        Ok(XorCipher { key: key.to_vec() })
+    }
+
+    /// Returns the key.
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    // XORs the data with the repeated key.
+    fn apply(&self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .zip(self.key.iter().cycle())
+            .map(|(d, k)| d ^ k)
+            .collect()
+    }
+
+    /// Enciphers the plaintext bytes.
+    pub fn encipher(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.apply(plaintext)
+    }
+
+    /// Deciphers the ciphertext bytes.
+    pub fn decipher(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.apply(ciphertext)
+    }
+
+    /// Enciphers the plaintext bytes and encodes the result as hex.
+    pub fn encipher_hex(&self, plaintext: &[u8]) -> String {
+        to_hex(&self.encipher(plaintext))
+    }
+
+    /// Deciphers a hex encoded ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext is not valid hex.
+    pub fn decipher_hex(&self, ciphertext: &str) -> Result<Vec<u8>, String> {
+        Ok(self.decipher(&from_hex(ciphertext)?))
+    }
+
+    /// Enciphers the plaintext bytes and encodes the result as base64.
+    pub fn encipher_base64(&self, plaintext: &[u8]) -> String {
+        to_base64(&self.encipher(plaintext))
+    }
+
+    /// Deciphers a base64 encoded ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext is not valid base64.
+    pub fn decipher_base64(&self, ciphertext: &str) -> Result<Vec<u8>, String> {
+        Ok(self.decipher(&from_base64(ciphertext)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_and_base64() {
+        let hex = "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d";
+        let base64 = "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t";
+        let data = from_hex(hex).unwrap();
+        assert_eq!(to_base64(&data), base64);
+        assert_eq!(from_base64(base64).unwrap(), data);
+        assert_eq!(to_hex(&data), hex);
+        assert_eq!(from_hex("0A ff").unwrap(), vec![10, 255]);
+        assert!(from_hex("abc").is_err());
+        assert!(from_hex("zz").is_err());
+
+        for len in 0..7 {
+            let data: Vec<u8> = (250..=255).take(len).collect();
+            let encoded = to_base64(&data);
+            assert_eq!(encoded.len() % 4, 0);
+            assert_eq!(from_base64(&encoded).unwrap(), data);
+            assert_eq!(from_base64(encoded.trim_end_matches('=')).unwrap(), data);
+        }
+        assert!(from_base64("TWFu!").is_err());
+        assert!(from_base64("TWFuT").is_err());
+    }
+
+    #[test]
+    fn test_xor_cipher() {
+        let cipher = XorCipher::new(b"ICE").unwrap();
+        let plaintext =
+            b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+        let hex = "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f";
+        assert_eq!(cipher.encipher_hex(plaintext), hex);
+        assert_eq!(cipher.decipher_hex(hex).unwrap(), plaintext);
+        let base64 = cipher.encipher_base64(plaintext);
+        assert_eq!(cipher.decipher_base64(&base64).unwrap(), plaintext);
+        assert_eq!(cipher.decipher(&cipher.encipher(b"\x00\x01")), b"\x00\x01");
+        assert!(XorCipher::new(b"").is_err());
+    }
+}
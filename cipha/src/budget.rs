@@ -0,0 +1,154 @@
+//! Bounds on how much work a solver may do.
+//!
+//! Solvers take a [`Budget`] so embedders such as bots and servers can cap their worst-case
+//! latency. When a budget runs out the solver stops and returns the best result found so far.
+//!
+//! ```rust
+//! use cipha::budget::Budget;
+//! use std::time::Duration;
+//!
+//! let budget = Budget::unlimited()
+//!     .with_max_time(Duration::from_millis(200))
+//!     .with_max_iterations(100_000);
+//! let mut tracker = budget.start();
+//! while tracker.tick() {
+//!     // one unit of search work
+//! }
+//! assert!(tracker.exhausted());
+//! ```
+
+use std::time::{Duration, Instant};
+
+/// Limits on the time and number of iterations a solver may use.
+///
+/// A `None` limit is unbounded, so the default budget never runs out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    /// The maximum wall-clock time to spend.
+    pub max_time: Option<Duration>,
+    /// The maximum number of search iterations (what counts as one is up to each solver).
+    pub max_iterations: Option<u64>,
+}
+
+impl Budget {
+    /// Creates a budget without any limits.
+    pub fn unlimited() -> Self {
+        Budget::default()
+    }
+
+    /// Limits the wall-clock time.
+    pub fn with_max_time(mut self, max_time: Duration) -> Self {
+        self.max_time = Some(max_time);
+        self
+    }
+
+    /// Limits the number of iterations.
+    pub fn with_max_iterations(mut self, max_iterations: u64) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    /// Starts spending the budget.
+    pub fn start(&self) -> BudgetTracker {
+        BudgetTracker {
+            budget: *self,
+            started: Instant::now(),
+            iterations: 0,
+            exhausted: false,
+        }
+    }
+}
+
+/// Tracks how much of a [`Budget`] has been spent.
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: Budget,
+    started: Instant,
+    iterations: u64,
+    exhausted: bool,
+}
+
+impl BudgetTracker {
+    /// Records one iteration and returns `true` if the solver may perform it.
+    ///
+    /// Once this returns `false` it keeps returning `false`.
+    pub fn tick(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        let over_iterations = self
+            .budget
+            .max_iterations
+            .is_some_and(|max| self.iterations >= max);
+        let over_time = self
+            .budget
+            .max_time
+            .is_some_and(|max| self.started.elapsed() >= max);
+        if over_iterations || over_time {
+            self.exhausted = true;
+            return false;
+        }
+        self.iterations += 1;
+        true
+    }
+
+    /// Returns `true` if the budget ran out before the solver finished.
+    pub fn exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Returns the number of iterations performed so far.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Returns what is left of the budget, for the next stage of a search that shares it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::budget::Budget;
+    /// let mut tracker = Budget::unlimited().with_max_iterations(5).start();
+    /// tracker.tick();
+    /// assert_eq!(tracker.remaining().max_iterations, Some(4));
+    /// ```
+    pub fn remaining(&self) -> Budget {
+        Budget {
+            max_time: self
+                .budget
+                .max_time
+                .map(|max| max.saturating_sub(self.started.elapsed())),
+            max_iterations: self
+                .budget
+                .max_iterations
+                .map(|max| max.saturating_sub(self.iterations)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_budget() {
+        let mut tracker = Budget::unlimited().start();
+        assert!((0..1000).all(|_| tracker.tick()));
+        assert!(!tracker.exhausted());
+        assert_eq!(tracker.iterations(), 1000);
+    }
+
+    #[test]
+    fn test_iteration_budget() {
+        let mut tracker = Budget::unlimited().with_max_iterations(3).start();
+        assert_eq!((0..10).filter(|_| tracker.tick()).count(), 3);
+        assert!(tracker.exhausted());
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let mut tracker = Budget::unlimited().with_max_time(Duration::ZERO).start();
+        assert!(!tracker.tick());
+        assert!(tracker.exhausted());
+    }
+}
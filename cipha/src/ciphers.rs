@@ -1,8 +1,443 @@
+use crate::alphabet::Alphabet;
+use crate::fractionation::{defractionate, fractionate, regroup, seriate, unseriate, Fractionator};
+use crate::grid::{self, SpiralDirection};
+use crate::keystream::ChainAddition;
+use crate::math::{determinant, gcd, matrix_inverse, mod_inverse, modulo};
+use crate::pipeline::Elementary;
+use crate::text::{preprocess, LetterCase, NonAlphaPolicy, PreprocessOptions};
+
+/// The placeholder for an unknown or illegible character, e.g. from a damaged document.
+///
+/// Ciphers keep placeholders rather than dropping them. A placeholder takes up the position of
+/// the letter it stands for, so keyed ciphers stay aligned with their key, and anything that
+/// depends on an unknown letter (a Hill block, an autokey letter, a fractionated pair) is
+/// unknown too.
+pub const UNKNOWN: char = '?';
+
+/// A token of a ciphertext that no character enciphers to, such as a number outside a numeric
+/// substitution or an undefined Morse code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownToken {
+    /// The token as it appears in the ciphertext.
+    pub token: String,
+    /// The character offset of the token in the ciphertext, from 0.
+    pub position: usize,
+}
+
+impl std::fmt::Display for UnknownToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unknown token {:?} at position {}",
+            self.token, self.position
+        )
+    }
+}
+
+// Finds the first token between separators that is not known, with its character offset.
+fn first_unknown_token(
+    text: &str,
+    is_separator: impl Fn(char) -> bool,
+    is_known: impl Fn(&str) -> bool,
+) -> Option<UnknownToken> {
+    let mut start = None;
+    for (position, (i, c)) in text.char_indices().chain([(text.len(), ' ')]).enumerate() {
+        let ends = i == text.len() || is_separator(c);
+        match start {
+            Some((byte, first)) if ends => {
+                if !is_known(&text[byte..i]) {
+                    return Some(UnknownToken {
+                        token: text[byte..i].to_string(),
+                        position: first,
+                    });
+                }
+                start = None;
+            }
+            None if !ends => start = Some((i, position)),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A common interface implemented by every cipher in this module.
+///
+/// The trait is object safe, so different ciphers can be stored together and chained:
+///
+/// ```rust
+/// use cipha::ciphers::{Cipher, CaesarCipher, Rot13Cipher, VigenereCipher};
+///
+/// let ciphers: Vec<Box<dyn Cipher>> = vec![
+///     Box::new(CaesarCipher::new(3)),
+///     Box::new(VigenereCipher::new("LEMON")),
+///     Box::new(Rot13Cipher::new()),
+/// ];
+/// let encrypted = ciphers.iter().fold("Attack at dawn".to_string(), |text, c| c.encipher(&text));
+/// let decrypted = ciphers.iter().rev().fold(encrypted, |text, c| c.decipher(&text));
+/// assert_eq!(decrypted, "Attack at dawn");
+/// ```
+pub trait Cipher {
+    /// Enciphers a given plaintext.
+    fn encipher(&self, plaintext: &str) -> String;
+
+    /// Deciphers a given ciphertext.
+    fn decipher(&self, ciphertext: &str) -> String;
+
+    /// Returns the plaintext to ciphertext mapping of the uppercase letters, for ciphers that
+    /// replace every letter by the same letter wherever it occurs.
+    ///
+    /// The default returns `None`, for ciphers whose output depends on a letter's position or
+    /// that do not map letters to single letters.
+    ///
+    /// ```rust
+    /// use cipha::ciphers::{CaesarCipher, Cipher, VigenereCipher};
+    /// let table = CaesarCipher::new(3).mapping_table().unwrap();
+    /// assert_eq!(table[0], ('A', 'D'));
+    /// assert_eq!(table[25], ('Z', 'C'));
+    /// assert!(VigenereCipher::new("LEMON").mapping_table().is_none());
+    /// ```
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        None
+    }
+
+    /// Returns the cipher as an [`Elementary`] stage, for ciphers the pipeline simplifier can
+    /// merge with their neighbours. The default returns `None`.
+    ///
+    /// ```rust
+    /// use cipha::ciphers::{Cipher, Rot13Cipher};
+    /// use cipha::pipeline::Elementary;
+    /// assert_eq!(Rot13Cipher::new().elementary(), Some(Elementary::Shift(13)));
+    /// ```
+    fn elementary(&self) -> Option<Elementary> {
+        None
+    }
+}
+
+/// A borrowed cipher, such as a `&dyn Cipher`, can be wrapped like an owned one.
+impl<C: Cipher + ?Sized> Cipher for &C {
+    fn encipher(&self, plaintext: &str) -> String {
+        (**self).encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        (**self).decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        (**self).mapping_table()
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        (**self).elementary()
+    }
+}
+
+/// A cipher that can reject a message, such as an authenticated cipher given a wrong key or a
+/// running key cipher whose key text runs out.
+///
+/// Such ciphers do not implement [`Cipher`], which always returns a text, so an error can never
+/// pass for a ciphertext or plaintext. Every [`Cipher`] is a `FallibleCipher` that never fails,
+/// so both kinds can be stages of the same [`Pipeline`](crate::pipeline::Pipeline):
+///
+/// ```rust
+/// use cipha::authentication::Authenticated;
+/// use cipha::ciphers::{CaesarCipher, FallibleCipher};
+///
+/// let stages: Vec<Box<dyn FallibleCipher>> = vec![
+///     Box::new(CaesarCipher::new(3)),
+///     Box::new(Authenticated::new(CaesarCipher::new(3), "secret")),
+/// ];
+/// assert_eq!(stages[0].try_encipher("Hello").unwrap(), "Khoor");
+/// assert!(stages[1].try_decipher("Khoor").is_err());
+/// ```
+pub trait FallibleCipher {
+    /// Enciphers a given plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cipher cannot encipher the plaintext.
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String>;
+
+    /// Deciphers a given ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cipher cannot decipher the ciphertext.
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String>;
+
+    /// Wraps the cipher so its plaintext passes `policy` before it is enciphered. Every cipher
+    /// takes a [`NonAlphaPolicy`] this way.
+    ///
+    /// ```rust
+    /// use cipha::ciphers::{FallibleCipher, RailFenceCipher, RunningKeyCipher};
+    /// use cipha::text::NonAlphaPolicy;
+    /// let cipher = RailFenceCipher::new(2).unwrap().with_non_alpha(NonAlphaPolicy::Strip);
+    /// assert_eq!(cipher.encipher("WE ARE DISCOVERED!"), Ok("WAEICVRDERDSOEE".to_string()));
+    /// let cipher = RunningKeyCipher::new("Call me Ishmael").with_non_alpha(NonAlphaPolicy::Error);
+    /// assert!(cipher.encipher("Hello, World").is_err());
+    /// ```
+    fn with_non_alpha(self, policy: NonAlphaPolicy) -> Filtered<Self>
+    where
+        Self: Sized,
+    {
+        Filtered::new(self, policy)
+    }
+}
+
+impl<C: Cipher + ?Sized> FallibleCipher for C {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        Ok(self.encipher(plaintext))
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        Ok(self.decipher(ciphertext))
+    }
+}
+
+/// A cipher whose output letters follow a [`LetterCase`] policy, one for the ciphertext and one
+/// for the plaintext.
+///
+/// Every cipher handles case its own way: some keep the case of the input, some put out
+/// capitals only. Wrapping a cipher makes its output follow the same convention as every other
+/// wrapped cipher. Mind that the policy also applies to encodings whose case carries
+/// information, such as base64. That is why case is an opt-in wrapper rather than a setting of
+/// every cipher: ciphers like [`Rot47Cipher`], whose ciphertext depends on the case, are only
+/// changed when asked to.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{Cased, Cipher, VigenereCipher};
+/// use cipha::text::LetterCase;
+///
+/// // The American Cryptogram Association convention
+/// let cipher = Cased::aca(VigenereCipher::new("LEMON"));
+/// assert_eq!(cipher.encipher("Attack at dawn"), "LXFOPV EF RNHR");
+/// assert_eq!(cipher.decipher("LXFOPV EF RNHR"), "attack at dawn");
+///
+/// let cipher = Cased::new(VigenereCipher::new("LEMON"), LetterCase::Lower, LetterCase::Preserve);
+/// assert_eq!(cipher.encipher("ATTACK"), "lxfopv");
+/// ```
+pub struct Cased<C> {
+    cipher: C,
+    ciphertext: LetterCase,
+    plaintext: LetterCase,
+}
+
+impl<C: Cipher> Cased<C> {
+    /// Wraps a cipher so its ciphertext follows `ciphertext` and its plaintext `plaintext`.
+    pub fn new(cipher: C, ciphertext: LetterCase, plaintext: LetterCase) -> Self {
+        Cased {
+            cipher,
+            ciphertext,
+            plaintext,
+        }
+    }
+
+    /// Wraps a cipher to write capital ciphertext and lowercase plaintext, as the American
+    /// Cryptogram Association does.
+    pub fn aca(cipher: C) -> Self {
+        Cased::new(cipher, LetterCase::Upper, LetterCase::Lower)
+    }
+
+    /// Returns the wrapped cipher.
+    pub fn inner(&self) -> &C {
+        &self.cipher
+    }
+}
+
+impl<C: Cipher> Cipher for Cased<C> {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.ciphertext.apply(&self.cipher.encipher(plaintext))
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.plaintext.apply(&self.cipher.decipher(ciphertext))
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        self.cipher.mapping_table()
+    }
+}
+
+/// A cipher whose plaintext is prepared with [`preprocess`] before it is enciphered.
+///
+/// Most ciphers only encipher the letters `A` to `Z` and pass anything else through, so the
+/// accented letters of a French or German text would be left in the clear. Normalizing first
+/// enciphers them as their base letters, and the ciphertext deciphers to the normalized text.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{Cipher, Normalized, VigenereCipher};
+/// use cipha::text::PreprocessOptions;
+///
+/// let cipher = Normalized::new(VigenereCipher::new("LEMON"), PreprocessOptions::ascii());
+/// let ciphertext = cipher.encipher("Ça déçoit à Köln");
+/// assert_eq!(ciphertext, "ne pspzmf o xzpz");
+/// assert_eq!(cipher.decipher(&ciphertext), "ca decoit a koln");
+/// ```
+pub struct Normalized<C> {
+    cipher: C,
+    options: PreprocessOptions,
+}
+
+impl<C: Cipher> Normalized<C> {
+    /// Wraps a cipher so its plaintext is normalized with `options` before enciphering.
+    pub fn new(cipher: C, options: PreprocessOptions) -> Self {
+        Normalized { cipher, options }
+    }
+
+    /// Returns the wrapped cipher.
+    pub fn inner(&self) -> &C {
+        &self.cipher
+    }
+}
+
+impl<C: Cipher> Cipher for Normalized<C> {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.cipher.encipher(&preprocess(plaintext, &self.options))
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.cipher.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        self.cipher.mapping_table()
+    }
+}
+
+/// A cipher whose plaintext passes a [`NonAlphaPolicy`] before it is enciphered.
+///
+/// The policy decides what happens to spaces, digits and punctuation whatever the cipher would
+/// do with them. Any cipher can be wrapped, with [`Filtered::new`] or
+/// [`FallibleCipher::with_non_alpha`]. [`NonAlphaPolicy::Error`] rejects a plaintext that is not
+/// all letters, so a filtered cipher is a [`FallibleCipher`] rather than a [`Cipher`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{CaesarCipher, Filtered};
+/// use cipha::text::NonAlphaPolicy;
+///
+/// let cipher = Filtered::new(CaesarCipher::new(3), NonAlphaPolicy::Strip);
+/// assert_eq!(cipher.encipher("Meet me at 10!"), Ok("Phhwphdw".to_string()));
+/// let cipher = Filtered::new(CaesarCipher::new(3), NonAlphaPolicy::Substitute('x'));
+/// assert_eq!(cipher.encipher("Meet me"), Ok("Phhwaph".to_string()));
+/// let cipher = Filtered::new(CaesarCipher::new(3), NonAlphaPolicy::Error);
+/// assert_eq!(cipher.encipher("Meet"), Ok("Phhw".to_string()));
+/// assert!(cipher.encipher("Meet me").is_err());
+/// ```
+pub struct Filtered<C> {
+    cipher: C,
+    policy: NonAlphaPolicy,
+}
+
+impl<C: FallibleCipher> Filtered<C> {
+    /// Wraps a cipher so its plaintext passes `policy` before enciphering.
+    pub fn new(cipher: C, policy: NonAlphaPolicy) -> Self {
+        Filtered { cipher, policy }
+    }
+
+    /// Returns the wrapped cipher.
+    pub fn inner(&self) -> &C {
+        &self.cipher
+    }
+
+    /// Enciphers a given plaintext after applying the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy rejects the plaintext or the cipher fails.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.cipher.try_encipher(&self.policy.apply(plaintext)?)
+    }
+
+    /// Deciphers a given ciphertext. The policy only applies to plaintexts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cipher fails.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.cipher.try_decipher(ciphertext)
+    }
+}
+
+impl<C: FallibleCipher> FallibleCipher for Filtered<C> {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A cipher that only moves the characters of the text.
+///
+/// The permutation describes the move as an index map, so it can be drawn or applied to data
+/// running in parallel with the text with [`grid::permute`]:
+///
+/// ```rust
+/// use cipha::ciphers::{Cipher, RailFenceCipher, Transposition};
+/// use cipha::grid::permute;
+///
+/// let cipher = RailFenceCipher::new(2).unwrap();
+/// let permutation = cipher.permutation(5);
+/// assert_eq!(permutation, vec![0, 3, 1, 4, 2]);
+/// assert_eq!(cipher.encipher("HELLO"), "HLOEL");
+/// assert_eq!(permute(&[1, 2, 3, 4, 5], &permutation), vec![1, 3, 5, 2, 4]);
+/// ```
+pub trait Transposition {
+    /// Returns the ciphertext position of every character of a plaintext of `len` characters:
+    /// entry `i` is where the character at position `i` ends up.
+    ///
+    /// With padding the ciphertext is longer than the plaintext, and the positions of the
+    /// padding characters are missing from the map.
+    fn permutation(&self, len: usize) -> Vec<usize>;
+}
+
+// Returns the positions of the first `len` characters of a padded text read along the route.
+fn padded_permutation(route: &[usize], len: usize) -> Vec<usize> {
+    let mut permutation = grid::inverse(route);
+    permutation.truncate(len);
+    permutation
+}
+
+// Returns the mapping table of a cipher that maps every uppercase letter to a single letter.
+fn letter_mapping(encipher: impl Fn(&str) -> String) -> Vec<(char, char)> {
+    ('A'..='Z')
+        .filter_map(|p| Some((p, encipher(&p.to_string()).chars().next()?)))
+        .collect()
+}
+
+// Returns the mapping table of a cipher over the letters of its alphabet, or A to Z.
+fn alphabet_mapping(
+    alphabet: Option<&Alphabet>,
+    encipher: impl Fn(&str) -> String,
+) -> Vec<(char, char)> {
+    match alphabet {
+        Some(alphabet) => alphabet
+            .letters()
+            .chars()
+            .filter_map(|p| Some((p, encipher(&p.to_string()).chars().next()?)))
+            .collect(),
+        None => letter_mapping(encipher),
+    }
+}
+
 /// A simple ROT13 cipher implementation.
 ///
 /// ROT13 is a special case of the Caesar cipher, where each letter is shifted 13 positions forward in the alphabet.
 pub struct Rot13Cipher;
 
+impl Default for Rot13Cipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Rot13Cipher {
     /// Creates a new instance of the ROT13 cipher.
     pub fn new() -> Self {
@@ -13,404 +448,8531 @@ impl Rot13Cipher {
     ///
     /// This function shifts each letter in the message by 13 positions forward in the alphabet.
     pub fn encipher(&self, message: &str) -> String {
-        message.chars().map(|c| {
-            match c {
+        message
+            .chars()
+            .map(|c| match c {
                 'a'..='z' => (((c as u8 - b'a' + 13) % 26) + b'a') as char,
                 'A'..='Z' => (((c as u8 - b'A' + 13) % 26) + b'A') as char,
                 _ => c,
-            }
-        }).collect()
+            })
+            .collect()
+    }
+
+    /// Deciphers a given message using the ROT13 cipher.
+    ///
+    /// Since ROT13 is symmetric, this method is identical to `encipher`.
+    pub fn decipher(&self, message: &str) -> String {
+        self.encipher(message)
+    }
+}
+
+impl Cipher for Rot13Cipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(letter_mapping(|p| self.encipher(p)))
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        Some(Elementary::Shift(13))
+    }
+}
+
+/// The ROT5 cipher, which rotates the digits by 5 and leaves everything else alone.
+///
+/// It hides numbers that ROT13 passes through, and like ROT13 it is its own inverse.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::Rot5Cipher;
+/// assert_eq!(Rot5Cipher::new().encipher("Call 555-0123"), "Call 000-5678");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rot5Cipher;
+
+impl Default for Rot5Cipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rot5Cipher {
+    /// Creates a new instance of the ROT5 cipher.
+    pub fn new() -> Self {
+        Rot5Cipher
+    }
+
+    /// Shifts each digit of the message 5 places on, wrapping from 9 to 0.
+    pub fn encipher(&self, message: &str) -> String {
+        message
+            .chars()
+            .map(|c| match c {
+                '0'..='9' => (((c as u8 - b'0' + 5) % 10) + b'0') as char,
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Deciphers a message, the same as enciphering it.
+    pub fn decipher(&self, message: &str) -> String {
+        self.encipher(message)
+    }
+}
+
+impl Cipher for Rot5Cipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(
+            ('0'..='9')
+                .filter_map(|d| Some((d, self.encipher(&d.to_string()).chars().next()?)))
+                .collect(),
+        )
+    }
+}
+
+/// The ROT18 cipher, ROT13 on the letters and ROT5 on the digits.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::Rot18Cipher;
+/// assert_eq!(Rot18Cipher::new().encipher("Room 101"), "Ebbz 656");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rot18Cipher;
+
+impl Default for Rot18Cipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rot18Cipher {
+    /// Creates a new instance of the ROT18 cipher.
+    pub fn new() -> Self {
+        Rot18Cipher
+    }
+
+    /// Shifts each letter of the message 13 places and each digit 5 places on.
+    pub fn encipher(&self, message: &str) -> String {
+        Rot5Cipher.encipher(&Rot13Cipher.encipher(message))
+    }
+
+    /// Deciphers a message, the same as enciphering it.
+    pub fn decipher(&self, message: &str) -> String {
+        self.encipher(message)
+    }
+}
+
+impl Cipher for Rot18Cipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(letter_mapping(|p| self.encipher(p)))
+    }
+}
+
+/// The ROT47 cipher, which rotates the 94 printable ASCII characters from `!` to `~` by 47.
+///
+/// Letters, digits and punctuation are all scrambled, while spaces and anything outside the
+/// printable ASCII range are left alone. Shifting by half the range makes it its own inverse.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::Rot47Cipher;
+/// let rot47 = Rot47Cipher::new();
+/// assert_eq!(rot47.encipher("Hello, World!"), "w6==@[ (@C=5P");
+/// assert_eq!(rot47.decipher("w6==@[ (@C=5P"), "Hello, World!");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rot47Cipher;
+
+impl Default for Rot47Cipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rot47Cipher {
+    /// Creates a new instance of the ROT47 cipher.
+    pub fn new() -> Self {
+        Rot47Cipher
+    }
+
+    /// Shifts each printable ASCII character of the message 47 places on, wrapping from `~`
+    /// to `!`.
+    pub fn encipher(&self, message: &str) -> String {
+        message
+            .chars()
+            .map(|c| match c {
+                '!'..='~' => (((c as u8 - b'!' + 47) % 94) + b'!') as char,
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Deciphers a message, the same as enciphering it.
+    pub fn decipher(&self, message: &str) -> String {
+        self.encipher(message)
+    }
+}
+
+impl Cipher for Rot47Cipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(letter_mapping(|p| self.encipher(p)))
+    }
+}
+
+/// A rotation by `shift` places around any alphabet, such as Cyrillic, Greek or a set of
+/// symbols.
+///
+/// Characters of the [`Alphabet`] are shifted along it, wrapping from the last to the first,
+/// and everything else is left alone. The characters are taken exactly as given, so an alphabet
+/// can hold both cases of a letter as different letters, as in ROT47. To shift the other case
+/// of the letters along with them, build the rotation from an [`Alphabet`] with
+/// [`RotN::with_alphabet`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::alphabet::Alphabet;
+/// use cipha::ciphers::RotN;
+/// let greek = RotN::new(3, "αβγδεζηθικλμνξοπρστυφχψω").unwrap();
+/// assert_eq!(greek.encipher("καλημέρα"), "νδξκοέυδ");
+/// assert_eq!(greek.decipher("νδξκοέυδ"), "καλημέρα");
+///
+/// let greek = RotN::with_alphabet(3, Alphabet::greek());
+/// assert_eq!(greek.encipher("Καλημέρα"), "Νδξκοέυδ");
+///
+/// let russian = RotN::reciprocal("абвгдеёжзийклмнопрстуфхцчшщъыьэюя").unwrap();
+/// assert_eq!(russian.shift(), 16);
+/// assert_eq!(russian.decipher(&russian.encipher("Привет")), "Привет");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotN {
+    alphabet: Alphabet,
+    shift: usize,
+}
+
+impl RotN {
+    /// Creates a rotation by `shift` places around the characters of `alphabet`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alphabet is empty or has a character twice (see
+    /// [`Alphabet::exact`]).
+    pub fn new(shift: usize, alphabet: &str) -> Result<Self, String> {
+        Ok(RotN::with_alphabet(shift, Alphabet::exact(alphabet)?))
+    }
+
+    /// Creates a rotation by `shift` places around an [`Alphabet`], whose letters keep their
+    /// case.
+    pub fn with_alphabet(shift: usize, alphabet: Alphabet) -> Self {
+        let shift = shift % alphabet.len();
+        RotN { alphabet, shift }
+    }
+
+    /// Creates a rotation by half the alphabet, the generalization of ROT13, which is its own
+    /// inverse if the alphabet has an even length.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alphabet is invalid (see [`RotN::new`]).
+    pub fn reciprocal(alphabet: &str) -> Result<Self, String> {
+        RotN::new(alphabet.chars().count() / 2, alphabet)
+    }
+
+    /// Returns the shift, less than the length of the alphabet.
+    pub fn shift(&self) -> usize {
+        self.shift
+    }
+
+    /// Returns the alphabet.
+    pub fn alphabet(&self) -> String {
+        self.alphabet.letters()
+    }
+
+    /// Shifts each character of the alphabet in the message `shift` places on.
+    pub fn encipher(&self, message: &str) -> String {
+        message
+            .chars()
+            .map(|c| self.alphabet.replace(c, |i| i + self.shift))
+            .collect()
+    }
+
+    /// Shifts each character of the alphabet in the message `shift` places back.
+    pub fn decipher(&self, message: &str) -> String {
+        let back = self.alphabet.len() - self.shift;
+        message
+            .chars()
+            .map(|c| self.alphabet.replace(c, |i| i + back))
+            .collect()
+    }
+}
+
+impl Cipher for RotN {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A Caesar cipher implementation with a variable shift.
+///
+/// The Caesar cipher is a type of substitution cipher where each letter in the plaintext is 'shifted' a certain number of places down the alphabet.
+///
+/// Some puzzles shift lowercase letters by a different amount than capitals, and digits by a
+/// third amount around 0-9; [`CaesarCipher::with_lower_shift`] and
+/// [`CaesarCipher::with_digit_shift`] set those. Digits are left unchanged by default. The
+/// letters are `A` to `Z` unless another [`Alphabet`] is set with
+/// [`CaesarCipher::with_alphabet`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::CaesarCipher;
+/// let cipher = CaesarCipher::new(3).with_lower_shift(7).with_digit_shift(5);
+/// assert_eq!(cipher.encipher("Agent 007"), "Dnlua 552");
+/// assert_eq!(cipher.decipher("Dnlua 552"), "Agent 007");
+/// ```
+pub struct CaesarCipher {
+    shift: u8,
+    // Shift of lowercase letters, if it differs from that of capitals
+    lower_shift: Option<u8>,
+    // Shift of digits, if they are shifted at all
+    digit_shift: Option<u8>,
+    // Letters to shift in place of A to Z
+    alphabet: Option<Alphabet>,
+}
+
+impl CaesarCipher {
+    /// Creates a new instance of the Caesar cipher with the specified shift.
+    pub fn new(shift: u8) -> Self {
+        CaesarCipher {
+            shift,
+            lower_shift: None,
+            digit_shift: None,
+            alphabet: None,
+        }
+    }
+
+    /// Shifts the letters of `alphabet` around it in place of `A` to `Z`.
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::CaesarCipher;
+    /// let caesar = CaesarCipher::new(1).with_alphabet(Alphabet::turkish());
+    /// assert_eq!(caesar.encipher("Işık"), "İtil");
+    /// ```
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+
+    /// Shifts lowercase letters by `shift` instead of the shift of capitals.
+    pub fn with_lower_shift(mut self, shift: u8) -> Self {
+        self.lower_shift = Some(shift);
+        self
+    }
+
+    /// Shifts digits by `shift` around 0-9.
+    pub fn with_digit_shift(mut self, shift: u8) -> Self {
+        self.digit_shift = Some(shift);
+        self
+    }
+
+    /// Enciphers a given message using the Caesar cipher.
+    ///
+    /// This function shifts each letter in the message by the specified shift.
+    pub fn encipher(&self, message: &str) -> String {
+        message.chars().map(|c| self.shifted(c, false)).collect()
+    }
+
+    /// Deciphers a given message using the Caesar cipher.
+    ///
+    /// This function shifts each letter in the message by the reverse of the specified shift.
+    pub fn decipher(&self, message: &str) -> String {
+        message.chars().map(|c| self.shifted(c, true)).collect()
+    }
+
+    // Shifts a character forwards, or backwards to decipher.
+    fn shifted(&self, c: char, reverse: bool) -> char {
+        if let Some(alphabet) = &self.alphabet {
+            if alphabet.index_of(c).is_some() {
+                let shift = match c.is_lowercase() {
+                    true => self.lower_shift.unwrap_or(self.shift),
+                    false => self.shift,
+                } as usize
+                    % alphabet.len();
+                let shift = if reverse {
+                    alphabet.len() - shift
+                } else {
+                    shift
+                };
+                return alphabet.replace(c, |i| i + shift);
+            }
+        }
+        let (base, size, shift) = match c {
+            _ if c.is_ascii_alphabetic() && self.alphabet.is_some() => return c,
+            'A'..='Z' => (b'A', 26, self.shift),
+            'a'..='z' => (b'a', 26, self.lower_shift.unwrap_or(self.shift)),
+            '0'..='9' => match self.digit_shift {
+                Some(shift) => (b'0', 10, shift),
+                None => return c,
+            },
+            _ => return c,
+        };
+        let shift = shift % size;
+        let shift = if reverse { size - shift } else { shift };
+        ((c as u8 - base + shift) % size + base) as char
+    }
+}
+
+impl Cipher for CaesarCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(alphabet_mapping(self.alphabet.as_ref(), |p| {
+            self.encipher(p)
+        }))
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        // Only a single shift of every letter composes with the other stages of a chain
+        let same_case = self
+            .lower_shift
+            .is_none_or(|lower| lower % 26 == self.shift % 26);
+        let latin = self.alphabet.as_ref().is_none_or(Alphabet::is_latin);
+        (same_case && latin && self.digit_shift.is_none())
+            .then_some(Elementary::Shift(self.shift % 26))
+    }
+}
+
+/// An Affine cipher implementation.
+///
+/// Each letter `x` (A=0 ... Z=25) is enciphered as `E(x) = (ax + b) mod 26` and deciphered with
+/// the modular inverse of `a` as `D(y) = a⁻¹(y - b) mod 26`. Case is preserved and other
+/// characters are left unchanged. With another [`Alphabet`], set by
+/// [`AffineCipher::with_alphabet`], the letters are counted in it and 26 becomes its length.
+pub struct AffineCipher {
+    // The key as given, to reduce modulo the length of another alphabet
+    key: (i64, i64),
+    a: i64,
+    b: i64,
+    a_inverse: i64,
+    alphabet: Option<Alphabet>,
+}
+
+impl AffineCipher {
+    /// Creates a new instance of the Affine cipher with multiplier `a` and offset `b`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `a` is not coprime with 26, since the cipher could not be deciphered.
+    pub fn new(a: i64, b: i64) -> Result<Self, String> {
+        AffineCipher::keyed(a, b, None)
+    }
+
+    /// Counts the letters in `alphabet` in place of `A` to `Z`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `a` is not coprime with the length of the alphabet.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::AffineCipher;
+    /// let affine = AffineCipher::new(5, 8).unwrap().with_alphabet(Alphabet::greek()).unwrap();
+    /// assert_eq!(affine.decipher(&affine.encipher("Αθήνα")), "Αθήνα");
+    /// // 3 is coprime with 26 but not with the 33 letters of the Russian alphabet
+    /// assert!(AffineCipher::new(3, 1).unwrap().with_alphabet(Alphabet::russian()).is_err());
+    /// ```
+    pub fn with_alphabet(self, alphabet: Alphabet) -> Result<Self, String> {
+        AffineCipher::keyed(self.key.0, self.key.1, Some(alphabet))
+    }
+
+    fn keyed(a: i64, b: i64, alphabet: Option<Alphabet>) -> Result<Self, String> {
+        let size = alphabet
+            .as_ref()
+            .map_or(26, |alphabet| alphabet.len() as i64);
+        if gcd(a, size) != 1 {
+            return Err(format!("Key a = {} must be coprime with {}", a, size));
+        }
+        let a_inverse = mod_inverse(a, size).expect("a is coprime with the alphabet length");
+        Ok(AffineCipher {
+            key: (a, b),
+            a: modulo(a, size),
+            b: modulo(b, size),
+            a_inverse,
+            alphabet,
+        })
+    }
+
+    // Applies `f` to the alphabet index of every letter.
+    fn map_letters(&self, text: &str, f: impl Fn(i64) -> i64) -> String {
+        if let Some(alphabet) = &self.alphabet {
+            return text
+                .chars()
+                .map(|c| alphabet.replace(c, |i| f(i as i64) as usize))
+                .collect();
+        }
+        text.chars()
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                    (first + f((c as u8 - first) as i64) as u8) as char
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+
+    // Returns the number of letters.
+    fn size(&self) -> i64 {
+        self.alphabet
+            .as_ref()
+            .map_or(26, |alphabet| alphabet.len() as i64)
+    }
+
+    /// Enciphers a given plaintext using the Affine cipher.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.map_letters(plaintext, |x| modulo(self.a * x + self.b, self.size()))
+    }
+
+    /// Deciphers a given ciphertext using the Affine cipher.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.map_letters(ciphertext, |y| {
+            modulo(self.a_inverse * (y - self.b), self.size())
+        })
+    }
+}
+
+impl Cipher for AffineCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(alphabet_mapping(self.alphabet.as_ref(), |p| {
+            self.encipher(p)
+        }))
+    }
+}
+
+/// A Hill cipher implementation with an NxN key matrix.
+///
+/// The letters of the message are split into blocks of N, and each block is treated as a column
+/// vector `p` (A=0 ... Z=25) and enciphered as `K·p mod 26`. Non-letters are dropped, the output
+/// is uppercase, and the last block is filled with the padding letter (`X` by default). Padding
+/// is not removed when deciphering.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::HillCipher;
+/// let hill = HillCipher::new(vec![vec![3, 3], vec![2, 5]]).unwrap();
+/// assert_eq!(hill.encipher("help"), "HIAT");
+/// assert_eq!(hill.decipher("HIAT"), "HELP");
+/// ```
+pub struct HillCipher {
+    key: Vec<Vec<i64>>,
+    key_inverse: Vec<Vec<i64>>,
+    padding: char,
+}
+
+impl HillCipher {
+    /// Creates a new instance of the Hill cipher with the given key matrix, given as rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the matrix is empty or not square, or if it is not invertible modulo
+    /// 26 (its determinant shares a factor with 26).
+    pub fn new(key: Vec<Vec<i64>>) -> Result<Self, String> {
+        let n = key.len();
+        if n == 0 || key.iter().any(|row| row.len() != n) {
+            return Err("Key matrix must be square and non-empty".to_string());
+        }
+        let key: Vec<Vec<i64>> = key
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| modulo(v, 26)).collect())
+            .collect();
+        let key_inverse = matrix_inverse(&key, 26).ok_or(format!(
+            "Key matrix is not invertible mod 26 (determinant {} shares a factor with 26)",
+            determinant(&key, 26)
+        ))?;
+        Ok(HillCipher {
+            key,
+            key_inverse,
+            padding: 'X',
+        })
+    }
+
+    /// Sets the letter used to fill the last block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `padding` is not an ASCII letter.
+    pub fn with_padding(mut self, padding: char) -> Result<Self, String> {
+        if !padding.is_ascii_alphabetic() {
+            return Err(format!("Padding must be a letter, found {:?}", padding));
+        }
+        self.padding = padding.to_ascii_uppercase();
+        Ok(self)
+    }
+
+    // Multiplies every block of letters by `matrix`. Blocks with an unknown letter are unknown.
+    fn apply(&self, text: &str, matrix: &[Vec<i64>]) -> String {
+        let n = matrix.len();
+        let mut letters: Vec<Option<i64>> = text
+            .chars()
+            .filter(|&c| c.is_ascii_alphabetic() || c == UNKNOWN)
+            .map(|c| {
+                c.is_ascii_alphabetic()
+                    .then(|| (c.to_ascii_uppercase() as u8 - b'A') as i64)
+            })
+            .collect();
+        while !letters.len().is_multiple_of(n) {
+            letters.push(Some((self.padding as u8 - b'A') as i64));
+        }
+        letters
+            .chunks(n)
+            .flat_map(|block| {
+                let block: Option<Vec<i64>> = block.iter().copied().collect();
+                matrix.iter().map(move |row| match &block {
+                    Some(block) => {
+                        let value = row.iter().zip(block).map(|(k, p)| k * p).sum::<i64>();
+                        (b'A' + modulo(value, 26) as u8) as char
+                    }
+                    None => UNKNOWN,
+                })
+            })
+            .collect()
+    }
+
+    /// Enciphers a given plaintext using the Hill cipher.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.apply(plaintext, &self.key)
+    }
+
+    /// Deciphers a given ciphertext using the Hill cipher.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.apply(ciphertext, &self.key_inverse)
+    }
+}
+
+impl Cipher for HillCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A general monoalphabetic substitution cipher.
+///
+/// Each plaintext letter `A ... Z` is replaced by the letter at the same position in the cipher
+/// alphabet. The alphabet can be given explicitly or mixed from a keyword. Case is preserved and
+/// other characters are left unchanged. The `_in` constructors substitute the letters of
+/// another [`Alphabet`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::SubstitutionCipher;
+/// let cipher = SubstitutionCipher::from_keyword("ZEBRAS").unwrap();
+/// assert_eq!(cipher.alphabet(), "ZEBRASCDFGHIJKLMNOPQTUVWXY");
+/// assert_eq!(cipher.encipher("Flee at once!"), "Siaa zq lkba!");
+/// assert_eq!(cipher.decipher("Siaa zq lkba!"), "Flee at once!");
+/// ```
+pub struct SubstitutionCipher {
+    plain: Alphabet,
+    // Position in the plain alphabet of the substitute of every letter, and of its original
+    mapping: Vec<usize>,
+    inverse: Vec<usize>,
+}
+
+impl SubstitutionCipher {
+    /// Creates a new substitution cipher from an explicit cipher alphabet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alphabet is not a permutation of the 26 letters.
+    pub fn from_alphabet(alphabet: &str) -> Result<Self, String> {
+        Self::from_alphabet_in(Alphabet::latin(), alphabet)
+    }
+
+    /// Creates a new substitution cipher of the letters of `plain` from a cipher alphabet of
+    /// the same letters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cipher alphabet is not a permutation of the letters of `plain`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::SubstitutionCipher;
+    /// let cipher = SubstitutionCipher::from_keyword_in(Alphabet::greek(), "ΣΟΦΙΑ").unwrap();
+    /// assert_eq!(cipher.alphabet(), "ΣΟΦΙΑΒΓΔΕΖΗΘΚΛΜΝΞΠΡΤΥΧΨΩ");
+    /// assert_eq!(cipher.encipher("Λόγοι"), "Ηόφμε");
+    /// assert_eq!(cipher.decipher("Ηόφμε"), "Λόγοι");
+    /// ```
+    pub fn from_alphabet_in(plain: Alphabet, alphabet: &str) -> Result<Self, String> {
+        let size = plain.len();
+        let mapping: Vec<usize> = alphabet
+            .chars()
+            .map(|c| plain.index_of(c))
+            .collect::<Option<_>>()
+            .filter(|mapping: &Vec<usize>| mapping.len() == size)
+            .ok_or(format!("Alphabet must consist of exactly {} letters", size))?;
+        let mut inverse = vec![None; size];
+        for (i, &letter) in mapping.iter().enumerate() {
+            if inverse[letter].replace(i).is_some() {
+                return Err(format!(
+                    "Alphabet repeats the letter {}",
+                    plain.letter(letter)
+                ));
+            }
+        }
+        Ok(SubstitutionCipher {
+            plain,
+            mapping,
+            inverse: inverse.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Creates a new substitution cipher from a keyword-mixed alphabet.
+    ///
+    /// The alphabet starts with the letters of the keyword (skipping repeats and non-letters),
+    /// followed by the remaining letters in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword contains no letters.
+    pub fn from_keyword(keyword: &str) -> Result<Self, String> {
+        if !keyword.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err("Keyword must contain at least one letter".to_string());
+        }
+        Self::from_alphabet(&keyed_alphabet(keyword))
+    }
+
+    /// Creates a new substitution cipher of the letters of `plain` from a keyword-mixed
+    /// alphabet, as [`SubstitutionCipher::from_keyword`] does for `A` to `Z`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword contains no letters of the alphabet.
+    pub fn from_keyword_in(plain: Alphabet, keyword: &str) -> Result<Self, String> {
+        let mut letters: Vec<usize> = keyword.chars().filter_map(|c| plain.index_of(c)).collect();
+        if letters.is_empty() {
+            return Err("Keyword must contain at least one letter".to_string());
+        }
+        letters.extend(0..plain.len());
+        let mut seen = vec![false; plain.len()];
+        let alphabet: String = letters
+            .into_iter()
+            .filter(|&i| !std::mem::replace(&mut seen[i], true))
+            .map(|i| plain.letter(i))
+            .collect();
+        Self::from_alphabet_in(plain, &alphabet)
+    }
+
+    /// Returns the cipher alphabet.
+    pub fn alphabet(&self) -> String {
+        self.mapping.iter().map(|&i| self.plain.letter(i)).collect()
+    }
+
+    // Replaces every letter by its entry in `mapping`, preserving case.
+    fn substitute(&self, text: &str, mapping: &[usize]) -> String {
+        text.chars()
+            .map(|c| self.plain.replace(c, |i| mapping[i]))
+            .collect()
+    }
+
+    /// Enciphers a given plaintext using the cipher alphabet.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.substitute(plaintext, &self.mapping)
+    }
+
+    /// Deciphers a given ciphertext using the inverted cipher alphabet.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.substitute(ciphertext, &self.inverse)
+    }
+}
+
+impl Cipher for SubstitutionCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(alphabet_mapping(Some(&self.plain), |p| self.encipher(p)))
+    }
+}
+
+/// The keyed Caesar cipher: a Caesar shift of a keyword-mixed alphabet.
+///
+/// The cipher alphabet is the keyword-mixed alphabet (as in [`SubstitutionCipher::from_keyword`])
+/// rotated left by the shift, written under the straight alphabet, so plaintext `A` becomes the
+/// letter `shift` places into the mixed alphabet. A shift of 0 is the keyword substitution
+/// itself; unlike a general substitution, the 26 shifts of one keyword share the same letter
+/// order, which is what makes the ACA puzzles built on it solvable by sliding the alphabet. Case
+/// is preserved and other characters are left unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::KeyedCaesarCipher;
+/// let cipher = KeyedCaesarCipher::new("KRYPTOS", 3).unwrap();
+/// assert_eq!(cipher.alphabet(), "PTOSABCDEFGHIJLMNQUVWXZKRY");
+/// assert_eq!(cipher.encipher("Hello, World!"), "Dahhl, Zlqhs!");
+/// assert_eq!(cipher.decipher("Dahhl, Zlqhs!"), "Hello, World!");
+/// ```
+pub struct KeyedCaesarCipher {
+    substitution: SubstitutionCipher,
+}
+
+impl KeyedCaesarCipher {
+    /// Creates a new keyed Caesar cipher from a keyword and a shift (taken modulo 26).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword contains no letters.
+    pub fn new(keyword: &str, shift: u8) -> Result<Self, String> {
+        if !keyword.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err("Keyword must contain at least one letter".to_string());
+        }
+        let mixed = keyed_alphabet(keyword);
+        let shift = shift as usize % 26;
+        let alphabet = format!("{}{}", &mixed[shift..], &mixed[..shift]);
+        Ok(KeyedCaesarCipher {
+            substitution: SubstitutionCipher::from_alphabet(&alphabet)?,
+        })
+    }
+
+    /// Returns the cipher alphabet.
+    pub fn alphabet(&self) -> String {
+        self.substitution.alphabet()
+    }
+
+    /// Enciphers a given plaintext using the shifted keyed alphabet.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.substitution.encipher(plaintext)
+    }
+
+    /// Deciphers a given ciphertext using the shifted keyed alphabet.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.substitution.decipher(ciphertext)
+    }
+}
+
+impl Cipher for KeyedCaesarCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(letter_mapping(|p| self.encipher(p)))
+    }
+}
+
+// Returns the uppercase alphabet mixed by a keyword: its distinct letters first, then the rest.
+fn keyed_alphabet(keyword: &str) -> String {
+    let mut alphabet = String::new();
+    for c in keyword
+        .chars()
+        .chain('A'..='Z')
+        .map(|c| c.to_ascii_uppercase())
+    {
+        if c.is_ascii_uppercase() && !alphabet.contains(c) {
+            alphabet.push(c);
+        }
+    }
+    alphabet
+}
+
+/// The size and alphabet of a [`PolybiusSquare`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolybiusGrid {
+    /// A 5x5 square of the letters with I and J sharing a cell.
+    FiveByFive,
+    /// A 6x6 square of the letters and the digits.
+    SixBySix,
+    /// A 7x7 square of the letters, the digits and the punctuation `.,:;!'"-()/&@`.
+    SevenBySeven,
+}
+
+impl PolybiusGrid {
+    /// Returns the number of rows and columns.
+    pub fn size(&self) -> usize {
+        match self {
+            PolybiusGrid::FiveByFive => 5,
+            PolybiusGrid::SixBySix => 6,
+            PolybiusGrid::SevenBySeven => 7,
+        }
+    }
+
+    // Returns the characters of the unmixed square, row by row.
+    fn alphabet(&self) -> &'static str {
+        match self {
+            PolybiusGrid::FiveByFive => "ABCDEFGHIKLMNOPQRSTUVWXYZ",
+            PolybiusGrid::SixBySix => "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789",
+            PolybiusGrid::SevenBySeven => "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789.,:;!'\"-()/&@",
+        }
+    }
+
+    // Maps a character to the one stored in the square, if any.
+    fn normalize(&self, c: char) -> Option<char> {
+        let c = match (self, c.to_ascii_uppercase()) {
+            (PolybiusGrid::FiveByFive, 'J') => 'I',
+            (_, c) => c,
+        };
+        self.alphabet().contains(c).then_some(c)
+    }
+}
+
+/// A Polybius square cipher.
+///
+/// Every letter (and, in a 6x6 square, every digit) is replaced by the labels of its row and
+/// column, e.g. `H` becomes `23` in the standard 5x5 square. The pairs are separated by spaces
+/// and characters that are not in the square are dropped. Deciphering reads the labels in pairs
+/// and ignores everything else.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{PolybiusGrid, PolybiusSquare};
+/// let square = PolybiusSquare::new(PolybiusGrid::FiveByFive);
+/// assert_eq!(square.encipher("Hello"), "23 15 31 31 34");
+/// assert_eq!(square.decipher("23 15 31 31 34"), "HELLO");
+///
+/// let square = PolybiusSquare::new(PolybiusGrid::FiveByFive).with_labels("ADFGX", "ADFGX").unwrap();
+/// assert_eq!(square.encipher("Hi"), "DF DG");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolybiusSquare {
+    grid: PolybiusGrid,
+    cells: Vec<char>,
+    row_labels: Vec<char>,
+    column_labels: Vec<char>,
+}
+
+impl PolybiusSquare {
+    /// Creates a new Polybius square with the alphabet in order and digit labels starting at 1.
+    pub fn new(grid: PolybiusGrid) -> Self {
+        let labels: Vec<char> = "1234567".chars().take(grid.size()).collect();
+        PolybiusSquare {
+            grid,
+            cells: grid.alphabet().chars().collect(),
+            row_labels: labels.clone(),
+            column_labels: labels,
+        }
+    }
+
+    /// Mixes the square with a keyword: its distinct characters fill the first cells, followed
+    /// by the rest of the alphabet in order.
+    pub fn with_keyword(mut self, keyword: &str) -> Self {
+        let mut cells: Vec<char> = Vec::new();
+        for c in keyword
+            .chars()
+            .filter_map(|c| self.grid.normalize(c))
+            .chain(self.grid.alphabet().chars())
+        {
+            if !cells.contains(&c) {
+                cells.push(c);
+            }
+        }
+        self.cells = cells;
+        self
+    }
+
+    /// Sets the characters labelling the rows and the columns, e.g. `"ADFGX"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either set of labels does not have one distinct character per row or
+    /// column.
+    pub fn with_labels(mut self, rows: &str, columns: &str) -> Result<Self, String> {
+        let size = self.grid.size();
+        let parse = |labels: &str| {
+            let labels: Vec<char> = labels.chars().map(|c| c.to_ascii_uppercase()).collect();
+            let distinct = labels
+                .iter()
+                .enumerate()
+                .all(|(i, c)| !labels[..i].contains(c));
+            if labels.len() != size || !distinct || labels.contains(&UNKNOWN) {
+                return Err(format!(
+                    "Expected {} distinct labels, found {:?}",
+                    size,
+                    labels.iter().collect::<String>()
+                ));
+            }
+            Ok(labels)
+        };
+        self.row_labels = parse(rows)?;
+        self.column_labels = parse(columns)?;
+        Ok(self)
+    }
+
+    /// Returns the size and alphabet of the square.
+    pub fn grid(&self) -> PolybiusGrid {
+        self.grid
+    }
+
+    /// Returns the characters of the square, row by row.
+    pub fn cells(&self) -> String {
+        self.cells.iter().collect()
+    }
+
+    /// Returns the row labels and the column labels.
+    pub fn labels(&self) -> (String, String) {
+        (
+            self.row_labels.iter().collect(),
+            self.column_labels.iter().collect(),
+        )
+    }
+
+    /// Returns the row and column labels of a character, or `None` if it is not in the square.
+    pub fn coordinates(&self, c: char) -> Option<(char, char)> {
+        let c = self.grid.normalize(c)?;
+        let index = self.cells.iter().position(|&cell| cell == c)?;
+        let size = self.grid.size();
+        Some((
+            self.row_labels[index / size],
+            self.column_labels[index % size],
+        ))
+    }
+
+    /// Returns the character at the given row and column labels, if both are labels.
+    pub fn character_at(&self, row: char, column: char) -> Option<char> {
+        let row = self
+            .row_labels
+            .iter()
+            .position(|&l| l == row.to_ascii_uppercase())?;
+        let column = self
+            .column_labels
+            .iter()
+            .position(|&l| l == column.to_ascii_uppercase())?;
+        Some(self.cells[row * self.grid.size() + column])
+    }
+
+    /// Enciphers a given plaintext into space separated pairs of labels.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        plaintext
+            .chars()
+            .filter_map(|c| Fractionator::fractionate(self, c))
+            .map(|pair| pair.into_iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Deciphers a given ciphertext by reading its labels in pairs.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let is_label = |c: &char| {
+            let c = c.to_ascii_uppercase();
+            c == UNKNOWN || self.row_labels.contains(&c) || self.column_labels.contains(&c)
+        };
+        let labels: Vec<char> = ciphertext.chars().filter(is_label).collect();
+        defractionate(regroup(&labels, 2), self)
+    }
+}
+
+impl Cipher for PolybiusSquare {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+impl Fractionator for PolybiusSquare {
+    fn fractionate(&self, c: char) -> Option<Vec<char>> {
+        if c == UNKNOWN {
+            return Some(vec![UNKNOWN; 2]);
+        }
+        self.coordinates(c).map(|(row, column)| vec![row, column])
+    }
+
+    fn defractionate(&self, symbols: &[char]) -> Option<char> {
+        match symbols {
+            _ if symbols.contains(&UNKNOWN) => Some(UNKNOWN),
+            [row, column] => self.character_at(*row, *column),
+            _ => None,
+        }
+    }
+}
+
+// The letters of the tap code square, row by row, without `K`.
+const TAP_CODE_SQUARE: &str = "ABCDEFGHIJLMNOPQRSTUVWXYZ";
+
+/// The tap code of prisoners, a Polybius square knocked out on a wall or a pipe.
+///
+/// The 5x5 square holds the alphabet without `K`, which is tapped as `C`. Each letter is tapped
+/// as the number of its row, a pause and the number of its column, written as two groups of
+/// dots separated by a space, e.g. `H` is `.. ...`. Letters are separated by two spaces and
+/// words by a `/`; other characters are dropped.
+///
+/// Deciphering is tolerant of how the taps were written down: any run of dots, asterisks or
+/// bullets, or a digit from 1 to 5, is a group, and the groups are read in pairs whatever
+/// separates them. A `/` ends a word. A pair with a group of more than five taps, or a group
+/// left without its partner, is read as [`UNKNOWN`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::TapCode;
+/// let tap = TapCode::new();
+/// assert_eq!(tap.encipher("Hi"), ".. ...  .. ....");
+/// assert_eq!(tap.decipher(".. ... .. ...."), "HI");
+/// assert_eq!(tap.decipher("23 24 / 1 3"), "HI C");
+///
+/// let tap = TapCode::new().with_dot('x').unwrap().with_separators("-", " ").unwrap();
+/// assert_eq!(tap.encipher("Ok"), "xxx-xxxx x-xxx");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapCode {
+    dot: char,
+    group_separator: String,
+    letter_separator: String,
+}
+
+impl Default for TapCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TapCode {
+    /// Creates a new tap code written with `.` for a tap.
+    pub fn new() -> Self {
+        TapCode {
+            dot: '.',
+            group_separator: " ".to_string(),
+            letter_separator: "  ".to_string(),
+        }
+    }
+
+    /// Writes a tap with the given character instead of `.`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the character is whitespace, a digit or `/`, or is in the separators.
+    pub fn with_dot(mut self, dot: char) -> Result<Self, String> {
+        if dot.is_whitespace() || dot.is_ascii_digit() || dot == '/' {
+            return Err(format!(
+                "A tap cannot be written as whitespace, a digit or '/', found {:?}",
+                dot
+            ));
+        }
+        self.dot = dot;
+        self.check_separator(&self.group_separator)?;
+        self.check_separator(&self.letter_separator)?;
+        Ok(self)
+    }
+
+    /// Sets what separates the two groups of taps of a letter and what separates letters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a separator is empty or contains a tap, a digit or `/`.
+    pub fn with_separators(mut self, group: &str, letter: &str) -> Result<Self, String> {
+        self.check_separator(group)?;
+        self.check_separator(letter)?;
+        self.group_separator = group.to_string();
+        self.letter_separator = letter.to_string();
+        Ok(self)
+    }
+
+    // Checks that a separator cannot be read as taps or a word break.
+    fn check_separator(&self, separator: &str) -> Result<(), String> {
+        if separator.is_empty()
+            || separator
+                .chars()
+                .any(|c| self.is_tap(c) || c.is_ascii_digit() || c == '/')
+        {
+            return Err(format!(
+                "Separators must not be empty or contain taps, digits or '/', found {:?}",
+                separator
+            ));
+        }
+        Ok(())
+    }
+
+    // Returns whether a character is read as a tap.
+    fn is_tap(&self, c: char) -> bool {
+        c == self.dot || matches!(c, '.' | '*' | '•' | '·')
+    }
+
+    /// Returns the row and column of a letter in the square, from 1, or `None` if it is not a
+    /// letter.
+    pub fn coordinates(c: char) -> Option<(usize, usize)> {
+        let c = match c.to_ascii_uppercase() {
+            'K' => 'C',
+            c => c,
+        };
+        let index = TAP_CODE_SQUARE.find(c)?;
+        Some((index / 5 + 1, index % 5 + 1))
+    }
+
+    /// Enciphers a given plaintext into groups of taps.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let taps = |count: usize| self.dot.to_string().repeat(count);
+        plaintext
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter_map(TapCode::coordinates)
+                    .map(|(row, column)| {
+                        format!("{}{}{}", taps(row), self.group_separator, taps(column))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&self.letter_separator)
+            })
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(&format!("{0}/{0}", self.letter_separator))
+    }
+
+    /// Deciphers a given ciphertext by reading its groups of taps in pairs.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let mut plaintext = String::new();
+        let mut row = None;
+        let mut chars = ciphertext.chars().peekable();
+        while let Some(c) = chars.next() {
+            let group = if self.is_tap(c) {
+                let mut count = 1;
+                while chars.next_if(|&c| self.is_tap(c)).is_some() {
+                    count += 1;
+                }
+                count
+            } else if let Some(digit) = c.to_digit(10) {
+                digit as usize
+            } else {
+                if c == '/' {
+                    if row.take().is_some() {
+                        plaintext.push(UNKNOWN);
+                    }
+                    if !plaintext.is_empty() && !plaintext.ends_with(' ') {
+                        plaintext.push(' ');
+                    }
+                }
+                continue;
+            };
+            match row.take() {
+                None => row = Some(group),
+                Some(row) => plaintext.push(match (row, group) {
+                    (1..=5, 1..=5) => TAP_CODE_SQUARE.as_bytes()[(row - 1) * 5 + group - 1] as char,
+                    _ => UNKNOWN,
+                }),
+            }
+        }
+        if row.is_some() {
+            plaintext.push(UNKNOWN);
+        }
+        plaintext.trim_end().to_string()
+    }
+}
+
+impl Cipher for TapCode {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The Playfair cipher, with the 5x5 square of Wheatstone and Playfair or a larger one.
+///
+/// The plaintext is split into pairs of characters of a keyword-mixed [`PolybiusSquare`], and
+/// every pair is replaced by two other characters of the square: a pair in one row by the
+/// characters to their right, a pair in one column by those below them, and any other pair by
+/// the characters in the same rows but each other's columns. The padding character (`X` by
+/// default) splits a pair of the same character and completes a lone last one, except that a
+/// doubled padding character is enciphered as a row. Characters not in the square are dropped,
+/// and the padding is not removed when deciphering.
+///
+/// The 5x5 square drops J for I, so messages come out in letters only. A 6x6 square adds the
+/// digits and a 7x7 square punctuation as well, so alphanumeric messages come through whole.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{PlayfairCipher, PolybiusGrid};
+/// let cipher = PlayfairCipher::new(PolybiusGrid::FiveByFive, "PLAYFAIR EXAMPLE");
+/// assert_eq!(cipher.encipher("Hide the gold in the tree stump"), "BMODZBXDNABEKUDMUIXMMOUVIF");
+/// assert_eq!(cipher.decipher("BMODZBXDNABEKUDMUIXMMOUVIF"), "HIDETHEGOLDINTHETREXESTUMP");
+///
+/// let cipher = PlayfairCipher::new(PolybiusGrid::SixBySix, "PLAYFAIR EXAMPLE");
+/// assert_eq!(cipher.decipher(&cipher.encipher("Room 101")), "ROOM101X");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayfairCipher {
+    square: PolybiusSquare,
+    padding: char,
+}
+
+impl PlayfairCipher {
+    /// Creates a new instance of the cipher with the square mixed by `keyword` and `X` as padding.
+    pub fn new(grid: PolybiusGrid, keyword: &str) -> Self {
+        PlayfairCipher {
+            square: PolybiusSquare::new(grid).with_keyword(keyword),
+            padding: 'X',
+        }
+    }
+
+    /// Pads with `padding` instead of `X`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `padding` is not in the square.
+    pub fn with_padding(mut self, padding: char) -> Result<Self, String> {
+        self.padding = self
+            .square
+            .grid
+            .normalize(padding)
+            .ok_or_else(|| format!("Padding {:?} is not in the square", padding))?;
+        Ok(self)
+    }
+
+    /// Returns the key square.
+    pub fn square(&self) -> &PolybiusSquare {
+        &self.square
+    }
+
+    // Replaces a pair, moving `step` cells right or down along a shared row or column.
+    fn substitute(&self, a: char, b: char, step: usize) -> [char; 2] {
+        let size = self.square.grid.size();
+        let cell = |c: char| {
+            let index = self
+                .square
+                .cells
+                .iter()
+                .position(|&cell| cell == c)
+                .expect("Normalized characters are in the square");
+            (index / size, index % size)
+        };
+        let ((row_a, column_a), (row_b, column_b)) = (cell(a), cell(b));
+        let [(row_a, column_a), (row_b, column_b)] = if row_a == row_b {
+            [
+                (row_a, (column_a + step) % size),
+                (row_b, (column_b + step) % size),
+            ]
+        } else if column_a == column_b {
+            [
+                ((row_a + step) % size, column_a),
+                ((row_b + step) % size, column_b),
+            ]
+        } else {
+            [(row_a, column_b), (row_b, column_a)]
+        };
+        [
+            self.square.cells[row_a * size + column_a],
+            self.square.cells[row_b * size + column_b],
+        ]
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let chars: Vec<char> = plaintext
+            .chars()
+            .filter_map(|c| self.square.grid.normalize(c))
+            .collect();
+        let mut ciphertext = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let a = chars[i];
+            let b = match chars.get(i + 1) {
+                Some(&b) if b != a || a == self.padding => {
+                    i += 2;
+                    b
+                }
+                _ => {
+                    i += 1;
+                    self.padding
+                }
+            };
+            ciphertext.extend(self.substitute(a, b, 1));
+        }
+        ciphertext
+    }
+
+    /// Deciphers a given ciphertext, ignoring a lone last character.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let chars: Vec<char> = ciphertext
+            .chars()
+            .filter_map(|c| self.square.grid.normalize(c))
+            .collect();
+        let size = self.square.grid.size();
+        chars
+            .chunks_exact(2)
+            .flat_map(|pair| self.substitute(pair[0], pair[1], size - 1))
+            .collect()
+    }
+}
+
+impl Cipher for PlayfairCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The tableau of a [`PolyalphabeticCipher`]: how a key letter transforms a plaintext letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tableau {
+    /// The Vigenere tableau, `C = P + K`.
+    Vigenere,
+    /// The Beaufort tableau, `C = K - P`. It is its own inverse.
+    Beaufort,
+    /// The variant Beaufort tableau, `C = P - K`, i.e. Vigenere deciphering.
+    VariantBeaufort,
+    /// The Porta tableau of 13 reciprocal alphabets, one for each pair of key letters (A/B,
+    /// C/D, ...). The first half of the alphabet always maps to the second and vice versa.
+    Porta,
+    /// The Porta tableau as della Porta printed it in 1563: the second half of the alphabet
+    /// shifts the other way, so key C enciphers A as Z rather than O.
+    HistoricalPorta,
+}
+
+impl Tableau {
+    // Enciphers a letter with a key value, both counted from 0 in an alphabet of `n` letters.
+    fn encipher(&self, p: usize, k: usize, n: usize) -> usize {
+        match self {
+            Tableau::Vigenere => (p + k) % n,
+            Tableau::Beaufort => (n + k - p) % n,
+            Tableau::VariantBeaufort => (n + p - k) % n,
+            Tableau::Porta => {
+                let (half, shift) = (n / 2, k / 2);
+                if p < half {
+                    half + (p + shift) % half
+                } else {
+                    (p - shift) % half
+                }
+            }
+            // Shifting forwards by n/2 - s halves is shifting backwards by s
+            Tableau::HistoricalPorta => Tableau::Porta.encipher(p, (n - k / 2 * 2) % n, n),
+        }
+    }
+
+    // Deciphers a letter with a key value, both counted from 0 in an alphabet of `n` letters.
+    fn decipher(&self, c: usize, k: usize, n: usize) -> usize {
+        match self {
+            Tableau::Vigenere => Tableau::VariantBeaufort.encipher(c, k, n),
+            Tableau::VariantBeaufort => Tableau::Vigenere.encipher(c, k, n),
+            Tableau::Beaufort | Tableau::Porta | Tableau::HistoricalPorta => self.encipher(c, k, n),
+        }
+    }
+}
+
+/// How a [`PolyalphabeticCipher`] extends its key over the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySchedule {
+    /// The key is repeated.
+    Repeating,
+    /// The key is followed by the plaintext itself.
+    Autokey,
+    /// The key is repeated, shifted on by the given step every time it starts over.
+    Progressive(u8),
+}
+
+/// Where a [`PolyalphabeticCipher`] starts its key over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Interruption {
+    /// The key runs on over the whole message.
+    Never,
+    /// The key starts over at every word.
+    AtWords,
+    /// The key starts over at the given letter positions, counted from 0.
+    AtLetters(Vec<usize>),
+}
+
+/// A polyalphabetic cipher built from a [`Tableau`] and a [`KeySchedule`].
+///
+/// This one engine covers the Vigenere family: Vigenere, Beaufort, variant Beaufort, Porta and
+/// Gronsfeld, each with a repeating, autokey or progressive key. Key letters count from A = 0;
+/// digits in a key stand for the shifts 0-9, and other key characters are ignored. Letters keep
+/// their case and other characters are left unchanged without using up the key.
+///
+/// The key can also be [interrupted](Self::with_interruption) or started at a position given by a
+/// [message indicator](Self::with_indicator), for any tableau and schedule. The letters are `A`
+/// to `Z` unless the cipher is created [with another alphabet](Self::with_alphabet).
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{KeySchedule, PolyalphabeticCipher};
+/// let cipher = PolyalphabeticCipher::vigenere("QUEENLY").with_schedule(KeySchedule::Autokey);
+/// assert_eq!(cipher.encipher("ATTACKATDAWN"), "QNXEPVYTWTWP");
+/// assert_eq!(cipher.decipher("QNXEPVYTWTWP"), "ATTACKATDAWN");
+///
+/// let cipher = PolyalphabeticCipher::beaufort("FORTIFICATION");
+/// assert_eq!(cipher.encipher("DEFENDTHEEASTWALL"), "CKMPVCPVWPIWUJOGI");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyalphabeticCipher {
+    tableau: Tableau,
+    key: Vec<u8>,
+    schedule: KeySchedule,
+    interruption: Interruption,
+    indicator: Option<char>,
+    // Letters to count with in place of A to Z
+    alphabet: Option<Alphabet>,
+}
+
+impl PolyalphabeticCipher {
+    /// Creates a new instance of the cipher with a repeating key.
+    pub fn new(tableau: Tableau, key: &str) -> Self {
+        PolyalphabeticCipher {
+            tableau,
+            key: key
+                .chars()
+                .filter_map(|c| {
+                    if c.is_ascii_alphabetic() {
+                        Some(c.to_ascii_uppercase() as u8 - b'A')
+                    } else {
+                        c.to_digit(10).map(|d| d as u8)
+                    }
+                })
+                .collect(),
+            schedule: KeySchedule::Repeating,
+            interruption: Interruption::Never,
+            indicator: None,
+            alphabet: None,
+        }
+    }
+
+    /// Creates a cipher with a repeating key that counts with the letters of `alphabet` in place
+    /// of `A` to `Z`. Key letters are their positions in the alphabet; whitespace in the key is
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key has no letters or a character that is not in the alphabet, if
+    /// the alphabet has more than 256 letters, or if the tableau is a Porta tableau and the
+    /// alphabet has an odd number of letters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::{PolyalphabeticCipher, Tableau};
+    /// let greek = Alphabet::greek();
+    /// let beaufort = PolyalphabeticCipher::with_alphabet(Tableau::Beaufort, "ΚΛΕΙΔΙ", greek).unwrap();
+    /// assert_eq!(beaufort.decipher(&beaufort.encipher("Επίθεση την αυγή")), "Επίθεση την αυγή");
+    /// // The 33 Russian letters cannot be split into the two halves of a Porta tableau
+    /// let russian = Alphabet::russian();
+    /// assert!(PolyalphabeticCipher::with_alphabet(Tableau::Porta, "КЛЮЧ", russian).is_err());
+    /// ```
+    pub fn with_alphabet(tableau: Tableau, key: &str, alphabet: Alphabet) -> Result<Self, String> {
+        if alphabet.len() > 256 {
+            return Err("Alphabet must have at most 256 letters".to_string());
+        }
+        if matches!(tableau, Tableau::Porta | Tableau::HistoricalPorta) && alphabet.len() % 2 == 1 {
+            return Err("The Porta tableau needs an even number of letters".to_string());
+        }
+        let key: Vec<u8> = key
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match alphabet.index_of(c) {
+                Some(i) => Ok(i as u8),
+                None => Err(format!("Key character {:?} is not in the alphabet", c)),
+            })
+            .collect::<Result<_, _>>()?;
+        if key.is_empty() {
+            return Err("Key must have at least one letter of the alphabet".to_string());
+        }
+        Ok(PolyalphabeticCipher {
+            key,
+            alphabet: Some(alphabet),
+            ..Self::new(tableau, "")
+        })
+    }
+
+    /// Creates a Vigenere cipher.
+    pub fn vigenere(key: &str) -> Self {
+        Self::new(Tableau::Vigenere, key)
+    }
+
+    /// Creates a Beaufort cipher.
+    pub fn beaufort(key: &str) -> Self {
+        Self::new(Tableau::Beaufort, key)
+    }
+
+    /// Creates a variant Beaufort cipher.
+    pub fn variant_beaufort(key: &str) -> Self {
+        Self::new(Tableau::VariantBeaufort, key)
+    }
+
+    /// Creates a Porta cipher.
+    pub fn porta(key: &str) -> Self {
+        Self::new(Tableau::Porta, key)
+    }
+
+    /// Creates a Gronsfeld cipher, a Vigenere cipher keyed by digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key contains anything but digits.
+    pub fn gronsfeld(key: &str) -> Result<Self, String> {
+        match key.chars().find(|c| !c.is_ascii_digit()) {
+            Some(c) => Err(format!("Key contains a non-digit: {}", c)),
+            None => Ok(Self::new(Tableau::Vigenere, key)),
+        }
+    }
+
+    /// Sets how the key is extended over the message.
+    pub fn with_schedule(mut self, schedule: KeySchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Sets where the key starts over.
+    pub fn with_interruption(mut self, interruption: Interruption) -> Self {
+        self.interruption = interruption;
+        self
+    }
+
+    /// Sends a message indicator letter in clear ahead of the ciphertext.
+    ///
+    /// The key starts at the position given by the indicator (A = 0, wrapping around the key)
+    /// rather than at its first letter. Deciphering reads the indicator from the first letter
+    /// of the ciphertext, so the same cipher can decipher messages sent with any indicator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the indicator is not a letter.
+    pub fn with_indicator(mut self, indicator: char) -> Result<Self, String> {
+        if !indicator.is_ascii_alphabetic() {
+            return Err(format!("Indicator must be a letter, found {:?}", indicator));
+        }
+        self.indicator = Some(indicator.to_ascii_uppercase());
+        Ok(self)
+    }
+
+    // Returns the key value for the `n`th letter since the key last started over, given the
+    // plaintext letters since then, or `None` if it comes from an unknown letter.
+    fn key_at(&self, n: usize, offset: usize, plaintext: &[Option<u8>]) -> Option<u8> {
+        let len = self.key.len();
+        match self.schedule {
+            KeySchedule::Repeating => Some(self.key[(n + offset) % len]),
+            KeySchedule::Autokey if n >= len => plaintext[n - len],
+            KeySchedule::Autokey => Some(self.key[(n + offset) % len]),
+            KeySchedule::Progressive(step) => {
+                let i = n + offset;
+                let n = self.alphabet_len();
+                Some(((self.key[i % len] as usize + step as usize * (i / len)) % n) as u8)
+            }
+        }
+    }
+
+    /// Returns the key values (A = 0) for the first `len` letters of a message, in the order
+    /// they are used, so a key can be shown under the plaintext or compared with another
+    /// implementation. For the Vigenere tableau they are the shifts.
+    ///
+    /// The stream starts at the message indicator and starts over at the letters of an
+    /// [`Interruption::AtLetters`]; interruptions at words depend on the message, as do autokey
+    /// values past the primer, which are `None`. [`key_stream_for`](Self::key_stream_for) gives
+    /// them for a given plaintext. An empty key leaves messages unchanged and has no stream.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::{KeySchedule, PolyalphabeticCipher};
+    /// let cipher = PolyalphabeticCipher::vigenere("LEMON");
+    /// assert_eq!(cipher.key_stream(7), vec![Some(11), Some(4), Some(12), Some(14), Some(13), Some(11), Some(4)]);
+    ///
+    /// let autokey = cipher.with_schedule(KeySchedule::Autokey);
+    /// assert_eq!(autokey.key_stream(6)[4..], [Some(13), None]);
+    /// assert_eq!(autokey.key_stream_for("Attack at dawn")[4..7], [Some(13), Some(0), Some(19)]);
+    /// ```
+    pub fn key_stream(&self, len: usize) -> Vec<Option<u8>> {
+        if self.key.is_empty() {
+            return Vec::new();
+        }
+        let offset = self.indicator.map_or(0, |i| (i as u8 - b'A') as usize);
+        let plaintext = vec![None; len];
+        let mut n = 0;
+        (0..len)
+            .map(|letter| {
+                if let Interruption::AtLetters(positions) = &self.interruption {
+                    if positions.contains(&letter) {
+                        n = 0;
+                    }
+                }
+                n += 1;
+                self.key_at(n - 1, offset, &plaintext)
+            })
+            .collect()
+    }
+
+    /// Returns the key value used for every letter of the plaintext when it is enciphered, or
+    /// `None` for unknown letters.
+    pub fn key_stream_for(&self, plaintext: &str) -> Vec<Option<u8>> {
+        let offset = self.indicator.map_or(0, |i| (i as u8 - b'A') as usize);
+        self.apply(plaintext, offset, false).1
+    }
+
+    // Returns the number of letters the tableau counts with.
+    fn alphabet_len(&self) -> usize {
+        self.alphabet.as_ref().map_or(26, Alphabet::len)
+    }
+
+    // Returns the position of a letter in the alphabet, or `None` for other characters.
+    fn letter_index(&self, c: char) -> Option<usize> {
+        match &self.alphabet {
+            Some(alphabet) => alphabet.index_of(c),
+            None if c.is_ascii_alphabetic() => Some((c.to_ascii_uppercase() as u8 - b'A') as usize),
+            None => None,
+        }
+    }
+
+    // Replaces a letter by the one at `index`, in the same case.
+    fn letter_at(&self, c: char, index: usize) -> char {
+        match &self.alphabet {
+            Some(alphabet) => alphabet.replace(c, |_| index),
+            None if c.is_ascii_lowercase() => (b'a' + index as u8) as char,
+            None => (b'A' + index as u8) as char,
+        }
+    }
+
+    // Runs the text through the tableau, deciphering if `decipher` is set, and returns the key
+    // value used for every letter.
+    fn apply(&self, text: &str, offset: usize, decipher: bool) -> (String, Vec<Option<u8>>) {
+        if self.key.is_empty() {
+            return (text.to_string(), Vec::new());
+        }
+        let n = self.alphabet_len();
+        let mut plaintext: Vec<Option<u8>> = Vec::new();
+        let mut keys = Vec::new();
+        let mut letters = 0;
+        let text = text
+            .chars()
+            .map(|c| {
+                let index = self.letter_index(c);
+                if index.is_none() && c != UNKNOWN {
+                    if c.is_whitespace() && self.interruption == Interruption::AtWords {
+                        plaintext.clear();
+                    }
+                    return c;
+                }
+                if let Interruption::AtLetters(positions) = &self.interruption {
+                    if positions.contains(&letters) {
+                        plaintext.clear();
+                    }
+                }
+                letters += 1;
+                let k = self.key_at(plaintext.len(), offset, &plaintext);
+                keys.push(k.filter(|_| index.is_some()));
+                let (k, index) = match (k, index) {
+                    (Some(k), Some(index)) => (k as usize, index),
+                    _ => {
+                        plaintext.push(None);
+                        return UNKNOWN;
+                    }
+                };
+                let (p, out) = if decipher {
+                    let p = self.tableau.decipher(index, k, n);
+                    (p, p)
+                } else {
+                    (index, self.tableau.encipher(index, k, n))
+                };
+                plaintext.push(Some(p as u8));
+                self.letter_at(c, out)
+            })
+            .collect();
+        (text, keys)
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        match self.indicator {
+            Some(indicator) => {
+                let offset = (indicator as u8 - b'A') as usize;
+                format!("{}{}", indicator, self.apply(plaintext, offset, false).0)
+            }
+            None => self.apply(plaintext, 0, false).0,
+        }
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        if self.indicator.is_none() {
+            return self.apply(ciphertext, 0, true).0;
+        }
+        let mut chars = ciphertext.chars();
+        match chars.find(|c| c.is_ascii_alphabetic()) {
+            Some(indicator) => {
+                let offset = (indicator.to_ascii_uppercase() as u8 - b'A') as usize;
+                self.apply(chars.as_str(), offset, true).0
+            }
+            None => ciphertext.to_string(),
+        }
+    }
+}
+
+impl Cipher for PolyalphabeticCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A Vigenere cipher implementation.
+///
+/// The Vigenere cipher is a method of encrypting alphabetic text by using a series of interwoven Caesar ciphers based on the letters of a keyword.
+/// It is the [`PolyalphabeticCipher`] with the [`Tableau::Vigenere`] tableau and a repeating key.
+pub struct VigenereCipher {
+    engine: PolyalphabeticCipher,
+    key: String,
+}
+
+impl VigenereCipher {
+    /// Creates a new instance of the Vigenere cipher with the specified key.
+    pub fn new(key: &str) -> Self {
+        VigenereCipher {
+            engine: PolyalphabeticCipher::vigenere(key),
+            key: key.to_string(),
+        }
+    }
+
+    /// Shifts the letters of `alphabet` around it in place of `A` to `Z`, by the positions of
+    /// the letters of the key in it. Whitespace in the key is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key has no letters, or a character that is not in the alphabet.
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::VigenereCipher;
+    /// let vigenere = VigenereCipher::new("ключ").with_alphabet(Alphabet::russian()).unwrap();
+    /// assert_eq!(vigenere.key_stream(5), vec![11, 12, 31, 24, 11]);
+    /// assert_eq!(vigenere.decipher(&vigenere.encipher("Атака на рассвете")), "Атака на рассвете");
+    /// assert!(VigenereCipher::new("KEY").with_alphabet(Alphabet::greek()).is_err());
+    /// ```
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Result<Self, String> {
+        self.engine = PolyalphabeticCipher::with_alphabet(Tableau::Vigenere, &self.key, alphabet)?;
+        Ok(self)
+    }
+
+    /// Enciphers a given plaintext using the Vigenere cipher.
+    ///
+    /// This function uses the provided key to shift each letter in the plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.engine.encipher(plaintext)
+    }
+
+    /// Deciphers a given ciphertext using the Vigenere cipher.
+    ///
+    /// This function uses the provided key to reverse the shift applied during encryption.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.engine.decipher(ciphertext)
+    }
+
+    /// Returns the shifts applied to the first `len` letters of a message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::VigenereCipher;
+    /// assert_eq!(VigenereCipher::new("KEY").key_stream(5), vec![10, 4, 24, 10, 4]);
+    /// ```
+    pub fn key_stream(&self, len: usize) -> Vec<u8> {
+        self.engine.key_stream(len).into_iter().flatten().collect()
+    }
+}
+
+impl Cipher for VigenereCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A Trithemius cipher: a shift that grows with every letter.
+///
+/// Trithemius enciphered the first letter with the alphabet shifted by 0, the second shifted by
+/// 1, and so on through his *tabula recta*. The first shift and how much it grows per letter can
+/// be changed with [`with_offset`](Self::with_offset) and [`with_step`](Self::with_step). It is
+/// the [`PolyalphabeticCipher`] with a one-letter Vigenere key and a
+/// [`KeySchedule::Progressive`] schedule.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::TrithemiusCipher;
+/// let cipher = TrithemiusCipher::new();
+/// assert_eq!(cipher.encipher("Hello, World!"), "Hfnos, Buytm!");
+/// assert_eq!(cipher.decipher("Hfnos, Buytm!"), "Hello, World!");
+///
+/// let cipher = TrithemiusCipher::new().with_offset(3).with_step(2);
+/// assert_eq!(cipher.encipher("AAAA"), "DFHJ");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrithemiusCipher {
+    offset: u8,
+    step: u8,
+}
+
+impl Default for TrithemiusCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrithemiusCipher {
+    /// Creates a new instance of the cipher, starting at a shift of 0 and growing by 1.
+    pub fn new() -> Self {
+        TrithemiusCipher { offset: 0, step: 1 }
+    }
+
+    /// Sets the shift of the first letter.
+    pub fn with_offset(mut self, offset: u8) -> Self {
+        self.offset = offset % 26;
+        self
+    }
+
+    /// Sets how much the shift grows with every letter.
+    pub fn with_step(mut self, step: u8) -> Self {
+        self.step = step % 26;
+        self
+    }
+
+    // Returns the equivalent progressive Vigenere cipher.
+    fn engine(&self) -> PolyalphabeticCipher {
+        let key = ((b'A' + self.offset) as char).to_string();
+        PolyalphabeticCipher::vigenere(&key).with_schedule(KeySchedule::Progressive(self.step))
+    }
+
+    /// Returns the shifts applied to the first `len` letters of a message.
+    pub fn key_stream(&self, len: usize) -> Vec<u8> {
+        self.engine()
+            .key_stream(len)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.engine().encipher(plaintext)
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.engine().decipher(ciphertext)
+    }
+}
+
+impl Cipher for TrithemiusCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// Which table of Porta alphabets a [`PortaCipher`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortaTable {
+    /// The table found in most modern references, [`Tableau::Porta`].
+    Modern,
+    /// Della Porta's original table, [`Tableau::HistoricalPorta`], also used by the ACA.
+    Historical,
+}
+
+/// The Porta cipher: a reciprocal polyalphabetic cipher with 13 half-alphabets.
+///
+/// Each pair of key letters (A/B, C/D, ... Y/Z) selects one alphabet that swaps the first half of
+/// the alphabet with the second, so enciphering and deciphering are the same operation. It is
+/// the [`PolyalphabeticCipher`] with a Porta [`Tableau`] and a repeating key.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{PortaCipher, PortaTable};
+/// let cipher = PortaCipher::new("FORTIFICATION");
+/// assert_eq!(cipher.encipher("DEFENDTHEEASTWALL"), "SYNNJSCVRNRLAHUTU");
+/// assert_eq!(cipher.encipher("SYNNJSCVRNRLAHUTU"), "DEFENDTHEEASTWALL");
+///
+/// let historical = PortaCipher::new("C").with_table(PortaTable::Historical);
+/// assert_eq!(historical.encipher("ABCDEFGHIJKLM"), "ZNOPQRSTUVWXY");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortaCipher {
+    engine: PolyalphabeticCipher,
+    key: String,
+}
+
+impl PortaCipher {
+    /// Creates a new instance of the cipher with the modern table.
+    pub fn new(key: &str) -> Self {
+        PortaCipher {
+            engine: PolyalphabeticCipher::porta(key),
+            key: key.to_string(),
+        }
+    }
+
+    /// Sets the table of alphabets.
+    pub fn with_table(mut self, table: PortaTable) -> Self {
+        let tableau = match table {
+            PortaTable::Modern => Tableau::Porta,
+            PortaTable::Historical => Tableau::HistoricalPorta,
+        };
+        self.engine = PolyalphabeticCipher::new(tableau, &self.key);
+        self
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.engine.encipher(plaintext)
+    }
+
+    /// Deciphers a given ciphertext, which is the same as enciphering it.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.engine.decipher(ciphertext)
+    }
+}
+
+impl Cipher for PortaCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A running key cipher: a Vigenere cipher keyed by a long text, such as a book passage, instead
+/// of a repeated keyword.
+///
+/// Only the letters of the key text are used, one per letter of the message, and the key never
+/// repeats. Enciphering and deciphering fail if the key text has fewer letters than the message,
+/// so this cipher is a [`FallibleCipher`] rather than a [`Cipher`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::RunningKeyCipher;
+/// let cipher = RunningKeyCipher::new("How doth the little crocodile improve his shining tail");
+/// assert_eq!(cipher.encipher("Meet me at the old mill").unwrap(), "Tsaw ax hm alp wew xmnc");
+/// assert_eq!(cipher.decipher("Tsaw ax hm alp wew xmnc").unwrap(), "Meet me at the old mill");
+/// assert!(cipher.encipher(&"a".repeat(50)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningKeyCipher {
+    key: String,
+}
+
+impl RunningKeyCipher {
+    /// Creates a new instance of the cipher with the key text.
+    pub fn new(key_text: &str) -> Self {
+        RunningKeyCipher {
+            key: key_text
+                .chars()
+                .filter(|c| c.is_ascii_alphabetic())
+                .collect(),
+        }
+    }
+
+    // Returns the Vigenere cipher for the text, checking the key is long enough.
+    fn engine(&self, text: &str) -> Result<PolyalphabeticCipher, String> {
+        let letters = text
+            .chars()
+            .filter(|&c| c.is_ascii_alphabetic() || c == UNKNOWN)
+            .count();
+        if self.key.len() < letters {
+            return Err(format!(
+                "Key text has {} letters but the message has {}",
+                self.key.len(),
+                letters
+            ));
+        }
+        Ok(PolyalphabeticCipher::vigenere(&self.key))
+    }
+
+    /// Enciphers a given plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key text is shorter than the message.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        Ok(self.engine(plaintext)?.encipher(plaintext))
+    }
+
+    /// Deciphers a given ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key text is shorter than the message.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        Ok(self.engine(ciphertext)?.decipher(ciphertext))
+    }
+
+    /// Returns the key value (A = 0) used for every letter of the plaintext, as
+    /// [`PolyalphabeticCipher::key_stream_for`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key text is shorter than the message.
+    pub fn key_stream_for(&self, plaintext: &str) -> Result<Vec<Option<u8>>, String> {
+        Ok(self.engine(plaintext)?.key_stream_for(plaintext))
+    }
+}
+
+impl FallibleCipher for RunningKeyCipher {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A Morse code converter.
+///
+/// This struct provides methods to convert text to Morse code and vice versa.
+const MORSE_CODE_MAP: &[(&str, &str)] = &[
+    ("A", ".-"),
+    ("B", "-..."),
+    ("C", "-.-."),
+    ("D", "-.."),
+    ("E", "."),
+    ("F", "..-."),
+    ("G", "--."),
+    ("H", "...."),
+    ("I", ".."),
+    ("J", ".---"),
+    ("K", "-.-"),
+    ("L", ".-.."),
+    ("M", "--"),
+    ("N", "-."),
+    ("O", "---"),
+    ("P", ".--."),
+    ("Q", "--.-"),
+    ("R", ".-."),
+    ("S", "..."),
+    ("T", "-"),
+    ("U", "..-"),
+    ("V", "...-"),
+    ("W", ".--"),
+    ("X", "-..-"),
+    ("Y", "-.--"),
+    ("Z", "--.."),
+    ("0", "-----"),
+    ("1", ".----"),
+    ("2", "..---"),
+    ("3", "...--"),
+    ("4", "....-"),
+    ("5", "....."),
+    ("6", "-...."),
+    ("7", "--..."),
+    ("8", "---.."),
+    ("9", "----."),
+    (" ", "/"), // Space separator
+    (".", ".-.-.-"),
+    (",", "--..--"),
+    ("?", "..--.."),
+    (";", "-.-.-."),
+    (":", "---..."),
+    ("-", "-....-"),
+    ("/", "-..-."),
+    ("'", ".----."),
+    ("\"", ".-..-."),
+    ("=", "-...-"),
+    ("_", "..--.-"),
+    ("+", ".-.-."),
+    ("-", "-....-"),
+    ("*", "-..-"),
+    ("(", "-.--."),
+    (")", "-.--.-"),
+];
+
+/// A single period of a Morse signal, with the signal either on (key down) or off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MorseTiming {
+    /// Whether the signal is on during this period.
+    pub on: bool,
+    /// How long the period lasts.
+    pub duration: std::time::Duration,
+}
+
+pub struct MorseCode {
+    morse_code_map: std::collections::HashMap<String, String>,
+    reverse_morse_code_map: std::collections::HashMap<String, String>,
+}
+
+impl Default for MorseCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MorseCode {
+    /// Creates a new instance of the Morse code converter.
+    pub fn new() -> Self {
+        let mut morse_code_map = std::collections::HashMap::new();
+        let mut reverse_morse_code_map = std::collections::HashMap::new();
+
+        for (key, value) in MORSE_CODE_MAP {
+            morse_code_map.insert(key.to_string(), value.to_string());
+            reverse_morse_code_map.insert(value.to_string(), key.to_string());
+        }
+
+        MorseCode {
+            morse_code_map,
+            reverse_morse_code_map,
+        }
+    }
+
+    /// Permutes the codes of the letters by a keyword, for the Morse substitution puzzle.
+    ///
+    /// The letters of the keyword-mixed alphabet (as in [`SubstitutionCipher::from_keyword`])
+    /// take the codes of `A`, `B`, `C`, ... in turn, so the first letter of the keyword is sent
+    /// as `.-`. Digits and punctuation keep their standard codes, and decoding with the same
+    /// keyword reverses the permutation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword contains no letters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::MorseCode;
+    /// let morse = MorseCode::new().with_keyword("KEY").unwrap();
+    /// assert_eq!(morse.encode("SOS"), "- .--. -");
+    /// assert_eq!(morse.decode("- .--. -"), "SOS");
+    /// assert_eq!(morse.encode("K1"), ".- .----");
+    /// ```
+    pub fn with_keyword(mut self, keyword: &str) -> Result<Self, String> {
+        if !keyword.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err("Keyword must contain at least one letter".to_string());
+        }
+        for (letter, standard) in keyed_alphabet(keyword).chars().zip('A'..='Z') {
+            let code = MORSE_CODE_MAP
+                .iter()
+                .find(|(key, _)| key.starts_with(standard))
+                .map(|(_, code)| code.to_string())
+                .expect("The Morse table has every letter");
+            self.reverse_morse_code_map
+                .insert(code.clone(), letter.to_string());
+            self.morse_code_map.insert(letter.to_string(), code);
+        }
+        Ok(self)
+    }
+
+    /// Encodes a given text into Morse code.
+    pub fn encode(&self, text: &str) -> String {
+        let mut encoded = String::new();
+
+        for c in text.to_uppercase().chars() {
+            if let Some(code) = self.morse_code_map.get(&c.to_string()) {
+                encoded.push_str(code);
+                encoded.push(' '); // Add space between characters
+            }
+        }
+
+        encoded.trim().to_string() // Remove trailing space
+    }
+
+    /// Finds the first code that is not in the Morse table, which [`decode`](Self::decode) drops.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::MorseCode;
+    /// let unknown = MorseCode::new().unknown_token("... ------- ...").unwrap();
+    /// assert_eq!((unknown.token.as_str(), unknown.position), ("-------", 4));
+    /// ```
+    pub fn unknown_token(&self, code: &str) -> Option<UnknownToken> {
+        first_unknown_token(
+            code,
+            |c| c == ' ',
+            |token| self.reverse_morse_code_map.contains_key(token),
+        )
+    }
+
+    /// Decodes a given Morse code into text.
+    pub fn decode(&self, code: &str) -> String {
+        let mut decoded = String::new();
+        let code_vec: Vec<&str> = code.split(' ').collect();
+
+        for morse_char in code_vec {
+            if let Some(character) = self.reverse_morse_code_map.get(morse_char) {
+                decoded.push_str(character);
+            }
+        }
+
+        decoded
+    }
+
+    /// Converts a given text into the on/off periods of a Morse signal sent at `wpm` words per minute.
+    ///
+    /// Timing follows the standard "PARIS" convention: a dot lasts one unit of `1.2 / wpm` seconds,
+    /// a dash three units, and the gaps between elements, characters and words last one, three and
+    /// seven units. The returned periods always start and end with the signal on.
+    pub fn timings(&self, text: &str, wpm: u32) -> Vec<MorseTiming> {
+        let unit = std::time::Duration::from_secs_f64(1.2 / wpm.max(1) as f64);
+        let mut timings = Vec::new();
+        // Silence (in units) owed before the next element
+        let mut gap = 0;
+
+        for code in self.encode(text).split(' ') {
+            if code == "/" {
+                gap = 7;
+                continue;
+            }
+            for symbol in code.chars() {
+                if !timings.is_empty() {
+                    timings.push(MorseTiming {
+                        on: false,
+                        duration: unit * gap,
+                    });
+                }
+                let length = if symbol == '-' { 3 } else { 1 };
+                timings.push(MorseTiming {
+                    on: true,
+                    duration: unit * length,
+                });
+                gap = 1;
+            }
+            gap = 3;
+        }
+
+        timings
+    }
+}
+
+impl Cipher for MorseCode {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encode(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decode(ciphertext)
+    }
+}
+
+// The ITA2 letters and figures shifts by 5-bit code, with bit 1 as the least significant bit.
+// NUL and codes with no character in a shift are `\0`.
+const ITA2_LETTERS: &[u8; 32] = b"\0E\nA SIU\rDRJNFCKTZLWHYPQOBG\0MXV\0";
+const ITA2_FIGURES: &[u8; 32] = b"\x003\n- '87\r\x054\x07,\0:(5+)2\x006019?\0\0./=\0";
+const ITA2_CR: u8 = 8;
+const ITA2_LF: u8 = 2;
+const ITA2_FIGS: u8 = 27;
+const ITA2_LTRS: u8 = 31;
+// The characters Bletchley Park wrote for the codes, the letters shift with figures for the
+// rest.
+const BLETCHLEY_LETTERS: &[u8; 32] = b"/E3A9SIU4DRJNFCKTZLWHYPQOBG5MXV8";
+
+/// How the 5-bit codes of a [`BaudotCode`] are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaudotFormat {
+    /// Space separated groups of five bits in tape order, bit 1 first, e.g. `11000` for `A`.
+    Binary,
+    /// Space separated hex values, with bit 1 as the least significant bit, e.g. `03` for `A`.
+    Hex,
+    /// One row of punched tape per code, e.g. `|oo.   |` for `A`, with `o` for a hole and the
+    /// feed hole `.` between bits 2 and 3.
+    Tape,
+    /// One character per code as written at Bletchley Park: the letter of the letters shift,
+    /// or `/`, `9`, `3`, `4`, `5` and `8` for NUL, space, line feed, carriage return, `FIGS`
+    /// and `LTRS`. Reading also takes `+` for `FIGS` and `-` for `LTRS`.
+    Bletchley,
+}
+
+/// The Baudot-Murray code of teleprinters, as standardised in ITA2.
+///
+/// Each character is sent as a 5-bit code. The 32 codes stand for letters in the letters shift
+/// and for digits and punctuation in the figures shift, which the `FIGS` and `LTRS` codes switch
+/// between; space, carriage return and line feed are the same in both. Encoding starts in the
+/// letters shift and inserts a shift code wherever the next character needs the other shift.
+/// Letters are encoded regardless of case, a newline is sent as carriage return and line feed,
+/// and characters ITA2 does not have are dropped.
+///
+/// Decoding follows the shifts, reads a line feed as a newline and drops `NUL` and carriage
+/// returns. A group that is not a 5-bit code is read as [`UNKNOWN`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{BaudotCode, BaudotFormat};
+/// let baudot = BaudotCode::new();
+/// assert_eq!(baudot.codes("A1"), [0x03, 0x1b, 0x17]);
+/// assert_eq!(baudot.encode("A1"), "11000 11011 11101");
+/// assert_eq!(baudot.decode("11000 11011 11101"), "A1");
+///
+/// let hex = BaudotCode::new().with_format(BaudotFormat::Hex);
+/// assert_eq!(hex.encode("Go 2"), "1a 18 04 1b 13");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaudotCode {
+    format: BaudotFormat,
+}
+
+impl Default for BaudotCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BaudotCode {
+    /// Creates a new ITA2 converter writing the codes in [`BaudotFormat::Binary`].
+    pub fn new() -> Self {
+        BaudotCode {
+            format: BaudotFormat::Binary,
+        }
+    }
+
+    /// Writes and reads the codes in the given format instead.
+    pub fn with_format(mut self, format: BaudotFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the format of the codes.
+    pub fn format(&self) -> BaudotFormat {
+        self.format
+    }
+
+    /// Converts a text into 5-bit codes, with shift codes where needed.
+    pub fn codes(&self, text: &str) -> Vec<u8> {
+        let find = |table: &[u8; 32], c: char| {
+            (1..32)
+                .find(|&code| table[code] != 0 && table[code] as char == c)
+                .map(|code| code as u8)
+        };
+        let mut codes = Vec::new();
+        let mut figures = false;
+        for c in text.chars().map(|c| c.to_ascii_uppercase()) {
+            match c {
+                '\n' => codes.extend([ITA2_CR, ITA2_LF]),
+                '\r' => {}
+                ' ' => codes.push(find(ITA2_LETTERS, c).expect("ITA2 has a space")),
+                _ => {
+                    let (code, in_figures) = match find(ITA2_LETTERS, c) {
+                        Some(code) => (code, false),
+                        None => match find(ITA2_FIGURES, c) {
+                            Some(code) => (code, true),
+                            None => continue,
+                        },
+                    };
+                    if in_figures != figures {
+                        codes.push(if in_figures { ITA2_FIGS } else { ITA2_LTRS });
+                        figures = in_figures;
+                    }
+                    codes.push(code);
+                }
+            }
+        }
+        codes
+    }
+
+    /// Converts 5-bit codes back into text, following the shift codes. Codes above 31 are
+    /// read as [`UNKNOWN`].
+    pub fn text(&self, codes: &[u8]) -> String {
+        let mut text = String::new();
+        let mut figures = false;
+        for &code in codes {
+            match code {
+                ITA2_FIGS => figures = true,
+                ITA2_LTRS => figures = false,
+                32.. => text.push(UNKNOWN),
+                _ => {
+                    let table = if figures { ITA2_FIGURES } else { ITA2_LETTERS };
+                    match table[code as usize] {
+                        0 | b'\r' => {}
+                        c => text.push(c as char),
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// Encodes a text into ITA2, written in the format of the converter.
+    pub fn encode(&self, text: &str) -> String {
+        self.write(&self.codes(text))
+    }
+
+    /// Writes 5-bit codes in the format of the converter.
+    pub fn write(&self, codes: &[u8]) -> String {
+        let bit = |code: u8, bit: usize| if code >> bit & 1 == 1 { 'o' } else { ' ' };
+        match self.format {
+            BaudotFormat::Binary => codes
+                .iter()
+                .map(|&code| (0..5).map(|i| (b'0' + (code >> i & 1)) as char).collect())
+                .collect::<Vec<String>>()
+                .join(" "),
+            BaudotFormat::Hex => codes
+                .iter()
+                .map(|code| format!("{:02x}", code))
+                .collect::<Vec<_>>()
+                .join(" "),
+            BaudotFormat::Tape => codes
+                .iter()
+                .map(|&code| {
+                    format!(
+                        "|{}{}.{}{}{}|",
+                        bit(code, 0),
+                        bit(code, 1),
+                        bit(code, 2),
+                        bit(code, 3),
+                        bit(code, 4)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            BaudotFormat::Bletchley => codes
+                .iter()
+                .map(|&code| {
+                    BLETCHLEY_LETTERS
+                        .get(code as usize)
+                        .map_or(UNKNOWN, |&c| c as char)
+                })
+                .collect(),
+        }
+    }
+
+    /// Decodes ITA2 written in the format of the converter.
+    ///
+    /// Binary groups and hex values may also be run together, e.g. `1100011011`.
+    pub fn decode(&self, code: &str) -> String {
+        self.text(&self.read(code))
+    }
+
+    /// Reads 5-bit codes written in the format of the converter, with `u8::MAX` for a group
+    /// that is not a code.
+    pub fn read(&self, code: &str) -> Vec<u8> {
+        match self.format {
+            BaudotFormat::Binary => code
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .flat_map(|group| read_groups(group, 5, 2))
+                .collect(),
+            BaudotFormat::Hex => code
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .flat_map(|group| read_groups(group, 2, 16))
+                .collect(),
+            BaudotFormat::Tape => code
+                .lines()
+                .filter(|row| !row.trim().is_empty())
+                .map(|row| {
+                    let holes: Vec<char> = row.chars().filter(|&c| c != '|' && c != '.').collect();
+                    if holes.len() != 5 {
+                        return u8::MAX;
+                    }
+                    holes
+                        .iter()
+                        .rev()
+                        .fold(0, |code, &hole| code << 1 | u8::from(hole != ' '))
+                })
+                .collect(),
+            BaudotFormat::Bletchley => code
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| match c.to_ascii_uppercase() {
+                    '+' => ITA2_FIGS,
+                    '-' => ITA2_LTRS,
+                    c => BLETCHLEY_LETTERS
+                        .iter()
+                        .position(|&b| b as char == c)
+                        .map_or(u8::MAX, |code| code as u8),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Reads a run of digits as codes of `width` digits each, binary ones in tape order. A run that
+// does not split into valid codes is read as one invalid code.
+fn read_groups(group: &str, width: usize, radix: u32) -> Vec<u8> {
+    if group.is_empty() {
+        return Vec::new();
+    }
+    let digits: Vec<char> = group.chars().collect();
+    if !digits.len().is_multiple_of(width) || !digits.iter().all(|c| c.is_digit(radix)) {
+        return vec![u8::MAX];
+    }
+    digits
+        .chunks(width)
+        .map(|digits| {
+            let digits: String = if radix == 2 {
+                digits.iter().rev().collect()
+            } else {
+                digits.iter().collect()
+            };
+            u8::from_str_radix(&digits, radix)
+                .ok()
+                .filter(|&code| code < 32)
+                .unwrap_or(u8::MAX)
+        })
+        .collect()
+}
+
+impl Cipher for BaudotCode {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encode(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decode(ciphertext)
+    }
+}
+
+/// An Atbash cipher implementation.
+///
+/// The Atbash cipher is a specific type of monoalphabetic cipher that was originally used to encode the Hebrew alphabet.
+/// It reverses `A` to `Z`, or the letters of another [`Alphabet`] set with
+/// [`AtbashCipher::with_alphabet`].
+pub struct AtbashCipher {
+    alphabet: Option<Alphabet>,
+}
+
+impl Default for AtbashCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtbashCipher {
+    /// Creates a new instance of the Atbash cipher.
+    pub fn new() -> Self {
+        AtbashCipher { alphabet: None }
+    }
+
+    /// Reverses the letters of `alphabet` in place of `A` to `Z`.
+    ///
+    /// ```rust
+    /// use cipha::alphabet::Alphabet;
+    /// use cipha::ciphers::AtbashCipher;
+    /// let hebrew = Alphabet::new("אבגדהוזחטיכלמנסעפצקרשת").unwrap();
+    /// // Babel becomes Jeremiah's Sheshach, here without the final form of its last letter
+    /// assert_eq!(AtbashCipher::new().with_alphabet(hebrew).encipher("בבל"), "ששכ");
+    /// ```
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+
+    /// Transforms a given text using the Atbash cipher.
+    ///
+    /// This function reverses the order of the alphabet for each letter in the text.
+    pub fn transform(&self, text: &str) -> String {
+        if let Some(alphabet) = &self.alphabet {
+            let last = alphabet.len() - 1;
+            return text
+                .chars()
+                .map(|c| alphabet.replace(c, |i| last - i))
+                .collect();
+        }
+        text.chars()
+            .map(|c| match c {
+                'a'..='z' => (b'a' + 25 - (c as u8 - b'a')) as char,
+                'A'..='Z' => (b'A' + 25 - (c as u8 - b'A')) as char,
+                _ => c,
+            })
+            .collect()
+    }
+
+    /// Enciphers a given plaintext using the Atbash cipher.
+    ///
+    /// Since the Atbash cipher is symmetric, this method is identical to `decipher`.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.transform(plaintext)
+    }
+
+    /// Deciphers a given ciphertext using the Atbash cipher.
+    ///
+    /// Since the Atbash cipher is symmetric, this method is identical to `encipher`.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.transform(ciphertext)
+    }
+}
+
+impl Cipher for AtbashCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        Some(alphabet_mapping(self.alphabet.as_ref(), |p| {
+            self.encipher(p)
+        }))
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        self.alphabet
+            .as_ref()
+            .is_none_or(Alphabet::is_latin)
+            .then_some(Elementary::Atbash)
+    }
+}
+
+/// A word-order reversal.
+///
+/// Reverses the order of the words (runs of non-whitespace) while the whitespace between them
+/// stays in place, so `"attack  at dawn"` becomes `"dawn  at attack"`. The transform is its own
+/// inverse.
+pub struct ReverseWordsCipher;
+
+impl Default for ReverseWordsCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReverseWordsCipher {
+    /// Creates a new instance of the word-order reversal.
+    pub fn new() -> Self {
+        ReverseWordsCipher
+    }
+
+    /// Reverses the order of the words in a given text.
+    pub fn transform(&self, text: &str) -> String {
+        let mut words = text.split_whitespace().rev();
+        let mut result = String::new();
+        let mut in_word = false;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                result.push(c);
+                in_word = false;
+            } else if !in_word {
+                result.push_str(words.next().unwrap_or_default());
+                in_word = true;
+            }
+        }
+        result
+    }
+
+    /// Enciphers a given plaintext by reversing its word order.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.transform(plaintext)
+    }
+
+    /// Deciphers a given ciphertext by reversing its word order.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.transform(ciphertext)
+    }
+}
+
+impl Cipher for ReverseWordsCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        Some(Elementary::ReverseWords)
+    }
+}
+
+/// A letter reversal within each word.
+///
+/// Reverses every run of alphanumeric characters in place, leaving punctuation and whitespace
+/// where they are, so `"Hello, World!"` becomes `"olleH, dlroW!"`. The transform is its own
+/// inverse.
+pub struct ReverseLettersCipher;
+
+impl Default for ReverseLettersCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReverseLettersCipher {
+    /// Creates a new instance of the letter reversal.
+    pub fn new() -> Self {
+        ReverseLettersCipher
+    }
+
+    /// Reverses the letters of every word in a given text.
+    pub fn transform(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut word = String::new();
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                result.extend(word.drain(..).rev());
+                result.push(c);
+            }
+        }
+        result.extend(word.chars().rev());
+        result
+    }
+
+    /// Enciphers a given plaintext by reversing the letters of each word.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.transform(plaintext)
+    }
+
+    /// Deciphers a given ciphertext by reversing the letters of each word.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.transform(ciphertext)
+    }
+}
+
+impl Cipher for ReverseLettersCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        Some(Elementary::ReverseLetters)
+    }
+}
+
+/// A keyword-driven columnar transposition of whole words.
+///
+/// The words are written in rows as wide as the keyword and read off column by column in the
+/// alphabetical order of the keyword letters (ties broken left to right). The last row may be
+/// incomplete. The output words are separated by single spaces.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::WordTranspositionCipher;
+/// let cipher = WordTranspositionCipher::new("CAB").unwrap();
+/// assert_eq!(cipher.encipher("we are discovered flee at once"), "are at discovered once we flee");
+/// assert_eq!(cipher.decipher("are at discovered once we flee"), "we are discovered flee at once");
+/// ```
+pub struct WordTranspositionCipher {
+    order: Vec<usize>,
+}
+
+impl WordTranspositionCipher {
+    /// Creates a new instance of the word transposition with the specified keyword.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword is empty.
+    pub fn new(keyword: &str) -> Result<Self, String> {
+        if keyword.trim().is_empty() {
+            return Err("Keyword must not be empty".to_string());
+        }
+        Ok(WordTranspositionCipher {
+            order: column_order(keyword.trim()),
+        })
+    }
+
+    /// Enciphers a given plaintext by reading its words off column by column.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let words: Vec<&str> = plaintext.split_whitespace().collect();
+        let columns = self.order.len();
+        self.order
+            .iter()
+            .flat_map(|&col| words.iter().skip(col).step_by(columns).copied())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Deciphers a given ciphertext by writing its words back into the columns.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let words: Vec<&str> = ciphertext.split_whitespace().collect();
+        let columns = self.order.len();
+        let mut grid: Vec<Option<&str>> = vec![None; words.len()];
+        let mut rest = words.iter();
+        for &col in &self.order {
+            for slot in (col..words.len()).step_by(columns) {
+                grid[slot] = rest.next().copied();
+            }
+        }
+        grid.into_iter().flatten().collect::<Vec<_>>().join(" ")
+    }
+}
+
+impl Cipher for WordTranspositionCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A columnar transposition cipher with keyword ordering.
+///
+/// The text is written in rows as wide as the keyword and read off column by column, taking the
+/// columns in the alphabetical order of the keyword letters (ties broken left to right). By
+/// default the transposition is incomplete (irregular): the last row is left short. With
+/// [`ColumnarTranspositionCipher::with_padding`] the last row is filled first, giving a complete
+/// transposition; the padding is not removed when deciphering.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::ColumnarTranspositionCipher;
+/// let cipher = ColumnarTranspositionCipher::new("ZEBRAS").unwrap();
+/// assert_eq!(cipher.encipher("WEAREDISCOVEREDFLEEATONCE"), "EVLNACDTESEAROFODEECWIREE");
+/// assert_eq!(cipher.decipher("EVLNACDTESEAROFODEECWIREE"), "WEAREDISCOVEREDFLEEATONCE");
+///
+/// let padded = cipher.with_padding('Q');
+/// assert_eq!(padded.encipher("WEAREDISCOVEREDFLEEATONCE"), "EVLNQACDTQESEAQROFOQDEECQWIREE");
+/// ```
+pub struct ColumnarTranspositionCipher {
+    order: Vec<usize>,
+    padding: Option<char>,
+}
+
+impl ColumnarTranspositionCipher {
+    /// Creates a new instance of the incomplete columnar transposition with the specified keyword.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword is empty.
+    pub fn new(keyword: &str) -> Result<Self, String> {
+        if keyword.trim().is_empty() {
+            return Err("Keyword must not be empty".to_string());
+        }
+        Ok(ColumnarTranspositionCipher {
+            order: column_order(keyword.trim()),
+            padding: None,
+        })
+    }
+
+    /// Fills the last row with `padding` so every column has the same length.
+    pub fn with_padding(mut self, padding: char) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Enciphers a given plaintext by reading it off column by column.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut text = plaintext.to_string();
+        if let Some(padding) = self.padding {
+            let len = plaintext.chars().count();
+            text.extend(std::iter::repeat_n(
+                padding,
+                grid::rows(len, self.order.len()) * self.order.len() - len,
+            ));
+        }
+        grid::read(
+            &text,
+            &grid::columnar_route(text.chars().count(), &self.order),
+        )
+    }
+
+    /// Deciphers a given ciphertext by writing it back column by column.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(
+            ciphertext,
+            &grid::columnar_route(ciphertext.chars().count(), &self.order),
+        )
+    }
+}
+
+impl Transposition for ColumnarTranspositionCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let width = self.order.len();
+        let padded = match self.padding {
+            Some(_) => grid::rows(len, width) * width,
+            None => len,
+        };
+        padded_permutation(&grid::columnar_route(padded, &self.order), len)
+    }
+}
+
+impl Cipher for ColumnarTranspositionCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The AMSCO cipher, a columnar transposition of alternating single letters and pairs.
+///
+/// The text is written in rows as wide as the keyword, with cells holding two and one
+/// characters in turn. The first row starts with a pair, as in the ACA's description, or with
+/// a single character if so chosen, and every row starts with the other kind of cell than the
+/// row above, so the cells also alternate down each column. The cells are read off column by
+/// column in the alphabetical order of the keyword letters (ties broken left to right), and the
+/// last cell may be short.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::AmscoCipher;
+/// let cipher = AmscoCipher::new("4132").unwrap();
+/// // IN C OM P / L ET E CO / LU M NA R
+/// assert_eq!(cipher.encipher("INCOMPLETECOLUMNAR"), "CETMPCOROMENAINLLU");
+/// assert_eq!(cipher.decipher("CETMPCOROMENAINLLU"), "INCOMPLETECOLUMNAR");
+/// let cipher = cipher.with_first_cell(1).unwrap();
+/// // I NC O MP / LE T EC O / L UM N AR
+/// assert_eq!(cipher.encipher("INCOMPLETECOLUMNAR"), "NCTUMMPOAROECNILEL");
+/// assert_eq!(cipher.decipher("NCTUMMPOAROECNILEL"), "INCOMPLETECOLUMNAR");
+/// ```
+pub struct AmscoCipher {
+    order: Vec<usize>,
+    first_cell: usize,
+}
+
+impl AmscoCipher {
+    /// Creates a new instance of the AMSCO cipher with the specified keyword, starting with a
+    /// pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword is empty.
+    pub fn new(keyword: &str) -> Result<Self, String> {
+        if keyword.trim().is_empty() {
+            return Err("Keyword must not be empty".to_string());
+        }
+        Ok(AmscoCipher {
+            order: column_order(keyword.trim()),
+            first_cell: 2,
+        })
+    }
+
+    /// Sets the number of characters, 1 or 2, of the first cell.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the size is not 1 or 2.
+    pub fn with_first_cell(mut self, size: usize) -> Result<Self, String> {
+        if !(1..=2).contains(&size) {
+            return Err(format!(
+                "The first cell holds 1 or 2 characters, not {}",
+                size
+            ));
+        }
+        self.first_cell = size;
+        Ok(self)
+    }
+
+    // Returns the route that reads the cells of a text of `len` characters column by column.
+    fn route(&self, len: usize) -> Vec<usize> {
+        let width = self.order.len();
+        // The characters of every cell, grouped by column
+        let mut columns = vec![Vec::new(); width];
+        let mut start = 0;
+        for cell in 0.. {
+            if start >= len {
+                break;
+            }
+            let (row, column) = (cell / width, cell % width);
+            let size = if (row + column).is_multiple_of(2) {
+                self.first_cell
+            } else {
+                3 - self.first_cell
+            };
+            let end = (start + size).min(len);
+            columns[column].extend(start..end);
+            start = end;
+        }
+        self.order
+            .iter()
+            .flat_map(|&column| columns[column].iter().copied())
+            .collect()
+    }
+
+    /// Enciphers a given plaintext by reading its cells off column by column.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        grid::read(plaintext, &self.route(plaintext.chars().count()))
+    }
+
+    /// Deciphers a given ciphertext by writing its cells back column by column.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(ciphertext, &self.route(ciphertext.chars().count()))
+    }
+}
+
+impl Transposition for AmscoCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&self.route(len))
+    }
+}
+
+impl Cipher for AmscoCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The ADFGX and ADFGVX field ciphers of World War I.
+///
+/// Each character is replaced by the row and column labels of its cell in a keyword-mixed
+/// [`PolybiusSquare`] labelled `ADFGX` (5x5) or `ADFGVX` (6x6). The resulting letters then go
+/// through a [`ColumnarTranspositionCipher`] with the transposition keyword. Characters that
+/// are not in the square are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{AdfgvxCipher, PolybiusGrid};
+/// let cipher = AdfgvxCipher::new(PolybiusGrid::FiveByFive, "BTALPDHOZKQFVSNGICUXMREWY", "CARGO").unwrap();
+/// assert_eq!(cipher.encipher("ATTACK AT ONCE"), "FAXDFADDDGDGFFFAFAXAFAFX");
+/// assert_eq!(cipher.decipher("FAXDFADDDGDGFFFAFAXAFAFX"), "ATTACKATONCE");
+/// ```
+pub struct AdfgvxCipher {
+    square: PolybiusSquare,
+    transposition: ColumnarTranspositionCipher,
+}
+
+impl AdfgvxCipher {
+    /// Creates a new instance of the cipher: ADFGX for a 5x5 grid, ADFGVX for a 6x6 grid.
+    ///
+    /// The square is mixed with `square_keyword`; a keyword holding the whole alphabet sets the
+    /// square directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transposition keyword is empty.
+    pub fn new(
+        grid: PolybiusGrid,
+        square_keyword: &str,
+        transposition_keyword: &str,
+    ) -> Result<Self, String> {
+        let labels = match grid {
+            PolybiusGrid::FiveByFive => "ADFGX",
+            PolybiusGrid::SixBySix => "ADFGVX",
+            PolybiusGrid::SevenBySeven => {
+                return Err("The ADFGVX cipher has no 7x7 square".to_string())
+            }
+        };
+        Ok(AdfgvxCipher {
+            square: PolybiusSquare::new(grid)
+                .with_keyword(square_keyword)
+                .with_labels(labels, labels)?,
+            transposition: ColumnarTranspositionCipher::new(transposition_keyword)?,
+        })
+    }
+
+    /// Returns the key square.
+    pub fn square(&self) -> &PolybiusSquare {
+        &self.square
+    }
+
+    /// Enciphers a given plaintext by fractionating it through the square and transposing it.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let labels: String = fractionate(plaintext, &self.square).into_iter().collect();
+        self.transposition.encipher(&labels)
+    }
+
+    /// Deciphers a given ciphertext by undoing the transposition and reading the square.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let labels: String = ciphertext
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let labels: Vec<char> = self.transposition.decipher(&labels).chars().collect();
+        defractionate(regroup(&labels, 2), &self.square)
+    }
+}
+
+impl Cipher for AdfgvxCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// Delastelle's bifid cipher.
+///
+/// The letters are split into blocks of `period` letters. The row labels of each block in a
+/// keyword-mixed 5x5 [`PolybiusSquare`] are written out, followed by the column labels, and
+/// the combined labels are read back in pairs as letters of the same square. Characters that
+/// are not in the square are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::BifidCipher;
+/// let cipher = BifidCipher::new("PHQGMEAYLNOFDXKRCVSZWBUTI", 5).unwrap();
+/// let ciphertext = cipher.encipher("defend the east wall of the castle");
+/// assert_eq!(ciphertext, "FFYHMKHYCPLIASHADTRLHCCHLBLR");
+/// assert_eq!(cipher.decipher(&ciphertext), "DEFENDTHEEASTWALLOFTHECASTLE");
+/// ```
+pub struct BifidCipher {
+    square: PolybiusSquare,
+    period: usize,
+}
+
+impl BifidCipher {
+    /// Creates a new instance of the cipher with the square mixed by `keyword`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the period is zero.
+    pub fn new(keyword: &str, period: usize) -> Result<Self, String> {
+        if period == 0 {
+            return Err("Period must be positive".to_string());
+        }
+        Ok(BifidCipher {
+            square: PolybiusSquare::new(PolybiusGrid::FiveByFive).with_keyword(keyword),
+            period,
+        })
+    }
+
+    /// Returns the key square.
+    pub fn square(&self) -> &PolybiusSquare {
+        &self.square
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let seriated = seriate(&fractionate(plaintext, &self.square), 2, self.period);
+        defractionate(regroup(&seriated, 2), &self.square)
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let symbols = unseriate(&fractionate(ciphertext, &self.square), 2, self.period);
+        defractionate(regroup(&symbols, 2), &self.square)
+    }
+}
+
+impl Cipher for BifidCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// A 3x3x3 cube of the letters and `+`, numbered layer, row and column from 1 to 3.
+struct TrifidCube {
+    cells: Vec<char>,
+}
+
+impl Fractionator for TrifidCube {
+    fn fractionate(&self, c: char) -> Option<Vec<char>> {
+        if c == UNKNOWN {
+            return Some(vec![UNKNOWN; 3]);
+        }
+        let index = self
+            .cells
+            .iter()
+            .position(|&cell| cell == c.to_ascii_uppercase())?;
+        Some(
+            [index / 9, index / 3 % 3, index % 3]
+                .iter()
+                .map(|&i| (b'1' + i as u8) as char)
+                .collect(),
+        )
+    }
+
+    fn defractionate(&self, symbols: &[char]) -> Option<char> {
+        if symbols.contains(&UNKNOWN) {
+            return Some(UNKNOWN);
+        }
+        let index = symbols.iter().try_fold(0, |index, c| match c {
+            '1'..='3' => Some(index * 3 + (*c as u8 - b'1') as usize),
+            _ => None,
+        })?;
+        self.cells.get(index).copied()
+    }
+}
+
+/// Delastelle's trifid cipher.
+///
+/// The three-dimensional counterpart of the [`BifidCipher`]: the letters and `+` fill a
+/// keyword-mixed 3x3x3 cube, every character is replaced by its layer, row and column, and
+/// each block of `period` characters is seriated and read back in threes. Characters that are
+/// not in the cube are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::TrifidCipher;
+/// let cipher = TrifidCipher::new("EPSDUCVWYM+ZLKXNBTFGORIJHAQ", 5).unwrap();
+/// let ciphertext = cipher.encipher("defend the east wall of the castle+");
+/// assert_eq!(ciphertext, "SUEFECPHSEGYYJIXIMFOFOCEJLBSP");
+/// assert_eq!(cipher.decipher(&ciphertext), "DEFENDTHEEASTWALLOFTHECASTLE+");
+/// ```
+pub struct TrifidCipher {
+    cube: TrifidCube,
+    period: usize,
+}
+
+impl TrifidCipher {
+    /// Creates a new instance of the cipher with the cube mixed by `keyword`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the period is zero.
+    pub fn new(keyword: &str, period: usize) -> Result<Self, String> {
+        if period == 0 {
+            return Err("Period must be positive".to_string());
+        }
+        let mut cells: Vec<char> = Vec::new();
+        for c in keyword
+            .chars()
+            .map(|c| c.to_ascii_uppercase())
+            .chain("ABCDEFGHIJKLMNOPQRSTUVWXYZ+".chars())
+        {
+            if (c.is_ascii_uppercase() || c == '+') && !cells.contains(&c) {
+                cells.push(c);
+            }
+        }
+        Ok(TrifidCipher {
+            cube: TrifidCube { cells },
+            period,
+        })
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let seriated = seriate(&fractionate(plaintext, &self.cube), 3, self.period);
+        defractionate(regroup(&seriated, 3), &self.cube)
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let symbols = unseriate(&fractionate(ciphertext, &self.cube), 3, self.period);
+        defractionate(regroup(&symbols, 3), &self.cube)
+    }
+}
+
+impl Cipher for TrifidCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// A hypercube with `side` cells along each of its `dimensions`, filled with an alphabet in
+// order and numbered from 0 along each axis, the first axis first.
+struct Hypercube {
+    cells: Vec<char>,
+    side: usize,
+    dimensions: usize,
+}
+
+impl Hypercube {
+    // Returns the cell of a character, trying the other ASCII case if it is not in the cube.
+    fn position(&self, c: char) -> Option<usize> {
+        [c, c.to_ascii_uppercase(), c.to_ascii_lowercase()]
+            .iter()
+            .find_map(|&c| self.cells.iter().position(|&cell| cell == c))
+    }
+}
+
+impl Fractionator for Hypercube {
+    fn fractionate(&self, c: char) -> Option<Vec<char>> {
+        if c == UNKNOWN {
+            return Some(vec![UNKNOWN; self.dimensions]);
+        }
+        let index = self.position(c)?;
+        Some(
+            (0..self.dimensions as u32)
+                .rev()
+                .map(|axis| {
+                    let coordinate = index / self.side.pow(axis) % self.side;
+                    char::from_digit(coordinate as u32, 36).unwrap_or(UNKNOWN)
+                })
+                .collect(),
+        )
+    }
+
+    fn defractionate(&self, symbols: &[char]) -> Option<char> {
+        if symbols.contains(&UNKNOWN) {
+            return Some(UNKNOWN);
+        }
+        let index = symbols.iter().try_fold(0, |index, c| {
+            let coordinate = c.to_digit(36).filter(|&d| (d as usize) < self.side)?;
+            Some(index * self.side + coordinate as usize)
+        })?;
+        self.cells.get(index).copied()
+    }
+}
+
+/// Delastelle's fractionation in any number of dimensions.
+///
+/// The [`BifidCipher`] and [`TrifidCipher`] generalized: the alphabet fills a hypercube with the
+/// same number of cells along each of its `dimensions`, every character is replaced by its
+/// coordinates, and each block of `period` characters is seriated and read back in groups of as
+/// many coordinates. An alphabet of 16 characters makes a four-dimensional cube with two cells
+/// along each axis, one of 81 a four-dimensional cube of side three. Characters that are not in
+/// the alphabet, in either ASCII case, are dropped, and the ciphertext is written in the case
+/// of the alphabet.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::DelastelleCipher;
+/// // 32 = 2^5 characters: a five-dimensional cube
+/// let cipher = DelastelleCipher::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ .,;:!", 5, 7)
+///     .unwrap()
+///     .with_keyword("HYPERCUBE");
+/// assert_eq!(cipher.side(), 2);
+/// let ciphertext = cipher.encipher("Meet me at noon, by the bridge!");
+/// assert_eq!(cipher.decipher(&ciphertext), "MEET ME AT NOON, BY THE BRIDGE!");
+/// ```
+pub struct DelastelleCipher {
+    cube: Hypercube,
+    period: usize,
+}
+
+impl DelastelleCipher {
+    /// Creates a new instance of the cipher with the alphabet in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than two dimensions, the period is zero, the alphabet
+    /// repeats a character or holds [`UNKNOWN`], or its length is not a side of 2 to 36 cells
+    /// raised to the number of dimensions.
+    pub fn new(alphabet: &str, dimensions: usize, period: usize) -> Result<Self, String> {
+        if dimensions < 2 {
+            return Err("Fractionation needs at least two dimensions".to_string());
+        }
+        if period == 0 {
+            return Err("Period must be positive".to_string());
+        }
+        let cells: Vec<char> = alphabet.chars().collect();
+        for (i, c) in cells.iter().enumerate() {
+            if *c == UNKNOWN {
+                return Err(format!("Alphabet cannot hold {:?}", UNKNOWN));
+            }
+            if cells[..i].contains(c) {
+                return Err(format!("Alphabet has {:?} twice", c));
+            }
+        }
+        let power = |side: usize| side.checked_pow(dimensions as u32).unwrap_or(usize::MAX);
+        let side = (2..=36)
+            .find(|&side| power(side) >= cells.len())
+            .unwrap_or(36);
+        if power(side) != cells.len() {
+            return Err(format!(
+                "Alphabet of {} characters does not fill a cube of {} dimensions",
+                cells.len(),
+                dimensions
+            ));
+        }
+        Ok(DelastelleCipher {
+            cube: Hypercube {
+                cells,
+                side,
+                dimensions,
+            },
+            period,
+        })
+    }
+
+    /// Mixes the alphabet with a keyword: the characters of the keyword come first, in the order
+    /// they first appear, then the rest of the alphabet.
+    pub fn with_keyword(mut self, keyword: &str) -> Self {
+        let mut cells = Vec::with_capacity(self.cube.cells.len());
+        for c in keyword.chars() {
+            if let Some(index) = self.cube.position(c) {
+                let cell = self.cube.cells[index];
+                if !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        for &cell in &self.cube.cells {
+            if !cells.contains(&cell) {
+                cells.push(cell);
+            }
+        }
+        self.cube.cells = cells;
+        self
+    }
+
+    /// Returns the number of cells along each dimension.
+    pub fn side(&self) -> usize {
+        self.cube.side
+    }
+
+    /// Returns the number of dimensions, the coordinates of every character.
+    pub fn dimensions(&self) -> usize {
+        self.cube.dimensions
+    }
+
+    /// Returns the alphabet in the order it fills the cube.
+    pub fn alphabet(&self) -> String {
+        self.cube.cells.iter().collect()
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let width = self.cube.dimensions;
+        let seriated = seriate(&fractionate(plaintext, &self.cube), width, self.period);
+        defractionate(regroup(&seriated, width), &self.cube)
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let width = self.cube.dimensions;
+        let symbols = unseriate(&fractionate(ciphertext, &self.cube), width, self.period);
+        defractionate(regroup(&symbols, width), &self.cube)
+    }
+}
+
+impl Cipher for DelastelleCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// The 26 trigrams of dots, dashes and separators (all but `xxx`), each standing for a letter of
+// a keyed alphabet.
+struct MorseTrigrams {
+    alphabet: Vec<char>,
+}
+
+impl MorseTrigrams {
+    const SYMBOLS: [char; 3] = ['.', '-', 'x'];
+}
+
+impl Fractionator for MorseTrigrams {
+    fn fractionate(&self, c: char) -> Option<Vec<char>> {
+        let index = self
+            .alphabet
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase())?;
+        Some(vec![
+            Self::SYMBOLS[index / 9],
+            Self::SYMBOLS[index / 3 % 3],
+            Self::SYMBOLS[index % 3],
+        ])
+    }
+
+    fn defractionate(&self, symbols: &[char]) -> Option<char> {
+        let index = symbols.iter().try_fold(0, |index, c| {
+            Some(index * 3 + Self::SYMBOLS.iter().position(|s| s == c)?)
+        })?;
+        self.alphabet.get(index).copied()
+    }
+}
+
+/// The fractionated Morse cipher.
+///
+/// The plaintext is written in Morse code with an `x` between letters and `xx` between words,
+/// padded with `x` to a multiple of three, and every three symbols are replaced by a letter of a
+/// keyword-mixed alphabet: `...` by the first letter, `..-` by the second and so on up to `xx-`.
+/// Characters without a Morse code are dropped and the deciphered text is in capitals.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::FractionatedMorseCipher;
+/// let cipher = FractionatedMorseCipher::new("ROUNDTABLE");
+/// let ciphertext = cipher.encipher("Come at once");
+/// assert_eq!(cipher.decipher(&ciphertext), "COME AT ONCE");
+/// ```
+pub struct FractionatedMorseCipher {
+    trigrams: MorseTrigrams,
+}
+
+impl FractionatedMorseCipher {
+    /// Creates a new instance of the cipher with the alphabet mixed by `keyword`.
+    pub fn new(keyword: &str) -> Self {
+        FractionatedMorseCipher {
+            trigrams: MorseTrigrams {
+                alphabet: keyed_alphabet(keyword).chars().collect(),
+            },
+        }
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut symbols: Vec<char> = morse_symbols(plaintext).chars().collect();
+        while !symbols.len().is_multiple_of(3) {
+            symbols.push('x');
+        }
+        defractionate(regroup(&symbols, 3), &self.trigrams)
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let symbols: String = fractionate(ciphertext, &self.trigrams)
+            .into_iter()
+            .collect();
+        morse_text(&symbols)
+    }
+}
+
+impl Cipher for FractionatedMorseCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// Writes the text in Morse code with an `x` between letters and `xx` between words, dropping
+// characters without a code.
+fn morse_symbols(text: &str) -> String {
+    let code = |c: char| {
+        let c = c.to_ascii_uppercase().to_string();
+        MORSE_CODE_MAP
+            .iter()
+            .find(|(text, _)| *text == c)
+            .map(|(_, code)| *code)
+    };
+    text.split_whitespace()
+        .map(|word| word.chars().filter_map(code).collect::<Vec<_>>().join("x"))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("xx")
+}
+
+// Reads Morse code written by `morse_symbols`, ignoring separators at either end.
+fn morse_text(symbols: &str) -> String {
+    let letter = |code: &str| {
+        MORSE_CODE_MAP
+            .iter()
+            .find(|(_, c)| *c == code)
+            .map(|(text, _)| *text)
+    };
+    symbols
+        .trim_matches('x')
+        .split("xx")
+        .map(|word| word.split('x').filter_map(letter).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The Pollux cipher.
+///
+/// The plaintext is written in Morse code with an `x` between letters and `xx` between words, as
+/// in [`FractionatedMorseCipher`], and every symbol is replaced by a digit. The key gives the
+/// symbol (`.`, `-` or `x`) of each digit from 0 to 9, and the occurrences of a symbol take its
+/// digits in turn, so repeated letters rarely look the same. Characters other than digits are
+/// ignored when deciphering and the deciphered text is in capitals.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::PolluxCipher;
+/// let cipher = PolluxCipher::new("x.-x.-x.-x").unwrap();
+/// assert_eq!(cipher.encipher("SOS"), "14702583147");
+/// assert_eq!(cipher.decipher("14702583147"), "SOS");
+/// ```
+pub struct PolluxCipher {
+    symbols: Vec<char>,
+}
+
+impl PolluxCipher {
+    /// Creates a new instance of the cipher with the symbols of the digits 0 to 9.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the key has ten symbols, each `.`, `-` or `x`, and uses all three.
+    pub fn new(key: &str) -> Result<Self, String> {
+        let symbols: Vec<char> = key.chars().filter(|c| !c.is_whitespace()).collect();
+        if symbols.len() != 10 {
+            return Err(format!(
+                "Key must have a symbol for each digit 0-9, but has {} symbols",
+                symbols.len()
+            ));
+        }
+        if let Some(c) = symbols.iter().find(|c| !MorseTrigrams::SYMBOLS.contains(c)) {
+            return Err(format!("Key symbols are '.', '-' or 'x', not {:?}", c));
+        }
+        if let Some(missing) = MorseTrigrams::SYMBOLS
+            .iter()
+            .find(|symbol| !symbols.contains(symbol))
+        {
+            return Err(format!("Key has no digit for {:?}", missing));
+        }
+        Ok(PolluxCipher { symbols })
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut turns = [0; 3];
+        morse_symbols(plaintext)
+            .chars()
+            .map(|symbol| {
+                let index = MorseTrigrams::SYMBOLS
+                    .iter()
+                    .position(|&s| s == symbol)
+                    .expect("Morse code is dots, dashes and separators");
+                let digits: Vec<usize> = (0..10).filter(|&d| self.symbols[d] == symbol).collect();
+                let digit = digits[turns[index] % digits.len()];
+                turns[index] += 1;
+                char::from_digit(digit as u32, 10).expect("A decimal digit")
+            })
+            .collect()
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let symbols: String = ciphertext
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .map(|d| self.symbols[d as usize])
+            .collect();
+        morse_text(&symbols)
+    }
+}
+
+impl Cipher for PolluxCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The Morbit cipher.
+///
+/// The plaintext is written in Morse code with an `x` between letters and `xx` between words, as
+/// in [`FractionatedMorseCipher`], padded with an `x` to an even length, and every pair of
+/// symbols is replaced by a digit from 1 to 9. The pairs `..`, `.-`, `.x`, `-.`, `--`, `-x`,
+/// `x.`, `x-` and `xx` take the numbers of the first nine letters of the keyword in alphabetical
+/// order, so with `WISECRACK` (9 5 8 4 2 7 1 3 6) `..` becomes 9 and `.-` becomes 5. Characters
+/// other than digits are ignored when deciphering and the deciphered text is in capitals.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::MorbitCipher;
+/// let cipher = MorbitCipher::new("WISECRACK").unwrap();
+/// assert_eq!(cipher.encipher("SOS"), "982798");
+/// assert_eq!(cipher.decipher("982798"), "SOS");
+/// ```
+pub struct MorbitCipher {
+    // The digit of each pair of symbols
+    digits: [char; 9],
+}
+
+impl MorbitCipher {
+    /// Creates a new instance of the cipher numbered by the first nine letters of `keyword`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword has fewer than nine letters.
+    pub fn new(keyword: &str) -> Result<Self, String> {
+        let letters: String = keyword
+            .chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .take(9)
+            .collect();
+        if letters.len() < 9 {
+            return Err(format!(
+                "Keyword must have at least 9 letters, but has {}",
+                letters.len()
+            ));
+        }
+        let mut digits = ['0'; 9];
+        for (rank, position) in column_order(&letters).into_iter().enumerate() {
+            digits[position] = char::from_digit(rank as u32 + 1, 10).expect("A decimal digit");
+        }
+        Ok(MorbitCipher { digits })
+    }
+
+    /// Enciphers a given plaintext.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut symbols: Vec<char> = morse_symbols(plaintext).chars().collect();
+        if !symbols.len().is_multiple_of(2) {
+            symbols.push('x');
+        }
+        let index = |symbol: &char| {
+            MorseTrigrams::SYMBOLS
+                .iter()
+                .position(|s| s == symbol)
+                .expect("Morse code is dots, dashes and separators")
+        };
+        symbols
+            .chunks_exact(2)
+            .map(|pair| self.digits[index(&pair[0]) * 3 + index(&pair[1])])
+            .collect()
+    }
+
+    /// Deciphers a given ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let symbols: String = ciphertext
+            .chars()
+            .filter_map(|c| self.digits.iter().position(|&d| d == c))
+            .flat_map(|pair| {
+                [
+                    MorseTrigrams::SYMBOLS[pair / 3],
+                    MorseTrigrams::SYMBOLS[pair % 3],
+                ]
+            })
+            .collect();
+        morse_text(&symbols)
+    }
+}
+
+impl Cipher for MorbitCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The Nihilist cipher of the Russian revolutionaries.
+///
+/// Every letter of the plaintext and of the key is turned into the two-digit number of its row
+/// and column in a keyword-mixed 5x5 [`PolybiusSquare`], and the key numbers are added to the
+/// plaintext numbers one by one, repeating the key. The ciphertext is the space separated sums.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::NihilistCipher;
+/// let cipher = NihilistCipher::new("ZEBRAS", "RUSSIAN").unwrap();
+/// assert_eq!(cipher.encipher("dynamite"), "37 106 62 36 67 47 86 26");
+/// assert_eq!(cipher.decipher("37 106 62 36 67 47 86 26"), "DYNAMITE");
+/// ```
+pub struct NihilistCipher {
+    square: PolybiusSquare,
+    key: Vec<u32>,
+}
+
+impl NihilistCipher {
+    /// Creates a new instance of the cipher with the square mixed by `square_keyword`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key has no letters.
+    pub fn new(square_keyword: &str, key: &str) -> Result<Self, String> {
+        let square = PolybiusSquare::new(PolybiusGrid::FiveByFive).with_keyword(square_keyword);
+        let key: Vec<u32> = Self::numbers(&square, key).into_iter().flatten().collect();
+        if key.is_empty() {
+            return Err("Key must contain letters".to_string());
+        }
+        Ok(NihilistCipher { square, key })
+    }
+
+    /// Returns the key square.
+    pub fn square(&self) -> &PolybiusSquare {
+        &self.square
+    }
+
+    // Returns the two-digit numbers of the characters in the square, `None` for unknown ones.
+    fn numbers(square: &PolybiusSquare, text: &str) -> Vec<Option<u32>> {
+        regroup(&fractionate(text, square), 2)
+            .map(|pair| Some(pair[0].to_digit(10)? * 10 + pair[1].to_digit(10)?))
+            .collect()
+    }
+
+    /// Enciphers a given plaintext, writing unknown characters as a lone placeholder.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        Self::numbers(&self.square, plaintext)
+            .iter()
+            .zip(self.key.iter().cycle())
+            .map(|(number, key)| match number {
+                Some(number) => (number + key).to_string(),
+                None => UNKNOWN.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Deciphers a given ciphertext, ignoring anything that is not a number or a placeholder.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let digits: Vec<char> = ciphertext
+            .split(|c: char| !c.is_ascii_digit() && c != UNKNOWN)
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse::<u32>().ok())
+            .zip(self.key.iter().cycle())
+            .map(|(number, key)| number.and_then(|number| number.checked_sub(*key)))
+            .flat_map(|number| match number {
+                Some(number) => [
+                    char::from_digit(number / 10 % 10, 10),
+                    char::from_digit(number % 10, 10),
+                ],
+                None => [Some(UNKNOWN); 2],
+            })
+            .flatten()
+            .collect();
+        defractionate(regroup(&digits, 2), &self.square)
+    }
+}
+
+impl Cipher for NihilistCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A double columnar transposition cipher.
+///
+/// The text goes through an incomplete [`ColumnarTranspositionCipher`] with the first keyword
+/// and then through another with the second keyword. Deciphering undoes the two transpositions
+/// in reverse order.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::DoubleTranspositionCipher;
+/// let cipher = DoubleTranspositionCipher::new("ZEBRAS", "STRIPE").unwrap();
+/// let ciphertext = cipher.encipher("WEAREDISCOVEREDFLEEATONCE");
+/// assert_eq!(ciphertext, "CAEENSOIAEDRLEFWEDREEVTOC");
+/// assert_eq!(cipher.decipher(&ciphertext), "WEAREDISCOVEREDFLEEATONCE");
+/// ```
+pub struct DoubleTranspositionCipher {
+    first: ColumnarTranspositionCipher,
+    second: ColumnarTranspositionCipher,
+}
+
+impl DoubleTranspositionCipher {
+    /// Creates a new instance of the double transposition with the two keywords, in the order
+    /// they are applied when enciphering.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either keyword is empty.
+    pub fn new(first: &str, second: &str) -> Result<Self, String> {
+        Ok(DoubleTranspositionCipher {
+            first: ColumnarTranspositionCipher::new(first)?,
+            second: ColumnarTranspositionCipher::new(second)?,
+        })
+    }
+
+    /// Enciphers a given plaintext with the first and then the second transposition.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.second.encipher(&self.first.encipher(plaintext))
+    }
+
+    /// Deciphers a given ciphertext by undoing the second and then the first transposition.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.first.decipher(&self.second.decipher(ciphertext))
+    }
+}
+
+impl Transposition for DoubleTranspositionCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let second = self.second.permutation(len);
+        self.first
+            .permutation(len)
+            .into_iter()
+            .map(|position| second[position])
+            .collect()
+    }
+}
+
+impl Cipher for DoubleTranspositionCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// Returns the column indices in the order they are read, sorted by keyword character and then
+// by position.
+pub(crate) fn column_order(keyword: &str) -> Vec<usize> {
+    let mut order: Vec<(usize, char)> = keyword
+        .chars()
+        .map(|c| c.to_ascii_uppercase())
+        .enumerate()
+        .collect();
+    order.sort_by_key(|&(i, c)| (c, i));
+    order.into_iter().map(|(i, _)| i).collect()
+}
+
+/// An interleave transform.
+///
+/// Splits the text into `n` streams, where stream `i` holds every `n`th character starting at
+/// position `i`, and concatenates the streams. Deciphering weaves the streams back together, so
+/// `"ACEGBDF"` with two streams becomes `"ABCDEFG"`.
+pub struct InterleaveCipher {
+    streams: usize,
+}
+
+impl InterleaveCipher {
+    /// Creates a new instance of the interleave transform with the specified number of streams.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `streams` is zero.
+    pub fn new(streams: usize) -> Result<Self, String> {
+        if streams == 0 {
+            return Err("Number of streams must be at least 1".to_string());
+        }
+        Ok(InterleaveCipher { streams })
+    }
+
+    /// Enciphers a given plaintext by concatenating its streams.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let chars: Vec<char> = plaintext.chars().collect();
+        (0..self.streams)
+            .flat_map(|i| chars.iter().skip(i).step_by(self.streams))
+            .collect()
+    }
+
+    /// Deciphers a given ciphertext by weaving its streams back together.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let chars: Vec<char> = ciphertext.chars().collect();
+        let mut result = vec![' '; chars.len()];
+        let len = chars.len();
+        let positions = (0..self.streams).flat_map(|i| (i..len).step_by(self.streams));
+        for (position, c) in positions.zip(chars) {
+            result[position] = c;
+        }
+        result.into_iter().collect()
+    }
+}
+
+impl Transposition for InterleaveCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&grid::columnar_route(
+            len,
+            &(0..self.streams).collect::<Vec<usize>>(),
+        ))
+    }
+}
+
+impl Cipher for InterleaveCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The rail fence cipher.
+///
+/// The text is written in a zig-zag down and up across `rails` rows and read off row by row.
+/// [`RailFenceCipher::with_offset`] starts the zig-zag partway through its first cycle, as if
+/// `offset` characters had been written before the text, which many puzzles do.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::RailFenceCipher;
+/// let cipher = RailFenceCipher::new(3).unwrap();
+/// assert_eq!(cipher.encipher("WEAREDISCOVERED"), "WECRERDSOEEAIVD");
+/// assert_eq!(cipher.decipher("WECRERDSOEEAIVD"), "WEAREDISCOVERED");
+///
+/// // Starting on the bottom rail, on the way up
+/// let offset = RailFenceCipher::new(3).unwrap().with_offset(2);
+/// assert_eq!(offset.encipher("WEAREDISCOVERED"), "AIVDERDSOEEWECR");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RailFenceCipher {
+    rails: usize,
+    offset: usize,
+}
+
+impl RailFenceCipher {
+    /// Creates a new instance of the rail fence cipher with `rails` rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `rails` is zero.
+    pub fn new(rails: usize) -> Result<Self, String> {
+        if rails == 0 {
+            return Err("Rails must be at least 1".to_string());
+        }
+        Ok(RailFenceCipher { rails, offset: 0 })
+    }
+
+    /// Starts the zig-zag `offset` characters into its cycle of `2 * (rails - 1)` characters.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    // Returns the route that reads the rails from top to bottom, in one pass over the text.
+    fn route(&self, len: usize) -> Vec<usize> {
+        let cycle = 2 * (self.rails - 1);
+        let rail = |i: usize| {
+            let phase = (i + self.offset) % cycle.max(1);
+            phase.min(cycle - phase)
+        };
+        let mut rails = vec![Vec::new(); self.rails];
+        for i in 0..len {
+            rails[rail(i)].push(i);
+        }
+        rails.concat()
+    }
+
+    /// Enciphers a given plaintext by reading the rails.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        grid::read(plaintext, &self.route(plaintext.chars().count()))
+    }
+
+    /// Deciphers a given ciphertext by laying it back on the rails.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(ciphertext, &self.route(ciphertext.chars().count()))
+    }
+}
+
+impl Transposition for RailFenceCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&self.route(len))
+    }
+}
+
+impl Cipher for RailFenceCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The scytale, the rod cipher of ancient Sparta.
+///
+/// A strip is wound around a rod that shows `diameter` letters around its circumference, and
+/// the message is written along the rod, one row per face. Unwound, the strip reads the rows
+/// interleaved: the text is written in `diameter` rows and read off column by column. By default
+/// the last row is left short; with [`ScytaleCipher::with_padding`] it is filled first. The
+/// padding is not removed when deciphering.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::ScytaleCipher;
+/// let cipher = ScytaleCipher::new(4).unwrap();
+/// assert_eq!(cipher.encipher("IAMHURTVERYBADLYHELP"), "IRYYATBHMVAEHEDLURLP");
+/// assert_eq!(cipher.decipher("IRYYATBHMVAEHEDLURLP"), "IAMHURTVERYBADLYHELP");
+///
+/// let padded = ScytaleCipher::new(4).unwrap().with_padding('X');
+/// assert_eq!(padded.encipher("HELPME"), "HLMXEPEX");
+/// ```
+pub struct ScytaleCipher {
+    diameter: usize,
+    padding: Option<char>,
+}
+
+impl ScytaleCipher {
+    /// Creates a new instance of the scytale with `diameter` letters around the rod.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `diameter` is zero.
+    pub fn new(diameter: usize) -> Result<Self, String> {
+        if diameter == 0 {
+            return Err("Diameter must be at least 1".to_string());
+        }
+        Ok(ScytaleCipher {
+            diameter,
+            padding: None,
+        })
+    }
+
+    /// Fills the last row with `padding` so the strip winds around the rod a whole number of times.
+    pub fn with_padding(mut self, padding: char) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    // Returns the route that unwinds a strip of `len` characters: the rows are as long as the
+    // number of turns around the rod.
+    fn route(&self, len: usize) -> Vec<usize> {
+        let turns = grid::rows(len, self.diameter).max(1);
+        grid::columnar_route(len, &(0..turns).collect::<Vec<usize>>())
+    }
+
+    /// Enciphers a given plaintext by writing it along the rod and unwinding the strip.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut text = plaintext.to_string();
+        if let Some(padding) = self.padding {
+            let len = plaintext.chars().count();
+            text.extend(std::iter::repeat_n(
+                padding,
+                grid::rows(len, self.diameter) * self.diameter - len,
+            ));
+        }
+        grid::read(&text, &self.route(text.chars().count()))
+    }
+
+    /// Deciphers a given ciphertext by winding the strip back around the rod.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(ciphertext, &self.route(ciphertext.chars().count()))
+    }
+}
+
+impl Transposition for ScytaleCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let padded = match self.padding {
+            Some(_) => grid::rows(len, self.diameter) * self.diameter,
+            None => len,
+        };
+        padded_permutation(&self.route(padded), len)
+    }
+}
+
+impl Cipher for ScytaleCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The Caesar box.
+///
+/// The text is written row by row into a square grid, the smallest that holds it, and read off
+/// column by column. A text that does not fill the square leaves the last row short, or the grid
+/// one row short of square. [`CaesarBoxCipher::with_width`] sets the width of the rows instead,
+/// for boxes that are not square.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::CaesarBoxCipher;
+/// let cipher = CaesarBoxCipher::new();
+/// assert_eq!(cipher.encipher("HELLOWORLD"), "HOLEWDLOLR");
+/// assert_eq!(cipher.decipher("HOLEWDLOLR"), "HELLOWORLD");
+///
+/// let wide = CaesarBoxCipher::new().with_width(5).unwrap();
+/// assert_eq!(wide.encipher("HELLOWORLD"), "HWEOLRLLOD");
+/// ```
+#[derive(Default)]
+pub struct CaesarBoxCipher {
+    width: Option<usize>,
+}
+
+impl CaesarBoxCipher {
+    /// Creates a new instance of the Caesar box with a square grid.
+    pub fn new() -> Self {
+        CaesarBoxCipher { width: None }
+    }
+
+    /// Writes the text in rows `width` characters wide instead of a square.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `width` is zero.
+    pub fn with_width(mut self, width: usize) -> Result<Self, String> {
+        if width == 0 {
+            return Err("Width must be at least 1".to_string());
+        }
+        self.width = Some(width);
+        Ok(self)
+    }
+
+    // Returns the route that reads the columns of the box holding `len` characters.
+    fn route(&self, len: usize) -> Vec<usize> {
+        let width = self
+            .width
+            .unwrap_or_else(|| (1..).find(|side| side * side >= len).unwrap_or(1));
+        grid::columnar_route(len, &(0..width).collect::<Vec<usize>>())
+    }
+
+    /// Enciphers a given plaintext by writing it in rows and reading the columns.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        grid::read(plaintext, &self.route(plaintext.chars().count()))
+    }
+
+    /// Deciphers a given ciphertext by writing it in columns and reading the rows.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(ciphertext, &self.route(ciphertext.chars().count()))
+    }
+}
+
+impl Transposition for CaesarBoxCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&self.route(len))
+    }
+}
+
+impl Cipher for CaesarBoxCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The pattern of holes of a turning grille.
+///
+/// A grille is a square card with an even side and a hole in a quarter of its cells, placed so
+/// that turning it clockwise four times uncovers every cell exactly once. A pattern is written
+/// row by row, with `X` for a hole and `.` for the card, the rows separated by newlines or `/`.
+/// With the `serde` feature a grille is saved as the list of its rows.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::Grille;
+/// let grille: Grille = "X.../.X.X/..../.X..".parse().unwrap();
+/// assert_eq!(grille.size(), 4);
+/// assert_eq!(grille.to_string(), "X...\n.X.X\n....\n.X..");
+///
+/// // The hole in the top left corner meets the one in the top right after a quarter turn
+/// assert!("X..X/..../..../....".parse::<Grille>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<String>", into = "Vec<String>")
+)]
+pub struct Grille {
+    size: usize,
+    // The row-major indices of the holes, in increasing order
+    holes: Vec<usize>,
+}
+
+impl Grille {
+    /// Reads a grille from its rows, `X` marking the holes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the grille is not square with an even side, has a character other
+    /// than `X` or `.`, or does not uncover every cell exactly once as it turns.
+    pub fn from_rows<S: AsRef<str>>(rows: &[S]) -> Result<Self, String> {
+        let size = rows.len();
+        if size == 0 || !size.is_multiple_of(2) {
+            return Err(format!(
+                "Grille must have an even number of rows, but has {}",
+                size
+            ));
+        }
+        let mut holes = Vec::new();
+        for (row, cells) in rows.iter().enumerate() {
+            let cells: Vec<char> = cells.as_ref().trim().chars().collect();
+            if cells.len() != size {
+                return Err(format!(
+                    "Row {} has {} cells, but the grille has {} rows",
+                    row + 1,
+                    cells.len(),
+                    size
+                ));
+            }
+            for (column, c) in cells.into_iter().enumerate() {
+                match c {
+                    'X' | 'x' => holes.push(row * size + column),
+                    '.' => {}
+                    _ => return Err(format!("Grille cells are 'X' or '.', not {:?}", c)),
+                }
+            }
+        }
+        let grille = Grille { size, holes };
+        let mut uncovered = vec![0; size * size];
+        for turn in 0..4 {
+            for cell in grille.turned(turn) {
+                uncovered[cell] += 1;
+            }
+        }
+        if let Some(cell) = uncovered.iter().position(|&count| count != 1) {
+            return Err(format!(
+                "The cell in row {}, column {} is uncovered {} times as the grille turns, not once",
+                cell / size + 1,
+                cell % size + 1,
+                uncovered[cell]
+            ));
+        }
+        Ok(grille)
+    }
+
+    /// Generates a random grille `size` cells wide, using the operating system's secure random
+    /// number generator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `size` is not a positive even number or no secure random source is
+    /// available.
+    pub fn random(size: usize) -> Result<Self, String> {
+        if size == 0 || !size.is_multiple_of(2) {
+            return Err(format!("Grille size must be even, but is {}", size));
+        }
+        let half = size / 2;
+        // Each cell of the top left quarter stands for the four cells a turn takes it to, and
+        // one of the four gets the hole
+        let turns = random_bytes(half * half)?;
+        let mut holes: Vec<usize> = turns
+            .iter()
+            .enumerate()
+            .map(|(cell, turn)| {
+                let (mut row, mut column) = (cell / half, cell % half);
+                for _ in 0..turn % 4 {
+                    (row, column) = (column, size - 1 - row);
+                }
+                row * size + column
+            })
+            .collect();
+        holes.sort_unstable();
+        Ok(Grille { size, holes })
+    }
+
+    /// Returns the number of cells along a side.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Returns the cells under the holes after `turns` clockwise quarter turns, in row-major
+    // order.
+    fn turned(&self, turns: usize) -> Vec<usize> {
+        let size = self.size;
+        let mut cells: Vec<usize> = self
+            .holes
+            .iter()
+            .map(|&hole| {
+                let (mut row, mut column) = (hole / size, hole % size);
+                for _ in 0..turns {
+                    (row, column) = (column, size - 1 - row);
+                }
+                row * size + column
+            })
+            .collect();
+        cells.sort_unstable();
+        cells
+    }
+
+    /// Returns the rows of the grille, `X` marking the holes.
+    pub fn rows(&self) -> Vec<String> {
+        (0..self.size)
+            .map(|row| {
+                (0..self.size)
+                    .map(|column| {
+                        if self.holes.contains(&(row * self.size + column)) {
+                            'X'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl std::str::FromStr for Grille {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, String> {
+        let rows: Vec<&str> = pattern
+            .split(['/', '\n'])
+            .filter(|row| !row.trim().is_empty())
+            .collect();
+        Grille::from_rows(&rows)
+    }
+}
+
+impl std::fmt::Display for Grille {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.rows().join("\n"))
+    }
+}
+
+impl TryFrom<Vec<String>> for Grille {
+    type Error = String;
+
+    fn try_from(rows: Vec<String>) -> Result<Self, String> {
+        Grille::from_rows(&rows)
+    }
+}
+
+impl From<Grille> for Vec<String> {
+    fn from(grille: Grille) -> Self {
+        grille.rows()
+    }
+}
+
+/// The turning grille, or Fleissner grille.
+///
+/// The text is written through the holes of the [`Grille`] laid on a square, row by row, then
+/// the grille is turned a quarter clockwise and the writing goes on, four times in all, and the
+/// square is read off row by row. A text longer than the square fills one square after another.
+/// The last square is left with gaps, which are skipped when it is read, unless
+/// [`GrilleCipher::with_padding`] fills them.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{Grille, GrilleCipher};
+/// let grille: Grille = "X.../.X.X/..../.X..".parse().unwrap();
+/// let cipher = GrilleCipher::new(grille.clone());
+/// assert_eq!(cipher.encipher("JIMATTACKSATDAWN"), "JDKTTIAMSAAWNACT");
+/// assert_eq!(cipher.decipher("JDKTTIAMSAAWNACT"), "JIMATTACKSATDAWN");
+///
+/// let padded = GrilleCipher::new(grille).with_padding('X');
+/// assert_eq!(padded.encipher("HELLO"), "HXXOXEXLXXXXXLXX");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrilleCipher {
+    grille: Grille,
+    padding: Option<char>,
+}
+
+impl GrilleCipher {
+    /// Creates a new instance of the turning grille.
+    pub fn new(grille: Grille) -> Self {
+        GrilleCipher {
+            grille,
+            padding: None,
+        }
+    }
+
+    /// Fills the gaps of the last square with `padding`.
+    pub fn with_padding(mut self, padding: char) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Returns the grille.
+    pub fn grille(&self) -> &Grille {
+        &self.grille
+    }
+
+    // Returns the route that writes `len` characters through the turning grille, as the rank
+    // of each written cell among the cells that get a character.
+    fn route(&self, len: usize) -> Vec<usize> {
+        let area = self.grille.size * self.grille.size;
+        let order: Vec<usize> = (0..4).flat_map(|turn| self.grille.turned(turn)).collect();
+        let mut route = Vec::with_capacity(len);
+        for start in (0..len).step_by(area.max(1)) {
+            let cells: Vec<usize> = order.iter().copied().take(len - start).collect();
+            let mut filled = cells.clone();
+            filled.sort_unstable();
+            route.extend(
+                cells
+                    .iter()
+                    .map(|cell| start + filled.binary_search(cell).unwrap_or(0)),
+            );
+        }
+        route
+    }
+
+    /// Enciphers a given plaintext by writing it through the turning grille.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut text = plaintext.to_string();
+        if let Some(padding) = self.padding {
+            let area = self.grille.size * self.grille.size;
+            let len = plaintext.chars().count();
+            text.extend(std::iter::repeat_n(
+                padding,
+                len.div_ceil(area) * area - len,
+            ));
+        }
+        grid::unread(&text, &self.route(text.chars().count()))
+    }
+
+    /// Deciphers a given ciphertext by reading it back through the turning grille.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::read(ciphertext, &self.route(ciphertext.chars().count()))
+    }
+}
+
+impl Transposition for GrilleCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let area = self.grille.size * self.grille.size;
+        let padded = match self.padding {
+            Some(_) => len.div_ceil(area) * area,
+            None => len,
+        };
+        let mut route = self.route(padded);
+        route.truncate(len);
+        route
+    }
+}
+
+impl Cipher for GrilleCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A boustrophedon transform.
+///
+/// The text is written in rows of the given width and read back with every second row
+/// reversed, as an ox ploughs a field.
+pub struct BoustrophedonCipher {
+    width: usize,
+}
+
+impl BoustrophedonCipher {
+    /// Creates a new instance of the boustrophedon transform with rows `width` characters wide.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `width` is zero.
+    pub fn new(width: usize) -> Result<Self, String> {
+        if width == 0 {
+            return Err("Width must be at least 1".to_string());
+        }
+        Ok(BoustrophedonCipher { width })
+    }
+
+    /// Enciphers a given plaintext by reversing every second row.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        grid::read(
+            plaintext,
+            &grid::boustrophedon_route(plaintext.chars().count(), self.width),
+        )
+    }
+
+    /// Deciphers a given ciphertext by reversing every second row back.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(
+            ciphertext,
+            &grid::boustrophedon_route(ciphertext.chars().count(), self.width),
+        )
+    }
+}
+
+impl Transposition for BoustrophedonCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&grid::boustrophedon_route(len, self.width))
+    }
+}
+
+impl Cipher for BoustrophedonCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A spiral route transposition.
+///
+/// The text is written in rows of the given width and read back along a clockwise spiral,
+/// either inward from the top left corner or outward from the centre.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::SpiralCipher;
+/// use cipha::grid::SpiralDirection;
+/// let spiral = SpiralCipher::new(4, SpiralDirection::Inward).unwrap();
+/// assert_eq!(spiral.encipher("ABCDEFGHIJKL"), "ABCDHLKJIEFG");
+/// assert_eq!(spiral.decipher("ABCDHLKJIEFG"), "ABCDEFGHIJKL");
+/// ```
+pub struct SpiralCipher {
+    width: usize,
+    direction: SpiralDirection,
+}
+
+impl SpiralCipher {
+    /// Creates a new instance of the spiral transposition with rows `width` characters wide.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `width` is zero.
+    pub fn new(width: usize, direction: SpiralDirection) -> Result<Self, String> {
+        if width == 0 {
+            return Err("Width must be at least 1".to_string());
+        }
+        Ok(SpiralCipher { width, direction })
+    }
+
+    /// Enciphers a given plaintext by reading it along the spiral.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        grid::read(
+            plaintext,
+            &grid::spiral_route(plaintext.chars().count(), self.width, self.direction),
+        )
+    }
+
+    /// Deciphers a given ciphertext by writing it back along the spiral.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        grid::unread(
+            ciphertext,
+            &grid::spiral_route(ciphertext.chars().count(), self.width, self.direction),
+        )
+    }
+}
+
+impl Transposition for SpiralCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        grid::inverse(&grid::spiral_route(len, self.width, self.direction))
+    }
+}
+
+impl Cipher for SpiralCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The path a [`RouteCipher`] reads its grid along.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// A clockwise spiral inward from the top left corner.
+    SpiralClockwise,
+    /// A counterclockwise spiral inward from the top left corner.
+    SpiralCounterclockwise,
+    /// The rows, with every second row reversed.
+    Boustrophedon,
+    /// Whole columns, taking the columns in the given order.
+    Columns(Vec<usize>),
+}
+
+/// A route transposition.
+///
+/// The text is written in rows into a grid of `width` by `height` cells and read back along the
+/// route. Text longer than the grid fills a fresh grid for every `width * height` characters; the
+/// last grid may be incomplete, in which case its missing cells are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{Route, RouteCipher};
+/// let cipher = RouteCipher::new(4, 3, Route::SpiralCounterclockwise).unwrap();
+/// assert_eq!(cipher.encipher("ABCDEFGHIJKL"), "AEIJKLHDCBFG");
+/// assert_eq!(cipher.decipher("AEIJKLHDCBFG"), "ABCDEFGHIJKL");
+///
+/// let columns = RouteCipher::new(3, 2, Route::Columns(vec![2, 0, 1])).unwrap();
+/// assert_eq!(columns.encipher("ABCDEF"), "CFADBE");
+/// ```
+pub struct RouteCipher {
+    width: usize,
+    height: usize,
+    route: Route,
+}
+
+impl RouteCipher {
+    /// Creates a new instance of the route transposition over a grid of `width` by `height`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either dimension is zero, or if a column order is not an ordering of
+    /// the columns `0..width`.
+    pub fn new(width: usize, height: usize, route: Route) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err("Width and height must be at least 1".to_string());
+        }
+        if let Route::Columns(order) = &route {
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            if !sorted.iter().copied().eq(0..width) {
+                return Err(format!(
+                    "Column order must list each of the columns 0 to {} once",
+                    width - 1
+                ));
+            }
+        }
+        Ok(RouteCipher {
+            width,
+            height,
+            route,
+        })
+    }
+
+    // Returns the route through a grid holding `len` characters.
+    fn route(&self, len: usize) -> Vec<usize> {
+        match &self.route {
+            Route::SpiralClockwise => grid::spiral_route(len, self.width, SpiralDirection::Inward),
+            Route::SpiralCounterclockwise => {
+                grid::counterclockwise_spiral_route(len, self.width, SpiralDirection::Inward)
+            }
+            Route::Boustrophedon => grid::boustrophedon_route(len, self.width),
+            Route::Columns(order) => grid::columnar_route(len, order),
+        }
+    }
+
+    // Applies `transform` to each grid-sized block of the text.
+    fn blocks(&self, text: &str, transform: fn(&str, &[usize]) -> String) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .chunks(self.width * self.height)
+            .map(|block| {
+                let block: String = block.iter().collect();
+                transform(&block, &self.route(block.chars().count()))
+            })
+            .collect()
+    }
+
+    /// Enciphers a given plaintext by writing it into the grid and reading it along the route.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.blocks(plaintext, grid::read)
+    }
+
+    /// Deciphers a given ciphertext by writing it along the route and reading the rows.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.blocks(ciphertext, grid::unread)
+    }
+}
+
+impl Transposition for RouteCipher {
+    fn permutation(&self, len: usize) -> Vec<usize> {
+        let area = self.width * self.height;
+        (0..len)
+            .step_by(area)
+            .flat_map(|start| {
+                grid::inverse(&self.route(area.min(len - start)))
+                    .into_iter()
+                    .map(move |position| start + position)
+            })
+            .collect()
+    }
+}
+
+impl Cipher for RouteCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A decimation (skip) cipher.
+///
+/// The ciphertext is formed by reading every `skip`th character of the message cyclically,
+/// starting with the first: the character at position `i` of the ciphertext is the one at
+/// position `i × skip mod n` of the plaintext. Every character is read exactly once only if
+/// `skip` is coprime with the message length `n`. Because that depends on the message,
+/// enciphering and deciphering return a `Result`, so this cipher does not implement [`Cipher`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::DecimationCipher;
+/// let cipher = DecimationCipher::new(3).unwrap();
+/// assert_eq!(cipher.encipher("GEOCACHE").unwrap(), "GCHEAEOC");
+/// assert_eq!(cipher.decipher("GCHEAEOC").unwrap(), "GEOCACHE");
+/// assert!(cipher.encipher("GEOCACHES").is_err()); // 3 divides 9
+/// ```
+pub struct DecimationCipher {
+    skip: usize,
+}
+
+impl DecimationCipher {
+    /// Creates a new instance of the decimation cipher that reads every `skip`th character.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `skip` is zero.
+    pub fn new(skip: usize) -> Result<Self, String> {
+        if skip == 0 {
+            return Err("Skip must be at least 1".to_string());
+        }
+        Ok(DecimationCipher { skip })
+    }
+
+    // Returns the plaintext position of every ciphertext position.
+    fn positions(&self, len: usize) -> Result<Vec<usize>, String> {
+        if len > 0 && gcd(self.skip as i64, len as i64) != 1 {
+            return Err(format!(
+                "Skip {} must be coprime with the message length {}",
+                self.skip, len
+            ));
+        }
+        Ok((0..len).map(|i| i * self.skip % len).collect())
+    }
+
+    /// Returns the ciphertext position of every character of a plaintext of `len` characters,
+    /// as [`Transposition::permutation`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `skip` is not coprime with `len`.
+    pub fn permutation(&self, len: usize) -> Result<Vec<usize>, String> {
+        Ok(grid::inverse(&self.positions(len)?))
+    }
+
+    /// Enciphers a given plaintext by reading every `skip`th character.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `skip` is not coprime with the length of the plaintext.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        let chars: Vec<char> = plaintext.chars().collect();
+        Ok(self
+            .positions(chars.len())?
+            .into_iter()
+            .map(|p| chars[p])
+            .collect())
+    }
+
+    /// Deciphers a given ciphertext by putting every character back at its position.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `skip` is not coprime with the length of the ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let chars: Vec<char> = ciphertext.chars().collect();
+        let mut result = vec![' '; chars.len()];
+        for (p, c) in self.positions(chars.len())?.into_iter().zip(chars) {
+            result[p] = c;
+        }
+        Ok(result.into_iter().collect())
+    }
+}
+
+/// A digit-additive cipher keyed by a chain addition keystream.
+///
+/// Each digit of the message is added (without carry) to the next digit of the chain addition
+/// sequence grown from the seed. Other characters are left unchanged and do not consume key digits.
+pub struct ChainAdditiveCipher {
+    seed: String,
+}
+
+impl ChainAdditiveCipher {
+    /// Creates a new instance of the chain additive cipher with the specified seed digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the seed is not at least two decimal digits.
+    pub fn new(seed: &str) -> Result<Self, String> {
+        ChainAddition::new(seed)?;
+        Ok(ChainAdditiveCipher {
+            seed: seed.to_string(),
+        })
+    }
+
+    /// Returns the key digits added to the first `len` digits of a message: the seed followed by
+    /// its chain addition, as in the Gromark cipher.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::ChainAdditiveCipher;
+    /// let cipher = ChainAdditiveCipher::new("715").unwrap();
+    /// assert_eq!(cipher.key_stream(6), vec![7, 1, 5, 8, 6, 3]);
+    /// ```
+    pub fn key_stream(&self, len: usize) -> Vec<u8> {
+        ChainAddition::new(&self.seed)
+            .expect("seed was validated in new")
+            .take(len)
+            .collect()
+    }
+
+    // Adds (or subtracts) the keystream to every digit of the text.
+    fn apply(&self, text: &str, subtract: bool) -> String {
+        let mut keystream = ChainAddition::new(&self.seed).expect("seed was validated in new");
+        text.chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) => {
+                    let k = keystream.next().unwrap() as u32;
+                    let d = if subtract { d + 10 - k } else { d + k };
+                    char::from_digit(d % 10, 10).unwrap()
+                }
+                None => c,
+            })
+            .collect()
+    }
+
+    /// Enciphers a given plaintext by adding the keystream to its digits.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.apply(plaintext, false)
+    }
+
+    /// Deciphers a given ciphertext by subtracting the keystream from its digits.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.apply(ciphertext, true)
+    }
+}
+
+impl Cipher for ChainAdditiveCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A straddling checkerboard, the letter-to-digit substitution of the VIC cipher family.
+///
+/// The board has ten columns labelled 0 to 9. The top row holds the eight letters of the keyword
+/// row and leaves two columns blank; the digits of the blank columns label the two rows below,
+/// which hold the rest of the alphabet in order followed by `.` and `/`. Letters of the top row
+/// become one digit and the others two, so frequent letters placed on top give short output.
+/// Lowercase letters are enciphered as uppercase; characters not on the board are dropped.
+///
+/// [`with_headings`](Self::with_headings) relabels the columns, as the VIC cipher does with a
+/// digit permutation derived from its key. [`with_additive_key`](Self::with_additive_key) adds a
+/// repeating key to the digits, without carrying, as other ciphers of the VIC family do.
+///
+/// Deciphering fails on digits that end in the middle of a letter, so the board is a
+/// [`FallibleCipher`] rather than a [`Cipher`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::StraddlingCheckerboard;
+/// // Blanks at 2 and 6:  E T _ A O N _ R I S
+/// let board = StraddlingCheckerboard::new("ETAONRIS", [2, 6]).unwrap();
+/// assert_eq!(board.encipher("Attack at dawn"), "3113212731223645");
+/// assert_eq!(board.decipher("3113212731223645").unwrap(), "ATTACKATDAWN");
+///
+/// let keyed = board.with_additive_key("0451").unwrap();
+/// assert_eq!(keyed.encipher("Attack at dawn"), "3564257835733096");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StraddlingCheckerboard {
+    // The symbol of every cell: row 0 is the top row, rows 1 and 2 are labelled by the blanks
+    rows: [[char; 10]; 3],
+    blanks: [u8; 2],
+    headings: [u8; 10],
+    additive_key: Vec<u8>,
+}
+
+impl StraddlingCheckerboard {
+    /// Creates a checkerboard with the eight letters of `keyword_row` on top, leaving the columns
+    /// in `blanks` empty. The columns are counted from 0 and headed by the digits 0 to 9.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyword row is not eight distinct letters, or the blanks are not
+    /// two different digits.
+    pub fn new(keyword_row: &str, blanks: [u8; 2]) -> Result<Self, String> {
+        let top: Vec<char> = keyword_row
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        if top.len() != 8 || top.iter().any(|c| !c.is_ascii_uppercase()) {
+            return Err("Keyword row must be eight letters".to_string());
+        }
+        if (1..top.len()).any(|i| top[..i].contains(&top[i])) {
+            return Err("Keyword row must not repeat a letter".to_string());
+        }
+        if blanks[0] == blanks[1] || blanks.iter().any(|&b| b > 9) {
+            return Err("Blanks must be two different digits".to_string());
+        }
+        let mut top = top.into_iter();
+        let keyword_row = keyword_row.to_ascii_uppercase();
+        let mut rest = ('A'..='Z')
+            .filter(|c| !keyword_row.contains(*c))
+            .chain(['.', '/']);
+        let mut rows = [[' '; 10]; 3];
+        for (column, cell) in rows[0].iter_mut().enumerate() {
+            if !blanks.contains(&(column as u8)) {
+                *cell = top.next().expect("the top row has eight letters");
+            }
+        }
+        for cell in rows[1..].iter_mut().flatten() {
+            *cell = rest
+                .next()
+                .expect("the lower rows hold the other twenty symbols");
+        }
+        Ok(StraddlingCheckerboard {
+            rows,
+            blanks,
+            headings: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+            additive_key: Vec::new(),
+        })
+    }
+
+    /// Creates the checkerboard that gives the shortest output for a language: the eight most
+    /// frequent letters of `frequencies`, the percentages of `A`..=`Z` such as
+    /// [`analysis::Language::frequencies`](crate::analysis::Language::frequencies), go on the
+    /// top row, most frequent first. [`keyword_row`](Self::keyword_row) and the blanks recreate
+    /// the board, and its `Display` draws it for sharing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the blanks are not two different digits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::analysis::Language;
+    /// use cipha::ciphers::StraddlingCheckerboard;
+    /// let board = StraddlingCheckerboard::optimized(Language::English.frequencies(), [2, 6]).unwrap();
+    /// assert_eq!(board.keyword_row(), "ETAOINSH");
+    /// assert_eq!(
+    ///     board.to_string(),
+    ///     "   0 1 2 3 4 5 6 7 8 9\n   E T   A O I   N S H\n2  B C D F G J K L M P\n6  Q R U V W X Y Z . /"
+    /// );
+    ///
+    /// let german = StraddlingCheckerboard::optimized(Language::German.frequencies(), [2, 6]).unwrap();
+    /// assert_eq!(german.keyword_row(), "ENSRIATD");
+    /// ```
+    pub fn optimized(frequencies: &[f64; 26], blanks: [u8; 2]) -> Result<Self, String> {
+        let mut letters: Vec<usize> = (0..26).collect();
+        letters.sort_by(|&a, &b| frequencies[b].total_cmp(&frequencies[a]));
+        let keyword_row: String = letters[..8]
+            .iter()
+            .map(|&i| (b'A' + i as u8) as char)
+            .collect();
+        Self::new(&keyword_row, blanks)
+    }
+
+    /// Returns the eight letters of the top row, from left to right.
+    pub fn keyword_row(&self) -> String {
+        self.rows[0]
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| !self.blanks.contains(&(*column as u8)))
+            .map(|(_, &c)| c)
+            .collect()
+    }
+
+    /// Heads the columns, from left to right, with the given digits instead of 0 to 9.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the headings are not the digits 0 to 9 in some order.
+    pub fn with_headings(mut self, headings: [u8; 10]) -> Result<Self, String> {
+        if (0..10).any(|d| !headings.contains(&d)) {
+            return Err("Headings must be the digits 0 to 9 in some order".to_string());
+        }
+        self.headings = headings;
+        Ok(self)
+    }
+
+    /// Adds the repeating `key` digits to the output, digit by digit without carrying.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is empty or contains a non-digit.
+    pub fn with_additive_key(mut self, key: &str) -> Result<Self, String> {
+        let key: Vec<u8> = key
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or(format!("Additive key contains a non-digit: {}", c))
+            })
+            .collect::<Result<_, _>>()?;
+        if key.is_empty() {
+            return Err("Additive key must not be empty".to_string());
+        }
+        self.additive_key = key;
+        Ok(self)
+    }
+
+    /// Returns the headings of the blank columns, which label the lower rows.
+    pub fn blanks(&self) -> [u8; 2] {
+        self.blanks.map(|b| self.headings[b as usize])
+    }
+
+    // Returns the digits of a symbol, if it is on the board.
+    fn digits(&self, symbol: char) -> Option<Vec<u8>> {
+        self.rows.iter().enumerate().find_map(|(row, cells)| {
+            let column = cells.iter().position(|&c| c == symbol)?;
+            let heading = self.headings[column];
+            match row {
+                0 if self.blanks.contains(&(column as u8)) => None,
+                0 => Some(vec![heading]),
+                _ => Some(vec![self.blanks()[row - 1], heading]),
+            }
+        })
+    }
+
+    // Adds (or subtracts) the additive key to the digits.
+    fn add_key(&self, digits: &mut [u8], subtract: bool) {
+        for (digit, k) in digits.iter_mut().zip(self.additive_key.iter().cycle()) {
+            *digit = if subtract {
+                (*digit + 10 - k) % 10
+            } else {
+                (*digit + k) % 10
+            };
+        }
+    }
+
+    /// Enciphers a given plaintext into digits.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let mut digits: Vec<u8> = plaintext
+            .chars()
+            .filter_map(|c| self.digits(c.to_ascii_uppercase()))
+            .flatten()
+            .collect();
+        self.add_key(&mut digits, false);
+        digits.into_iter().map(|d| (b'0' + d) as char).collect()
+    }
+
+    /// Deciphers the digits of a ciphertext; other characters, such as group spacing, are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext ends with a row digit that has no column after it.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let mut digits: Vec<u8> = ciphertext
+            .chars()
+            .filter_map(|c| c.to_digit(10).map(|d| d as u8))
+            .collect();
+        self.add_key(&mut digits, true);
+        let column = |digit: u8| self.headings.iter().position(|&h| h == digit).unwrap();
+        let mut plaintext = String::new();
+        let mut digits = digits.into_iter();
+        while let Some(digit) = digits.next() {
+            match self.blanks().iter().position(|&b| b == digit) {
+                Some(row) => {
+                    let digit = digits
+                        .next()
+                        .ok_or("Ciphertext ends in the middle of a letter")?;
+                    plaintext.push(self.rows[row + 1][column(digit)]);
+                }
+                None => plaintext.push(self.rows[0][column(digit)]),
+            }
+        }
+        Ok(plaintext)
+    }
+}
+
+impl std::fmt::Display for StraddlingCheckerboard {
+    /// Draws the board with the column headings on top and the row labels on the left.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let row = |cells: &[char]| {
+            cells
+                .iter()
+                .map(char::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let headings: Vec<char> = self.headings.iter().map(|&d| (b'0' + d) as char).collect();
+        write!(f, "   {}\n   {}", row(&headings), row(&self.rows[0]))?;
+        for (label, cells) in self.blanks().iter().zip(&self.rows[1..]) {
+            write!(f, "\n{}  {}", label, row(cells))?;
+        }
+        Ok(())
+    }
+}
+
+impl FallibleCipher for StraddlingCheckerboard {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        Ok(self.encipher(plaintext))
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The domain a [`OneTimePad`] works in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PadMode {
+    /// Pencil-and-paper pads: each letter is shifted by the next pad letter, mod 26.
+    Letters,
+    /// Pads for arbitrary data: each byte is XORed with the next pad byte.
+    Bytes,
+}
+
+/// A one-time pad cipher.
+///
+/// The pad must be truly random, at least as long as the message and never used twice;
+/// [`generate`](Self::generate) draws one from the operating system's secure random number
+/// generator. A letter pad is kept as uppercase ASCII letters, so [`pad`](Self::pad) is
+/// always the form to hand to the recipient.
+///
+/// Letter pads encipher text: letters keep their case, other characters pass through without
+/// consuming the pad, and an [`UNKNOWN`] letter uses up its pad letter and stays unknown.
+/// Byte pads XOR arbitrary data, so enciphering and deciphering are the same operation.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{OneTimePad, PadMode};
+/// let otp = OneTimePad::letters("XMCKL").unwrap();
+/// assert_eq!(otp.encipher("hello").unwrap(), "eqnvz");
+/// assert_eq!(otp.decipher("eqnvz").unwrap(), "hello");
+/// assert!(otp.encipher("hello world").is_err()); // the pad is too short
+///
+/// let otp = OneTimePad::generate(PadMode::Bytes, 16).unwrap();
+/// let ciphertext = otp.xor(b"attack at dawn").unwrap();
+/// assert_eq!(otp.xor(&ciphertext).unwrap(), b"attack at dawn");
+/// ```
+pub struct OneTimePad {
+    mode: PadMode,
+    pad: Vec<u8>,
+}
+
+impl OneTimePad {
+    /// Creates a letter pad from its text, ignoring the whitespace between letter groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pad contains anything other than letters and whitespace.
+    pub fn letters(pad: &str) -> Result<Self, String> {
+        let pad = pad
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    Ok(c.to_ascii_uppercase() as u8)
+                } else {
+                    Err(format!("Invalid pad character '{}'", c))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(OneTimePad {
+            mode: PadMode::Letters,
+            pad,
+        })
+    }
+
+    /// Creates a byte pad.
+    pub fn bytes(pad: &[u8]) -> Self {
+        OneTimePad {
+            mode: PadMode::Bytes,
+            pad: pad.to_vec(),
+        }
+    }
+
+    /// Generates a random pad of `len` letters or bytes with the operating system's secure
+    /// random number generator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the random number generator is unavailable.
+    pub fn generate(mode: PadMode, len: usize) -> Result<Self, String> {
+        let pad = match mode {
+            PadMode::Bytes => random_bytes(len)?,
+            PadMode::Letters => {
+                // Reject the bytes above the largest multiple of 26 so every letter is as likely.
+                let mut letters = Vec::with_capacity(len);
+                while letters.len() < len {
+                    letters.extend(
+                        random_bytes(len - letters.len())?
+                            .into_iter()
+                            .filter(|&b| b < 234)
+                            .map(|b| b'A' + b % 26),
+                    );
+                }
+                letters
+            }
+        };
+        Ok(OneTimePad { mode, pad })
+    }
+
+    /// Returns the mode of the pad.
+    pub fn mode(&self) -> PadMode {
+        self.mode
+    }
+
+    /// Returns the pad: uppercase ASCII letters for a letter pad, raw bytes for a byte pad.
+    pub fn pad(&self) -> &[u8] {
+        &self.pad
+    }
+
+    // Checks that the pad covers `needed` positions in the expected mode.
+    fn check(&self, mode: PadMode, needed: usize) -> Result<(), String> {
+        if self.mode != mode {
+            return Err(match mode {
+                PadMode::Letters => "A byte pad cannot encipher text, use xor".to_string(),
+                PadMode::Bytes => "A letter pad cannot XOR bytes, use encipher".to_string(),
+            });
+        }
+        if self.pad.len() < needed {
+            return Err(format!(
+                "The pad has {} positions but the message needs {}",
+                self.pad.len(),
+                needed
+            ));
+        }
+        Ok(())
+    }
+
+    // Shifts every letter of the text by the next pad letter, forwards or backwards.
+    fn shift(&self, text: &str, decipher: bool) -> Result<String, String> {
+        let needed = text
+            .chars()
+            .filter(|&c| c.is_ascii_alphabetic() || c == UNKNOWN)
+            .count();
+        self.check(PadMode::Letters, needed)?;
+        let mut pad = self.pad.iter().map(|b| b - b'A');
+        Ok(text
+            .chars()
+            .map(|c| {
+                if c == UNKNOWN {
+                    pad.next();
+                    return c;
+                }
+                if !c.is_ascii_alphabetic() {
+                    return c;
+                }
+                let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                let k = pad.next().unwrap();
+                let k = if decipher { 26 - k } else { k };
+                ((c as u8 - base + k) % 26 + base) as char
+            })
+            .collect())
+    }
+
+    /// Enciphers a given plaintext with a letter pad.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is a byte pad or if the pad has fewer letters than the plaintext.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.shift(plaintext, false)
+    }
+
+    /// Deciphers a given ciphertext with a letter pad.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is a byte pad or if the pad has fewer letters than the ciphertext.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.shift(ciphertext, true)
+    }
+
+    /// XORs the data with a byte pad, which both enciphers and deciphers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this is a letter pad or if the pad is shorter than the data.
+    pub fn xor(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        self.check(PadMode::Bytes, data.len())?;
+        Ok(data.iter().zip(&self.pad).map(|(d, k)| d ^ k).collect())
+    }
+}
+
+// Reads `len` bytes from the operating system's secure random number generator.
+fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("No secure random source: {}", e))?;
+    Ok(bytes)
+}
+
+/// A codebook cipher that replaces tokens using a user supplied translation table.
+///
+/// Tokens may be words, syllables or symbols, so the same mechanism covers Morse, NATO spelling
+/// and nomenclators. Encoding scans the text left to right and replaces the longest source token
+/// matching at each position; decoding does the same with the code tokens. Text without a
+/// matching token is copied unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::TableCipher;
+/// let table = TableCipher::from_csv("attack,0425\nat,17\ndawn,1172").unwrap();
+/// assert_eq!(table.encipher("attack at dawn"), "0425 17 1172");
+/// assert_eq!(table.decipher("0425 17 1172"), "attack at dawn");
+/// ```
+pub struct TableCipher {
+    entries: Vec<(String, String)>,
+}
+
+impl TableCipher {
+    /// Creates a new table cipher from `(source, code)` token pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token is empty, or if a source or code token appears twice, since the
+    /// table could then not be inverted.
+    pub fn new(entries: Vec<(String, String)>) -> Result<Self, String> {
+        let mut sources = std::collections::HashSet::new();
+        let mut codes = std::collections::HashSet::new();
+        for (source, code) in &entries {
+            if source.is_empty() || code.is_empty() {
+                return Err("Table tokens must not be empty".to_string());
+            }
+            if !sources.insert(source) {
+                return Err(format!("Duplicate source token: {}", source));
+            }
+            if !codes.insert(code) {
+                return Err(format!("Duplicate code token: {}", code));
+            }
+        }
+        Ok(TableCipher { entries })
+    }
+
+    /// Loads a table cipher from CSV or TSV text with one `source,code` pair per line.
+    ///
+    /// The delimiter is a tab if the first entry contains one and a comma otherwise. Fields may be
+    /// wrapped in double quotes (with `""` for a literal quote) to include the delimiter itself.
+    /// Blank lines and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line does not have exactly two fields or the table is invalid (see
+    /// [`TableCipher::new`]).
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let lines: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .collect();
+        let delimiter = match lines.first() {
+            Some((_, line)) if line.contains('\t') => '\t',
+            _ => ',',
+        };
+        let entries = lines
+            .into_iter()
+            .map(
+                |(number, line)| match split_record(line, delimiter).as_slice() {
+                    [source, code] => Ok((source.clone(), code.clone())),
+                    fields => Err(format!(
+                        "Line {}: expected 2 fields, found {}",
+                        number + 1,
+                        fields.len()
+                    )),
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        TableCipher::new(entries)
+    }
+
+    /// Returns `true` if any concatenation of code tokens can be split back into tokens in only
+    /// one way.
+    ///
+    /// Codes such as `1`, `12` and `21` are not uniquely decodable (`121` is both `1 21` and
+    /// `12 1`), so ciphertext without separators may decode wrongly. Uses the Sardinas–Patterson
+    /// test.
+    pub fn is_uniquely_decodable(&self) -> bool {
+        let codes: std::collections::HashSet<&str> =
+            self.entries.iter().map(|(_, code)| code.as_str()).collect();
+        // Dangling suffixes left over when one code is a prefix of another
+        let mut frontier: std::collections::HashSet<String> = std::collections::HashSet::new();
+        for a in &codes {
+            for b in &codes {
+                if a != b && b.starts_with(a) {
+                    frontier.insert(b[a.len()..].to_string());
+                }
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        while !frontier.is_empty() {
+            if frontier
+                .iter()
+                .any(|suffix| codes.contains(suffix.as_str()))
+            {
+                return false;
+            }
+            let mut next = std::collections::HashSet::new();
+            for suffix in &frontier {
+                for code in &codes {
+                    if code.len() > suffix.len() && code.starts_with(suffix.as_str()) {
+                        next.insert(code[suffix.len()..].to_string());
+                    } else if suffix.len() > code.len() && suffix.starts_with(code) {
+                        next.insert(suffix[code.len()..].to_string());
+                    }
+                }
+            }
+            seen.extend(frontier);
+            frontier = next
+                .into_iter()
+                .filter(|suffix| !seen.contains(suffix))
+                .collect();
+        }
+        true
+    }
+
+    // Replaces the longest matching `from` token at every position with its `to` token.
+    fn translate(&self, text: &str, decode: bool) -> String {
+        let mut pairs: Vec<(&str, &str)> = self
+            .entries
+            .iter()
+            .map(|(source, code)| {
+                if decode {
+                    (code.as_str(), source.as_str())
+                } else {
+                    (source.as_str(), code.as_str())
+                }
+            })
+            .collect();
+        pairs.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match pairs.iter().find(|(from, _)| rest.starts_with(from)) {
+                Some((from, to)) => {
+                    result.push_str(to);
+                    rest = &rest[from.len()..];
+                }
+                None => {
+                    result.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        result
+    }
+
+    /// Enciphers a given plaintext by replacing source tokens with their codes.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.translate(plaintext, false)
+    }
+
+    /// Deciphers a given ciphertext by replacing codes with their source tokens.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.translate(ciphertext, true)
+    }
+}
+
+impl Cipher for TableCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A nomenclator: a code table of numbers for whole words, syllables and single letters, as in
+/// the Great Cipher of Louis XIV.
+///
+/// Encoding reads the text ignoring case and replaces the longest token of the table matching at
+/// each position with its number, so `the king` may go as one code while an unlisted word is
+/// spelled out of syllables and letters. A token may have several numbers (homophones), used in
+/// turn. Characters other than letters and digits are dropped unless the table has a token for
+/// them, such as a space, and a letter or digit the table cannot spell becomes [`UNKNOWN`]. The
+/// numbers are separated by spaces.
+///
+/// Decoding reads every run of digits as a number and writes out its token as given in the
+/// table. Null numbers, listed with an empty token, decode to nothing, and numbers that are not
+/// in the table decode to [`UNKNOWN`].
+///
+/// With the `serde` feature a nomenclator is saved as a map from each token to its number, or to
+/// the list of its homophones, with the nulls under the empty token. Numbers are read from JSON
+/// numbers or strings, e.g. `{"le roi": 125, "an": [41, "078"], "": 99}`, and saved as strings
+/// to keep their leading zeros.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::NomenclatorCipher;
+/// let nomenclator = NomenclatorCipher::from_csv(
+///     "le roi,125\nfr,22\nan,41,78\nce,303\n,99\ns,6\na,7",
+/// )
+/// .unwrap();
+/// assert_eq!(nomenclator.encipher("Le roi a France"), "125 7 22 41 303");
+/// assert_eq!(nomenclator.encipher("sans"), "6 41 6");
+/// assert_eq!(nomenclator.encipher("an an"), "41 78");
+/// assert_eq!(nomenclator.decipher("125 99 7 22-41 303"), "le roiafrance");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "NomenclatorTable", into = "NomenclatorTable")
+)]
+pub struct NomenclatorCipher {
+    // Tokens with their numbers, longest token first.
+    entries: Vec<(String, Vec<String>)>,
+    nulls: Vec<String>,
+}
+
+impl NomenclatorCipher {
+    /// Creates a nomenclator from tokens and their numbers. The numbers of an empty token are
+    /// nulls.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token has no number, a number is not made of digits, a number is
+    /// used twice or a token is listed twice, ignoring case.
+    pub fn new(entries: Vec<(String, Vec<String>)>) -> Result<Self, String> {
+        let mut tokens = std::collections::HashSet::new();
+        let mut numbers = std::collections::HashSet::new();
+        let mut nulls = Vec::new();
+        let mut table = Vec::new();
+        for (token, codes) in entries {
+            if codes.is_empty() {
+                return Err(format!("Token {:?} has no number", token));
+            }
+            for code in &codes {
+                if code.is_empty() || !code.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(format!("Number {:?} of {:?} is not a number", code, token));
+                }
+                if !numbers.insert(code.clone()) {
+                    return Err(format!("Number {} is used twice", code));
+                }
+            }
+            if token.is_empty() {
+                nulls.extend(codes);
+            } else if !tokens.insert(token.to_lowercase()) {
+                return Err(format!("Token {:?} is listed twice", token));
+            } else {
+                table.push((token, codes));
+            }
+        }
+        table.sort_by_key(|(token, _)| std::cmp::Reverse(token.chars().count()));
+        Ok(NomenclatorCipher {
+            entries: table,
+            nulls,
+        })
+    }
+
+    /// Loads a nomenclator from CSV or TSV text with one token and its numbers per line, e.g.
+    /// `le roi,125` or `an,41,78`. A line with an empty token lists nulls.
+    ///
+    /// The delimiter and quoting are those of [`TableCipher::from_csv`]: quote a token to
+    /// include the delimiter or surrounding spaces, e.g. `" ",0` for a word space. Blank lines
+    /// and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line has no number or the table is invalid (see
+    /// [`NomenclatorCipher::new`]).
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let lines: Vec<(usize, &str)> = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .collect();
+        let delimiter = match lines.first() {
+            Some((_, line)) if line.contains('\t') => '\t',
+            _ => ',',
+        };
+        let entries = lines
+            .into_iter()
+            .map(|(number, line)| {
+                let mut fields = split_record(line, delimiter).into_iter();
+                let token = fields.next().unwrap_or_default();
+                let codes: Vec<String> = fields.filter(|code| !code.is_empty()).collect();
+                if codes.is_empty() {
+                    return Err(format!("Line {}: expected a token and numbers", number + 1));
+                }
+                Ok((token, codes))
+            })
+            .collect::<Result<_, _>>()?;
+        NomenclatorCipher::new(entries)
+    }
+
+    /// Returns the number of tokens, not counting nulls.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the table has no tokens.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Replaces the longest token at every position with its next number.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let text: Vec<char> = plaintext.chars().flat_map(char::to_lowercase).collect();
+        let tokens: Vec<Vec<char>> = self
+            .entries
+            .iter()
+            .map(|(token, _)| token.chars().flat_map(char::to_lowercase).collect())
+            .collect();
+        let mut used = vec![0; self.entries.len()];
+        let mut codes: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < text.len() {
+            match tokens.iter().position(|token| text[i..].starts_with(token)) {
+                Some(entry) => {
+                    let homophones = &self.entries[entry].1;
+                    codes.push(&homophones[used[entry] % homophones.len()]);
+                    used[entry] += 1;
+                    i += tokens[entry].len();
+                }
+                None => {
+                    if text[i].is_alphanumeric() || text[i] == UNKNOWN {
+                        codes.push("?");
+                    }
+                    i += 1;
+                }
+            }
+        }
+        codes.join(" ")
+    }
+
+    /// Replaces every number with its token.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let mut plaintext = String::new();
+        let mut rest = ciphertext;
+        while let Some(c) = rest.chars().next() {
+            if c.is_ascii_digit() {
+                let end = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let code = &rest[..end];
+                match self
+                    .entries
+                    .iter()
+                    .find(|(_, codes)| codes.iter().any(|c| c == code))
+                {
+                    Some((token, _)) => plaintext.push_str(token),
+                    None if self.nulls.iter().any(|null| null == code) => {}
+                    None => plaintext.push(UNKNOWN),
+                }
+                rest = &rest[end..];
+            } else {
+                if c == UNKNOWN {
+                    plaintext.push(UNKNOWN);
+                }
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+        plaintext
+    }
+}
+
+impl Cipher for NomenclatorCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+// The saved form of a nomenclator: the numbers of each token, nulls under the empty token.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+struct NomenclatorTable(std::collections::BTreeMap<String, NomenclatorCodes>);
+
+// One number or a list of homophones.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum NomenclatorCodes {
+    One(NomenclatorCode),
+    Homophones(Vec<NomenclatorCode>),
+}
+
+// A number written as a number or as a string of digits.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum NomenclatorCode {
+    Number(u64),
+    Digits(String),
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<NomenclatorTable> for NomenclatorCipher {
+    type Error = String;
+
+    fn try_from(table: NomenclatorTable) -> Result<Self, String> {
+        let code = |code: NomenclatorCode| match code {
+            NomenclatorCode::Number(number) => number.to_string(),
+            NomenclatorCode::Digits(digits) => digits,
+        };
+        let entries = table
+            .0
+            .into_iter()
+            .map(|(token, codes)| {
+                let codes = match codes {
+                    NomenclatorCodes::One(one) => vec![code(one)],
+                    NomenclatorCodes::Homophones(homophones) => {
+                        homophones.into_iter().map(code).collect()
+                    }
+                };
+                (token, codes)
+            })
+            .collect();
+        NomenclatorCipher::new(entries)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<NomenclatorCipher> for NomenclatorTable {
+    fn from(nomenclator: NomenclatorCipher) -> Self {
+        let codes = |mut codes: Vec<String>| match codes.len() {
+            1 => NomenclatorCodes::One(NomenclatorCode::Digits(codes.remove(0))),
+            _ => NomenclatorCodes::Homophones(
+                codes.into_iter().map(NomenclatorCode::Digits).collect(),
+            ),
+        };
+        let mut table: std::collections::BTreeMap<String, NomenclatorCodes> = nomenclator
+            .entries
+            .into_iter()
+            .map(|(token, numbers)| (token, codes(numbers)))
+            .collect();
+        if !nomenclator.nulls.is_empty() {
+            table.insert(String::new(), codes(nomenclator.nulls));
+        }
+        NomenclatorTable(table)
+    }
+}
+
+// Splits a CSV record into fields, honouring double-quoted fields.
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c == delimiter && !quoted {
+            fields.push(unquote(&field));
+            field.clear();
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(unquote(&field));
+    fields
+}
+
+// Trims an unquoted field, or returns the verbatim contents of a quoted one.
+fn unquote(field: &str) -> String {
+    let trimmed = field.trim();
+    match trimmed.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => trimmed.to_string(),
+    }
+}
+
+/// How a [`BookCipher`] locates a symbol in the key text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookScheme {
+    /// Whole words, as page, line and word numbers. Pages are separated by form feeds.
+    PageLineWord,
+    /// Single letters, as line, word and letter numbers.
+    LineWordLetter,
+}
+
+/// A book cipher: replaces each word or letter of the message with its coordinates in a key
+/// text shared by both sides, such as an agreed edition of a book.
+///
+/// Coordinates are numbered from 1 and written as `page.line.word` or `line.word.letter`,
+/// depending on the [`BookScheme`]. Blank lines count as lines, and words are matched ignoring
+/// case and surrounding punctuation. When a word or letter occurs more than once in the key text,
+/// repeated uses in the message take successive occurrences, so the ciphertext does not repeat.
+///
+/// Deciphering reads the numbers in threes and ignores whatever separates them, so `1.2.3 4.5.6`,
+/// `1:2:3, 4:5:6`, `[1,2,3] [4,5,6]` and `1-2-3/4-5-6` are all understood.
+///
+/// A word missing from the key text or coordinates pointing outside it are errors, so this cipher
+/// is a [`FallibleCipher`] rather than a [`Cipher`].
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{BookCipher, BookScheme};
+/// let book = "It was the best of times,\nit was the worst of times.\n\u{c}It was the age of wisdom.";
+///
+/// let cipher = BookCipher::new(book);
+/// assert_eq!(cipher.encipher("It was the age").unwrap(), "1.1.1 1.1.2 1.1.3 2.1.4");
+/// assert_eq!(cipher.decipher("[1,1,1] [1,1,2] [1,1,3] [2,1,4]").unwrap(), "it was the age");
+///
+/// let letters = BookCipher::new(book).with_scheme(BookScheme::LineWordLetter);
+/// assert_eq!(letters.encipher("Tea").unwrap(), "1.1.2 1.3.3 1.2.2");
+/// assert_eq!(letters.decipher("1:1:2, 1:3:3, 1:2:2").unwrap(), "tea");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookCipher {
+    text: String,
+    scheme: BookScheme,
+}
+
+impl BookCipher {
+    /// Creates a new book cipher over the key text, locating words by page, line and word.
+    pub fn new(key_text: &str) -> Self {
+        BookCipher {
+            text: key_text.to_string(),
+            scheme: BookScheme::PageLineWord,
+        }
+    }
+
+    /// Sets how symbols are located in the key text.
+    pub fn with_scheme(mut self, scheme: BookScheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+
+    // Lists the coordinates of every symbol in the key text, in reading order.
+    fn index(&self) -> Vec<([usize; 3], String)> {
+        let mut entries = Vec::new();
+        match self.scheme {
+            BookScheme::PageLineWord => {
+                for (page, text) in self.text.split('\u{c}').enumerate() {
+                    for (line, text) in text.lines().enumerate() {
+                        for (word, text) in book_words(text).enumerate() {
+                            entries.push(([page + 1, line + 1, word + 1], text.to_lowercase()));
+                        }
+                    }
+                }
+            }
+            BookScheme::LineWordLetter => {
+                for (line, text) in self.text.lines().enumerate() {
+                    for (word, text) in book_words(text).enumerate() {
+                        let letters = text.chars().filter(|c| c.is_alphanumeric());
+                        for (letter, c) in letters.enumerate() {
+                            entries.push((
+                                [line + 1, word + 1, letter + 1],
+                                c.to_lowercase().collect(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        entries
+    }
+
+    /// Enciphers a given plaintext into space-separated coordinates.
+    ///
+    /// Only the words (or, with [`BookScheme::LineWordLetter`], the letters and digits) of the
+    /// plaintext are enciphered; punctuation is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a word or letter of the plaintext is not in the key text.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        let symbols: Vec<String> = match self.scheme {
+            BookScheme::PageLineWord => book_words(plaintext).map(str::to_lowercase).collect(),
+            BookScheme::LineWordLetter => plaintext
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .map(|c| c.to_lowercase().collect())
+                .collect(),
+        };
+        let index = self.index();
+        let mut uses: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        let mut coordinates = Vec::new();
+        for symbol in &symbols {
+            let occurrences: Vec<&[usize; 3]> = index
+                .iter()
+                .filter(|(_, s)| s == symbol)
+                .map(|(position, _)| position)
+                .collect();
+            if occurrences.is_empty() {
+                return Err(format!("{:?} is not in the key text", symbol));
+            }
+            let used = uses.entry(symbol).or_insert(0);
+            let [a, b, c] = occurrences[*used % occurrences.len()];
+            *used += 1;
+            coordinates.push(format!("{}.{}.{}", a, b, c));
+        }
+        Ok(coordinates.join(" "))
+    }
+
+    /// Deciphers coordinates back into the words or letters of the key text.
+    ///
+    /// Words are joined by spaces and letters are joined without them, all in lowercase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the numbers do not come in threes or a coordinate is outside the key
+    /// text.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let numbers = ciphertext
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse().map_err(|_| format!("Invalid number: {}", n)))
+            .collect::<Result<Vec<usize>, String>>()?;
+        if !numbers.len().is_multiple_of(3) {
+            return Err(format!(
+                "Coordinates have three numbers each, but found {} numbers",
+                numbers.len()
+            ));
+        }
+        let index: std::collections::HashMap<[usize; 3], String> =
+            self.index().into_iter().collect();
+        let symbols = numbers
+            .chunks(3)
+            .map(|c| {
+                index
+                    .get(&[c[0], c[1], c[2]])
+                    .map(String::as_str)
+                    .ok_or_else(|| format!("Nothing at {}.{}.{} in the key text", c[0], c[1], c[2]))
+            })
+            .collect::<Result<Vec<&str>, String>>()?;
+        let separator = match self.scheme {
+            BookScheme::PageLineWord => " ",
+            BookScheme::LineWordLetter => "",
+        };
+        Ok(symbols.join(separator))
+    }
+}
+
+impl FallibleCipher for BookCipher {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+// Splits text into words without their surrounding punctuation, skipping tokens without letters
+// or digits.
+fn book_words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+}
+
+/// A numeric substitution cipher parameterised by the value of each letter.
+///
+/// Every letter is replaced by its number and the numbers are separated by spaces. Words are
+/// separated by `/`, and other characters are kept as tokens of their own. The numbers come from
+/// a mapping function, with presets for alphabet positions (also available as
+/// [`AlphaNumConverter`]), primes, squares and shifted ASCII codes.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::NumericSubstitution;
+/// let primes = NumericSubstitution::primes();
+/// assert_eq!(primes.encipher("Hi there!"), "19 23 / 71 19 11 61 11 !");
+/// assert_eq!(primes.decipher("19 23 / 71 19 11 61 11 !"), "HI THERE!");
+///
+/// let cubes = NumericSubstitution::from_fn(|i| (i as i64 + 1).pow(3)).unwrap();
+/// assert_eq!(cubes.encipher("ABC"), "1 8 27");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericSubstitution {
+    values: [i64; 26],
+}
+
+impl NumericSubstitution {
+    /// Creates a numeric substitution from a function giving the number of the letter with
+    /// alphabet index `i` (A=0 ... Z=25).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two letters get the same number, since the cipher could not be
+    /// deciphered.
+    pub fn from_fn(value: impl Fn(usize) -> i64) -> Result<Self, String> {
+        let mut values = [0; 26];
+        for (i, slot) in values.iter_mut().enumerate() {
+            *slot = value(i);
+        }
+        if let Some(i) = (1..26).find(|&i| values[..i].contains(&values[i])) {
+            return Err(format!(
+                "Letter {} has the same number {} as an earlier letter",
+                (b'A' + i as u8) as char,
+                values[i]
+            ));
+        }
+        Ok(NumericSubstitution { values })
+    }
+
+    /// Numbers the letters by their position in the alphabet, A=1 ... Z=26.
+    pub fn positions() -> Self {
+        Self::from_fn(|i| i as i64 + 1).expect("positions are distinct")
+    }
+
+    /// Numbers the letters with the first 26 primes, A=2, B=3, C=5 ... Z=101.
+    pub fn primes() -> Self {
+        let primes: Vec<i64> = (2..)
+            .filter(|&n: &i64| (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0))
+            .take(26)
+            .collect();
+        Self::from_fn(|i| primes[i]).expect("primes are distinct")
+    }
+
+    /// Numbers the letters with the squares of their positions, A=1, B=4 ... Z=676.
+    pub fn squares() -> Self {
+        Self::from_fn(|i| (i as i64 + 1).pow(2)).expect("squares are distinct")
+    }
+
+    /// Numbers the letters with their uppercase ASCII codes plus `offset`, A=65+offset.
+    pub fn ascii(offset: i64) -> Self {
+        Self::from_fn(|i| b'A' as i64 + i as i64 + offset).expect("ASCII codes are distinct")
+    }
+
+    /// Enciphers a given plaintext into numbers.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        plaintext
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .map(|c| {
+                        if c.is_ascii_alphabetic() {
+                            self.values[(c.to_ascii_uppercase() as u8 - b'A') as usize].to_string()
+                        } else {
+                            c.to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Finds the first number of a ciphertext that no letter has, which
+    /// [`decipher`](Self::decipher) writes as `?`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::NumericSubstitution;
+    /// let unknown = NumericSubstitution::primes().unknown_token("2 3 / 4 5").unwrap();
+    /// assert_eq!((unknown.token.as_str(), unknown.position), ("4", 6));
+    /// ```
+    pub fn unknown_token(&self, ciphertext: &str) -> Option<UnknownToken> {
+        first_unknown_token(ciphertext, char::is_whitespace, |token| {
+            token
+                .parse::<i64>()
+                .map_or(true, |number| self.values.contains(&number))
+        })
+    }
+
+    /// Deciphers a given ciphertext into uppercase letters, writing `?` for unknown numbers.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        ciphertext
+            .split_whitespace()
+            .map(|token| match (token, token.parse::<i64>()) {
+                ("/", _) => " ".to_string(),
+                (_, Ok(number)) => match self.values.iter().position(|&v| v == number) {
+                    Some(i) => ((b'A' + i as u8) as char).to_string(),
+                    None => "?".to_string(),
+                },
+                (token, Err(_)) => token.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Cipher for NumericSubstitution {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A code that writes every character as a token, such as a playing card, a domino tile or a
+/// signal flag.
+///
+/// The tokens are separated by spaces and words by `/`, and characters without a token are
+/// kept as tokens of their own. Letters are looked up regardless of case, and so are tokens
+/// when none matches exactly. Decoding reads a token with a letter or digit that is not in the
+/// code as [`UNKNOWN`], and keeps other tokens, such as punctuation, as they are.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::TokenCode;
+/// let cards = TokenCode::playing_cards();
+/// assert_eq!(cards.encipher("Go 4!"), "7♣ 2♦ / 4♥ !");
+/// assert_eq!(cards.decipher("7♣ 2♦ / 4♥ !"), "GO 4!");
+///
+/// let dominoes = TokenCode::dominoes();
+/// assert_eq!(dominoes.encipher("Key"), "1|1 0|4 2|7");
+///
+/// let flags = TokenCode::signal_flags();
+/// assert_eq!(flags.encipher("SOS 1"), "Sierra Oscar Sierra / Unaone");
+/// assert_eq!(flags.decipher("mike alfa yankee delta alfa yankee"), "MAYDAY");
+///
+/// let code = TokenCode::from_csv("S,🌞\nM,🌙").unwrap();
+/// assert_eq!(code.encipher("sms"), "🌞 🌙 🌞");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenCode {
+    entries: Vec<(char, String)>,
+}
+
+impl TokenCode {
+    /// Creates a code from `(character, token)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a character or token appears twice, or a token is empty, `/` or
+    /// contains whitespace.
+    pub fn new(entries: Vec<(char, String)>) -> Result<Self, String> {
+        for (i, (c, token)) in entries.iter().enumerate() {
+            if token.is_empty() || token == "/" || token.contains(char::is_whitespace) {
+                return Err(format!(
+                    "The token of {:?} must not be empty, / or contain whitespace",
+                    c
+                ));
+            }
+            if entries[..i].iter().any(|(other, _)| other == c) {
+                return Err(format!("Duplicate character: {:?}", c));
+            }
+            if entries[..i].iter().any(|(_, other)| other == token) {
+                return Err(format!("Duplicate token: {}", token));
+            }
+        }
+        Ok(TokenCode { entries })
+    }
+
+    /// Loads a code from CSV or TSV text with one `character,token` pair per line, in the
+    /// format of [`TableCipher::from_csv`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be read, a source is not a single character or the
+    /// code is invalid (see [`TokenCode::new`]).
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let entries = TableCipher::from_csv(text)?
+            .entries
+            .into_iter()
+            .map(|(source, token)| {
+                let mut chars = source.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok((c, token)),
+                    _ => Err(format!("Expected a single character, found {:?}", source)),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        TokenCode::new(entries)
+    }
+
+    /// The playing cards: `A` to `M` are the clubs from the ace to the king, `N` to `Z` the
+    /// diamonds, and the digits `1` to `9` the hearts from the ace to the nine, with `0` the ten.
+    pub fn playing_cards() -> Self {
+        let ranks = [
+            "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+        ];
+        let cards = ['♣', '♦']
+            .iter()
+            .flat_map(|suit| ranks.iter().map(move |rank| format!("{}{}", rank, suit)));
+        let hearts = ranks[..10].iter().map(|rank| format!("{}♥", rank));
+        let characters = ('A'..='Z').chain('1'..='9').chain(['0']);
+        TokenCode::new(characters.zip(cards.chain(hearts)).collect()).expect("valid cards")
+    }
+
+    /// The tiles of a double-nine domino set in order, `0|0`, `0|1` ... `0|9`, `1|1` ...,
+    /// for `A` to `Z` and then the digits `0` to `9`.
+    pub fn dominoes() -> Self {
+        let tiles = (0..=9).flat_map(|low| (low..=9).map(move |high| format!("{}|{}", low, high)));
+        let characters = ('A'..='Z').chain('0'..='9');
+        TokenCode::new(characters.zip(tiles).collect()).expect("valid tiles")
+    }
+
+    /// The color bands of resistors for the digits: `0` black, `1` brown, `2` red, `3` orange,
+    /// `4` yellow, `5` green, `6` blue, `7` violet, `8` grey and `9` white.
+    pub fn resistor_colors() -> Self {
+        let colors = [
+            "black", "brown", "red", "orange", "yellow", "green", "blue", "violet", "grey", "white",
+        ];
+        TokenCode::new(('0'..='9').zip(colors.map(String::from)).collect()).expect("valid colors")
+    }
+
+    /// The flags of the International Code of Signals by name: `Alfa` to `Zulu` for the letters
+    /// and the numeral pennants `Nadazero` to `Novenine` for the digits.
+    pub fn signal_flags() -> Self {
+        let names = [
+            "Alfa",
+            "Bravo",
+            "Charlie",
+            "Delta",
+            "Echo",
+            "Foxtrot",
+            "Golf",
+            "Hotel",
+            "India",
+            "Juliett",
+            "Kilo",
+            "Lima",
+            "Mike",
+            "November",
+            "Oscar",
+            "Papa",
+            "Quebec",
+            "Romeo",
+            "Sierra",
+            "Tango",
+            "Uniform",
+            "Victor",
+            "Whiskey",
+            "X-ray",
+            "Yankee",
+            "Zulu",
+            "Nadazero",
+            "Unaone",
+            "Bissotwo",
+            "Terrathree",
+            "Kartefour",
+            "Pantafive",
+            "Soxisix",
+            "Setteseven",
+            "Oktoeight",
+            "Novenine",
+        ];
+        let characters = ('A'..='Z').chain('0'..='9');
+        TokenCode::new(characters.zip(names.map(String::from)).collect()).expect("valid flags")
+    }
+
+    /// The flags of the letters as Unicode regional indicators, `🇦` to `🇿`, and the digits as
+    /// keycaps, `0️⃣` to `9️⃣`. The tokens are kept apart by spaces, since two regional
+    /// indicators in a row are shown as the flag of a country.
+    pub fn flag_emoji() -> Self {
+        let letters = ('A'..='Z').map(|c| {
+            let indicator = char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32));
+            (c, indicator.unwrap_or(c).to_string())
+        });
+        let digits = ('0'..='9').map(|c| (c, format!("{}\u{fe0f}\u{20e3}", c)));
+        TokenCode::new(letters.chain(digits).collect()).expect("valid flags")
+    }
+
+    /// Returns the `(character, token)` pairs of the code.
+    pub fn entries(&self) -> &[(char, String)] {
+        &self.entries
+    }
+
+    fn token(&self, c: char) -> Option<&str> {
+        [c, c.to_ascii_uppercase()].iter().find_map(|&c| {
+            self.entries
+                .iter()
+                .find(|(other, _)| *other == c)
+                .map(|(_, token)| token.as_str())
+        })
+    }
+
+    fn character(&self, token: &str) -> Option<char> {
+        let exact = self.entries.iter().find(|(_, other)| other == token);
+        exact
+            .or_else(|| {
+                let token = token.to_lowercase();
+                self.entries
+                    .iter()
+                    .find(|(_, other)| other.to_lowercase() == token)
+            })
+            .map(|&(c, _)| c)
+    }
+
+    /// Enciphers a given plaintext into tokens.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        plaintext
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .map(|c| match self.token(c) {
+                        Some(token) => token.to_string(),
+                        None => c.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Finds the first token of a ciphertext with a letter or digit that is not in the code,
+    /// which [`decipher`](Self::decipher) writes as `?`.
+    pub fn unknown_token(&self, ciphertext: &str) -> Option<UnknownToken> {
+        first_unknown_token(ciphertext, char::is_whitespace, |token| {
+            !token.chars().any(char::is_alphanumeric) || self.character(token).is_some()
+        })
+    }
+
+    /// Deciphers a given ciphertext, writing `?` for unknown tokens.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        ciphertext
+            .split_whitespace()
+            .map(|token| match self.character(token) {
+                _ if token == "/" => " ".to_string(),
+                Some(c) => c.to_string(),
+                None if token.chars().any(char::is_alphanumeric) => UNKNOWN.to_string(),
+                None => token.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl Cipher for TokenCode {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The glyphs of [`PigpenCipher::new`], `A` to `Z`.
+///
+/// The letters of the two grids are drawn as the sides of their cell with Unicode corners,
+/// squares and brackets, and those of the X as wedges; the second grid and X add a combining dot
+/// (U+0307).
+pub const PIGPEN_GLYPHS: [&str; 26] = [
+    "⌟",
+    "⊔",
+    "⌞",
+    "⊐",
+    "□",
+    "⊏",
+    "⌝",
+    "⊓",
+    "⌜",
+    "⌟\u{307}",
+    "⊔\u{307}",
+    "⌞\u{307}",
+    "⊐\u{307}",
+    "□\u{307}",
+    "⊏\u{307}",
+    "⌝\u{307}",
+    "⊓\u{307}",
+    "⌜\u{307}",
+    "∨",
+    "›",
+    "‹",
+    "∧",
+    "∨\u{307}",
+    "›\u{307}",
+    "‹\u{307}",
+    "∧\u{307}",
+];
+
+/// The pigpen (masonic) cipher.
+///
+/// The letters `A` to `I` fill a noughts-and-crosses grid and `J` to `R` a second grid with a
+/// dot in every cell; `S` to `V` fill the four wedges of an X and `W` to `Z` a dotted X. Each
+/// letter is written as the outline of its cell, with the dot if it has one.
+///
+/// Unicode has no pigpen symbols, so the outlines are drawn with look-alike characters,
+/// [`PIGPEN_GLYPHS`] by default or any other set given to [`PigpenCipher::with_glyphs`]. For
+/// places without those characters, [`PigpenCipher::art`] draws the symbols as three lines of
+/// ASCII art, which [`PigpenCipher::from_art`] reads back. Letters are enciphered regardless
+/// of case and deciphered in uppercase; other characters pass through.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::PigpenCipher;
+/// let pigpen = PigpenCipher::new();
+/// assert_eq!(pigpen.encipher("Abe"), "⌟⊔□");
+/// assert_eq!(pigpen.decipher("⌟⊔□ ∧\u{307}"), "ABE Z");
+///
+/// let art = PigpenCipher::art("Ox");
+/// assert_eq!(art, "+-- \\\n|.  .>\n+-- /");
+/// assert_eq!(PigpenCipher::from_art(&art), "OX");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PigpenCipher {
+    glyphs: Vec<String>,
+}
+
+impl Default for PigpenCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PigpenCipher {
+    /// Creates a new instance of the pigpen cipher with the [`PIGPEN_GLYPHS`].
+    pub fn new() -> Self {
+        PigpenCipher {
+            glyphs: PIGPEN_GLYPHS
+                .iter()
+                .map(|glyph| glyph.to_string())
+                .collect(),
+        }
+    }
+
+    /// Writes the letters `A` to `Z` with the given glyphs instead.
+    ///
+    /// Deciphering reads the longest glyph that fits, so a glyph may extend another, as the
+    /// dotted glyphs do. Glyphs that can be written as a sequence of other glyphs decipher
+    /// ambiguously.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are 26 different glyphs, none empty or with whitespace.
+    pub fn with_glyphs(glyphs: &[&str]) -> Result<Self, String> {
+        if glyphs.len() != 26 {
+            return Err(format!(
+                "Expected 26 glyphs, one per letter, found {}",
+                glyphs.len()
+            ));
+        }
+        for (i, glyph) in glyphs.iter().enumerate() {
+            if glyph.is_empty() || glyph.contains(char::is_whitespace) {
+                return Err(format!(
+                    "The glyph of {} must not be empty or contain whitespace",
+                    (b'A' + i as u8) as char
+                ));
+            }
+            if glyphs[..i].contains(glyph) {
+                return Err(format!("Glyph {:?} is used for two letters", glyph));
+            }
+        }
+        Ok(PigpenCipher {
+            glyphs: glyphs.iter().map(|glyph| glyph.to_string()).collect(),
+        })
+    }
+
+    /// Returns the glyphs of the letters `A` to `Z`.
+    pub fn glyphs(&self) -> &[String] {
+        &self.glyphs
+    }
+
+    /// Enciphers a given plaintext into glyphs.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        plaintext
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    self.glyphs[(c.to_ascii_uppercase() as u8 - b'A') as usize].clone()
+                } else {
+                    c.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Deciphers a given ciphertext, reading the longest glyph at every position.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let mut plaintext = String::new();
+        let mut rest = ciphertext;
+        while let Some(c) = rest.chars().next() {
+            let letter = (0..26)
+                .filter(|&i| rest.starts_with(self.glyphs[i].as_str()))
+                .max_by_key(|&i| self.glyphs[i].len());
+            match letter {
+                Some(i) => {
+                    plaintext.push((b'A' + i as u8) as char);
+                    rest = &rest[self.glyphs[i].len()..];
+                }
+                None => {
+                    plaintext.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        plaintext
+    }
+
+    /// Draws a text as pigpen symbols in ASCII art, three lines high and three characters wide
+    /// per symbol, with a space between symbols.
+    ///
+    /// Characters other than letters are drawn in the middle of their block. The lines of the
+    /// text are drawn one below the other, separated by an empty line.
+    pub fn art(plaintext: &str) -> String {
+        plaintext
+            .lines()
+            .map(|line| {
+                let blocks: Vec<[String; 3]> = line.chars().map(pigpen_art).collect();
+                (0..3)
+                    .map(|row| {
+                        blocks
+                            .iter()
+                            .map(|block| block[row].as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .trim_end()
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Reads pigpen symbols drawn by [`art`](Self::art) back as uppercase letters.
+    ///
+    /// Blocks that are neither a symbol nor a single character are read as [`UNKNOWN`].
+    pub fn from_art(art: &str) -> String {
+        let rows: Vec<&str> = art.lines().collect();
+        rows.chunks(4)
+            .map(|rows| {
+                let rows: Vec<Vec<char>> = rows
+                    .iter()
+                    .take(3)
+                    .map(|row| row.chars().collect())
+                    .collect();
+                let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+                let cell = |row: usize, col: usize| {
+                    rows.get(row)
+                        .and_then(|row| row.get(col))
+                        .copied()
+                        .unwrap_or(' ')
+                };
+                (0..width.div_ceil(4))
+                    .map(|block| {
+                        let block: [String; 3] = std::array::from_fn(|row| {
+                            (0..3).map(|col| cell(row, block * 4 + col)).collect()
+                        });
+                        if let Some(letter) = ('A'..='Z').find(|&c| pigpen_art(c) == block) {
+                            return letter;
+                        }
+                        let marks: Vec<char> = block
+                            .iter()
+                            .flat_map(|row| row.chars())
+                            .filter(|&c| c != ' ')
+                            .collect();
+                        match marks[..] {
+                            [] => ' ',
+                            [c] if block[1].chars().nth(1) == Some(c) => c,
+                            _ => UNKNOWN,
+                        }
+                    })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Draws a character as a pigpen symbol of three rows of three characters.
+fn pigpen_art(c: char) -> [String; 3] {
+    if !c.is_ascii_alphabetic() {
+        let blank = "   ".to_string();
+        return [blank.clone(), format!(" {} ", c), blank];
+    }
+    let index = (c.to_ascii_uppercase() as u8 - b'A') as usize;
+    let dot = if (9..18).contains(&index) || index >= 22 {
+        '.'
+    } else {
+        ' '
+    };
+    let rows = if index < 18 {
+        // The sides of the cell, from its row and column in the grid
+        let (row, col) = (index % 9 / 3, index % 9 % 3);
+        let (top, bottom, left, right) = (row > 0, row < 2, col > 0, col < 2);
+        let corner = |horizontal: bool, vertical: bool| match (horizontal, vertical) {
+            (true, true) => '+',
+            (false, true) => '|',
+            (true, false) => '-',
+            (false, false) => ' ',
+        };
+        let edge = |horizontal: bool| if horizontal { '-' } else { ' ' };
+        let side = |vertical: bool| if vertical { '|' } else { ' ' };
+        [
+            [corner(top, left), edge(top), corner(top, right)],
+            [side(left), dot, side(right)],
+            [corner(bottom, left), edge(bottom), corner(bottom, right)],
+        ]
+    } else {
+        match (index - 18) % 4 {
+            0 => [['\\', dot, '/'], [' ', 'V', ' '], [' ', ' ', ' ']],
+            1 => [['\\', ' ', ' '], [dot, '>', ' '], ['/', ' ', ' ']],
+            2 => [[' ', ' ', '/'], [' ', '<', dot], [' ', ' ', '\\']],
+            _ => [[' ', ' ', ' '], [' ', '^', ' '], ['/', dot, '\\']],
+        }
+    };
+    rows.map(|row| row.iter().collect())
+}
+
+impl Cipher for PigpenCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The alphabet of a [`BaconCipher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaconAlphabet {
+    /// Bacon's original 24 letters, with I/J and U/V sharing a code.
+    Historic24,
+    /// A distinct code for each of the 26 letters.
+    Full26,
+}
+
+impl BaconAlphabet {
+    // Returns the letters in code order.
+    fn letters(&self) -> &'static str {
+        match self {
+            BaconAlphabet::Historic24 => "ABCDEFGHIKLMNOPQRSTUWXYZ",
+            BaconAlphabet::Full26 => "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+        }
+    }
+
+    // Returns the code number of a letter, if the alphabet has it.
+    fn index(&self, c: char) -> Option<usize> {
+        let c = match (self, c.to_ascii_uppercase()) {
+            (BaconAlphabet::Historic24, 'J') => 'I',
+            (BaconAlphabet::Historic24, 'V') => 'U',
+            (_, c) => c,
+        };
+        self.letters().chars().position(|l| l == c)
+    }
+}
+
+/// Bacon's biliteral cipher.
+///
+/// Every letter becomes a group of five symbols, `A` and `B` by default, spelling its position
+/// in the alphabet in binary: `AAAAA` for A, `AAAAB` for B and so on. Groups are separated by
+/// spaces and characters outside the alphabet are dropped.
+///
+/// The groups can also be [hidden](Self::hide) in the capitalization of an innocent cover text,
+/// lowercase letters standing for `A` and uppercase letters for `B`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{BaconAlphabet, BaconCipher};
+/// let bacon = BaconCipher::new(BaconAlphabet::Full26);
+/// assert_eq!(bacon.encipher("Hi"), "AABBB ABAAA");
+/// assert_eq!(bacon.decipher("AABBB ABAAA"), "HI");
+///
+/// let hidden = bacon.hide("Hi", "the weather is fine").unwrap();
+/// assert_eq!(hidden, "thE WEaTher is fine");
+/// assert_eq!(bacon.reveal(&hidden), "HI");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaconCipher {
+    alphabet: BaconAlphabet,
+    symbols: (char, char),
+}
+
+impl BaconCipher {
+    /// Creates a new instance of the cipher writing the groups with `A` and `B`.
+    pub fn new(alphabet: BaconAlphabet) -> Self {
+        BaconCipher {
+            alphabet,
+            symbols: ('A', 'B'),
+        }
+    }
+
+    /// Sets the two symbols the groups are written with, e.g. `0` and `1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the symbols are the same.
+    pub fn with_symbols(mut self, a: char, b: char) -> Result<Self, String> {
+        if a == b {
+            return Err(format!("Symbols must differ, found {:?} twice", a));
+        }
+        self.symbols = (a, b);
+        Ok(self)
+    }
+
+    // Returns the five bits of every letter of the text, `true` standing for B.
+    fn bits(&self, text: &str) -> Vec<bool> {
+        text.chars()
+            .filter_map(|c| self.alphabet.index(c))
+            .flat_map(|index| (0..5).rev().map(move |bit| index >> bit & 1 == 1))
+            .collect()
+    }
+
+    // Reads the letters from groups of five bits, dropping an incomplete last group.
+    fn letters(&self, bits: &[bool]) -> String {
+        let letters: Vec<char> = self.alphabet.letters().chars().collect();
+        bits.chunks_exact(5)
+            .filter_map(|group| {
+                let index = group.iter().fold(0, |index, &b| index * 2 + b as usize);
+                letters.get(index).copied()
+            })
+            .collect()
+    }
+
+    /// Enciphers a given plaintext into space separated groups of five symbols.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let (a, b) = self.symbols;
+        self.bits(plaintext)
+            .chunks(5)
+            .map(|group| group.iter().map(|&bit| if bit { b } else { a }).collect())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Deciphers a given ciphertext into uppercase letters, ignoring anything but the symbols.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let (a, b) = self.symbols;
+        let matches = |c: char, symbol: char| c.to_lowercase().eq(symbol.to_lowercase());
+        let bits: Vec<bool> = ciphertext
+            .chars()
+            .filter_map(|c| {
+                if matches(c, a) {
+                    Some(false)
+                } else if matches(c, b) {
+                    Some(true)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.letters(&bits)
+    }
+
+    /// Hides the plaintext in the capitalization of the cover text.
+    ///
+    /// Each letter of the cover carries one symbol, lowercase for `A` and uppercase for `B`;
+    /// the cover letters left over are written in lowercase.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cover text has fewer than five letters per plaintext letter.
+    pub fn hide(&self, plaintext: &str, cover: &str) -> Result<String, String> {
+        let bits = self.bits(plaintext);
+        let available = cover.chars().filter(|c| c.is_alphabetic()).count();
+        if available < bits.len() {
+            return Err(format!(
+                "Cover text needs {} letters, found {}",
+                bits.len(),
+                available
+            ));
+        }
+        let mut bits = bits.into_iter();
+        Ok(cover
+            .chars()
+            .flat_map(|c| {
+                let upper = c.is_alphabetic() && bits.next().unwrap_or(false);
+                let converted: Vec<char> = if !c.is_alphabetic() {
+                    vec![c]
+                } else if upper {
+                    c.to_uppercase().collect()
+                } else {
+                    c.to_lowercase().collect()
+                };
+                converted
+            })
+            .collect())
+    }
+
+    /// Reveals a plaintext hidden with [`hide`](Self::hide).
+    ///
+    /// The lowercase cover letters after the message are padding, so reading stops at the end
+    /// of the group holding the last uppercase letter. A plaintext ending in the all-`A` letter
+    /// loses it, as it can't be told apart from the padding.
+    pub fn reveal(&self, text: &str) -> String {
+        let mut bits: Vec<bool> = text
+            .chars()
+            .filter(|c| c.is_alphabetic())
+            .map(|c| c.is_uppercase())
+            .collect();
+        let end = bits
+            .iter()
+            .rposition(|&bit| bit)
+            .map_or(0, |last| last / 5 * 5 + 5);
+        bits.truncate(end);
+        self.letters(&bits)
+    }
+}
+
+impl Cipher for BaconCipher {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// An alphabetic to numerical converter.
+///
+/// This is the [`NumericSubstitution::positions`] preset, numbering the letters A=1 ... Z=26,
+/// under the name earlier versions used.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::AlphaNumConverter;
+/// let converter = AlphaNumConverter::new();
+/// assert_eq!(converter.alpha_to_num("Hi, Bob"), "8 9 , / 2 15 2");
+/// assert_eq!(converter.num_to_alpha("8 9 , / 2 15 2"), "HI, BOB");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaNumConverter(NumericSubstitution);
+
+impl Default for AlphaNumConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlphaNumConverter {
+    /// Creates a new instance of the alphabetic to numerical converter.
+    pub fn new() -> Self {
+        AlphaNumConverter(NumericSubstitution::positions())
+    }
+
+    /// Converts the letters of a text to their positions in the alphabet.
+    pub fn alpha_to_num(&self, text: &str) -> String {
+        self.0.encipher(text)
+    }
+
+    /// Converts the numbers of a text back to uppercase letters.
+    pub fn num_to_alpha(&self, cipher_text: &str) -> String {
+        self.0.decipher(cipher_text)
+    }
+}
+
+impl Cipher for AlphaNumConverter {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.alpha_to_num(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.num_to_alpha(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_cipher_trait_objects() {
+        let ciphers: Vec<Box<dyn Cipher>> = vec![
+            Box::new(Rot13Cipher::new()),
+            Box::new(CaesarCipher::new(3)),
+            Box::new(VigenereCipher::new("LEMON")),
+            Box::new(AtbashCipher::new()),
+        ];
+        let plaintext = "Hello, World!";
+        for cipher in &ciphers {
+            assert_eq!(cipher.decipher(&cipher.encipher(plaintext)), plaintext);
+        }
+
+        let morse: Box<dyn Cipher> = Box::new(MorseCode::new());
+        assert_eq!(morse.encipher("SOS"), "... --- ...");
+        let converter: Box<dyn Cipher> = Box::new(AlphaNumConverter::new());
+        assert_eq!(converter.decipher(&converter.encipher("abc")), "ABC");
+    }
+
+    #[test]
+    fn test_rot13_encipher() {
+        let rot13 = Rot13Cipher::new();
+        let input = "Hello, World!";
+        let expected = "Uryyb, Jbeyq!";
+        assert_eq!(rot13.encipher(input), expected);
+    }
+
+    #[test]
+    fn test_rot13_decipher() {
+        let rot13 = Rot13Cipher::new();
+        let input = "Uryyb, Jbeyq!";
+        let expected = "Hello, World!";
+        assert_eq!(rot13.decipher(input), expected);
+    }
+
+    #[test]
+    fn test_rot_variants() {
+        let text = "Agent 007, meet at 22:15 ~ Café!";
+        assert_eq!(
+            Rot5Cipher::new().encipher(text),
+            "Agent 552, meet at 77:60 ~ Café!"
+        );
+        assert_eq!(
+            Rot18Cipher::new().encipher(text),
+            "Ntrag 552, zrrg ng 77:60 ~ Pnsé!"
+        );
+        assert_eq!(
+            Rot47Cipher::new().encipher(text),
+            "p86?E __f[ >66E 2E aai`d O r27éP"
+        );
+        for cipher in [
+            Box::new(Rot5Cipher::new()) as Box<dyn Cipher>,
+            Box::new(Rot18Cipher::new()),
+            Box::new(Rot47Cipher::new()),
+        ] {
+            assert_eq!(cipher.decipher(&cipher.encipher(text)), text);
+        }
+        assert_eq!(Rot47Cipher::new().encipher("!~"), "PO");
+        let digits = Cipher::mapping_table(&Rot5Cipher::new()).unwrap();
+        assert_eq!(digits.len(), 10);
+        assert_eq!(digits[0], ('0', '5'));
+        assert_eq!(digits[9], ('9', '4'));
+    }
+
+    #[test]
+    fn test_rot_n() {
+        let latin = RotN::reciprocal("abcdefghijklmnopqrstuvwxyz").unwrap();
+        assert_eq!(
+            latin.encipher("hello, world!"),
+            Rot13Cipher::new().encipher("hello, world!")
+        );
+        // Both cases are letters of their own, so they rotate into each other
+        let base62 = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        let mixed = RotN::new(5, base62).unwrap();
+        assert_eq!(mixed.encipher("Zz9 Az!"), "e4E F4!");
+        assert_eq!(mixed.decipher("e4E F4!"), "Zz9 Az!");
+        assert_eq!(RotN::reciprocal(base62).unwrap().shift(), 31);
+        let symbols = RotN::new(9, "♠♥♦♣").unwrap();
+        assert_eq!(symbols.shift(), 1);
+        assert_eq!(symbols.encipher("A♠♥♣"), "A♥♦♠");
+        assert_eq!(symbols.decipher("A♥♦♠"), "A♠♥♣");
+        // An alphabet in capitals shifts lowercase letters too
+        let german =
+            RotN::with_alphabet(1, Alphabet::new("ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÜ").unwrap());
+        assert_eq!(german.encipher("Zürich"), "Äasjdi");
+        assert_eq!(german.decipher("Äasjdi"), "Zürich");
+        assert!(RotN::new(1, "").is_err());
+        assert!(RotN::new(1, "abca").is_err());
+    }
+
+    #[test]
+    fn test_filtered() {
+        let cipher = VigenereCipher::new("LEMON").with_non_alpha(NonAlphaPolicy::Error);
+        assert_eq!(cipher.encipher("Attack"), Ok("Lxfopv".to_string()));
+        // The rejected plaintext is an error, not an empty ciphertext
+        assert_eq!(
+            cipher.encipher("Attack at dawn"),
+            NonAlphaPolicy::Error.apply("Attack at dawn")
+        );
+        let stage: &dyn FallibleCipher = &cipher;
+        assert!(stage.try_encipher("Attack at dawn").is_err());
+        assert_eq!(
+            stage.try_decipher("Lxfopv ef rnhr").unwrap(),
+            "Attack at dawn"
+        );
+
+        // Fallible ciphers take a policy too, and keep their own errors
+        let running =
+            RunningKeyCipher::new("Call me Ishmael").with_non_alpha(NonAlphaPolicy::Strip);
+        assert!(running.encipher("Attack at dawn!").is_ok());
+        assert!(running.encipher("Attack at dawn, please").is_err());
+
+        let pipeline = Pipeline::new()
+            .then(CaesarCipher::new(3))
+            .then_fallible(CaesarCipher::new(1).with_non_alpha(NonAlphaPolicy::Error));
+        assert_eq!(pipeline.encipher("Hello").unwrap(), "Lipps");
+        assert!(pipeline.encipher("Hello world").is_err());
+    }
+
+    #[test]
+    fn test_ciphers_with_alphabet() {
+        let caesar = CaesarCipher::new(3).with_alphabet(Alphabet::greek());
+        // Latin letters are outside the Greek alphabet
+        assert_eq!(caesar.encipher("Ωμέγα, ABC"), "Γοέζδ, ABC");
+        assert_eq!(caesar.elementary(), None);
+        assert_eq!(caesar.mapping_table().unwrap()[0], ('Α', 'Δ'));
+        assert_eq!(
+            CaesarCipher::new(3)
+                .with_alphabet(Alphabet::latin())
+                .elementary(),
+            Some(Elementary::Shift(3))
+        );
+
+        let atbash = AtbashCipher::new().with_alphabet(Alphabet::russian());
+        assert_eq!(atbash.encipher("Ая"), "Яа");
+        assert_eq!(atbash.elementary(), None);
+        assert_eq!(AtbashCipher::new().elementary(), Some(Elementary::Atbash));
+
+        let substitution =
+            SubstitutionCipher::from_alphabet_in(Alphabet::new("♠♥♦♣").unwrap(), "♣♦♥♠").unwrap();
+        assert_eq!(substitution.encipher("♠♠♥x"), "♣♣♦x");
+        assert!(SubstitutionCipher::from_alphabet_in(Alphabet::greek(), "ΑΒΓ").is_err());
+        assert_eq!(
+            SubstitutionCipher::from_alphabet("AACDEFGHIJKLMNOPQRSTUVWXYZ").err(),
+            Some("Alphabet repeats the letter A".to_string())
+        );
+
+        let vigenere = VigenereCipher::new("ΚΛΕΙΔΙ")
+            .with_alphabet(Alphabet::greek())
+            .unwrap();
+        assert_eq!(
+            vigenere.decipher(&vigenere.encipher("Καλημέρα")),
+            "Καλημέρα"
+        );
+        // Latin K, E and Y are not Greek letters, however alike they look
+        assert_eq!(
+            VigenereCipher::new("KEY")
+                .with_alphabet(Alphabet::greek())
+                .err(),
+            Some("Key character 'K' is not in the alphabet".to_string())
+        );
+        assert!(VigenereCipher::new(" ")
+            .with_alphabet(Alphabet::greek())
+            .is_err());
+        // An unknown letter uses up a key letter, as it does in A to Z
+        let vigenere = VigenereCipher::new("ΒΓ")
+            .with_alphabet(Alphabet::greek())
+            .unwrap();
+        assert_eq!(vigenere.encipher("ΑΑ"), "ΒΓ");
+        assert_eq!(vigenere.encipher("Α?Α"), "Β?Β");
+        assert_eq!(VigenereCipher::new("BC").encipher("A?A"), "B?B");
+
+        // The alphabet works with every schedule and tableau of the engine
+        let latin =
+            PolyalphabeticCipher::with_alphabet(Tableau::Vigenere, "LEMON", Alphabet::latin())
+                .unwrap()
+                .with_schedule(KeySchedule::Autokey);
+        let autokey = PolyalphabeticCipher::vigenere("LEMON").with_schedule(KeySchedule::Autokey);
+        assert_eq!(
+            latin.encipher("Attack at dawn!"),
+            autokey.encipher("Attack at dawn!")
+        );
+        let porta =
+            PolyalphabeticCipher::with_alphabet(Tableau::Porta, "ΚΛΕΙΔΙ", Alphabet::greek())
+                .unwrap();
+        assert_eq!(porta.encipher(&porta.encipher("Καλημέρα")), "Καλημέρα");
+        let progressive =
+            PolyalphabeticCipher::with_alphabet(Tableau::Beaufort, "КЛЮЧ", Alphabet::russian())
+                .unwrap()
+                .with_schedule(KeySchedule::Progressive(5));
+        assert_eq!(
+            progressive.decipher(&progressive.encipher("Атака на рассвете")),
+            "Атака на рассвете"
+        );
+        assert_eq!(
+            AffineCipher::new(2, 1).err(),
+            Some("Key a = 2 must be coprime with 26".to_string())
+        );
+    }
+
+    #[test]
+    fn test_caesar_encipher() {
+        let caesar = CaesarCipher::new(3);
+        let input = "Hello, World!";
+        let expected = "Khoor, Zruog!";
+        assert_eq!(caesar.encipher(input), expected);
+    }
+
+    #[test]
+    fn test_caesar_decipher() {
+        let caesar = CaesarCipher::new(3);
+        let input = "Khoor, Zruog!";
+        let expected = "Hello, World!";
+        assert_eq!(caesar.decipher(input), expected);
+    }
+
+    #[test]
+    fn test_affine_cipher() {
+        let affine = AffineCipher::new(5, 8).unwrap();
+        assert_eq!(affine.encipher("Affine Cipher!"), "Ihhwvc Swfrcp!");
+        assert_eq!(affine.decipher("Ihhwvc Swfrcp!"), "Affine Cipher!");
+        assert!(AffineCipher::new(13, 1).is_err());
+        assert!(AffineCipher::new(4, 1).is_err());
+    }
+
+    #[test]
+    fn test_hill_cipher() {
+        let hill =
+            HillCipher::new(vec![vec![6, 24, 1], vec![13, 16, 10], vec![20, 17, 15]]).unwrap();
+        assert_eq!(hill.encipher("ACT"), "POH");
+        assert_eq!(hill.decipher("POH"), "ACT");
+
+        let hill = HillCipher::new(vec![vec![3, 3], vec![2, 5]])
+            .unwrap()
+            .with_padding('q')
+            .unwrap();
+        assert_eq!(hill.decipher(&hill.encipher("Hello!")), "HELLOQ");
+    }
+
+    #[test]
+    fn test_hill_cipher_rejects_invalid_key() {
+        assert!(HillCipher::new(vec![vec![2, 4], vec![1, 3]]).is_err());
+        assert!(HillCipher::new(vec![vec![1, 2, 3], vec![4, 5, 6]]).is_err());
+        assert!(HillCipher::new(vec![]).is_err());
+        assert!(HillCipher::new(vec![vec![3, 3], vec![2, 5]])
+            .unwrap()
+            .with_padding('1')
+            .is_err());
+    }
+
+    #[test]
+    fn test_substitution_cipher() {
+        let cipher = SubstitutionCipher::from_alphabet("qwertyuiopasdfghjklzxcvbnm").unwrap();
+        assert_eq!(cipher.encipher("Hello, World!"), "Itssg, Vgksr!");
+        assert_eq!(cipher.decipher("Itssg, Vgksr!"), "Hello, World!");
+        assert_eq!(
+            SubstitutionCipher::from_keyword("Kryptos")
+                .unwrap()
+                .alphabet(),
+            "KRYPTOSABCDEFGHIJLMNQUVWXZ"
+        );
+    }
+
+    #[test]
+    fn test_mapping_table() {
+        let substitution = SubstitutionCipher::from_keyword("ZEBRAS").unwrap();
+        let table = substitution.mapping_table().unwrap();
+        assert_eq!(table.len(), 26);
+        assert_eq!(&table[..3], &[('A', 'Z'), ('B', 'E'), ('C', 'B')]);
+        assert_eq!(Rot13Cipher::new().mapping_table().unwrap()[0], ('A', 'N'));
+        let affine = AffineCipher::new(5, 8).unwrap().mapping_table().unwrap();
+        assert_eq!(affine[2], ('C', 'S'));
+        assert!(ColumnarTranspositionCipher::new("KEY")
+            .unwrap()
+            .mapping_table()
+            .is_none());
+    }
+
+    #[test]
+    fn test_substitution_cipher_rejects_invalid_alphabet() {
+        assert!(SubstitutionCipher::from_alphabet("ABC").is_err());
+        assert!(SubstitutionCipher::from_alphabet("AACDEFGHIJKLMNOPQRSTUVWXYZ").is_err());
+        assert!(SubstitutionCipher::from_alphabet("ABCDEFGHIJKLMNOPQRSTUVWXY1").is_err());
+        assert!(SubstitutionCipher::from_keyword("123").is_err());
+    }
+
+    #[test]
+    fn test_polybius_square() {
+        let square = PolybiusSquare::new(PolybiusGrid::FiveByFive).with_keyword("PLAYFAIR EXAMPLE");
+        assert_eq!(square.cells(), "PLAYFIREXMBCDGHKNOQSTUVWZ");
+        assert_eq!(square.encipher("Jump!"), "21 52 25 11");
+        assert_eq!(square.decipher("21 52 25 11"), "IUMP");
+
+        let square = PolybiusSquare::new(PolybiusGrid::SixBySix)
+            .with_labels("ADFGVX", "abcdef")
+            .unwrap();
+        assert_eq!(square.encipher("Agent 007"), "AA DA AE FB GB VC VC XD");
+        assert_eq!(square.decipher("AADAAEFBGBVCVCXD"), "AGENT007");
+    }
+
+    #[test]
+    fn test_polybius_square_rejects_bad_labels() {
+        let square = PolybiusSquare::new(PolybiusGrid::FiveByFive);
+        assert!(square.clone().with_labels("1234", "12345").is_err());
+        assert!(square.with_labels("12345", "11234").is_err());
+    }
+
+    #[test]
+    fn test_vigenere_encipher() {
+        let vigenere = VigenereCipher::new("LEMON");
+        let input = "ATTACKATDAWN";
+        let expected = "LXFOPVEFRNHR";
+        assert_eq!(vigenere.encipher(input), expected);
+    }
+
+    #[test]
+    fn test_vigenere_decipher() {
+        let vigenere = VigenereCipher::new("LEMON");
+        let input = "LXFOPVEFRNHR";
+        let expected = "ATTACKATDAWN";
+        assert_eq!(vigenere.decipher(input), expected);
+    }
+
+    #[test]
+    fn test_trithemius() {
+        let trithemius = TrithemiusCipher::new();
+        assert_eq!(
+            trithemius.encipher("ABCDEFGHIJKLMNOPQRSTUVWXYZA"),
+            "ACEGIKMOQSUWYACEGIKMOQSUWYA"
+        );
+        let keyed = TrithemiusCipher::new().with_offset(29).with_step(27);
+        assert_eq!(keyed, TrithemiusCipher::new().with_offset(3));
+        assert_eq!(keyed.encipher("zzz"), "cde");
+        assert_eq!(keyed.decipher("cde"), "zzz");
+        // A step of 0 is a Caesar shift
+        assert_eq!(
+            TrithemiusCipher::new()
+                .with_offset(3)
+                .with_step(0)
+                .encipher("Attack"),
+            CaesarCipher::new(3).encipher("Attack")
+        );
+    }
+
+    #[test]
+    fn test_porta() {
+        let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        for (key, modern, historical) in [
+            (
+                "A",
+                "NOPQRSTUVWXYZABCDEFGHIJKLM",
+                "NOPQRSTUVWXYZABCDEFGHIJKLM",
+            ),
+            (
+                "d",
+                "OPQRSTUVWXYZNMABCDEFGHIJKL",
+                "ZNOPQRSTUVWXYBCDEFGHIJKLMA",
+            ),
+            (
+                "Z",
+                "ZNOPQRSTUVWXYBCDEFGHIJKLMA",
+                "OPQRSTUVWXYZNMABCDEFGHIJKL",
+            ),
+        ] {
+            let cipher = PortaCipher::new(key);
+            assert_eq!(cipher.encipher(alphabet), modern);
+            let cipher = cipher.with_table(PortaTable::Historical);
+            assert_eq!(cipher.encipher(alphabet), historical);
+            assert_eq!(cipher.decipher(historical), alphabet);
+        }
+        let cipher = PortaCipher::new("KEY").with_table(PortaTable::Historical);
+        let ciphertext = cipher.encipher("Meet me at noon!");
+        assert_eq!(cipher.encipher(&ciphertext), "Meet me at noon!");
+        assert_eq!(
+            cipher.with_table(PortaTable::Modern),
+            PortaCipher::new("KEY")
+        );
+    }
+
+    #[test]
+    fn test_running_key() {
+        let cipher = RunningKeyCipher::new("It was the best of times, it was the worst of times");
+        let ciphertext = cipher.encipher("Attack at dawn!").unwrap();
+        // The key runs on without repeating, unlike a Vigenere keyword
+        assert_eq!(ciphertext, "Impaud hx eeog!");
+        assert_eq!(
+            ciphertext,
+            VigenereCipher::new("ITWASTHEBESTOF").encipher("Attack at dawn!")
+        );
+        assert_eq!(cipher.decipher(&ciphertext).unwrap(), "Attack at dawn!");
+
+        let short = RunningKeyCipher::new("Call me Ishmael.");
+        // A key as long as the message is enough
+        assert!(short.encipher("Attack at dawn!").is_ok());
+        assert!(short.encipher("Attack at dawn, please").is_err());
+        let stage: &dyn FallibleCipher = &short;
+        assert_eq!(
+            stage.try_decipher("Attack at dawn, please"),
+            Err("Key text has 13 letters but the message has 18".to_string())
+        );
+        let pipeline = Pipeline::new()
+            .then(CaesarCipher::new(3))
+            .then_fallible(short);
+        assert!(pipeline.encipher("Attack at dawn!").is_ok());
+        assert!(pipeline.encipher("Attack at dawn, please").is_err());
+    }
+
+    #[test]
+    fn test_book_cipher() {
+        let book = "Call me Ishmael. Some years ago--never mind how long\n\
+                    \n\
+                    precisely--having little or no money in my purse,\u{c}\
+                    and nothing particular to interest me on shore";
+        let cipher = BookCipher::new(book);
+        // Repeated words take successive occurrences, wrapping around
+        assert_eq!(
+            cipher.encipher("Me, me and me!").unwrap(),
+            "1.1.2 2.1.6 2.1.1 1.1.2"
+        );
+        assert_eq!(
+            cipher.decipher("1.1.2 2.1.6 2.1.1 1.1.2").unwrap(),
+            "me me and me"
+        );
+        // Blank lines are counted
+        assert_eq!(cipher.encipher("money").unwrap(), "1.3.5");
+        assert_eq!(
+            cipher.encipher("whale"),
+            Err("\"whale\" is not in the key text".to_string())
+        );
+
+        let letters = BookCipher::new(book).with_scheme(BookScheme::LineWordLetter);
+        let ciphertext = letters.encipher("Call home").unwrap();
+        assert_eq!(
+            ciphertext,
+            "1.1.1 1.1.2 1.1.3 1.1.4 1.3.3 1.4.2 1.2.1 1.2.2"
+        );
+        assert_eq!(letters.decipher(&ciphertext).unwrap(), "callhome");
+
+        // Separator styles
+        for ciphertext in ["1.3.5", "1:3:5", "[1,3,5]", " 1-3-5\n", "1/3/5"] {
+            assert_eq!(cipher.decipher(ciphertext).unwrap(), "money");
+        }
+        assert_eq!(
+            cipher.decipher("1.3"),
+            Err("Coordinates have three numbers each, but found 2 numbers".to_string())
+        );
+        assert_eq!(
+            cipher.decipher("1.2.1"),
+            Err("Nothing at 1.2.1 in the key text".to_string())
+        );
+
+        let stage: &dyn FallibleCipher = &cipher;
+        assert_eq!(
+            stage.try_encipher("whale"),
+            Err("\"whale\" is not in the key text".to_string())
+        );
+        let pipeline = Pipeline::new().then_fallible(cipher);
+        assert_eq!(pipeline.decipher("1.3.5").unwrap(), "money");
+        assert!(pipeline.decipher("1.2.1").is_err());
+    }
+
+    #[test]
+    fn test_keyed_caesar() {
+        // A shift of 0 is the keyword substitution
+        let unshifted = KeyedCaesarCipher::new("ZEBRAS", 0).unwrap();
+        let substitution = SubstitutionCipher::from_keyword("ZEBRAS").unwrap();
+        assert_eq!(unshifted.alphabet(), substitution.alphabet());
+        assert_eq!(unshifted.encipher("Flee at once!"), "Siaa zq lkba!");
+
+        // Every shift keeps the letter order of the mixed alphabet
+        let cipher = KeyedCaesarCipher::new("zebras", 29).unwrap();
+        assert_eq!(cipher.alphabet(), "RASCDFGHIJKLMNOPQTUVWXYZEB");
+        let ciphertext = cipher.encipher("Flee at once!");
+        assert_eq!(ciphertext, "Fldd rv onsd!");
+        assert_eq!(cipher.decipher(&ciphertext), "Flee at once!");
+
+        assert!(KeyedCaesarCipher::new("123", 3).is_err());
+    }
+
+    #[test]
+    fn test_caesar_case_shifts() {
+        let cipher = CaesarCipher::new(3).with_lower_shift(7);
+        assert_eq!(cipher.encipher("Hello, World! 42"), "Klssv, Zvysk! 42");
+        assert_eq!(cipher.decipher("Klssv, Zvysk! 42"), "Hello, World! 42");
+        assert_eq!(cipher.elementary(), None);
+
+        let cipher = CaesarCipher::new(1).with_digit_shift(13);
+        assert_eq!(cipher.encipher("Zz 1999"), "Aa 4222");
+        assert_eq!(cipher.decipher("Aa 4222"), "Zz 1999");
+
+        // The same shift for both cases is the plain Caesar cipher
+        let cipher = CaesarCipher::new(29).with_lower_shift(3);
+        assert_eq!(cipher.elementary(), Some(Elementary::Shift(3)));
+        assert_eq!(
+            cipher.decipher(&cipher.encipher("Attack at dawn")),
+            "Attack at dawn"
+        );
+    }
+
+    #[test]
+    fn test_caesar_box() {
+        let cipher = CaesarBoxCipher::new();
+        // Sixteen letters fill a 4x4 square exactly
+        assert_eq!(cipher.encipher("ABCDEFGHIJKLMNOP"), "AEIMBFJNCGKODHLP");
+        assert_eq!(cipher.decipher("AEIMBFJNCGKODHLP"), "ABCDEFGHIJKLMNOP");
+        // Seventeen need a 5x5 square with only four rows in use
+        let ciphertext = cipher.encipher("ABCDEFGHIJKLMNOPQ");
+        assert_eq!(ciphertext, "AFKPBGLQCHMDINEJO");
+        assert_eq!(cipher.decipher(&ciphertext), "ABCDEFGHIJKLMNOPQ");
+        assert_eq!(cipher.encipher(""), "");
+
+        let cipher = CaesarBoxCipher::new().with_width(3).unwrap();
+        assert_eq!(cipher.encipher("ABCDEFG"), "ADGBECF");
+        assert_eq!(cipher.decipher("ADGBECF"), "ABCDEFG");
+        assert!(CaesarBoxCipher::new().with_width(0).is_err());
+    }
+
+    #[test]
+    fn test_grille() {
+        let grille: Grille = "X.../.X.X/..../.X..".parse().unwrap();
+        let cipher = GrilleCipher::new(grille.clone());
+        // Twenty letters fill one square and four cells of the next
+        let message = "THEGRILLETURNSAROUNDA";
+        let ciphertext = cipher.encipher(message);
+        assert_eq!(&ciphertext[..16], "TNERIHLETSUARGLR");
+        assert_eq!(cipher.decipher(&ciphertext), message);
+        assert_eq!(cipher.encipher(""), "");
+
+        let padded = GrilleCipher::new(grille).with_padding('Q');
+        assert_eq!(padded.encipher(message).len(), 32);
+        assert!(padded
+            .decipher(&padded.encipher(message))
+            .starts_with(message));
+
+        assert!("X.../.X.X/..../".parse::<Grille>().is_err());
+        assert!("XX/..".parse::<Grille>().is_err());
+        assert!("X.X/.../...".parse::<Grille>().is_err());
+        assert!("X./.?".parse::<Grille>().is_err());
+    }
+
+    #[test]
+    fn test_random_grille() {
+        for size in [2, 4, 6, 10] {
+            let grille = Grille::random(size).unwrap();
+            assert_eq!(grille.size(), size);
+            // A random grille is a valid one
+            assert_eq!(grille.to_string().parse::<Grille>(), Ok(grille.clone()));
+            let cipher = GrilleCipher::new(grille);
+            let message = "The quick brown fox jumps over the lazy dog";
+            assert_eq!(cipher.decipher(&cipher.encipher(message)), message);
+        }
+        assert!(Grille::random(5).is_err());
+        assert!(Grille::random(0).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_grille_serde() {
+        let grille: Grille = "X.../.X.X/..../.X..".parse().unwrap();
+        let json = serde_json::to_string(&grille).unwrap();
+        assert_eq!(json, r#"["X...",".X.X","....",".X.."]"#);
+        assert_eq!(serde_json::from_str::<Grille>(&json).unwrap(), grille);
+        assert!(serde_json::from_str::<Grille>(r#"["XX",".."]"#).is_err());
+    }
+
+    #[test]
+    fn test_playfair() {
+        let cipher = PlayfairCipher::new(PolybiusGrid::FiveByFive, "PLAYFAIR EXAMPLE");
+        // The doubled E of TREE is split by the padding, and J is enciphered as I
+        assert_eq!(cipher.encipher("tree"), "UIXMXM");
+        assert_eq!(cipher.encipher("JO"), cipher.encipher("IO"));
+
+        let cipher = PlayfairCipher::new(PolybiusGrid::SixBySix, "CIPHA 2024")
+            .with_padding('Q')
+            .unwrap();
+        let ciphertext = cipher.encipher("Meet at 0800");
+        assert_eq!(cipher.decipher(&ciphertext), "MEETAT080Q0Q");
+
+        let cipher = PlayfairCipher::new(PolybiusGrid::SevenBySeven, "KEY");
+        let ciphertext = cipher.encipher("Call 555-0199, ok?");
+        assert_eq!(cipher.decipher(&ciphertext), "CALXL55X5-019X9,OK");
+        // A doubled padding character is enciphered as a row and deciphered back
+        assert_eq!(cipher.decipher(&cipher.encipher("XX")), "XX");
+        assert!(cipher.clone().with_padding('?').is_err());
+        assert_eq!(cipher.square().cells().len(), 49);
+        assert!(AdfgvxCipher::new(PolybiusGrid::SevenBySeven, "KEY", "CARGO").is_err());
+    }
+
+    #[test]
+    fn test_amsco() {
+        // The example of the ACA, which starts with a pair
+        let cipher = AmscoCipher::new("41325").unwrap();
+        let plaintext = "INCOMPLETECOLUMNARWITHALTERNATINGSINGLELETTERSANDDIGRAPHS";
+        let ciphertext = "CECRTEGLENPHPLUTNANTEIOMOWIRSITDDSINTNALINESAALEMHATGLRGR";
+        assert_eq!(cipher.encipher(plaintext), ciphertext);
+        assert_eq!(cipher.decipher(ciphertext), plaintext);
+
+        let cipher = AmscoCipher::new("4132")
+            .unwrap()
+            .with_first_cell(1)
+            .unwrap();
+        // The last row stops in the middle of a pair: I NC O MP / LE T EC O / L U
+        assert_eq!(cipher.encipher("INCOMPLETECOLU"), "NCTUMPOOECILEL");
+        assert_eq!(cipher.decipher("NCTUMPOOECILEL"), "INCOMPLETECOLU");
+
+        let cipher = AmscoCipher::new("ZEBRAS").unwrap();
+        let plaintext = "WE ARE DISCOVERED, FLEE AT ONCE";
+        let ciphertext = cipher.encipher(plaintext);
+        assert_ne!(ciphertext, plaintext);
+        assert_eq!(cipher.decipher(&ciphertext), plaintext);
+        assert_eq!(cipher.encipher(""), "");
+        assert!(AmscoCipher::new(" ").is_err());
+        assert!(AmscoCipher::new("ZEBRAS")
+            .unwrap()
+            .with_first_cell(3)
+            .is_err());
+    }
+
+    #[test]
+    fn test_key_streams() {
+        let progressive =
+            PolyalphabeticCipher::vigenere("AB").with_schedule(KeySchedule::Progressive(1));
+        let shifts: Vec<u8> = progressive.key_stream(6).into_iter().flatten().collect();
+        assert_eq!(shifts, vec![0, 1, 1, 2, 2, 3]);
+        // The stream matches what encipher does to a message of A's
+        assert_eq!(progressive.encipher("AAAAAA"), "ABBCCD");
+
+        let interrupted = PolyalphabeticCipher::vigenere("KEY")
+            .with_interruption(Interruption::AtLetters(vec![2]))
+            .with_indicator('B')
+            .unwrap();
+        assert_eq!(
+            interrupted.key_stream(4),
+            vec![Some(4), Some(24), Some(4), Some(24)]
+        );
+
+        let words = PolyalphabeticCipher::vigenere("KEY").with_interruption(Interruption::AtWords);
+        assert_eq!(
+            words.key_stream_for("at dawn"),
+            vec![Some(10), Some(4), Some(10), Some(4), Some(24), Some(10)]
+        );
+        let autokey = PolyalphabeticCipher::vigenere("KEY").with_schedule(KeySchedule::Autokey);
+        assert_eq!(
+            autokey.key_stream_for("a?ta"),
+            vec![Some(10), None, Some(24), Some(0)]
+        );
+        assert!(PolyalphabeticCipher::vigenere("").key_stream(3).is_empty());
+
+        assert_eq!(
+            TrithemiusCipher::new()
+                .with_offset(3)
+                .with_step(2)
+                .key_stream(4),
+            vec![3, 5, 7, 9]
+        );
+    }
+
+    #[test]
+    fn test_polyalphabetic_tableaux() {
+        let plaintext = "DEFENDTHEEASTWALL";
+        let porta = PolyalphabeticCipher::porta("FORTIFICATION");
+        assert_eq!(porta.encipher(plaintext), "SYNNJSCVRNRLAHUTU");
+        assert_eq!(porta.decipher("SYNNJSCVRNRLAHUTU"), plaintext);
+
+        let beaufort = PolyalphabeticCipher::beaufort("FORTIFICATION");
+        assert_eq!(beaufort.decipher(&beaufort.encipher(plaintext)), plaintext);
+        let variant = PolyalphabeticCipher::variant_beaufort("LEMON");
+        assert_eq!(variant.encipher("LXFOPVEFRNHR"), "ATTACKATDAWN");
+
+        let gronsfeld = PolyalphabeticCipher::gronsfeld("31415").unwrap();
+        assert_eq!(gronsfeld.encipher("Attack at dawn"), "Duxbhn bx efzo");
+        assert_eq!(gronsfeld.decipher("Duxbhn bx efzo"), "Attack at dawn");
+        assert!(PolyalphabeticCipher::gronsfeld("31A").is_err());
+    }
+
+    #[test]
+    fn test_polyalphabetic_schedules() {
+        let progressive =
+            PolyalphabeticCipher::vigenere("AB").with_schedule(KeySchedule::Progressive(1));
+        assert_eq!(progressive.encipher("AAAAAA"), "ABBCCD");
+        assert_eq!(progressive.decipher("ABBCCD"), "AAAAAA");
+
+        // The autokey schedule works with every tableau
+        let autokey = PolyalphabeticCipher::porta("KEY").with_schedule(KeySchedule::Autokey);
+        let ciphertext = autokey.encipher("Meet me at noon");
+        assert_eq!(autokey.decipher(&ciphertext), "Meet me at noon");
+    }
+
+    #[test]
+    fn test_polyalphabetic_interruption_and_indicator() {
+        let words = PolyalphabeticCipher::vigenere("BC").with_interruption(Interruption::AtWords);
+        assert_eq!(words.encipher("aaa aa"), "bcb bc");
+        assert_eq!(words.decipher("bcb bc"), "aaa aa");
+
+        let letters = PolyalphabeticCipher::vigenere("BC")
+            .with_schedule(KeySchedule::Autokey)
+            .with_interruption(Interruption::AtLetters(vec![3]));
+        assert_eq!(letters.encipher("abcabc"), "bdcbdc");
+        assert_eq!(letters.decipher("bdcbdc"), "abcabc");
+
+        let indicated = PolyalphabeticCipher::beaufort("LEMON")
+            .with_indicator('c')
+            .unwrap();
+        let ciphertext = indicated.encipher("ATTACK");
+        assert!(ciphertext.starts_with('C'));
+        assert_eq!(
+            &ciphertext[1..],
+            PolyalphabeticCipher::beaufort("MONLE").encipher("ATTACK")
+        );
+        assert_eq!(indicated.decipher(&ciphertext), "ATTACK");
+        assert!(PolyalphabeticCipher::vigenere("KEY")
+            .with_indicator('1')
+            .is_err());
+    }
+
+    #[test]
+    fn test_unknown_letters_propagate() {
+        // The placeholder keeps its key position, so the letters after it still decipher
+        let vigenere = VigenereCipher::new("LEMON");
+        assert_eq!(vigenere.decipher("LX?OPVEFRNHR"), "AT?ACKATDAWN");
+        let autokey = PolyalphabeticCipher::vigenere("KEY").with_schedule(KeySchedule::Autokey);
+        let ciphertext = autokey.encipher("ATTACKATDAWN");
+        let mut damaged: Vec<char> = ciphertext.chars().collect();
+        damaged[1] = UNKNOWN;
+        let damaged: String = damaged.into_iter().collect();
+        // The unknown plaintext letter keys the letter three places on, and so on down the line
+        assert_eq!(autokey.decipher(&damaged), "A?TA?KA?DA?N");
+
+        let hill = HillCipher::new(vec![vec![3, 3], vec![2, 5]]).unwrap();
+        assert_eq!(hill.decipher("HI?T"), "HE??");
+
+        let square = PolybiusSquare::new(PolybiusGrid::FiveByFive);
+        assert_eq!(square.encipher("H?"), "23 ??");
+        assert_eq!(square.decipher("23 ?5 31"), "H?L");
+        let bifid = BifidCipher::new("", 5).unwrap();
+        assert_eq!(bifid.decipher(&bifid.encipher("HE?LO")).len(), 5);
+        let nihilist = NihilistCipher::new("ZEBRAS", "RUSSIAN").unwrap();
+        assert_eq!(nihilist.decipher("37 ? 62 36"), "D?NA");
+        assert_eq!(nihilist.encipher("D?NA"), "37 ? 62 36");
+    }
+
+    #[test]
+    fn test_morse_code_encode() {
+        let morse_code = MorseCode::new();
+        let input = "HELLO";
+        let expected = ".... . .-.. .-.. ---";
+        assert_eq!(morse_code.encode(input), expected);
+    }
+
+    #[test]
+    fn test_morse_code_decode() {
+        let morse_code = MorseCode::new();
+        let input = ".... . .-.. .-.. ---";
+        let expected = "HELLO";
+        assert_eq!(morse_code.decode(input), expected);
+    }
+
+    #[test]
+    fn test_morse_code_keyword() {
+        let morse = MorseCode::new().with_keyword("ZEBRAS").unwrap();
+        // Z takes the code of A, E of B, B of C, and the letters after the keyword are shifted
+        assert_eq!(morse.encode("ZEB"), ".- -... -.-.");
+        assert_eq!(morse.encode("T"), "..-");
+        let code = morse.encode("Flee at once");
+        assert_ne!(code, MorseCode::new().encode("Flee at once"));
+        assert_eq!(morse.decode(&code), "FLEE AT ONCE");
+
+        // The keyword-mixed alphabet of the alphabet itself is the standard table
+        let plain = MorseCode::new().with_keyword("ABC").unwrap();
+        assert_eq!(plain.encode("Hello"), MorseCode::new().encode("Hello"));
+        assert!(MorseCode::new().with_keyword("42").is_err());
+    }
+
+    #[test]
+    fn test_morse_code_timings() {
+        let morse_code = MorseCode::new();
+        let units: Vec<(bool, u128)> = morse_code
+            .timings("EA E", 20)
+            .iter()
+            .map(|t| (t.on, t.duration.as_millis() / 60))
+            .collect();
+        let expected = vec![
+            (true, 1),
+            (false, 3),
+            (true, 1),
+            (false, 1),
+            (true, 3),
+            (false, 7),
+            (true, 1),
+        ];
+        assert_eq!(units, expected);
+    }
+
+    #[test]
+    fn test_atbash_encipher() {
+        let atbash = AtbashCipher::new();
+        let input = "ATTACKATDAWN";
+        let expected = "ZGGZXPZGWZDM";
+        assert_eq!(atbash.encipher(input), expected);
+    }
+
+    #[test]
+    fn test_atbash_decipher() {
+        let atbash = AtbashCipher::new();
+        let input = "ZGGZXPZGWZDM";
+        let expected = "ATTACKATDAWN";
+        assert_eq!(atbash.decipher(input), expected);
     }
 
-    /// Deciphers a given message using the ROT13 cipher.
-    ///
-    /// Since ROT13 is symmetric, this method is identical to `encipher`.
-    pub fn decipher(&self, message: &str) -> String {
-        self.encipher(message)
+    #[test]
+    fn test_reverse_words() {
+        let cipher = ReverseWordsCipher::new();
+        assert_eq!(cipher.encipher("attack  at dawn!"), "dawn!  at attack");
+        assert_eq!(cipher.decipher(" dawn!  at attack"), " attack  at dawn!");
     }
-}
-
-/// A Caesar cipher implementation with a variable shift.
-///
-/// The Caesar cipher is a type of substitution cipher where each letter in the plaintext is 'shifted' a certain number of places down the alphabet.
-pub struct CaesarCipher {
-    shift: u8,
-}
 
-impl CaesarCipher {
-    /// Creates a new instance of the Caesar cipher with the specified shift.
-    pub fn new(shift: u8) -> Self {
-        CaesarCipher { shift }
+    #[test]
+    fn test_reverse_letters() {
+        let cipher = ReverseLettersCipher::new();
+        assert_eq!(cipher.encipher("Hello, World!"), "olleH, dlroW!");
+        assert_eq!(cipher.decipher("olleH, dlroW!"), "Hello, World!");
     }
 
-    /// Enciphers a given message using the Caesar cipher.
-    ///
-    /// This function shifts each letter in the message by the specified shift.
-    pub fn encipher(&self, message: &str) -> String {
-        message.chars().map(|c| {
-            match c {
-                'a'..='z' => (((c as u8 - b'a' + self.shift) % 26) + b'a') as char,
-                'A'..='Z' => (((c as u8 - b'A' + self.shift) % 26) + b'A') as char,
-                _ => c,
-            }
-        }).collect()
+    #[test]
+    fn test_word_transposition() {
+        let cipher = WordTranspositionCipher::new("zebra").unwrap();
+        let plaintext = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            cipher.encipher(plaintext),
+            "jumps brown lazy quick the fox dog the over"
+        );
+        assert_eq!(cipher.decipher(&cipher.encipher(plaintext)), plaintext);
+        assert!(WordTranspositionCipher::new(" ").is_err());
     }
 
-    /// Deciphers a given message using the Caesar cipher.
-    ///
-    /// This function shifts each letter in the message by the reverse of the specified shift.
-    pub fn decipher(&self, message: &str) -> String {
-        let reverse_shift = 26 - self.shift;
-        message.chars().map(|c| {
-            match c {
-                'a'..='z' => (((c as u8 - b'a' + reverse_shift) % 26) + b'a') as char,
-                'A'..='Z' => (((c as u8 - b'A' + reverse_shift) % 26) + b'A') as char,
-                _ => c,
-            }
-        }).collect()
+    #[test]
+    fn test_columnar_transposition() {
+        let cipher = ColumnarTranspositionCipher::new("GERMAN").unwrap();
+        assert_eq!(
+            cipher.encipher("defend the east wall"),
+            "n wetsld alee deafht"
+        );
+        assert_eq!(
+            cipher.decipher("n wetsld alee deafht"),
+            "defend the east wall"
+        );
+
+        let padded = ColumnarTranspositionCipher::new("CAB")
+            .unwrap()
+            .with_padding('X');
+        assert_eq!(padded.encipher("ATTACK"), "TCTKAA");
+        assert_eq!(padded.encipher("ATTACKS"), "TCXTKXAAS");
+        assert_eq!(padded.decipher("TCXTKXAAS"), "ATTACKSXX");
+        assert!(ColumnarTranspositionCipher::new("").is_err());
     }
-}
 
-/// A Vigenere cipher implementation.
-///
-/// The Vigenere cipher is a method of encrypting alphabetic text by using a series of interwoven Caesar ciphers based on the letters of a keyword.
-pub struct VigenereCipher {
-    key: String,
-}
+    #[test]
+    fn test_double_transposition() {
+        let cipher = DoubleTranspositionCipher::new("CAB", "BA").unwrap();
+        // CAB turns "ATTACKS" into "TCTKAAS", BA then into "CKATTAS"
+        assert_eq!(cipher.encipher("ATTACKS"), "CKATTAS");
+        assert_eq!(cipher.decipher("CKATTAS"), "ATTACKS");
+        assert!(DoubleTranspositionCipher::new("CAB", "").is_err());
+    }
 
-impl VigenereCipher {
-    /// Creates a new instance of the Vigenere cipher with the specified key.
-    pub fn new(key: &str) -> Self {
-        VigenereCipher { key: key.to_ascii_lowercase() }
+    #[test]
+    fn test_adfgvx() {
+        let cipher = AdfgvxCipher::new(
+            PolybiusGrid::SixBySix,
+            "NA1C3H8TB2OME5WRPD4F6G7I9J0KLQSUVXYZ",
+            "PRIVACY",
+        )
+        .unwrap();
+        let ciphertext = "DGDD DAGD DGAF ADDF DADV DVFA ADVX";
+        assert_eq!(
+            cipher.encipher("ATTACK AT 1200AM"),
+            ciphertext.replace(' ', "")
+        );
+        assert_eq!(cipher.decipher(ciphertext), "ATTACKAT1200AM");
+        assert!(AdfgvxCipher::new(PolybiusGrid::FiveByFive, "KEY", "").is_err());
     }
 
-    /// Enciphers a given plaintext using the Vigenere cipher.
-    ///
-    /// This function uses the provided key to shift each letter in the plaintext.
-    pub fn encipher(&self, plaintext: &str) -> String {
-        let key_len = self.key.len();
-        if key_len == 0 {
-            return plaintext.to_string();
-        }
+    #[test]
+    fn test_bifid() {
+        let cipher = BifidCipher::new("BGWKZQPNDSIOAXEFCLUMTHYVR", 10).unwrap();
+        assert_eq!(cipher.encipher("Flee at once!"), "UAEOLWRINS");
+        assert_eq!(cipher.decipher("UAEOLWRINS"), "FLEEATONCE");
 
-        let mut index = 0;
-        plaintext.chars().map(|c| {
-            if c.is_ascii_alphabetic() {
-                let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                let shift = self.key.as_bytes()[index % key_len] as u8 - b'a';
-                index += 1;
-                (first + (c as u8 - first + shift) % 26) as char
-            } else {
-                c
-            }
-        }).collect()
+        // A shorter period changes the ciphertext, the last block may be short
+        let cipher = BifidCipher::new("BGWKZQPNDSIOAXEFCLUMTHYVR", 3).unwrap();
+        let ciphertext = cipher.encipher("FLEEATONCE");
+        assert_ne!(ciphertext, "UAEOLWRINS");
+        assert_eq!(cipher.decipher(&ciphertext), "FLEEATONCE");
+        assert!(BifidCipher::new("KEY", 0).is_err());
     }
 
-    /// Deciphers a given ciphertext using the Vigenere cipher.
-    ///
-    /// This function uses the provided key to reverse the shift applied during encryption.
-    pub fn decipher(&self, ciphertext: &str) -> String {
-        let key_len = self.key.len();
-        if key_len == 0 {
-            return ciphertext.to_string();
-        }
+    #[test]
+    fn test_trifid() {
+        let cipher = TrifidCipher::new("FELIX MARIE DELASTELLE", 5).unwrap();
+        let ciphertext = cipher.encipher("Aide-toi, le ciel t'aidera");
+        assert_eq!(ciphertext.len(), 20);
+        assert_eq!(cipher.decipher(&ciphertext), "AIDETOILECIELTAIDERA");
+        assert!(TrifidCipher::new("KEY", 0).is_err());
+    }
 
-        let mut index = 0;
-        ciphertext.chars().map(|c| {
-            if c.is_ascii_alphabetic() {
-                let first = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-                let shift = self.key.as_bytes()[index % key_len] as u8 - b'a';
-                index += 1;
-                (first + (c as u8 - first - shift + 26) % 26) as char
-            } else {
-                c
-            }
-        }).collect()
+    #[test]
+    fn test_delastelle() {
+        // Two and three dimensions are the bifid and trifid ciphers
+        let bifid = DelastelleCipher::new("PHQGMEAYLNOFDXKRCVSZWBUTI", 2, 5).unwrap();
+        assert_eq!(
+            bifid.encipher("defend the east wall of the castle"),
+            "FFYHMKHYCPLIASHADTRLHCCHLBLR"
+        );
+        let trifid = DelastelleCipher::new("ABCDEFGHIJKLMNOPQRSTUVWXYZ+", 3, 5)
+            .unwrap()
+            .with_keyword("FELIX MARIE DELASTELLE");
+        let message = "Aide-toi, le ciel t'aidera";
+        assert_eq!(
+            trifid.encipher(message),
+            TrifidCipher::new("FELIX MARIE DELASTELLE", 5)
+                .unwrap()
+                .encipher(message)
+        );
+
+        let hex = DelastelleCipher::new("0123456789abcdef", 4, 3).unwrap();
+        assert_eq!((hex.side(), hex.dimensions()), (2, 4));
+        // 1 = 0001 and 2 = 0010 seriate to 00 00 01 10, read as 0 and 6
+        assert_eq!(hex.encipher("12"), "06");
+        assert_eq!(hex.decipher("06"), "12");
+        assert_eq!(hex.decipher(&hex.encipher("C0FFEE?")), "c0ffee?");
+
+        assert!(DelastelleCipher::new("ABCDEFGHI", 1, 5).is_err());
+        assert!(DelastelleCipher::new("ABCDEFGHI", 2, 0).is_err());
+        assert!(DelastelleCipher::new("ABCDEFGH", 2, 5).is_err());
+        assert!(DelastelleCipher::new("ABCDEFGHA", 2, 5).is_err());
+        assert!(DelastelleCipher::new("ABCDEFGH?", 2, 5).is_err());
     }
-}
 
-/// A Morse code converter.
-///
-/// This struct provides methods to convert text to Morse code and vice versa.
-const MORSE_CODE_MAP: &[(&str, &str)] = &[
-    ("A", ".-"), ("B", "-..."), ("C", "-.-."), ("D", "-.."), ("E", "."), ("F", "..-."),
-    ("G", "--."), ("H", "...."), ("I", ".."), ("J", ".---"), ("K", "-.-"), ("L", ".-.."),
-    ("M", "--"), ("N", "-."), ("O", "---"), ("P", ".--."), ("Q", "--.-"), ("R", ".-."),
-    ("S", "..."), ("T", "-"), ("U", "..-"), ("V", "...-"), ("W", ".--"), ("X", "-..-"),
-    ("Y", "-.--"), ("Z", "--.."),
-    ("0", "-----"), ("1", ".----"), ("2", "..---"), ("3", "...--"), ("4", "....-"),
-    ("5", "....."), ("6", "-...."), ("7", "--..."), ("8", "---.."), ("9", "----."),
-    (" ", "/"), // Space separator
-    (".", ".-.-.-"), (",", "--..--"), ("?", "..--.."), (";", "-.-.-."),
-    (":", "---..."), ("-", "-....-"), ("/", "-..-."), ("'", ".----."),
-    ("\"", ".-..-."),
-    ("=", "-...-"), ("_", "..--.-"), ("+", ".-.-."), ("-", "-....-"),
-    ("*", "-..-"), ("(", "-.--."), (")", "-.--.-"),
-];
+    #[test]
+    fn test_token_code() {
+        let cards = TokenCode::playing_cards();
+        assert_eq!(cards.entries().len(), 36);
+        assert_eq!(cards.encipher("Zen 10"), "K♦ 5♣ A♦ / A♥ 10♥");
+        assert_eq!(cards.decipher("K♦ 5♣ A♦ / A♥ 10♥"), "ZEN 10");
+        assert_eq!(cards.decipher("Q♠ - 3♣"), "?-C");
+        let unknown = cards.unknown_token("A♣ - Q♠").unwrap();
+        assert_eq!((unknown.token.as_str(), unknown.position), ("Q♠", 5));
 
-pub struct MorseCode {
-    morse_code_map: std::collections::HashMap<String, String>,
-    reverse_morse_code_map: std::collections::HashMap<String, String>,
-}
+        let dominoes = TokenCode::dominoes();
+        assert_eq!(dominoes.encipher("Z 09"), "2|8 / 2|9 4|5");
+        assert_eq!(dominoes.decipher("2|8 / 2|9 4|5"), "Z 09");
 
-impl MorseCode {
-    /// Creates a new instance of the Morse code converter.
-    pub fn new() -> Self {
-        let mut morse_code_map = std::collections::HashMap::new();
-        let mut reverse_morse_code_map = std::collections::HashMap::new();
+        let resistor = TokenCode::resistor_colors();
+        assert_eq!(resistor.encipher("470"), "yellow violet black");
+        assert_eq!(resistor.decipher("Brown BLACK red"), "102");
+        let flags = TokenCode::flag_emoji();
+        assert_eq!(flags.encipher("Go 7"), "🇬 🇴 / 7\u{fe0f}\u{20e3}");
+        assert_eq!(flags.decipher("🇬 🇴 / 7\u{fe0f}\u{20e3}"), "GO 7");
 
-        for (key, value) in MORSE_CODE_MAP {
-            morse_code_map.insert(key.to_string(), value.to_string());
-            reverse_morse_code_map.insert(value.to_string(), key.to_string());
-        }
+        assert!(TokenCode::new(vec![('A', "x".to_string()), ('B', "x".to_string())]).is_err());
+        assert!(TokenCode::new(vec![('A', "x y".to_string())]).is_err());
+        assert!(TokenCode::from_csv("AB,x").is_err());
+    }
 
-        MorseCode {
-            morse_code_map,
-            reverse_morse_code_map,
-        }
+    #[test]
+    fn test_fractionated_morse() {
+        let cipher = FractionatedMorseCipher::new("ROUNDTABLE");
+        // E is ".", padded to ".xx", the ninth trigram
+        assert_eq!(cipher.encipher("e"), "L");
+        assert_eq!(cipher.decipher("L"), "E");
+        let ciphertext = cipher.encipher("Defend the east wall");
+        assert!(ciphertext.chars().all(|c| c.is_ascii_uppercase()));
+        assert_eq!(cipher.decipher(&ciphertext), "DEFEND THE EAST WALL");
     }
 
-    /// Encodes a given text into Morse code.
-    pub fn encode(&self, text: &str) -> String {
-        let mut encoded = String::new();
+    #[test]
+    fn test_pollux() {
+        let cipher = PolluxCipher::new("x.-x.-x.-x").unwrap();
+        // The dots of S take the digits 1, 4 and 7 in turn, then start again
+        assert_eq!(cipher.encipher("SE"), "14701");
+        let ciphertext = cipher.encipher("Defend the east wall");
+        assert!(ciphertext.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(cipher.decipher(&ciphertext), "DEFEND THE EAST WALL");
+        assert!(PolluxCipher::new("x.-x.-x.-").is_err());
+        assert!(PolluxCipher::new("x.-x.-x.-o").is_err());
+        assert!(PolluxCipher::new("x.x.x.x.x.").is_err());
+    }
 
-        for c in text.to_uppercase().chars() {
-            if let Some(code) = self.morse_code_map.get(&c.to_string()) {
-                encoded.push_str(code);
-                encoded.push(' '); // Add space between characters
-            }
-        }
+    #[test]
+    fn test_morbit() {
+        let cipher = MorbitCipher::new("WISECRACK").unwrap();
+        // E is ".", padded to ".x", the third pair, numbered 8 by the S of WISECRACK
+        assert_eq!(cipher.encipher("e"), "8");
+        assert_eq!(cipher.decipher("8"), "E");
+        let ciphertext = cipher.encipher("Once upon a time");
+        assert!(ciphertext.chars().all(|c| ('1'..='9').contains(&c)));
+        assert_eq!(cipher.decipher(&ciphertext), "ONCE UPON A TIME");
+        assert!(MorbitCipher::new("SHORT").is_err());
+    }
 
-        encoded.trim().to_string() // Remove trailing space
+    #[test]
+    fn test_nihilist() {
+        let cipher = NihilistCipher::new("ZEBRAS", "RUSSIAN").unwrap();
+        let ciphertext = "37 106 62 36 67 47 86 26 104 53 62 77 27 55 57 66 55 36 54 27";
+        assert_eq!(cipher.encipher("DYNAMITE WINTER PALACE"), ciphertext);
+        assert_eq!(cipher.decipher(ciphertext), "DYNAMITEWINTERPALACE");
+        assert!(NihilistCipher::new("ZEBRAS", "123").is_err());
     }
 
-    /// Decodes a given Morse code into text.
-    pub fn decode(&self, code: &str) -> String {
-        let mut decoded = String::new();
-        let code_vec: Vec<&str> = code.split(' ').collect();
+    #[test]
+    fn test_interleave() {
+        let cipher = InterleaveCipher::new(3).unwrap();
+        assert_eq!(cipher.encipher("HELLO WORLD"), "HLWLEOODL R");
+        assert_eq!(cipher.decipher("HLWLEOODL R"), "HELLO WORLD");
+        assert_eq!(InterleaveCipher::new(1).unwrap().encipher("abc"), "abc");
+        assert!(InterleaveCipher::new(0).is_err());
+    }
 
-        for morse_char in code_vec {
-            if let Some(character) = self.reverse_morse_code_map.get(morse_char) {
-                decoded.push_str(character);
+    #[test]
+    fn test_scytale() {
+        let cipher = ScytaleCipher::new(3).unwrap();
+        // Rows ABCD EFGH IJ: the last row is short
+        assert_eq!(cipher.encipher("ABCDEFGHIJ"), "AEIBFJCGDH");
+        assert_eq!(cipher.decipher("AEIBFJCGDH"), "ABCDEFGHIJ");
+        let padded = ScytaleCipher::new(3).unwrap().with_padding('X');
+        assert_eq!(padded.encipher("ABCDEFGHIJ"), "AEIBFJCGXDHX");
+        assert_eq!(padded.decipher("AEIBFJCGXDHX"), "ABCDEFGHIJXX");
+        for len in 0..12 {
+            let text: String = "THE SCYTALE".chars().take(len).collect();
+            for diameter in 1..6 {
+                let cipher = ScytaleCipher::new(diameter).unwrap();
+                assert_eq!(cipher.decipher(&cipher.encipher(&text)), text);
             }
         }
-
-        decoded
+        assert!(ScytaleCipher::new(0).is_err());
     }
-}
 
-/// An Atbash cipher implementation.
-///
-/// The Atbash cipher is a specific type of monoalphabetic cipher that was originally used to encode the Hebrew alphabet.
-pub struct AtbashCipher;
+    #[test]
+    fn test_boustrophedon() {
+        let cipher = BoustrophedonCipher::new(5).unwrap();
+        assert_eq!(cipher.encipher("WE ARE DISCOVERED"), "WE ARSID ECOVERDE");
+        assert_eq!(cipher.decipher("WE ARSID ECOVERDE"), "WE ARE DISCOVERED");
+        assert!(BoustrophedonCipher::new(0).is_err());
+    }
 
-impl AtbashCipher {
-    /// Creates a new instance of the Atbash cipher.
-    pub fn new() -> Self {
-        AtbashCipher
+    #[test]
+    fn test_spiral() {
+        let outward = SpiralCipher::new(3, SpiralDirection::Outward).unwrap();
+        assert_eq!(outward.encipher("ABCDEFGHI"), "EDGHIFCBA");
+        assert_eq!(outward.decipher("EDGHIFCBA"), "ABCDEFGHI");
+        assert!(SpiralCipher::new(0, SpiralDirection::Inward).is_err());
     }
 
-    /// Transforms a given text using the Atbash cipher.
-    ///
-    /// This function reverses the order of the alphabet for each letter in the text.
-    pub fn transform(&self, text: &str) -> String {
-        text.chars().map(|c| match c {
-            'a'..='z' => ('a' as u8 + 25 - (c as u8 - 'a' as u8)) as char,
-            'A'..='Z' => ('A' as u8 + 25 - (c as u8 - 'A' as u8)) as char,
-            _ => c,
-        }).collect()
+    #[test]
+    fn test_route() {
+        let clockwise = RouteCipher::new(3, 3, Route::SpiralClockwise).unwrap();
+        // Two grids: ABCDEFGHI and the incomplete JKLM
+        assert_eq!(clockwise.encipher("ABCDEFGHIJKLM"), "ABCFIHGDEJKLM");
+        let counterclockwise = RouteCipher::new(3, 3, Route::SpiralCounterclockwise).unwrap();
+        assert_eq!(counterclockwise.encipher("ABCDEFGHI"), "ADGHIFCBE");
+        let boustrophedon = RouteCipher::new(3, 2, Route::Boustrophedon).unwrap();
+        assert_eq!(boustrophedon.encipher("ABCDEFGHIJ"), "ABCFEDGHIJ");
+        let routes = [
+            Route::SpiralClockwise,
+            Route::SpiralCounterclockwise,
+            Route::Boustrophedon,
+            Route::Columns(vec![1, 3, 0, 2]),
+        ];
+        for route in routes {
+            let cipher = RouteCipher::new(4, 3, route).unwrap();
+            for len in 0..30 {
+                let text: String = "THE ROUTE CIPHER OF THE UNION ARMY"
+                    .chars()
+                    .take(len)
+                    .collect();
+                assert_eq!(cipher.decipher(&cipher.encipher(&text)), text);
+            }
+        }
+        assert!(RouteCipher::new(0, 3, Route::Boustrophedon).is_err());
+        assert!(RouteCipher::new(3, 0, Route::Boustrophedon).is_err());
+        assert!(RouteCipher::new(3, 3, Route::Columns(vec![0, 1])).is_err());
+        assert!(RouteCipher::new(3, 3, Route::Columns(vec![0, 1, 1])).is_err());
     }
 
-    /// Enciphers a given plaintext using the Atbash cipher.
-    ///
-    /// Since the Atbash cipher is symmetric, this method is identical to `decipher`.
-    pub fn encipher(&self, plaintext: &str) -> String {
-        self.transform(plaintext)
+    #[test]
+    fn test_decimation() {
+        let cipher = DecimationCipher::new(5).unwrap();
+        let ciphertext = cipher.encipher("Hello, World!").unwrap();
+        assert_eq!(ciphertext, "H,llW!ore dlo");
+        assert_eq!(cipher.decipher(&ciphertext).unwrap(), "Hello, World!");
+        assert!(cipher.decipher("ABCDEFGHIJ").is_err());
+        assert!(DecimationCipher::new(0).is_err());
     }
 
-    /// Deciphers a given ciphertext using the Atbash cipher.
-    ///
-    /// Since the Atbash cipher is symmetric, this method is identical to `encipher`.
-    pub fn decipher(&self, ciphertext: &str) -> String {
-        self.transform(ciphertext)
+    #[test]
+    fn test_chain_additive_cipher() {
+        let cipher = ChainAdditiveCipher::new("715").unwrap();
+        // keystream 7 1 5 8 6 3
+        assert_eq!(cipher.encipher("12-3456"), "83-8219");
+        assert_eq!(cipher.decipher("83-8219"), "12-3456");
+        assert!(ChainAdditiveCipher::new("x").is_err());
     }
-}
 
-/// An alphabetic to numerical converter.
-///
-/// This struct provides methods to convert alphabetic characters to their corresponding numerical values and vice versa.
-pub struct AlphaNumConverter;
+    #[test]
+    fn test_straddling_checkerboard() {
+        let board = StraddlingCheckerboard::new("ESTONIAR", [2, 6]).unwrap();
+        // E S _ T O N _ I A R, then BCDFGHJKLM on row 2 and PQUVWXYZ./ on row 6
+        assert_eq!(board.encipher("Run, Joe!"), "96252640");
+        assert_eq!(board.decipher("96252640").unwrap(), "RUNJOE");
+        assert_eq!(board.decipher("96252 640").unwrap(), "RUNJOE");
+        assert_eq!(board.encipher("SEND 2."), "1052268");
+        assert_eq!(board.decipher("1052268").unwrap(), "SEND.");
+        assert_eq!(board.blanks(), [2, 6]);
+        assert!(board.decipher("1052").is_err());
+        let stage: &dyn FallibleCipher = &board;
+        assert_eq!(
+            stage.try_decipher("1052"),
+            Err("Ciphertext ends in the middle of a letter".to_string())
+        );
+        let pipeline = Pipeline::new().then_fallible(board.clone());
+        assert_eq!(pipeline.inverse().encipher("96252640").unwrap(), "RUNJOE");
+        assert!(pipeline.decipher("1052").is_err());
 
-impl AlphaNumConverter {
-    /// Creates a new instance of the alphabetic to numerical converter.
-    pub fn new() -> Self {
-        AlphaNumConverter
+        let keyed = board.clone().with_additive_key("123").unwrap();
+        let ciphertext = keyed.encipher("ATTACK AT DAWN");
+        assert_eq!(keyed.decipher(&ciphertext).unwrap(), "ATTACKATDAWN");
+        assert_ne!(ciphertext, board.encipher("ATTACK AT DAWN"));
+
+        assert!(StraddlingCheckerboard::new("ESTONIA", [2, 6]).is_err());
+        assert!(StraddlingCheckerboard::new("ESTONIAE", [2, 6]).is_err());
+        assert!(StraddlingCheckerboard::new("ESTONIAR", [2, 2]).is_err());
+        assert!(StraddlingCheckerboard::new("ESTONIAR", [2, 10]).is_err());
+        assert!(board.clone().with_additive_key("12a").is_err());
+
+        // Headed 9 to 0 the letters keep their cells but change digits
+        let headed = board.with_headings([9, 8, 7, 6, 5, 4, 3, 2, 1, 0]).unwrap();
+        assert_eq!(headed.blanks(), [7, 3]);
+        assert_eq!(headed.encipher("RUN"), "0374");
+        assert_eq!(headed.decipher("0374").unwrap(), "RUN");
+        assert!(headed.with_headings([0; 10]).is_err());
     }
 
-    /// Converts alphabetic characters in a given text to their corresponding numerical values.
-    pub fn alpha_to_num(&self, text: &str) -> String {
-        let mut result = String::new();
+    #[test]
+    fn test_optimized_checkerboard() {
+        use crate::analysis::Language;
 
-        for c in text.chars() {
-            if c.is_ascii_alphabetic() {
-                let base = if c.is_ascii_uppercase() { 'A' as u32 } else { 'a' as u32 };
-                let number = (c as u32 - base + 1) as u8;
-                result.push_str(&number.to_string());
-                result.push(' ');
-            } else {
-                result.push(c);
-                result.push(' ');
-            }
-        }
+        let message = "Le chiffre est cache sous la pierre du vieux moulin";
+        let french = StraddlingCheckerboard::optimized(Language::French.frequencies(), [0, 9]);
+        let french = french.unwrap();
+        assert_eq!(french.keyword_row(), "ESAITNRU");
+        assert_eq!(french.blanks(), [0, 9]);
+        // The frequent letters on top give shorter output than the alphabet's first letters
+        let plain = StraddlingCheckerboard::new("ABCDEFGH", [0, 9]).unwrap();
+        assert!(french.encipher(message).len() < plain.encipher(message).len());
+        assert_eq!(
+            french.decipher(&french.encipher(message)).unwrap(),
+            message.to_uppercase().replace(' ', "")
+        );
 
-        result.trim().to_string()
+        // The keyword row and blanks recreate the board
+        let shared = StraddlingCheckerboard::new(&french.keyword_row(), [0, 9]).unwrap();
+        assert_eq!(shared, french);
+        assert_eq!(shared.to_string().lines().count(), 4);
+        assert!(
+            StraddlingCheckerboard::optimized(Language::English.frequencies(), [3, 3]).is_err()
+        );
     }
 
-    /// Converts numerical values in a given text back to their corresponding alphabetic characters.
-    pub fn num_to_alpha(&self, cipher_text: &str) -> String {
-        let mut decrypted_text = String::new();
-        let mut number_buffer = String::new();
-
-        for char in cipher_text.chars() {
-            if char.is_digit(10) {
-                number_buffer.push(char);
-            } else if char.is_whitespace() {
-                if !number_buffer.is_empty() {
-                    let number: u32 = number_buffer.parse().unwrap();
-                    let char_code = if number <= 26 {
-                        // Handle lowercase letters
-                        'a' as u32 + number - 1
-                    } else if number <= 52 {
-                        // Handle uppercase letters
-                        'A' as u32 + number - 27
-                    } else {
-                        // Ignore invalid numbers
-                        continue;
-                    };
-                    decrypted_text.push(char::from_u32(char_code).unwrap());
-                    number_buffer.clear();
-                }
-            }
-        }
+    #[test]
+    fn test_one_time_pad_letters() {
+        let otp = OneTimePad::letters("xmckl qz").unwrap();
+        assert_eq!(otp.pad(), b"XMCKLQZ");
+        assert_eq!(otp.encipher("Hello, ?x!").unwrap(), "Eqnvz, ?w!");
+        assert_eq!(otp.decipher("Eqnvz, ?w!").unwrap(), "Hello, ?x!");
+        assert!(otp.encipher("Hello, world").is_err());
+        assert!(otp.xor(b"data").is_err());
+        assert!(OneTimePad::letters("XMC-KL").is_err());
+    }
 
-        // Handle the last number if any
-        if !number_buffer.is_empty() {
-            let number: u32 = number_buffer.parse().unwrap();
-            let char_code = if number <= 26 {
-                'a' as u32 + number - 1
-            } else if number <= 52 {
-                'A' as u32 + number - 27
-            } else {
-                // Ignore invalid numbers
-                return decrypted_text;
-            };
-            decrypted_text.push(char::from_u32(char_code).unwrap());
-        }
+    #[test]
+    fn test_one_time_pad_bytes() {
+        let otp = OneTimePad::bytes(&[0x0f, 0xf0, 0xff]);
+        assert_eq!(
+            otp.xor(&[0xff, 0xff, 0x00]).unwrap(),
+            vec![0xf0, 0x0f, 0xff]
+        );
+        assert_eq!(otp.xor(&[]).unwrap(), Vec::<u8>::new());
+        assert!(otp.xor(&[1, 2, 3, 4]).is_err());
+        assert!(otp.encipher("abc").is_err());
+    }
+
+    #[test]
+    fn test_one_time_pad_generate() {
+        let otp = OneTimePad::generate(PadMode::Letters, 500).unwrap();
+        assert_eq!(otp.mode(), PadMode::Letters);
+        assert_eq!(otp.pad().len(), 500);
+        assert!(otp.pad().iter().all(u8::is_ascii_uppercase));
+        let message = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            otp.decipher(&otp.encipher(message).unwrap()).unwrap(),
+            message
+        );
 
-        decrypted_text
+        let otp = OneTimePad::generate(PadMode::Bytes, 64).unwrap();
+        assert_eq!(otp.pad().len(), 64);
+        let other = OneTimePad::generate(PadMode::Bytes, 64).unwrap();
+        assert_ne!(otp.pad(), other.pad());
     }
-}
 
+    #[test]
+    fn test_table_cipher() {
+        let table = TableCipher::from_csv("# NATO\nA,\"ALFA \"\nB,\"BRAVO \"\nAB,\"ALFA, BRAVO \"")
+            .unwrap();
+        assert_eq!(table.encipher("ABBA"), "ALFA, BRAVO BRAVO ALFA ");
+        assert_eq!(table.decipher("ALFA, BRAVO BRAVO ALFA "), "ABBA");
 
+        let syllables = TableCipher::from_csv("ka\t1\nki\t12\nko\t21").unwrap();
+        assert_eq!(syllables.encipher("kakiko"), "11221");
+        assert!(!syllables.is_uniquely_decodable());
+        assert!(TableCipher::from_csv("a,1\nb,01\nc,001")
+            .unwrap()
+            .is_uniquely_decodable());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_table_cipher_rejects_ambiguous_table() {
+        assert!(TableCipher::from_csv("a,1\nb,1").is_err());
+        assert!(TableCipher::from_csv("a,1\na,2").is_err());
+        assert!(TableCipher::from_csv("a,1,2").is_err());
+    }
 
     #[test]
-    fn test_rot13_encipher() {
-        let rot13 = Rot13Cipher::new();
-        let input = "Hello, World!";
-        let expected = "Uryyb, Jbeyq!";
-        assert_eq!(rot13.encipher(input), expected);
+    fn test_nomenclator_cipher() {
+        let nomenclator = NomenclatorCipher::new(vec![
+            ("the king".to_string(), vec!["125".to_string()]),
+            ("th".to_string(), vec!["7".to_string(), "70".to_string()]),
+            (" ".to_string(), vec!["0".to_string()]),
+            ("e".to_string(), vec!["5".to_string()]),
+            ("i".to_string(), vec!["9".to_string()]),
+            ("s".to_string(), vec!["19".to_string()]),
+            (String::new(), vec!["44".to_string()]),
+        ])
+        .unwrap();
+        assert_eq!(nomenclator.len(), 6);
+        assert_eq!(nomenclator.encipher("The King is!"), "125 0 9 19");
+        assert_eq!(nomenclator.encipher("this, the"), "7 9 19 0 70 5");
+        // Letters without a code are marked, the rest dropped
+        assert_eq!(nomenclator.encipher("tax"), "? ? ?");
+        assert_eq!(
+            nomenclator.decipher("125 44 0 9.19 0 70 5 31 ?"),
+            "the king is the??"
+        );
     }
 
     #[test]
-    fn test_rot13_decipher() {
-        let rot13 = Rot13Cipher::new();
-        let input = "Uryyb, Jbeyq!";
-        let expected = "Hello, World!";
-        assert_eq!(rot13.decipher(input), expected);
+    fn test_nomenclator_cipher_rejects_invalid_table() {
+        assert!(NomenclatorCipher::from_csv("a,1\nb,1").is_err());
+        assert!(NomenclatorCipher::from_csv("a,1\nA,2").is_err());
+        assert!(NomenclatorCipher::from_csv("a,x1").is_err());
+        assert!(NomenclatorCipher::from_csv("a").is_err());
+        assert!(NomenclatorCipher::from_csv("\"a\",\"\"").is_err());
+        assert!(NomenclatorCipher::from_csv("a\t1\t2\n\t3").is_ok());
     }
 
-   
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_nomenclator_serde() {
+        let nomenclator: NomenclatorCipher =
+            serde_json::from_str(r#"{"le roi": 125, "an": [41, "078"], "": 99}"#).unwrap();
+        assert_eq!(nomenclator.encipher("Le roi an an"), "125 41 078");
+        assert_eq!(nomenclator.decipher("125 99 078"), "le roian");
+        let json = serde_json::to_string(&nomenclator).unwrap();
+        assert_eq!(json, r#"{"":"99","an":["41","078"],"le roi":"125"}"#);
+        assert_eq!(
+            serde_json::from_str::<NomenclatorCipher>(&json).unwrap(),
+            nomenclator
+        );
+        assert!(serde_json::from_str::<NomenclatorCipher>(r#"{"a": 1, "b": "1"}"#).is_err());
+        assert!(serde_json::from_str::<NomenclatorCipher>(r#"{"a": []}"#).is_err());
+        assert!(serde_json::from_str::<NomenclatorCipher>(r#"{"a": true}"#).is_err());
+    }
 
     #[test]
-    fn test_caesar_encipher() {
-        let caesar = CaesarCipher::new(3);
-        let input = "Hello, World!";
-        let expected = "Khoor, Zruog!";
-        assert_eq!(caesar.encipher(input), expected);
+    fn test_baudot_code() {
+        let baudot = BaudotCode::new();
+        let text = "Ryry 73, de K1ABC.\nEnd";
+        assert_eq!(baudot.decode(&baudot.encode(text)), text.to_uppercase());
+        // Shift codes only where the shift changes, space in either shift
+        assert_eq!(baudot.codes("1 2a"), [0x1b, 0x17, 0x04, 0x13, 0x1f, 0x03]);
+        assert_eq!(baudot.codes("a~b\r\n"), [0x03, 0x19, 0x08, 0x02]);
+        assert_eq!(baudot.decode("110001100011000"), "AAA");
+        assert_eq!(baudot.decode("11000 1100 22222"), "A??");
+
+        let hex = baudot.with_format(BaudotFormat::Hex);
+        assert_eq!(hex.decode("1B 17 03 +1 ff"), "1-??");
+        assert_eq!(hex.decode(&hex.encode(text)), text.to_uppercase());
+
+        let tape = baudot.with_format(BaudotFormat::Tape);
+        assert_eq!(tape.encode("Hi"), "|  .o o|\n| o.o  |");
+        assert_eq!(tape.decode("|  .o o|\n\n| o.o  |\n|x|"), "HI?");
+        assert_eq!(tape.decode(&tape.encode(text)), text.to_uppercase());
+
+        let bletchley = baudot.with_format(BaudotFormat::Bletchley);
+        assert_eq!(bletchley.encode("Tunny 1944\n"), "TUNNY95QORR43");
+        assert_eq!(bletchley.read("/+-?"), [0, ITA2_FIGS, ITA2_LTRS, u8::MAX]);
+        assert_eq!(
+            bletchley.decode(&bletchley.encode(text)),
+            text.to_uppercase()
+        );
     }
 
     #[test]
-    fn test_caesar_decipher() {
-        let caesar = CaesarCipher::new(3);
-        let input = "Khoor, Zruog!";
-        let expected = "Hello, World!";
-        assert_eq!(caesar.decipher(input), expected);
+    fn test_tap_code() {
+        let tap = TapCode::new();
+        assert_eq!(
+            tap.encipher("Kick it!"),
+            ". ...  .. ....  . ...  . ...  /  .. ....  .... ...."
+        );
+        assert_eq!(tap.decipher(&tap.encipher("Kick it!")), "CICC IT");
+        // Spacing, marks and digits do not matter
+        assert_eq!(tap.decipher("** ***   •• ····\n1 1"), "HIA");
+        assert_eq!(tap.decipher("...... . / .."), "? ?");
+
+        let custom = TapCode::new()
+            .with_dot('+')
+            .unwrap()
+            .with_separators(",", " ")
+            .unwrap();
+        assert_eq!(custom.encipher("no"), "+++,+++ +++,++++");
+        assert_eq!(custom.decipher("+++,+++ +++,++++"), "NO");
+        assert!(TapCode::new().with_dot('/').is_err());
+        assert!(TapCode::new().with_separators(".", " ").is_err());
+        assert!(TapCode::new().with_separators(" ", "").is_err());
     }
 
     #[test]
-    fn test_vigenere_encipher() {
-        let vigenere = VigenereCipher::new("LEMON");
-        let input = "ATTACKATDAWN";
-        let expected = "LXFOPVEFRNHR";
-        assert_eq!(vigenere.encipher(input), expected);
+    fn test_pigpen() {
+        let pigpen = PigpenCipher::new();
+        let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        assert_eq!(pigpen.decipher(&pigpen.encipher(alphabet)), alphabet);
+        assert_eq!(pigpen.encipher("Ja, wz!"), "⌟\u{307}⌟, ∨\u{307}∧\u{307}!");
+
+        let art = PigpenCipher::art("The End\nsv");
+        assert_eq!(PigpenCipher::from_art(&art), "THE END\nSV");
+        assert_eq!(PigpenCipher::from_art("+-+\n| |\n+ +"), "?");
+
+        let glyphs: Vec<String> = (1..=26).map(|n| format!("<{}>", n)).collect();
+        let glyphs: Vec<&str> = glyphs.iter().map(String::as_str).collect();
+        let numbered = PigpenCipher::with_glyphs(&glyphs).unwrap();
+        assert_eq!(numbered.encipher("Abz"), "<1><2><26>");
+        assert_eq!(numbered.decipher("<1><2><26>"), "ABZ");
+        assert!(PigpenCipher::with_glyphs(&["a"; 26]).is_err());
+        assert!(PigpenCipher::with_glyphs(&["a", "b"]).is_err());
     }
 
-    // #[test]
-    // fn test_vigenere_decipher() {
-    //     let vigenere = VigenereCipher::new("LEMON");
-    //     let input = "LXFOPVEFRNHR";
-    //     let expected = "ATTACKATDAWN";
-    //     assert_eq!(vigenere.decipher(input), expected);
-    // }
     #[test]
-    fn test_morse_code_encode() {
-        let morse_code = MorseCode::new();
-        let input = "HELLO";
-        let expected = ".... . .-.. .-.. ---";
-        assert_eq!(morse_code.encode(input), expected);
+    fn test_bacon() {
+        let historic = BaconCipher::new(BaconAlphabet::Historic24);
+        assert_eq!(historic.encipher("KW"), "ABAAB BABAA");
+        // J and V share the codes of I and U
+        assert_eq!(historic.encipher("jv"), historic.encipher("IU"));
+        assert_eq!(historic.decipher("ABAAB BABAA"), "KW");
+
+        let full = BaconCipher::new(BaconAlphabet::Full26)
+            .with_symbols('0', '1')
+            .unwrap();
+        assert_eq!(full.encipher("Hello!"), "00111 00100 01011 01011 01110");
+        assert_eq!(full.decipher("00111 00100 01011 01011 01110"), "HELLO");
+        assert!(BaconCipher::new(BaconAlphabet::Full26)
+            .with_symbols('x', 'x')
+            .is_err());
     }
 
     #[test]
-    fn test_morse_code_decode() {
-        let morse_code = MorseCode::new();
-        let input = ".... . .-.. .-.. ---";
-        let expected = "HELLO";
-        assert_eq!(morse_code.decode(input), expected);
+    fn test_bacon_steganography() {
+        let bacon = BaconCipher::new(BaconAlphabet::Historic24);
+        let hidden = bacon.hide("Hi", "Nothing to see here.").unwrap();
+        assert_eq!(hidden, "noTHInG to see here.");
+        assert_eq!(bacon.reveal(&hidden), "HI");
+        assert_eq!(bacon.reveal("no capitals here"), "");
+        assert!(bacon.hide("Hello", "too short").is_err());
     }
 
     #[test]
-    fn test_atbash_encipher() {
-        let atbash = AtbashCipher::new();
-        let input = "ATTACKATDAWN";
-        let expected = "ZGGZXPZGWZDM";
-        assert_eq!(atbash.encipher(input), expected);
+    fn test_numeric_substitution() {
+        assert_eq!(
+            NumericSubstitution::positions().encipher("Hi, Bob"),
+            "8 9 , / 2 15 2"
+        );
+        assert_eq!(NumericSubstitution::squares().encipher("AZ"), "1 676");
+        assert_eq!(NumericSubstitution::primes().encipher("Z"), "101");
+
+        let ascii = NumericSubstitution::ascii(-10);
+        assert_eq!(ascii.encipher("Cache"), "57 55 57 62 59");
+        assert_eq!(ascii.decipher("57 55 57 62 59 / 1"), "CACHE ?");
+        assert!(NumericSubstitution::from_fn(|i| i as i64 % 13).is_err());
     }
 
     #[test]
-    fn test_atbash_decipher() {
-        let atbash = AtbashCipher::new();
-        let input = "ZGGZXPZGWZDM";
-        let expected = "ATTACKATDAWN";
-        assert_eq!(atbash.decipher(input), expected);
+    fn test_transposition_permutations() {
+        trait CipherTransposition: Cipher + Transposition {}
+        impl<T: Cipher + Transposition> CipherTransposition for T {}
+
+        let text = "WEAREDISCOVEREDFLEEATONCE";
+        let chars: Vec<char> = text.chars().collect();
+        let grille: Grille = "X.../.X.X/..../.X..".parse().unwrap();
+        let ciphers: Vec<Box<dyn CipherTransposition>> = vec![
+            Box::new(ColumnarTranspositionCipher::new("ZEBRAS").unwrap()),
+            Box::new(AmscoCipher::new("4132").unwrap()),
+            Box::new(DoubleTranspositionCipher::new("CAB", "DOG").unwrap()),
+            Box::new(InterleaveCipher::new(3).unwrap()),
+            Box::new(RailFenceCipher::new(4).unwrap().with_offset(1)),
+            Box::new(ScytaleCipher::new(4).unwrap()),
+            Box::new(CaesarBoxCipher::new()),
+            Box::new(GrilleCipher::new(grille.clone())),
+            Box::new(BoustrophedonCipher::new(4).unwrap()),
+            Box::new(SpiralCipher::new(4, SpiralDirection::Outward).unwrap()),
+            Box::new(RouteCipher::new(3, 2, Route::SpiralCounterclockwise).unwrap()),
+        ];
+        for cipher in &ciphers {
+            let permutation = cipher.permutation(chars.len());
+            let mut sorted = permutation.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..chars.len()).collect::<Vec<_>>());
+            let permuted: String = grid::permute(&chars, &permutation).into_iter().collect();
+            assert_eq!(permuted, cipher.encipher(text));
+        }
+
+        // Padding takes positions that no character of the plaintext moves to
+        let padded: Vec<Box<dyn CipherTransposition>> = vec![
+            Box::new(
+                ColumnarTranspositionCipher::new("ZEBRAS")
+                    .unwrap()
+                    .with_padding('#'),
+            ),
+            Box::new(ScytaleCipher::new(4).unwrap().with_padding('#')),
+            Box::new(GrilleCipher::new(grille).with_padding('#')),
+        ];
+        for cipher in &padded {
+            let permutation = cipher.permutation(chars.len());
+            let ciphertext: Vec<char> = cipher.encipher(text).chars().collect();
+            for (i, &position) in permutation.iter().enumerate() {
+                assert_eq!(ciphertext[position], chars[i]);
+            }
+        }
+
+        let decimation = DecimationCipher::new(3).unwrap();
+        let permutation = decimation.permutation(8).unwrap();
+        let permuted: String = grid::permute(&"GEOCACHE".chars().collect::<Vec<_>>(), &permutation)
+            .into_iter()
+            .collect();
+        assert_eq!(permuted, "GCHEAEOC");
+        assert!(decimation.permutation(9).is_err());
+    }
+
+    #[test]
+    fn test_unknown_tokens() {
+        let squares = NumericSubstitution::squares();
+        assert_eq!(squares.unknown_token("1 4 , / 9"), None);
+        // Positions count characters, not bytes
+        assert_eq!(
+            squares.unknown_token("é\t1  17 4"),
+            Some(UnknownToken {
+                token: "17".to_string(),
+                position: 5
+            })
+        );
+        let unknown = MorseCode::new().unknown_token(".-  / ..-.-").unwrap();
+        assert_eq!(unknown.to_string(), "Unknown token \"..-.-\" at position 6");
+        assert_eq!(MorseCode::new().unknown_token(".- / -..."), None);
     }
 
     #[test]
     fn test_alpha_to_num() {
         let converter = AlphaNumConverter::new();
         let input = "Hello, World!";
-        let expected = "8 5 12 12 15 ,   23 15 18 12 4 !";
+        let expected = "8 5 12 12 15 , / 23 15 18 12 4 !";
         assert_eq!(converter.alpha_to_num(input), expected);
     }
 
     #[test]
     fn test_num_to_alpha() {
         let converter = AlphaNumConverter::new();
-        let input = "8 5 12 12 15 , 23 15 18 12 4 !";
-        let expected = "helloworld";
+        let input = "8 5 12 12 15 , / 23 15 18 12 4 !";
+        let expected = "HELLO, WORLD!";
         assert_eq!(converter.num_to_alpha(input), expected);
     }
 }
@@ -0,0 +1,480 @@
+//! Reference vectors from well-known implementations, and the conventions cipha follows.
+//!
+//! Classical ciphers leave details open: whether spaces use up key letters, which letter shares
+//! a cell of a 5x5 square, how ties in a transposition key are broken, whether a bifid works in
+//! blocks. Two correct implementations can disagree on all of them. [`verify_conventions`] runs
+//! every cipher against worked examples published by a reference source (Wikipedia, Practical
+//! Cryptography, dCode) and reports the convention it follows, so integrators know whose outputs
+//! they will get.
+//!
+//! This module is only built with the `compat` feature.
+//!
+//! ```rust
+//! use cipha::compat::verify_conventions;
+//!
+//! for report in verify_conventions() {
+//!     assert!(report.passed(), "{:?}", report.failures);
+//!     println!("{} ({}): {}", report.cipher, report.reference, report.convention);
+//! }
+//! ```
+
+use crate::ciphers::{
+    AdfgvxCipher, AffineCipher, AtbashCipher, BifidCipher, CaesarCipher, Cipher,
+    ColumnarTranspositionCipher, HillCipher, KeySchedule, MorseCode, NihilistCipher,
+    PlayfairCipher, PolyalphabeticCipher, PolybiusGrid, PolybiusSquare, PortaCipher, Rot13Cipher,
+    TrifidCipher,
+};
+use crate::machines::{EnigmaMachine, Reflector, Rotor};
+
+/// The outcome of checking one cipher against its reference vectors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionReport {
+    /// The cipher, named as on the command line.
+    pub cipher: &'static str,
+    /// The convention the cipher follows where implementations differ.
+    pub convention: &'static str,
+    /// Where the reference vectors come from.
+    pub reference: &'static str,
+    /// The number of vectors checked.
+    pub vectors: usize,
+    /// A description of every vector that did not match.
+    pub failures: Vec<String>,
+}
+
+impl ConventionReport {
+    /// Returns `true` if every vector matched.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+// A worked example: the key in the cipher's own notation, the plaintext and the ciphertext.
+struct Vector {
+    key: &'static str,
+    plaintext: &'static str,
+    ciphertext: &'static str,
+}
+
+// A cipher with its convention, reference vectors and a way to build it from a vector's key.
+struct Reference {
+    cipher: &'static str,
+    convention: &'static str,
+    reference: &'static str,
+    build: fn(&str) -> Result<Box<dyn Cipher>, String>,
+    vectors: &'static [Vector],
+}
+
+const REFERENCES: &[Reference] = &[
+    Reference {
+        cipher: "caesar",
+        convention: "Shifts letters forward, keeping their case; other characters are unchanged",
+        reference: "Wikipedia",
+        build: |key| Ok(Box::new(CaesarCipher::new(number(key)?))),
+        vectors: &[Vector {
+            key: "3",
+            plaintext: "The quick brown fox jumps over the lazy dog",
+            ciphertext: "Wkh txlfn eurzq ira mxpsv ryhu wkh odcb grj",
+        }],
+    },
+    Reference {
+        cipher: "rot13",
+        convention: "Keeps the case of letters; other characters are unchanged",
+        reference: "Wikipedia",
+        build: |_| Ok(Box::new(Rot13Cipher::new())),
+        vectors: &[Vector {
+            key: "",
+            plaintext: "Why did the chicken cross the road?",
+            ciphertext: "Jul qvq gur puvpxra pebff gur ebnq?",
+        }],
+    },
+    Reference {
+        cipher: "atbash",
+        convention: "Reverses the Latin alphabet, keeping the case of letters",
+        reference: "Wikipedia",
+        build: |_| Ok(Box::new(AtbashCipher::new())),
+        vectors: &[Vector {
+            key: "",
+            plaintext: "wizard",
+            ciphertext: "draziw",
+        }],
+    },
+    Reference {
+        cipher: "affine",
+        convention: "E(x) = (ax + b) mod 26 with A = 0; the key is written a,b",
+        reference: "Wikipedia",
+        build: |key| {
+            let (a, b) = key.split_once(',').ok_or("expected a,b")?;
+            Ok(Box::new(AffineCipher::new(number(a)?, number(b)?)?))
+        },
+        vectors: &[Vector {
+            key: "5,8",
+            plaintext: "AFFINE CIPHER",
+            ciphertext: "IHHWVC SWFRCP",
+        }],
+    },
+    Reference {
+        cipher: "vigenere",
+        convention: "Key letters count from A = 0; characters other than letters are copied and \
+                     do not use up the key, as in dCode's default",
+        reference: "Wikipedia, dCode",
+        build: |key| Ok(Box::new(PolyalphabeticCipher::vigenere(key))),
+        vectors: &[
+            Vector {
+                key: "LEMON",
+                plaintext: "ATTACKATDAWN",
+                ciphertext: "LXFOPVEFRNHR",
+            },
+            Vector {
+                key: "LEMON",
+                plaintext: "Attack at dawn!",
+                ciphertext: "Lxfopv ef rnhr!",
+            },
+        ],
+    },
+    Reference {
+        cipher: "autokey",
+        convention: "The keyword is followed by the plaintext",
+        reference: "Wikipedia",
+        build: |key| {
+            Ok(Box::new(
+                PolyalphabeticCipher::vigenere(key).with_schedule(KeySchedule::Autokey),
+            ))
+        },
+        vectors: &[Vector {
+            key: "QUEENLY",
+            plaintext: "ATTACKATDAWN",
+            ciphertext: "QNXEPVYTWTWP",
+        }],
+    },
+    Reference {
+        cipher: "beaufort",
+        convention: "C = K - P mod 26, the reciprocal form (not the variant Beaufort, C = P - K)",
+        reference: "Practical Cryptography",
+        build: |key| Ok(Box::new(PolyalphabeticCipher::beaufort(key))),
+        vectors: &[Vector {
+            key: "FORTIFICATION",
+            plaintext: "DEFENDTHEEASTWALL",
+            ciphertext: "CKMPVCPVWPIWUJOGI",
+        }],
+    },
+    Reference {
+        cipher: "porta",
+        convention: "The modern table, as in Practical Cryptography and dCode; della Porta's \
+                     own table, used by the ACA, is PortaTable::Historical",
+        reference: "Practical Cryptography",
+        build: |key| Ok(Box::new(PortaCipher::new(key))),
+        vectors: &[Vector {
+            key: "FORTIFICATION",
+            plaintext: "DEFENDTHEEASTWALL",
+            ciphertext: "SYNNJSCVRNRLAHUTU",
+        }],
+    },
+    Reference {
+        cipher: "hill",
+        convention: "The key matrix multiplies column vectors of plaintext, with A = 0 and the \
+                     key letters read row by row",
+        reference: "Wikipedia",
+        build: |key| {
+            let values: Vec<i64> = key.bytes().map(|b| (b - b'A') as i64).collect();
+            let size = (values.len() as f64).sqrt() as usize;
+            Ok(Box::new(HillCipher::new(
+                values.chunks(size.max(1)).map(<[i64]>::to_vec).collect(),
+            )?))
+        },
+        vectors: &[
+            Vector {
+                key: "GYBNQKURP",
+                plaintext: "ACT",
+                ciphertext: "POH",
+            },
+            Vector {
+                key: "GYBNQKURP",
+                plaintext: "CAT",
+                ciphertext: "FIN",
+            },
+        ],
+    },
+    Reference {
+        cipher: "polybius",
+        convention: "Row then column, numbered from 1; J shares the cell of I",
+        reference: "Wikipedia",
+        build: |_| Ok(Box::new(PolybiusSquare::new(PolybiusGrid::FiveByFive))),
+        vectors: &[Vector {
+            key: "",
+            plaintext: "Hello",
+            ciphertext: "23 15 31 31 34",
+        }],
+    },
+    Reference {
+        cipher: "playfair",
+        convention: "Letters are paired after dropping everything else; X splits a doubled \
+                     letter and completes a lone last one, and is kept when deciphering. J \
+                     shares the cell of I",
+        reference: "Wikipedia",
+        build: |key| Ok(Box::new(PlayfairCipher::new(PolybiusGrid::FiveByFive, key))),
+        vectors: &[Vector {
+            key: "PLAYFAIR EXAMPLE",
+            // "Hide the gold in the tree stump", split into pairs with the X in "TREE"
+            plaintext: "HI DE TH EG OL DI NT HE TR EX ES TU MP",
+            ciphertext: "BMODZBXDNABEKUDMUIXMMOUVIF",
+        }],
+    },
+    Reference {
+        cipher: "bifid",
+        convention: "Each block of `period` letters is written as its row labels followed by its \
+                     column labels, as in Practical Cryptography and dCode; a period as long as \
+                     the message gives Delastelle's unbroken form. J shares the cell of I",
+        reference: "Practical Cryptography, Wikipedia",
+        build: |key| {
+            let (keyword, period) = key.split_once(',').ok_or("expected keyword,period")?;
+            Ok(Box::new(BifidCipher::new(keyword, number(period)?)?))
+        },
+        vectors: &[
+            Vector {
+                key: "PHQGMEAYLNOFDXKRCVSZWBUTI,5",
+                plaintext: "defend the east wall of the castle",
+                ciphertext: "FFYHMKHYCPLIASHADTRLHCCHLBLR",
+            },
+            Vector {
+                key: "BGWKZQPNDSIOAXEFCLUMTHYVR,10",
+                plaintext: "FLEEATONCE",
+                ciphertext: "UAEOLWRINS",
+            },
+        ],
+    },
+    Reference {
+        cipher: "trifid",
+        convention:
+            "Layer, row and column of a 27-cell cube with + as the extra symbol, in blocks \
+                     of `period` characters",
+        reference: "Practical Cryptography",
+        build: |key| {
+            let (keyword, period) = key.split_once(',').ok_or("expected keyword,period")?;
+            Ok(Box::new(TrifidCipher::new(keyword, number(period)?)?))
+        },
+        vectors: &[Vector {
+            key: "EPSDUCVWYM+ZLKXNBTFGORIJHAQ,5",
+            plaintext: "defend the east wall of the castle+",
+            ciphertext: "SUEFECPHSEGYYJIXIMFOFOCEJLBSP",
+        }],
+    },
+    Reference {
+        cipher: "adfgx",
+        convention: "Row label then column label, transposed with the key letters in alphabetical \
+                     order and ties broken left to right",
+        reference: "Wikipedia",
+        build: |key| {
+            let (square, keyword) = key.split_once(',').ok_or("expected square,keyword")?;
+            Ok(Box::new(AdfgvxCipher::new(
+                PolybiusGrid::FiveByFive,
+                square,
+                keyword,
+            )?))
+        },
+        vectors: &[Vector {
+            key: "BTALPDHOZKQFVSNGICUXMREWY,CARGO",
+            plaintext: "ATTACK AT ONCE",
+            ciphertext: "FAXDFADDDGDGFFFAFAXAFAFX",
+        }],
+    },
+    Reference {
+        cipher: "columnar",
+        convention: "Incomplete (irregular) transposition unless padding is set; columns are read \
+                     in the alphabetical order of the key letters, ties left to right",
+        reference: "Wikipedia",
+        build: |key| Ok(Box::new(ColumnarTranspositionCipher::new(key)?)),
+        vectors: &[Vector {
+            key: "ZEBRAS",
+            plaintext: "WEAREDISCOVEREDFLEEATONCE",
+            ciphertext: "EVLNACDTESEAROFODEECWIREE",
+        }],
+    },
+    Reference {
+        cipher: "nihilist",
+        convention: "Row and column numbers from 1 are added without carrying past 100, so sums \
+                     may have three digits",
+        reference: "Wikipedia",
+        build: |key| {
+            let (square, keyword) = key.split_once(',').ok_or("expected square,keyword")?;
+            Ok(Box::new(NihilistCipher::new(square, keyword)?))
+        },
+        vectors: &[Vector {
+            key: "ZEBRAS,RUSSIAN",
+            plaintext: "dynamite",
+            ciphertext: "37 106 62 36 67 47 86 26",
+        }],
+    },
+    Reference {
+        cipher: "morse",
+        convention: "International Morse code, letters separated by spaces and words by /",
+        reference: "ITU-R M.1677",
+        build: |_| Ok(Box::new(MorseCode::new())),
+        vectors: &[Vector {
+            key: "",
+            plaintext: "SOS",
+            ciphertext: "... --- ...",
+        }],
+    },
+    Reference {
+        cipher: "enigma",
+        convention: "Enigma I / M3 with the double step of the middle rotor; the key is written \
+                     rotors|reflector|rings|positions|plugboard",
+        reference: "Wikipedia, the Operation Barbarossa message",
+        build: |key| {
+            let fields: Vec<&str> = key.split('|').collect();
+            let [rotors, reflector, rings, positions, plugboard] = fields[..] else {
+                return Err("expected rotors|reflector|rings|positions|plugboard".to_string());
+            };
+            let rotors: Vec<Rotor> = rotors
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()?;
+            let rotors: [Rotor; 3] = rotors.try_into().map_err(|_| "expected three rotors")?;
+            let reflector: Reflector = reflector.parse()?;
+            Ok(Box::new(
+                EnigmaMachine::new(rotors, reflector)?
+                    .with_rings(rings)?
+                    .with_positions(positions)?
+                    .with_plugboard(plugboard)?,
+            ))
+        },
+        vectors: &[
+            Vector {
+                key: "I II III|B|AAA|AAA|",
+                plaintext: "AAAAA",
+                ciphertext: "BDZGO",
+            },
+            Vector {
+                key: "II IV V|B|02 21 12|BLA|AV BS CG DL FU HZ IN KM OW RX",
+                plaintext: "AUFKL XABTE ILUNG",
+                ciphertext: "EDPUD NRGYS ZRCXN",
+            },
+        ],
+    },
+];
+
+// Parses a number in a vector's key.
+fn number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("expected a number, found {:?}", text))
+}
+
+// Keeps the letters and digits of a text in uppercase, for comparing deciphered text, which may
+// have lost its case, spacing and punctuation.
+fn letters(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Checks every cipher against its reference vectors, reporting the convention it follows.
+///
+/// Each vector is enciphered and compared exactly, then deciphered and compared ignoring case,
+/// spaces and punctuation, which some ciphers drop.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::compat::verify_conventions;
+///
+/// let reports = verify_conventions();
+/// let bifid = reports.iter().find(|r| r.cipher == "bifid").unwrap();
+/// assert!(bifid.passed());
+/// assert!(bifid.convention.starts_with("Each block of `period` letters"));
+/// ```
+pub fn verify_conventions() -> Vec<ConventionReport> {
+    REFERENCES.iter().map(check).collect()
+}
+
+// Checks one cipher against its reference vectors.
+fn check(reference: &Reference) -> ConventionReport {
+    let mut failures = Vec::new();
+    for vector in reference.vectors {
+        let cipher = match (reference.build)(vector.key) {
+            Ok(cipher) => cipher,
+            Err(e) => {
+                failures.push(format!("Key {:?} was rejected: {}", vector.key, e));
+                continue;
+            }
+        };
+        let ciphertext = cipher.encipher(vector.plaintext);
+        if ciphertext != vector.ciphertext {
+            failures.push(format!(
+                "Key {:?} enciphered {:?} as {:?}, expected {:?}",
+                vector.key, vector.plaintext, ciphertext, vector.ciphertext
+            ));
+        }
+        let plaintext = cipher.decipher(vector.ciphertext);
+        if letters(&plaintext) != letters(vector.plaintext) {
+            failures.push(format!(
+                "Key {:?} deciphered {:?} as {:?}, expected {:?}",
+                vector.key, vector.ciphertext, plaintext, vector.plaintext
+            ));
+        }
+    }
+    ConventionReport {
+        cipher: reference.cipher,
+        convention: reference.convention,
+        reference: reference.reference,
+        vectors: reference.vectors.len(),
+        failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_conventions() {
+        let reports = verify_conventions();
+        for report in &reports {
+            assert!(report.passed(), "{}: {:?}", report.cipher, report.failures);
+            assert!(report.vectors > 0, "{}", report.cipher);
+        }
+        let mut names: Vec<&str> = reports.iter().map(|r| r.cipher).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), reports.len());
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let reference = Reference {
+            cipher: "caesar",
+            convention: "",
+            reference: "",
+            build: |key| Ok(Box::new(CaesarCipher::new(number(key)?))),
+            vectors: &[
+                Vector {
+                    key: "3",
+                    plaintext: "abc",
+                    ciphertext: "def",
+                },
+                Vector {
+                    key: "4",
+                    plaintext: "abc",
+                    ciphertext: "def",
+                },
+                Vector {
+                    key: "four",
+                    plaintext: "abc",
+                    ciphertext: "efg",
+                },
+            ],
+        };
+        let report = check(&reference);
+        assert!(!report.passed());
+        assert_eq!(report.vectors, 3);
+        assert_eq!(
+            report.failures,
+            vec![
+                "Key \"4\" enciphered \"abc\" as \"efg\", expected \"def\"",
+                "Key \"4\" deciphered \"def\" as \"zab\", expected \"abc\"",
+                "Key \"four\" was rejected: expected a number, found \"four\"",
+            ]
+        );
+    }
+}
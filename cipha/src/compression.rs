@@ -0,0 +1,225 @@
+//! Compression stages for pipelines.
+//!
+//! Layered puzzles often compress a message between cipher layers. Pipeline stages pass text,
+//! so the compressed bytes travel between stages as base64: [`Compress`] turns text into base64
+//! of its compressed bytes and [`Decompress`] turns such base64 back into text. Each codec needs
+//! its feature: `gzip` or `zstd`. Decompressing fails on text that is not valid base64 of
+//! compressed data, so both stages are [`FallibleCipher`]s.
+//!
+//! ```rust
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use cipha::ciphers::Rot13Cipher;
+//! use cipha::compression::{Codec, Compress, Decompress};
+//! use cipha::pipeline::Pipeline;
+//!
+//! // The puzzle: rot13, then gzip, then base64
+//! let puzzle = Pipeline::new()
+//!     .then(Rot13Cipher::new())
+//!     .then_fallible(Compress::new(Codec::Gzip))
+//!     .encipher("Hello, World!")
+//!     .unwrap();
+//! let unwrap = Pipeline::new()
+//!     .then_fallible(Decompress::new(Codec::Gzip))
+//!     .then(Rot13Cipher::new());
+//! assert_eq!(unwrap.encipher(&puzzle).unwrap(), "Hello, World!");
+//! assert!(unwrap.encipher("!!").is_err());
+//! # }
+//! ```
+
+use std::io::Read;
+
+use crate::binary::{from_base64, to_base64};
+use crate::ciphers::FallibleCipher;
+
+/// A compression format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Gzip (RFC 1952).
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard (RFC 8878).
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// Compresses the bytes.
+pub fn compress(codec: Codec, data: &[u8]) -> Vec<u8> {
+    match codec {
+        #[cfg(feature = "gzip")]
+        Codec::Gzip => {
+            use std::io::Write;
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder
+                .write_all(data)
+                .and_then(|_| encoder.finish())
+                .expect("Compressing into memory cannot fail")
+        }
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => zstd::encode_all(data, zstd::DEFAULT_COMPRESSION_LEVEL)
+            .expect("Compressing into memory cannot fail"),
+    }
+}
+
+/// Decompresses the bytes.
+///
+/// # Errors
+///
+/// Returns an error if the data is not valid for the codec.
+pub fn decompress(codec: Codec, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decompressed = Vec::new();
+    let result = match codec {
+        #[cfg(feature = "gzip")]
+        Codec::Gzip => flate2::read::GzDecoder::new(data).read_to_end(&mut decompressed),
+        #[cfg(feature = "zstd")]
+        Codec::Zstd => zstd::Decoder::new(data).and_then(|mut d| d.read_to_end(&mut decompressed)),
+    };
+    result.map_err(|e| format!("Could not decompress: {}", e))?;
+    Ok(decompressed)
+}
+
+// Decodes base64 and decompresses it into text.
+fn decompress_text(codec: Codec, base64: &str) -> Result<String, String> {
+    let data = decompress(codec, &from_base64(base64)?)?;
+    String::from_utf8(data).map_err(|_| "Decompressed data is not UTF-8 text".to_string())
+}
+
+/// A pipeline stage that compresses text into base64; deciphering decompresses it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Compress {
+    codec: Codec,
+}
+
+impl Compress {
+    /// Creates a compression stage with the given codec.
+    pub fn new(codec: Codec) -> Self {
+        Compress { codec }
+    }
+
+    /// Compresses the plaintext into base64.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        to_base64(&compress(self.codec, plaintext.as_bytes()))
+    }
+
+    /// Decompresses the base64 ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext is not base64 of compressed UTF-8 text.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        decompress_text(self.codec, ciphertext)
+    }
+}
+
+impl FallibleCipher for Compress {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        Ok(self.encipher(plaintext))
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A pipeline stage that decompresses base64 into text; deciphering compresses it.
+///
+/// This is [`Compress`] run backwards, for pipelines written in the order a puzzle is unwrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decompress {
+    codec: Codec,
+}
+
+impl Decompress {
+    /// Creates a decompression stage with the given codec.
+    pub fn new(codec: Codec) -> Self {
+        Decompress { codec }
+    }
+
+    /// Decompresses the base64 plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the plaintext is not base64 of compressed UTF-8 text.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        Compress::new(self.codec).decipher(plaintext)
+    }
+
+    /// Compresses the ciphertext into base64.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        Compress::new(self.codec).encipher(ciphertext)
+    }
+}
+
+impl FallibleCipher for Decompress {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        Ok(self.decipher(ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    fn codecs() -> Vec<Codec> {
+        vec![
+            #[cfg(feature = "gzip")]
+            Codec::Gzip,
+            #[cfg(feature = "zstd")]
+            Codec::Zstd,
+        ]
+    }
+
+    #[test]
+    fn test_compression_round_trip() {
+        let text = "Attack at dawn! ".repeat(20);
+        for codec in codecs() {
+            let compressed = compress(codec, text.as_bytes());
+            assert!(compressed.len() < text.len());
+            assert_eq!(decompress(codec, &compressed).unwrap(), text.as_bytes());
+            assert!(decompress(codec, b"not compressed").is_err());
+
+            let stage = Compress::new(codec);
+            let base64 = stage.encipher(&text);
+            assert_eq!(stage.decipher(&base64).unwrap(), text);
+            assert_eq!(Decompress::new(codec).encipher(&base64).unwrap(), text);
+            assert_eq!(
+                Decompress::new(codec).encipher("!!"),
+                Err("Invalid base64 character '!'".to_string())
+            );
+            assert!(Decompress::new(codec).encipher("AAAA").is_err());
+
+            let fallible: &dyn FallibleCipher = &stage;
+            assert_eq!(fallible.try_decipher(&base64).unwrap(), text);
+            assert!(fallible.try_decipher("!!").is_err());
+            let pipeline = Pipeline::new().then_fallible(Decompress::new(codec));
+            assert_eq!(pipeline.encipher(&base64).unwrap(), text);
+            assert!(pipeline.encipher("!!").is_err());
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_decompresses_other_tools_output() {
+        // printf 'hello' | gzip -n | base64
+        assert_eq!(
+            Decompress::new(Codec::Gzip).encipher("H4sIAAAAAAAAA8tIzcnJBwCGphA2BQAAAA=="),
+            Ok("hello".to_string())
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_decompresses_other_tools_output() {
+        // printf 'hello' | zstd | base64
+        assert_eq!(
+            Decompress::new(Codec::Zstd).encipher("KLUv/QRYKQAAaGVsbG+jbZ+I"),
+            Ok("hello".to_string())
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Building blocks for fractionating ciphers.
+//!
+//! A fractionating cipher replaces every character by a group of coordinate symbols, e.g. the
+//! row and column of a [`PolybiusSquare`](crate::ciphers::PolybiusSquare), rearranges the
+//! symbols, and reads them back in groups. The steps are:
+//!
+//! 1. [`fractionate`]: replace each character by its symbols from a [`Fractionator`].
+//! 2. [`seriate`]: rearrange the symbols, here the Bifid/Trifid way of writing each block of
+//!    letters in columns and reading the rows. Other seriations, such as a columnar
+//!    transposition, can be applied to the symbols directly.
+//! 3. [`regroup`]: cut the symbols into groups of a fixed width.
+//! 4. [`defractionate`]: replace each group by its character.
+//!
+//! ```rust
+//! use cipha::ciphers::{PolybiusGrid, PolybiusSquare};
+//! use cipha::fractionation::{defractionate, fractionate, regroup, seriate, unseriate};
+//!
+//! let square = PolybiusSquare::new(PolybiusGrid::FiveByFive);
+//! let symbols = fractionate("HELP", &square);
+//! assert_eq!(symbols.iter().collect::<String>(), "23153135");
+//! let seriated = seriate(&symbols, 2, 4);
+//! assert_eq!(seriated.iter().collect::<String>(), "21333515");
+//! assert_eq!(defractionate(regroup(&seriated, 2), &square), "FNPE");
+//! assert_eq!(unseriate(&seriated, 2, 4), symbols);
+//! ```
+
+/// A table that turns characters into groups of coordinate symbols and back.
+pub trait Fractionator {
+    /// Returns the symbols for a character, or `None` if the table does not hold it.
+    fn fractionate(&self, c: char) -> Option<Vec<char>>;
+
+    /// Returns the character for a group of symbols, or `None` if no character has them.
+    fn defractionate(&self, symbols: &[char]) -> Option<char>;
+}
+
+/// Replaces every character of the text by its symbols, dropping characters without any.
+pub fn fractionate(text: &str, table: &impl Fractionator) -> Vec<char> {
+    text.chars()
+        .filter_map(|c| table.fractionate(c))
+        .flatten()
+        .collect()
+}
+
+/// Rearranges the symbols of each block of `period` characters, `width` symbols per character.
+///
+/// The symbols of the block are written in columns, one column per character, and the rows
+/// are read one after the other. The last block may be shorter than the period; a period of
+/// zero takes the whole text as a single block.
+pub fn seriate(symbols: &[char], width: usize, period: usize) -> Vec<char> {
+    blocks(symbols.len(), width, period)
+        .flat_map(|(start, letters)| {
+            (0..width).flat_map(move |row| {
+                (0..letters).map(move |letter| symbols[start + letter * width + row])
+            })
+        })
+        .collect()
+}
+
+/// Undoes [`seriate`] with the same width and period.
+pub fn unseriate(symbols: &[char], width: usize, period: usize) -> Vec<char> {
+    blocks(symbols.len(), width, period)
+        .flat_map(|(start, letters)| {
+            (0..letters).flat_map(move |letter| {
+                (0..width).map(move |row| symbols[start + row * letters + letter])
+            })
+        })
+        .collect()
+}
+
+// Returns the start and the number of characters of every block, ignoring trailing symbols
+// that do not make up a whole character.
+fn blocks(len: usize, width: usize, period: usize) -> impl Iterator<Item = (usize, usize)> {
+    let width = width.max(1);
+    let letters = len / width;
+    let period = if period == 0 { letters.max(1) } else { period };
+    (0..letters)
+        .step_by(period)
+        .map(move |first| (first * width, period.min(letters - first)))
+}
+
+/// Cuts the symbols into groups of `width`, dropping an incomplete last group.
+pub fn regroup(symbols: &[char], width: usize) -> std::slice::ChunksExact<'_, char> {
+    symbols.chunks_exact(width.max(1))
+}
+
+/// Replaces every group of symbols by its character, dropping groups without one.
+pub fn defractionate<'a>(
+    groups: impl IntoIterator<Item = &'a [char]>,
+    table: &impl Fractionator,
+) -> String {
+    groups
+        .into_iter()
+        .filter_map(|group| table.defractionate(group))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Maps the digits to their two-digit base 3 representation.
+    struct Base3;
+
+    impl Fractionator for Base3 {
+        fn fractionate(&self, c: char) -> Option<Vec<char>> {
+            let d = c.to_digit(10).filter(|&d| d < 9)?;
+            Some(vec![
+                char::from_digit(d / 3, 10)?,
+                char::from_digit(d % 3, 10)?,
+            ])
+        }
+
+        fn defractionate(&self, symbols: &[char]) -> Option<char> {
+            let d = symbols[0].to_digit(3)? * 3 + symbols[1].to_digit(3)?;
+            char::from_digit(d, 10)
+        }
+    }
+
+    #[test]
+    fn test_fractionate_round_trip() {
+        let symbols = fractionate("5x19", &Base3);
+        assert_eq!(symbols, vec!['1', '2', '0', '1']);
+        assert_eq!(defractionate(regroup(&symbols, 2), &Base3), "51");
+    }
+
+    #[test]
+    fn test_seriate() {
+        let symbols: Vec<char> = "abcdefghij".chars().collect();
+        // Two characters per block: ab cd | ef gh | ij
+        assert_eq!(
+            seriate(&symbols, 2, 2).iter().collect::<String>(),
+            "acbdegfhij"
+        );
+        // One block: the first symbols of every character, then the second ones
+        assert_eq!(
+            seriate(&symbols, 2, 0).iter().collect::<String>(),
+            "acegibdfhj"
+        );
+        for period in 0..7 {
+            for width in 1..4 {
+                let len = symbols.len() / width * width;
+                let seriated = seriate(&symbols[..len], width, period);
+                assert_eq!(unseriate(&seriated, width, period), &symbols[..len]);
+            }
+        }
+    }
+}
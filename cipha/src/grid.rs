@@ -0,0 +1,222 @@
+//! Grid utilities for route transpositions.
+//!
+//! A text of `len` characters is written row by row into a grid `width` characters wide, with
+//! the last row possibly incomplete. A route is the order in which the cells are read, given as
+//! the row-major index of every cell; [`read`] and [`unread`] apply a route and undo it.
+//!
+//! ```rust
+//! use cipha::grid::{boustrophedon_route, read, unread};
+//!
+//! let route = boustrophedon_route(7, 3);
+//! assert_eq!(route, vec![0, 1, 2, 5, 4, 3, 6]);
+//! assert_eq!(read("ABCDEFG", &route), "ABCFEDG");
+//! assert_eq!(unread("ABCFEDG", &route), "ABCDEFG");
+//! ```
+
+/// The direction in which a spiral route is traced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpiralDirection {
+    /// Clockwise from the top left corner towards the centre.
+    Inward,
+    /// From the centre outwards, the reverse of [`SpiralDirection::Inward`].
+    Outward,
+}
+
+/// Returns the number of rows needed for `len` characters in a grid `width` wide.
+pub fn rows(len: usize, width: usize) -> usize {
+    len.div_ceil(width.max(1))
+}
+
+/// Returns the route that reads the rows alternately left to right and right to left.
+pub fn boustrophedon_route(len: usize, width: usize) -> Vec<usize> {
+    let width = width.max(1);
+    (0..rows(len, width))
+        .flat_map(|row| {
+            let mut cells: Vec<usize> = (row * width..((row + 1) * width).min(len)).collect();
+            if row % 2 == 1 {
+                cells.reverse();
+            }
+            cells
+        })
+        .collect()
+}
+
+/// Returns the route that traces a clockwise spiral over the grid.
+///
+/// Cells past the end of the text in the incomplete last row are skipped.
+pub fn spiral_route(len: usize, width: usize, direction: SpiralDirection) -> Vec<usize> {
+    let width = width.max(1);
+    let (mut top, mut bottom, mut left, mut right) = (0, rows(len, width), 0, width);
+    let mut route = Vec::with_capacity(len);
+    while top < bottom && left < right {
+        route.extend((left..right).map(|col| top * width + col));
+        route.extend((top + 1..bottom).map(|row| row * width + right - 1));
+        if top + 1 < bottom {
+            route.extend(
+                (left..right - 1)
+                    .rev()
+                    .map(|col| (bottom - 1) * width + col),
+            );
+        }
+        if left + 1 < right {
+            route.extend((top + 1..bottom - 1).rev().map(|row| row * width + left));
+        }
+        top += 1;
+        bottom -= 1;
+        left += 1;
+        right -= 1;
+    }
+    route.retain(|&cell| cell < len);
+    if direction == SpiralDirection::Outward {
+        route.reverse();
+    }
+    route
+}
+
+/// Returns the route that traces a counterclockwise spiral over the grid, starting down the first
+/// column.
+///
+/// This is the clockwise spiral of the transposed grid. [`SpiralDirection::Outward`] traces it
+/// from the centre outwards instead. Cells past the end of the text in the incomplete last row
+/// are skipped.
+pub fn counterclockwise_spiral_route(
+    len: usize,
+    width: usize,
+    direction: SpiralDirection,
+) -> Vec<usize> {
+    let width = width.max(1);
+    let height = rows(len, width);
+    spiral_route(height * width, height, direction)
+        .into_iter()
+        .map(|cell| (cell % height) * width + cell / height)
+        .filter(|&cell| cell < len)
+        .collect()
+}
+
+/// Returns the route that reads whole columns, taking the columns in the given order.
+///
+/// The grid is as wide as `column_order` is long; columns under the incomplete last row are one
+/// cell shorter.
+pub fn columnar_route(len: usize, column_order: &[usize]) -> Vec<usize> {
+    let width = column_order.len().max(1);
+    column_order
+        .iter()
+        .flat_map(|&col| (col..len).step_by(width))
+        .collect()
+}
+
+/// Reads the characters of `text` in the order given by `route`.
+pub fn read(text: &str, route: &[usize]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    route.iter().filter_map(|&cell| chars.get(cell)).collect()
+}
+
+/// Writes the characters of `text` back to the cells given by `route`, undoing [`read`].
+pub fn unread(text: &str, route: &[usize]) -> String {
+    let mut cells = vec![None; route.len()];
+    for (&cell, c) in route.iter().zip(text.chars()) {
+        if let Some(slot) = cells.get_mut(cell) {
+            *slot = Some(c);
+        }
+    }
+    cells.into_iter().flatten().collect()
+}
+
+/// Returns the inverse of a route: the position in the output of [`read`] of every cell.
+pub fn inverse(route: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; route.len()];
+    for (position, &cell) in route.iter().enumerate() {
+        if let Some(slot) = positions.get_mut(cell) {
+            *slot = position;
+        }
+    }
+    positions
+}
+
+/// Moves every item to the position given by `permutation`, closing the gaps of positions no
+/// item moves to, e.g. those of padding.
+///
+/// This applies the permutation of a [`Transposition`](crate::ciphers::Transposition) to data
+/// that runs in parallel with the text, such as the styles or timings of its characters.
+///
+/// ```rust
+/// use cipha::grid::permute;
+/// assert_eq!(permute(&[10, 20, 30], &[2, 0, 1]), vec![20, 30, 10]);
+/// assert_eq!(permute(&['a', 'b'], &[3, 1]), vec!['b', 'a']);
+/// ```
+pub fn permute<T: Clone>(items: &[T], permutation: &[usize]) -> Vec<T> {
+    let end = permutation
+        .iter()
+        .map(|&position| position + 1)
+        .max()
+        .unwrap_or(0);
+    let mut moved = vec![None; end];
+    for (&position, item) in permutation.iter().zip(items) {
+        moved[position] = Some(item);
+    }
+    moved.into_iter().flatten().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spiral_route() {
+        // A B C D
+        // E F G H
+        // I J K L
+        assert_eq!(
+            read(
+                "ABCDEFGHIJKL",
+                &spiral_route(12, 4, SpiralDirection::Inward)
+            ),
+            "ABCDHLKJIEFG"
+        );
+        assert_eq!(
+            read(
+                "ABCDEFGHIJKL",
+                &spiral_route(12, 4, SpiralDirection::Outward)
+            ),
+            "GFEIJKLHDCBA"
+        );
+        // The missing cells of an incomplete last row are skipped
+        assert_eq!(
+            read("ABCDEFGHIJ", &spiral_route(10, 4, SpiralDirection::Inward)),
+            "ABCDHJIEFG"
+        );
+        assert_eq!(
+            read(
+                "ABCDEFGHIJKL",
+                &counterclockwise_spiral_route(12, 4, SpiralDirection::Inward)
+            ),
+            "AEIJKLHDCBFG"
+        );
+        assert_eq!(
+            read(
+                "ABCDEFGHIJ",
+                &counterclockwise_spiral_route(10, 4, SpiralDirection::Inward)
+            ),
+            "AEIJHDCBFG"
+        );
+    }
+
+    #[test]
+    fn test_read_and_unread() {
+        for len in 0..30 {
+            let text: String = ('a'..='z').chain('A'..='Z').take(len).collect();
+            for width in 1..8 {
+                let order: Vec<usize> = (0..width).rev().collect();
+                for route in [
+                    boustrophedon_route(len, width),
+                    spiral_route(len, width, SpiralDirection::Outward),
+                    counterclockwise_spiral_route(len, width, SpiralDirection::Inward),
+                    columnar_route(len, &order),
+                ] {
+                    assert_eq!(route.len(), len);
+                    assert_eq!(unread(&read(&text, &route), &route), text);
+                }
+            }
+        }
+    }
+}
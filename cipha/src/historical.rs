@@ -0,0 +1,452 @@
+//! Complete cipher systems of the past, built from the simpler ciphers of this crate.
+//!
+//! The systems here combine substitution, keystream and transposition steps the way their users
+//! did by hand. Their intermediate keys are exposed, so each step can be checked on its own.
+
+use crate::ciphers::{column_order, FallibleCipher, StraddlingCheckerboard};
+use crate::grid;
+use crate::keystream::ChainAddition;
+
+/// Numbers the symbols in alphabetical order, from 1, with equal symbols numbered left to right.
+///
+/// The digit 0 counts as ten, so it comes after 9, as in the key derivations of the VIC cipher.
+/// Written as digits the numbers run 1 to 9 and then 0, which is the usual form for ten symbols.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::historical::sequentialize;
+/// assert_eq!(sequentialize("IDREAMOFJE"), vec![6, 2, 10, 3, 1, 8, 9, 5, 7, 4]);
+/// assert_eq!(sequentialize("3019"), vec![2, 4, 1, 3]);
+/// ```
+pub fn sequentialize(symbols: &str) -> Vec<usize> {
+    // ':' follows '9', so it stands in for ten
+    let order = column_order(&symbols.replace('0', ":"));
+    let mut numbers = vec![0; order.len()];
+    for (rank, position) in order.into_iter().enumerate() {
+        numbers[position] = rank + 1;
+    }
+    numbers
+}
+
+// Sequentializes ten symbols to the digits 1 to 9 and 0.
+fn sequentialize_digits(symbols: &str) -> Vec<u8> {
+    sequentialize(symbols)
+        .into_iter()
+        .map(|n| (n % 10) as u8)
+        .collect()
+}
+
+// Parses the decimal digits of a string, ignoring everything else.
+fn digits_of(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter_map(|c| c.to_digit(10).map(|d| d as u8))
+        .collect()
+}
+
+// Writes digits as a string.
+fn digit_string(digits: &[u8]) -> String {
+    digits.iter().map(|&d| (b'0' + d) as char).collect()
+}
+
+// Returns the next `len` digits of the chain addition sequence grown from `seed`.
+fn chain_add(seed: &[u8], len: usize) -> Vec<u8> {
+    ChainAddition::new(&digit_string(seed))
+        .expect("seeds are at least two digits")
+        .skip(seed.len())
+        .take(len)
+        .collect()
+}
+
+// Returns the route of a disrupted transposition: the text is written in rows, first into the
+// cells outside the disrupted areas and then into the disrupted areas, and read off by columns.
+fn disrupted_route(len: usize, column_order: &[usize]) -> Vec<usize> {
+    let width = column_order.len();
+    let rows = grid::rows(len, width);
+    // A disrupted area starts at the column with the next key number and runs to the right edge,
+    // starting one column further right on every row, until a row is left undisrupted
+    let mut disrupted = vec![false; rows * width];
+    let (mut area, mut start, mut offset) = (0, column_order[0], 0);
+    for row in 0..rows {
+        if start + offset >= width {
+            area = (area + 1) % width;
+            start = column_order[area];
+            offset = 0;
+            continue;
+        }
+        for column in start + offset..width {
+            disrupted[row * width + column] = true;
+        }
+        offset += 1;
+    }
+    let cells = 0..len;
+    let written: Vec<usize> = cells
+        .clone()
+        .filter(|&cell| !disrupted[cell])
+        .chain(cells.filter(|&cell| disrupted[cell]))
+        .collect();
+    let mut position = vec![0; len];
+    for (i, cell) in written.into_iter().enumerate() {
+        position[cell] = i;
+    }
+    grid::columnar_route(len, column_order)
+        .into_iter()
+        .map(|cell| position[cell])
+        .collect()
+}
+
+/// The keys of one VIC message, derived from the long-term keys and the message indicator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VicKeys {
+    /// The checkerboard, with its columns headed by the sequentialized last line of the key block.
+    pub checkerboard: StraddlingCheckerboard,
+    /// The column order of the first, plain columnar transposition.
+    pub first_transposition: Vec<usize>,
+    /// The column order of the second, disrupted transposition.
+    pub second_transposition: Vec<usize>,
+}
+
+impl VicKeys {
+    /// Enciphers a plaintext with these keys, without inserting the indicator.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let digits = self.checkerboard.encipher(plaintext);
+        let len = digits.len();
+        let digits = grid::read(
+            &digits,
+            &grid::columnar_route(len, &self.first_transposition),
+        );
+        grid::read(&digits, &disrupted_route(len, &self.second_transposition))
+    }
+
+    /// Deciphers the digits of a ciphertext with these keys, the indicator already removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the digits do not decode on the checkerboard.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let digits = digit_string(&digits_of(ciphertext));
+        let len = digits.len();
+        let digits = grid::unread(&digits, &disrupted_route(len, &self.second_transposition));
+        let digits = grid::unread(
+            &digits,
+            &grid::columnar_route(len, &self.first_transposition),
+        );
+        self.checkerboard.decipher(&digits)
+    }
+}
+
+/// The VIC cipher, the hand cipher of the Soviet spy Reino Häyhänen.
+///
+/// Every message is keyed by its own five digit indicator, combined with the long-term keys: a
+/// phrase of at least 20 letters, a date of at least six digits and the agent's personal number.
+/// The key derivation follows the usual description of the system:
+///
+/// 1. Subtract the first five digits of the date from the indicator, without borrowing, and
+///    extend the result to ten digits by chain addition.
+/// 2. Add the sequentialized first ten letters of the phrase, without carrying, and replace
+///    every digit by the sequentialized second ten letters (1 to 9 and 0 map to them in order).
+/// 3. Extend that line to 50 more digits by chain addition, written as five rows of ten.
+/// 4. The last two different digits of the last row, each added to the personal number, are the
+///    lengths of the two transposition keys. The keys are read off the columns of the rows in the
+///    order of the sequentialized line from step 2.
+/// 5. The sequentialized last row heads the columns of the checkerboard.
+///
+/// The plaintext is then substituted on the checkerboard (by default `AT ONE SIR` with blanks in
+/// the third and seventh columns), put through a columnar and a disrupted transposition and cut
+/// into groups of five. The indicator is inserted as the group that is `n`th from the end, where
+/// `n` is the sixth digit of the date.
+///
+/// Enciphering needs random numbers and deciphering needs a well-formed indicator group, so this
+/// cipher is a [`FallibleCipher`] rather than a [`Cipher`](crate::ciphers::Cipher).
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::historical::VicCipher;
+/// let vic = VicCipher::new("I dream of Jeannie with the light brown hair", "741776", 8).unwrap();
+/// let ciphertext = vic.encipher_with_indicator("Meet at the old bridge.", "77651").unwrap();
+/// assert_eq!(vic.decipher(&ciphertext).unwrap(), "MEETATTHEOLDBRIDGE.");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VicCipher {
+    phrase: String,
+    date: Vec<u8>,
+    personal_number: usize,
+    keyword_row: String,
+    blanks: [u8; 2],
+}
+
+impl VicCipher {
+    /// Creates a VIC cipher from the long-term keys.
+    ///
+    /// Only the first 20 letters of the phrase and the first six digits of the date are used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the phrase has fewer than 20 letters, the date fewer than six digits,
+    /// or the personal number is not between 1 and 16.
+    pub fn new(phrase: &str, date: &str, personal_number: usize) -> Result<Self, String> {
+        let phrase: String = phrase
+            .chars()
+            .filter(char::is_ascii_alphabetic)
+            .map(|c| c.to_ascii_uppercase())
+            .take(20)
+            .collect();
+        if phrase.len() < 20 {
+            return Err("Phrase must have at least 20 letters".to_string());
+        }
+        let date: Vec<u8> = digits_of(date).into_iter().take(6).collect();
+        if date.len() < 6 {
+            return Err("Date must have at least six digits".to_string());
+        }
+        // Both keys must fit in the 50 digits of the key block
+        if !(1..=16).contains(&personal_number) {
+            return Err("Personal number must be between 1 and 16".to_string());
+        }
+        Ok(VicCipher {
+            phrase,
+            date,
+            personal_number,
+            keyword_row: "ATONESIR".to_string(),
+            blanks: [2, 6],
+        })
+    }
+
+    /// Uses a checkerboard with the given keyword row and blank columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if they do not make a valid [`StraddlingCheckerboard`].
+    pub fn with_checkerboard(mut self, keyword_row: &str, blanks: [u8; 2]) -> Result<Self, String> {
+        StraddlingCheckerboard::new(keyword_row, blanks)?;
+        self.keyword_row = keyword_row.to_string();
+        self.blanks = blanks;
+        Ok(self)
+    }
+
+    /// Derives the keys of the message with the given five digit indicator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the indicator is not five digits.
+    pub fn keys(&self, indicator: &str) -> Result<VicKeys, String> {
+        if indicator.len() != 5 || !indicator.chars().all(|c| c.is_ascii_digit()) {
+            return Err("Indicator must be five digits".to_string());
+        }
+        let (line, block) = self.key_block(&digits_of(indicator));
+        let last_row = &block[40..];
+        let last = last_row[9];
+        let before_last = *last_row.iter().rev().find(|&&d| d != last).unwrap_or(&last);
+        let first_len = self.personal_number + before_last as usize;
+        let second_len = self.personal_number + last as usize;
+
+        let by_columns: Vec<u8> = column_order(&digit_string(&line).replace('0', ":"))
+            .into_iter()
+            .flat_map(|column| block.iter().skip(column).step_by(10).copied())
+            .collect();
+        let first_key = digit_string(&by_columns[..first_len]);
+        let second_key = digit_string(&by_columns[first_len..first_len + second_len]);
+
+        let mut headings = [0; 10];
+        headings.copy_from_slice(&sequentialize_digits(&digit_string(last_row)));
+        let checkerboard =
+            StraddlingCheckerboard::new(&self.keyword_row, self.blanks)?.with_headings(headings)?;
+        Ok(VicKeys {
+            checkerboard,
+            first_transposition: column_order(&first_key.replace('0', ":")),
+            second_transposition: column_order(&second_key.replace('0', ":")),
+        })
+    }
+
+    // Returns the line of steps 1 and 2 and the 50 digits of the key block grown from it.
+    fn key_block(&self, indicator: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let indicator_minus_date: Vec<u8> = indicator
+            .iter()
+            .zip(&self.date)
+            .map(|(a, b)| (a + 10 - b) % 10)
+            .collect();
+        let mut line = indicator_minus_date.clone();
+        line.extend(chain_add(&indicator_minus_date, 5));
+
+        let first_half = sequentialize_digits(&self.phrase[..10]);
+        let second_half = sequentialize_digits(&self.phrase[10..]);
+        let line: Vec<u8> = line
+            .iter()
+            .zip(&first_half)
+            .map(|(a, b)| (a + b) % 10)
+            .collect();
+        // 1 2 3 4 5 6 7 8 9 0 are replaced by the second half, in that order
+        let line: Vec<u8> = line
+            .iter()
+            .map(|&d| second_half[(d as usize + 9) % 10])
+            .collect();
+
+        let block = chain_add(&line, 50);
+        (line, block)
+    }
+
+    // Returns the group index of the indicator among `groups` groups.
+    fn indicator_group(&self, groups: usize) -> usize {
+        groups - 1 - (self.date[5] as usize).min(groups - 1)
+    }
+
+    /// Enciphers a plaintext with the given five digit indicator, in groups of five digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the indicator is not five digits.
+    pub fn encipher_with_indicator(
+        &self,
+        plaintext: &str,
+        indicator: &str,
+    ) -> Result<String, String> {
+        let digits = self.keys(indicator)?.encipher(plaintext);
+        let mut groups: Vec<String> = digits
+            .as_bytes()
+            .chunks(5)
+            .map(|group| String::from_utf8_lossy(group).into_owned())
+            .collect();
+        let index = self.indicator_group(groups.len() + 1);
+        groups.insert(index, indicator.to_string());
+        Ok(groups.join(" "))
+    }
+
+    /// Enciphers a plaintext with a random indicator.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operating system cannot provide random numbers.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        let mut bytes = [0u8; 5];
+        getrandom::getrandom(&mut bytes)
+            .map_err(|e| format!("Could not get random numbers: {}", e))?;
+        let indicator: Vec<u8> = bytes.iter().map(|b| b % 10).collect();
+        self.encipher_with_indicator(plaintext, &digit_string(&indicator))
+    }
+
+    /// Deciphers a ciphertext in groups of five digits, taking the indicator from its group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the indicator group is missing or the digits do not decode.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        let mut groups: Vec<&str> = ciphertext.split_whitespace().collect();
+        if groups.is_empty() {
+            return Err("Ciphertext must contain the indicator group".to_string());
+        }
+        let indicator = groups.remove(self.indicator_group(groups.len()));
+        self.keys(indicator)?.decipher(&groups.concat())
+    }
+}
+
+impl FallibleCipher for VicCipher {
+    /// Enciphers with a random indicator.
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::Pipeline;
+
+    #[test]
+    fn test_sequentialize() {
+        assert_eq!(
+            sequentialize_digits("IDREAMOFJE"),
+            vec![6, 2, 0, 3, 1, 8, 9, 5, 7, 4]
+        );
+        assert_eq!(sequentialize("BANANA"), vec![4, 1, 5, 2, 6, 3]);
+        assert_eq!(sequentialize(""), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_disrupted_route() {
+        // Key 2 1 3 on 9 cells: the first area starts in column 1 (key number 1)
+        //   row 0: columns 1, 2 disrupted; row 1: column 2; row 2: undisrupted
+        // Written: 0 1 2 | 3 4 5 | 6 7 8 go to cells 0, 3, 4, 6, 7, 8, then 1, 2, 5
+        let route = disrupted_route(9, &[1, 0, 2]);
+        assert_eq!(
+            grid::read("ABCDEFGHI", &route),
+            // Grid A G H / B C I / D E F read by columns 1, 0, 2
+            "GCEABDHIF"
+        );
+        for len in 0..40 {
+            let text: String = ('A'..='Z').cycle().take(len).collect();
+            let route = disrupted_route(len, &[3, 0, 4, 1, 2]);
+            assert_eq!(grid::unread(&grid::read(&text, &route), &route), text);
+        }
+    }
+
+    #[test]
+    fn test_vic_keys() {
+        let vic =
+            VicCipher::new("I dream of Jeannie with the light brown hair", "741776", 8).unwrap();
+        // The lines of the worked example: 77651 - 74177 = 03584, extended to 0358438327
+        // + 6203189574 (IDREAMOFJE) = 6551517891, through 1674205839 (ANNIEWITHT) = 0221215831
+        let (line, block) = vic.key_block(&digits_of("77651"));
+        assert_eq!(digit_string(&line), "0221215831");
+        assert_eq!(sequentialize_digits("ANNIEWITHT"), digits_of("1674205839"));
+        // The last row of the block is 1204339669: key lengths 8 + 6 and 8 + 9
+        assert_eq!(digit_string(&block[40..]), "1204339669");
+        let keys = vic.keys("77651").unwrap();
+        assert_eq!(
+            keys.first_transposition,
+            vec![8, 12, 0, 3, 7, 9, 10, 4, 2, 1, 5, 13, 6, 11]
+        );
+        assert_eq!(
+            keys.second_transposition,
+            vec![14, 5, 8, 3, 6, 11, 15, 1, 16, 4, 7, 2, 9, 13, 0, 12, 10]
+        );
+        // Headed 1 2 0 5 3 4 8 6 7 9, the blank columns are 0 and 8
+        assert_eq!(keys.checkerboard.blanks(), [0, 8]);
+        assert_ne!(keys, vic.keys("77652").unwrap());
+        assert!(vic.keys("7765").is_err());
+        assert!(vic.keys("7765x1").is_err());
+    }
+
+    #[test]
+    fn test_vic_round_trip() {
+        let vic = VicCipher::new("Twas the night before Christmas", "130919", 13).unwrap();
+        let plaintext = "WE ARE PLEASED TO HEAR OF YOUR SAFE ARRIVAL.";
+        let ciphertext = vic.encipher_with_indicator(plaintext, "58932").unwrap();
+        // The sixth date digit puts the indicator ninth from the end, or first if shorter
+        let groups: Vec<&str> = ciphertext.split(' ').collect();
+        assert_eq!(groups[groups.len().saturating_sub(10)], "58932");
+        assert!(groups.iter().rev().skip(1).all(|g| g.len() == 5));
+        assert_eq!(
+            vic.decipher(&ciphertext).unwrap(),
+            "WEAREPLEASEDTOHEAROFYOURSAFEARRIVAL."
+        );
+        let random = vic.encipher("ATTACK AT DAWN").unwrap();
+        assert_eq!(vic.decipher(&random).unwrap(), "ATTACKATDAWN");
+
+        let other = vic.clone().with_checkerboard("ESTONIAR", [3, 7]).unwrap();
+        let ciphertext = other.encipher_with_indicator(plaintext, "58932").unwrap();
+        assert_eq!(
+            other.decipher(&ciphertext).unwrap(),
+            "WEAREPLEASEDTOHEAROFYOURSAFEARRIVAL."
+        );
+        assert_ne!(vic.decipher(&ciphertext).ok().as_deref(), Some(plaintext));
+
+        assert!(VicCipher::new("too short", "130919", 13).is_err());
+        assert!(VicCipher::new("Twas the night before Christmas", "1309", 13).is_err());
+        assert!(VicCipher::new("Twas the night before Christmas", "130919", 17).is_err());
+        assert!(vic.decipher("").is_err());
+
+        let stage: &dyn FallibleCipher = &vic;
+        assert_eq!(
+            stage.try_decipher(""),
+            Err("Ciphertext must contain the indicator group".to_string())
+        );
+        let pipeline = Pipeline::new().then_fallible(vic);
+        let ciphertext = pipeline.encipher("ATTACK AT DAWN").unwrap();
+        assert_eq!(pipeline.decipher(&ciphertext).unwrap(), "ATTACKATDAWN");
+        assert!(pipeline.decipher("").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Keystream generators used by additive ciphers.
+
+/// A chain addition (lagged Fibonacci) digit generator, as used by the Gromark and VIC ciphers.
+///
+/// Starting from a seed of `n` digits, every new digit is the sum modulo 10 of the digit `n`
+/// places back and the one after it, so `7 1 5` continues with `8 6 3 4 9 ...`. The iterator
+/// yields the seed digits first; use `.skip(n)` for the generated digits only.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::keystream::ChainAddition;
+/// let digits: Vec<u8> = ChainAddition::new("715").unwrap().take(8).collect();
+/// assert_eq!(digits, vec![7, 1, 5, 8, 6, 3, 4, 9]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainAddition {
+    window: Vec<u8>,
+    position: usize,
+}
+
+impl ChainAddition {
+    /// Creates a generator from a seed of at least two decimal digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the seed has fewer than two characters or contains a non-digit.
+    pub fn new(seed: &str) -> Result<Self, String> {
+        let window: Vec<u8> = seed
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or(format!("Seed contains a non-digit: {}", c))
+            })
+            .collect::<Result<_, _>>()?;
+        if window.len() < 2 {
+            return Err("Seed must have at least two digits".to_string());
+        }
+        Ok(ChainAddition {
+            window,
+            position: 0,
+        })
+    }
+}
+
+impl Iterator for ChainAddition {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let n = self.window.len();
+        let digit = self.window[self.position];
+        // Replace the consumed digit with the one `n` places further along the chain
+        self.window[self.position] = (digit + self.window[(self.position + 1) % n]) % 10;
+        self.position = (self.position + 1) % n;
+        Some(digit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_addition() {
+        let digits: Vec<u8> = ChainAddition::new("23456").unwrap().take(15).collect();
+        assert_eq!(digits, vec![2, 3, 4, 5, 6, 5, 7, 9, 1, 1, 2, 6, 0, 2, 3]);
+    }
+
+    #[test]
+    fn test_chain_addition_rejects_bad_seed() {
+        assert!(ChainAddition::new("7").is_err());
+        assert!(ChainAddition::new("12a").is_err());
+    }
+}
@@ -1,18 +1,18 @@
 //! # Cipha
-//! 
+//!
 //! Cipha is a simple CLI and Package for classical Ciphers and Cryptography in Rust
-//! 
+//!
 //! `cipha` consist of two main part the `cipha` and  `cipha-cli`.
-//! 
+//!
 //! ## Installation
 //! Simply put the following in your **Cargo.toml**.
-//! 
+//!
 //! ```toml
 //! [dependencies]
 //! cipha = "0.1.0"
 //! ```
 //! Or use `cargo add cipha`
-//! 
+//!
 //! ## Usage
 //! E.g. using the Functional Approach.
 //! ```rust
@@ -39,127 +39,202 @@
 //!
 //!```
 
-
 // It contains various cipher and crypto functions.
 
 pub mod ciphers;
 
+pub mod alphabet;
+
+pub mod analysis;
+
+pub mod budget;
+
+pub mod text;
+
+pub mod keystream;
+
+pub mod grid;
+
+pub mod fractionation;
+
+pub mod pipeline;
+
+pub mod binary;
+
+pub mod machines;
+
+pub mod authentication;
+
+pub mod historical;
+
+pub mod render;
+
+pub mod nulls;
+
+pub mod prelude;
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod compression;
+
+#[cfg(feature = "compat")]
+pub mod compat;
+
+mod math;
+
 pub mod utils {
     use std::collections::HashMap;
-/// Applies the ROT13 cipher to the input message.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::rot13;
-/// let input = "Hello, World!".to_string();
-/// let output = rot13(input);
-/// assert_eq!(output, "Uryyb, Jbeyq!");
-/// ```
-// Implement the ROT13 function
-pub fn rot13(message: String) -> String {
-    message.chars().map(|c| {
-        match c {
-            'a'..='z' => (((c as u8 - b'a' + 13) % 26) + b'a') as char,
-            'A'..='Z' => (((c as u8 - b'A' + 13) % 26) + b'A') as char,
-            _ => c,
-        }
-    }).collect()
-}
+    /// Applies the ROT13 cipher to the input message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::rot13;
+    /// let input = "Hello, World!".to_string();
+    /// let output = rot13(input);
+    /// assert_eq!(output, "Uryyb, Jbeyq!");
+    /// ```
+    // Implement the ROT13 function
+    pub fn rot13(message: String) -> String {
+        message
+            .chars()
+            .map(|c| match c {
+                'a'..='z' => (((c as u8 - b'a' + 13) % 26) + b'a') as char,
+                'A'..='Z' => (((c as u8 - b'A' + 13) % 26) + b'A') as char,
+                _ => c,
+            })
+            .collect()
+    }
 
-/// Applies the Caesar cipher to the input message with the given shift.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::caesar_cipher;
-/// let input = "Hello, World!".to_string();
-/// let shift = 3;
-/// let output = caesar_cipher(input, shift);
-/// assert_eq!(output, "Khoor, Zruog!");
-/// ```
-// Implement the Caesar Cipher function
-pub fn caesar_cipher(message: String, shift: u8) -> String {
-    message.chars().map(|c| {
-        match c {
-            'a'..='z' => (((c as u8 - b'a' + shift) % 26) + b'a') as char,
-            'A'..='Z' => (((c as u8 - b'A' + shift) % 26) + b'A') as char,
-            _ => c,
-        }
-    }).collect()
-}
+    /// Applies the ROT5 cipher to the digits of the input message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::rot5;
+    /// assert_eq!(rot5("Call 555-0123".to_string()), "Call 000-5678");
+    /// ```
+    pub fn rot5(message: String) -> String {
+        crate::ciphers::Rot5Cipher::new().encipher(&message)
+    }
 
+    /// Applies ROT13 to the letters and ROT5 to the digits of the input message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::rot18;
+    /// assert_eq!(rot18("Room 101".to_string()), "Ebbz 656");
+    /// ```
+    pub fn rot18(message: String) -> String {
+        crate::ciphers::Rot18Cipher::new().encipher(&message)
+    }
 
-/// Reverses the input message.
-///
-/// This function takes a string slice and returns a new string with the characters in reverse order.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::reverse_cipher;
-/// let input = "Hello, World!";
-/// let reversed = reverse_cipher(input);
-/// assert_eq!(reversed, "!dlroW ,olleH");
-/// ```
-pub fn reverse_cipher(message: &str) -> String {
-    message.chars().rev().collect()
-}
+    /// Applies the ROT47 cipher to the printable ASCII characters of the input message.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::rot47;
+    /// assert_eq!(rot47("Hello, World!".to_string()), "w6==@[ (@C=5P");
+    /// ```
+    pub fn rot47(message: String) -> String {
+        crate::ciphers::Rot47Cipher::new().encipher(&message)
+    }
 
+    /// Applies the Caesar cipher to the input message with the given shift.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::caesar_cipher;
+    /// let input = "Hello, World!".to_string();
+    /// let shift = 3;
+    /// let output = caesar_cipher(input, shift);
+    /// assert_eq!(output, "Khoor, Zruog!");
+    /// ```
+    // Implement the Caesar Cipher function
+    pub fn caesar_cipher(message: String, shift: u8) -> String {
+        message
+            .chars()
+            .map(|c| match c {
+                'a'..='z' => (((c as u8 - b'a' + shift) % 26) + b'a') as char,
+                'A'..='Z' => (((c as u8 - b'A' + shift) % 26) + b'A') as char,
+                _ => c,
+            })
+            .collect()
+    }
 
-/// Converts alphabetic characters in the input text to their corresponding numerical values.
-///
-/// This function iterates over each character in the input string. If the character is an alphabetic character, it converts it to its corresponding numerical value (A=1, B=2, ..., Z=26, a=1, b=2, ..., z=26) and appends it to the result string. Non-alphabetic characters are appended as is.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::alpha2num;
-/// let input = "Hello, World!";
-/// let result = alpha2num(input);
-/// assert_eq!(result, "8 5 12 12 15 ,   23 15 18 12 4 !");
-/// ```
-pub fn alpha2num(text: &str) -> String {
-    let mut result = String::new();
-
-    for c in text.chars() {
-        if c.is_ascii_alphabetic() {
-            let base = if c.is_ascii_uppercase() { 'A' as u32 } else { 'a' as u32 };
-            let number = (c as u32 - base + 1) as u8;
-            result.push_str(&number.to_string());
-            result.push(' ');
-        } else {
-            result.push(c);
-            result.push(' ');
-        }
+    /// Reverses the input message.
+    ///
+    /// This function takes a string slice and returns a new string with the characters in reverse order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::reverse_cipher;
+    /// let input = "Hello, World!";
+    /// let reversed = reverse_cipher(input);
+    /// assert_eq!(reversed, "!dlroW ,olleH");
+    /// ```
+    pub fn reverse_cipher(message: &str) -> String {
+        message.chars().rev().collect()
     }
 
-    result.trim().to_string()
-}
+    /// Converts alphabetic characters in the input text to their corresponding numerical values.
+    ///
+    /// This function iterates over each character in the input string. If the character is an alphabetic character, it converts it to its corresponding numerical value (A=1, B=2, ..., Z=26, a=1, b=2, ..., z=26) and appends it to the result string. Non-alphabetic characters are appended as is.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::alpha2num;
+    /// let input = "Hello, World!";
+    /// let result = alpha2num(input);
+    /// assert_eq!(result, "8 5 12 12 15 ,   23 15 18 12 4 !");
+    /// ```
+    pub fn alpha2num(text: &str) -> String {
+        let mut result = String::new();
 
+        for c in text.chars() {
+            if c.is_ascii_alphabetic() {
+                let base = if c.is_ascii_uppercase() {
+                    'A' as u32
+                } else {
+                    'a' as u32
+                };
+                let number = (c as u32 - base + 1) as u8;
+                result.push_str(&number.to_string());
+                result.push(' ');
+            } else {
+                result.push(c);
+                result.push(' ');
+            }
+        }
+
+        result.trim().to_string()
+    }
 
-/// Converts numerical values in the input text back to their corresponding alphabetic characters.
-///
-/// This function parses the input string for numerical values separated by spaces. Each number is converted back to its corresponding alphabetic character (1=A, 2=B, ..., 26=Z, 27=A, 28=B, ..., 52=Z) and appended to the result string. Non-numerical characters are ignored.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::num2alpha;
-/// let input = "8 5 12 12 15 , 23 15 18 12 4 !";
-/// let result = num2alpha(input);
-/// assert_eq!(result, "helloworld");
-/// ```
-/// 
-pub fn num2alpha(cipher_text: &str) -> String {
-    let mut decrypted_text = String::new();
-    let mut number_buffer = String::new();
-
-    for char in cipher_text.chars() {
-        if char.is_digit(10) {
-            number_buffer.push(char);
-        } else if char.is_whitespace() {
-            if !number_buffer.is_empty() {
+    /// Converts numerical values in the input text back to their corresponding alphabetic characters.
+    ///
+    /// This function parses the input string for numerical values separated by spaces. Each number is converted back to its corresponding alphabetic character (1=A, 2=B, ..., 26=Z, 27=A, 28=B, ..., 52=Z) and appended to the result string. Non-numerical characters are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::num2alpha;
+    /// let input = "8 5 12 12 15 , 23 15 18 12 4 !";
+    /// let result = num2alpha(input);
+    /// assert_eq!(result, "helloworld");
+    /// ```
+    ///
+    pub fn num2alpha(cipher_text: &str) -> String {
+        let mut decrypted_text = String::new();
+        let mut number_buffer = String::new();
+
+        for char in cipher_text.chars() {
+            if char.is_ascii_digit() {
+                number_buffer.push(char);
+            } else if char.is_whitespace() && !number_buffer.is_empty() {
                 let number: u32 = number_buffer.parse().unwrap();
                 let char_code = if number <= 26 {
                     // Handle lowercase letters
@@ -175,214 +250,203 @@ pub fn num2alpha(cipher_text: &str) -> String {
                 number_buffer.clear();
             }
         }
-    }
 
-    // Handle the last number if any
-    if !number_buffer.is_empty() {
-        let number: u32 = number_buffer.parse().unwrap();
-        let char_code = if number <= 26 {
-            'a' as u32 + number - 1
-        } else if number <= 52 {
-            'A' as u32 + number - 27
-        } else {
-            // Ignore invalid numbers
-            return decrypted_text;
-        };
-        decrypted_text.push(char::from_u32(char_code).unwrap());
-    }
-
-    decrypted_text
-}
-
-/// Encrypts the input message using the Vigenère cipher with the given key.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::vigenere_cipher;
-/// let plaintext = "ATTACKATDAWN".to_string();
-/// let key = "LEMON".to_string();
-/// let ciphertext = vigenere_cipher(&plaintext, &key);
-/// assert_eq!(ciphertext, "LXFOPVEFRNHR");
-/// ```
-pub fn vigenere_cipher(plaintext: &str, key: &str) -> String {
-    let key: String = key.to_ascii_lowercase();
-    let key_len = key.len();
-    if key_len == 0 {
-        return plaintext.to_string();
-    }
-
-    let mut index = 0;
-    plaintext
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphabetic() {
-                let first = if c.is_ascii_lowercase() {
-                    b'a'
-                } else {
-                    b'A'
-                };
-                let shift = key.as_bytes()[index % key_len] as u8 - b'a';
-                index += 1;
-                // Modulo to keep character range
-                (first + (c as u8 - first + shift) % 26) as char
+        // Handle the last number if any
+        if !number_buffer.is_empty() {
+            let number: u32 = number_buffer.parse().unwrap();
+            let char_code = if number <= 26 {
+                'a' as u32 + number - 1
+            } else if number <= 52 {
+                'A' as u32 + number - 27
             } else {
-                c
-            }
-        })
-        .collect()
-}
+                // Ignore invalid numbers
+                return decrypted_text;
+            };
+            decrypted_text.push(char::from_u32(char_code).unwrap());
+        }
 
-pub fn vigenere_decipher(ciphertext: &str, key: &str) -> String {
-    let key: String = key.to_ascii_lowercase();
-    let key_len = key.len();
-    if key_len == 0 {
-        return ciphertext.to_string();
+        decrypted_text
     }
 
-    let mut index = 0;
-    ciphertext
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphabetic() {
-                let first = if c.is_ascii_lowercase() {
-                    b'a'
-                } else {
-                    b'A'
-                };
-                let shift = key.as_bytes()[index % key_len] as u8 - b'a';
-                index += 1;
-                // Modulo to keep character range
-               // Calculate the decrypted character code, avoiding overflow
-               let decrypted_char_code = (c as u8 - first - shift + 26) % 26 + first;
-           // Convert the decrypted character code back to a char
-           decrypted_char_code as char
-            } else {
-                c
-            }
-        })
-        .collect()
-}
-
-
-// Define the Morse code mapping
-
-// Define the Morse code mapping
-const MORSE_CODE_MAP: &[(&str, &str)] = &[
-    ("A", ".-"), ("B", "-..."), ("C", "-.-."), ("D", "-.."), ("E", "."), ("F", "..-."),
-    ("G", "--."), ("H", "...."), ("I", ".."), ("J", ".---"), ("K", "-.-"), ("L", ".-.."),
-    ("M", "--"), ("N", "-."), ("O", "---"), ("P", ".--."), ("Q", "--.-"), ("R", ".-."),
-    ("S", "..."), ("T", "-"), ("U", "..-"), ("V", "...-"), ("W", ".--"), ("X", "-..-"),
-    ("Y", "-.--"), ("Z", "--.."),
-    ("0", "-----"), ("1", ".----"), ("2", "..---"), ("3", "...--"), ("4", "....-"),
-    ("5", "....."), ("6", "-...."), ("7", "--..."), ("8", "---.."), ("9", "----."),
-    (" ", "/"), // Space separator
-    (".", ".-.-.-"), (",", "--..--"), ("?", "..--.."), (";", "-.-.-."),
-    (":", "---..."), ("-", "-....-"), ("/", "-..-."), ("'", ".----."),
-    ("\"", ".-..-."),
-    ("=", "-...-"), ("_", "..--.-"), ("+", ".-.-."), ("-", "-....-"),
-    ("*", "-..-"), ("(", "-.--."), (")", "-.--.-"),
-];
-
-// Create a HashMap from the MORSE_CODE_MAP
-pub fn create_morse_code_map() -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    for (key, value) in MORSE_CODE_MAP {
-        map.insert(key.to_string(), value.to_string());
+    /// Encrypts the input message using the Vigenère cipher with the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::vigenere_cipher;
+    /// let plaintext = "ATTACKATDAWN".to_string();
+    /// let key = "LEMON".to_string();
+    /// let ciphertext = vigenere_cipher(&plaintext, &key);
+    /// assert_eq!(ciphertext, "LXFOPVEFRNHR");
+    /// ```
+    pub fn vigenere_cipher(plaintext: &str, key: &str) -> String {
+        crate::ciphers::VigenereCipher::new(key).encipher(plaintext)
     }
-    map
-}
 
-// Create a reverse HashMap for decoding
-pub fn create_reverse_morse_code_map() -> HashMap<String, String> {
-    let mut map = HashMap::new();
-    for (key, value) in MORSE_CODE_MAP {
-        map.insert(value.to_string(), key.to_string());
+    /// Decrypts a message encrypted with the Vigenère cipher using the given key.
+    pub fn vigenere_decipher(ciphertext: &str, key: &str) -> String {
+        crate::ciphers::VigenereCipher::new(key).decipher(ciphertext)
     }
-    map
-}
 
-/// Encodes the input message into Morse code.
-///
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::morse_code_cipher;
-/// let input = "HELLO".to_string();
-/// let morse_code = morse_code_cipher(&input);
-/// assert_eq!(morse_code, ".... . .-.. .-.. ---");
-/// ```
-// Function to encode a string into Morse code
-pub fn morse_code_cipher(text: &str) -> String {
-    let morse_code = create_morse_code_map();
-    let mut encoded = String::new();
-
-    for c in text.to_uppercase().chars() {
-        if let Some(code) = morse_code.get(&c.to_string()) {
-            encoded.push_str(code);
-            encoded.push(' '); // Add space between characters
+    // Define the Morse code mapping
+
+    // Define the Morse code mapping
+    const MORSE_CODE_MAP: &[(&str, &str)] = &[
+        ("A", ".-"),
+        ("B", "-..."),
+        ("C", "-.-."),
+        ("D", "-.."),
+        ("E", "."),
+        ("F", "..-."),
+        ("G", "--."),
+        ("H", "...."),
+        ("I", ".."),
+        ("J", ".---"),
+        ("K", "-.-"),
+        ("L", ".-.."),
+        ("M", "--"),
+        ("N", "-."),
+        ("O", "---"),
+        ("P", ".--."),
+        ("Q", "--.-"),
+        ("R", ".-."),
+        ("S", "..."),
+        ("T", "-"),
+        ("U", "..-"),
+        ("V", "...-"),
+        ("W", ".--"),
+        ("X", "-..-"),
+        ("Y", "-.--"),
+        ("Z", "--.."),
+        ("0", "-----"),
+        ("1", ".----"),
+        ("2", "..---"),
+        ("3", "...--"),
+        ("4", "....-"),
+        ("5", "....."),
+        ("6", "-...."),
+        ("7", "--..."),
+        ("8", "---.."),
+        ("9", "----."),
+        (" ", "/"), // Space separator
+        (".", ".-.-.-"),
+        (",", "--..--"),
+        ("?", "..--.."),
+        (";", "-.-.-."),
+        (":", "---..."),
+        ("-", "-....-"),
+        ("/", "-..-."),
+        ("'", ".----."),
+        ("\"", ".-..-."),
+        ("=", "-...-"),
+        ("_", "..--.-"),
+        ("+", ".-.-."),
+        ("-", "-....-"),
+        ("*", "-..-"),
+        ("(", "-.--."),
+        (")", "-.--.-"),
+    ];
+
+    // Create a HashMap from the MORSE_CODE_MAP
+    pub fn create_morse_code_map() -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for (key, value) in MORSE_CODE_MAP {
+            map.insert(key.to_string(), value.to_string());
         }
+        map
     }
 
-    encoded.trim().to_string() // Remove trailing space
-}
-
-// Function to decode Morse code into a string
-pub fn morse_code_decipher(code: &str) -> String {
-    let reverse_morse_code = create_reverse_morse_code_map();
-    let mut decoded = String::new();
-    let code_vec: Vec<&str> = code.split(' ').collect();
+    // Create a reverse HashMap for decoding
+    pub fn create_reverse_morse_code_map() -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for (key, value) in MORSE_CODE_MAP {
+            map.insert(value.to_string(), key.to_string());
+        }
+        map
+    }
 
-    for morse_char in code_vec {
-        if let Some(character) = reverse_morse_code.get(morse_char) {
-            decoded.push_str(character);
+    /// Encodes the input message into Morse code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::morse_code_cipher;
+    /// let input = "HELLO".to_string();
+    /// let morse_code = morse_code_cipher(&input);
+    /// assert_eq!(morse_code, ".... . .-.. .-.. ---");
+    /// ```
+    // Function to encode a string into Morse code
+    pub fn morse_code_cipher(text: &str) -> String {
+        let morse_code = create_morse_code_map();
+        let mut encoded = String::new();
+
+        for c in text.to_uppercase().chars() {
+            if let Some(code) = morse_code.get(&c.to_string()) {
+                encoded.push_str(code);
+                encoded.push(' '); // Add space between characters
+            }
         }
+
+        encoded.trim().to_string() // Remove trailing space
     }
 
-    decoded
-}
+    // Function to decode Morse code into a string
+    pub fn morse_code_decipher(code: &str) -> String {
+        let reverse_morse_code = create_reverse_morse_code_map();
+        let mut decoded = String::new();
+        let code_vec: Vec<&str> = code.split(' ').collect();
 
+        for morse_char in code_vec {
+            if let Some(character) = reverse_morse_code.get(morse_char) {
+                decoded.push_str(character);
+            }
+        }
 
-/// Function to encipher a given plaintext using the Atbash cipher.
-/// 
-/// The Atbash cipher is a simple substitution cipher where each letter in the alphabet is replaced by its corresponding letter at the opposite end of the alphabet
-/// # Parameters
-/// - `plaintext`: The text to be encrypted.
-///
-/// # Returns
-/// - The encrypted ciphertext.
-/// # Examples
-///
-/// ```rust
-/// use cipha::utils::atbash_cipher;
-/// let input = "ATTACKATDAWN".to_string();
-/// let atbash = atbash_cipher(&input);
-/// assert_eq!(atbash, "ZGGZXPZGWZDM");
-/// ```
-pub fn atbash_cipher(plaintext: &str) -> String {
-    plaintext.chars().map(|c| match c {
-            'a'..='z' => ('a' as u8 + 25 - (c as u8 - 'a' as u8)) as char,
-            'A'..='Z' => ('A' as u8 + 25 - (c as u8 - 'A' as u8)) as char,
-            _ => c,
-        }).collect()
-}
+        decoded
+    }
 
-/// Function to decipher a given ciphertext using the Atbash cipher.
-/// 
-/// The Atbash cipher is a simple substitution cipher where each letter in the alphabet is replaced by its corresponding letter at the opposite end of the alphabet
-/// Note: The Atbash cipher is reciprocal, meaning the same function can be used for both encryption and decryption.
-///
-/// # Parameters
-/// - `ciphertext`: The text to be decrypted.
-///
-/// # Returns
-/// - The decrypted plaintext.
-pub fn atbash_decipher(ciphertext: &str) -> String {
-    atbash_cipher(ciphertext)
-}
+    /// Function to encipher a given plaintext using the Atbash cipher.
+    ///
+    /// The Atbash cipher is a simple substitution cipher where each letter in the alphabet is replaced by its corresponding letter at the opposite end of the alphabet
+    /// # Parameters
+    /// - `plaintext`: The text to be encrypted.
+    ///
+    /// # Returns
+    /// - The encrypted ciphertext.
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::atbash_cipher;
+    /// let input = "ATTACKATDAWN".to_string();
+    /// let atbash = atbash_cipher(&input);
+    /// assert_eq!(atbash, "ZGGZXPZGWZDM");
+    /// ```
+    pub fn atbash_cipher(plaintext: &str) -> String {
+        plaintext
+            .chars()
+            .map(|c| match c {
+                'a'..='z' => (b'a' + 25 - (c as u8 - b'a')) as char,
+                'A'..='Z' => (b'A' + 25 - (c as u8 - b'A')) as char,
+                _ => c,
+            })
+            .collect()
+    }
 
+    /// Function to decipher a given ciphertext using the Atbash cipher.
+    ///
+    /// The Atbash cipher is a simple substitution cipher where each letter in the alphabet is replaced by its corresponding letter at the opposite end of the alphabet
+    /// Note: The Atbash cipher is reciprocal, meaning the same function can be used for both encryption and decryption.
+    ///
+    /// # Parameters
+    /// - `ciphertext`: The text to be decrypted.
+    ///
+    /// # Returns
+    /// - The decrypted plaintext.
+    pub fn atbash_decipher(ciphertext: &str) -> String {
+        atbash_cipher(ciphertext)
+    }
 
-/// Function to encipher a given plaintext using the Rail Fence cipher.
+    /// Function to encipher a given plaintext using the Rail Fence cipher.
     ///
     /// # Parameters
     /// - `plaintext`: The text to be encrypted.
@@ -391,25 +455,7 @@ pub fn atbash_decipher(ciphertext: &str) -> String {
     /// # Returns
     /// - The encrypted ciphertext.
     pub fn rail_fence_cipher(plaintext: &str, rails: usize) -> String {
-        if rails == 1 || rails >= plaintext.len() {
-            return plaintext.to_string();
-        }
-
-        let mut fence: Vec<String> = vec![String::new(); rails];
-        let mut dir = 1; // Direction: 1 for downwards, -1 for upwards
-        let mut rail = 0;
-
-        for c in plaintext.chars() {
-            fence[rail].push(c);
-            if rail == 0 {
-                dir = 1;
-            } else if rail == rails - 1 {
-                dir = -1;
-            }
-            rail = (rail as i32 + dir) as usize;
-        }
-
-        fence.iter().map(|s| s.clone()).collect::<Vec<String>>().join("")
+        rail_fence_cipher_with_offset(plaintext, rails, 0)
     }
 
     /// Function to decipher a given ciphertext using the Rail Fence cipher.
@@ -421,58 +467,59 @@ pub fn atbash_decipher(ciphertext: &str) -> String {
     /// # Returns
     /// - The decrypted plaintext.
     pub fn rail_fence_decipher(ciphertext: &str, rails: usize) -> String {
-        if rails == 1 || rails >= ciphertext.len() {
-            return ciphertext.to_string();
-        }
-
-        let mut fence: Vec<String> = vec![String::new(); rails];
-        let mut dir = 1; // Direction: 1 for downwards, -1 for upwards
-        let mut rail = 0;
-
-        // Mark the positions of characters in the fence
-        let mut positions: Vec<usize> = vec![0; rails];
-        for _ in ciphertext.chars() {
-            positions[rail] += 1;
-            if rail == 0 {
-                dir = 1;
-            } else if rail == rails - 1 {
-                dir = -1;
-            }
-            rail = (rail as i32 + dir) as usize;
-        }
+        rail_fence_decipher_with_offset(ciphertext, rails, 0)
+    }
 
-        // Fill the fence with characters from the ciphertext
-        let mut ciphertext_chars = ciphertext.chars();
-        for i in 0..rails {
-            for _ in 0..positions[i] {
-                fence[i].push(ciphertext_chars.next().unwrap());
-            }
+    /// Function to encipher a given plaintext using the Rail Fence cipher, starting the
+    /// zig-zag partway through its cycle.
+    ///
+    /// # Parameters
+    /// - `plaintext`: The text to be encrypted.
+    /// - `rails`: The number of rails in the fence.
+    /// - `offset`: The number of places the zig-zag has moved before the first character.
+    ///
+    /// # Returns
+    /// - The encrypted ciphertext, or the plaintext unchanged if there are no rails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::utils::rail_fence_cipher_with_offset;
+    /// let ciphertext = rail_fence_cipher_with_offset("WEAREDISCOVERED", 3, 2);
+    /// assert_eq!(ciphertext, "AIVDERDSOEEWECR");
+    /// ```
+    pub fn rail_fence_cipher_with_offset(plaintext: &str, rails: usize, offset: usize) -> String {
+        match crate::ciphers::RailFenceCipher::new(rails) {
+            Ok(cipher) => cipher.with_offset(offset).encipher(plaintext),
+            Err(_) => plaintext.to_string(),
         }
+    }
 
-        // Read the characters from the fence in the correct order
-        let mut result = String::new();
-        rail = 0;
-        dir = 1;
-        while result.len() < ciphertext.len() {
-            if !fence[rail].is_empty() {
-                result.push(fence[rail].remove(0));
-            }
-            if rail == 0 {
-                dir = 1;
-            } else if rail == rails - 1 {
-                dir = -1;
-            }
-            rail = (rail as i32 + dir) as usize;
+    /// Function to decipher a given ciphertext using the Rail Fence cipher, starting the
+    /// zig-zag partway through its cycle.
+    ///
+    /// # Parameters
+    /// - `ciphertext`: The text to be decrypted.
+    /// - `rails`: The number of rails in the fence.
+    /// - `offset`: The number of places the zig-zag had moved before the first character.
+    ///
+    /// # Returns
+    /// - The decrypted plaintext, or the ciphertext unchanged if there are no rails.
+    pub fn rail_fence_decipher_with_offset(
+        ciphertext: &str,
+        rails: usize,
+        offset: usize,
+    ) -> String {
+        match crate::ciphers::RailFenceCipher::new(rails) {
+            Ok(cipher) => cipher.with_offset(offset).decipher(ciphertext),
+            Err(_) => ciphertext.to_string(),
         }
-
-        result
     }
-
 }
 
 #[cfg(test)]
 mod tests {
-    
+
     use crate::utils::*;
 
     #[test]
@@ -482,6 +529,13 @@ mod tests {
         assert_eq!(rot13(input), expected);
     }
 
+    #[test]
+    fn test_rot_variants() {
+        assert_eq!(rot5("2024".to_string()), "7579");
+        assert_eq!(rot18("Hello 2024".to_string()), "Uryyb 7579");
+        assert_eq!(rot47(rot47("Hello, World!".to_string())), "Hello, World!");
+    }
+
     #[test]
     fn test_caesar_cipher() {
         let input = "Hello, World!".to_string();
@@ -498,13 +552,13 @@ mod tests {
         assert_eq!(vigenere_cipher(&plaintext, &key), expected);
     }
 
-    // #[test]
-    // fn test_vigenere_decipher() {
-    //     let ciphertext = "hello".to_string();
-    //     let key = "some".to_string();
-    //     let expected = "ATTACKATDAWN".to_string();
-    //     assert_eq!(vigenere_decipher(&ciphertext, &key), expected);
-    // }
+    #[test]
+    fn test_vigenere_decipher() {
+        let ciphertext = "LXFOPVEFRNHR".to_string();
+        let key = "LEMON".to_string();
+        let expected = "ATTACKATDAWN".to_string();
+        assert_eq!(vigenere_decipher(&ciphertext, &key), expected);
+    }
 
     #[test]
     fn test_morse_code_cipher() {
@@ -520,7 +574,6 @@ mod tests {
         assert_eq!(morse_code_decipher(&input), expected);
     }
 
-
     #[test]
     fn test_atbash_cipher() {
         let plaintext = "ATTACKATDAWN";
@@ -531,7 +584,6 @@ mod tests {
         assert_eq!(decrypted_text, plaintext);
     }
 
-
     #[test]
     fn test_rail_fence_cipher() {
         let plaintext = "WEAREDISCOVEREDSAVEYOURSELF";
@@ -542,4 +594,29 @@ mod tests {
         let decrypted_text = rail_fence_decipher(&ciphertext, rails);
         assert_eq!(decrypted_text, plaintext);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rail_fence_offset() {
+        let plaintext = "WEAREDISCOVEREDSAVEYOURSELF";
+        assert_eq!(
+            rail_fence_cipher_with_offset(plaintext, 3, 0),
+            rail_fence_cipher(plaintext, 3)
+        );
+        // A whole cycle of 2 * (rails - 1) is no offset at all
+        assert_eq!(
+            rail_fence_cipher_with_offset(plaintext, 4, 6),
+            rail_fence_cipher(plaintext, 4)
+        );
+        for offset in 0..6 {
+            let ciphertext = rail_fence_cipher_with_offset(plaintext, 4, offset);
+            assert_eq!(
+                rail_fence_decipher_with_offset(&ciphertext, 4, offset),
+                plaintext
+            );
+        }
+        assert_eq!(rail_fence_cipher_with_offset("ABCD", 2, 1), "BDAC");
+        assert_eq!(rail_fence_cipher(plaintext, 1), plaintext);
+        assert_eq!(rail_fence_cipher(plaintext, 0), plaintext);
+        assert_eq!(rail_fence_cipher("Héllo", 9), "Héllo");
+    }
+}
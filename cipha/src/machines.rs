@@ -0,0 +1,1553 @@
+//! Simulators of cipher machines.
+
+use crate::analysis::{english_score, Candidate};
+use crate::budget::{Budget, BudgetTracker};
+use crate::ciphers::{BaudotCode, BaudotFormat, Cipher, UNKNOWN};
+
+/// The rotors of the Wehrmacht and Kriegsmarine Enigma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotor {
+    I,
+    II,
+    III,
+    IV,
+    V,
+    /// Naval rotor with two notches.
+    VI,
+    /// Naval rotor with two notches.
+    VII,
+    /// Naval rotor with two notches.
+    VIII,
+}
+
+impl Rotor {
+    /// Returns the wiring: the letter each of `A`..=`Z` is wired to at ring setting `A`.
+    pub fn wiring(self) -> &'static str {
+        match self {
+            Rotor::I => "EKMFLGDQVZNTOWYHXUSPAIBRCJ",
+            Rotor::II => "AJDKSIRUXBLHWTMCQGZNPYFVOE",
+            Rotor::III => "BDFHJLCPRTXVZNYEIWGAKMUSQO",
+            Rotor::IV => "ESOVPZJAYQUIRHXLNFTGKDCMWB",
+            Rotor::V => "VZBRGITYUPSDNHLXAWMJQOFECK",
+            Rotor::VI => "JPGVOUMFYQBENHZRDKASXLICTW",
+            Rotor::VII => "NZJHGRCXMYSWBOUFAIVLPEKQDT",
+            Rotor::VIII => "FKQHTLXOCBJSPDZRAMEWNIUYGV",
+        }
+    }
+
+    /// Returns the window letters at which the rotor turns its left neighbour over.
+    pub fn notches(self) -> &'static str {
+        match self {
+            Rotor::I => "Q",
+            Rotor::II => "E",
+            Rotor::III => "V",
+            Rotor::IV => "J",
+            Rotor::V => "Z",
+            Rotor::VI | Rotor::VII | Rotor::VIII => "ZM",
+        }
+    }
+}
+
+impl std::str::FromStr for Rotor {
+    type Err = String;
+
+    /// Parses a rotor from its Roman numeral, e.g. `"IV"`.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "I" => Ok(Rotor::I),
+            "II" => Ok(Rotor::II),
+            "III" => Ok(Rotor::III),
+            "IV" => Ok(Rotor::IV),
+            "V" => Ok(Rotor::V),
+            "VI" => Ok(Rotor::VI),
+            "VII" => Ok(Rotor::VII),
+            "VIII" => Ok(Rotor::VIII),
+            _ => Err(format!("Unknown rotor {:?} (expected I to VIII)", name)),
+        }
+    }
+}
+
+/// The reflectors (Umkehrwalzen) of the Enigma.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflector {
+    B,
+    C,
+}
+
+impl Reflector {
+    /// Returns the wiring: the letter each of `A`..=`Z` is reflected to.
+    pub fn wiring(self) -> &'static str {
+        match self {
+            Reflector::B => "YRUHQSLDPXNGOKMIEBFZCWVJAT",
+            Reflector::C => "FVPJIAOYEDRZXWGCTKUQSBNMHL",
+        }
+    }
+}
+
+impl std::str::FromStr for Reflector {
+    type Err = String;
+
+    /// Parses a reflector from its letter, `"B"` or `"C"`.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "B" => Ok(Reflector::B),
+            "C" => Ok(Reflector::C),
+            _ => Err(format!("Unknown reflector {:?} (expected B or C)", name)),
+        }
+    }
+}
+
+// Converts a wiring string to letter numbers.
+fn wiring_table(wiring: &str) -> [u8; 26] {
+    let mut table = [0; 26];
+    for (i, c) in wiring.bytes().enumerate() {
+        table[i] = c - b'A';
+    }
+    table
+}
+
+// Parses three window letters or ring settings into numbers 0..26.
+fn three_settings(settings: &str, what: &str) -> Result<[u8; 3], String> {
+    let values: Vec<u8> = if settings.chars().any(|c| c.is_ascii_digit()) {
+        settings
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| match s.parse::<u8>() {
+                Ok(n @ 1..=26) => Ok(n - 1),
+                _ => Err(format!(
+                    "{} must be numbers from 1 to 26, found {:?}",
+                    what, s
+                )),
+            })
+            .collect::<Result<_, _>>()?
+    } else {
+        settings
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| {
+                if c.is_ascii_alphabetic() {
+                    Ok(c.to_ascii_uppercase() as u8 - b'A')
+                } else {
+                    Err(format!("{} must be letters, found {:?}", what, c))
+                }
+            })
+            .collect::<Result<_, _>>()?
+    };
+    values
+        .try_into()
+        .map_err(|_| format!("{} need one setting per rotor, e.g. AAA", what))
+}
+
+/// A three-rotor Enigma I / M3 machine.
+///
+/// The rotors are given left to right, as they sit in the machine, and so are the window
+/// positions and ring settings. Before every letter the right rotor steps; a rotor showing its
+/// notch letter turns its left neighbour over, and the middle rotor also steps itself when it
+/// turns the left one over (the double step).
+///
+/// The machine is reciprocal, so enciphering and deciphering are the same. Every call starts
+/// from the configured window positions. Letters keep their case, other characters pass
+/// through without moving the rotors, and an [`UNKNOWN`] letter moves them and stays unknown.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::machines::{EnigmaMachine, Reflector, Rotor};
+/// let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B).unwrap();
+/// assert_eq!(enigma.encipher("AAAAA"), "BDZGO");
+///
+/// let enigma = EnigmaMachine::new([Rotor::II, Rotor::IV, Rotor::V], Reflector::B)
+///     .unwrap()
+///     .with_rings("02 21 12")
+///     .unwrap()
+///     .with_positions("BLA")
+///     .unwrap()
+///     .with_plugboard("AV BS CG DL FU HZ IN KM OW RX")
+///     .unwrap();
+/// assert_eq!(enigma.decipher("EDPUD NRGYS ZRCXN"), "AUFKL XABTE ILUNG");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnigmaMachine {
+    rotors: [Rotor; 3],
+    reflector: Reflector,
+    positions: [u8; 3],
+    rings: [u8; 3],
+    plugboard: [u8; 26],
+}
+
+impl EnigmaMachine {
+    /// Creates a machine with the rotors in the given order, left to right, all at window
+    /// position and ring setting `A`, and an empty plugboard.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rotor is used twice.
+    pub fn new(rotors: [Rotor; 3], reflector: Reflector) -> Result<Self, String> {
+        if rotors[0] == rotors[1] || rotors[0] == rotors[2] || rotors[1] == rotors[2] {
+            return Err("Each rotor can only be used once".to_string());
+        }
+        Ok(EnigmaMachine {
+            rotors,
+            reflector,
+            positions: [0; 3],
+            rings: [0; 3],
+            plugboard: std::array::from_fn(|i| i as u8),
+        })
+    }
+
+    /// Sets the starting window positions, left to right, e.g. `"BLA"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the positions are three letters or three numbers from 1 to 26.
+    pub fn with_positions(mut self, positions: &str) -> Result<Self, String> {
+        self.positions = three_settings(positions, "Rotor positions")?;
+        Ok(self)
+    }
+
+    /// Sets the ring settings, left to right, as letters (`"BUL"`) or numbers (`"02 21 12"`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the settings are three letters or three numbers from 1 to 26.
+    pub fn with_rings(mut self, rings: &str) -> Result<Self, String> {
+        self.rings = three_settings(rings, "Ring settings")?;
+        Ok(self)
+    }
+
+    /// Connects pairs of letters on the plugboard, e.g. `"AV BS CG"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pair is not two letters or a letter is plugged twice.
+    pub fn with_plugboard(mut self, pairs: &str) -> Result<Self, String> {
+        let mut plugboard: [u8; 26] = std::array::from_fn(|i| i as u8);
+        for pair in pairs.split(|c: char| c == ',' || c.is_whitespace()) {
+            if pair.is_empty() {
+                continue;
+            }
+            let letters: Vec<u8> = pair.bytes().map(|b| b.to_ascii_uppercase()).collect();
+            let (a, b) = match letters[..] {
+                [a, b] if a.is_ascii_uppercase() && b.is_ascii_uppercase() && a != b => {
+                    ((a - b'A') as usize, (b - b'A') as usize)
+                }
+                _ => return Err(format!("Invalid plugboard pair {:?}", pair)),
+            };
+            if plugboard[a] != a as u8 || plugboard[b] != b as u8 {
+                return Err(format!("Letter plugged twice in {:?}", pair));
+            }
+            plugboard[a] = b as u8;
+            plugboard[b] = a as u8;
+        }
+        self.plugboard = plugboard;
+        Ok(self)
+    }
+
+    /// Returns the rotors, left to right.
+    pub fn rotors(&self) -> [Rotor; 3] {
+        self.rotors
+    }
+
+    /// Returns the reflector.
+    pub fn reflector(&self) -> Reflector {
+        self.reflector
+    }
+
+    /// Returns the starting window positions, left to right.
+    pub fn positions(&self) -> String {
+        self.positions.iter().map(|&p| (b'A' + p) as char).collect()
+    }
+
+    /// Returns the ring settings, left to right.
+    pub fn rings(&self) -> String {
+        self.rings.iter().map(|&r| (b'A' + r) as char).collect()
+    }
+
+    /// Returns the plugboard pairs, e.g. `"AV BS CG"`.
+    pub fn plugboard(&self) -> String {
+        (0..26u8)
+            .filter(|&x| self.plugboard[x as usize] > x)
+            .map(|x| {
+                format!(
+                    "{}{}",
+                    (b'A' + x) as char,
+                    (b'A' + self.plugboard[x as usize]) as char
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the window positions after the machine has processed `letters` letters.
+    pub fn positions_after(&self, letters: usize) -> String {
+        let mut positions = self.positions;
+        for _ in 0..letters {
+            self.step(&mut positions);
+        }
+        positions.iter().map(|&p| (b'A' + p) as char).collect()
+    }
+
+    // Steps the rotors once, including the double step of the middle rotor.
+    fn step(&self, positions: &mut [u8; 3]) {
+        let at_notch = |slot: usize| {
+            self.rotors[slot]
+                .notches()
+                .bytes()
+                .any(|n| n - b'A' == positions[slot])
+        };
+        if at_notch(1) {
+            positions[0] = (positions[0] + 1) % 26;
+            positions[1] = (positions[1] + 1) % 26;
+        } else if at_notch(2) {
+            positions[1] = (positions[1] + 1) % 26;
+        }
+        positions[2] = (positions[2] + 1) % 26;
+    }
+
+    /// Runs the message through the machine, starting from the configured positions.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let scrambler = Scrambler::new(self.rotors, self.reflector);
+        let mut positions = self.positions;
+        plaintext
+            .chars()
+            .map(|c| {
+                if c != UNKNOWN && !c.is_ascii_alphabetic() {
+                    return c;
+                }
+                self.step(&mut positions);
+                if c == UNKNOWN {
+                    return c;
+                }
+                let offsets: [u8; 3] =
+                    std::array::from_fn(|slot| (positions[slot] + 26 - self.rings[slot]) % 26);
+                let x = self.plugboard[(c.to_ascii_uppercase() as u8 - b'A') as usize];
+                let x = scrambler.scramble(x, offsets);
+                let x = (b'A' + self.plugboard[x as usize]) as char;
+                if c.is_ascii_lowercase() {
+                    x.to_ascii_lowercase()
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+
+    /// Runs the message through the machine, which undoes [`encipher`](Self::encipher).
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.encipher(ciphertext)
+    }
+
+    /// Searches for the machines that decipher the message to a text starting with `prefix`,
+    /// such as the `WETTERBERICHT` that opened the daily weather reports.
+    ///
+    /// Every order of three of the given rotors is tried with both reflectors and all 17,576
+    /// start positions, with an empty plugboard. Only letters are compared, ignoring case.
+    ///
+    /// The ring settings of the left and middle rotors only shift their positions, so they are
+    /// taken as `A`. The ring of the right rotor also decides at which letter the middle rotor
+    /// steps, so it is searched too: one machine is returned for each letter of the message the
+    /// first turnover can come at, and the plaintexts tell them apart. The double step of the
+    /// middle rotor, once in 650 letters, may come at the wrong letter, and a message sent with
+    /// plugged letters is not found; see [`bombe`](Self::bombe) for that.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::machines::{EnigmaMachine, Reflector, Rotor};
+    /// let enigma = EnigmaMachine::new([Rotor::II, Rotor::I, Rotor::III], Reflector::B)
+    ///     .unwrap()
+    ///     .with_positions("QMX")
+    ///     .unwrap();
+    /// let ciphertext = enigma.encipher("WETTERBERICHT FUER DIE BUCHT");
+    /// let found = EnigmaMachine::settings_for_prefix(
+    ///     &ciphertext,
+    ///     "WETTERBERICHT",
+    ///     &[Rotor::I, Rotor::II, Rotor::III],
+    /// );
+    /// assert!(found.contains(&enigma));
+    /// assert!(found.iter().all(|m| m.decipher(&ciphertext).starts_with("WETTERBERICHT")));
+    /// ```
+    pub fn settings_for_prefix(ciphertext: &str, prefix: &str, rotors: &[Rotor]) -> Vec<Self> {
+        Self::settings_for_prefix_with_budget(ciphertext, prefix, rotors, &Budget::unlimited())
+    }
+
+    /// Like [`settings_for_prefix`](Self::settings_for_prefix), but stops when the budget runs
+    /// out and returns the machines found so far. Every start position tried is one iteration.
+    pub fn settings_for_prefix_with_budget(
+        ciphertext: &str,
+        prefix: &str,
+        rotors: &[Rotor],
+        budget: &Budget,
+    ) -> Vec<Self> {
+        Self::settings_for_prefix_with_tracker(ciphertext, prefix, rotors, &mut budget.start())
+    }
+
+    /// Like [`settings_for_prefix_with_budget`](Self::settings_for_prefix_with_budget), but
+    /// spends a budget shared with other stages of a search.
+    pub fn settings_for_prefix_with_tracker(
+        ciphertext: &str,
+        prefix: &str,
+        rotors: &[Rotor],
+        tracker: &mut BudgetTracker,
+    ) -> Vec<Self> {
+        let prefix = letter_numbers(prefix);
+        let ciphertext = letter_numbers(ciphertext);
+        if prefix.is_empty() || prefix.len() > ciphertext.len() {
+            return Vec::new();
+        }
+        let mut found = Vec::new();
+        for (machine, scrambler) in machines_of(rotors) {
+            for start in 0..26 * 26 * 26 {
+                if !tracker.tick() {
+                    return found;
+                }
+                let start = [
+                    (start / 676) as u8,
+                    (start / 26 % 26) as u8,
+                    (start % 26) as u8,
+                ];
+                // How many letters of the opening the right ring deciphers before a mismatch
+                let matched = |ring: u8| {
+                    let mut positions = [start[0], start[1], (start[2] + ring) % 26];
+                    prefix
+                        .iter()
+                        .zip(&ciphertext)
+                        .take_while(|&(&p, &c)| {
+                            machine.step(&mut positions);
+                            let offsets =
+                                [positions[0], positions[1], (positions[2] + 26 - ring) % 26];
+                            scrambler.scramble(c, offsets) == p
+                        })
+                        .count()
+                };
+                // Rings whose middle rotor first steps at the same letter of the opening
+                // decipher it alike, so a mismatch before a later turnover rules out all the
+                // rings that turn over after it
+                let mut first_mismatch = prefix.len();
+                let mut fitting = Vec::new();
+                for turnover in (0..26).rev() {
+                    if first_mismatch < turnover.min(prefix.len()) {
+                        continue;
+                    }
+                    for ring in machine.right_rings(start[2], turnover) {
+                        let matched = matched(ring);
+                        first_mismatch = first_mismatch.min(matched);
+                        if matched == prefix.len() {
+                            fitting.push((turnover, ring));
+                        }
+                    }
+                }
+                found.extend(machine.ring_variants(start, &fitting, ciphertext.len()));
+            }
+        }
+        found
+    }
+
+    /// Searches like the Turing-Welchman Bombe for the machines, plugboard included, that
+    /// decipher the message to a text starting with `prefix`.
+    ///
+    /// Each letter of the opening and the ciphertext letter under it make a link of the menu:
+    /// the scrambler at that letter turns what the plugboard connects the one to into what it
+    /// connects the other to. At every start position of every order of three of the given
+    /// rotors, with both reflectors, the letter with the most links is assumed plugged to each
+    /// letter in turn and the links are followed from it. An assumption that plugs a letter to
+    /// two others is dropped, and the ones left are the stops, returned with the pairs they
+    /// found. Letters off the menu are left unplugged, so the plaintext of a stop is only partly
+    /// right until the rest of the plugboard is worked out.
+    ///
+    /// As on the Bombe, the middle rotor is assumed not to step within the opening, and every
+    /// ring of the right rotor that turns it over later is returned as in
+    /// [`settings_for_prefix`](Self::settings_for_prefix).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the links close at least three loops: with fewer, most start
+    /// positions are stops.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::budget::Budget;
+    /// use cipha::machines::{EnigmaMachine, Reflector, Rotor};
+    /// let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B)
+    ///     .unwrap()
+    ///     .with_positions("DOW")
+    ///     .unwrap()
+    ///     .with_plugboard("AE RT")
+    ///     .unwrap();
+    /// let ciphertext = enigma.encipher("WETTERVORHERSAGE FUER DIE DEUTSCHE BUCHT");
+    /// let rotors = [Rotor::I, Rotor::II, Rotor::III];
+    /// assert!(EnigmaMachine::bombe(&ciphertext, "WETTERBERICHT", &rotors).is_err());
+    ///
+    /// // I II III with reflector B is the first rotor order tried
+    /// let budget = Budget::unlimited().with_max_iterations(26 * 26 * 26);
+    /// let found =
+    ///     EnigmaMachine::bombe_with_budget(&ciphertext, "WETTERVORHERSAGE FUER DIE", &rotors, &budget)
+    ///         .unwrap();
+    /// assert!(found.contains(&enigma));
+    /// ```
+    pub fn bombe(ciphertext: &str, prefix: &str, rotors: &[Rotor]) -> Result<Vec<Self>, String> {
+        Self::bombe_with_budget(ciphertext, prefix, rotors, &Budget::unlimited())
+    }
+
+    /// Like [`bombe`](Self::bombe), but stops when the budget runs out and returns the machines
+    /// found so far. Every start position tried is one iteration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the links close at least three loops.
+    pub fn bombe_with_budget(
+        ciphertext: &str,
+        prefix: &str,
+        rotors: &[Rotor],
+        budget: &Budget,
+    ) -> Result<Vec<Self>, String> {
+        Self::bombe_with_tracker(ciphertext, prefix, rotors, &mut budget.start())
+    }
+
+    /// Like [`bombe_with_budget`](Self::bombe_with_budget), but spends a budget shared with
+    /// other stages of a search.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the links close at least three loops.
+    pub fn bombe_with_tracker(
+        ciphertext: &str,
+        prefix: &str,
+        rotors: &[Rotor],
+        tracker: &mut BudgetTracker,
+    ) -> Result<Vec<Self>, String> {
+        let menu = Menu::new(prefix, ciphertext);
+        let loops = menu.loops();
+        if loops < 3 {
+            return Err(format!(
+                "The opening closes {} loops, the Bombe needs at least 3",
+                loops
+            ));
+        }
+        let length = letter_numbers(ciphertext).len();
+        let mut found = Vec::new();
+        for (machine, scrambler) in machines_of(rotors) {
+            // Where the scrambler sends each letter at each of the 17,576 rotor offsets
+            let table: Vec<[u8; 26]> = (0..26 * 26 * 26)
+                .map(|offsets| {
+                    let offsets = [
+                        (offsets / 676) as u8,
+                        (offsets / 26 % 26) as u8,
+                        (offsets % 26) as u8,
+                    ];
+                    std::array::from_fn(|x| scrambler.scramble(x as u8, offsets))
+                })
+                .collect();
+            let mut scramblers = vec![&table[0]; menu.len];
+            for start in 0..26 * 26 * 26 {
+                if !tracker.tick() {
+                    return Ok(found);
+                }
+                let start = [
+                    (start / 676) as u8,
+                    (start / 26 % 26) as u8,
+                    (start % 26) as u8,
+                ];
+                let fitting: Vec<(usize, u8)> = (menu.len..26)
+                    .rev()
+                    .flat_map(|turnover| {
+                        machine
+                            .right_rings(start[2], turnover)
+                            .map(move |ring| (turnover, ring))
+                    })
+                    .collect();
+                let Some(&(_, ring)) = fitting.first() else {
+                    continue;
+                };
+                // The scramblers at the letters of the opening
+                let enigma = machine.at_offsets(start, ring);
+                let mut positions = enigma.positions;
+                for scrambler in scramblers.iter_mut() {
+                    enigma.step(&mut positions);
+                    let [left, middle, right] = enigma.offsets(positions).map(usize::from);
+                    *scrambler = &table[left * 676 + middle * 26 + right];
+                }
+                let mut scramble = |i: usize, x: u8| scramblers[i][x as usize];
+                for partner in 0..26 {
+                    if let Some(plugboard) = menu.follow(partner, &mut scramble) {
+                        let plugged = EnigmaMachine {
+                            plugboard,
+                            ..machine.clone()
+                        };
+                        found.extend(plugged.ring_variants(start, &fitting, length));
+                    }
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    // Returns the machine at the given start offsets, with the right ring `ring`.
+    fn at_offsets(&self, offsets: [u8; 3], ring: u8) -> Self {
+        EnigmaMachine {
+            positions: [offsets[0], offsets[1], (offsets[2] + ring) % 26],
+            rings: [0, 0, ring],
+            ..self.clone()
+        }
+    }
+
+    // Returns how far the rotors at the window positions are turned from their rings.
+    fn offsets(&self, positions: [u8; 3]) -> [u8; 3] {
+        std::array::from_fn(|slot| (positions[slot] + 26 - self.rings[slot]) % 26)
+    }
+
+    // Returns the rings of the right rotor, turned to the offset, that first turn the middle
+    // rotor over after `letters` letters.
+    fn right_rings(&self, offset: u8, letters: usize) -> impl Iterator<Item = u8> {
+        let notches = self.rotors[2].notches().as_bytes();
+        let turnover = move |ring: u8| {
+            notches
+                .iter()
+                .map(|&n| (n - b'A' + 52 - offset - ring) as usize % 26)
+                .min()
+        };
+        notches
+            .iter()
+            .map(move |&n| ((n - b'A') as usize + 52 - offset as usize - letters) as u8 % 26)
+            .filter(move |&ring| turnover(ring) == Some(letters))
+    }
+
+    // Returns the machines at the start offsets with the fitting right rings, latest turnover
+    // first, one for each letter of a message of `length` letters the first turnover comes at.
+    // The rings that turn over after the message all give the same machine, at ring A if it is
+    // one of them.
+    fn ring_variants(&self, offsets: [u8; 3], fitting: &[(usize, u8)], length: usize) -> Vec<Self> {
+        let after_message: Vec<u8> = fitting
+            .iter()
+            .filter(|&&(turnover, _)| turnover >= length)
+            .map(|&(_, ring)| ring)
+            .collect();
+        let ring = if after_message.contains(&0) {
+            Some(0)
+        } else {
+            after_message.first().copied()
+        };
+        ring.into_iter()
+            .chain(
+                fitting
+                    .iter()
+                    .filter(|&&(turnover, _)| turnover < length)
+                    .map(|&(_, ring)| ring),
+            )
+            .map(|ring| self.at_offsets(offsets, ring))
+            .collect()
+    }
+}
+
+// Returns the letters of the text as numbers 0..26, ignoring case and other characters.
+fn letter_numbers(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase() - b'A')
+        .collect()
+}
+
+// Returns a machine and its scrambler for every order of three of the rotors, with both
+// reflectors.
+fn machines_of(rotors: &[Rotor]) -> Vec<(EnigmaMachine, Scrambler)> {
+    let mut machines = Vec::new();
+    for &left in rotors {
+        for &middle in rotors {
+            for &right in rotors {
+                for reflector in [Reflector::B, Reflector::C] {
+                    if let Ok(machine) = EnigmaMachine::new([left, middle, right], reflector) {
+                        let scrambler = Scrambler::new(machine.rotors, reflector);
+                        machines.push((machine, scrambler));
+                    }
+                }
+            }
+        }
+    }
+    machines
+}
+
+// The links between the letters of an opening and the ciphertext under it, as the Bombe
+// was wired up for them.
+struct Menu {
+    // The number of links
+    len: usize,
+    // The letter with the most links, which the Bombe tests the plugboard assumptions on
+    test: u8,
+    // For every letter, the links to it: the letter of the opening they are at and the other
+    // letter
+    links: [Vec<(usize, u8)>; 26],
+}
+
+impl Menu {
+    fn new(prefix: &str, ciphertext: &str) -> Self {
+        let pairs: Vec<(u8, u8)> = letter_numbers(prefix)
+            .into_iter()
+            .zip(letter_numbers(ciphertext))
+            .collect();
+        let mut links: [Vec<(usize, u8)>; 26] = std::array::from_fn(|_| Vec::new());
+        for (i, &(p, c)) in pairs.iter().enumerate() {
+            links[p as usize].push((i, c));
+            links[c as usize].push((i, p));
+        }
+        let test = (0..26)
+            .max_by_key(|&x| (links[x].len(), std::cmp::Reverse(x)))
+            .unwrap_or(0) as u8;
+        Menu {
+            len: pairs.len(),
+            test,
+            links,
+        }
+    }
+
+    // Returns the number of independent loops in the part of the menu linked to the test
+    // letter: its links less its letters, plus one.
+    fn loops(&self) -> usize {
+        let mut reached = [false; 26];
+        reached[self.test as usize] = true;
+        let mut part = vec![self.test];
+        let mut i = 0;
+        while i < part.len() {
+            for &(_, to) in &self.links[part[i] as usize] {
+                if !std::mem::replace(&mut reached[to as usize], true) {
+                    part.push(to);
+                }
+            }
+            i += 1;
+        }
+        // Every link is counted from both its letters
+        let links: usize = part.iter().map(|&x| self.links[x as usize].len()).sum();
+        (links / 2 + 1).saturating_sub(part.len())
+    }
+
+    // Plugs the test letter to `partner` and follows the links, returning the plugboard they
+    // lead to, or `None` if they plug a letter twice. Each pair found is also followed from its
+    // other letter, like the diagonal board of the Bombe.
+    fn follow(&self, partner: u8, scramble: &mut impl FnMut(usize, u8) -> u8) -> Option<[u8; 26]> {
+        let mut plugboard = [u8::MAX; 26];
+        // The letters in the order they were plugged, the ones from `followed` on still to follow
+        let mut plugged = Vec::with_capacity(26);
+        let plug = |plugboard: &mut [u8; 26], plugged: &mut Vec<u8>, a: u8, b: u8| match (
+            plugboard[a as usize],
+            plugboard[b as usize],
+        ) {
+            (x, _) if x == b => true,
+            (u8::MAX, u8::MAX) => {
+                plugboard[a as usize] = b;
+                plugboard[b as usize] = a;
+                plugged.push(a);
+                if a != b {
+                    plugged.push(b);
+                }
+                true
+            }
+            _ => false,
+        };
+        plug(&mut plugboard, &mut plugged, self.test, partner);
+        let mut followed = 0;
+        while let Some(&a) = plugged.get(followed) {
+            followed += 1;
+            let b = plugboard[a as usize];
+            for &(i, to) in &self.links[a as usize] {
+                if !plug(&mut plugboard, &mut plugged, to, scramble(i, b)) {
+                    return None;
+                }
+            }
+        }
+        Some(std::array::from_fn(|x| match plugboard[x] {
+            u8::MAX => x as u8,
+            y => y,
+        }))
+    }
+}
+
+// The wiring of the rotors in their slots and of the reflector, as letter tables.
+struct Scrambler {
+    forward: [[u8; 26]; 3],
+    backward: [[u8; 26]; 3],
+    reflector: [u8; 26],
+}
+
+impl Scrambler {
+    fn new(rotors: [Rotor; 3], reflector: Reflector) -> Self {
+        let forward = rotors.map(|rotor| wiring_table(rotor.wiring()));
+        let backward = forward.map(|wiring| {
+            let mut inverse = [0; 26];
+            for (i, &w) in wiring.iter().enumerate() {
+                inverse[w as usize] = i as u8;
+            }
+            inverse
+        });
+        Scrambler {
+            forward,
+            backward,
+            reflector: wiring_table(reflector.wiring()),
+        }
+    }
+
+    // Sends a letter through the rotors, turned by the offsets, to the reflector and back.
+    fn scramble(&self, x: u8, offsets: [u8; 3]) -> u8 {
+        let through = |wiring: &[u8; 26], c: u8, offset: u8| {
+            (wiring[((c + offset) % 26) as usize] + 26 - offset) % 26
+        };
+        let mut x = x;
+        for slot in (0..3).rev() {
+            x = through(&self.forward[slot], x, offsets[slot]);
+        }
+        x = self.reflector[x as usize];
+        for (backward, &offset) in self.backward.iter().zip(&offsets) {
+            x = through(backward, x, offset);
+        }
+        x
+    }
+}
+
+impl Cipher for EnigmaMachine {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// A Jefferson wheel cypher, the cylinder the US Army issued as the M-94.
+///
+/// A stack of wheels, each with its own scrambled alphabet around the rim, turns on a common
+/// axle. The clerk takes the wheels in the agreed order, spells the first letters of the
+/// message along one row and copies out any other row as the ciphertext; the next letters go
+/// on the wheels in the same way. The receiver spells the ciphertext on the wheels and searches
+/// the other rows for the one that reads as plain language.
+///
+/// Here the row is chosen by its offset: the ciphertext letter is found `offset` places further
+/// round the wheel than the plaintext letter. Letters keep their case, other characters pass
+/// through, and an [`UNKNOWN`] letter takes up a wheel and stays unknown.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::machines::JeffersonWheel;
+/// let wheels = JeffersonWheel::new(&[
+///     "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+///     "ZYXWVUTSRQPONMLKJIHGFEDCBA",
+///     "QWERTYUIOPASDFGHJKLZXCVBNM",
+/// ])
+/// .unwrap()
+/// .with_order(&[3, 1, 2])
+/// .unwrap()
+/// .with_offset(2)
+/// .unwrap();
+/// let ciphertext = wheels.encipher("Retreat at once");
+/// assert_eq!(ciphertext, "Ygrygyu cr apat");
+/// assert_eq!(wheels.decipher(&ciphertext), "Retreat at once");
+/// assert_eq!(wheels.rows(&ciphertext)[0].key, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JeffersonWheel {
+    wheels: Vec<[u8; 26]>,
+    order: Vec<usize>,
+    offset: u8,
+}
+
+impl JeffersonWheel {
+    /// Creates a cylinder of wheels with the given alphabets, used in the given order, with
+    /// the ciphertext read one row below the plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no wheels or an alphabet does not have each letter once.
+    pub fn new(wheels: &[&str]) -> Result<Self, String> {
+        if wheels.is_empty() {
+            return Err("A cylinder needs at least one wheel".to_string());
+        }
+        let wheels = wheels
+            .iter()
+            .enumerate()
+            .map(|(number, alphabet)| {
+                let mut wheel = [0; 26];
+                let mut seen = [false; 26];
+                let letters = alphabet.bytes().map(|b| b.to_ascii_uppercase());
+                let mut count = 0;
+                for (i, b) in letters.enumerate() {
+                    if i >= 26
+                        || !b.is_ascii_uppercase()
+                        || std::mem::replace(&mut seen[(b - b'A') as usize], true)
+                    {
+                        break;
+                    }
+                    wheel[i] = b - b'A';
+                    count += 1;
+                }
+                if count != 26 || alphabet.len() != 26 {
+                    return Err(format!(
+                        "Wheel {} must have each letter A to Z once, found {:?}",
+                        number + 1,
+                        alphabet
+                    ));
+                }
+                Ok(wheel)
+            })
+            .collect::<Result<Vec<[u8; 26]>, String>>()?;
+        Ok(JeffersonWheel {
+            order: (0..wheels.len()).collect(),
+            wheels,
+            offset: 1,
+        })
+    }
+
+    /// Puts the wheels on the axle in the given order, by their numbers from 1, e.g. `[3, 1, 2]`.
+    /// Wheels left out are not used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the order is empty, or a number is used twice or has no wheel.
+    pub fn with_order(mut self, order: &[usize]) -> Result<Self, String> {
+        if order.is_empty() {
+            return Err("The wheel order needs at least one wheel".to_string());
+        }
+        let mut used = vec![false; self.wheels.len()];
+        for &number in order {
+            match number.checked_sub(1).and_then(|i| used.get_mut(i)) {
+                Some(used) if !*used => *used = true,
+                Some(_) => return Err(format!("Wheel {} can only be used once", number)),
+                None => {
+                    return Err(format!(
+                        "Wheel numbers must be from 1 to {}, found {}",
+                        self.wheels.len(),
+                        number
+                    ))
+                }
+            }
+        }
+        self.order = order.iter().map(|number| number - 1).collect();
+        Ok(self)
+    }
+
+    /// Sets how many rows below the plaintext the ciphertext is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless the offset is from 1 to 25.
+    pub fn with_offset(mut self, offset: u8) -> Result<Self, String> {
+        if !(1..26).contains(&offset) {
+            return Err(format!("Row offset must be from 1 to 25, found {}", offset));
+        }
+        self.offset = offset;
+        Ok(self)
+    }
+
+    /// Returns the wheel numbers from 1 in the order they are used.
+    pub fn order(&self) -> Vec<usize> {
+        self.order.iter().map(|i| i + 1).collect()
+    }
+
+    /// Returns the row offset.
+    pub fn offset(&self) -> u8 {
+        self.offset
+    }
+
+    // Turns every letter the given number of places round its wheel.
+    fn turn(&self, text: &str, places: usize) -> String {
+        let inverses: Vec<[u8; 26]> = self
+            .wheels
+            .iter()
+            .map(|wheel| {
+                let mut inverse = [0; 26];
+                for (i, &w) in wheel.iter().enumerate() {
+                    inverse[w as usize] = i as u8;
+                }
+                inverse
+            })
+            .collect();
+        let mut letters = 0;
+        text.chars()
+            .map(|c| {
+                if c != UNKNOWN && !c.is_ascii_alphabetic() {
+                    return c;
+                }
+                let wheel = self.order[letters % self.order.len()];
+                letters += 1;
+                if c == UNKNOWN {
+                    return c;
+                }
+                let place = inverses[wheel][(c.to_ascii_uppercase() as u8 - b'A') as usize];
+                let x = (b'A' + self.wheels[wheel][(place as usize + places) % 26]) as char;
+                if c.is_ascii_lowercase() {
+                    x.to_ascii_lowercase()
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+
+    /// Reads the message off the row at the configured offset.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        self.turn(plaintext, self.offset as usize)
+    }
+
+    /// Reads the ciphertext back off the row `offset` places above it.
+    pub fn decipher_row(&self, ciphertext: &str, offset: u8) -> String {
+        self.turn(ciphertext, 26 - offset as usize % 26)
+    }
+
+    /// Reads all 25 other rows of the ciphertext, keyed by their offset and ranked by
+    /// [`english_score`], the most English-like first.
+    pub fn rows(&self, ciphertext: &str) -> Vec<Candidate> {
+        let mut rows: Vec<Candidate> = (1..26)
+            .map(|offset| {
+                let plaintext = self.decipher_row(ciphertext, offset);
+                Candidate {
+                    key: offset as usize,
+                    score: english_score(&plaintext),
+                    plaintext,
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| b.score.total_cmp(&a.score));
+        rows
+    }
+
+    /// Reads the ciphertext back off the row at the configured offset. Use [`rows`](Self::rows)
+    /// to search for the plaintext row when the offset is not known.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher_row(ciphertext, self.offset)
+    }
+}
+
+impl Cipher for JeffersonWheel {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+/// The number of pins on the Lorenz wheels: the five chi wheels, the five psi wheels, and the
+/// motor wheels mu61 and mu37.
+pub const LORENZ_WHEEL_SIZES: [usize; 12] = [41, 31, 29, 26, 23, 43, 47, 51, 53, 59, 61, 37];
+
+/// A Lorenz SZ40 teleprinter attachment, the machine Bletchley Park called Tunny.
+///
+/// The message is turned into 5-bit ITA2 codes by a [`BaudotCode`] converter and each code is
+/// added, bit by bit, to a key made of one pin from each of the five chi wheels and one from
+/// each of the five psi wheels. Adding the same key again takes it off, so deciphering is the
+/// same operation. The chi wheels and the mu61 motor wheel step with every character, mu37
+/// steps when the mu61 pin was active, and the psi wheels step together when the mu37 pin was
+/// active. The limitations of the later models are not simulated.
+///
+/// The pins are set with one pattern per wheel, in the order chi1 to chi5, psi1 to psi5, mu61
+/// and mu37, with `x` (or `1`) for an active pin and `.` (or `0`) for an inactive one. The
+/// ciphertext is written in the [`BaudotFormat::Bletchley`] letters by default.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::machines::{LorenzMachine, LORENZ_WHEEL_SIZES};
+/// let patterns: Vec<String> = LORENZ_WHEEL_SIZES
+///     .iter()
+///     .map(|&size| (0..size).map(|i| if i % 3 == 0 { 'x' } else { '.' }).collect())
+///     .collect();
+/// let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+/// let lorenz = LorenzMachine::new(&patterns)
+///     .unwrap()
+///     .with_positions(&[5, 12, 7, 1, 3, 30, 2, 40, 9, 11, 17, 21])
+///     .unwrap();
+/// let ciphertext = lorenz.encipher("Attack at dawn");
+/// assert_eq!(lorenz.decipher(&ciphertext), "ATTACK AT DAWN");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LorenzMachine {
+    pins: [Vec<bool>; 12],
+    positions: [usize; 12],
+    format: BaudotFormat,
+}
+
+impl LorenzMachine {
+    /// Creates a machine with the given pin patterns and all wheels at position 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are twelve patterns of `x`/`.` or `1`/`0` with the
+    /// lengths in [`LORENZ_WHEEL_SIZES`].
+    pub fn new(patterns: &[&str]) -> Result<Self, String> {
+        if patterns.len() != 12 {
+            return Err(format!(
+                "The Lorenz machine needs 12 pin patterns, found {}",
+                patterns.len()
+            ));
+        }
+        let mut pins: [Vec<bool>; 12] = Default::default();
+        for (wheel, pattern) in patterns.iter().enumerate() {
+            pins[wheel] = pattern
+                .chars()
+                .map(|c| match c {
+                    'x' | 'X' | '1' => Ok(true),
+                    '.' | '0' => Ok(false),
+                    _ => Err(format!(
+                        "Invalid pin {:?} on {}, use x or . (1 or 0)",
+                        c,
+                        lorenz_wheel_name(wheel)
+                    )),
+                })
+                .collect::<Result<_, _>>()?;
+            if pins[wheel].len() != LORENZ_WHEEL_SIZES[wheel] {
+                return Err(format!(
+                    "{} has {} pins, found {}",
+                    lorenz_wheel_name(wheel),
+                    LORENZ_WHEEL_SIZES[wheel],
+                    pins[wheel].len()
+                ));
+            }
+        }
+        Ok(LorenzMachine {
+            pins,
+            positions: [0; 12],
+            format: BaudotFormat::Bletchley,
+        })
+    }
+
+    /// Sets the start positions of the twelve wheels, numbered from 1, in the same order as
+    /// the patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless there are twelve positions, each within its wheel.
+    pub fn with_positions(mut self, positions: &[usize]) -> Result<Self, String> {
+        if positions.len() != 12 {
+            return Err(format!(
+                "The Lorenz machine needs 12 wheel positions, found {}",
+                positions.len()
+            ));
+        }
+        for (wheel, &position) in positions.iter().enumerate() {
+            if !(1..=LORENZ_WHEEL_SIZES[wheel]).contains(&position) {
+                return Err(format!(
+                    "The position of {} must be from 1 to {}, found {}",
+                    lorenz_wheel_name(wheel),
+                    LORENZ_WHEEL_SIZES[wheel],
+                    position
+                ));
+            }
+            self.positions[wheel] = position - 1;
+        }
+        Ok(self)
+    }
+
+    /// Sets the format the ciphertext is written in.
+    pub fn with_format(mut self, format: BaudotFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the start positions of the wheels, numbered from 1.
+    pub fn positions(&self) -> [usize; 12] {
+        self.positions.map(|position| position + 1)
+    }
+
+    /// Returns the first `len` codes of the key, the chi and psi pins added together.
+    pub fn key_stream(&self, len: usize) -> Vec<u8> {
+        let mut positions = self.positions;
+        let pin = |positions: &[usize; 12], wheel: usize| self.pins[wheel][positions[wheel]];
+        (0..len)
+            .map(|_| {
+                let key = (0..5).fold(0, |key, impulse| {
+                    let bit = pin(&positions, impulse) ^ pin(&positions, impulse + 5);
+                    key | u8::from(bit) << impulse
+                });
+                let mu61 = pin(&positions, 10);
+                let mu37 = pin(&positions, 11);
+                let mut step = |wheel: usize| {
+                    positions[wheel] = (positions[wheel] + 1) % LORENZ_WHEEL_SIZES[wheel];
+                };
+                (0..5).for_each(&mut step);
+                step(10);
+                if mu61 {
+                    step(11);
+                }
+                if mu37 {
+                    (5..10).for_each(&mut step);
+                }
+                key
+            })
+            .collect()
+    }
+
+    /// Adds the key to 5-bit codes; adding it again takes it off.
+    pub fn encipher_codes(&self, codes: &[u8]) -> Vec<u8> {
+        codes
+            .iter()
+            .zip(self.key_stream(codes.len()))
+            .map(|(&code, key)| if code < 32 { code ^ key } else { code })
+            .collect()
+    }
+
+    /// Takes the key off 5-bit codes, the same operation as [`LorenzMachine::encipher_codes`].
+    pub fn decipher_codes(&self, codes: &[u8]) -> Vec<u8> {
+        self.encipher_codes(codes)
+    }
+
+    /// Converts the text to ITA2, adds the key and writes the codes in the machine's format.
+    pub fn encipher(&self, plaintext: &str) -> String {
+        let baudot = BaudotCode::new().with_format(self.format);
+        baudot.write(&self.encipher_codes(&baudot.codes(plaintext)))
+    }
+
+    /// Reads the codes in the machine's format, takes the key off and prints the text.
+    pub fn decipher(&self, ciphertext: &str) -> String {
+        let baudot = BaudotCode::new().with_format(self.format);
+        baudot.text(&self.decipher_codes(&baudot.read(ciphertext)))
+    }
+}
+
+impl Cipher for LorenzMachine {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.encipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.decipher(ciphertext)
+    }
+}
+
+fn lorenz_wheel_name(wheel: usize) -> String {
+    match wheel {
+        0..=4 => format!("chi{}", wheel + 1),
+        5..=9 => format!("psi{}", wheel - 4),
+        10 => "mu61".to_string(),
+        _ => "mu37".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_stepping() {
+        let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B)
+            .unwrap()
+            .with_positions("ADU")
+            .unwrap();
+        let positions: Vec<String> = (0..4).map(|n| enigma.positions_after(n)).collect();
+        assert_eq!(positions, vec!["ADU", "ADV", "AEW", "BFX"]);
+
+        // Rotors VI to VIII turn over at two letters
+        let naval = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::VI], Reflector::C)
+            .unwrap()
+            .with_positions("AAL")
+            .unwrap();
+        assert_eq!(naval.positions_after(2), "ABN");
+    }
+
+    #[test]
+    fn test_enigma_settings_for_prefix() {
+        let rotors = [Rotor::I, Rotor::IV, Rotor::V];
+        let message = "Dear Karl, meet me at noon";
+        // Rings other than A are found at the positions they shift to
+        let enigma = EnigmaMachine::new([Rotor::V, Rotor::I, Rotor::IV], Reflector::C)
+            .unwrap()
+            .with_rings("A A C")
+            .unwrap()
+            .with_positions("KDM")
+            .unwrap();
+        let ciphertext = enigma.encipher(message);
+        let found = EnigmaMachine::settings_for_prefix(&ciphertext, "DEAR KARL", &rotors);
+        let right: Vec<String> = found
+            .iter()
+            .filter(|m| m.decipher(&ciphertext) == message)
+            .map(|m| format!("{} {}", m.positions(), m.rings()))
+            .collect();
+        // Ring C steps the middle rotor after the message, like ring A, and from KCJ with ring
+        // Z it steps from C to D at the first letter
+        assert_eq!(right, ["KCJ AAZ", "KDK AAA"]);
+        // The other rings of the right rotor step it at each later letter
+        assert_eq!(found.len(), right.len() + 21 - 9);
+
+        let plugged = enigma.with_plugboard("DQ EU").unwrap().encipher(message);
+        assert!(EnigmaMachine::settings_for_prefix(&plugged, "DEAR KARL", &rotors).is_empty());
+        assert!(EnigmaMachine::settings_for_prefix("ABC", "DEAR", &rotors).is_empty());
+        assert!(EnigmaMachine::settings_for_prefix("ABC", "", &rotors).is_empty());
+    }
+
+    #[test]
+    fn test_enigma_settings_for_prefix_turnover() {
+        let enigma = EnigmaMachine::new([Rotor::III, Rotor::V, Rotor::I], Reflector::B)
+            .unwrap()
+            .with_rings("C F P")
+            .unwrap()
+            .with_positions("MAX")
+            .unwrap();
+        let message = "Attack at dawn on the northern bridge with two tanks";
+        let ciphertext = enigma.encipher(message);
+        // With the rings at A the positions are shifted to KVI, which steps the middle rotor at
+        // the wrong letter
+        let shifted = EnigmaMachine::new([Rotor::III, Rotor::V, Rotor::I], Reflector::B)
+            .unwrap()
+            .with_positions("KVI")
+            .unwrap();
+        assert_eq!(
+            shifted.decipher(&ciphertext),
+            "Attack at dvhh lo zut hqrthern bridge witn iil tmjrc"
+        );
+        let rotors = [Rotor::I, Rotor::III, Rotor::V];
+        let found = EnigmaMachine::settings_for_prefix(&ciphertext, "ATTACK AT DAWN", &rotors);
+        let right: Vec<&EnigmaMachine> = found
+            .iter()
+            .filter(|m| m.decipher(&ciphertext) == message)
+            .collect();
+        assert_eq!(right.len(), 1);
+        assert_eq!(right[0].positions(), "KVX");
+        assert_eq!(right[0].rings(), "AAP");
+    }
+
+    #[test]
+    fn test_enigma_reciprocal() {
+        let enigma = EnigmaMachine::new([Rotor::VIII, Rotor::V, Rotor::I], Reflector::C)
+            .unwrap()
+            .with_rings("5 26 1")
+            .unwrap()
+            .with_positions("QEV")
+            .unwrap()
+            .with_plugboard("PO ML IU KJ NH YT GB VF RE DC")
+            .unwrap();
+        let message = "Wetterbericht: ?heute klar";
+        let ciphertext = enigma.encipher(message);
+        assert_eq!(enigma.decipher(&ciphertext), message);
+        // The Enigma never enciphers a letter to itself
+        for (p, c) in message.chars().zip(ciphertext.chars()) {
+            assert!(!p.is_ascii_alphabetic() || p != c);
+        }
+        assert_eq!(ciphertext.find('?'), Some(15));
+    }
+
+    #[test]
+    fn test_enigma_barbarossa() {
+        // The first part of a 1941 message from Operation Barbarossa, as published by Frode
+        // Weierud: the message key KCH at the ground setting WXC gives BLA
+        let enigma = EnigmaMachine::new([Rotor::II, Rotor::IV, Rotor::V], Reflector::B)
+            .unwrap()
+            .with_rings("BUL")
+            .unwrap()
+            .with_plugboard("AV BS CG DL FU HZ IN KM OW RX")
+            .unwrap();
+        let ground = enigma.clone().with_positions("WXC").unwrap();
+        assert_eq!(ground.decipher("KCH"), "BLA");
+        let enigma = enigma.with_positions("BLA").unwrap();
+        let ciphertext = "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK \
+                          UBPMM YLKLT TDEIS MDICA GYKUA CTCDO MOHWX MUUIA UBSTS LRNBZ SZWNR \
+                          FXWFY SSXJZ VIJHI DISHP RKLKA YUPAD TXQSP INQMA TLPIF SVKDA SCTAC \
+                          DPBOP VHJK";
+        assert_eq!(
+            enigma.decipher(&ciphertext.replace(' ', "")),
+            "AUFKLXABTEILUNGXVONXKURTINOWAXKURTINOWAXNORDWESTLXSEBEZXSEBEZXUAFFLIEGERSTRASZERI\
+             QTUNGXDUBROWKIXDUBROWKIXOPOTSCHKAXOPOTSCHKAXUMXEINSAQTDREINULLXUHRANGETRETENXANGRIFFX\
+             INFXRGTX"
+        );
+    }
+
+    #[test]
+    fn test_enigma_bombe() {
+        let enigma = EnigmaMachine::new([Rotor::II, Rotor::IV, Rotor::V], Reflector::B)
+            .unwrap()
+            .with_rings("BUL")
+            .unwrap()
+            .with_positions("BLA")
+            .unwrap()
+            .with_plugboard("AV BS CG DL FU HZ IN KM OW RX")
+            .unwrap();
+        let ciphertext = "EDPUD NRGYS ZRCXN UYTPO MRMBO FKTBZ REZKM LXLVE FGUEY SIOZV EQMIK";
+        let plaintext = enigma.decipher(ciphertext);
+        let rotors = [Rotor::II, Rotor::IV, Rotor::V];
+        assert_eq!(
+            EnigmaMachine::bombe(ciphertext, "AUFKLXABTEILUNGXVONX", &rotors),
+            Err("The opening closes 1 loops, the Bombe needs at least 3".to_string())
+        );
+
+        // II IV V with reflector B is the first rotor order tried
+        let budget = Budget::unlimited().with_max_iterations(26 * 26 * 26);
+        let found = EnigmaMachine::bombe_with_budget(
+            ciphertext,
+            "AUFKLXABTEILUNGXVONXKURT",
+            &rotors,
+            &budget,
+        )
+        .unwrap();
+        assert_eq!(found.len(), 2);
+        // The rings of the left and middle rotors are taken as A, and the right ring is L
+        assert_eq!(found[0].positions(), "ARA");
+        assert_eq!(found[0].rings(), "AAL");
+        assert_eq!(found[0].plugboard(), "AV BS CG DL FU HZ IN KM OW RX");
+        assert_eq!(found[0].decipher(ciphertext), plaintext);
+        assert_ne!(found[1].decipher(ciphertext), plaintext);
+    }
+
+    #[test]
+    fn test_enigma_settings_errors() {
+        let enigma = EnigmaMachine::new([Rotor::I, Rotor::II, Rotor::III], Reflector::B).unwrap();
+        assert!(EnigmaMachine::new([Rotor::I, Rotor::I, Rotor::III], Reflector::B).is_err());
+        assert!(enigma.clone().with_positions("AB").is_err());
+        assert!(enigma.clone().with_rings("1 2 27").is_err());
+        assert!(enigma.clone().with_plugboard("AB BC").is_err());
+        assert!(enigma.clone().with_plugboard("AA").is_err());
+        assert_eq!("viii".parse::<Rotor>(), Ok(Rotor::VIII));
+        assert!("IX".parse::<Rotor>().is_err());
+        assert_eq!("c".parse::<Reflector>(), Ok(Reflector::C));
+    }
+    #[test]
+    fn test_jefferson_wheel_row_search() {
+        let alphabets = [
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            "ZYXWVUTSRQPONMLKJIHGFEDCBA",
+            "QWERTYUIOPASDFGHJKLZXCVBNM",
+            "MNBVCXZLKJHGFDSAPOIUYTREWQ",
+            "PLOKMIJNUHBYGVTFCRDXESZWAQ",
+        ];
+        let wheels = JeffersonWheel::new(&alphabets)
+            .unwrap()
+            .with_order(&[4, 2, 5, 1])
+            .unwrap()
+            .with_offset(17)
+            .unwrap();
+        assert_eq!(wheels.order(), vec![4, 2, 5, 1]);
+        let message = "The enemy is massing troops north of the river";
+        let ciphertext = wheels.encipher(message);
+        assert_ne!(ciphertext, message);
+        assert_eq!(wheels.decipher_row(&ciphertext, 17), message);
+
+        // The receiver finds the plaintext row without knowing the offset
+        let rows = wheels.rows(&ciphertext);
+        assert_eq!(rows.len(), 25);
+        assert_eq!(rows[0].key, 17);
+        assert_eq!(rows[0].plaintext, message);
+        assert_eq!(wheels.decipher(&ciphertext), message);
+        let wrong_row = wheels.clone().with_offset(3).unwrap();
+        assert_ne!(Cipher::decipher(&wrong_row, &ciphertext), message);
+        assert_eq!(wheels.encipher("a?b c").chars().nth(1), Some(UNKNOWN));
+    }
+
+    #[test]
+    fn test_jefferson_wheel_errors() {
+        let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        assert!(JeffersonWheel::new(&[]).is_err());
+        assert!(JeffersonWheel::new(&["ABC"]).is_err());
+        assert!(JeffersonWheel::new(&["AACDEFGHIJKLMNOPQRSTUVWXYZ"]).is_err());
+        assert!(JeffersonWheel::new(&["ABCDEFGHIJKLMNOPQRSTUVWXYZA"]).is_err());
+        let wheels = JeffersonWheel::new(&[alphabet, alphabet]).unwrap();
+        assert_eq!(wheels.offset(), 1);
+        assert!(wheels.clone().with_order(&[]).is_err());
+        assert!(wheels.clone().with_order(&[1, 1]).is_err());
+        assert!(wheels.clone().with_order(&[0]).is_err());
+        assert!(wheels.clone().with_order(&[3]).is_err());
+        assert!(wheels.clone().with_offset(0).is_err());
+        assert!(wheels.clone().with_offset(26).is_err());
+        assert_eq!(wheels.encipher("Hazy"), "Ibaz");
+    }
+
+    fn lorenz_patterns(pins: impl Fn(usize, usize) -> bool) -> Vec<String> {
+        LORENZ_WHEEL_SIZES
+            .iter()
+            .enumerate()
+            .map(|(wheel, &size)| {
+                (0..size)
+                    .map(|i| if pins(wheel, i) { 'x' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lorenz_machine() {
+        let patterns = lorenz_patterns(|wheel, i| (i * 7 + wheel * 3) % 5 < 2);
+        let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        let lorenz = LorenzMachine::new(&patterns)
+            .unwrap()
+            .with_positions(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12])
+            .unwrap();
+        assert_eq!(lorenz.positions()[11], 12);
+        let message = "Move 1st Panzer to Kursk\nStop";
+        let ciphertext = lorenz.encipher(message);
+        assert_ne!(
+            ciphertext,
+            BaudotCode::new()
+                .with_format(BaudotFormat::Bletchley)
+                .encode(message)
+        );
+        assert_eq!(lorenz.decipher(&ciphertext), message.to_uppercase());
+
+        let hex = lorenz.clone().with_format(BaudotFormat::Hex);
+        assert_eq!(hex.decipher(&hex.encipher(message)), message.to_uppercase());
+        let codes = [0, 1, 2, 31, 32];
+        let enciphered = lorenz.encipher_codes(&codes);
+        assert_eq!(enciphered[4], 32);
+        assert_eq!(lorenz.decipher_codes(&enciphered), codes);
+    }
+
+    #[test]
+    fn test_lorenz_motor() {
+        // chi1 and psi1 have a single active pin at the start, the other wheels none
+        let still = lorenz_patterns(|wheel, i| (wheel == 0 || wheel == 5) && i == 0);
+        let still: Vec<&str> = still.iter().map(String::as_str).collect();
+        let lorenz = LorenzMachine::new(&still).unwrap();
+        // Both pins add at the first character, then the chi moves on and the psi stays
+        assert_eq!(lorenz.key_stream(3), [0, 1, 1]);
+
+        // With every motor pin active the psi wheels step too
+        let moving = lorenz_patterns(|wheel, i| wheel >= 10 || (wheel == 5 && i == 0));
+        let moving: Vec<&str> = moving.iter().map(String::as_str).collect();
+        let lorenz = LorenzMachine::new(&moving).unwrap();
+        assert_eq!(lorenz.key_stream(3), [1, 0, 0]);
+        let lorenz = lorenz
+            .with_positions(&[1, 1, 1, 1, 1, 43, 1, 1, 1, 1, 1, 1])
+            .unwrap();
+        assert_eq!(lorenz.key_stream(3), [0, 1, 0]);
+    }
+
+    #[test]
+    fn test_lorenz_known_answer() {
+        // Worked by hand from the wheel motion described in the General Report on Tunny:
+        // chi1 has pins 1 and 2 active and chi2 pin 1, psi1 pins 1 and 2 and psi2 pin 2, mu61
+        // pin 1 and mu37 pin 2. The mu61 pin moves mu37 on after the first letter, and from
+        // then on the mu37 pin moves the psi wheels after every letter.
+        let patterns = lorenz_patterns(|wheel, i| {
+            matches!(
+                (wheel, i),
+                (0, 0 | 1) | (1, 0) | (5, 0 | 1) | (6, 1) | (10, 0) | (11, 1)
+            )
+        });
+        let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        let lorenz = LorenzMachine::new(&patterns).unwrap();
+        // The chi impulses xx, x., .. and .. plus the psi impulses x., x., xx and .. give 3/A/
+        assert_eq!(lorenz.key_stream(4), [0b00010, 0, 0b00011, 0]);
+        assert_eq!(
+            BaudotCode::new()
+                .with_format(BaudotFormat::Bletchley)
+                .write(&lorenz.key_stream(4)),
+            "3/A/"
+        );
+        // A + 3 = E, B + / = B, C + A = F and D + / = D in the Tunny addition square
+        assert_eq!(lorenz.encipher("ABCD"), "EBFD");
+        assert_eq!(lorenz.decipher("EBFD"), "ABCD");
+    }
+
+    #[test]
+    fn test_lorenz_errors() {
+        let patterns = lorenz_patterns(|_, _| false);
+        let mut patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+        let lorenz = LorenzMachine::new(&patterns).unwrap();
+        assert!(LorenzMachine::new(&patterns[..11]).is_err());
+        assert!(lorenz.clone().with_positions(&[1; 11]).is_err());
+        assert!(lorenz.clone().with_positions(&[0; 12]).is_err());
+        let mut positions = [1; 12];
+        positions[3] = 27;
+        assert!(lorenz.clone().with_positions(&positions).is_err());
+        patterns[2] = "x.";
+        assert!(LorenzMachine::new(&patterns).is_err());
+        let bad = "o".repeat(41);
+        patterns[0] = &bad;
+        assert!(LorenzMachine::new(&patterns).is_err());
+    }
+}
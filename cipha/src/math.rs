@@ -0,0 +1,154 @@
+//! Small number theory helpers shared by the ciphers.
+
+/// Returns the greatest common divisor of `a` and `b`.
+pub(crate) fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Returns `a` reduced into `0..m`, also for negative `a`.
+pub(crate) fn modulo(a: i64, m: i64) -> i64 {
+    ((a % m) + m) % m
+}
+
+/// Returns the inverse of `a` modulo `m`, or `None` if `a` and `m` are not coprime.
+pub(crate) fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    // Extended Euclidean algorithm
+    let (mut old_r, mut r) = (modulo(a, m), m);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    if old_r == 1 {
+        Some(modulo(old_s, m))
+    } else {
+        None
+    }
+}
+
+/// Brings the columns of `rows` up to `columns` into upper triangular form modulo `m` by
+/// Gaussian elimination, returning the sign the row swaps give the determinant.
+///
+/// Each column is cleared below the diagonal with the Euclidean algorithm on pairs of rows, so no
+/// pivot has to be invertible: the pivot ends up as the greatest common divisor of the column,
+/// which also works when `m` is not prime.
+fn triangulate(rows: &mut [Vec<i64>], columns: usize, m: i64) -> i64 {
+    let mut sign = 1;
+    for col in 0..columns.min(rows.len()) {
+        for row in col + 1..rows.len() {
+            while rows[row][col] != 0 {
+                let q = rows[col][col] / rows[row][col];
+                for k in col..rows[row].len() {
+                    rows[col][k] = modulo(rows[col][k] - q * rows[row][k], m);
+                }
+                rows.swap(col, row);
+                sign = -sign;
+            }
+        }
+    }
+    sign
+}
+
+/// Returns the determinant of a square matrix modulo `m`, in `0..m`.
+pub(crate) fn determinant(matrix: &[Vec<i64>], m: i64) -> i64 {
+    let mut rows: Vec<Vec<i64>> = matrix
+        .iter()
+        .map(|row| row.iter().map(|&v| modulo(v, m)).collect())
+        .collect();
+    let sign = triangulate(&mut rows, matrix.len(), m);
+    (0..rows.len()).fold(modulo(sign, m), |det, i| modulo(det * rows[i][i], m))
+}
+
+/// Returns the inverse of a square matrix modulo `m`, or `None` if its determinant is not
+/// coprime with `m`.
+pub(crate) fn matrix_inverse(matrix: &[Vec<i64>], m: i64) -> Option<Vec<Vec<i64>>> {
+    let n = matrix.len();
+    // Reduce the matrix next to the identity, which turns into the inverse
+    let mut rows: Vec<Vec<i64>> = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut augmented: Vec<i64> = row.iter().map(|&v| modulo(v, m)).collect();
+            augmented.extend((0..n).map(|j| i64::from(i == j)));
+            augmented
+        })
+        .collect();
+    triangulate(&mut rows, n, m);
+    for col in (0..n).rev() {
+        let pivot_inverse = mod_inverse(rows[col][col], m)?;
+        for v in rows[col].iter_mut() {
+            *v = modulo(*v * pivot_inverse, m);
+        }
+        let (above, pivot) = rows.split_at_mut(col);
+        for row in above {
+            let factor = row[col];
+            for (v, &p) in row[col..].iter_mut().zip(&pivot[0][col..]) {
+                *v = modulo(*v - factor * p, m);
+            }
+        }
+    }
+    Some(rows.into_iter().map(|row| row[n..].to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(26, 8), 2);
+        assert_eq!(gcd(26, 7), 1);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(5, 26), Some(21));
+        assert_eq!(mod_inverse(-5, 26), Some(5));
+        assert_eq!(mod_inverse(13, 26), None);
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(determinant(&[vec![3, 3], vec![2, 5]], 26), 9);
+        // 441 mod 26
+        assert_eq!(
+            determinant(&[vec![6, 24, 1], vec![13, 16, 10], vec![20, 17, 15]], 26),
+            25
+        );
+        assert_eq!(determinant(&[vec![2, 4], vec![1, 3]], 26), 2);
+        assert_eq!(determinant(&[vec![0, 1], vec![1, 0]], 26), 25);
+    }
+
+    #[test]
+    fn test_matrix_inverse() {
+        assert_eq!(
+            matrix_inverse(&[vec![3, 3], vec![2, 5]], 26),
+            Some(vec![vec![15, 17], vec![20, 9]])
+        );
+        assert_eq!(matrix_inverse(&[vec![2, 4], vec![1, 3]], 26), None);
+
+        // No entry of the first column is invertible mod 26, but the matrix is
+        let matrix = [vec![2, 13, 5], vec![13, 2, 7], vec![4, 8, 1]];
+        let inverse = matrix_inverse(&matrix, 26).unwrap();
+        for (i, row) in matrix.iter().enumerate() {
+            let product: Vec<i64> = (0..3)
+                .map(|j| modulo(row.iter().zip(&inverse).map(|(a, b)| a * b[j]).sum(), 26))
+                .collect();
+            let identity: Vec<i64> = (0..3).map(|j| i64::from(i == j)).collect();
+            assert_eq!(product, identity);
+        }
+
+        // Large keys no longer take factorial time
+        let n = 40;
+        let matrix: Vec<Vec<i64>> = (0..n)
+            .map(|i| (0..n).map(|j| i64::from(i == j || j == i + 1)).collect())
+            .collect();
+        let inverse = matrix_inverse(&matrix, 26).unwrap();
+        assert_eq!(inverse[0][n - 1], if n % 2 == 0 { 25 } else { 1 });
+    }
+}
@@ -0,0 +1,257 @@
+//! Null padding stages for pipelines.
+//!
+//! Clerks padded telegrams with nulls, meaningless letters, to hide the letter frequencies a
+//! codebreaker counts. [`NullPadding`] adds nulls chosen to flatten those frequencies: every
+//! null is the letter that is rarest so far, so the common letters of the message are drowned
+//! out by the rare ones. The nulls are placed so that deciphering can strip them again, either
+//! after the message with their count at the end ([`NullPlacement::Append`]) or one after every
+//! few letters ([`NullPlacement::Interleave`]).
+//!
+//! ```rust
+//! use cipha::analysis::index_of_coincidence;
+//! use cipha::ciphers::{Cipher, VigenereCipher};
+//! use cipha::nulls::{NullPadding, NullPlacement};
+//! use cipha::pipeline::Pipeline;
+//!
+//! let pipeline = Pipeline::new()
+//!     .then(VigenereCipher::new("LEMON"))
+//!     .then(NullPadding::new(NullPlacement::Append));
+//! let message = "Attack the east wall of the castle at dawn";
+//! let ciphertext = pipeline.encipher(message).unwrap();
+//! assert!(index_of_coincidence(&ciphertext) < index_of_coincidence(message));
+//! assert_eq!(pipeline.inverse().encipher(&ciphertext).unwrap(), message);
+//! ```
+
+use crate::ciphers::Cipher;
+
+/// The number of letters that give the null count of [`NullPlacement::Append`].
+const COUNT_LETTERS: u32 = 3;
+
+/// Where [`NullPadding`] puts its nulls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullPlacement {
+    /// After the message, until every letter is as common as the most common one, followed by
+    /// three letters giving the number of nulls in base 26 (`AAA` for none).
+    Append,
+    /// One null after every `n` letters of the message.
+    Interleave(usize),
+}
+
+/// A stage that pads the letters of a message with nulls chosen to flatten its letter
+/// frequencies, and strips them again when deciphering.
+///
+/// Each null is the letter with the lowest count so far, the earliest in the alphabet on a tie,
+/// in the case of the letter before it. Characters other than ASCII letters pass through and
+/// are not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullPadding {
+    placement: NullPlacement,
+}
+
+impl NullPadding {
+    /// Creates a null padding stage.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::Cipher;
+    /// use cipha::nulls::{NullPadding, NullPlacement};
+    ///
+    /// // Every letter but A and B is missing twice, so 48 nulls follow, counted by ABW
+    /// let appended = NullPadding::new(NullPlacement::Append);
+    /// let padded = appended.encipher("ABBA");
+    /// assert_eq!(&padded[..10], "ABBACDEFGH");
+    /// assert_eq!(&padded[padded.len() - 3..], "ABW");
+    /// assert_eq!(appended.decipher(&padded), "ABBA");
+    ///
+    /// let interleaved = NullPadding::new(NullPlacement::Interleave(2));
+    /// assert_eq!(interleaved.encipher("see me"), "seae mbe");
+    /// assert_eq!(interleaved.decipher("seae mbe"), "see me");
+    /// ```
+    pub fn new(placement: NullPlacement) -> Self {
+        NullPadding { placement }
+    }
+
+    /// Creates a stage that puts a null after every `every` letters of the message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `every` is 0.
+    pub fn interleaved(every: usize) -> Result<Self, String> {
+        if every == 0 {
+            return Err("Nulls must come after at least 1 letter".to_string());
+        }
+        Ok(Self::new(NullPlacement::Interleave(every)))
+    }
+
+    /// Returns where the nulls go.
+    pub fn placement(&self) -> NullPlacement {
+        self.placement
+    }
+
+    /// Pads the message with nulls.
+    pub fn pad(&self, text: &str) -> String {
+        let mut counts = [0usize; 26];
+        for c in text.chars().filter(char::is_ascii_alphabetic) {
+            counts[(c.to_ascii_uppercase() as u8 - b'A') as usize] += 1;
+        }
+        let next_null = |counts: &mut [usize; 26]| {
+            let rarest = (0..26).min_by_key(|&i| counts[i]).unwrap_or(0);
+            counts[rarest] += 1;
+            (b'A' + rarest as u8) as char
+        };
+        let in_case = |null: char, last: Option<char>| match last {
+            Some(c) if c.is_ascii_lowercase() => null.to_ascii_lowercase(),
+            _ => null,
+        };
+        match self.placement {
+            NullPlacement::Append => {
+                let most = counts.iter().copied().max().unwrap_or(0);
+                let total = counts.iter().map(|&count| most - count).sum::<usize>();
+                let total = total.min(26usize.pow(COUNT_LETTERS) - 1);
+                let last = text.chars().rev().find(char::is_ascii_alphabetic);
+                let mut padded = text.to_string();
+                for _ in 0..total {
+                    padded.push(in_case(next_null(&mut counts), last));
+                }
+                for place in (0..COUNT_LETTERS).rev() {
+                    let digit = (total / 26usize.pow(place) % 26) as u8;
+                    padded.push(in_case((b'A' + digit) as char, last));
+                }
+                padded
+            }
+            NullPlacement::Interleave(every) => {
+                let every = every.max(1);
+                let mut padded = String::new();
+                let mut letters = 0;
+                for c in text.chars() {
+                    padded.push(c);
+                    if c.is_ascii_alphabetic() {
+                        letters += 1;
+                        if letters % every == 0 {
+                            padded.push(in_case(next_null(&mut counts), Some(c)));
+                        }
+                    }
+                }
+                padded
+            }
+        }
+    }
+
+    /// Strips the nulls from a padded message.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the count at the end of an appended padding is missing or larger
+    /// than the letters before it.
+    pub fn strip(&self, text: &str) -> Result<String, String> {
+        let letters = text.chars().filter(char::is_ascii_alphabetic).count();
+        let keep: Box<dyn Fn(usize) -> bool> = match self.placement {
+            NullPlacement::Append => {
+                let count_letters = COUNT_LETTERS as usize;
+                if letters < count_letters {
+                    return Err(format!(
+                        "Padded text must end with {} letters giving the null count",
+                        count_letters
+                    ));
+                }
+                let total = text
+                    .chars()
+                    .filter(char::is_ascii_alphabetic)
+                    .skip(letters - count_letters)
+                    .fold(0, |total, c| {
+                        total * 26 + (c.to_ascii_uppercase() as u8 - b'A') as usize
+                    });
+                if total > letters - count_letters {
+                    return Err(format!(
+                        "Padded text has {} nulls but only {} letters before its null count",
+                        total,
+                        letters - count_letters
+                    ));
+                }
+                let message_letters = letters - count_letters - total;
+                Box::new(move |letter| letter < message_letters)
+            }
+            NullPlacement::Interleave(every) => {
+                let every = every.max(1);
+                Box::new(move |letter| letter % (every + 1) != every)
+            }
+        };
+        let mut letter = 0;
+        Ok(text
+            .chars()
+            .filter(|c| {
+                if !c.is_ascii_alphabetic() {
+                    return true;
+                }
+                letter += 1;
+                keep(letter - 1)
+            })
+            .collect())
+    }
+}
+
+impl Cipher for NullPadding {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.pad(plaintext)
+    }
+
+    /// Strips the nulls, leaving text without a valid null count unchanged.
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.strip(ciphertext)
+            .unwrap_or_else(|_| ciphertext.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::index_of_coincidence;
+
+    #[test]
+    fn test_appended_nulls_flatten_frequencies() {
+        let padding = NullPadding::new(NullPlacement::Append);
+        let message = "Meet me at the old mill at midnight";
+        let padded = padding.pad(message);
+        assert!(padded.starts_with(message));
+
+        // Apart from the count, every letter is as common as the most common one
+        let letters: Vec<char> = padded.chars().filter(char::is_ascii_alphabetic).collect();
+        let mut counts = [0; 26];
+        for c in &letters[..letters.len() - 3] {
+            counts[(c.to_ascii_uppercase() as u8 - b'A') as usize] += 1;
+        }
+        assert!(counts.iter().all(|&count| count == counts[0]));
+        assert!(index_of_coincidence(&padded) < index_of_coincidence(message));
+        assert_eq!(padding.strip(&padded), Ok(message.to_string()));
+
+        // A message with flat frequencies only gets the count
+        let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        assert_eq!(padding.pad(alphabet), format!("{}AAA", alphabet));
+        assert_eq!(padding.pad(""), "AAA");
+        assert_eq!(padding.strip("AAA"), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_interleaved_nulls() {
+        let padding = NullPadding::interleaved(3).unwrap();
+        assert_eq!(padding.placement(), NullPlacement::Interleave(3));
+        let message = "Eleven elves, seven geese.";
+        let padded = padding.pad(message);
+        assert_eq!(padded.len(), message.len() + 21 / 3);
+        assert!(index_of_coincidence(&padded) < index_of_coincidence(message));
+        assert_eq!(padding.strip(&padded), Ok(message.to_string()));
+        assert_eq!(padding.decipher(&padded), message);
+
+        assert!(NullPadding::interleaved(0).is_err());
+    }
+
+    #[test]
+    fn test_strip_rejects_bad_counts() {
+        let padding = NullPadding::new(NullPlacement::Append);
+        assert!(padding.strip("AB").is_err());
+        // ZZZ claims more nulls than there are letters
+        assert!(padding.strip("HELLOZZZ").is_err());
+        assert_eq!(padding.decipher("HELLOZZZ"), "HELLOZZZ");
+    }
+}
@@ -0,0 +1,480 @@
+//! Chains of cipher stages.
+//!
+//! A [`Pipeline`] runs a message through its stages in order. Its [`inverse`](Pipeline::inverse)
+//! undoes them: the stages run in reverse order and each one deciphers instead of enciphering,
+//! so the decoding recipe never has to be written by hand.
+//!
+//! ```rust
+//! use cipha::ciphers::{CaesarCipher, ReverseWordsCipher, VigenereCipher};
+//! use cipha::pipeline::Pipeline;
+//!
+//! let pipeline = Pipeline::new()
+//!     .then(CaesarCipher::new(3))
+//!     .then(VigenereCipher::new("LEMON"))
+//!     .then(ReverseWordsCipher::new());
+//! let ciphertext = pipeline.encipher("Attack at dawn").unwrap();
+//! assert_eq!(pipeline.inverse().encipher(&ciphertext).unwrap(), "Attack at dawn");
+//! ```
+//!
+//! A pipeline can also be [simplified](Pipeline::simplify) before it runs: adjacent Caesar shifts
+//! merge and stages that undo each other, such as two Atbash stages, cancel out.
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ciphers::{
+    AtbashCipher, CaesarCipher, Cipher, FallibleCipher, ReverseLettersCipher, ReverseWordsCipher,
+};
+
+/// A stage the pipeline simplifier knows how to combine with its neighbours.
+///
+/// Ciphers report theirs through [`Cipher::elementary`]. Every elementary stage is a cipher
+/// itself, so merged stages can run in place of the ones they replace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Elementary {
+    /// A Caesar shift of the letters, 0 to 25. ROT13 is a shift of 13.
+    Shift(u8),
+    /// The Atbash cipher.
+    Atbash,
+    /// Reversing the whole text.
+    Reverse,
+    /// Reversing the order of the words.
+    ReverseWords,
+    /// Reversing the letters of every word.
+    ReverseLetters,
+}
+
+impl Elementary {
+    /// Returns the stage that undoes this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            Elementary::Shift(shift) => Elementary::Shift((26 - shift % 26) % 26),
+            _ => self,
+        }
+    }
+
+    // Combines this stage with the one after it: `None` if they cannot be combined, `Some(None)`
+    // if they cancel out and `Some(Some(stage))` if they merge into one.
+    fn then(self, next: Elementary) -> Option<Option<Elementary>> {
+        match (self, next) {
+            (Elementary::Shift(a), Elementary::Shift(b)) => {
+                let shift = (a as usize + b as usize) % 26;
+                Some((shift != 0).then_some(Elementary::Shift(shift as u8)))
+            }
+            (a, b) if a == b && a.inverse() == a => Some(None),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Elementary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Elementary::Shift(13) => f.write_str("ROT13"),
+            Elementary::Shift(shift) => write!(f, "Caesar shift {}", shift),
+            Elementary::Atbash => f.write_str("Atbash"),
+            Elementary::Reverse => f.write_str("Reverse"),
+            Elementary::ReverseWords => f.write_str("Reverse words"),
+            Elementary::ReverseLetters => f.write_str("Reverse letters"),
+        }
+    }
+}
+
+impl Cipher for Elementary {
+    fn encipher(&self, plaintext: &str) -> String {
+        match self {
+            Elementary::Shift(shift) => CaesarCipher::new(*shift).encipher(plaintext),
+            Elementary::Atbash => AtbashCipher::new().encipher(plaintext),
+            Elementary::Reverse => plaintext.chars().rev().collect(),
+            Elementary::ReverseWords => ReverseWordsCipher::new().encipher(plaintext),
+            Elementary::ReverseLetters => ReverseLettersCipher::new().encipher(plaintext),
+        }
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.inverse().encipher(ciphertext)
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        Some(*self)
+    }
+}
+
+/// A stage of a simplified sequence: one kept as it was, or an elementary stage standing for one
+/// or more merged stages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Simplified<T> {
+    /// A stage the simplifier left alone.
+    Stage(T),
+    /// An elementary stage.
+    Elementary(Elementary),
+}
+
+/// Simplifies a sequence of stages, given the elementary form of each stage if it has one.
+///
+/// Adjacent elementary stages are combined: Caesar shifts (ROT13 included) add up, and a second
+/// Atbash or reversal of the same kind cancels the first. Cancelling can expose further pairs,
+/// so `[atbash, rot13, rot13, atbash]` simplifies to nothing. Shifts by 0 are dropped. Other
+/// stages are kept in place and are never combined across.
+///
+/// This works on any kind of stage, e.g. the stage descriptions of a recipe, so a caller can
+/// show what a recipe effectively does.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::pipeline::{simplify_stages, Elementary, Simplified};
+///
+/// let recipe = ["caesar 3", "caesar 5", "vigenere", "atbash", "atbash"];
+/// let elementary = |stage: &&str| match *stage {
+///     "caesar 3" => Some(Elementary::Shift(3)),
+///     "caesar 5" => Some(Elementary::Shift(5)),
+///     "atbash" => Some(Elementary::Atbash),
+///     _ => None,
+/// };
+/// assert_eq!(
+///     simplify_stages(recipe, elementary),
+///     [Simplified::Elementary(Elementary::Shift(8)), Simplified::Stage("vigenere")]
+/// );
+/// ```
+pub fn simplify_stages<T>(
+    stages: impl IntoIterator<Item = T>,
+    elementary: impl Fn(&T) -> Option<Elementary>,
+) -> Vec<Simplified<T>> {
+    let mut simplified: Vec<Simplified<T>> = Vec::new();
+    for stage in stages {
+        let Some(next) = elementary(&stage) else {
+            simplified.push(Simplified::Stage(stage));
+            continue;
+        };
+        let combined = match simplified.last() {
+            Some(Simplified::Elementary(previous)) => previous.then(next),
+            _ => None,
+        };
+        match combined {
+            Some(merged) => {
+                simplified.pop();
+                simplified.extend(merged.map(Simplified::Elementary));
+            }
+            None if next == Elementary::Shift(0) => {}
+            None => simplified.push(Simplified::Elementary(next)),
+        }
+    }
+    simplified
+}
+
+/// A cipher stage run backwards: enciphering deciphers with the stage and vice versa.
+#[derive(Clone)]
+pub struct Inverse(Rc<dyn Cipher>);
+
+impl Cipher for Inverse {
+    fn encipher(&self, plaintext: &str) -> String {
+        self.0.decipher(plaintext)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        self.0.encipher(ciphertext)
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        let mut table: Vec<(char, char)> = self
+            .0
+            .mapping_table()?
+            .into_iter()
+            .map(|(p, c)| (c, p))
+            .collect();
+        table.sort();
+        Some(table)
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        self.0.elementary().map(Elementary::inverse)
+    }
+}
+
+/// A fallible stage run backwards: enciphering deciphers with the stage and vice versa.
+struct InverseFallible(Rc<dyn FallibleCipher>);
+
+impl FallibleCipher for InverseFallible {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.0.try_decipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.0.try_encipher(ciphertext)
+    }
+}
+
+// A stage of a pipeline. Fallible stages have no mapping table or elementary form.
+#[derive(Clone)]
+enum Stage {
+    Cipher(Rc<dyn Cipher>),
+    Fallible(Rc<dyn FallibleCipher>),
+}
+
+impl Stage {
+    fn inverse(&self) -> Stage {
+        match self {
+            Stage::Cipher(cipher) => Stage::Cipher(Rc::new(Inverse(Rc::clone(cipher)))),
+            Stage::Fallible(cipher) => Stage::Fallible(Rc::new(InverseFallible(Rc::clone(cipher)))),
+        }
+    }
+
+    fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        match self {
+            Stage::Cipher(cipher) => Ok(cipher.encipher(plaintext)),
+            Stage::Fallible(cipher) => cipher.try_encipher(plaintext),
+        }
+    }
+
+    fn elementary(&self) -> Option<Elementary> {
+        match self {
+            Stage::Cipher(cipher) => cipher.elementary(),
+            Stage::Fallible(_) => None,
+        }
+    }
+
+    fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        match self {
+            Stage::Cipher(cipher) => cipher.mapping_table(),
+            Stage::Fallible(_) => None,
+        }
+    }
+}
+
+/// A sequence of cipher stages applied one after the other.
+///
+/// Stages are [`Cipher`]s, or [`FallibleCipher`]s that can reject a message; the first stage to
+/// fail stops the pipeline with its error. Pipelines are fallible ciphers themselves, so they can
+/// be nested as stages of other pipelines.
+///
+/// ```rust
+/// use cipha::authentication::Authenticated;
+/// use cipha::ciphers::CaesarCipher;
+/// use cipha::pipeline::Pipeline;
+///
+/// let pipeline = Pipeline::new()
+///     .then(CaesarCipher::new(3))
+///     .then_fallible(Authenticated::new(CaesarCipher::new(5), "secret"));
+/// let ciphertext = pipeline.encipher("Attack at dawn").unwrap();
+/// assert_eq!(pipeline.decipher(&ciphertext).unwrap(), "Attack at dawn");
+///
+/// let wrong_key = Pipeline::new()
+///     .then(CaesarCipher::new(3))
+///     .then_fallible(Authenticated::new(CaesarCipher::new(6), "secret"));
+/// assert!(wrong_key.decipher(&ciphertext).is_err());
+/// ```
+#[derive(Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline, which leaves messages unchanged.
+    pub fn new() -> Self {
+        Pipeline { stages: Vec::new() }
+    }
+
+    /// Appends a stage to the pipeline.
+    pub fn then(mut self, stage: impl Cipher + 'static) -> Self {
+        self.push(stage);
+        self
+    }
+
+    /// Appends a stage to the pipeline in place.
+    pub fn push(&mut self, stage: impl Cipher + 'static) {
+        self.stages.push(Stage::Cipher(Rc::new(stage)));
+    }
+
+    /// Appends a stage that can fail to the pipeline, such as another pipeline.
+    pub fn then_fallible(mut self, stage: impl FallibleCipher + 'static) -> Self {
+        self.push_fallible(stage);
+        self
+    }
+
+    /// Appends a stage that can fail to the pipeline in place.
+    pub fn push_fallible(&mut self, stage: impl FallibleCipher + 'static) {
+        self.stages.push(Stage::Fallible(Rc::new(stage)));
+    }
+
+    /// Returns the number of stages.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns `true` if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Returns the pipeline that undoes this one: the stages in reverse order, each inverted.
+    pub fn inverse(&self) -> Pipeline {
+        Pipeline {
+            stages: self.stages.iter().rev().map(Stage::inverse).collect(),
+        }
+    }
+
+    /// Returns an equivalent pipeline with adjacent elementary stages combined, as described
+    /// for [`simplify_stages`]. Stages that are not [elementary](Cipher::elementary) are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::ciphers::{AtbashCipher, CaesarCipher, Rot13Cipher};
+    /// use cipha::pipeline::Pipeline;
+    ///
+    /// let pipeline = Pipeline::new()
+    ///     .then(CaesarCipher::new(3))
+    ///     .then(CaesarCipher::new(5))
+    ///     .then(AtbashCipher::new())
+    ///     .then(Rot13Cipher::new())
+    ///     .then(Rot13Cipher::new())
+    ///     .then(AtbashCipher::new());
+    /// let simplified = pipeline.simplify();
+    /// assert_eq!(simplified.len(), 1);
+    /// assert_eq!(simplified.encipher("Hello"), pipeline.encipher("Hello"));
+    /// ```
+    pub fn simplify(&self) -> Pipeline {
+        let stages = simplify_stages(self.stages.iter().cloned(), Stage::elementary);
+        Pipeline {
+            stages: stages
+                .into_iter()
+                .map(|stage| match stage {
+                    Simplified::Stage(stage) => stage,
+                    Simplified::Elementary(elementary) => Stage::Cipher(Rc::new(elementary)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Runs the message through every stage in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first stage that fails.
+    pub fn encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.stages
+            .iter()
+            .try_fold(plaintext.to_string(), |text, stage| stage.encipher(&text))
+    }
+
+    /// Undoes the pipeline, running the message through its [inverse](Self::inverse).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first stage that fails.
+    pub fn decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.inverse().encipher(ciphertext)
+    }
+
+    /// Composes the mapping tables of the stages, if every stage has one, as
+    /// [`Cipher::mapping_table`] does for a single cipher.
+    ///
+    /// A letter missing from a stage's table passes through that stage unchanged.
+    pub fn mapping_table(&self) -> Option<Vec<(char, char)>> {
+        let mut table: Vec<(char, char)> = ('A'..='Z').map(|c| (c, c)).collect();
+        for stage in &self.stages {
+            let stage_table = stage.mapping_table()?;
+            for (_, c) in table.iter_mut() {
+                if let Some(&(_, mapped)) = stage_table.iter().find(|(p, _)| p == c) {
+                    *c = mapped;
+                }
+            }
+        }
+        Some(table)
+    }
+}
+
+impl FallibleCipher for Pipeline {
+    fn try_encipher(&self, plaintext: &str) -> Result<String, String> {
+        self.encipher(plaintext)
+    }
+
+    fn try_decipher(&self, ciphertext: &str) -> Result<String, String> {
+        self.decipher(ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphers::{InterleaveCipher, Rot13Cipher};
+
+    #[test]
+    fn test_inverse_reverses_and_inverts_stages() {
+        let pipeline = Pipeline::new()
+            .then(CaesarCipher::new(1))
+            .then(InterleaveCipher::new(2).unwrap());
+        // ABCD -> BCDE -> BDCE
+        assert_eq!(pipeline.encipher("ABCD").unwrap(), "BDCE");
+        let inverse = pipeline.inverse();
+        assert_eq!(inverse.len(), 2);
+        assert_eq!(inverse.encipher("BDCE").unwrap(), "ABCD");
+        assert_eq!(inverse.decipher("ABCD").unwrap(), "BDCE");
+        assert_eq!(pipeline.decipher("BDCE").unwrap(), "ABCD");
+    }
+
+    #[test]
+    fn test_mapping_table_composes() {
+        let pipeline = Pipeline::new()
+            .then(CaesarCipher::new(1))
+            .then(AtbashCipher::new());
+        let table = pipeline.mapping_table().unwrap();
+        // A -> B -> Y
+        assert_eq!(table[0], ('A', 'Y'));
+        assert_eq!(pipeline.inverse().mapping_table().unwrap()[24], ('Y', 'A'));
+        let transposed = pipeline.then(InterleaveCipher::new(2).unwrap());
+        assert!(transposed.mapping_table().is_none());
+    }
+
+    #[test]
+    fn test_nested_pipeline() {
+        let inner = Pipeline::new().then(AtbashCipher::new());
+        let mut outer = Pipeline::new();
+        outer.push(CaesarCipher::new(2));
+        outer.push_fallible(inner);
+        assert_eq!(outer.encipher("abc").unwrap(), "xwv");
+        assert_eq!(outer.inverse().encipher("xwv").unwrap(), "abc");
+        assert!(Pipeline::new().is_empty());
+        assert_eq!(Pipeline::new().encipher("same").unwrap(), "same");
+    }
+
+    #[test]
+    fn test_simplify() {
+        let pipeline = Pipeline::new()
+            .then(Rot13Cipher::new())
+            .then(CaesarCipher::new(20))
+            .then(InterleaveCipher::new(2).unwrap())
+            .then(ReverseWordsCipher::new())
+            .then(ReverseLettersCipher::new())
+            .then(ReverseLettersCipher::new())
+            .then(ReverseWordsCipher::new())
+            .then(CaesarCipher::new(26));
+        let simplified = pipeline.simplify();
+        // Caesar 7, then the interleave
+        assert_eq!(simplified.len(), 2);
+        let message = "Attack at dawn, 10 o'clock";
+        assert_eq!(
+            simplified.encipher(message).unwrap(),
+            pipeline.encipher(message).unwrap()
+        );
+        assert_eq!(
+            simplified.decipher(message).unwrap(),
+            pipeline.decipher(message).unwrap()
+        );
+
+        // An inverted stage inverts its elementary form
+        let inverse = Pipeline::new()
+            .then(CaesarCipher::new(3))
+            .then(Inverse(Rc::new(CaesarCipher::new(3))));
+        assert!(inverse.simplify().is_empty());
+        assert_eq!(
+            pipeline.inverse().simplify().encipher(message).unwrap(),
+            pipeline.decipher(message).unwrap()
+        );
+
+        assert_eq!(Elementary::Shift(13).to_string(), "ROT13");
+        assert_eq!(Elementary::Reverse.encipher("abc"), "cba");
+        assert_eq!(Elementary::Shift(1).decipher("b"), "a");
+    }
+}
@@ -0,0 +1,57 @@
+//! The types most programs need, in one import.
+//!
+//! `use cipha::prelude::*;` brings in the [`Cipher`] and [`FallibleCipher`] traits, every cipher
+//! and machine, the [`Pipeline`] and the settings they are configured with, such as
+//! [`KeySchedule`] or [`Rotor`]. The functions of the functional approach live in [`fns`], so that
+//! `fns::rot13` and [`Rot13Cipher`] can be told apart at a glance.
+//!
+//! ```rust
+//! use cipha::prelude::*;
+//!
+//! let pipeline = Pipeline::new()
+//!     .then(VigenereCipher::new("LEMON"))
+//!     .then(RailFenceCipher::new(3).unwrap());
+//! let ciphertext = pipeline.encipher("ATTACKATDAWN").unwrap();
+//! assert_eq!(pipeline.decipher(&ciphertext).unwrap(), "ATTACKATDAWN");
+//! assert_eq!(fns::rot13("Hello".to_string()), Rot13Cipher::new().encipher("Hello"));
+//! ```
+
+pub use crate::alphabet::Alphabet;
+pub use crate::authentication::Authenticated;
+pub use crate::binary::XorCipher;
+pub use crate::budget::Budget;
+pub use crate::ciphers::{
+    AdfgvxCipher, AffineCipher, AlphaNumConverter, AmscoCipher, AtbashCipher, BaconAlphabet,
+    BaconCipher, BaudotCode, BaudotFormat, BifidCipher, BookCipher, BookScheme,
+    BoustrophedonCipher, CaesarBoxCipher, CaesarCipher, Cased, ChainAdditiveCipher, Cipher,
+    ColumnarTranspositionCipher, DecimationCipher, DelastelleCipher, DoubleTranspositionCipher,
+    FallibleCipher, Filtered, FractionatedMorseCipher, Grille, GrilleCipher, HillCipher,
+    InterleaveCipher, Interruption, KeySchedule, KeyedCaesarCipher, MorbitCipher, MorseCode,
+    MorseTiming, NihilistCipher, NomenclatorCipher, Normalized, NumericSubstitution, OneTimePad,
+    PadMode, PigpenCipher, PlayfairCipher, PolluxCipher, PolyalphabeticCipher, PolybiusGrid,
+    PolybiusSquare, PortaCipher, PortaTable, RailFenceCipher, ReverseLettersCipher,
+    ReverseWordsCipher, Rot13Cipher, Rot18Cipher, Rot47Cipher, Rot5Cipher, RotN, Route,
+    RouteCipher, RunningKeyCipher, ScytaleCipher, SpiralCipher, StraddlingCheckerboard,
+    SubstitutionCipher, TableCipher, Tableau, TapCode, TokenCode, Transposition, TrifidCipher,
+    TrithemiusCipher, VigenereCipher, WordTranspositionCipher, UNKNOWN,
+};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use crate::compression::{Codec, Compress, Decompress};
+pub use crate::fractionation::Fractionator;
+pub use crate::historical::{VicCipher, VicKeys};
+pub use crate::keystream::ChainAddition;
+pub use crate::machines::{EnigmaMachine, JeffersonWheel, LorenzMachine, Reflector, Rotor};
+pub use crate::nulls::{NullPadding, NullPlacement};
+pub use crate::pipeline::{Elementary, Inverse, Pipeline};
+pub use crate::text::{Formatter, LetterCase, NonAlphaPolicy, Preprocess, PreprocessOptions};
+
+/// The free functions, named apart from the structs of the prelude.
+///
+/// These are the functions of [`utils`](crate::utils) together with the byte encodings of
+/// [`binary`](crate::binary) and, with a compression feature, `compress` and `decompress`.
+pub mod fns {
+    pub use crate::binary::{from_base64, from_hex, to_base64, to_hex};
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub use crate::compression::{compress, decompress};
+    pub use crate::utils::*;
+}
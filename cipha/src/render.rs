@@ -0,0 +1,353 @@
+//! Renderers for showing results rather than transmitting them.
+//!
+//! [`big_text`] draws text as large letters made of `#`, five rows high, for projecting a
+//! puzzle in a classroom or printing clues for a scavenger hunt. [`key_grid`] and
+//! [`polybius_grid`] lay out key squares so a hand-built square can be checked against the one
+//! a keyword produces. [`aligned_rows`] sets a plaintext, its key and its ciphertext one above
+//! the other for teaching handouts.
+//!
+//! ```rust
+//! use cipha::render::big_text;
+//!
+//! assert_eq!(
+//!     big_text("Hi"),
+//!     "#   # ###\n#   #  #\n#####  #\n#   #  #\n#   # ###"
+//! );
+//! ```
+
+use crate::ciphers::PolybiusSquare;
+
+/// The height of the [`big_text`] font in rows.
+pub const BIG_TEXT_HEIGHT: usize = 5;
+
+// Glyphs of the built-in font, row by row. Every row of a glyph has the same width.
+const FONT: &[(char, [&str; BIG_TEXT_HEIGHT])] = &[
+    ('A', [" ### ", "#   #", "#####", "#   #", "#   #"]),
+    ('B', ["#### ", "#   #", "#### ", "#   #", "#### "]),
+    ('C', [" ####", "#    ", "#    ", "#    ", " ####"]),
+    ('D', ["#### ", "#   #", "#   #", "#   #", "#### "]),
+    ('E', ["#####", "#    ", "#### ", "#    ", "#####"]),
+    ('F', ["#####", "#    ", "#### ", "#    ", "#    "]),
+    ('G', [" ####", "#    ", "#  ##", "#   #", " ### "]),
+    ('H', ["#   #", "#   #", "#####", "#   #", "#   #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  ###", "   # ", "   # ", "#  # ", " ##  "]),
+    ('K', ["#   #", "#  # ", "###  ", "#  # ", "#   #"]),
+    ('L', ["#    ", "#    ", "#    ", "#    ", "#####"]),
+    ('M', ["#   #", "## ##", "# # #", "#   #", "#   #"]),
+    ('N', ["#   #", "##  #", "# # #", "#  ##", "#   #"]),
+    ('O', [" ### ", "#   #", "#   #", "#   #", " ### "]),
+    ('P', ["#### ", "#   #", "#### ", "#    ", "#    "]),
+    ('Q', [" ### ", "#   #", "# # #", "#  # ", " ## #"]),
+    ('R', ["#### ", "#   #", "#### ", "#  # ", "#   #"]),
+    ('S', [" ####", "#    ", " ### ", "    #", "#### "]),
+    ('T', ["#####", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('U', ["#   #", "#   #", "#   #", "#   #", " ### "]),
+    ('V', ["#   #", "#   #", "#   #", " # # ", "  #  "]),
+    ('W', ["#   #", "#   #", "# # #", "## ##", "#   #"]),
+    ('X', ["#   #", " # # ", "  #  ", " # # ", "#   #"]),
+    ('Y', ["#   #", " # # ", "  #  ", "  #  ", "  #  "]),
+    ('Z', ["#####", "   # ", "  #  ", " #   ", "#####"]),
+    ('0', [" ### ", "#  ##", "# # #", "##  #", " ### "]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', [" ### ", "#   #", "  ## ", " #   ", "#####"]),
+    ('3', ["#### ", "    #", " ### ", "    #", "#### "]),
+    ('4', ["#   #", "#   #", "#####", "    #", "    #"]),
+    ('5', ["#####", "#    ", "#### ", "    #", "#### "]),
+    ('6', [" ### ", "#    ", "#### ", "#   #", " ### "]),
+    ('7', ["#####", "    #", "   # ", "  #  ", "  #  "]),
+    ('8', [" ### ", "#   #", " ### ", "#   #", " ### "]),
+    ('9', [" ### ", "#   #", " ####", "    #", " ### "]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('.', [" ", " ", " ", " ", "#"]),
+    (',', ["  ", "  ", "  ", " #", "# "]),
+    ('!', ["#", "#", "#", " ", "#"]),
+    ('?', [" ### ", "#   #", "  ## ", "     ", "  #  "]),
+    ('\'', ["#", "#", " ", " ", " "]),
+    ('"', ["# #", "# #", "   ", "   ", "   "]),
+    (':', [" ", "#", " ", "#", " "]),
+    (';', ["  ", " #", "  ", " #", "# "]),
+    ('-', ["    ", "    ", "####", "    ", "    "]),
+    ('+', ["     ", "  #  ", "#####", "  #  ", "     "]),
+    ('=', ["    ", "####", "    ", "####", "    "]),
+    ('/', ["    #", "   # ", "  #  ", " #   ", "#    "]),
+    ('(', [" #", "# ", "# ", "# ", " #"]),
+    (')', ["# ", " #", " #", " #", "# "]),
+];
+
+// Returns the glyph of the character, drawing letters in uppercase and anything the font
+// lacks as a question mark.
+fn glyph(c: char) -> &'static [&'static str; BIG_TEXT_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+    FONT.iter()
+        .find(|(glyph_char, _)| *glyph_char == c)
+        .or_else(|| FONT.iter().find(|(glyph_char, _)| *glyph_char == '?'))
+        .map(|(_, rows)| rows)
+        .expect("The font has a question mark")
+}
+
+/// Draws the text in large letters of the built-in font, [`BIG_TEXT_HEIGHT`] rows per line of
+/// text.
+///
+/// Letters are drawn in uppercase, one column apart, and characters the font lacks (accented
+/// letters, for example) as question marks. Lines of text are separated by a blank row and
+/// trailing spaces are trimmed.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::render::big_text;
+///
+/// let art = big_text("Go!\nok");
+/// assert_eq!(art.lines().count(), 11);
+/// assert_eq!(art.lines().next(), Some(" ####  ###  #"));
+/// ```
+pub fn big_text(text: &str) -> String {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            rows.push(String::new());
+        }
+        for row in 0..BIG_TEXT_HEIGHT {
+            let cells: Vec<&str> = line.chars().map(|c| glyph(c)[row]).collect();
+            rows.push(cells.join(" ").trim_end().to_string());
+        }
+    }
+    rows.join("\n")
+}
+
+/// Lays out the cells of a key square as a grid `width` cells wide, one space between cells.
+///
+/// Row labels go down the left and column labels across the top; leave both empty for a bare
+/// grid.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::render::key_grid;
+///
+/// let cells: Vec<char> = "ABCDEFGHI".chars().collect();
+/// assert_eq!(key_grid(&cells, 3, &[], &[]), "A B C\nD E F\nG H I");
+/// assert_eq!(
+///     key_grid(&cells, 3, &['1', '2', '3'], &['x', 'y', 'z']),
+///     "  x y z\n1 A B C\n2 D E F\n3 G H I"
+/// );
+/// ```
+pub fn key_grid(
+    cells: &[char],
+    width: usize,
+    row_labels: &[char],
+    column_labels: &[char],
+) -> String {
+    let join = |chars: &[char]| {
+        chars
+            .iter()
+            .map(char::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut rows = Vec::new();
+    if !column_labels.is_empty() {
+        let indent = if row_labels.is_empty() { "" } else { "  " };
+        rows.push(format!("{}{}", indent, join(column_labels)));
+    }
+    for (i, row) in cells.chunks(width.max(1)).enumerate() {
+        match row_labels.get(i) {
+            Some(label) => rows.push(format!("{} {}", label, join(row))),
+            None => rows.push(join(row)),
+        }
+    }
+    rows.join("\n")
+}
+
+/// Lays out a Polybius square with its row and column labels, as used by the Polybius, ADFGX,
+/// ADFGVX, bifid and Nihilist ciphers.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{AdfgvxCipher, PolybiusGrid};
+/// use cipha::render::polybius_grid;
+///
+/// let cipher = AdfgvxCipher::new(PolybiusGrid::FiveByFive, "BTALPDHOZKQFVSNGICUXMREWY", "CARGO").unwrap();
+/// assert_eq!(
+///     polybius_grid(cipher.square()),
+///     "  A D F G X\n\
+///      A B T A L P\n\
+///      D D H O Z K\n\
+///      F Q F V S N\n\
+///      G G I C U X\n\
+///      X M R E W Y"
+/// );
+/// ```
+pub fn polybius_grid(square: &PolybiusSquare) -> String {
+    let (rows, columns) = square.labels();
+    let cells: Vec<char> = square.cells().chars().collect();
+    let rows: Vec<char> = rows.chars().collect();
+    let columns: Vec<char> = columns.chars().collect();
+    key_grid(&cells, square.grid().size(), &rows, &columns)
+}
+
+/// The markup of an [`aligned_rows`] table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    /// Plain text, every row starting with its label.
+    Text,
+    /// A LaTeX `tabular` with one column per character.
+    Latex,
+}
+
+/// Lays out labelled rows one above the other, character under character, in blocks of `width`
+/// characters, such as a plaintext, the key letter used for each of its letters and the
+/// ciphertext.
+///
+/// Rows shorter than the longest are padded with spaces. As text, each row of a block starts
+/// with its label and a colon, padded to the longest label, and blocks are separated by a blank
+/// line. As LaTeX, each block is a `tabular` with the labels in the first column and the
+/// characters that LaTeX treats specially escaped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::render::{aligned_rows, Markup};
+///
+/// let rows = [("Plain", "ATTACK AT"), ("Key", "LEMONL EM"), ("Cipher", "LXFOPV EF")];
+/// assert_eq!(
+///     aligned_rows(&rows, 6, Markup::Text),
+///     "Plain:  ATTACK\nKey:    LEMONL\nCipher: LXFOPV\n\nPlain:   AT\nKey:     EM\nCipher:  EF"
+/// );
+/// assert!(aligned_rows(&rows, 80, Markup::Latex).starts_with("\\begin{tabular}{l*{9}{c}}\n"));
+/// ```
+pub fn aligned_rows(rows: &[(&str, &str)], width: usize, markup: Markup) -> String {
+    let rows: Vec<(&str, Vec<char>)> = rows
+        .iter()
+        .map(|(label, text)| (*label, text.chars().collect()))
+        .collect();
+    let len = rows.iter().map(|(_, chars)| chars.len()).max().unwrap_or(0);
+    let label_width = rows.iter().map(|(label, _)| label.chars().count()).max();
+    let label_width = label_width.unwrap_or(0) + 1;
+    let width = width.max(1);
+    let mut blocks = Vec::new();
+    for start in (0..len).step_by(width) {
+        let end = (start + width).min(len);
+        let cells = |chars: &[char]| -> Vec<char> {
+            (start..end)
+                .map(|i| chars.get(i).copied().unwrap_or(' '))
+                .collect()
+        };
+        let block = match markup {
+            Markup::Text => rows
+                .iter()
+                .map(|(label, chars)| {
+                    let line = format!(
+                        "{:<label_width$} {}",
+                        format!("{}:", label),
+                        cells(chars).into_iter().collect::<String>()
+                    );
+                    line.trim_end().to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Markup::Latex => {
+                let mut lines = vec![format!("\\begin{{tabular}}{{l*{{{}}}{{c}}}}", end - start)];
+                for (label, chars) in &rows {
+                    let mut line = latex_escape(label);
+                    for c in cells(chars) {
+                        line.push_str(" & ");
+                        line.push_str(&latex_escape(&c.to_string()));
+                    }
+                    line.push_str(" \\\\");
+                    lines.push(line);
+                }
+                lines.push("\\end{tabular}".to_string());
+                lines.join("\n")
+            }
+        };
+        blocks.push(block);
+    }
+    blocks.join("\n\n")
+}
+
+// Escapes the characters LaTeX treats specially, leaving spaces as empty cells.
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            ' ' => {}
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ciphers::PolybiusGrid;
+
+    #[test]
+    fn test_big_text() {
+        for (c, rows) in FONT {
+            let width = rows[0].len();
+            assert!(rows.iter().all(|r| r.len() == width), "{:?}", c);
+        }
+        assert_eq!(
+            big_text("a1"),
+            " ###   #\n#   # ##\n#####  #\n#   #  #\n#   # ###"
+        );
+        // Unknown characters are drawn as question marks
+        assert_eq!(big_text("é"), big_text("?"));
+        assert_eq!(big_text("A B").lines().next(), Some(" ###      ####"));
+        assert_eq!(big_text(""), "");
+    }
+
+    #[test]
+    fn test_aligned_rows() {
+        // The shorter row is padded, and trailing spaces are trimmed
+        let rows = [("P", "AB CD"), ("Key", "KE")];
+        assert_eq!(
+            aligned_rows(&rows, 3, Markup::Text),
+            "P:   AB\nKey: KE\n\nP:   CD\nKey:"
+        );
+        assert_eq!(
+            aligned_rows(&rows, 0, Markup::Text).lines().count(),
+            2 * 5 + 4
+        );
+        assert_eq!(aligned_rows(&[("P", "")], 10, Markup::Text), "");
+
+        let latex = aligned_rows(
+            &[("Plain", "A&B C"), ("Key_1", "~^\\ %")],
+            10,
+            Markup::Latex,
+        );
+        assert_eq!(
+            latex,
+            "\\begin{tabular}{l*{5}{c}}\n\
+             Plain & A & \\& & B &  & C \\\\\n\
+             Key\\_1 & \\textasciitilde{} & \\textasciicircum{} & \\textbackslash{} &  & \\% \\\\\n\
+             \\end{tabular}"
+        );
+    }
+
+    #[test]
+    fn test_key_grids() {
+        let square = PolybiusSquare::new(PolybiusGrid::SixBySix).with_keyword("KEY");
+        let grid = polybius_grid(&square);
+        assert_eq!(grid.lines().count(), 7);
+        assert_eq!(grid.lines().next(), Some("  1 2 3 4 5 6"));
+        assert_eq!(grid.lines().nth(1), Some("1 K E Y A B C"));
+        assert_eq!(grid.lines().last(), Some("6 4 5 6 7 8 9"));
+
+        // Labels on one side only
+        let cells: Vec<char> = "ABCD".chars().collect();
+        assert_eq!(key_grid(&cells, 2, &[], &['x', 'y']), "x y\nA B\nC D");
+        assert_eq!(key_grid(&cells, 2, &['1', '2'], &[]), "1 A B\n2 C D");
+    }
+}
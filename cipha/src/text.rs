@@ -0,0 +1,1708 @@
+//! Text preparation helpers used before enciphering.
+//!
+//! Classical cryptograms are conventionally written in uppercase letters only, without
+//! accents, digits, spaces or punctuation ("ATTACK AT DAWN" becomes "ATTACKATDAWN").
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::ciphers::Cipher;
+
+const DIGIT_WORDS: [&str; 10] = [
+    "ZERO", "ONE", "TWO", "THREE", "FOUR", "FIVE", "SIX", "SEVEN", "EIGHT", "NINE",
+];
+
+/// Options controlling [`preprocess`].
+///
+/// Each step is off by default; [`PreprocessOptions::cryptogram`] turns on the classical
+/// cryptogram convention and [`PreprocessOptions::ascii`] brings text in European languages down
+/// to the letters `a` to `z` that most ciphers work on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreprocessOptions {
+    /// Replace compatibility characters by their plain forms (NFKC), e.g. `ﬁ` becomes `fi` and
+    /// `²` becomes `2`.
+    pub compatibility: bool,
+    /// Spell letters that do not decompose with plain letters: `ß` becomes `ss`, `æ` `ae`, `œ`
+    /// `oe`, `þ` `th`, and `ø`, `ł`, `đ`, `ð` and `ı` lose their stroke or dot.
+    pub expand_letters: bool,
+    /// Remove accents by decomposing characters (NFD) and dropping the combining marks.
+    pub strip_accents: bool,
+    /// Replace every digit with its English name, e.g. `1` becomes `ONE`.
+    pub spell_digits: bool,
+    /// Convert letters to uppercase.
+    pub uppercase: bool,
+    /// Convert letters to lowercase. This runs after `uppercase`, so setting both lowercases.
+    pub lowercase: bool,
+    /// Remove every character that is not a letter.
+    pub letters_only: bool,
+}
+
+impl PreprocessOptions {
+    /// Creates options with every step turned off.
+    pub fn new() -> Self {
+        PreprocessOptions::default()
+    }
+
+    /// Creates options matching the classical cryptogram convention: uppercase letters only,
+    /// without accents, with the digits spelled out.
+    pub fn cryptogram() -> Self {
+        PreprocessOptions {
+            uppercase: true,
+            strip_accents: true,
+            spell_digits: true,
+            letters_only: true,
+            ..PreprocessOptions::new()
+        }
+    }
+
+    /// Creates options that bring text down to lowercase `a` to `z`, keeping digits, spaces and
+    /// punctuation, for ciphers that only know the basic Latin letters.
+    pub fn ascii() -> Self {
+        PreprocessOptions {
+            compatibility: true,
+            expand_letters: true,
+            strip_accents: true,
+            lowercase: true,
+            ..PreprocessOptions::new()
+        }
+    }
+}
+
+/// Prepares text for enciphering according to the given options.
+///
+/// The steps run in a fixed order (compatibility forms, letters, accents, digits, case,
+/// non-letters), so that a ligature is first split and its letters then stripped, and the output
+/// only depends on the input and the options. A cipher that passes other characters through
+/// leaves accented letters unenciphered; preprocessing first enciphers them as their base
+/// letters, and the text deciphers to its preprocessed form.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{preprocess, PreprocessOptions};
+/// let output = preprocess("Attaque à l'aube, 5 heures!", &PreprocessOptions::cryptogram());
+/// assert_eq!(output, "ATTAQUEALAUBEFIVEHEURES");
+/// assert_eq!(
+///     preprocess("Straße nach Zürich", &PreprocessOptions::ascii()),
+///     "strasse nach zurich"
+/// );
+///
+/// let options = PreprocessOptions {
+///     strip_accents: true,
+///     ..PreprocessOptions::new()
+/// };
+/// assert_eq!(preprocess("Łódź", &options), "Łodz");
+/// ```
+pub fn preprocess(text: &str, options: &PreprocessOptions) -> String {
+    let mut result: String = if options.compatibility {
+        text.nfkc().collect()
+    } else {
+        text.to_string()
+    };
+
+    if options.expand_letters {
+        result = result
+            .chars()
+            .map(|c| match expanded_letter(c) {
+                Some(letters) => letters.to_string(),
+                None => c.to_string(),
+            })
+            .collect();
+    }
+
+    if options.strip_accents {
+        result = result.nfd().filter(|c| !is_combining_mark(*c)).collect();
+    }
+
+    if options.spell_digits {
+        result = result
+            .chars()
+            .map(|c| match c.to_digit(10) {
+                Some(d) if options.uppercase && !options.lowercase => {
+                    DIGIT_WORDS[d as usize].to_string()
+                }
+                Some(d) => DIGIT_WORDS[d as usize].to_lowercase(),
+                None => c.to_string(),
+            })
+            .collect();
+    }
+
+    if options.uppercase {
+        result = result.to_uppercase();
+    }
+
+    if options.lowercase {
+        result = result.to_lowercase();
+    }
+
+    if options.letters_only {
+        result.retain(|c| c.is_alphabetic());
+    }
+
+    result
+}
+
+/// A pipeline stage that runs [`preprocess`] on the plaintext.
+///
+/// Preprocessing loses information, so deciphering passes the text through unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::ciphers::{CaesarCipher, Cipher};
+/// use cipha::pipeline::Pipeline;
+/// use cipha::text::{Preprocess, PreprocessOptions};
+///
+/// let pipeline = Pipeline::new()
+///     .then(Preprocess::new(PreprocessOptions::cryptogram()))
+///     .then(CaesarCipher::new(3));
+/// let ciphertext = pipeline.encipher("Attack at dawn").unwrap();
+/// assert_eq!(ciphertext, "DWWDFNDWGDZQ");
+/// assert_eq!(pipeline.inverse().encipher(&ciphertext).unwrap(), "ATTACKATDAWN");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Preprocess {
+    options: PreprocessOptions,
+}
+
+impl Preprocess {
+    /// Creates a stage that preprocesses text with the given options.
+    pub fn new(options: PreprocessOptions) -> Self {
+        Preprocess { options }
+    }
+}
+
+impl Cipher for Preprocess {
+    fn encipher(&self, plaintext: &str) -> String {
+        preprocess(plaintext, &self.options)
+    }
+
+    fn decipher(&self, ciphertext: &str) -> String {
+        ciphertext.to_string()
+    }
+}
+
+// Returns the plain letters for a letter that has no decomposition into a base letter.
+fn expanded_letter(c: char) -> Option<&'static str> {
+    Some(match c {
+        'ß' => "ss",
+        'ẞ' => "SS",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'þ' => "th",
+        'Þ' => "TH",
+        'ø' => "o",
+        'Ø' => "O",
+        'ł' => "l",
+        'Ł' => "L",
+        'đ' | 'ð' => "d",
+        'Đ' | 'Ð' => "D",
+        'ı' => "i",
+        _ => return None,
+    })
+}
+
+/// How [`spell_numbers`] writes numbers out as words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberStyle {
+    /// Every digit on its own: `1915` becomes `ONENINEONEFIVE`.
+    Digits,
+    /// The full cardinal number: `1915` becomes `ONETHOUSANDNINEHUNDREDFIFTEEN`.
+    Cardinal,
+    /// Four digit numbers are read as years (`1915` becomes `NINETEENFIFTEEN`), others as cardinals.
+    Year,
+}
+
+const TEENS: [&str; 10] = [
+    "TEN",
+    "ELEVEN",
+    "TWELVE",
+    "THIRTEEN",
+    "FOURTEEN",
+    "FIFTEEN",
+    "SIXTEEN",
+    "SEVENTEEN",
+    "EIGHTEEN",
+    "NINETEEN",
+];
+const TENS: [&str; 10] = [
+    "", "", "TWENTY", "THIRTY", "FORTY", "FIFTY", "SIXTY", "SEVENTY", "EIGHTY", "NINETY",
+];
+const SCALES: [(u64, &str); 3] = [
+    (1_000_000_000, "BILLION"),
+    (1_000_000, "MILLION"),
+    (1_000, "THOUSAND"),
+];
+
+/// Replaces every run of digits in the text with uppercase words in the given style.
+///
+/// Numbers with a leading zero (like `007`) are always spelled digit by digit so they can be
+/// parsed back unchanged. Other characters, such as date separators, are kept as they are.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{spell_numbers, NumberStyle};
+/// assert_eq!(spell_numbers("1915", NumberStyle::Digits), "ONENINEONEFIVE");
+/// assert_eq!(spell_numbers("1915", NumberStyle::Year), "NINETEENFIFTEEN");
+/// assert_eq!(spell_numbers("25 DEC 1915", NumberStyle::Year), "TWENTYFIVE DEC NINETEENFIFTEEN");
+/// ```
+pub fn spell_numbers(text: &str, style: NumberStyle) -> String {
+    let mut result = String::new();
+    let mut digits = String::new();
+    for c in text.chars().chain(std::iter::once('\0')) {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        if !digits.is_empty() {
+            result.push_str(&spell_digit_run(&digits, style));
+            digits.clear();
+        }
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+    result
+}
+
+// Spells a run of ASCII digits.
+fn spell_digit_run(digits: &str, style: NumberStyle) -> String {
+    let by_digit = || {
+        digits
+            .bytes()
+            .map(|d| DIGIT_WORDS[(d - b'0') as usize])
+            .collect::<String>()
+    };
+    if style == NumberStyle::Digits || (digits.len() > 1 && digits.starts_with('0')) {
+        return by_digit();
+    }
+    let number: u64 = match digits.parse() {
+        Ok(number) if number < 1_000_000_000_000 => number,
+        _ => return by_digit(),
+    };
+    if style == NumberStyle::Year && digits.len() == 4 {
+        spell_year(number)
+    } else {
+        spell_cardinal(number)
+    }
+}
+
+// Spells a number below one trillion, e.g. 1915 as ONETHOUSANDNINEHUNDREDFIFTEEN.
+fn spell_cardinal(number: u64) -> String {
+    if number == 0 {
+        return DIGIT_WORDS[0].to_string();
+    }
+    let mut words = String::new();
+    let mut rest = number;
+    for (scale, name) in SCALES {
+        if rest >= scale {
+            words.push_str(&spell_below_thousand(rest / scale));
+            words.push_str(name);
+            rest %= scale;
+        }
+    }
+    words.push_str(&spell_below_thousand(rest));
+    words
+}
+
+// Spells a number below one thousand, or nothing for zero.
+fn spell_below_thousand(number: u64) -> String {
+    let mut words = String::new();
+    if number >= 100 {
+        words.push_str(DIGIT_WORDS[(number / 100) as usize]);
+        words.push_str("HUNDRED");
+    }
+    let rest = (number % 100) as usize;
+    match rest {
+        0 => {}
+        1..=9 => words.push_str(DIGIT_WORDS[rest]),
+        10..=19 => words.push_str(TEENS[rest - 10]),
+        _ => {
+            words.push_str(TENS[rest / 10]);
+            if !rest.is_multiple_of(10) {
+                words.push_str(DIGIT_WORDS[rest % 10]);
+            }
+        }
+    }
+    words
+}
+
+// Spells a four digit year the way it is spoken, e.g. 1905 as NINETEENOHFIVE.
+fn spell_year(year: u64) -> String {
+    let (century, rest) = (year / 100, year % 100);
+    if year % 1000 < 10 {
+        // 2000 to 2009 and 1000 to 1009 are read as cardinals
+        spell_cardinal(year)
+    } else if rest == 0 {
+        format!("{}HUNDRED", spell_cardinal(century))
+    } else if rest < 10 {
+        format!(
+            "{}OH{}",
+            spell_cardinal(century),
+            DIGIT_WORDS[rest as usize]
+        )
+    } else {
+        format!("{}{}", spell_cardinal(century), spell_cardinal(rest))
+    }
+}
+
+// A number word recognised by `parse_spelled_numbers`.
+#[derive(Clone, Copy)]
+enum NumberWord {
+    // ZERO to NINE, and OH for zero
+    Unit(u64),
+    // TEN to NINETEEN
+    Teen(u64),
+    // TWENTY to NINETY
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+// Every number word, longest first so that e.g. SEVENTEEN wins over SEVEN.
+fn number_words() -> Vec<(String, NumberWord)> {
+    let mut words: Vec<(String, NumberWord)> = Vec::new();
+    for (i, word) in DIGIT_WORDS.iter().enumerate() {
+        words.push((word.to_string(), NumberWord::Unit(i as u64)));
+    }
+    words.push(("OH".to_string(), NumberWord::Unit(0)));
+    for (i, word) in TEENS.iter().enumerate() {
+        words.push((word.to_string(), NumberWord::Teen(10 + i as u64)));
+    }
+    for (i, word) in TENS.iter().enumerate().skip(2) {
+        words.push((word.to_string(), NumberWord::Tens(10 * i as u64)));
+    }
+    words.push(("HUNDRED".to_string(), NumberWord::Hundred));
+    for (scale, word) in SCALES {
+        words.push((word.to_string(), NumberWord::Scale(scale)));
+    }
+    words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+    words
+}
+
+// Turns a run of number words into digits, splitting it into several numbers where the words
+// cannot belong to the same number (ONE NINE, NINETEEN FIFTEEN, NINETEEN OH FIVE).
+fn number_words_to_digits(run: &[NumberWord]) -> String {
+    // Writes out the number read so far and starts a new one
+    fn flush(total: &mut u64, current: &mut u64, started: &mut bool, digits: &mut String) {
+        if *started {
+            digits.push_str(&(*total + *current).to_string());
+        }
+        *total = 0;
+        *current = 0;
+        *started = false;
+    }
+
+    let mut digits = String::new();
+    // The completed scales (thousands, millions...) and the group below a thousand being read
+    let mut total = 0;
+    let mut current = 0;
+    let mut started = false;
+    // What the current group may still be followed by
+    let mut accepts_unit = true;
+    let mut accepts_tens = true;
+
+    for (i, word) in run.iter().enumerate() {
+        match *word {
+            NumberWord::Unit(0) if started || i > 0 => {
+                // OH/ZERO inside a run is a literal zero digit
+                flush(&mut total, &mut current, &mut started, &mut digits);
+                digits.push('0');
+                accepts_unit = true;
+                accepts_tens = true;
+            }
+            NumberWord::Unit(value) => {
+                if started && !accepts_unit {
+                    flush(&mut total, &mut current, &mut started, &mut digits);
+                }
+                current += value;
+                started = true;
+                accepts_unit = false;
+                accepts_tens = false;
+            }
+            NumberWord::Teen(value) | NumberWord::Tens(value) => {
+                if started && !accepts_tens {
+                    flush(&mut total, &mut current, &mut started, &mut digits);
+                }
+                current += value;
+                started = true;
+                accepts_unit = matches!(word, NumberWord::Tens(_));
+                accepts_tens = false;
+            }
+            NumberWord::Hundred => {
+                current = current.max(1) * 100;
+                started = true;
+                accepts_unit = true;
+                accepts_tens = true;
+            }
+            NumberWord::Scale(scale) => {
+                total += current.max(1) * scale;
+                current = 0;
+                started = true;
+                accepts_unit = true;
+                accepts_tens = true;
+            }
+        }
+    }
+    flush(&mut total, &mut current, &mut started, &mut digits);
+    digits
+}
+
+/// Replaces spelled-out numbers in the text with digits, as a best-effort inverse of [`spell_numbers`].
+///
+/// Matching ignores case. A run of number words is only replaced when it is a separate word or
+/// when it has at least two number words, so words such as `BONE` or `OFTEN` are left alone.
+/// Digit-by-digit, cardinal and year styles are all understood.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::parse_spelled_numbers;
+/// assert_eq!(parse_spelled_numbers("ONENINEONEFIVE"), "1915");
+/// assert_eq!(parse_spelled_numbers("NINETEENFIFTEEN"), "1915");
+/// assert_eq!(parse_spelled_numbers("ROOM FORTY TWO"), "ROOM 42");
+/// ```
+pub fn parse_spelled_numbers(text: &str) -> String {
+    let words = number_words();
+    let chars: Vec<char> = text.chars().collect();
+    let upper: Vec<char> = chars.iter().map(|c| c.to_ascii_uppercase()).collect();
+    let matches_at = |position: usize| {
+        words.iter().find(|(word, _)| {
+            word.len() <= upper.len() - position
+                && word.chars().zip(&upper[position..]).all(|(a, &b)| a == b)
+        })
+    };
+
+    let mut result = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        // Collect a run of number words, allowing spaces between them
+        let mut run = Vec::new();
+        let mut end = i;
+        let mut j = i;
+        while let Some((word, value)) = matches_at(j) {
+            // OH only counts as zero after another number word
+            if run.is_empty() && word == "OH" {
+                break;
+            }
+            run.push(*value);
+            j += word.len();
+            end = j;
+            while j < chars.len() && chars[j] == ' ' {
+                j += 1;
+            }
+        }
+
+        let starts_word = i == 0 || !chars[i - 1].is_alphabetic();
+        let ends_word = end == chars.len() || !chars[end].is_alphabetic();
+        if run.len() >= 2 || (run.len() == 1 && starts_word && ends_word) {
+            result.push_str(&number_words_to_digits(&run));
+            i = end;
+        } else {
+            result.push(chars[i]);
+            i += 1;
+        }
+    }
+    result
+}
+
+const ROMAN_NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// How strictly [`parse_roman`] reads Roman numerals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomanParsing {
+    /// Only canonical uppercase numerals from 1 to 3999, as written by [`to_roman`].
+    Strict,
+    /// Any case and any sequence of numeral letters, such as `iiii`, `IC` or the medieval `viij`
+    /// (a trailing `J` reads as `I`). A letter followed by a larger one is subtracted.
+    Permissive,
+}
+
+/// Writes a number as a canonical Roman numeral.
+///
+/// # Errors
+///
+/// Returns an error if the number is not between 1 and 3999.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{parse_roman, to_roman, RomanParsing};
+/// assert_eq!(to_roman(1994).unwrap(), "MCMXCIV");
+/// assert_eq!(parse_roman("MCMXCIV", RomanParsing::Strict).unwrap(), 1994);
+/// assert!(parse_roman("IIII", RomanParsing::Strict).is_err());
+/// assert_eq!(parse_roman("iiii", RomanParsing::Permissive).unwrap(), 4);
+/// ```
+pub fn to_roman(number: u32) -> Result<String, String> {
+    if !(1..=3999).contains(&number) {
+        return Err(format!(
+            "{} cannot be written as a Roman numeral (1 to 3999)",
+            number
+        ));
+    }
+    let mut rest = number;
+    let mut numeral = String::new();
+    for (value, letters) in ROMAN_NUMERALS {
+        while rest >= value {
+            numeral.push_str(letters);
+            rest -= value;
+        }
+    }
+    Ok(numeral)
+}
+
+/// Reads a Roman numeral.
+///
+/// # Errors
+///
+/// Returns an error if the numeral is empty, contains other letters, or (in strict mode) is not
+/// the canonical form of its value.
+pub fn parse_roman(numeral: &str, mode: RomanParsing) -> Result<u32, String> {
+    let values = numeral
+        .chars()
+        .map(|c| {
+            let c = if mode == RomanParsing::Permissive {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            };
+            match c {
+                'I' => Ok(1),
+                'J' if mode == RomanParsing::Permissive => Ok(1),
+                'V' => Ok(5),
+                'X' => Ok(10),
+                'L' => Ok(50),
+                'C' => Ok(100),
+                'D' => Ok(500),
+                'M' => Ok(1000),
+                _ => Err(format!("{:?} is not a Roman numeral", numeral)),
+            }
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+    if values.is_empty() {
+        return Err("Roman numeral must not be empty".to_string());
+    }
+
+    // Every subtracted letter is followed by a larger one, so the total ends up positive
+    let total = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            if values.get(i + 1).is_some_and(|&next| next > value) {
+                -(value as i64)
+            } else {
+                value as i64
+            }
+        })
+        .sum::<i64>() as u32;
+    if mode == RomanParsing::Strict && to_roman(total).ok().as_deref() != Some(numeral) {
+        return Err(format!("{:?} is not a canonical Roman numeral", numeral));
+    }
+    Ok(total)
+}
+
+/// Replaces every run of digits in the text with a Roman numeral.
+///
+/// Numbers outside 1 to 3999, and numbers with a leading zero, are left as they are.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::romanize_numbers;
+/// assert_eq!(romanize_numbers("Chapter 14, page 0"), "Chapter XIV, page 0");
+/// ```
+pub fn romanize_numbers(text: &str) -> String {
+    replace_runs(
+        text,
+        |c| c.is_ascii_digit(),
+        |digits| {
+            if digits.starts_with('0') {
+                return None;
+            }
+            digits.parse().ok().and_then(|number| to_roman(number).ok())
+        },
+    )
+}
+
+/// Replaces every word that is a Roman numeral with its value in digits, as an inverse of
+/// [`romanize_numbers`].
+///
+/// Words are runs of letters. Ordinary words can be valid numerals too (`I`, `MIX`, `CIVIC`), so
+/// this is meant for text that is known to hold numerals, such as the output of a cipher stage.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{parse_roman_numerals, RomanParsing};
+/// assert_eq!(parse_roman_numerals("Chapter XIV", RomanParsing::Strict), "Chapter 14");
+/// ```
+pub fn parse_roman_numerals(text: &str, mode: RomanParsing) -> String {
+    replace_runs(text, char::is_alphabetic, |word| {
+        parse_roman(word, mode).ok().map(|n| n.to_string())
+    })
+}
+
+/// What [`normalize_transcription`] does with editorial brackets (`[]`, `<>`, `{}`, `⟨⟩`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EditorialBrackets {
+    /// Leave the brackets and their contents alone.
+    #[default]
+    Keep,
+    /// Drop the brackets but keep their contents, e.g. letters supplied by the editor.
+    StripMarks,
+    /// Drop the brackets together with their contents, e.g. editorial notes such as `[illegible]`.
+    Remove,
+}
+
+/// Options controlling [`normalize_transcription`].
+///
+/// Every step is off by default. The presets follow common transcription conventions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TranscriptionOptions {
+    /// Replace every run of whitespace, line breaks included, with one space and trim the ends.
+    pub collapse_whitespace: bool,
+    /// Write both U and V as this letter, keeping the case.
+    pub u_v: Option<char>,
+    /// Write both I and J as this letter, keeping the case.
+    pub i_j: Option<char>,
+    /// Replace the long s (`ſ`) with `s`.
+    pub long_s: bool,
+    /// How editorial brackets are handled.
+    pub brackets: EditorialBrackets,
+}
+
+impl TranscriptionOptions {
+    /// Creates options with every step turned off.
+    pub fn new() -> Self {
+        TranscriptionOptions::default()
+    }
+
+    /// Early modern printing: U/V as `U`, I/J as `I`, long s as `s`, brackets stripped.
+    pub fn early_modern() -> Self {
+        TranscriptionOptions {
+            collapse_whitespace: true,
+            u_v: Some('U'),
+            i_j: Some('I'),
+            long_s: true,
+            brackets: EditorialBrackets::StripMarks,
+        }
+    }
+
+    /// Classical Latin inscriptions: U/V as `V`, I/J as `I`, editorial notes removed.
+    pub fn classical_latin() -> Self {
+        TranscriptionOptions {
+            collapse_whitespace: true,
+            u_v: Some('V'),
+            i_j: Some('I'),
+            long_s: true,
+            brackets: EditorialBrackets::Remove,
+        }
+    }
+}
+
+/// A normalized transcription and what was changed to get it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptionReport {
+    /// The normalized text.
+    pub text: String,
+    /// The number of bracketed passages whose brackets or contents were dropped.
+    pub brackets: usize,
+    /// The number of letters replaced by their unified form (U/V, I/J and long s).
+    pub letters_unified: usize,
+    /// The number of whitespace characters dropped by collapsing.
+    pub whitespace_removed: usize,
+    /// The number of letters in the normalized text.
+    pub letters: usize,
+}
+
+/// Cleans up a transcribed historical ciphertext before it is handed to a solver.
+///
+/// The steps run in a fixed order (brackets, letter variants, whitespace), so text removed
+/// with the brackets does not leave a double space behind.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{normalize_transcription, TranscriptionOptions};
+/// let report = normalize_transcription(
+///     "Iuſtice  [sic] vn-\n  to all",
+///     &TranscriptionOptions::early_modern(),
+/// );
+/// assert_eq!(report.text, "Iustice sic un- to all");
+/// assert_eq!(report.letters_unified, 2);
+/// ```
+pub fn normalize_transcription(text: &str, options: &TranscriptionOptions) -> TranscriptionReport {
+    let mut report = TranscriptionReport::default();
+
+    let mut depth = 0usize;
+    let mut bracketed = String::new();
+    for c in text.chars() {
+        let (opening, closing) = (
+            matches!(c, '[' | '<' | '{' | '⟨'),
+            matches!(c, ']' | '>' | '}' | '⟩'),
+        );
+        match options.brackets {
+            EditorialBrackets::Keep => bracketed.push(c),
+            _ if opening => {
+                if depth == 0 {
+                    report.brackets += 1;
+                }
+                depth += 1;
+            }
+            _ if closing => depth = depth.saturating_sub(1),
+            EditorialBrackets::Remove if depth > 0 => {}
+            _ => bracketed.push(c),
+        }
+    }
+
+    let unify = |c: char, target: Option<char>| {
+        let target = target?;
+        Some(if c.is_lowercase() {
+            target.to_ascii_lowercase()
+        } else {
+            target.to_ascii_uppercase()
+        })
+    };
+    let unified: String = bracketed
+        .chars()
+        .map(|c| {
+            let replacement = match c {
+                'u' | 'v' | 'U' | 'V' => unify(c, options.u_v),
+                'i' | 'j' | 'I' | 'J' => unify(c, options.i_j),
+                'ſ' if options.long_s => Some('s'),
+                _ => None,
+            };
+            match replacement {
+                Some(r) if r != c => {
+                    report.letters_unified += 1;
+                    r
+                }
+                _ => c,
+            }
+        })
+        .collect();
+
+    report.text = if options.collapse_whitespace {
+        let collapsed = unified.split_whitespace().collect::<Vec<_>>().join(" ");
+        report.whitespace_removed =
+            unified.chars().filter(|c| c.is_whitespace()).count() - collapsed.matches(' ').count();
+        collapsed
+    } else {
+        unified
+    };
+    report.letters = report.text.chars().filter(|c| c.is_alphabetic()).count();
+    report
+}
+
+// Replaces every maximal run of characters matching `in_run` for which `replace` returns a value.
+fn replace_runs(
+    text: &str,
+    in_run: impl Fn(char) -> bool,
+    replace: impl Fn(&str) -> Option<String>,
+) -> String {
+    let mut result = String::new();
+    let mut run = String::new();
+    for c in text.chars().chain(std::iter::once('\0')) {
+        if c != '\0' && in_run(c) {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            result.push_str(&replace(&run).unwrap_or_else(|| run.clone()));
+            run.clear();
+        }
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// A character encoding of text files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    /// UTF-8, with or without a byte order mark.
+    Utf8,
+    /// UTF-16, little-endian unless a byte order mark says otherwise.
+    Utf16Le,
+    /// UTF-16, big-endian unless a byte order mark says otherwise.
+    Utf16Be,
+    /// ISO 8859-1, where every byte is the code point of the same number.
+    Latin1,
+    /// Windows-1252, Latin-1 with printable characters such as curly quotes and the euro sign in
+    /// place of the control codes 0x80 to 0x9F.
+    Windows1252,
+}
+
+impl std::str::FromStr for TextEncoding {
+    type Err = String;
+
+    /// Parses an encoding name in either case, e.g. `"utf-8"`, `"latin1"` or `"cp1252"`.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => Ok(TextEncoding::Utf8),
+            "utf16" | "utf16le" => Ok(TextEncoding::Utf16Le),
+            "utf16be" => Ok(TextEncoding::Utf16Be),
+            "latin1" | "iso88591" => Ok(TextEncoding::Latin1),
+            "windows1252" | "cp1252" => Ok(TextEncoding::Windows1252),
+            _ => Err(format!(
+                "Unknown encoding {:?} (expected utf-8, utf-16le, utf-16be, latin-1 or windows-1252)",
+                name
+            )),
+        }
+    }
+}
+
+// The characters of Windows-1252 bytes 0x80 to 0x9F. Unassigned bytes keep their Latin-1
+// control code.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// Guesses the encoding of a text file.
+///
+/// A byte order mark decides; otherwise valid UTF-8 is UTF-8. Anything else is taken as
+/// Windows-1252 if it uses the bytes 0x80 to 0x9F, which are control codes in Latin-1 and hardly
+/// ever meant as such, and as Latin-1 if not. UTF-16 is only recognised by its byte order mark.
+pub fn detect_encoding(bytes: &[u8]) -> TextEncoding {
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        TextEncoding::Utf16Le
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        TextEncoding::Utf16Be
+    } else if std::str::from_utf8(bytes).is_ok() {
+        TextEncoding::Utf8
+    } else if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) {
+        TextEncoding::Windows1252
+    } else {
+        TextEncoding::Latin1
+    }
+}
+
+/// Decodes the bytes of a text file, detecting the encoding with [`detect_encoding`] unless one
+/// is given. A leading byte order mark is removed.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not valid in the encoding; Latin-1 and Windows-1252 accept
+/// any bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{decode_text, TextEncoding};
+/// assert_eq!(decode_text(b"caf\xe9", None).unwrap(), "café");
+/// assert_eq!(decode_text(b"\x93Hi\x94", None).unwrap(), "“Hi”");
+/// assert_eq!(decode_text(b"\xff\xfeH\0i\0", None).unwrap(), "Hi");
+/// assert_eq!(decode_text(b"\x93", Some(TextEncoding::Latin1)).unwrap(), "\u{93}");
+/// ```
+pub fn decode_text(bytes: &[u8], encoding: Option<TextEncoding>) -> Result<String, String> {
+    let encoding = encoding.unwrap_or_else(|| detect_encoding(bytes));
+    match encoding {
+        TextEncoding::Utf8 => {
+            let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).map_err(|e| format!("Invalid UTF-8: {}", e))
+        }
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let (big_endian, bytes) = match bytes {
+                [0xFF, 0xFE, rest @ ..] => (false, rest),
+                [0xFE, 0xFF, rest @ ..] => (true, rest),
+                _ => (encoding == TextEncoding::Utf16Be, bytes),
+            };
+            if bytes.len() % 2 != 0 {
+                return Err("Invalid UTF-16: odd number of bytes".to_string());
+            }
+            let units = bytes.chunks(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if big_endian {
+                    u16::from_be_bytes(pair)
+                } else {
+                    u16::from_le_bytes(pair)
+                }
+            });
+            char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map_err(|e| format!("Invalid UTF-16: {}", e))
+        }
+        TextEncoding::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+        TextEncoding::Windows1252 => Ok(bytes
+            .iter()
+            .map(|&b| match b {
+                0x80..=0x9F => WINDOWS_1252_HIGH[(b - 0x80) as usize],
+                _ => b as char,
+            })
+            .collect()),
+    }
+}
+
+/// Encodes text for writing to a file, with a byte order mark if `bom` is set.
+///
+/// # Errors
+///
+/// Returns an error if a character cannot be written in Latin-1 or Windows-1252, or a byte order
+/// mark is asked for in an encoding that has none.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{encode_text, TextEncoding};
+/// assert_eq!(encode_text("Hi", TextEncoding::Utf8, true).unwrap(), b"\xEF\xBB\xBFHi");
+/// assert_eq!(encode_text("Hi", TextEncoding::Utf16Le, true).unwrap(), b"\xFF\xFEH\0i\0");
+/// assert_eq!(encode_text("“€”", TextEncoding::Windows1252, false).unwrap(), b"\x93\x80\x94");
+/// ```
+pub fn encode_text(text: &str, encoding: TextEncoding, bom: bool) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    match encoding {
+        TextEncoding::Utf8 => {
+            if bom {
+                bytes.extend_from_slice(b"\xEF\xBB\xBF");
+            }
+            bytes.extend_from_slice(text.as_bytes());
+        }
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let big_endian = encoding == TextEncoding::Utf16Be;
+            let units = bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+            for unit in units {
+                if big_endian {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+        }
+        TextEncoding::Latin1 | TextEncoding::Windows1252 => {
+            if bom {
+                return Err(format!("{:?} has no byte order mark", encoding));
+            }
+            for c in text.chars() {
+                let code = c as u32;
+                let byte = match encoding {
+                    TextEncoding::Windows1252 if (0x80..=0x9F).contains(&code) || code > 0xFF => {
+                        let position = WINDOWS_1252_HIGH.iter().position(|&h| h == c);
+                        position.map(|p| 0x80 + p as u8)
+                    }
+                    _ => u8::try_from(code).ok(),
+                };
+                bytes.push(byte.ok_or_else(|| format!("{:?} cannot encode {:?}", encoding, c))?);
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// A line ending convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// A line feed, as on Unix.
+    Lf,
+    /// A carriage return and a line feed, as on Windows.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the line ending most lines of the text end with, or `None` if it has no line
+    /// breaks. Ties go to [`LineEnding::CrLf`].
+    pub fn detect(text: &str) -> Option<Self> {
+        let line_feeds = text.matches('\n').count();
+        let crlfs = text.matches("\r\n").count();
+        match line_feeds {
+            0 => None,
+            _ if crlfs * 2 >= line_feeds => Some(LineEnding::CrLf),
+            _ => Some(LineEnding::Lf),
+        }
+    }
+
+    /// Returns the characters of the line ending.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+impl std::str::FromStr for LineEnding {
+    type Err = String;
+
+    /// Parses `"lf"` or `"crlf"` in either case.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "lf" => Ok(LineEnding::Lf),
+            "crlf" => Ok(LineEnding::CrLf),
+            _ => Err(format!(
+                "Unknown line ending {:?} (expected lf or crlf)",
+                name
+            )),
+        }
+    }
+}
+
+/// Rewrites every line ending of the text, LF or CRLF, as `ending`.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{convert_line_endings, LineEnding};
+/// assert_eq!(convert_line_endings("a\r\nb\nc", LineEnding::Lf), "a\nb\nc");
+/// assert_eq!(convert_line_endings("a\r\nb\nc", LineEnding::CrLf), "a\r\nb\r\nc");
+/// ```
+pub fn convert_line_endings(text: &str, ending: LineEnding) -> String {
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<&str>>()
+        .join(ending.as_str())
+}
+
+/// A policy for the case of the letters a cipher outputs.
+///
+/// Conventions differ between communities: the American Cryptogram Association writes
+/// ciphertext in capitals and plaintext in lowercase, while most ciphers here keep the case of
+/// their input where they can. [`Cased`](crate::ciphers::Cased) applies a policy to any cipher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LetterCase {
+    /// Leaves the output as the cipher produced it.
+    #[default]
+    Preserve,
+    /// Turns every letter into a capital.
+    Upper,
+    /// Turns every letter into a small letter.
+    Lower,
+}
+
+impl LetterCase {
+    /// Applies the policy to a text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::text::LetterCase;
+    /// assert_eq!(LetterCase::Upper.apply("Attack at dawn"), "ATTACK AT DAWN");
+    /// assert_eq!(LetterCase::Lower.apply("WKLNU DSAJQ"), "wklnu dsajq");
+    /// assert_eq!(LetterCase::Preserve.apply("Mixed Case"), "Mixed Case");
+    /// ```
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            LetterCase::Preserve => text.to_string(),
+            LetterCase::Upper => text.to_uppercase(),
+            LetterCase::Lower => text.to_lowercase(),
+        }
+    }
+}
+
+impl std::str::FromStr for LetterCase {
+    type Err = String;
+
+    /// Parses `"preserve"`, `"upper"` or `"lower"` in either case.
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "preserve" => Ok(LetterCase::Preserve),
+            "upper" => Ok(LetterCase::Upper),
+            "lower" => Ok(LetterCase::Lower),
+            _ => Err(format!(
+                "Unknown letter case {:?} (expected preserve, upper or lower)",
+                name
+            )),
+        }
+    }
+}
+
+/// The layout of a ciphertext: groups of a fixed size, lines of a fixed width and the case of
+/// the letters.
+///
+/// Ciphertext was traditionally sent in groups of five letters, which hides the word lengths
+/// of the plaintext and makes a message easy to count and copy. [`Formatter::unformat`] undoes
+/// the grouping and wrapping before deciphering, as far as the cipher needs: the case cannot be
+/// restored, and a grouped ciphertext loses its original spaces.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::{Formatter, LetterCase};
+/// let formatter = Formatter::new()
+///     .with_groups(5)
+///     .with_width(11)
+///     .with_case(LetterCase::Upper);
+/// let formatted = formatter.format("lxfopv ef rnhr");
+/// assert_eq!(formatted, "LXFOP VEFRN\nHR");
+/// assert_eq!(formatter.unformat(&formatted), "LXFOPVEFRNHR");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Formatter {
+    group: usize,
+    width: usize,
+    case: LetterCase,
+}
+
+impl Formatter {
+    /// Creates a formatter that leaves the text as it is.
+    pub fn new() -> Self {
+        Formatter::default()
+    }
+
+    /// Drops the whitespace and writes the rest in groups of `size` characters separated by
+    /// spaces. A size of zero leaves the spacing as it is.
+    pub fn with_groups(mut self, size: usize) -> Self {
+        self.group = size;
+        self
+    }
+
+    /// Breaks the lines at `width` characters, between groups if the text is grouped. A group
+    /// wider than the lines takes a line of its own. A width of zero leaves the lines as they
+    /// are.
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Writes the letters in the given case.
+    pub fn with_case(mut self, case: LetterCase) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Lays out a text.
+    pub fn format(&self, text: &str) -> String {
+        let text = self.case.apply(text);
+        if self.group > 0 {
+            let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+            let mut result = String::new();
+            let mut line = 0;
+            for group in chars.chunks(self.group) {
+                if line > 0 {
+                    if self.width > 0 && line + 1 + group.len() > self.width {
+                        result.push('\n');
+                        line = 0;
+                    } else {
+                        result.push(' ');
+                        line += 1;
+                    }
+                }
+                result.extend(group);
+                line += group.len();
+            }
+            result
+        } else if self.width > 0 {
+            text.split('\n')
+                .map(|line| {
+                    let chars: Vec<char> = line.chars().collect();
+                    chars
+                        .chunks(self.width)
+                        .map(|chunk| chunk.iter().collect::<String>())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            text
+        }
+    }
+
+    /// Undoes the grouping by dropping all whitespace, or else the wrapping by dropping the line
+    /// breaks.
+    pub fn unformat(&self, text: &str) -> String {
+        if self.group > 0 {
+            text.chars().filter(|c| !c.is_whitespace()).collect()
+        } else if self.width > 0 {
+            text.chars().filter(|&c| c != '\n' && c != '\r').collect()
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// What to do with the characters of a plaintext that are not letters, such as spaces, digits
+/// and punctuation.
+///
+/// Most ciphers pass such characters through, which keeps the word lengths of the plaintext
+/// readable in the ciphertext. Puzzles often want them gone, or marked, before enciphering.
+/// [`with_non_alpha`](crate::ciphers::FallibleCipher::with_non_alpha) applies a policy to any
+/// cipher, wrapping it in a [`Filtered`](crate::ciphers::Filtered).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonAlphaPolicy {
+    /// Keeps the characters as they are.
+    #[default]
+    Preserve,
+    /// Removes the characters.
+    Strip,
+    /// Rejects a text with any such character.
+    Error,
+    /// Replaces every such character with the given one.
+    Substitute(char),
+}
+
+impl NonAlphaPolicy {
+    /// Applies the policy to a text. Letters are the alphabetic characters of any script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first character that is not a letter and its position, from
+    /// 0, if the policy is [`NonAlphaPolicy::Error`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cipha::text::NonAlphaPolicy;
+    /// assert_eq!(NonAlphaPolicy::Strip.apply("Meet me at 10!"), Ok("Meetmeat".to_string()));
+    /// assert_eq!(NonAlphaPolicy::Substitute('X').apply("a.b c"), Ok("aXbXc".to_string()));
+    /// assert!(NonAlphaPolicy::Error.apply("Two words").is_err());
+    /// ```
+    pub fn apply(self, text: &str) -> Result<String, String> {
+        match self {
+            NonAlphaPolicy::Preserve => Ok(text.to_string()),
+            NonAlphaPolicy::Strip => Ok(preprocess(
+                text,
+                &PreprocessOptions {
+                    letters_only: true,
+                    ..PreprocessOptions::new()
+                },
+            )),
+            NonAlphaPolicy::Error => match text.chars().position(|c| !c.is_alphabetic()) {
+                Some(position) => Err(format!(
+                    "Non-alphabetic character {:?} at position {}",
+                    text.chars().nth(position).unwrap_or_default(),
+                    position
+                )),
+                None => Ok(text.to_string()),
+            },
+            NonAlphaPolicy::Substitute(substitute) => Ok(text
+                .chars()
+                .map(|c| if c.is_alphabetic() { c } else { substitute })
+                .collect()),
+        }
+    }
+}
+
+impl std::str::FromStr for NonAlphaPolicy {
+    type Err = String;
+
+    /// Parses `"preserve"`, `"strip"` or `"error"` in either case, or a single character to
+    /// substitute.
+    fn from_str(name: &str) -> Result<Self, String> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(NonAlphaPolicy::Substitute(c));
+        }
+        match name.trim().to_ascii_lowercase().as_str() {
+            "preserve" => Ok(NonAlphaPolicy::Preserve),
+            "strip" => Ok(NonAlphaPolicy::Strip),
+            "error" => Ok(NonAlphaPolicy::Error),
+            _ => Err(format!(
+                "Unknown non-alphabetic policy {:?} (expected preserve, strip, error or a single character)",
+                name
+            )),
+        }
+    }
+}
+
+/// The letter and group counts of a message, as telegrams and CW traffic report them.
+///
+/// Letters are the alphanumeric characters and groups the runs of them separated by whitespace.
+/// The group count is the check a receiving operator compares against what they copied, written
+/// `GR 27` in the message header.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::GroupCount;
+/// let count = GroupCount::of("WKLNU DSAJQ TOBX");
+/// assert_eq!((count.letters, count.groups), (14, 3));
+/// assert_eq!(count.to_string(), "GR 3");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupCount {
+    /// The number of letters and digits.
+    pub letters: usize,
+    /// The number of groups.
+    pub groups: usize,
+}
+
+impl GroupCount {
+    /// Counts the letters and groups of the message.
+    pub fn of(text: &str) -> Self {
+        GroupCount {
+            letters: text.chars().filter(|c| c.is_alphanumeric()).count(),
+            groups: text
+                .split_whitespace()
+                .filter(|group| group.chars().any(char::is_alphanumeric))
+                .count(),
+        }
+    }
+}
+
+impl std::fmt::Display for GroupCount {
+    /// Writes the check in telegram style, e.g. `GR 27`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "GR {}", self.groups)
+    }
+}
+
+/// Puts the group count check of the message on a header line before it.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::add_group_count;
+/// assert_eq!(add_group_count("WKLNU DSAJQ"), "GR 2\nWKLNU DSAJQ");
+/// ```
+pub fn add_group_count(text: &str) -> String {
+    format!("{}\n{}", GroupCount::of(text), text)
+}
+
+/// Removes a group count header line added by [`add_group_count`] and checks it against the
+/// message. Text without such a header is returned unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the message does not have as many groups as the header says.
+///
+/// # Examples
+///
+/// ```rust
+/// use cipha::text::strip_group_count;
+/// assert_eq!(strip_group_count("GR 2\nWKLNU DSAJQ"), Ok("WKLNU DSAJQ"));
+/// assert_eq!(strip_group_count("WKLNU DSAJQ"), Ok("WKLNU DSAJQ"));
+/// assert!(strip_group_count("GR 3\nWKLNU DSAJQ").is_err());
+/// ```
+pub fn strip_group_count(text: &str) -> Result<&str, String> {
+    let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+    let expected = header
+        .trim()
+        .strip_prefix("GR")
+        .filter(|count| count.starts_with(char::is_whitespace))
+        .and_then(|count| count.trim().parse::<usize>().ok());
+    let Some(expected) = expected else {
+        return Ok(text);
+    };
+    let body = body.strip_prefix('\r').unwrap_or(body);
+    let count = GroupCount::of(body);
+    if count.groups == expected {
+        Ok(body)
+    } else {
+        Err(format!(
+            "Group count mismatch: the header says GR {} but the message has {} groups",
+            expected, count.groups
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_case() {
+        assert_eq!("Upper".parse(), Ok(LetterCase::Upper));
+        assert_eq!(" lower ".parse(), Ok(LetterCase::Lower));
+        assert_eq!("preserve".parse(), Ok(LetterCase::Preserve));
+        assert!("title".parse::<LetterCase>().is_err());
+        assert_eq!(LetterCase::default(), LetterCase::Preserve);
+        assert_eq!(LetterCase::Upper.apply("Straße ?"), "STRASSE ?");
+    }
+
+    #[test]
+    fn test_preprocess_cryptogram() {
+        let options = PreprocessOptions::cryptogram();
+        assert_eq!(preprocess("ATTACK AT DAWN", &options), "ATTACKATDAWN");
+        assert_eq!(preprocess("Crème brûlée", &options), "CREMEBRULEE");
+        assert_eq!(preprocess("Room 42", &options), "ROOMFOURTWO");
+
+        let stage = Preprocess::new(options);
+        assert_eq!(stage.encipher("Room 42"), "ROOMFOURTWO");
+        assert_eq!(stage.decipher("Room 42"), "Room 42");
+    }
+
+    #[test]
+    fn test_formatter() {
+        let text = "Attack at dawn, then hold";
+        let grouped = Formatter::new().with_groups(5);
+        assert_eq!(grouped.format(text), "Attac katda wn,th enhol d");
+        assert_eq!(grouped.unformat("Attac katda\nwn,th"), "Attackatdawn,th");
+        // Whole groups per line, and a group wider than the line on its own
+        let wrapped = grouped.with_width(12);
+        assert_eq!(wrapped.format(text), "Attac katda\nwn,th enhol\nd");
+        assert_eq!(
+            Formatter::new()
+                .with_groups(5)
+                .with_width(3)
+                .format("abcdefg"),
+            "abcde\nfg"
+        );
+        let wrapped = Formatter::new().with_width(4);
+        assert_eq!(wrapped.format("abcdefghij\nxyz"), "abcd\nefgh\nij\nxyz");
+        assert_eq!(wrapped.unformat("abcd\r\nefgh\nij"), "abcdefghij");
+        assert_eq!(Formatter::new().format(text), text);
+        assert_eq!(Formatter::new().unformat("a b\nc"), "a b\nc");
+    }
+
+    #[test]
+    fn test_non_alpha_policy() {
+        assert_eq!("Strip".parse(), Ok(NonAlphaPolicy::Strip));
+        assert_eq!("_".parse(), Ok(NonAlphaPolicy::Substitute('_')));
+        assert!("remove".parse::<NonAlphaPolicy>().is_err());
+        assert_eq!(
+            NonAlphaPolicy::Preserve.apply("Día 1"),
+            Ok("Día 1".to_string())
+        );
+        assert_eq!(NonAlphaPolicy::Strip.apply("Día 1"), Ok("Día".to_string()));
+        assert_eq!(
+            NonAlphaPolicy::Error.apply("Día 1"),
+            Err("Non-alphabetic character ' ' at position 3".to_string())
+        );
+        assert_eq!(NonAlphaPolicy::Error.apply("Día"), Ok("Día".to_string()));
+    }
+
+    #[test]
+    fn test_preprocess_ascii() {
+        let options = PreprocessOptions::ascii();
+        assert_eq!(
+            preprocess("ﬁn Æsir Øresund ²", &options),
+            "fin aesir oresund 2"
+        );
+        assert_eq!(preprocess("GROẞE Þing", &options), "grosse thing");
+        let options = PreprocessOptions {
+            expand_letters: true,
+            ..PreprocessOptions::new()
+        };
+        // Without stripping, the letters keep their diacritics and case
+        assert_eq!(preprocess("Ærø Straße", &options), "AEro Strasse");
+        let options = PreprocessOptions {
+            compatibility: true,
+            ..PreprocessOptions::new()
+        };
+        // Accented letters stay composed rather than leaving loose combining marks
+        assert_eq!(preprocess("ﬁancé", &options), "fiancé");
+        assert_eq!(preprocess("ﬁancé", &options).chars().count(), 6);
+    }
+
+    #[test]
+    fn test_normalize_transcription() {
+        let text = "  ſiue [uel] Iouem\n\n<illeg.> IVLIVS ";
+        let report = normalize_transcription(text, &TranscriptionOptions::classical_latin());
+        assert_eq!(report.text, "sive Iovem IVLIVS");
+        assert_eq!(report.brackets, 2);
+        // The long s and the two lowercase u
+        assert_eq!(report.letters_unified, 3);
+        assert_eq!(report.whitespace_removed, 6);
+        assert_eq!(report.letters, 15);
+
+        let report = normalize_transcription(text, &TranscriptionOptions::new());
+        assert_eq!(report.text, text);
+        assert_eq!(
+            report,
+            TranscriptionReport {
+                text: text.to_string(),
+                letters: 23,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_preprocess_individual_steps() {
+        assert_eq!(preprocess("Día 1", &PreprocessOptions::new()), "Día 1");
+
+        let options = PreprocessOptions {
+            strip_accents: true,
+            ..PreprocessOptions::new()
+        };
+        assert_eq!(preprocess("Día 1", &options), "Dia 1");
+
+        let options = PreprocessOptions {
+            spell_digits: true,
+            ..PreprocessOptions::new()
+        };
+        assert_eq!(preprocess("Día 1", &options), "Día one");
+
+        let options = PreprocessOptions {
+            letters_only: true,
+            ..PreprocessOptions::new()
+        };
+        assert_eq!(preprocess("Día 1", &options), "Día");
+    }
+
+    #[test]
+    fn test_spell_numbers() {
+        assert_eq!(spell_numbers("1915", NumberStyle::Digits), "ONENINEONEFIVE");
+        assert_eq!(
+            spell_numbers("1915", NumberStyle::Cardinal),
+            "ONETHOUSANDNINEHUNDREDFIFTEEN"
+        );
+        assert_eq!(spell_numbers("1915", NumberStyle::Year), "NINETEENFIFTEEN");
+        assert_eq!(
+            spell_numbers("1900 1905 2005", NumberStyle::Year),
+            "NINETEENHUNDRED NINETEENOHFIVE TWOTHOUSANDFIVE"
+        );
+        assert_eq!(spell_numbers("0", NumberStyle::Cardinal), "ZERO");
+        assert_eq!(spell_numbers("007", NumberStyle::Cardinal), "ZEROZEROSEVEN");
+        assert_eq!(
+            spell_numbers("1000042", NumberStyle::Cardinal),
+            "ONEMILLIONFORTYTWO"
+        );
+    }
+
+    #[test]
+    fn test_parse_spelled_numbers() {
+        for style in [
+            NumberStyle::Digits,
+            NumberStyle::Cardinal,
+            NumberStyle::Year,
+        ] {
+            for date in ["12/25/1915", "1900", "1905", "2005", "42", "7"] {
+                assert_eq!(
+                    parse_spelled_numbers(&spell_numbers(date, style)),
+                    date,
+                    "{:?}",
+                    style
+                );
+            }
+        }
+        assert_eq!(parse_spelled_numbers("ROOMFOURTWO"), "ROOM42");
+        assert_eq!(
+            parse_spelled_numbers("one thousand and one nights"),
+            "1000 and 1 nights"
+        );
+        assert_eq!(parse_spelled_numbers("OFTEN ALONE"), "OFTEN ALONE");
+    }
+
+    #[test]
+    fn test_roman_numerals() {
+        for number in 1..=3999 {
+            let numeral = to_roman(number).unwrap();
+            assert_eq!(parse_roman(&numeral, RomanParsing::Strict).unwrap(), number);
+        }
+        assert!(to_roman(0).is_err());
+        assert!(to_roman(4000).is_err());
+        assert_eq!(to_roman(3888).unwrap(), "MMMDCCCLXXXVIII");
+    }
+
+    #[test]
+    fn test_parse_roman_modes() {
+        for numeral in ["IIII", "IC", "xiv", "VX", "", "ABC"] {
+            assert!(
+                parse_roman(numeral, RomanParsing::Strict).is_err(),
+                "{}",
+                numeral
+            );
+        }
+        assert_eq!(parse_roman("IIII", RomanParsing::Permissive).unwrap(), 4);
+        assert_eq!(parse_roman("IC", RomanParsing::Permissive).unwrap(), 99);
+        assert_eq!(parse_roman("viij", RomanParsing::Permissive).unwrap(), 8);
+        assert_eq!(
+            parse_roman("MMMMD", RomanParsing::Permissive).unwrap(),
+            4500
+        );
+        assert!(parse_roman("ABC", RomanParsing::Permissive).is_err());
+    }
+
+    #[test]
+    fn test_roman_text_transforms() {
+        assert_eq!(romanize_numbers("25/12/1915 007"), "XXV/XII/MCMXV 007");
+        assert_eq!(
+            parse_roman_numerals("XXV/XII/MCMXV 007", RomanParsing::Strict),
+            "25/12/1915 007"
+        );
+        assert_eq!(
+            parse_roman_numerals("Louis xiv met Henry IIII", RomanParsing::Permissive),
+            "Louis 14 met Henry 4"
+        );
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(detect_encoding("naïve".as_bytes()), TextEncoding::Utf8);
+        assert_eq!(detect_encoding(b"na\xefve"), TextEncoding::Latin1);
+        assert_eq!(detect_encoding(b"\x80 5"), TextEncoding::Windows1252);
+        assert_eq!(decode_text(b"\xef\xbb\xbfHi", None).unwrap(), "Hi");
+        assert_eq!(decode_text(b"na\xefve", None).unwrap(), "naïve");
+        assert_eq!(decode_text(b"\x80 5 \x85", None).unwrap(), "€ 5 …");
+        assert_eq!(decode_text(b"\xfe\xff\0H\0i", None).unwrap(), "Hi");
+        assert_eq!(
+            decode_text(b"H\0i\0", Some(TextEncoding::Utf16Le)).unwrap(),
+            "Hi"
+        );
+        assert!(decode_text(b"\xff\xfeH", None).is_err());
+        assert!(decode_text(b"\xe9", Some(TextEncoding::Utf8)).is_err());
+        assert_eq!("CP-1252".parse(), Ok(TextEncoding::Windows1252));
+        assert_eq!("utf_16".parse(), Ok(TextEncoding::Utf16Le));
+        assert!("ebcdic".parse::<TextEncoding>().is_err());
+    }
+
+    #[test]
+    fn test_encode_text_and_line_endings() {
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            let bytes = encode_text("“Café”\r\n", encoding, true).unwrap();
+            assert_eq!(detect_encoding(&bytes), encoding);
+            assert_eq!(decode_text(&bytes, None).unwrap(), "“Café”\r\n");
+        }
+        let bytes = encode_text("“Café”", TextEncoding::Windows1252, false).unwrap();
+        assert_eq!(decode_text(&bytes, None).unwrap(), "“Café”");
+        assert_eq!(
+            encode_text("é", TextEncoding::Latin1, false).unwrap(),
+            b"\xe9"
+        );
+        assert!(encode_text("€", TextEncoding::Latin1, false).is_err());
+        assert!(encode_text("\u{80}", TextEncoding::Windows1252, false).is_err());
+        assert!(encode_text("a", TextEncoding::Latin1, true).is_err());
+
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), Some(LineEnding::CrLf));
+        assert_eq!(LineEnding::detect("a\nb\nc\r\n"), Some(LineEnding::Lf));
+        assert_eq!(LineEnding::detect("abc"), None);
+        assert_eq!("CRLF".parse(), Ok(LineEnding::CrLf));
+        assert_eq!(convert_line_endings("a\r\n", LineEnding::CrLf), "a\r\n");
+        assert_eq!(convert_line_endings("a\rb", LineEnding::Lf), "a\rb");
+    }
+
+    #[test]
+    fn test_group_count() {
+        let message = "VCTQM XRNIQ\r\nHYLFM 42";
+        assert_eq!(
+            GroupCount::of(message),
+            GroupCount {
+                letters: 17,
+                groups: 4
+            }
+        );
+        assert_eq!(
+            GroupCount::of("  - . "),
+            GroupCount {
+                letters: 0,
+                groups: 0
+            }
+        );
+        let sent = add_group_count(message);
+        assert!(sent.starts_with("GR 4\n"));
+        assert_eq!(strip_group_count(&sent), Ok(message));
+        assert_eq!(strip_group_count("GR 1\r\nHELLO"), Ok("HELLO"));
+        assert_eq!(strip_group_count("GRAND PLAN"), Ok("GRAND PLAN"));
+        assert_eq!(strip_group_count("GR X\nHELLO"), Ok("GR X\nHELLO"));
+        assert_eq!(
+            strip_group_count("GR 5\nVCTQM XRNIQ"),
+            Err(
+                "Group count mismatch: the header says GR 5 but the message has 2 groups"
+                    .to_string()
+            )
+        );
+    }
+}